@@ -22,6 +22,7 @@ use deepbook_sandbox_backend::sandbox::state_loader::{DeepBookConfig, PoolId, St
 use deepbook_sandbox_backend::sandbox::swap_executor::{
     CommandInfo, EventInfo, PtbExecution, SessionManager, TradingSession,
 };
+use deepbook_sandbox_backend::units;
 
 const DIRECT_SWAP_SUI_AMOUNT: u64 = 10_000_000_000; // 10 SUI
 const TWO_HOP_SWAP_SUI_AMOUNT: u64 = 5_000_000_000; // 5 SUI
@@ -79,19 +80,19 @@ async fn main() -> Result<()> {
     println!("Final balances:");
     println!(
         "  SUI:  {:.6}",
-        final_session.balances.sui as f64 / 1_000_000_000.0
+        units::to_human(final_session.balances.sui, units::SUI_DECIMALS)
     );
     println!(
         "  USDC: {:.6}",
-        final_session.balances.usdc as f64 / 1_000_000.0
+        units::to_human(final_session.balances.usdc, units::USDC_DECIMALS)
     );
     println!(
         "  DEEP: {:.6}",
-        final_session.balances.deep as f64 / 1_000_000.0
+        units::to_human(final_session.balances.deep, units::DEEP_DECIMALS)
     );
     println!(
         "  WAL:  {:.6}",
-        final_session.balances.wal as f64 / 1_000_000_000.0
+        units::to_human(final_session.balances.wal, units::WAL_DECIMALS)
     );
     println!("Swap history entries: {}", final_session.swap_history.len());
 
@@ -220,15 +221,15 @@ async fn run_direct_swap(
     let deep_budget = { session.read().await.balances.deep };
 
     let swap_vm = router_handle
-        .execute_single_hop_swap(pool_id, input_amount, deep_budget, true)
+        .execute_single_hop_swap(pool_id, input_amount, deep_budget, true, 0, None)
         .await
         .map_err(|e| anyhow!("MoveVM single-hop swap failed: {}", e))?;
     if swap_vm.output_amount == 0 {
         return Err(anyhow!("Direct MoveVM swap returned zero output"));
     }
     let consumed_input = input_amount.saturating_sub(swap_vm.input_refund);
-    let input_human = format_human(consumed_input, get_decimals(from));
-    let output_human = format_human(swap_vm.output_amount, get_decimals(to));
+    let input_human = units::to_human(consumed_input, get_decimals(from));
+    let output_human = units::to_human(swap_vm.output_amount, get_decimals(to));
     let effective_price = if input_human > 0.0 {
         output_human / input_human
     } else {
@@ -238,10 +239,10 @@ async fn run_direct_swap(
     println!();
     println!(
         "Direct swap VM output: {:.6} {} (requested {:.6}) -> {:.6} {}",
-        format_human(consumed_input, get_decimals(from)),
+        units::to_human(consumed_input, get_decimals(from)),
         from,
-        format_human(input_amount, get_decimals(from)),
-        format_human(swap_vm.output_amount, get_decimals(to)),
+        units::to_human(input_amount, get_decimals(from)),
+        units::to_human(swap_vm.output_amount, get_decimals(to)),
         to
     );
 
@@ -302,6 +303,7 @@ async fn run_direct_swap(
         created_objects: vec![],
         mutated_objects: vec![pool_id.display_name().to_string()],
         deleted_objects: vec![],
+        command_effects: vec![],
     };
 
     let mut session = session.write().await;
@@ -328,9 +330,9 @@ async fn run_direct_swap(
 
     println!(
         "Direct swap executed: {:.6} {} -> {:.6} {}",
-        format_human(consumed_input, get_decimals(from)),
+        units::to_human(consumed_input, get_decimals(from)),
         from,
-        format_human(swap.output_amount, get_decimals(to)),
+        units::to_human(swap.output_amount, get_decimals(to)),
         to
     );
 
@@ -350,7 +352,7 @@ async fn run_two_hop_swap(
     let deep_budget = { session.read().await.balances.deep };
 
     let swap_vm = router_handle
-        .execute_two_hop_swap(first_pool, second_pool, amount, deep_budget)
+        .execute_two_hop_swap(first_pool, second_pool, amount, deep_budget, 0, None)
         .await
         .map_err(|e| anyhow!("MoveVM two-hop swap failed: {}", e))?;
     let intermediate_usdc = swap_vm.intermediate_amount;
@@ -363,11 +365,11 @@ async fn run_two_hop_swap(
 
     println!(
         "Two-hop swap VM output: {:.6} {} (requested {:.6}) -> {:.6} USDC -> {:.6} {}",
-        format_human(consumed_input, get_decimals(from)),
+        units::to_human(consumed_input, get_decimals(from)),
         from,
-        format_human(amount, get_decimals(from)),
-        format_human(intermediate_usdc, get_decimals("USDC")),
-        format_human(output_amount, get_decimals(to)),
+        units::to_human(amount, get_decimals(from)),
+        units::to_human(intermediate_usdc, get_decimals("USDC")),
+        units::to_human(output_amount, get_decimals(to)),
         to
     );
 
@@ -456,11 +458,12 @@ async fn run_two_hop_swap(
             second_pool.display_name().to_string(),
         ],
         deleted_objects: vec![],
+        command_effects: vec![],
     };
 
     let mut session = session.write().await;
-    let input_human = format_human(consumed_input, get_decimals(from));
-    let output_human = format_human(output_amount, get_decimals(to));
+    let input_human = units::to_human(consumed_input, get_decimals(from));
+    let output_human = units::to_human(output_amount, get_decimals(to));
     let effective_price = if input_human > 0.0 {
         output_human / input_human
     } else {
@@ -490,23 +493,15 @@ async fn run_two_hop_swap(
 
     println!(
         "Two-hop swap executed: {:.6} {} -> {:.6} {}",
-        format_human(consumed_input, get_decimals(from)),
+        units::to_human(consumed_input, get_decimals(from)),
         from,
-        format_human(swap.output_amount, get_decimals(to)),
+        units::to_human(swap.output_amount, get_decimals(to)),
         to
     );
 
     Ok(())
 }
 
-fn get_decimals(token: &str) -> i32 {
-    match token {
-        "SUI" | "WAL" => 9,
-        "USDC" | "DEEP" => 6,
-        _ => 9,
-    }
-}
-
-fn format_human(amount: u64, decimals: i32) -> f64 {
-    amount as f64 / 10f64.powi(decimals)
+fn get_decimals(token: &str) -> u8 {
+    units::token_decimals(token).unwrap_or(units::DEFAULT_DECIMALS)
 }
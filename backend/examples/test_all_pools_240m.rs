@@ -7,6 +7,7 @@ use std::path::Path;
 
 use deepbook_sandbox_backend::sandbox::orderbook_builder::OrderbookBuilder;
 use deepbook_sandbox_backend::sandbox::state_loader::{DeepBookConfig, PoolId, StateLoader};
+use deepbook_sandbox_backend::units;
 
 fn test_pool(pool_id: PoolId, data_file: &str, pool_wrapper: &str) -> Result<()> {
     let config = DeepBookConfig::for_pool(pool_id);
@@ -49,14 +50,12 @@ fn test_pool(pool_id: PoolId, data_file: &str, pool_wrapper: &str) -> Result<()>
         Ok(orderbook) => {
             println!("\n  ✓ Orderbook built successfully!");
 
-            // Calculate price divisor: USDC (10^6) * normalization (10^(9 - base_decimals))
-            let price_divisor = 1_000_000.0 * 10f64.powi(9 - orderbook.base_decimals as i32);
-            let qty_divisor = 10f64.powi(orderbook.base_decimals as i32);
 
             println!("\n  Bids (top 5):");
             for (i, level) in orderbook.bids.iter().take(5).enumerate() {
-                let price = level.price as f64 / price_divisor;
-                let qty = level.total_quantity as f64 / qty_divisor;
+                let price =
+                    units::scale_price(level.price, orderbook.base_decimals, orderbook.quote_decimals);
+                let qty = units::to_human(level.total_quantity, orderbook.base_decimals);
                 println!(
                     "    {}. ${:.6} - {:.4} ({} orders)",
                     i + 1,
@@ -68,8 +67,9 @@ fn test_pool(pool_id: PoolId, data_file: &str, pool_wrapper: &str) -> Result<()>
 
             println!("\n  Asks (top 5):");
             for (i, level) in orderbook.asks.iter().take(5).enumerate() {
-                let price = level.price as f64 / price_divisor;
-                let qty = level.total_quantity as f64 / qty_divisor;
+                let price =
+                    units::scale_price(level.price, orderbook.base_decimals, orderbook.quote_decimals);
+                let qty = units::to_human(level.total_quantity, orderbook.base_decimals);
                 println!(
                     "    {}. ${:.6} - {:.4} ({} orders)",
                     i + 1,
@@ -8,6 +8,7 @@ use std::path::Path;
 // Re-use the backend's modules
 use deepbook_sandbox_backend::sandbox::orderbook_builder::OrderbookBuilder;
 use deepbook_sandbox_backend::sandbox::state_loader::{DeepBookConfig, PoolId, StateLoader};
+use deepbook_sandbox_backend::units;
 
 fn main() -> Result<()> {
     // Initialize logging
@@ -62,8 +63,8 @@ fn main() -> Result<()> {
 
             println!("Bids (top 10):");
             for (i, level) in orderbook.bids.iter().take(10).enumerate() {
-                let price = level.price as f64 / 1_000_000.0;
-                let qty = level.total_quantity as f64 / 1_000_000_000.0;
+                let price = units::scale_price(level.price, units::SUI_DECIMALS, units::USDC_DECIMALS);
+                let qty = units::to_human(level.total_quantity, units::SUI_DECIMALS);
                 println!(
                     "  {}. ${:.6} - {:.4} SUI ({} orders)",
                     i + 1,
@@ -75,8 +76,8 @@ fn main() -> Result<()> {
 
             println!("\nAsks (top 10):");
             for (i, level) in orderbook.asks.iter().take(10).enumerate() {
-                let price = level.price as f64 / 1_000_000.0;
-                let qty = level.total_quantity as f64 / 1_000_000_000.0;
+                let price = units::scale_price(level.price, units::SUI_DECIMALS, units::USDC_DECIMALS);
+                let qty = units::to_human(level.total_quantity, units::SUI_DECIMALS);
                 println!(
                     "  {}. ${:.6} - {:.4} SUI ({} orders)",
                     i + 1,
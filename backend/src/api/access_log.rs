@@ -0,0 +1,144 @@
+//! Per-request access log middleware
+//!
+//! Logs one INFO line per HTTP request (target `access_log`) with method,
+//! path, status, latency and request id. Swap and quote handlers add the
+//! pools and route type they resolved via [`record_route`]. The line format
+//! is `ACCESS_LOG_FORMAT=text` (default) or `json`.
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use serde::Serialize;
+use std::cell::RefCell;
+
+use crate::sandbox::state_loader::PoolId;
+
+/// Header carrying the request id, accepted from clients and echoed back
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+/// Longest client-supplied request id that is honored
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessLogFormat {
+    Text,
+    Json,
+}
+
+impl AccessLogFormat {
+    pub fn from_env() -> Self {
+        match std::env::var("ACCESS_LOG_FORMAT") {
+            Ok(raw) => match raw.trim().to_ascii_lowercase().as_str() {
+                "text" | "" => AccessLogFormat::Text,
+                "json" => AccessLogFormat::Json,
+                _ => {
+                    tracing::warn!("Invalid ACCESS_LOG_FORMAT={:?}, defaulting to text", raw);
+                    AccessLogFormat::Text
+                }
+            },
+            Err(_) => AccessLogFormat::Text,
+        }
+    }
+}
+
+/// Business context a handler attaches to its access log line
+#[derive(Debug, Default)]
+struct RouteContext {
+    pools: Vec<&'static str>,
+    route_type: Option<&'static str>,
+}
+
+tokio::task_local! {
+    static ROUTE_CONTEXT: RefCell<RouteContext>;
+}
+
+/// Attach the pools and route type a swap or quote resolved to the current
+/// request's access log line. A no-op outside the middleware.
+pub fn record_route(pools: &[PoolId], route_type: &'static str) {
+    let _ = ROUTE_CONTEXT.try_with(|context| {
+        let mut context = context.borrow_mut();
+        context.pools = pools.iter().map(PoolId::as_str).collect();
+        context.route_type = Some(route_type);
+    });
+}
+
+#[derive(Debug, Serialize)]
+struct AccessLogLine<'a> {
+    request_id: &'a str,
+    method: &'a str,
+    path: &'a str,
+    status: u16,
+    latency_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pool: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    route_type: Option<&'static str>,
+}
+
+/// Client-supplied request id if it is usable, otherwise a fresh UUID
+fn request_id(req: &Request) -> String {
+    req.headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN)
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string())
+}
+
+/// Log the request once the response is ready and echo `x-request-id`.
+pub async fn access_log(
+    State(format): State<AccessLogFormat>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let start = std::time::Instant::now();
+    let request_id = request_id(&req);
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+
+    let context = RefCell::new(RouteContext::default());
+    let (mut response, context) = ROUTE_CONTEXT
+        .scope(context, async {
+            let response = next.run(req).await;
+            let context = ROUTE_CONTEXT.with(|context| context.take());
+            (response, context)
+        })
+        .await;
+
+    let line = AccessLogLine {
+        request_id: &request_id,
+        method: method.as_str(),
+        path: &path,
+        status: response.status().as_u16(),
+        latency_ms: start.elapsed().as_secs_f64() * 1000.0,
+        pool: (!context.pools.is_empty()).then(|| context.pools.join(",")),
+        route_type: context.route_type,
+    };
+    match format {
+        AccessLogFormat::Json => match serde_json::to_string(&line) {
+            Ok(json) => tracing::info!(target: "access_log", "{}", json),
+            Err(e) => tracing::warn!("Failed to serialize access log line: {}", e),
+        },
+        AccessLogFormat::Text => tracing::info!(
+            target: "access_log",
+            "{} {} {} {:.1}ms request_id={} pool={} route_type={}",
+            line.method,
+            line.path,
+            line.status,
+            line.latency_ms,
+            line.request_id,
+            line.pool.as_deref().unwrap_or("-"),
+            line.route_type.unwrap_or("-")
+        ),
+    }
+
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response
+            .headers_mut()
+            .insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+    }
+    response
+}
@@ -7,18 +7,43 @@ use axum::{
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use crate::api::AppState;
+use crate::api::{router_error, AppState};
+use crate::sandbox::router::{
+    debug_coin_type, RouterHandle, RouterTimeout, VmCreatedObject, VmFaucetResult,
+    UNSUPPORTED_FAUCET_COIN_TYPE,
+};
 use crate::types::{ApiError, ApiResult};
+use crate::units;
 
 const SUI_TYPE: &str = "0x2::sui::SUI";
 const USDC_TYPE: &str =
     "0xdba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7::usdc::USDC";
 const WAL_TYPE: &str =
     "0x356a26eb9e012a68958082340d4c4116e7f55615cf27affcff209cf0ae544f59::wal::WAL";
-const DEEP_TYPE: &str =
+pub(super) const DEEP_TYPE: &str =
     "0xdeeb7a4662eec9f2f3def03fb937a663dddaa2e215b8078a284d026b7946c270::deep::DEEP";
-const DEBUG_TYPE: &str =
-    "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa::debug_token::DEBUG_TOKEN";
+
+/// Reserve coin type backing a normalized (uppercase) token symbol;
+/// `debug_symbols` are the debug tokens this instance knows
+/// (`DebugPoolState::token_symbols`)
+pub(super) fn coin_type_for_symbol(token: &str, debug_symbols: &[String]) -> Option<String> {
+    match token {
+        "SUI" => Some(SUI_TYPE.to_string()),
+        "USDC" => Some(USDC_TYPE.to_string()),
+        "WAL" => Some(WAL_TYPE.to_string()),
+        "DEEP" => Some(DEEP_TYPE.to_string()),
+        _ if debug_symbols.iter().any(|symbol| symbol == token) => Some(debug_coin_type(token)),
+        _ => None,
+    }
+}
+
+/// Token symbols `POST /api/faucet` accepts, in display order
+fn faucet_symbols(debug_symbols: &[String]) -> Vec<&str> {
+    ["SUI", "USDC", "WAL", "DEEP"]
+        .into_iter()
+        .chain(debug_symbols.iter().map(String::as_str))
+        .collect()
+}
 
 #[derive(Debug, Serialize)]
 pub struct BalanceResponse {
@@ -49,6 +74,8 @@ pub struct FaucetRequest {
     pub session_id: String,
     pub token: String, // "sui" | "usdc" | "wal" | "deep"
     pub amount: String,
+    /// Optional VM sender override (faucet coin is transferred to this address)
+    pub sender: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -57,6 +84,75 @@ pub struct FaucetResponse {
     pub new_balance: String,
     pub new_balance_human: f64,
     pub token: String,
+    /// Objects the faucet PTB created, with their type and coin value
+    pub created_objects: Vec<VmCreatedObject>,
+}
+
+/// A VM faucet mint charged to a session's faucet allowance
+pub(super) struct FaucetMint<'a> {
+    pub session_id: &'a str,
+    /// Normalized (uppercase) token symbol the allowance is tracked under
+    pub token: &'a str,
+    pub coin_type: &'a str,
+    pub amount: u64,
+    pub decimals: u8,
+    pub sender: Option<String>,
+}
+
+/// Reserve `mint.amount` of the session's faucet allowance, then mint it.
+///
+/// The reservation is released when the mint definitely did not happen: the
+/// router rejected it or minted a different amount. After a router timeout
+/// the mint may still complete in the VM, so the reservation stands.
+/// Crediting the session is left to the caller.
+pub(super) async fn mint_within_allowance<F>(
+    state: &AppState,
+    router: &RouterHandle,
+    mint: FaucetMint<'_>,
+    map_err: F,
+) -> ApiResult<VmFaucetResult>
+where
+    F: FnOnce(anyhow::Error) -> ApiError,
+{
+    let FaucetMint {
+        session_id,
+        token,
+        coin_type,
+        amount,
+        decimals,
+        sender,
+    } = mint;
+    state
+        .session_manager
+        .reserve_faucet_allowance(session_id, token, amount, decimals)
+        .await
+        .map_err(|e| ApiError::BadRequest(format!("Faucet limit exceeded: {}", e)))?;
+
+    let failed = match router
+        .vm_faucet(coin_type.to_string(), amount, sender)
+        .await
+    {
+        Ok(minted) if minted.amount == amount => return Ok(minted),
+        Ok(minted) => ApiError::Internal(format!(
+            "VM faucet amount mismatch: requested {}, minted {}",
+            amount, minted.amount
+        )),
+        Err(e) if e.is::<RouterTimeout>() => {
+            tracing::warn!(
+                "Faucet mint of {} {} for session {} timed out; keeping its allowance reserved",
+                amount,
+                token,
+                session_id
+            );
+            return Err(router_error(map_err)(e));
+        }
+        Err(e) => map_err(e),
+    };
+    state
+        .session_manager
+        .release_faucet_allowance(session_id, token, amount)
+        .await;
+    Err(failed)
 }
 
 /// GET /api/balance/:session_id - Get token balances for a session
@@ -77,13 +173,13 @@ pub async fn get_balance(
         session_id,
         balances: TokenBalances {
             sui: b.sui.to_string(),
-            sui_human: b.sui as f64 / 1_000_000_000.0,
+            sui_human: units::to_human(b.sui, units::SUI_DECIMALS),
             usdc: b.usdc.to_string(),
-            usdc_human: b.usdc as f64 / 1_000_000.0,
+            usdc_human: units::to_human(b.usdc, units::USDC_DECIMALS),
             deep: b.deep.to_string(),
-            deep_human: b.deep as f64 / 1_000_000.0,
+            deep_human: units::to_human(b.deep, units::DEEP_DECIMALS),
             wal: b.wal.to_string(),
-            wal_human: b.wal as f64 / 1_000_000_000.0,
+            wal_human: units::to_human(b.wal, units::WAL_DECIMALS),
             custom: b
                 .custom
                 .iter()
@@ -104,66 +200,71 @@ pub async fn faucet(
         .await
         .ok_or_else(|| ApiError::NotFound(format!("Session not found: {}", req.session_id)))?;
 
-    let debug_symbol = state.debug_pool.read().await.token_symbol.to_uppercase();
+    let (debug_symbol, debug_symbols) = {
+        let debug = state.debug_pool.read().await;
+        (debug.token_symbol.to_uppercase(), debug.token_symbols())
+    };
     let token_upper = req.token.to_uppercase();
     let token = if token_upper == "DEBUG" || token_upper == "DBG" || token_upper == debug_symbol {
         debug_symbol.clone()
     } else {
         token_upper
     };
-    if !["SUI", "USDC", "WAL", "DEEP"].contains(&token.as_str()) && token != debug_symbol {
-        return Err(ApiError::BadRequest(format!("Unknown token: {}", token)));
-    }
+    let coin_type = coin_type_for_symbol(&token, &debug_symbols).ok_or_else(|| {
+        ApiError::BadRequest(format!(
+            "Unsupported faucet token: {}. Supported tokens: {} (DEBUG and DBG alias {})",
+            req.token,
+            faucet_symbols(&debug_symbols).join(", "),
+            debug_symbol
+        ))
+    })?;
 
     let amount: u64 = req
         .amount
         .parse()
         .map_err(|_| ApiError::BadRequest("Invalid amount".into()))?;
-
-    let coin_type = match token.as_str() {
-        "SUI" => SUI_TYPE,
-        "USDC" => USDC_TYPE,
-        "WAL" => WAL_TYPE,
-        "DEEP" => DEEP_TYPE,
-        _ if token == debug_symbol => DEBUG_TYPE,
-        _ => return Err(ApiError::BadRequest(format!("Unknown token: {}", token))),
+    let decimals = if debug_symbols.contains(&token) {
+        units::DEBUG_TOKEN_DECIMALS
+    } else {
+        units::token_decimals(&token).unwrap_or(units::DEFAULT_DECIMALS)
     };
 
     let router = state
         .router
         .as_ref()
-        .ok_or_else(|| ApiError::Internal("MoveVM router is not initialized".into()))?;
-    let vm_result = router
-        .vm_faucet(coin_type.to_string(), amount)
-        .await
-        .map_err(|e| {
+        .ok_or_else(|| ApiError::ServiceUnavailable("MoveVM router is not initialized".into()))?;
+    let mint = FaucetMint {
+        session_id: &req.session_id,
+        token: &token,
+        coin_type: &coin_type,
+        amount,
+        decimals,
+        sender: req.sender.clone(),
+    };
+    let vm_result = mint_within_allowance(&state, router, mint, |e| {
+        let message = e.to_string();
+        if message.starts_with(UNSUPPORTED_FAUCET_COIN_TYPE) {
+            ApiError::BadRequest(message)
+        } else {
             ApiError::Internal(format!(
                 "VM faucet execution failed for {} (type {}): {}",
-                token, coin_type, e
+                token, coin_type, message
             ))
-        })?;
-    if vm_result.amount != amount {
-        return Err(ApiError::Internal(format!(
-            "VM faucet amount mismatch: requested {}, minted {}",
-            amount, vm_result.amount
-        )));
-    }
+        }
+    })
+    .await?;
 
     let mut session = session_arc.write().await;
     session.balances.add(&token, vm_result.amount);
+    session.record_faucet_draw(&token, vm_result.amount);
 
     let new_balance = session.balances.get(&token);
-    let decimals = match token.as_str() {
-        "SUI" | "WAL" => 9,
-        "USDC" | "DEEP" => 6,
-        _ if token == debug_symbol => 9,
-        _ => 9,
-    };
 
     Ok(Json(FaucetResponse {
         success: true,
         new_balance: new_balance.to_string(),
-        new_balance_human: new_balance as f64 / 10f64.powi(decimals),
+        new_balance_human: units::to_human(new_balance, decimals),
         token,
+        created_objects: vm_result.created_objects,
     }))
 }
@@ -0,0 +1,287 @@
+//! Per-pool circuit breaker for repeated MoveVM failures
+//!
+//! After `failure_threshold` consecutive VM failures within `window`, a pool's
+//! circuit opens and requests fast-fail with 503 until `cooldown` elapses.
+//! The circuit then half-opens and lets a single probe request through:
+//! success closes it, failure re-opens it for another cooldown.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::sandbox::state_loader::PoolId;
+use crate::types::ApiError;
+
+const DEFAULT_FAILURE_THRESHOLD: usize = 5;
+const DEFAULT_WINDOW_SECS: u64 = 60;
+const DEFAULT_COOLDOWN_SECS: u64 = 30;
+
+#[derive(Debug, Clone)]
+enum CircuitState {
+    Closed { failures: Vec<Instant> },
+    Open { until: Instant },
+    HalfOpen { probe_in_flight: bool },
+}
+
+impl Default for CircuitState {
+    fn default() -> Self {
+        CircuitState::Closed {
+            failures: Vec::new(),
+        }
+    }
+}
+
+/// Circuit breaker state for every pool.
+#[derive(Debug)]
+pub struct PoolCircuitBreakers {
+    failure_threshold: usize,
+    window: Duration,
+    cooldown: Duration,
+    pools: HashMap<PoolId, CircuitState>,
+}
+
+impl Default for PoolCircuitBreakers {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_FAILURE_THRESHOLD,
+            Duration::from_secs(DEFAULT_WINDOW_SECS),
+            Duration::from_secs(DEFAULT_COOLDOWN_SECS),
+        )
+    }
+}
+
+impl PoolCircuitBreakers {
+    pub fn new(failure_threshold: usize, window: Duration, cooldown: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            window,
+            cooldown,
+            pools: HashMap::new(),
+        }
+    }
+
+    /// Build from `POOL_CIRCUIT_FAILURE_THRESHOLD`, `POOL_CIRCUIT_WINDOW_SECS`
+    /// and `POOL_CIRCUIT_COOLDOWN_SECS`, falling back to defaults.
+    pub fn from_env() -> Self {
+        fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(default)
+        }
+        Self::new(
+            env_or("POOL_CIRCUIT_FAILURE_THRESHOLD", DEFAULT_FAILURE_THRESHOLD),
+            Duration::from_secs(env_or("POOL_CIRCUIT_WINDOW_SECS", DEFAULT_WINDOW_SECS)),
+            Duration::from_secs(env_or("POOL_CIRCUIT_COOLDOWN_SECS", DEFAULT_COOLDOWN_SECS)),
+        )
+    }
+
+    /// Check whether a request may proceed for `pool_id`.
+    ///
+    /// Returns a 503 error while the circuit is open, or while a half-open
+    /// probe is already in flight.
+    pub fn check(&mut self, pool_id: PoolId) -> Result<(), ApiError> {
+        let now = Instant::now();
+        let state = self.pools.entry(pool_id).or_default();
+        match state {
+            CircuitState::Closed { .. } => Ok(()),
+            CircuitState::Open { until } if now < *until => {
+                let retry_in = until.saturating_duration_since(now).as_secs().max(1);
+                Err(ApiError::ServiceUnavailable(format!(
+                    "pool circuit open for {} (retry in {}s)",
+                    pool_id.display_name(),
+                    retry_in
+                )))
+            }
+            CircuitState::Open { .. } => {
+                tracing::info!(
+                    "Circuit breaker half-open for {}: allowing probe request",
+                    pool_id.display_name()
+                );
+                *state = CircuitState::HalfOpen {
+                    probe_in_flight: true,
+                };
+                Ok(())
+            }
+            CircuitState::HalfOpen { probe_in_flight } => {
+                if *probe_in_flight {
+                    Err(ApiError::ServiceUnavailable(format!(
+                        "pool circuit open for {} (recovery probe in progress)",
+                        pool_id.display_name()
+                    )))
+                } else {
+                    *probe_in_flight = true;
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    /// Record a successful VM operation; closes the circuit and resets the counter.
+    pub fn record_success(&mut self, pool_id: PoolId) {
+        let state = self.pools.entry(pool_id).or_default();
+        if !matches!(state, CircuitState::Closed { .. }) {
+            tracing::info!("Circuit breaker closed for {}", pool_id.display_name());
+        }
+        *state = CircuitState::default();
+    }
+
+    /// Record an outcome that says nothing about VM health (e.g. a rejected
+    /// request); frees a half-open probe slot without changing state.
+    pub fn record_neutral(&mut self, pool_id: PoolId) {
+        if let Some(CircuitState::HalfOpen { probe_in_flight }) = self.pools.get_mut(&pool_id) {
+            *probe_in_flight = false;
+        }
+    }
+
+    /// Record a failed VM operation; may open the circuit.
+    pub fn record_failure(&mut self, pool_id: PoolId) {
+        let now = Instant::now();
+        let window = self.window;
+        let threshold = self.failure_threshold;
+        let cooldown = self.cooldown;
+        let state = self.pools.entry(pool_id).or_default();
+        let open = match state {
+            CircuitState::Closed { failures } => {
+                failures.retain(|t| now.duration_since(*t) <= window);
+                failures.push(now);
+                failures.len() >= threshold
+            }
+            // A failed probe (or a failure racing the open state) re-opens.
+            CircuitState::HalfOpen { .. } | CircuitState::Open { .. } => true,
+        };
+        if open {
+            tracing::warn!(
+                "Circuit breaker opened for {} for {}s after repeated VM failures",
+                pool_id.display_name(),
+                cooldown.as_secs()
+            );
+            *state = CircuitState::Open {
+                until: now + cooldown,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POOL: PoolId = PoolId::SuiUsdc;
+
+    /// Breakers that open after 3 failures and half-open on the next check
+    fn breakers(window: Duration) -> PoolCircuitBreakers {
+        PoolCircuitBreakers::new(3, window, Duration::ZERO)
+    }
+
+    fn open_circuit(breakers: &mut PoolCircuitBreakers) {
+        for _ in 0..3 {
+            breakers.record_failure(POOL);
+        }
+    }
+
+    #[test]
+    fn test_opens_after_threshold_failures_within_window() {
+        let mut breakers =
+            PoolCircuitBreakers::new(3, Duration::from_secs(60), Duration::from_secs(60));
+        breakers.record_failure(POOL);
+        breakers.record_failure(POOL);
+        assert!(breakers.check(POOL).is_ok());
+
+        breakers.record_failure(POOL);
+        let err = breakers.check(POOL).unwrap_err();
+        assert!(
+            matches!(err, ApiError::ServiceUnavailable(ref msg) if msg.contains("pool circuit open"))
+        );
+        assert!(
+            breakers.check(PoolId::WalUsdc).is_ok(),
+            "other pools stay closed"
+        );
+    }
+
+    #[test]
+    fn test_failures_outside_window_do_not_open() {
+        let mut breakers = breakers(Duration::from_millis(20));
+        breakers.record_failure(POOL);
+        breakers.record_failure(POOL);
+        std::thread::sleep(Duration::from_millis(40));
+
+        breakers.record_failure(POOL);
+        assert!(breakers.check(POOL).is_ok());
+        assert!(
+            matches!(breakers.pools[&POOL], CircuitState::Closed { ref failures } if failures.len() == 1)
+        );
+    }
+
+    #[test]
+    fn test_success_resets_failure_count() {
+        let mut breakers = breakers(Duration::from_secs(60));
+        breakers.record_failure(POOL);
+        breakers.record_failure(POOL);
+        breakers.record_success(POOL);
+        breakers.record_failure(POOL);
+        assert!(breakers.check(POOL).is_ok());
+    }
+
+    #[test]
+    fn test_half_open_admits_exactly_one_probe() {
+        let mut breakers = breakers(Duration::from_secs(60));
+        open_circuit(&mut breakers);
+
+        assert!(
+            breakers.check(POOL).is_ok(),
+            "first check after cooldown is the probe"
+        );
+        let err = breakers.check(POOL).unwrap_err();
+        assert!(
+            matches!(err, ApiError::ServiceUnavailable(ref msg) if msg.contains("probe in progress"))
+        );
+    }
+
+    #[test]
+    fn test_failed_probe_reopens_circuit() {
+        let mut breakers =
+            PoolCircuitBreakers::new(3, Duration::from_secs(60), Duration::from_millis(20));
+        open_circuit(&mut breakers);
+        std::thread::sleep(Duration::from_millis(40));
+        assert!(breakers.check(POOL).is_ok());
+
+        breakers.record_failure(POOL);
+        assert!(matches!(breakers.pools[&POOL], CircuitState::Open { .. }));
+        assert!(breakers.check(POOL).is_err());
+    }
+
+    #[test]
+    fn test_successful_probe_closes_circuit() {
+        let mut breakers = breakers(Duration::from_secs(60));
+        open_circuit(&mut breakers);
+        assert!(breakers.check(POOL).is_ok());
+
+        breakers.record_success(POOL);
+        assert!(breakers.check(POOL).is_ok());
+        assert!(breakers.check(POOL).is_ok());
+        assert!(
+            matches!(breakers.pools[&POOL], CircuitState::Closed { ref failures } if failures.is_empty())
+        );
+    }
+
+    #[test]
+    fn test_neutral_outcome_frees_probe_slot() {
+        let mut breakers = breakers(Duration::from_secs(60));
+        open_circuit(&mut breakers);
+        assert!(breakers.check(POOL).is_ok());
+        assert!(breakers.check(POOL).is_err());
+
+        breakers.record_neutral(POOL);
+        assert!(matches!(
+            breakers.pools[&POOL],
+            CircuitState::HalfOpen {
+                probe_in_flight: false
+            }
+        ));
+        assert!(
+            breakers.check(POOL).is_ok(),
+            "the next request becomes the probe"
+        );
+        assert!(breakers.check(POOL).is_err());
+    }
+}
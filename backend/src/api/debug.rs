@@ -1,16 +1,31 @@
 //! Debug pool management endpoints.
 
-use axum::{extract::State, Json};
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
 use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
 
-use crate::api::AppState;
-use crate::sandbox::router::DebugPoolCreateConfig;
+use crate::api::orderbook::invalid_pool_message;
+use crate::api::{router_error, AppState, DebugPoolState};
+use crate::sandbox::router::{
+    order_status_label, DebugPoolCreateConfig, DebugPoolInfo, DecodedOrder,
+    DeepPriceBootstrapReport, PoolResyncReport, RawVmObject, INVALID_DEBUG_POOL_CONFIG,
+};
+use crate::sandbox::state_loader::PoolId;
 use crate::types::{ApiError, ApiResult};
+use crate::units;
+
+pub(super) const DEFAULT_ITER_ORDERS_LIMIT: u64 = 20;
+pub(super) const MAX_ITER_ORDERS_LIMIT: u64 = 500;
 
 #[derive(Debug, Serialize)]
 pub struct EnsureDebugPoolResponse {
     pub success: bool,
     pub created: bool,
+    /// Pool key, e.g. `debug_usdc`
+    pub pool: PoolId,
     pub pool_object_id: String,
     pub token_symbol: String,
     pub token_name: String,
@@ -26,6 +41,8 @@ pub struct EnsureDebugPoolResponse {
 pub struct DebugPoolStatusResponse {
     pub success: bool,
     pub created: bool,
+    /// Pool key, e.g. `debug_usdc`
+    pub pool: PoolId,
     pub pool_object_id: Option<String>,
     pub token_symbol: String,
     pub token_name: String,
@@ -42,6 +59,69 @@ pub struct DebugPoolListResponse {
     pub pools: Vec<DebugPoolStatusResponse>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct PoolResyncResponse {
+    pub success: bool,
+    pub report: PoolResyncReport,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PrimeDeepPriceResponse {
+    pub success: bool,
+    pub report: DeepPriceBootstrapReport,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DebugPoolQuery {
+    /// Debug pool key or token symbol; the primary debug pool by default
+    pub pool: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IterOrdersQuery {
+    /// Debug pool key or token symbol; the primary debug pool by default
+    pub pool: Option<String>,
+    /// `true` (default) for bids, `false` for asks
+    pub bids: Option<bool>,
+    pub limit: Option<u64>,
+    /// Order id (u128, decimal) to page from; use the last id of the previous page
+    pub start_order_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IterOrderEntry {
+    #[serde(flatten)]
+    pub order: DecodedOrder,
+    /// Label for the numeric `status` (see [`order_status_label`])
+    pub status_name: &'static str,
+    /// Quote per base (human-readable)
+    pub price_human: f64,
+    pub quantity_human: f64,
+    pub filled_quantity_human: f64,
+}
+
+impl IterOrderEntry {
+    pub(super) fn new(order: DecodedOrder, base_decimals: u8, quote_decimals: u8) -> Self {
+        Self {
+            status_name: order_status_label(order.status),
+            price_human: units::scale_price(order.price, base_decimals, quote_decimals),
+            quantity_human: units::to_human(order.quantity, base_decimals),
+            filled_quantity_human: units::to_human(order.filled_quantity, base_decimals),
+            order,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct IterOrdersResponse {
+    pub success: bool,
+    pub side: &'static str,
+    pub limit: u64,
+    pub order_count: usize,
+    pub orders: Vec<IterOrderEntry>,
+    pub has_next_page: bool,
+}
+
 #[derive(Debug, Serialize)]
 pub struct DebugPoolConfigResponse {
     pub tick_size: u64,
@@ -56,6 +136,11 @@ pub struct DebugPoolConfigResponse {
     pub base_liquidity: u64,
     pub quote_liquidity: u64,
     pub deep_fee_budget: u64,
+    pub bid_levels: u32,
+    pub ask_levels: u32,
+    pub price_step: u64,
+    /// Seed order makers, cycled per order
+    pub maker_senders: Vec<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -76,6 +161,12 @@ pub struct EnsureDebugPoolRequest {
     pub base_liquidity: Option<u64>,
     pub quote_liquidity: Option<u64>,
     pub deep_fee_budget: Option<u64>,
+    /// Seed orders per side, each `price_step` ticks further from mid
+    pub bid_levels: Option<u32>,
+    pub ask_levels: Option<u32>,
+    pub price_step: Option<u64>,
+    /// Addresses whose balance managers place the seed orders, cycled per order
+    pub maker_senders: Option<Vec<String>>,
 }
 
 impl EnsureDebugPoolRequest {
@@ -96,6 +187,10 @@ impl EnsureDebugPoolRequest {
             || self.base_liquidity.is_some()
             || self.quote_liquidity.is_some()
             || self.deep_fee_budget.is_some()
+            || self.bid_levels.is_some()
+            || self.ask_levels.is_some()
+            || self.price_step.is_some()
+            || self.maker_senders.is_some()
     }
 }
 
@@ -131,6 +226,13 @@ fn cfg_to_response(cfg: &DebugPoolCreateConfig) -> DebugPoolConfigResponse {
         base_liquidity: cfg.base_liquidity,
         quote_liquidity: cfg.quote_liquidity,
         deep_fee_budget: cfg.deep_fee_budget,
+        bid_levels: cfg.bid_levels,
+        ask_levels: cfg.ask_levels,
+        price_step: cfg.price_step,
+        maker_senders: cfg
+            .seed_makers()
+            .map(|makers| makers.iter().map(|m| m.to_hex_literal()).collect())
+            .unwrap_or_default(),
     }
 }
 
@@ -185,12 +287,36 @@ fn build_requested_config(req: EnsureDebugPoolRequest) -> Result<DebugPoolCreate
     if let Some(v) = req.deep_fee_budget {
         cfg.deep_fee_budget = v;
     }
+    if let Some(v) = req.bid_levels {
+        cfg.bid_levels = v;
+    }
+    if let Some(v) = req.ask_levels {
+        cfg.ask_levels = v;
+    }
+    if let Some(v) = req.price_step {
+        cfg.price_step = v;
+    }
+    if let Some(v) = req.maker_senders {
+        cfg.maker_senders = v.iter().map(|sender| sender.trim().to_string()).collect();
+    }
 
     Ok(cfg)
 }
 
-async fn sync_debug_state(state: &AppState, info: &crate::sandbox::router::DebugPoolInfo) {
+/// Record a created debug pool; the primary one also fills the top-level fields.
+pub(super) async fn sync_debug_state(state: &AppState, info: &DebugPoolInfo) {
     let mut debug = state.debug_pool.write().await;
+    match debug
+        .pools
+        .iter_mut()
+        .find(|pool| pool.pool_id == info.pool_id)
+    {
+        Some(pool) => *pool = info.clone(),
+        None => debug.pools.push(info.clone()),
+    }
+    if info.pool_id != PoolId::DebugUsdc {
+        return;
+    }
     debug.created = true;
     debug.pool_object_id = Some(info.pool_object_id.clone());
     debug.token_symbol = info.token_symbol.clone();
@@ -202,10 +328,11 @@ async fn sync_debug_state(state: &AppState, info: &crate::sandbox::router::Debug
     debug.config = info.config.clone();
 }
 
-fn status_from_state(debug: &crate::api::DebugPoolState) -> DebugPoolStatusResponse {
+fn status_from_state(debug: &DebugPoolState) -> DebugPoolStatusResponse {
     DebugPoolStatusResponse {
         success: true,
         created: debug.created,
+        pool: PoolId::DebugUsdc,
         pool_object_id: debug.pool_object_id.clone(),
         token_symbol: debug.token_symbol.clone(),
         token_name: debug.token_name.clone(),
@@ -217,7 +344,51 @@ fn status_from_state(debug: &crate::api::DebugPoolState) -> DebugPoolStatusRespo
     }
 }
 
-/// GET /api/debug/pool - Return active debug pool configuration/status.
+fn status_from_info(info: &DebugPoolInfo) -> DebugPoolStatusResponse {
+    DebugPoolStatusResponse {
+        success: true,
+        created: true,
+        pool: info.pool_id,
+        pool_object_id: Some(info.pool_object_id.clone()),
+        token_symbol: info.token_symbol.clone(),
+        token_name: info.config.token_name.clone(),
+        token_description: info.config.token_description.clone(),
+        token_icon_url: info.config.token_icon_url.clone(),
+        token_decimals: info.config.token_decimals,
+        token_type: info.token_type.clone(),
+        config: cfg_to_response(&info.config),
+    }
+}
+
+/// The created debug pool `pool` names, by pool key or token symbol; the
+/// primary one when `pool` is omitted.
+fn created_debug_pool(debug: &DebugPoolState, pool: Option<&str>) -> ApiResult<DebugPoolInfo> {
+    let found = match pool {
+        Some(pool) => {
+            let pool_id = PoolId::from_str(pool);
+            let symbol = pool.trim().to_uppercase();
+            debug
+                .pools
+                .iter()
+                .find(|info| Some(info.pool_id) == pool_id || info.token_symbol == symbol)
+        }
+        None => debug
+            .pools
+            .iter()
+            .find(|info| info.pool_id == PoolId::DebugUsdc),
+    };
+    found.cloned().ok_or_else(|| {
+        ApiError::NotFound(match pool {
+            Some(pool) => format!(
+                "Debug pool {} has not been created; POST /api/debug/pool with its token_symbol first",
+                pool
+            ),
+            None => "Debug pool has not been created; POST /api/debug/pool first".into(),
+        })
+    })
+}
+
+/// GET /api/debug/pool - Return primary debug pool configuration/status.
 pub async fn get_debug_pool_status(
     State(state): State<AppState>,
 ) -> ApiResult<Json<DebugPoolStatusResponse>> {
@@ -226,16 +397,12 @@ pub async fn get_debug_pool_status(
     Ok(Json(status_from_state(&debug)))
 }
 
-/// GET /api/debug/pools - List created custom pools/tokens (currently max 1).
+/// GET /api/debug/pools - List created custom pools/tokens, in creation order.
 pub async fn list_debug_pools(
     State(state): State<AppState>,
 ) -> ApiResult<Json<DebugPoolListResponse>> {
     let debug = state.debug_pool.read().await;
-    let pools = if debug.created {
-        vec![status_from_state(&debug)]
-    } else {
-        Vec::new()
-    };
+    let pools = debug.pools.iter().map(status_from_info).collect();
 
     Ok(Json(DebugPoolListResponse {
         success: true,
@@ -243,7 +410,20 @@ pub async fn list_debug_pools(
     }))
 }
 
+/// Config validation failures are the caller's fault; anything else is a VM failure.
+fn ensure_debug_pool_error(e: anyhow::Error) -> ApiError {
+    let msg = e.to_string();
+    if msg.starts_with(INVALID_DEBUG_POOL_CONFIG) {
+        ApiError::BadRequest(msg)
+    } else {
+        ApiError::Internal(format!("Failed to ensure debug pool: {}", e))
+    }
+}
+
 /// POST /api/debug/pool - Create+seed debug pool in local VM (idempotent).
+///
+/// Each `token_symbol` gets its own pool; without overrides this is the
+/// primary DBG/USDC pool.
 pub async fn ensure_debug_pool(
     State(state): State<AppState>,
     Json(req): Json<Option<EnsureDebugPoolRequest>>,
@@ -251,7 +431,7 @@ pub async fn ensure_debug_pool(
     let router = state
         .router
         .as_ref()
-        .ok_or_else(|| ApiError::Internal("MoveVM router is not initialized".into()))?;
+        .ok_or_else(|| ApiError::ServiceUnavailable("MoveVM router is not initialized".into()))?;
 
     let info = match req {
         Some(body) if body.has_overrides() => {
@@ -259,12 +439,12 @@ pub async fn ensure_debug_pool(
             router
                 .ensure_debug_pool_with_config(cfg)
                 .await
-                .map_err(|e| ApiError::Internal(format!("Failed to ensure debug pool: {}", e)))?
+                .map_err(router_error(ensure_debug_pool_error))?
         }
         _ => router
             .ensure_debug_pool()
             .await
-            .map_err(|e| ApiError::Internal(format!("Failed to ensure debug pool: {}", e)))?,
+            .map_err(router_error(ensure_debug_pool_error))?,
     };
 
     sync_debug_state(&state, &info).await;
@@ -272,6 +452,7 @@ pub async fn ensure_debug_pool(
     Ok(Json(EnsureDebugPoolResponse {
         success: true,
         created: true,
+        pool: info.pool_id,
         pool_object_id: info.pool_object_id,
         token_symbol: info.token_symbol,
         token_name: info.config.token_name.clone(),
@@ -283,3 +464,171 @@ pub async fn ensure_debug_pool(
         message: "Debug token/USDC pool is ready in local VM".to_string(),
     }))
 }
+
+/// POST /api/debug/pool/:id/resync - Force a dynamic-field reconciliation pass on a pool.
+pub async fn resync_pool(
+    State(state): State<AppState>,
+    Path(pool): Path<String>,
+) -> ApiResult<Json<PoolResyncResponse>> {
+    let pool_id = PoolId::from_str(&pool)
+        .ok_or_else(|| ApiError::BadRequest(invalid_pool_message(&pool, true)))?;
+    let router = state
+        .router
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("MoveVM router is not initialized".into()))?;
+
+    let report = router
+        .resync_pool(pool_id)
+        .await
+        .map_err(router_error(|e| {
+            ApiError::Internal(format!("Failed to resync pool: {}", e))
+        }))?;
+
+    Ok(Json(PoolResyncResponse {
+        success: true,
+        report,
+    }))
+}
+
+/// POST /api/debug/pool/prime-deep-price - Re-run a debug pool's deep_price bootstrap.
+///
+/// Adds deep_price points from the first reference pool that accepts them and
+/// reports which one succeeded and the resulting `deep_per_asset`. `?pool=`
+/// picks the debug pool; the primary one by default.
+pub async fn prime_debug_deep_price(
+    State(state): State<AppState>,
+    Query(query): Query<DebugPoolQuery>,
+) -> ApiResult<Json<PrimeDeepPriceResponse>> {
+    let info = created_debug_pool(&*state.debug_pool.read().await, query.pool.as_deref())?;
+    let router = state
+        .router
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("MoveVM router is not initialized".into()))?;
+
+    let report = router
+        .prime_debug_deep_price(info.pool_id)
+        .await
+        .map_err(router_error(|e| {
+            ApiError::Internal(format!("deep_price bootstrap failed: {}", e))
+        }))?;
+
+    Ok(Json(PrimeDeepPriceResponse {
+        success: true,
+        report,
+    }))
+}
+
+/// GET /api/debug/pool/iter-orders - Decoded `order_query::iter_orders` page for a debug pool.
+pub async fn get_debug_iter_orders(
+    State(state): State<AppState>,
+    Query(query): Query<IterOrdersQuery>,
+) -> ApiResult<Json<IterOrdersResponse>> {
+    let bids = query.bids.unwrap_or(true);
+    let limit = query.limit.unwrap_or(DEFAULT_ITER_ORDERS_LIMIT);
+    if limit == 0 || limit > MAX_ITER_ORDERS_LIMIT {
+        return Err(ApiError::BadRequest(format!(
+            "limit must be between 1 and {}",
+            MAX_ITER_ORDERS_LIMIT
+        )));
+    }
+    let start_order_id = query
+        .start_order_id
+        .as_deref()
+        .map(|id| {
+            id.trim().parse::<u128>().map_err(|_| {
+                ApiError::BadRequest(format!("Invalid start_order_id '{}': expected a u128", id))
+            })
+        })
+        .transpose()?;
+
+    let info = created_debug_pool(&*state.debug_pool.read().await, query.pool.as_deref())?;
+    let router = state
+        .router
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("MoveVM router is not initialized".into()))?;
+
+    let page = router
+        .iter_orders(info.pool_id, bids, limit, start_order_id)
+        .await
+        .map_err(router_error(|e| {
+            ApiError::Internal(format!("Failed to iterate debug pool orders: {}", e))
+        }))?;
+
+    let orders: Vec<IterOrderEntry> = page
+        .orders
+        .into_iter()
+        .map(|order| IterOrderEntry::new(order, info.config.token_decimals, units::USDC_DECIMALS))
+        .collect();
+
+    Ok(Json(IterOrdersResponse {
+        success: true,
+        side: if bids { "bids" } else { "asks" },
+        limit,
+        order_count: orders.len(),
+        orders,
+        has_next_page: page.has_next_page,
+    }))
+}
+
+/// Raw object inspection is opt-in via `DEBUG_ENDPOINTS_ENABLED`
+fn debug_endpoints_enabled() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    *ENABLED.get_or_init(|| {
+        std::env::var("DEBUG_ENDPOINTS_ENABLED")
+            .map(|v| {
+                matches!(
+                    v.trim().to_ascii_lowercase().as_str(),
+                    "1" | "true" | "on" | "yes"
+                )
+            })
+            .unwrap_or(false)
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DebugObjectQuery {
+    /// Parent object id; when set, `:id` is read as a dynamic field child id
+    pub parent: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DebugObjectResponse {
+    pub success: bool,
+    pub object: RawVmObject,
+}
+
+/// GET /api/debug/object/:id - Type, version and hex BCS of an object loaded in the VM.
+///
+/// With `?parent=<id>`, `:id` is a dynamic field child under that parent.
+pub async fn get_debug_object(
+    State(state): State<AppState>,
+    Path(object_id): Path<String>,
+    Query(query): Query<DebugObjectQuery>,
+) -> ApiResult<Json<DebugObjectResponse>> {
+    if !debug_endpoints_enabled() {
+        return Err(ApiError::NotFound(
+            "Debug object inspection is disabled; set DEBUG_ENDPOINTS_ENABLED=true".into(),
+        ));
+    }
+    let router = state
+        .router
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("MoveVM router is not initialized".into()))?;
+
+    let object = router
+        .inspect_object(object_id.clone(), query.parent.clone())
+        .await
+        .map_err(router_error(|e| ApiError::BadRequest(e.to_string())))?
+        .ok_or_else(|| match &query.parent {
+            Some(parent) => ApiError::NotFound(format!(
+                "No dynamic field {} under {} in the VM",
+                object_id, parent
+            )),
+            None => ApiError::NotFound(format!("Object not loaded in the VM: {}", object_id)),
+        })?;
+
+    Ok(Json(DebugObjectResponse {
+        success: true,
+        object,
+    }))
+}
@@ -8,25 +8,41 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+pub mod access_log;
 mod balance;
+pub mod circuit_breaker;
 mod debug;
+mod order;
 pub mod orderbook;
 mod session;
+mod strategy;
 mod swap;
 mod system;
+pub mod trade_tape;
 
 pub use orderbook::SharedPoolRegistry;
 
+use circuit_breaker::PoolCircuitBreakers;
+use trade_tape::TradeTape;
+
+use crate::metrics::Metrics;
+use crate::sandbox::mainnet_quote::MainnetQuoter;
 use crate::sandbox::orderbook_builder::SandboxOrderbook;
-use crate::sandbox::router::{DebugPoolCreateConfig, RouterHandle};
+use crate::sandbox::router::{DebugPoolCreateConfig, DebugPoolInfo, RouterHandle, RouterTimeout};
 use crate::sandbox::state_loader::{PoolId, PoolRegistry};
 use crate::sandbox::swap_executor::SessionManager;
+use crate::types::ApiError;
 
 /// MoveVM-built orderbooks cached at startup, keyed by PoolId
 pub type SharedOrderbooks = Arc<RwLock<HashMap<PoolId, SandboxOrderbook>>>;
 pub type SharedDebugPoolState = Arc<RwLock<DebugPoolState>>;
+pub type SharedCircuitBreakers = Arc<RwLock<PoolCircuitBreakers>>;
+pub type SharedTradeTape = Arc<RwLock<TradeTape>>;
 
-/// Runtime metadata for the active debug pool/token exposed to API handlers.
+/// Runtime metadata for the debug pools/tokens exposed to API handlers.
+///
+/// The top-level fields describe the primary pool (`PoolId::DebugUsdc`),
+/// which the `DEBUG`/`DBG` aliases and session bundles refer to.
 #[derive(Debug, Clone)]
 pub struct DebugPoolState {
     pub created: bool,
@@ -38,6 +54,21 @@ pub struct DebugPoolState {
     pub token_decimals: u8,
     pub token_type: String,
     pub config: DebugPoolCreateConfig,
+    /// Every created debug pool, in creation order
+    pub pools: Vec<DebugPoolInfo>,
+}
+
+impl DebugPoolState {
+    /// Symbols of the primary debug token and of every created debug pool
+    pub fn token_symbols(&self) -> Vec<String> {
+        let mut symbols = vec![self.token_symbol.to_uppercase()];
+        for info in &self.pools {
+            if !symbols.contains(&info.token_symbol) {
+                symbols.push(info.token_symbol.clone());
+            }
+        }
+        symbols
+    }
 }
 
 impl Default for DebugPoolState {
@@ -51,9 +82,9 @@ impl Default for DebugPoolState {
             token_description: cfg.token_description.clone(),
             token_icon_url: cfg.token_icon_url.clone(),
             token_decimals: cfg.token_decimals,
-            token_type: "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa::debug_token::DEBUG_TOKEN"
-                .to_string(),
+            token_type: cfg.token_type(),
             config: cfg,
+            pools: Vec::new(),
         }
     }
 }
@@ -66,6 +97,19 @@ pub struct AppState {
     pub orderbooks: SharedOrderbooks,
     pub router: Option<RouterHandle>,
     pub debug_pool: SharedDebugPoolState,
+    pub circuit_breakers: SharedCircuitBreakers,
+    /// Recent fills per pool decoded from swap events (`TRADE_TAPE_CAPACITY`)
+    pub trade_tape: SharedTradeTape,
+    /// JSON-RPC client for live mainnet quotes (`SUI_RPC_URL`)
+    pub mainnet_quoter: Arc<MainnetQuoter>,
+    /// Pool priority for pairs served by more than one pool (`PREFERRED_POOLS`)
+    pub preferred_pools: Arc<Vec<PoolId>>,
+    /// Default tolerance for `/api/swap/protected` (`SWAP_SLIPPAGE_BPS`)
+    pub default_slippage_bps: u32,
+    /// Longest route `determine_route` may return (`ROUTER_MAX_HOPS`, 1-3)
+    pub max_route_hops: usize,
+    /// Prometheus counters served at `/metrics`; shared with the router thread
+    pub metrics: Arc<Metrics>,
 }
 
 impl AppState {
@@ -75,17 +119,77 @@ impl AppState {
         orderbooks: SharedOrderbooks,
         router: Option<RouterHandle>,
     ) -> Self {
+        let metrics = router
+            .as_ref()
+            .map_or_else(|| Arc::new(Metrics::new()), RouterHandle::metrics);
         Self {
             pool_registry,
             session_manager,
             orderbooks,
             router,
             debug_pool: Arc::new(RwLock::new(DebugPoolState::default())),
+            circuit_breakers: Arc::new(RwLock::new(PoolCircuitBreakers::from_env())),
+            trade_tape: Arc::new(RwLock::new(TradeTape::from_env())),
+            mainnet_quoter: Arc::new(MainnetQuoter::from_env()),
+            preferred_pools: Arc::new(swap::preferred_pools_from_env()),
+            default_slippage_bps: swap::default_slippage_bps_from_env(),
+            max_route_hops: swap::max_route_hops_from_env(),
+            metrics,
+        }
+    }
+
+    /// Copy whose router builds clock inputs at `timestamp_ms` (see
+    /// [`RouterHandle::at_clock`]); `None` keeps the synthetic clock.
+    pub fn at_clock(&self, timestamp_ms: Option<u64>) -> AppState {
+        AppState {
+            router: self.router.as_ref().map(|r| r.at_clock(timestamp_ms)),
+            ..self.clone()
+        }
+    }
+
+    /// Copy whose router skips the single-hop quote cache (see
+    /// [`RouterHandle::without_quote_cache`]).
+    pub fn without_quote_cache(&self) -> AppState {
+        AppState {
+            router: self.router.as_ref().map(RouterHandle::without_quote_cache),
+            ..self.clone()
         }
     }
 }
 
-/// Create the API router with all endpoints
+#[cfg(test)]
+impl AppState {
+    /// No pools, sessions, or router, and the default routing settings
+    /// regardless of the environment
+    pub(crate) fn for_tests() -> Self {
+        Self {
+            preferred_pools: Arc::new(Vec::new()),
+            default_slippage_bps: 50,
+            max_route_hops: 2,
+            ..Self::new(
+                Arc::new(RwLock::new(PoolRegistry::new())),
+                Arc::new(SessionManager::new(HashMap::new())),
+                Arc::new(RwLock::new(HashMap::new())),
+                None,
+            )
+        }
+    }
+}
+
+/// Error mapper for router calls: a [`RouterTimeout`] becomes
+/// `ApiError::RouterTimeout`, any other failure goes through `map`.
+pub(crate) fn router_error<F>(map: F) -> impl FnOnce(anyhow::Error) -> ApiError
+where
+    F: FnOnce(anyhow::Error) -> ApiError,
+{
+    move |e| match e.downcast_ref::<RouterTimeout>() {
+        Some(timeout) => ApiError::RouterTimeout(timeout.to_string()),
+        None => map(e),
+    }
+}
+
+/// Create the app router: every API endpoint under `/api` plus `/health/detailed`
+/// and `/metrics`
 pub fn router(
     pool_registry: SharedPoolRegistry,
     session_manager: Arc<SessionManager>,
@@ -94,29 +198,101 @@ pub fn router(
 ) -> Router {
     let app_state = AppState::new(pool_registry, session_manager, orderbooks, router_handle);
 
+    Router::new()
+        .route("/health/detailed", get(system::get_detailed_health))
+        .route("/metrics", get(system::get_metrics))
+        .nest("/api", api_routes())
+        .with_state(app_state)
+}
+
+fn api_routes() -> Router<AppState> {
     Router::new()
         // Session management
         .route("/session", post(session::create_session))
         .route("/session/:id", get(session::get_session))
         .route("/session/:id/history", get(session::get_swap_history))
         .route("/session/:id/reset", post(session::reset_session))
+        .route("/session/:id/account", get(session::get_account))
+        .route("/session/:id/orders", get(session::get_session_orders))
+        .route(
+            "/session/:id/orders/cancel-all",
+            post(order::cancel_all_orders),
+        )
+        .route(
+            "/session/:id/withdraw-settled",
+            post(order::withdraw_settled),
+        )
+        .route("/session/:id/export", get(session::export_session))
+        .route("/session/import", post(session::import_session))
         // Wallet operations
         .route("/balance/:session_id", get(balance::get_balance))
         .route("/faucet", post(balance::faucet))
         // Swap operations
         .route("/swap", post(swap::execute_swap))
         .route("/swap/quote", post(swap::get_quote))
+        .route("/swap/quote/batch", post(swap::get_quote_batch))
+        .route("/swap/quote/reverse", post(swap::get_reverse_quote))
+        .route("/swap/protected", post(swap::execute_protected_swap))
+        // Limit orders
+        .route("/order/limit", post(order::place_limit_order))
+        .route("/order/cancel", post(order::cancel_order))
+        // Strategy backtesting
+        .route("/strategy/run", post(strategy::run_strategy))
         .route("/startup-check", get(system::get_startup_check))
+        .route("/status/full", get(system::get_full_status))
+        .route("/router/reserves", get(system::get_reserve_status))
+        .route("/router/contract", get(system::get_router_contract))
+        .route("/router/startup-check", post(system::refresh_startup_check))
+        .route("/router/reset", post(system::reset_router))
+        .route("/router/epoch", post(system::set_router_epoch))
+        .route("/router/snapshot/save", post(system::save_router_snapshot))
+        .route("/router/snapshot/load", post(system::load_router_snapshot))
+        .route(
+            "/router/health-check",
+            post(system::run_router_health_check),
+        )
+        .route(
+            "/router/clock",
+            get(system::get_router_clock).post(system::set_router_clock),
+        )
+        .route(
+            "/router/sender",
+            get(system::get_router_sender).post(system::set_router_sender),
+        )
         .route(
             "/debug/pool",
             get(debug::get_debug_pool_status).post(debug::ensure_debug_pool),
         )
         .route("/debug/pools", get(debug::list_debug_pools))
+        .route("/debug/pool/iter-orders", get(debug::get_debug_iter_orders))
+        .route(
+            "/debug/pool/prime-deep-price",
+            post(debug::prime_debug_deep_price),
+        )
+        .route("/debug/pool/:id/resync", post(debug::resync_pool))
+        .route("/debug/object/:id", get(debug::get_debug_object))
         // Pool listing
         .route("/pools", get(orderbook::list_pools))
+        .route("/pools/:id/volumes", get(orderbook::get_pool_volumes))
+        .route("/pools/:id/state", get(orderbook::get_pool_state))
+        .route("/pools/:id/deep-price", get(orderbook::get_pool_deep_price))
         // Orderbook (supports ?pool=sui_usdc|wal_usdc|deep_usdc)
         .route("/orderbook", get(orderbook::get_orderbook))
         .route("/orderbook/depth", get(orderbook::get_depth))
+        .route(
+            "/orderbook/depth/aggregate",
+            get(orderbook::get_aggregate_depth),
+        )
         .route("/orderbook/stats", get(orderbook::get_stats))
-        .with_state(app_state)
+        .route("/orderbook/ws", get(orderbook::orderbook_ws))
+        .route("/orderbook/trades", get(orderbook::get_trades))
+        .route("/orderbook/orders", get(orderbook::get_orders))
+        .route("/orderbook/two-sided-quote", get(swap::get_two_sided_quote))
+        .route(
+            "/orderbook/rebuild-all",
+            post(orderbook::rebuild_all_orderbooks),
+        )
+        .route("/orderbook/reload", post(orderbook::reload_orderbooks))
+        // Simulation fidelity
+        .route("/validate/quote", get(swap::validate_quote))
 }
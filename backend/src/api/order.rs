@@ -0,0 +1,504 @@
+//! Limit order endpoints
+//!
+//! Orders are placed in MoveVM through a balance manager owned by the
+//! session. The router creates it on the session's first order and reuses it
+//! afterwards; the funds an order locks are debited from the session, and
+//! cancelling it credits everything the balance manager held back.
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::api::swap::{pool_pairs, PoolPairs};
+use crate::api::{router_error, AppState};
+use crate::sandbox::deepbook_errors::DeepBookAbort;
+use crate::sandbox::router::{
+    limit_order_deposits, order_status_label, CancelOrderResult, LimitOrderParams, ORDER_NOT_FOUND,
+    ORDER_NOT_OWNED,
+};
+use crate::sandbox::state_loader::PoolId;
+use crate::sandbox::swap_executor::TradingSession;
+use crate::types::{ApiError, ApiResult};
+use crate::units;
+
+/// Most DEEP taken from the session for fees when `deep_amount` is omitted
+const DEFAULT_LIMIT_ORDER_DEEP_BUDGET: u64 = 10_000_000; // 10 DEEP
+
+#[derive(Debug, Deserialize)]
+pub struct LimitOrderRequest {
+    pub session_id: String,
+    pub pool: String,
+    /// DeepBook price (quote units per base unit, scaled by 1e9); must be a
+    /// multiple of the pool's tick size
+    pub price: String,
+    /// Base quantity in smallest units; a multiple of the lot size, at least the min size
+    pub quantity: String,
+    pub is_bid: bool,
+    /// VM clock expiry in ms; omitted orders never expire
+    pub expire_timestamp: Option<u64>,
+    #[serde(default)]
+    pub client_order_id: u64,
+    /// DEEP deposited for fees on pools that charge them (smallest units);
+    /// defaults to the session's DEEP balance, capped at 10 DEEP
+    pub deep_amount: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LimitOrderResponse {
+    pub success: bool,
+    pub order_id: String,
+    /// DeepBook order status: "live", "partially_filled", "filled", ...
+    pub status: String,
+    /// Numeric DeepBook status code behind `status`
+    pub status_code: u8,
+    /// False when the order did not rest on the book
+    pub inserted: bool,
+    pub price: String,
+    pub price_human: f64,
+    pub original_quantity: String,
+    pub executed_quantity: String,
+    pub cumulative_quote_quantity: String,
+    pub balance_manager_id: String,
+    /// True when this order created the session's balance manager
+    pub balance_manager_created: bool,
+    /// Token moved from the session into the balance manager (the quote coin for bids)
+    pub deposit_token: String,
+    pub deposit_amount: String,
+    pub deep_deposit: String,
+    pub gas_used: String,
+}
+
+fn parse_amount(raw: &str, field: &str) -> ApiResult<u64> {
+    raw.parse()
+        .map_err(|_| ApiError::BadRequest(format!("Invalid {}: {}", field, raw)))
+}
+
+/// POST /api/order/limit - Place a limit order from a session's balance manager
+pub async fn place_limit_order(
+    State(state): State<AppState>,
+    Json(req): Json<LimitOrderRequest>,
+) -> ApiResult<Json<LimitOrderResponse>> {
+    let session_arc = state
+        .session_manager
+        .get_session(&req.session_id)
+        .await
+        .ok_or_else(|| ApiError::NotFound(format!("Session not found: {}", req.session_id)))?;
+
+    let pool_id = PoolId::from_str(&req.pool)
+        .ok_or_else(|| ApiError::BadRequest(format!("Invalid pool: {}", req.pool)))?;
+    let price = parse_amount(&req.price, "price")?;
+    let quantity = parse_amount(&req.quantity, "quantity")?;
+    if price == 0 || quantity == 0 {
+        return Err(ApiError::BadRequest(
+            "price and quantity must be greater than zero".into(),
+        ));
+    }
+
+    let config = state.pool_registry.read().await.config(pool_id);
+    let (base_decimals, quote_decimals) = if pool_id.is_debug() {
+        let debug = state.debug_pool.read().await;
+        let info = debug
+            .pools
+            .iter()
+            .find(|info| info.pool_id == pool_id)
+            .ok_or_else(|| {
+                ApiError::BadRequest(
+                    "Debug pool has not been created; POST /api/debug/pool first".into(),
+                )
+            })?;
+        (info.config.token_decimals, units::USDC_DECIMALS)
+    } else {
+        (config.base_decimals, config.quote_decimals)
+    };
+    let pairs = pool_pairs(&state).await;
+    let router = state
+        .router
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("MoveVM router is not initialized".into()))?;
+
+    let (base_deposit, quote_deposit) = limit_order_deposits(req.is_bid, price, quantity);
+    let (deposit_token, deposit_amount) = if req.is_bid {
+        (pairs.quote_symbol(pool_id), quote_deposit)
+    } else {
+        (pairs.base_symbol(pool_id), base_deposit)
+    };
+    let deep_amount = {
+        let session = session_arc.read().await;
+        let available = session.balances.get(&deposit_token);
+        if available < deposit_amount {
+            return Err(ApiError::BadRequest(format!(
+                "Insufficient {} balance: have {}, need {}",
+                deposit_token, available, deposit_amount
+            )));
+        }
+        let deep_balance = session.balances.get("DEEP");
+        match req.deep_amount.as_deref() {
+            Some(raw) => {
+                let deep_amount = parse_amount(raw, "deep_amount")?;
+                if deep_balance < deep_amount {
+                    return Err(ApiError::BadRequest(format!(
+                        "Insufficient DEEP balance: have {}, need {}",
+                        deep_balance, deep_amount
+                    )));
+                }
+                deep_amount
+            }
+            None => deep_balance.min(DEFAULT_LIMIT_ORDER_DEEP_BUDGET),
+        }
+    };
+
+    let placed = router
+        .place_limit_order(
+            req.session_id.clone(),
+            LimitOrderParams {
+                pool_id,
+                price,
+                quantity,
+                is_bid: req.is_bid,
+                expire_timestamp: req.expire_timestamp,
+                client_order_id: req.client_order_id,
+                deep_amount,
+            },
+        )
+        .await
+        .map_err(router_error(|e| {
+            let err_text = e.to_string();
+            match DeepBookAbort::classify(&err_text) {
+                Some(abort) => ApiError::DeepBook {
+                    deepbook_code: abort.code(),
+                    message: format!("Failed to place limit order: {}", abort.message()),
+                },
+                None => ApiError::BadRequest(format!("Failed to place limit order: {}", err_text)),
+            }
+        }))?;
+
+    let deposited = if req.is_bid {
+        placed.quote_deposit
+    } else {
+        placed.base_deposit
+    };
+    {
+        let mut session = session_arc.write().await;
+        session
+            .balances
+            .subtract(&deposit_token, deposited)
+            .map_err(|e| ApiError::Internal(e.to_string()))?;
+        session
+            .balances
+            .subtract("DEEP", placed.deep_deposit)
+            .map_err(|e| ApiError::Internal(e.to_string()))?;
+        session.record_swap_draw(&deposit_token, deposited);
+        session.record_swap_draw("DEEP", placed.deep_deposit);
+    }
+
+    Ok(Json(LimitOrderResponse {
+        success: true,
+        order_id: placed.order_id.to_string(),
+        status: order_status_label(placed.status).to_string(),
+        status_code: placed.status,
+        inserted: placed.inserted,
+        price: placed.price.to_string(),
+        price_human: units::scale_price(placed.price, base_decimals, quote_decimals),
+        original_quantity: placed.original_quantity.to_string(),
+        executed_quantity: placed.executed_quantity.to_string(),
+        cumulative_quote_quantity: placed.cumulative_quote_quantity.to_string(),
+        balance_manager_id: placed.balance_manager_id,
+        balance_manager_created: placed.balance_manager_created,
+        deposit_token,
+        deposit_amount: deposited.to_string(),
+        deep_deposit: placed.deep_deposit.to_string(),
+        gas_used: placed.gas_used.to_string(),
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CancelOrderRequest {
+    pub session_id: String,
+    pub pool: String,
+    /// u128 order id as returned by `/api/order/limit`
+    pub order_id: String,
+    /// Defaults to the session's balance manager
+    pub balance_manager_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CancelOrderResponse {
+    pub success: bool,
+    pub order_id: String,
+    pub is_bid: bool,
+    pub price: String,
+    /// Quantity still open when the order was cancelled
+    pub cancelled_quantity: String,
+    pub filled_quantity: String,
+    pub balance_manager_id: String,
+    /// Credited back to the session, keyed by token symbol
+    pub base_token: String,
+    pub base_refund: String,
+    pub quote_refund: String,
+    pub deep_refund: String,
+    pub gas_used: String,
+}
+
+/// POST /api/order/cancel - Cancel a resting order and refund the session
+pub async fn cancel_order(
+    State(state): State<AppState>,
+    Json(req): Json<CancelOrderRequest>,
+) -> ApiResult<Json<CancelOrderResponse>> {
+    let session_arc = state
+        .session_manager
+        .get_session(&req.session_id)
+        .await
+        .ok_or_else(|| ApiError::NotFound(format!("Session not found: {}", req.session_id)))?;
+
+    let pool_id = PoolId::from_str(&req.pool)
+        .ok_or_else(|| ApiError::BadRequest(format!("Invalid pool: {}", req.pool)))?;
+    let order_id: u128 = req
+        .order_id
+        .parse()
+        .map_err(|_| ApiError::BadRequest(format!("Invalid order_id: {}", req.order_id)))?;
+
+    let pairs = pool_pairs(&state).await;
+    let router = state
+        .router
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("MoveVM router is not initialized".into()))?;
+
+    let cancelled = router
+        .cancel_order(
+            req.session_id.clone(),
+            pool_id,
+            req.balance_manager_id.clone(),
+            order_id,
+        )
+        .await
+        .map_err(router_error(|e| {
+            let msg = e.to_string();
+            if msg.starts_with(ORDER_NOT_FOUND)
+                || msg.starts_with(ORDER_NOT_OWNED)
+                || msg.starts_with("Session ")
+                || msg.starts_with("Balance manager ")
+            {
+                ApiError::BadRequest(msg)
+            } else {
+                ApiError::Internal(format!("Failed to cancel order: {}", msg))
+            }
+        }))?;
+
+    credit_withdrawal(
+        &mut *session_arc.write().await,
+        &pairs,
+        pool_id,
+        cancelled.refunds(),
+    );
+    Ok(Json(CancelOrderResponse::new(
+        cancelled,
+        pairs.base_symbol(pool_id),
+    )))
+}
+
+impl CancelOrderResponse {
+    fn new(cancelled: CancelOrderResult, base_token: String) -> Self {
+        let order = cancelled.order;
+        Self {
+            success: true,
+            order_id: order.order_id,
+            is_bid: order.is_bid,
+            price: order.price.to_string(),
+            cancelled_quantity: order
+                .quantity
+                .saturating_sub(order.filled_quantity)
+                .to_string(),
+            filled_quantity: order.filled_quantity.to_string(),
+            balance_manager_id: cancelled.balance_manager_id,
+            base_token,
+            base_refund: cancelled.base_refund.to_string(),
+            quote_refund: cancelled.quote_refund.to_string(),
+            deep_refund: cancelled.deep_refund.to_string(),
+            gas_used: cancelled.gas_used.to_string(),
+        }
+    }
+}
+
+/// Credit `pool_id`'s base, quote and DEEP withdrawn from the session's
+/// balance manager back to the session
+fn credit_withdrawal(
+    session: &mut TradingSession,
+    pairs: &PoolPairs,
+    pool_id: PoolId,
+    amounts: [u64; 3],
+) {
+    for (token, amount) in [
+        (pairs.base_symbol(pool_id), amounts[0]),
+        (pairs.quote_symbol(pool_id), amounts[1]),
+        ("DEEP".to_string(), amounts[2]),
+    ] {
+        session.balances.add(&token, amount);
+        session.record_swap_return(&token, amount);
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CancelledOrderEntry {
+    pub pool: String,
+    #[serde(flatten)]
+    pub cancel: CancelOrderResponse,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FailedCancelEntry {
+    pub pool: String,
+    pub order_id: String,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CancelAllOrdersResponse {
+    /// False when any open order failed to cancel
+    pub success: bool,
+    pub session_id: String,
+    /// `None` when the session has never placed a limit order
+    pub balance_manager_id: Option<String>,
+    pub cancelled_order_ids: Vec<String>,
+    /// Each cancelled order with the refunds credited to the session
+    pub cancelled: Vec<CancelledOrderEntry>,
+    pub failed: Vec<FailedCancelEntry>,
+}
+
+/// POST /api/session/:id/orders/cancel-all - Cancel every open order of the
+/// session's balance manager and refund the session
+pub async fn cancel_all_orders(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<CancelAllOrdersResponse>> {
+    let session_arc = state
+        .session_manager
+        .get_session(&id)
+        .await
+        .ok_or_else(|| ApiError::NotFound(format!("Session not found: {}", id)))?;
+
+    let pairs = pool_pairs(&state).await;
+    let router = state
+        .router
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("MoveVM router is not initialized".into()))?;
+
+    let result = router
+        .cancel_all_orders(id.clone())
+        .await
+        .map_err(router_error(|e| {
+            ApiError::Internal(format!("Failed to cancel open orders: {}", e))
+        }))?;
+
+    let mut cancelled = Vec::with_capacity(result.cancelled.len());
+    {
+        let mut session = session_arc.write().await;
+        for (pool_id, order) in result.cancelled {
+            credit_withdrawal(&mut session, &pairs, pool_id, order.refunds());
+            cancelled.push(CancelledOrderEntry {
+                pool: pool_id.as_str().to_string(),
+                cancel: CancelOrderResponse::new(order, pairs.base_symbol(pool_id)),
+            });
+        }
+    }
+    let failed: Vec<FailedCancelEntry> = result
+        .failed
+        .into_iter()
+        .map(|failure| FailedCancelEntry {
+            pool: failure.pool_id.as_str().to_string(),
+            order_id: failure.order_id,
+            error: failure.error,
+        })
+        .collect();
+
+    Ok(Json(CancelAllOrdersResponse {
+        success: failed.is_empty(),
+        session_id: id,
+        balance_manager_id: result.balance_manager_id,
+        cancelled_order_ids: cancelled
+            .iter()
+            .map(|entry| entry.cancel.order_id.clone())
+            .collect(),
+        cancelled,
+        failed,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WithdrawSettledRequest {
+    pub pool: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WithdrawSettledResponse {
+    pub success: bool,
+    pub session_id: String,
+    pub pool: String,
+    pub balance_manager_id: String,
+    /// Credited to the session, keyed by token symbol
+    pub base_token: String,
+    pub base_amount: String,
+    pub quote_amount: String,
+    pub deep_amount: String,
+    pub gas_used: String,
+}
+
+/// POST /api/session/:id/withdraw-settled - Claim the settled balances of the
+/// session's balance manager in a pool and credit them to the session
+pub async fn withdraw_settled(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<WithdrawSettledRequest>,
+) -> ApiResult<Json<WithdrawSettledResponse>> {
+    let session_arc = state
+        .session_manager
+        .get_session(&id)
+        .await
+        .ok_or_else(|| ApiError::NotFound(format!("Session not found: {}", id)))?;
+
+    let pool_id = PoolId::from_str(&req.pool)
+        .ok_or_else(|| ApiError::BadRequest(format!("Invalid pool: {}", req.pool)))?;
+
+    let pairs = pool_pairs(&state).await;
+    let router = state
+        .router
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("MoveVM router is not initialized".into()))?;
+
+    let withdrawn = router
+        .withdraw_settled(id.clone(), pool_id)
+        .await
+        .map_err(router_error(|e| {
+            let msg = e.to_string();
+            if msg.starts_with("Session ") {
+                ApiError::BadRequest(msg)
+            } else {
+                ApiError::Internal(format!("Failed to withdraw settled balances: {}", msg))
+            }
+        }))?;
+
+    let base_token = pairs.base_symbol(pool_id);
+    credit_withdrawal(
+        &mut *session_arc.write().await,
+        &pairs,
+        pool_id,
+        [
+            withdrawn.base_amount,
+            withdrawn.quote_amount,
+            withdrawn.deep_amount,
+        ],
+    );
+
+    Ok(Json(WithdrawSettledResponse {
+        success: true,
+        session_id: id,
+        pool: pool_id.as_str().to_string(),
+        balance_manager_id: withdrawn.balance_manager_id,
+        base_token,
+        base_amount: withdrawn.base_amount.to_string(),
+        quote_amount: withdrawn.quote_amount.to_string(),
+        deep_amount: withdrawn.deep_amount.to_string(),
+        gas_used: withdrawn.gas_used.to_string(),
+    }))
+}
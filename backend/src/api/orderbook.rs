@@ -3,16 +3,34 @@
 //! Returns the current orderbook state built via MoveVM `iter_orders` execution.
 
 use axum::{
-    extract::{Query, State},
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, Query, State,
+    },
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::{error::RecvError, Receiver};
 use tokio::sync::RwLock;
 
-use crate::api::AppState;
-use crate::sandbox::orderbook_builder::SandboxOrderbook;
+use crate::api::debug::{IterOrderEntry, DEFAULT_ITER_ORDERS_LIMIT, MAX_ITER_ORDERS_LIMIT};
+use crate::api::trade_tape::Trade;
+use crate::api::{router_error, swap, AppState};
+use crate::sandbox::orderbook_builder::{
+    build_orderbook_and_loader_from_file, LevelSort, SandboxOrderbook,
+};
+use crate::sandbox::router::{PoolDeepPrice, PoolParams, RouterEpochVolumes, RouterPoolState};
 use crate::sandbox::state_loader::{PoolId, PoolRegistry};
+use crate::sandbox::swap_executor::PoolUpdate;
+use crate::types::{ApiError, ApiResult};
+use crate::units;
 
 // --- Orderbook API response types (formerly in sandbox::deepbook) ---
 
@@ -28,6 +46,7 @@ pub struct OrderbookSnapshot {
     pub spread_bps: Option<u64>,
     pub bids: Vec<OrderbookLevel>,
     pub asks: Vec<OrderbookLevel>,
+    pub checkpoint: u64,
     pub timestamp: u64,
 }
 
@@ -70,40 +89,77 @@ pub struct BinanceOrderbookExtended {
     pub timestamp: u64,
 }
 
+/// Which forked state an orderbook-derived response reflects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderbookProvenance {
+    /// Source checkpoint of the pool state the book was built from
+    pub checkpoint: u64,
+    /// When the book was built (unix ms)
+    pub built_at_unix_ms: u64,
+    /// "global" for the startup book, "session" for a session's consumed copy
+    pub source: String,
+}
+
+impl OrderbookProvenance {
+    pub fn from_orderbook(ob: &SandboxOrderbook, source: &str) -> Self {
+        Self {
+            checkpoint: ob.checkpoint,
+            built_at_unix_ms: ob.built_at_unix_ms,
+            source: source.to_string(),
+        }
+    }
+}
+
 /// Shared pool registry wrapped for async access
 pub type SharedPoolRegistry = Arc<RwLock<PoolRegistry>>;
 
+/// How long a fetched mainnet checkpoint height is reused by `/orderbook/stats`
+const MAINNET_HEAD_TTL: Duration = Duration::from_secs(30);
+
 /// Query parameters for orderbook endpoint
 #[derive(Debug, Deserialize)]
 pub struct OrderbookQuery {
-    /// Pool to query (sui_usdc, wal_usdc, deep_usdc). Defaults to sui_usdc
+    /// Pool to query (e.g. sui_usdc, or a POOLS_MANIFEST key). Defaults to sui_usdc
     #[serde(default = "default_pool")]
     pub pool: String,
     /// Optional session_id to get session-specific orderbook (reflects consumed liquidity)
     pub session_id: Option<String>,
+    /// Level ordering: price_asc | price_desc | quantity_desc (default: bids desc, asks asc)
+    pub sort: Option<LevelSort>,
+    /// Maximum number of levels returned per side
+    pub limit: Option<usize>,
 }
 
 fn default_pool() -> String {
     "sui_usdc".to_string()
 }
 
+/// Error for a `pool` parameter that names no pool, listing the valid ones
+pub(crate) fn invalid_pool_message(pool: &str, with_debug: bool) -> String {
+    let mut valid: Vec<&str> = PoolId::all().iter().map(PoolId::as_str).collect();
+    if with_debug {
+        valid.push(PoolId::DebugUsdc.as_str());
+    }
+    format!("Invalid pool '{}'. Valid pools: {}", pool, valid.join(", "))
+}
+
 /// GET /api/orderbook - Returns the current orderbook snapshot
 pub async fn get_orderbook(
     State(state): State<AppState>,
     Query(query): Query<OrderbookQuery>,
-) -> Json<OrderbookResponse> {
+    headers: HeaderMap,
+) -> Response {
     let pool_id = match PoolId::from_str(&query.pool) {
         Some(id) => id,
         None => {
             return Json(OrderbookResponse {
                 success: false,
-                error: Some(format!(
-                    "Invalid pool '{}'. Valid pools: sui_usdc, wal_usdc, deep_usdc",
-                    query.pool
-                )),
+                error: Some(invalid_pool_message(&query.pool, false)),
+                provenance: None,
                 orderbook: None,
                 stats: None,
-            });
+            })
+            .into_response();
         }
     };
 
@@ -114,10 +170,20 @@ pub async fn get_orderbook(
         None
     };
 
-    let snapshot = if let Some(ref session_arc) = session_arc {
+    let (snapshot, provenance, etag) = if let Some(ref session_arc) = session_arc {
         let session = session_arc.read().await;
         match session.orderbooks.get(&pool_id) {
-            Some(ob) => sandbox_orderbook_to_snapshot(ob),
+            Some(ob) => {
+                let etag = orderbook_etag(ob, "session", &query);
+                if let Some(not_modified) = not_modified(&headers, etag.as_deref()) {
+                    return not_modified;
+                }
+                (
+                    sandbox_orderbook_to_snapshot(ob, query.sort, query.limit),
+                    OrderbookProvenance::from_orderbook(ob, "session"),
+                    etag,
+                )
+            }
             None => {
                 return Json(OrderbookResponse {
                     success: false,
@@ -125,16 +191,28 @@ pub async fn get_orderbook(
                         "Pool '{}' orderbook not built",
                         pool_id.display_name()
                     )),
+                    provenance: None,
                     orderbook: None,
                     stats: None,
-                });
+                })
+                .into_response();
             }
         }
     } else {
         // Global orderbook (no session)
         let orderbooks = state.orderbooks.read().await;
         match orderbooks.get(&pool_id) {
-            Some(ob) => sandbox_orderbook_to_snapshot(ob),
+            Some(ob) => {
+                let etag = orderbook_etag(ob, "global", &query);
+                if let Some(not_modified) = not_modified(&headers, etag.as_deref()) {
+                    return not_modified;
+                }
+                (
+                    sandbox_orderbook_to_snapshot(ob, query.sort, query.limit),
+                    OrderbookProvenance::from_orderbook(ob, "global"),
+                    etag,
+                )
+            }
             None => {
                 return Json(OrderbookResponse {
                     success: false,
@@ -142,14 +220,17 @@ pub async fn get_orderbook(
                         "Pool '{}' orderbook not built",
                         pool_id.display_name()
                     )),
+                    provenance: None,
                     orderbook: None,
                     stats: None,
-                });
+                })
+                .into_response();
             }
         }
     };
 
     // Get stats from registry for object counts
+    let skipped_filled_orders = skipped_filled_orders(&state, pool_id).await;
     let registry = state.pool_registry.read().await;
     let stats = registry.get(pool_id).map(|loader| {
         let s = loader.stats();
@@ -159,25 +240,312 @@ pub async fn get_orderbook(
             bids_slices: s.bids_slices,
             max_checkpoint: s.max_checkpoint,
             max_version: s.max_version,
+            duplicate_objects: s.duplicate_objects,
+            skipped_filled_orders,
+            checkpoint: None,
+            checkpoints_behind: None,
         }
     });
 
-    Json(OrderbookResponse {
-        success: true,
-        error: None,
-        orderbook: Some(snapshot),
-        stats,
-    })
+    with_etag(
+        Json(OrderbookResponse {
+            success: true,
+            error: None,
+            provenance: Some(provenance),
+            orderbook: Some(snapshot),
+            stats,
+        }),
+        etag,
+    )
+}
+
+/// Query parameters for the orderbook stream
+#[derive(Debug, Deserialize)]
+pub struct OrderbookStreamQuery {
+    /// Only stream this pool; omitted streams every pool
+    pub pool: Option<String>,
+    /// Stream the session's orderbooks and only push after its own swaps
+    pub session_id: Option<String>,
+    pub sort: Option<LevelSort>,
+    pub limit: Option<usize>,
+}
+
+impl OrderbookStreamQuery {
+    /// Whether `update` changed a book this stream is watching. Global streams
+    /// push after every swap and rebuild; session streams only after the
+    /// session's own swaps.
+    fn watches(&self, update: &PoolUpdate) -> bool {
+        match (&self.session_id, &update.session_id) {
+            (Some(watched), Some(updated)) => watched == updated,
+            (Some(_), None) => false,
+            (None, _) => true,
+        }
+    }
+}
+
+/// Re-read `pools` from the router VM into the global books and, when given,
+/// the session's books, so snapshots and streams reflect what a committed
+/// swap consumed. A pool that cannot be read keeps its previous levels.
+pub(crate) async fn refresh_books_from_vm(
+    state: &AppState,
+    session_id: Option<&str>,
+    pools: &[PoolId],
+) {
+    let Some(router) = state.router.as_ref() else {
+        return;
+    };
+    let session_arc = match session_id {
+        Some(sid) => state.session_manager.get_session(sid).await,
+        None => None,
+    };
+    for &pool_id in pools {
+        let levels = match router.book_levels(pool_id).await {
+            Ok(levels) => levels,
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to refresh {} orderbook from the VM: {}",
+                    pool_id.display_name(),
+                    e
+                );
+                continue;
+            }
+        };
+        if let Some(ob) = state.orderbooks.write().await.get_mut(&pool_id) {
+            ob.apply_vm_levels(&levels);
+        }
+        if let Some(session_arc) = &session_arc {
+            if let Some(ob) = session_arc.write().await.orderbooks.get_mut(&pool_id) {
+                ob.apply_vm_levels(&levels);
+            }
+        }
+    }
+}
+
+/// GET /api/orderbook/ws - Push an orderbook snapshot whenever a swap changes the pool
+///
+/// Sends one message per watched pool on connect, then another each time a
+/// swap (or a global rebuild) touches that pool. Messages have the same shape
+/// as `/api/orderbook` responses, without `stats`.
+pub async fn orderbook_ws(
+    State(state): State<AppState>,
+    Query(query): Query<OrderbookStreamQuery>,
+    ws: WebSocketUpgrade,
+) -> ApiResult<Response> {
+    let pools = match query.pool.as_deref() {
+        Some(pool) => vec![PoolId::from_str(pool)
+            .ok_or_else(|| ApiError::BadRequest(invalid_pool_message(pool, false)))?],
+        None => state.pool_registry.read().await.expected_pools(),
+    };
+    if let Some(ref sid) = query.session_id {
+        if state.session_manager.get_session(sid).await.is_none() {
+            return Err(ApiError::NotFound(format!("Session not found: {}", sid)));
+        }
+    }
+
+    // Subscribe before upgrading so no swap between the handshake and the
+    // first snapshot goes unnoticed.
+    let updates = state.session_manager.subscribe_pool_updates();
+    Ok(ws.on_upgrade(move |socket| stream_orderbooks(socket, state, query, pools, updates)))
+}
+
+/// Drive one orderbook stream until the client disconnects or a send fails.
+async fn stream_orderbooks(
+    mut socket: WebSocket,
+    state: AppState,
+    query: OrderbookStreamQuery,
+    pools: Vec<PoolId>,
+    mut updates: Receiver<PoolUpdate>,
+) {
+    if !send_orderbooks(&mut socket, &state, &query, &pools).await {
+        return;
+    }
+    loop {
+        tokio::select! {
+            update = updates.recv() => {
+                let changed: Vec<PoolId> = match update {
+                    Ok(update) if query.watches(&update) => update
+                        .pools
+                        .into_iter()
+                        .filter(|pool_id| pools.contains(pool_id))
+                        .collect(),
+                    Ok(_) => continue,
+                    // Missed updates may have touched any pool; resend them all.
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::debug!("Orderbook stream lagged by {} updates", skipped);
+                        pools.clone()
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                if !send_orderbooks(&mut socket, &state, &query, &changed).await {
+                    break;
+                }
+            }
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // Pings are answered by axum; anything else from the client is ignored.
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+/// Send the current snapshot of each pool; false once the socket is gone.
+async fn send_orderbooks(
+    socket: &mut WebSocket,
+    state: &AppState,
+    query: &OrderbookStreamQuery,
+    pools: &[PoolId],
+) -> bool {
+    for &pool_id in pools {
+        let message = orderbook_message(state, query, pool_id).await;
+        let json = match serde_json::to_string(&message) {
+            Ok(json) => json,
+            Err(e) => {
+                tracing::warn!("Failed to serialize orderbook stream message: {}", e);
+                continue;
+            }
+        };
+        if socket.send(Message::Text(json)).await.is_err() {
+            return false;
+        }
+    }
+    true
+}
+
+async fn orderbook_message(
+    state: &AppState,
+    query: &OrderbookStreamQuery,
+    pool_id: PoolId,
+) -> OrderbookResponse {
+    let to_response = |ob: Option<&SandboxOrderbook>, source: &str| match ob {
+        Some(ob) => OrderbookResponse {
+            success: true,
+            error: None,
+            provenance: Some(OrderbookProvenance::from_orderbook(ob, source)),
+            orderbook: Some(sandbox_orderbook_to_snapshot(ob, query.sort, query.limit)),
+            stats: None,
+        },
+        None => OrderbookResponse {
+            success: false,
+            error: Some(format!(
+                "Pool '{}' orderbook not built",
+                pool_id.display_name()
+            )),
+            provenance: None,
+            orderbook: None,
+            stats: None,
+        },
+    };
+
+    let session_arc = match query.session_id {
+        Some(ref sid) => state.session_manager.get_session(sid).await,
+        None => None,
+    };
+    match session_arc {
+        Some(session_arc) => {
+            let session = session_arc.read().await;
+            to_response(session.orderbooks.get(&pool_id), "session")
+        }
+        None => {
+            let orderbooks = state.orderbooks.read().await;
+            to_response(orderbooks.get(&pool_id), "global")
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PoolVolumesQuery {
+    /// Epoch to read (defaults to the pool's current history epoch)
+    pub epoch: Option<u64>,
+}
+
+/// GET /api/pools/:id/volumes - Decoded `history::Volumes` for an epoch
+pub async fn get_pool_volumes(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<PoolVolumesQuery>,
+) -> ApiResult<Json<RouterEpochVolumes>> {
+    let pool_id =
+        PoolId::from_str(&id).ok_or_else(|| ApiError::BadRequest(format!("Invalid pool: {}", id)))?;
+    let router = state
+        .router
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("MoveVM router is not initialized".into()))?;
+
+    let volumes = router
+        .read_epoch_volumes(pool_id, query.epoch)
+        .await
+        .map_err(router_error(|e| {
+            ApiError::BadRequest(format!("Failed to read pool volumes: {}", e))
+        }))?;
+    Ok(Json(volumes))
+}
+
+/// GET /api/pools/:id/state - Key `PoolInner` state decoded from the VM
+pub async fn get_pool_state(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<RouterPoolState>> {
+    let pool_id = PoolId::from_str(&id)
+        .ok_or_else(|| ApiError::BadRequest(format!("Invalid pool: {}", id)))?;
+    let router = state
+        .router
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("MoveVM router is not initialized".into()))?;
+
+    let pool_state = router
+        .read_pool_state(pool_id)
+        .await
+        .map_err(router_error(|e| {
+            ApiError::BadRequest(format!("Failed to read pool state: {}", e))
+        }))?;
+    Ok(Json(pool_state))
+}
+
+/// GET /api/pools/:id/deep-price - Current `OrderDeepPrice` used to convert
+/// fees into DEEP
+pub async fn get_pool_deep_price(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<PoolDeepPrice>> {
+    let pool_id = PoolId::from_str(&id)
+        .ok_or_else(|| ApiError::BadRequest(format!("Invalid pool: {}", id)))?;
+    let router = state
+        .router
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("MoveVM router is not initialized".into()))?;
+
+    let deep_price = router
+        .read_deep_price(pool_id)
+        .await
+        .map_err(router_error(|e| {
+            ApiError::BadRequest(format!("Failed to read deep price: {}", e))
+        }))?;
+    Ok(Json(deep_price))
 }
 
 /// GET /api/pools - List all available pools and their status
 pub async fn list_pools(State(state): State<AppState>) -> Json<PoolsListResponse> {
+    // Read params before taking the registry locks; the router caches them.
+    let expected = state.pool_registry.read().await.expected_pools();
+    let mut params = HashMap::new();
+    if let Some(router) = state.router.as_ref() {
+        for pool_id in &expected {
+            match router.pool_params(*pool_id).await {
+                Ok(pool_params) => {
+                    params.insert(*pool_id, pool_params);
+                }
+                Err(e) => tracing::debug!("No pool params for {}: {}", pool_id.display_name(), e),
+            }
+        }
+    }
+
     let registry = state.pool_registry.read().await;
     let orderbooks = state.orderbooks.read().await;
     let summary = registry.summary();
 
-    // Include all possible pools with their loaded status
-    let pools: Vec<PoolInfo> = PoolId::all()
+    // Include every expected pool with its loaded status
+    let pools: Vec<PoolInfo> = expected
         .iter()
         .map(|pool_id| {
             let loaded = registry.is_loaded(*pool_id);
@@ -196,6 +564,7 @@ pub async fn list_pools(State(state): State<AppState>) -> Json<PoolsListResponse
                 asks_slices: pool_summary.map(|p| p.asks_slices),
                 bids_slices: pool_summary.map(|p| p.bids_slices),
                 checkpoint: pool_summary.map(|p| p.checkpoint),
+                params: params.get(pool_id).copied(),
             }
         })
         .collect();
@@ -210,18 +579,18 @@ pub async fn list_pools(State(state): State<AppState>) -> Json<PoolsListResponse
 pub async fn get_depth(
     State(state): State<AppState>,
     Query(query): Query<OrderbookQuery>,
-) -> Json<BinanceDepthResponse> {
+    headers: HeaderMap,
+) -> Response {
     let pool_id = match PoolId::from_str(&query.pool) {
         Some(id) => id,
         None => {
             return Json(BinanceDepthResponse {
                 success: false,
-                error: Some(format!(
-                    "Invalid pool '{}'. Valid pools: sui_usdc, wal_usdc, deep_usdc",
-                    query.pool
-                )),
+                error: Some(invalid_pool_message(&query.pool, false)),
+                provenance: None,
                 data: None,
-            });
+            })
+            .into_response();
         }
     };
 
@@ -235,17 +604,351 @@ pub async fn get_depth(
                     "Pool '{}' orderbook not built",
                     pool_id.display_name()
                 )),
+                provenance: None,
                 data: None,
-            });
+            })
+            .into_response();
         }
     };
 
-    let depth = sandbox_orderbook_to_binance(ob);
-    Json(BinanceDepthResponse {
+    let etag = orderbook_etag(ob, "global", &query);
+    if let Some(not_modified) = not_modified(&headers, etag.as_deref()) {
+        return not_modified;
+    }
+
+    let depth = sandbox_orderbook_to_binance(ob, query.sort, query.limit);
+    with_etag(
+        Json(BinanceDepthResponse {
+            success: true,
+            error: None,
+            provenance: Some(OrderbookProvenance::from_orderbook(ob, "global")),
+            data: Some(depth),
+        }),
+        etag,
+    )
+}
+
+/// Query parameters for the aggregated depth endpoint
+#[derive(Debug, Deserialize)]
+pub struct AggregateDepthQuery {
+    /// Token whose depth is aggregated: the base coin of some pool
+    pub base: String,
+    /// Token the levels are priced in. Defaults to the quote coin of the
+    /// first pool trading `base`
+    pub quote: Option<String>,
+    /// Maximum number of merged levels returned per side
+    pub limit: Option<usize>,
+}
+
+/// One route contributing levels to an aggregated book
+#[derive(Debug, Serialize)]
+pub struct DepthSource {
+    /// "direct" or "two_hop"
+    pub route_type: &'static str,
+    pub pools: Vec<String>,
+    /// Mid of the second pool the first pool's levels are divided by;
+    /// omitted for direct routes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conversion_mid: Option<f64>,
+    pub bid_levels: usize,
+    pub ask_levels: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AggregateDepthResponse {
+    pub success: bool,
+    pub base: String,
+    pub quote: String,
+    pub sources: Vec<DepthSource>,
+    pub data: BinanceOrderbookExtended,
+}
+
+/// GET /api/orderbook/depth/aggregate - Binance-style depth merged across routes
+///
+/// Combines the levels of every direct and two-hop route from `base` to
+/// `quote`. A two-hop route (base -> shared quote coin -> quote) contributes
+/// the base pool's levels divided by the quote pool's mid, as in two-hop quotes,
+/// so no VM calls are made. Levels at the same price are summed.
+pub async fn get_aggregate_depth(
+    State(state): State<AppState>,
+    Query(query): Query<AggregateDepthQuery>,
+) -> ApiResult<Json<AggregateDepthResponse>> {
+    let pairs = swap::pool_pairs(&state).await;
+    let base = swap::normalize_token(&query.base, pairs.debug_symbol());
+    let Some(default_quote) = pairs.quote_for_base(&base) else {
+        return Err(ApiError::BadRequest(format!(
+            "base must be the base coin of a pool; {} depth is the inverse of a pool book",
+            base
+        )));
+    };
+    let quote = match query.quote.as_deref() {
+        Some(quote) => swap::normalize_token(quote, pairs.debug_symbol()),
+        None => default_quote,
+    };
+    if base == quote {
+        return Err(ApiError::BadRequest(
+            "base and quote must be different tokens".into(),
+        ));
+    }
+
+    let orderbooks = state.orderbooks.read().await;
+    let mut sources = Vec::new();
+    // Keyed by price in millionths of a quote unit so equal prices merge
+    let mut bids: BTreeMap<u64, f64> = BTreeMap::new();
+    let mut asks: BTreeMap<u64, f64> = BTreeMap::new();
+    let mut checkpoint = u64::MAX;
+    for path in swap::candidate_paths(&base, &quote, &pairs, &state.preferred_pools, 2) {
+        let (base_pool, conversion_mid) = match path.as_slice() {
+            [direct] if direct.is_sell_base => (direct.pool_id, None),
+            [first, second] if first.is_sell_base && !second.is_sell_base => {
+                let Some(second_mid) = orderbooks
+                    .get(&second.pool_id)
+                    .and_then(|ob| ob.mid_price())
+                else {
+                    continue;
+                };
+                (first.pool_id, Some(second_mid))
+            }
+            _ => continue,
+        };
+        let Some(ob) = orderbooks.get(&base_pool) else {
+            continue;
+        };
+
+        let price_div = ob.price_divisor_value();
+        let to_quote = |price: u64| {
+            let pool_price = price as f64 / price_div;
+            match conversion_mid {
+                Some(second_mid) => swap::two_hop_mid(pool_price, second_mid),
+                None => pool_price,
+            }
+        };
+        for (levels, side) in [(&ob.bids, &mut bids), (&ob.asks, &mut asks)] {
+            for level in levels {
+                let price = to_quote(level.price);
+                if price <= 0.0 {
+                    continue;
+                }
+                *side.entry((price * 1e6).round() as u64).or_default() +=
+                    units::to_human(level.total_quantity, ob.base_decimals);
+            }
+        }
+        checkpoint = checkpoint.min(ob.checkpoint);
+        sources.push(DepthSource {
+            route_type: if conversion_mid.is_some() {
+                "two_hop"
+            } else {
+                "direct"
+            },
+            pools: path
+                .iter()
+                .map(|hop| hop.pool_id.as_str().to_string())
+                .collect(),
+            conversion_mid,
+            bid_levels: ob.bids.len(),
+            ask_levels: ob.asks.len(),
+        });
+    }
+    if sources.is_empty() {
+        return Err(ApiError::NotFound(format!(
+            "No built orderbook route for {} -> {}",
+            base, quote
+        )));
+    }
+
+    let level = |(&price, &quantity): (&u64, &f64)| {
+        [
+            format!("{:.6}", price as f64 / 1e6),
+            format!("{:.4}", quantity),
+        ]
+    };
+    let limit = query.limit.unwrap_or(usize::MAX);
+    let best_bid = bids.keys().next_back().map(|&p| p as f64 / 1e6);
+    let best_ask = asks.keys().next().map(|&p| p as f64 / 1e6);
+    let (mid_price, spread_bps) = match (best_bid, best_ask) {
+        (Some(bid), Some(ask)) => {
+            let mid = (bid + ask) / 2.0;
+            (Some(mid), Some(((ask - bid).abs() / mid * 10_000.0) as u64))
+        }
+        _ => (None, None),
+    };
+    let data = BinanceOrderbookExtended {
+        orderbook: BinanceOrderbook {
+            symbol: format!("{}{}", base, quote),
+            last_update_id: checkpoint,
+            bids: bids.iter().rev().take(limit).map(level).collect(),
+            asks: asks.iter().take(limit).map(level).collect(),
+        },
+        mid_price: mid_price.map(|p| format!("{:.6}", p)),
+        best_bid: best_bid.map(|p| format!("{:.6}", p)),
+        best_ask: best_ask.map(|p| format!("{:.6}", p)),
+        spread_bps,
+        total_bid_depth: format!("{:.4}", bids.values().sum::<f64>()),
+        total_ask_depth: format!("{:.4}", asks.values().sum::<f64>()),
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0),
+    };
+
+    Ok(Json(AggregateDepthResponse {
         success: true,
-        error: None,
-        data: Some(depth),
-    })
+        base,
+        quote,
+        sources,
+        data,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TradesQuery {
+    #[serde(default = "default_pool")]
+    pub pool: String,
+    /// Maximum number of trades returned (defaults to the tape capacity)
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TradesResponse {
+    pub pool: String,
+    /// Newest first
+    pub trades: Vec<Trade>,
+    /// Trades retained per pool (`TRADE_TAPE_CAPACITY`)
+    pub capacity: usize,
+}
+
+/// GET /api/orderbook/trades - Recent fills decoded from executed swap events
+pub async fn get_trades(
+    State(state): State<AppState>,
+    Query(query): Query<TradesQuery>,
+) -> ApiResult<Json<TradesResponse>> {
+    let pool_id = PoolId::from_str(&query.pool)
+        .ok_or_else(|| ApiError::BadRequest(format!("Invalid pool: {}", query.pool)))?;
+    let tape = state.trade_tape.read().await;
+    let limit = query.limit.unwrap_or_else(|| tape.capacity());
+
+    Ok(Json(TradesResponse {
+        pool: pool_id.as_str().to_string(),
+        trades: tape.recent(pool_id, limit),
+        capacity: tape.capacity(),
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OrdersQuery {
+    #[serde(default = "default_pool")]
+    pub pool: String,
+    /// "bids" (default) or "asks"
+    pub side: Option<String>,
+    pub limit: Option<u64>,
+    /// `next_cursor` of the previous page (the last order id it returned)
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrdersResponse {
+    pub success: bool,
+    pub pool: String,
+    pub side: &'static str,
+    pub limit: u64,
+    pub order_count: usize,
+    pub orders: Vec<IterOrderEntry>,
+    pub has_next_page: bool,
+    /// Pass as `cursor` to fetch the next page; omitted on the last page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// GET /api/orderbook/orders - Resting orders decoded from the VM pool, one page at a time
+///
+/// Reads the router's live pool via `order_query::iter_orders`, so the page
+/// reflects executed swaps and placed or cancelled limit orders.
+pub async fn get_orders(
+    State(state): State<AppState>,
+    Query(query): Query<OrdersQuery>,
+) -> ApiResult<Json<OrdersResponse>> {
+    let pool_id = PoolId::from_str(&query.pool)
+        .ok_or_else(|| ApiError::BadRequest(format!("Invalid pool: {}", query.pool)))?;
+    let bids = match query.side.as_deref().map(str::trim) {
+        None | Some("bids") => true,
+        Some("asks") => false,
+        Some(other) => {
+            return Err(ApiError::BadRequest(format!(
+                "Invalid side '{}': expected bids or asks",
+                other
+            )));
+        }
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_ITER_ORDERS_LIMIT);
+    if limit == 0 || limit > MAX_ITER_ORDERS_LIMIT {
+        return Err(ApiError::BadRequest(format!(
+            "limit must be between 1 and {}",
+            MAX_ITER_ORDERS_LIMIT
+        )));
+    }
+    let cursor = query
+        .cursor
+        .as_deref()
+        .map(|id| {
+            id.trim().parse::<u128>().map_err(|_| {
+                ApiError::BadRequest(format!("Invalid cursor '{}': expected a u128 order id", id))
+            })
+        })
+        .transpose()?;
+
+    let (base_decimals, quote_decimals) = if pool_id.is_debug() {
+        let debug = state.debug_pool.read().await;
+        let info = debug
+            .pools
+            .iter()
+            .find(|info| info.pool_id == pool_id)
+            .ok_or_else(|| {
+                ApiError::NotFound(
+                    "Debug pool has not been created; POST /api/debug/pool first".into(),
+                )
+            })?;
+        (info.config.token_decimals, units::USDC_DECIMALS)
+    } else {
+        let config = state.pool_registry.read().await.config(pool_id);
+        (config.base_decimals, config.quote_decimals)
+    };
+    let router = state
+        .router
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("MoveVM router is not initialized".into()))?;
+
+    let page = router
+        .iter_orders(pool_id, bids, limit, cursor)
+        .await
+        .map_err(router_error(|e| {
+            ApiError::Internal(format!(
+                "Failed to iterate {} orders: {}",
+                pool_id.display_name(),
+                e
+            ))
+        }))?;
+
+    let next_cursor = page
+        .orders
+        .last()
+        .filter(|_| page.has_next_page)
+        .map(|order| order.order_id.clone());
+    let orders: Vec<IterOrderEntry> = page
+        .orders
+        .into_iter()
+        .map(|order| IterOrderEntry::new(order, base_decimals, quote_decimals))
+        .collect();
+
+    Ok(Json(OrdersResponse {
+        success: true,
+        pool: pool_id.as_str().to_string(),
+        side: if bids { "bids" } else { "asks" },
+        limit,
+        order_count: orders.len(),
+        orders,
+        has_next_page: page.has_next_page,
+        next_cursor,
+    }))
 }
 
 /// GET /api/orderbook/stats - Get loaded state statistics
@@ -264,6 +967,19 @@ pub async fn get_stats(
         }
     };
 
+    let skipped_filled_orders = skipped_filled_orders(&state, pool_id).await;
+    let checkpoint = state
+        .orderbooks
+        .read()
+        .await
+        .get(&pool_id)
+        .map(|ob| ob.checkpoint);
+    let checkpoints_behind = match checkpoint {
+        Some(checkpoint) => mainnet_checkpoint_height()
+            .await
+            .map(|head| head.saturating_sub(checkpoint)),
+        None => None,
+    };
     let registry = state.pool_registry.read().await;
 
     let loader = match registry.get(pool_id) {
@@ -295,23 +1011,310 @@ pub async fn get_stats(
             bids_slices: stats.bids_slices,
             max_checkpoint: stats.max_checkpoint,
             max_version: stats.max_version,
+            duplicate_objects: stats.duplicate_objects,
+            skipped_filled_orders,
+            checkpoint,
+            checkpoints_behind,
         }),
     })
 }
 
+/// Count of fully-filled orders skipped while building the global orderbook
+/// Whether orderbook responses carry an `ETag` and honor `If-None-Match`.
+///
+/// Controlled by `ORDERBOOK_ETAG` (default: enabled; `0`/`false`/`off` disables).
+/// Outcome of rebuilding one pool's global orderbook
+#[derive(Debug, Serialize)]
+pub struct PoolRebuildResult {
+    pub pool_id: String,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bid_levels: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ask_levels: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mid_price: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<OrderbookProvenance>,
+    pub build_time_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RebuildAllResponse {
+    /// True when every pool rebuilt; failed pools keep their previous book
+    pub success: bool,
+    pub rebuilt: usize,
+    pub failed: usize,
+    pub results: Vec<PoolRebuildResult>,
+}
+
+/// POST /api/orderbook/rebuild-all - Rebuild every global orderbook from its state file
+///
+/// Each loaded pool rebuilds from the file it was loaded from at startup (its
+/// `POOLS_MANIFEST` entry, or the built-in checkpoint 240M file). Pools build
+/// in parallel blocking tasks; the successful books replace the global map in
+/// one write, and new sessions clone the refreshed set. Existing sessions
+/// keep their own copies until reset.
+pub async fn rebuild_all_orderbooks(State(state): State<AppState>) -> Json<RebuildAllResponse> {
+    let files = {
+        let registry = state.pool_registry.read().await;
+        registry
+            .loaded_pools()
+            .into_iter()
+            .filter_map(|pool_id| {
+                registry
+                    .state_file(pool_id)
+                    .map(std::path::Path::to_path_buf)
+                    .or_else(|| pool_id.state_file().map(PathBuf::from))
+                    .map(|path| (pool_id, path))
+            })
+            .collect()
+    };
+    Json(rebuild_orderbooks(&state, files).await)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReloadOrderbooksRequest {
+    /// State file (JSONL) per pool, keyed by pool id (e.g. `sui_usdc`)
+    pub pools: HashMap<String, String>,
+}
+
+/// POST /api/orderbook/reload - Rebuild the given pools' global orderbooks from new state files
+///
+/// Each file must load and build before it replaces the pool's book and
+/// registry state; a pool whose file fails keeps its previous book. The
+/// MoveVM router keeps the state it loaded at startup.
+pub async fn reload_orderbooks(
+    State(state): State<AppState>,
+    Json(req): Json<ReloadOrderbooksRequest>,
+) -> ApiResult<Json<RebuildAllResponse>> {
+    if req.pools.is_empty() {
+        return Err(ApiError::BadRequest(
+            "pools must name at least one pool".into(),
+        ));
+    }
+    let mut files = Vec::with_capacity(req.pools.len());
+    for (pool, path) in req.pools {
+        let pool_id = PoolId::from_str(&pool)
+            .filter(|pool_id| !pool_id.is_debug())
+            .ok_or_else(|| ApiError::BadRequest(format!("Invalid pool: {}", pool)))?;
+        let path = PathBuf::from(path);
+        if !path.is_file() {
+            return Err(ApiError::BadRequest(format!(
+                "State file not found for {}: {}",
+                pool_id.as_str(),
+                path.display()
+            )));
+        }
+        files.push((pool_id, path));
+    }
+    Ok(Json(rebuild_orderbooks(&state, files).await))
+}
+
+/// Build each `(pool, state file)` in a parallel blocking task and swap the
+/// successful books and pool states in with one write.
+async fn rebuild_orderbooks(state: &AppState, files: Vec<(PoolId, PathBuf)>) -> RebuildAllResponse {
+    let tasks: Vec<_> = files
+        .into_iter()
+        .map(|(pool_id, path)| {
+            let task = tokio::task::spawn_blocking(move || {
+                let started = Instant::now();
+                let result = build_orderbook_and_loader_from_file(pool_id, &path);
+                (result, started.elapsed().as_millis() as u64)
+            });
+            (pool_id, task)
+        })
+        .collect();
+
+    let mut rebuilt = Vec::new();
+    let mut results = Vec::new();
+    for (pool_id, task) in tasks {
+        let (result, build_time_ms) = match task.await {
+            Ok(outcome) => outcome,
+            Err(e) => (Err(anyhow::anyhow!("build task panicked: {}", e)), 0),
+        };
+        match result {
+            Ok((ob, loader)) => {
+                results.push(PoolRebuildResult {
+                    pool_id: pool_id.as_str().to_string(),
+                    success: true,
+                    error: None,
+                    bid_levels: Some(ob.bids.len()),
+                    ask_levels: Some(ob.asks.len()),
+                    mid_price: ob.mid_price(),
+                    provenance: Some(OrderbookProvenance::from_orderbook(&ob, "global")),
+                    build_time_ms,
+                });
+                rebuilt.push((pool_id, ob, loader));
+            }
+            Err(e) => {
+                tracing::error!(
+                    "Failed to rebuild {} orderbook: {}",
+                    pool_id.display_name(),
+                    e
+                );
+                results.push(PoolRebuildResult {
+                    pool_id: pool_id.as_str().to_string(),
+                    success: false,
+                    error: Some(e.to_string()),
+                    bid_levels: None,
+                    ask_levels: None,
+                    mid_price: None,
+                    provenance: None,
+                    build_time_ms,
+                });
+            }
+        }
+    }
+
+    let rebuilt_count = rebuilt.len();
+    if rebuilt_count > 0 {
+        let rebuilt_pools: Vec<PoolId> = rebuilt.iter().map(|(pool_id, _, _)| *pool_id).collect();
+        let snapshot = {
+            let mut registry = state.pool_registry.write().await;
+            let mut orderbooks = state.orderbooks.write().await;
+            for (pool_id, ob, loader) in rebuilt {
+                registry.insert_pool(pool_id, loader);
+                orderbooks.insert(pool_id, ob);
+            }
+            orderbooks.clone()
+        };
+        state.session_manager.set_global_orderbooks(snapshot).await;
+        state
+            .session_manager
+            .notify_pool_update(None, &rebuilt_pools);
+    }
+
+    let failed = results.len() - rebuilt_count;
+    RebuildAllResponse {
+        success: failed == 0,
+        rebuilt: rebuilt_count,
+        failed,
+        results,
+    }
+}
+
+/// Latest mainnet checkpoint height, cached for [`MAINNET_HEAD_TTL`].
+///
+/// `None` (with a warning) when the gRPC endpoint cannot be reached.
+async fn mainnet_checkpoint_height() -> Option<u64> {
+    static CACHE: OnceLock<Mutex<Option<(Instant, u64)>>> = OnceLock::new();
+    let cache = CACHE.get_or_init(|| Mutex::new(None));
+    if let Some((fetched_at, height)) = *cache.lock().unwrap() {
+        if fetched_at.elapsed() < MAINNET_HEAD_TTL {
+            return Some(height);
+        }
+    }
+
+    // The gRPC client runs on its own runtime, like the orderbook builder.
+    let fetched = tokio::task::spawn_blocking(|| -> anyhow::Result<u64> {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(async {
+            let grpc = sui_transport::grpc::GrpcClient::mainnet().await?;
+            Ok(grpc.get_service_info().await?.checkpoint_height)
+        })
+    })
+    .await;
+    match fetched {
+        Ok(Ok(height)) => {
+            *cache.lock().unwrap() = Some((Instant::now(), height));
+            Some(height)
+        }
+        Ok(Err(e)) => {
+            tracing::warn!("Failed to read mainnet checkpoint height: {}", e);
+            None
+        }
+        Err(e) => {
+            tracing::warn!("Mainnet checkpoint height task failed: {}", e);
+            None
+        }
+    }
+}
+
+fn etag_enabled() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    *ENABLED.get_or_init(|| {
+        std::env::var("ORDERBOOK_ETAG")
+            .map(|v| {
+                !matches!(
+                    v.trim().to_ascii_lowercase().as_str(),
+                    "0" | "false" | "off" | "no"
+                )
+            })
+            .unwrap_or(true)
+    })
+}
+
+/// Strong ETag for an orderbook view: book content plus the query shaping it.
+fn orderbook_etag(ob: &SandboxOrderbook, source: &str, query: &OrderbookQuery) -> Option<String> {
+    if !etag_enabled() {
+        return None;
+    }
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    ob.content_hash().hash(&mut hasher);
+    source.hash(&mut hasher);
+    query.sort.hash(&mut hasher);
+    query.limit.hash(&mut hasher);
+    Some(format!("\"{:016x}\"", hasher.finish()))
+}
+
+/// 304 response when the client's `If-None-Match` matches `etag`.
+fn not_modified(headers: &HeaderMap, etag: Option<&str>) -> Option<Response> {
+    let etag = etag?;
+    let if_none_match = headers.get(header::IF_NONE_MATCH)?.to_str().ok()?;
+    // Weak comparison per RFC 9110: ignore any W/ prefix
+    let matches = if_none_match.split(',').map(str::trim).any(|candidate| {
+        candidate == "*" || candidate.trim_start_matches("W/") == etag
+    });
+    if !matches {
+        return None;
+    }
+    let mut response = StatusCode::NOT_MODIFIED.into_response();
+    if let Ok(value) = HeaderValue::from_str(etag) {
+        response.headers_mut().insert(header::ETAG, value);
+    }
+    Some(response)
+}
+
+fn with_etag(body: impl IntoResponse, etag: Option<String>) -> Response {
+    let mut response = body.into_response();
+    if let Some(value) = etag.and_then(|e| HeaderValue::from_str(&e).ok()) {
+        response.headers_mut().insert(header::ETAG, value);
+    }
+    response
+}
+
+async fn skipped_filled_orders(state: &AppState, pool_id: PoolId) -> usize {
+    state
+        .orderbooks
+        .read()
+        .await
+        .get(&pool_id)
+        .map(|ob| ob.skipped_filled_orders)
+        .unwrap_or(0)
+}
+
 // --- Conversion helpers: SandboxOrderbook -> API response types ---
 
 /// Convert a MoveVM-built SandboxOrderbook to an OrderbookSnapshot for the API
-fn sandbox_orderbook_to_snapshot(ob: &SandboxOrderbook) -> OrderbookSnapshot {
+///
+/// `sort`/`limit` only affect the returned levels; best bid/ask and spread
+/// are always taken from the full book.
+fn sandbox_orderbook_to_snapshot(
+    ob: &SandboxOrderbook,
+    sort: Option<LevelSort>,
+    limit: Option<usize>,
+) -> OrderbookSnapshot {
     let price_div = ob.price_divisor_value();
-    let base_scale = 10f64.powi(ob.base_decimals as i32);
 
     let bids: Vec<OrderbookLevel> = ob
-        .bids
+        .bids_view(sort, limit)
         .iter()
         .map(|l| {
             let price = l.price as f64 / price_div;
-            let quantity = l.total_quantity as f64 / base_scale;
+            let quantity = units::to_human(l.total_quantity, ob.base_decimals);
             OrderbookLevel {
                 price,
                 quantity,
@@ -322,11 +1325,11 @@ fn sandbox_orderbook_to_snapshot(ob: &SandboxOrderbook) -> OrderbookSnapshot {
         .collect();
 
     let asks: Vec<OrderbookLevel> = ob
-        .asks
+        .asks_view(sort, limit)
         .iter()
         .map(|l| {
             let price = l.price as f64 / price_div;
-            let quantity = l.total_quantity as f64 / base_scale;
+            let quantity = units::to_human(l.total_quantity, ob.base_decimals);
             OrderbookLevel {
                 price,
                 quantity,
@@ -336,8 +1339,8 @@ fn sandbox_orderbook_to_snapshot(ob: &SandboxOrderbook) -> OrderbookSnapshot {
         })
         .collect();
 
-    let best_bid = bids.first().map(|l| l.price);
-    let best_ask = asks.first().map(|l| l.price);
+    let best_bid = ob.best_bid();
+    let best_ask = ob.best_ask();
     let mid_price = match (best_bid, best_ask) {
         (Some(bid), Some(ask)) => Some((bid + ask) / 2.0),
         (Some(bid), None) => Some(bid),
@@ -349,23 +1352,17 @@ fn sandbox_orderbook_to_snapshot(ob: &SandboxOrderbook) -> OrderbookSnapshot {
         _ => None,
     };
 
-    let base_symbol = match ob.pool_id {
-        PoolId::SuiUsdc => "SUI",
-        PoolId::DeepUsdc => "DEEP",
-        PoolId::WalUsdc => "WAL",
-        PoolId::DebugUsdc => "DBG",
-    };
-
     OrderbookSnapshot {
         pool_id: ob.pool_id.as_str().to_string(),
-        base_symbol: base_symbol.to_string(),
-        quote_symbol: "USDC".to_string(),
+        base_symbol: ob.base_symbol.clone(),
+        quote_symbol: ob.quote_symbol.clone(),
         mid_price,
         best_bid,
         best_ask,
         spread_bps,
         bids,
         asks,
+        checkpoint: ob.checkpoint,
         timestamp: std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .map(|d| d.as_secs())
@@ -374,34 +1371,33 @@ fn sandbox_orderbook_to_snapshot(ob: &SandboxOrderbook) -> OrderbookSnapshot {
 }
 
 /// Convert a MoveVM-built SandboxOrderbook to Binance-style format
-fn sandbox_orderbook_to_binance(ob: &SandboxOrderbook) -> BinanceOrderbookExtended {
+///
+/// Depth totals always cover the full book, regardless of `sort`/`limit`.
+fn sandbox_orderbook_to_binance(
+    ob: &SandboxOrderbook,
+    sort: Option<LevelSort>,
+    limit: Option<usize>,
+) -> BinanceOrderbookExtended {
     let price_div = ob.price_divisor_value();
-    let base_scale = 10f64.powi(ob.base_decimals as i32);
 
-    let base_symbol = match ob.pool_id {
-        PoolId::SuiUsdc => "SUI",
-        PoolId::DeepUsdc => "DEEP",
-        PoolId::WalUsdc => "WAL",
-        PoolId::DebugUsdc => "DBG",
-    };
-    let symbol = format!("{}USDC", base_symbol);
+    let symbol = format!("{}{}", ob.base_symbol, ob.quote_symbol);
 
     let bids: Vec<[String; 2]> = ob
-        .bids
+        .bids_view(sort, limit)
         .iter()
         .map(|l| {
             let price = l.price as f64 / price_div;
-            let quantity = l.total_quantity as f64 / base_scale;
+            let quantity = units::to_human(l.total_quantity, ob.base_decimals);
             [format!("{:.6}", price), format!("{:.4}", quantity)]
         })
         .collect();
 
     let asks: Vec<[String; 2]> = ob
-        .asks
+        .asks_view(sort, limit)
         .iter()
         .map(|l| {
             let price = l.price as f64 / price_div;
-            let quantity = l.total_quantity as f64 / base_scale;
+            let quantity = units::to_human(l.total_quantity, ob.base_decimals);
             [format!("{:.6}", price), format!("{:.4}", quantity)]
         })
         .collect();
@@ -409,12 +1405,12 @@ fn sandbox_orderbook_to_binance(ob: &SandboxOrderbook) -> BinanceOrderbookExtend
     let bid_depth: f64 = ob
         .bids
         .iter()
-        .map(|l| l.total_quantity as f64 / base_scale)
+        .map(|l| units::to_human(l.total_quantity, ob.base_decimals))
         .sum();
     let ask_depth: f64 = ob
         .asks
         .iter()
-        .map(|l| l.total_quantity as f64 / base_scale)
+        .map(|l| units::to_human(l.total_quantity, ob.base_decimals))
         .sum();
 
     let best_bid = ob.best_bid();
@@ -452,6 +1448,8 @@ pub struct OrderbookResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<OrderbookProvenance>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub orderbook: Option<OrderbookSnapshot>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stats: Option<StatsResponse>,
@@ -483,6 +1481,10 @@ pub struct PoolInfo {
     pub bids_slices: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub checkpoint: Option<u64>,
+    /// tick/lot/min size, whitelist status and taker/maker fees from the VM;
+    /// omitted when the router cannot read the pool
+    #[serde(flatten)]
+    pub params: Option<PoolParams>,
 }
 
 #[derive(Debug, Serialize)]
@@ -492,6 +1494,15 @@ pub struct StatsResponse {
     pub bids_slices: usize,
     pub max_checkpoint: u64,
     pub max_version: u64,
+    pub duplicate_objects: usize,
+    /// Fully-filled orders excluded from the MoveVM-built orderbook
+    pub skipped_filled_orders: usize,
+    /// Source checkpoint of the global orderbook (`/orderbook/stats` only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checkpoint: Option<u64>,
+    /// Mainnet checkpoint height minus `checkpoint`; omitted when mainnet is unreachable
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checkpoints_behind: Option<u64>,
 }
 
 #[derive(Debug, Serialize)]
@@ -508,5 +1519,7 @@ pub struct BinanceDepthResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<OrderbookProvenance>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub data: Option<BinanceOrderbookExtended>,
 }
@@ -1,15 +1,22 @@
 //! Session management endpoints
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
     Json,
 };
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use crate::api::AppState;
-use crate::sandbox::swap_executor::{SwapResult, UserBalances};
+use crate::api::balance::coin_type_for_symbol;
+use crate::api::debug::{sync_debug_state, IterOrderEntry};
+use crate::api::{router_error, AppState};
+use crate::sandbox::router::{DebugPoolCreateConfig, RouterAccountSnapshot, RouterHandle};
+use crate::sandbox::state_loader::PoolId;
+use crate::sandbox::swap_executor::{ReserveDraw, SwapResult, UserBalances};
 use crate::types::{ApiError, ApiResult};
+use crate::units;
 
 #[derive(Debug, Serialize)]
 pub struct SessionResponse {
@@ -18,6 +25,30 @@ pub struct SessionResponse {
     pub expires_at: u64,
     pub checkpoint: u64,
     pub balances: BalanceInfo,
+    /// Per-token drawdown of the shared VM reserves by this session
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub reserve_usage: Vec<ReserveUsageInfo>,
+}
+
+/// Aggregate reserve usage (all sessions) at which `near_depletion` is set
+const RESERVE_DEPLETION_WARN_BPS: u32 = 9_000;
+
+#[derive(Debug, Serialize)]
+pub struct ReserveUsageInfo {
+    pub token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coin_type: Option<String>,
+    pub drawn_by_faucet: String,
+    pub drawn_by_swaps: String,
+    pub drawn_total: String,
+    pub drawn_total_human: f64,
+    /// Live reserve coin value, shared by every session
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reserve_remaining: Option<String>,
+    /// Share of the bootstrapped reserve consumed by all sessions, in bps
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reserve_used_bps: Option<u32>,
+    pub near_depletion: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -38,13 +69,13 @@ impl From<&UserBalances> for BalanceInfo {
     fn from(b: &UserBalances) -> Self {
         Self {
             sui: b.sui.to_string(),
-            sui_human: b.sui as f64 / 1_000_000_000.0,
+            sui_human: units::to_human(b.sui, units::SUI_DECIMALS),
             usdc: b.usdc.to_string(),
-            usdc_human: b.usdc as f64 / 1_000_000.0,
+            usdc_human: units::to_human(b.usdc, units::USDC_DECIMALS),
             deep: b.deep.to_string(),
-            deep_human: b.deep as f64 / 1_000_000.0,
+            deep_human: units::to_human(b.deep, units::DEEP_DECIMALS),
             wal: b.wal.to_string(),
-            wal_human: b.wal as f64 / 1_000_000_000.0,
+            wal_human: units::to_human(b.wal, units::WAL_DECIMALS),
             custom: b
                 .custom
                 .iter()
@@ -54,8 +85,25 @@ impl From<&UserBalances> for BalanceInfo {
     }
 }
 
+/// Largest raw starting balance `POST /api/session` accepts per token
+///
+/// 10^18 leaves headroom for faucet and swap credits on top without
+/// overflowing u64.
+const MAX_INITIAL_BALANCE: u64 = 1_000_000_000_000_000_000;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CreateSessionRequest {
+    /// Raw starting balances keyed by token symbol (SUI, USDC, WAL, DEEP or
+    /// the debug token); omitted tokens start at zero
+    #[serde(default)]
+    pub initial_balances: HashMap<String, String>,
+}
+
 #[derive(Debug, Deserialize)]
-pub struct CreateSessionRequest {}
+pub struct HistoryQuery {
+    /// "json" (default) or "csv"
+    pub format: Option<String>,
+}
 
 #[derive(Debug, Serialize)]
 pub struct SwapHistoryResponse {
@@ -64,6 +112,46 @@ pub struct SwapHistoryResponse {
     pub history: Vec<SwapResult>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct AccountQuery {
+    /// Pool whose `state.accounts` table to read (defaults to sui_usdc)
+    pub pool: Option<String>,
+    /// Balance manager object id owning the account
+    pub balance_manager: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountResponse {
+    pub session_id: String,
+    pub account: RouterAccountSnapshot,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionOrderEntry {
+    pub pool: String,
+    #[serde(flatten)]
+    pub order: IterOrderEntry,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MissingOrder {
+    pub pool: String,
+    pub order_id: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionOrdersResponse {
+    pub session_id: String,
+    /// Omitted for sessions that never placed a limit order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub balance_manager_id: Option<String>,
+    pub order_count: usize,
+    pub orders: Vec<SessionOrderEntry>,
+    /// Listed as open in the account but not found on the book
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub missing_orders: Vec<MissingOrder>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct ResetResponse {
     pub success: bool,
@@ -72,14 +160,121 @@ pub struct ResetResponse {
     pub balances: BalanceInfo,
 }
 
+/// Layout version of `GET /api/session/:id/export` bundles
+const SESSION_EXPORT_VERSION: u32 = 1;
+
+/// Self-contained snapshot of a session, for sharing or reproducing it elsewhere
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionExport {
+    pub version: u32,
+    pub exported_at: u64,
+    pub source_session_id: String,
+    pub checkpoint: u64,
+    /// Raw balances; debug-token balances are under `custom`, keyed by symbol
+    pub balances: UserBalances,
+    #[serde(default)]
+    pub swap_history: Vec<SwapResult>,
+    /// Reserve drawdown on the exporting instance (informational on import)
+    #[serde(default)]
+    pub reserve_draws: HashMap<String, ReserveDraw>,
+    /// Pool state the session's orderbooks were built from
+    #[serde(default)]
+    pub pools: Vec<ExportedPool>,
+    /// Debug pool config, when the exporting instance had created it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debug_pool: Option<DebugPoolCreateConfig>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedPool {
+    pub pool: String,
+    pub checkpoint: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportSessionResponse {
+    pub success: bool,
+    pub session_id: String,
+    pub source_session_id: String,
+    pub balances: BalanceInfo,
+    pub swap_count: usize,
+    /// Differences between the bundle and this instance that may affect reproduction
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
 /// POST /api/session - Create a new sandbox session
+///
+/// `initial_balances` are minted through the VM faucet, like an import, and
+/// are what `POST /api/session/:id/reset` restores.
 pub async fn create_session(
     State(state): State<AppState>,
-    Json(_req): Json<Option<CreateSessionRequest>>,
+    Json(req): Json<Option<CreateSessionRequest>>,
 ) -> ApiResult<Json<SessionResponse>> {
+    let req = req.unwrap_or_default();
+    let (debug_symbol, debug_symbols, debug_created) = {
+        let debug = state.debug_pool.read().await;
+        (
+            debug.token_symbol.to_uppercase(),
+            debug.token_symbols(),
+            debug.created,
+        )
+    };
+    let mut mints: Vec<(String, String, u64)> = Vec::new();
+    for (token, raw) in &req.initial_balances {
+        let symbol = token.trim().to_uppercase();
+        let coin_type = coin_type_for_symbol(&symbol, &debug_symbols).ok_or_else(|| {
+            ApiError::BadRequest(format!(
+                "Unsupported initial balance token '{}'. Expected SUI, USDC, WAL, DEEP or {}",
+                token,
+                debug_symbols.join(", ")
+            ))
+        })?;
+        if symbol == debug_symbol && !debug_created {
+            return Err(ApiError::BadRequest(format!(
+                "Create the debug pool (POST /api/debug/pool) before funding {}",
+                debug_symbol
+            )));
+        }
+        let amount: u64 = raw.trim().parse().map_err(|_| {
+            ApiError::BadRequest(format!(
+                "Invalid initial balance for {}: '{}' is not a non-negative integer amount",
+                symbol, raw
+            ))
+        })?;
+        if amount > MAX_INITIAL_BALANCE {
+            return Err(ApiError::BadRequest(format!(
+                "Initial balance for {} exceeds the maximum of {}",
+                symbol, MAX_INITIAL_BALANCE
+            )));
+        }
+        if mints.iter().any(|(minted, _, _)| *minted == symbol) {
+            return Err(ApiError::BadRequest(format!(
+                "Initial balance for {} given more than once",
+                symbol
+            )));
+        }
+        if amount > 0 {
+            mints.push((symbol, coin_type, amount));
+        }
+    }
+
+    let mut initial_balances = UserBalances::initial();
+    if !mints.is_empty() {
+        let router = state.router.as_ref().ok_or_else(|| {
+            ApiError::ServiceUnavailable(
+                "MoveVM router is not initialized; initial balances cannot be minted".into(),
+            )
+        })?;
+        mint_balances(router, &mints, "minting the initial balance of").await?;
+        for (token, _, amount) in &mints {
+            initial_balances.add(token, *amount);
+        }
+    }
+
     let session_id = state
         .session_manager
-        .create_session()
+        .create_session_with_balances(initial_balances)
         .await
         .map_err(|e| ApiError::Internal(format!("Failed to create session: {}", e)))?;
 
@@ -89,7 +284,10 @@ pub async fn create_session(
         .await
         .ok_or_else(|| ApiError::Internal("Session creation failed".into()))?;
 
-    let session = session_arc.read().await;
+    let mut session = session_arc.write().await;
+    for (token, _, amount) in &mints {
+        session.record_faucet_draw(token, *amount);
+    }
 
     let now = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
@@ -102,9 +300,37 @@ pub async fn create_session(
         expires_at: now + 3600, // 1 hour TTL
         checkpoint: session.checkpoint,
         balances: BalanceInfo::from(&session.balances),
+        reserve_usage: Vec::new(),
     }))
 }
 
+/// Mint `(token, coin type, amount)` entries through the VM faucet, failing
+/// on the first mint that errors or comes back short.
+async fn mint_balances(
+    router: &RouterHandle,
+    mints: &[(String, String, u64)],
+    purpose: &str,
+) -> ApiResult<()> {
+    for (token, coin_type, amount) in mints {
+        let minted = router
+            .vm_faucet(coin_type.to_string(), *amount, None)
+            .await
+            .map_err(router_error(|e| {
+                ApiError::Internal(format!(
+                    "VM faucet failed while {} {}: {}",
+                    purpose, token, e
+                ))
+            }))?;
+        if minted.amount != *amount {
+            return Err(ApiError::Internal(format!(
+                "VM faucet amount mismatch for {}: requested {}, minted {}",
+                token, amount, minted.amount
+            )));
+        }
+    }
+    Ok(())
+}
+
 /// GET /api/session/:id - Get session info
 pub async fn get_session(
     State(state): State<AppState>,
@@ -116,7 +342,16 @@ pub async fn get_session(
         .await
         .ok_or_else(|| ApiError::NotFound(format!("Session not found: {}", id)))?;
 
-    let session = session_arc.read().await;
+    // Snapshot the session so the lock is not held across the reserve query.
+    let (elapsed_secs, checkpoint, balances, reserve_draws) = {
+        let session = session_arc.read().await;
+        (
+            session.created_at.elapsed().as_secs(),
+            session.checkpoint,
+            BalanceInfo::from(&session.balances),
+            session.reserve_draws.clone(),
+        )
+    };
 
     let now = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
@@ -124,7 +359,6 @@ pub async fn get_session(
         .as_secs();
 
     // Calculate elapsed time since session creation
-    let elapsed_secs = session.created_at.elapsed().as_secs();
     let created_at = now.saturating_sub(elapsed_secs);
     let expires_at = created_at + 3600; // 1 hour from creation
 
@@ -132,16 +366,76 @@ pub async fn get_session(
         session_id: id,
         created_at,
         expires_at,
-        checkpoint: session.checkpoint,
-        balances: BalanceInfo::from(&session.balances),
+        checkpoint,
+        balances,
+        reserve_usage: reserve_usage(&state, &reserve_draws).await,
     }))
 }
 
+/// Combine a session's reserve draws with live reserve coin values.
+///
+/// Reserve values are best effort: without a router, or if the query fails,
+/// only the session-side counters are reported.
+async fn reserve_usage(
+    state: &AppState,
+    draws: &HashMap<String, ReserveDraw>,
+) -> Vec<ReserveUsageInfo> {
+    if draws.is_empty() {
+        return Vec::new();
+    }
+    let reserves = match state.router.as_ref() {
+        Some(router) => router.reserve_status().await.unwrap_or_else(|e| {
+            tracing::warn!("Failed to query reserve status for session info: {}", e);
+            Vec::new()
+        }),
+        None => Vec::new(),
+    };
+    let debug_symbols = state.debug_pool.read().await.token_symbols();
+
+    let mut tokens: Vec<&String> = draws.keys().collect();
+    tokens.sort();
+    tokens
+        .into_iter()
+        .map(|token| {
+            let draw = draws[token];
+            let coin_type = coin_type_for_symbol(token, &debug_symbols);
+            let reserve = coin_type
+                .and_then(|ct| reserves.iter().find(|r| r.coin_type == ct))
+                .filter(|r| r.present);
+            let reserve_used_bps = reserve.and_then(|r| {
+                let initial = r.source_value.filter(|v| *v > 0)?;
+                let used = initial.saturating_sub(r.value?);
+                Some((used as u128 * units::BPS_DENOMINATOR as u128 / initial as u128) as u32)
+            });
+            let decimals = if debug_symbols.contains(token) {
+                units::DEBUG_TOKEN_DECIMALS
+            } else {
+                units::token_decimals(token).unwrap_or(units::DEFAULT_DECIMALS)
+            };
+            ReserveUsageInfo {
+                token: token.clone(),
+                coin_type: coin_type.map(str::to_string),
+                drawn_by_faucet: draw.faucet.to_string(),
+                drawn_by_swaps: draw.swaps.to_string(),
+                drawn_total: draw.total().to_string(),
+                drawn_total_human: units::to_human(draw.total(), decimals),
+                reserve_remaining: reserve.and_then(|r| r.value).map(|v| v.to_string()),
+                reserve_used_bps,
+                near_depletion: reserve_used_bps
+                    .is_some_and(|bps| bps >= RESERVE_DEPLETION_WARN_BPS),
+            }
+        })
+        .collect()
+}
+
 /// GET /api/session/:id/history - Get swap history for a session
+///
+/// `?format=csv` returns one CSV row per swap instead (see [`HISTORY_CSV_HEADER`]).
 pub async fn get_swap_history(
     State(state): State<AppState>,
     Path(id): Path<String>,
-) -> ApiResult<Json<SwapHistoryResponse>> {
+    Query(query): Query<HistoryQuery>,
+) -> ApiResult<Response> {
     let session_arc = state
         .session_manager
         .get_session(&id)
@@ -150,11 +444,87 @@ pub async fn get_swap_history(
 
     let session = session_arc.read().await;
 
-    Ok(Json(SwapHistoryResponse {
-        session_id: id,
-        swap_count: session.swap_history.len(),
-        history: session.swap_history.clone(),
-    }))
+    match query.format.as_deref() {
+        None | Some("json") => Ok(Json(SwapHistoryResponse {
+            session_id: id,
+            swap_count: session.swap_history.len(),
+            history: session.swap_history.clone(),
+        })
+        .into_response()),
+        Some("csv") => Ok((
+            [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"swap-history-{}.csv\"", id),
+                ),
+            ],
+            swap_history_csv(&session.swap_history),
+        )
+            .into_response()),
+        Some(other) => Err(ApiError::BadRequest(format!(
+            "Invalid format '{}'. Expected json or csv",
+            other
+        ))),
+    }
+}
+
+/// Column schema of `GET /api/session/:id/history?format=csv`.
+///
+/// Balances after each swap are flattened for the four listed tokens; any
+/// other (debug token) balances go in `custom_after` as a JSON object.
+const HISTORY_CSV_HEADER: &str = "timestamp_ms,route_type,input_token,input_amount,output_token,\
+output_amount,effective_price,gas_used,execution_time_ms,sui_after,usdc_after,deep_after,\
+wal_after,custom_after";
+
+fn swap_history_csv(history: &[SwapResult]) -> String {
+    let mut out = String::with_capacity(128 * (history.len() + 1));
+    out.push_str(HISTORY_CSV_HEADER);
+    out.push('\n');
+    for swap in history {
+        let balances = &swap.balances_after;
+        let custom_after = if balances.custom.is_empty() {
+            String::new()
+        } else {
+            let sorted: BTreeMap<&String, &u64> = balances.custom.iter().collect();
+            serde_json::to_string(&sorted).unwrap_or_default()
+        };
+        let row = [
+            swap.timestamp_ms.to_string(),
+            swap.route_type.clone(),
+            swap.input_token.clone(),
+            swap.input_amount.to_string(),
+            swap.output_token.clone(),
+            swap.output_amount.to_string(),
+            swap.effective_price.to_string(),
+            swap.gas_used.to_string(),
+            swap.execution_time_ms.to_string(),
+            balances.sui.to_string(),
+            balances.usdc.to_string(),
+            balances.deep.to_string(),
+            balances.wal.to_string(),
+            custom_after,
+        ];
+        for (idx, field) in row.iter().enumerate() {
+            if idx > 0 {
+                out.push(',');
+            }
+            push_csv_field(&mut out, field);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Append `field`, quoted (RFC 4180) when it holds a delimiter, quote or newline
+fn push_csv_field(out: &mut String, field: &str) {
+    if field.contains([',', '"', '\n', '\r']) {
+        out.push('"');
+        out.push_str(&field.replace('"', "\"\""));
+        out.push('"');
+    } else {
+        out.push_str(field);
+    }
 }
 
 /// POST /api/session/:id/reset - Reset session to initial state
@@ -180,3 +550,262 @@ pub async fn reset_session(
         balances: BalanceInfo::from(&session.balances),
     }))
 }
+
+/// GET /api/session/:id/account - Settled/owed balances and rebates for a balance manager
+///
+/// Sessions do not own a balance manager yet, so the manager id is passed explicitly.
+pub async fn get_account(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<AccountQuery>,
+) -> ApiResult<Json<AccountResponse>> {
+    state
+        .session_manager
+        .get_session(&id)
+        .await
+        .ok_or_else(|| ApiError::NotFound(format!("Session not found: {}", id)))?;
+
+    let pool_name = query.pool.as_deref().unwrap_or("sui_usdc");
+    let pool_id = PoolId::from_str(pool_name)
+        .ok_or_else(|| ApiError::BadRequest(format!("Invalid pool: {}", pool_name)))?;
+    let router = state
+        .router
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("MoveVM router is not initialized".into()))?;
+
+    let account = router
+        .read_account(pool_id, query.balance_manager)
+        .await
+        .map_err(router_error(|e| {
+            ApiError::BadRequest(format!("Failed to read account: {}", e))
+        }))?;
+
+    Ok(Json(AccountResponse {
+        session_id: id,
+        account,
+    }))
+}
+
+/// GET /api/session/:id/orders - Open orders of the session's balance manager
+///
+/// Reads each pool's `Account.open_orders` for the manager the session's
+/// limit orders created and looks every id up on the book. Sessions that
+/// never placed a limit order get an empty list.
+pub async fn get_session_orders(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<SessionOrdersResponse>> {
+    state
+        .session_manager
+        .get_session(&id)
+        .await
+        .ok_or_else(|| ApiError::NotFound(format!("Session not found: {}", id)))?;
+    let router = state
+        .router
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("MoveVM router is not initialized".into()))?;
+
+    let open_orders = router
+        .session_open_orders(id.clone())
+        .await
+        .map_err(router_error(|e| {
+            ApiError::Internal(format!("Failed to read open orders: {}", e))
+        }))?;
+
+    let debug_decimals = state.debug_pool.read().await.token_decimals;
+    let registry = state.pool_registry.read().await;
+    let orders: Vec<SessionOrderEntry> = open_orders
+        .orders
+        .into_iter()
+        .map(|(pool_id, order)| {
+            let config = registry.config(pool_id);
+            let base_decimals = if pool_id.is_debug() {
+                debug_decimals
+            } else {
+                config.base_decimals
+            };
+            SessionOrderEntry {
+                pool: pool_id.as_str().to_string(),
+                order: IterOrderEntry::new(order, base_decimals, config.quote_decimals),
+            }
+        })
+        .collect();
+    let missing_orders = open_orders
+        .missing
+        .into_iter()
+        .map(|(pool_id, order_id)| MissingOrder {
+            pool: pool_id.as_str().to_string(),
+            order_id: order_id.to_string(),
+        })
+        .collect();
+
+    Ok(Json(SessionOrdersResponse {
+        session_id: id,
+        balance_manager_id: open_orders.balance_manager_id,
+        order_count: orders.len(),
+        orders,
+        missing_orders,
+    }))
+}
+
+/// GET /api/session/:id/export - Export a session as a self-contained JSON bundle
+pub async fn export_session(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<SessionExport>> {
+    let session_arc = state
+        .session_manager
+        .get_session(&id)
+        .await
+        .ok_or_else(|| ApiError::NotFound(format!("Session not found: {}", id)))?;
+
+    let debug_pool = {
+        let debug = state.debug_pool.read().await;
+        debug.created.then(|| debug.config.clone())
+    };
+    let session = session_arc.read().await;
+    let mut pools: Vec<ExportedPool> = session
+        .orderbooks
+        .iter()
+        .map(|(pool_id, ob)| ExportedPool {
+            pool: pool_id.as_str().to_string(),
+            checkpoint: ob.checkpoint,
+        })
+        .collect();
+    pools.sort_by(|a, b| a.pool.cmp(&b.pool));
+
+    Ok(Json(SessionExport {
+        version: SESSION_EXPORT_VERSION,
+        exported_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+        source_session_id: id,
+        checkpoint: session.checkpoint,
+        balances: session.balances.clone(),
+        swap_history: session.swap_history.clone(),
+        reserve_draws: session.reserve_draws.clone(),
+        pools,
+        debug_pool,
+    }))
+}
+
+/// POST /api/session/import - Recreate a session from an export bundle
+///
+/// Balances are re-minted through the VM faucet so the reserves on this
+/// instance back them; history and checkpoint are copied verbatim.
+pub async fn import_session(
+    State(state): State<AppState>,
+    Json(bundle): Json<SessionExport>,
+) -> ApiResult<Json<ImportSessionResponse>> {
+    if bundle.version != SESSION_EXPORT_VERSION {
+        return Err(ApiError::BadRequest(format!(
+            "Unsupported session export version {} (expected {})",
+            bundle.version, SESSION_EXPORT_VERSION
+        )));
+    }
+    let router = state
+        .router
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("MoveVM router is not initialized".into()))?;
+
+    let mut warnings = Vec::new();
+    let custom: Vec<(String, u64)> = bundle
+        .balances
+        .custom
+        .iter()
+        .filter(|(_, amount)| **amount > 0)
+        .map(|(symbol, amount)| (symbol.to_uppercase(), *amount))
+        .collect();
+
+    // Debug-token balances need this instance's debug pool, created from the
+    // bundle's config when it does not exist yet.
+    let debug_created = state.debug_pool.read().await.created;
+    if !custom.is_empty() && !debug_created {
+        let config = bundle.debug_pool.clone().ok_or_else(|| {
+            ApiError::BadRequest(
+                "Bundle holds custom token balances but no debug_pool config to create them from"
+                    .into(),
+            )
+        })?;
+        let info = router
+            .ensure_debug_pool_with_config(config)
+            .await
+            .map_err(router_error(|e| {
+                ApiError::Internal(format!("Failed to create debug pool: {}", e))
+            }))?;
+        sync_debug_state(&state, &info).await;
+    } else if let Some(config) = &bundle.debug_pool {
+        if debug_created && state.debug_pool.read().await.config != *config {
+            warnings.push(
+                "Debug pool config differs from the exporting instance; debug pool quotes may not match"
+                    .to_string(),
+            );
+        }
+    }
+    let debug_symbols = state.debug_pool.read().await.token_symbols();
+
+    let balances = ["SUI", "USDC", "WAL", "DEEP"]
+        .into_iter()
+        .map(|token| (token.to_string(), bundle.balances.get(token)))
+        .filter(|(_, amount)| *amount > 0)
+        .chain(custom);
+    let mut mints: Vec<(String, String, u64)> = Vec::new();
+    for (symbol, amount) in balances {
+        let coin_type = coin_type_for_symbol(&symbol, &debug_symbols).ok_or_else(|| {
+            ApiError::BadRequest(format!(
+                "Bundle holds {} but this instance's debug tokens are {}",
+                symbol,
+                debug_symbols.join(", ")
+            ))
+        })?;
+        mints.push((symbol, coin_type, amount));
+    }
+
+    {
+        let orderbooks = state.orderbooks.read().await;
+        for pool in &bundle.pools {
+            let local = PoolId::from_str(&pool.pool).and_then(|pool_id| orderbooks.get(&pool_id));
+            match local {
+                Some(ob) if ob.checkpoint != pool.checkpoint => warnings.push(format!(
+                    "{} is loaded at checkpoint {} here but was at {} when exported",
+                    pool.pool, ob.checkpoint, pool.checkpoint
+                )),
+                Some(_) => {}
+                None => warnings.push(format!("{} is not loaded on this instance", pool.pool)),
+            }
+        }
+    }
+
+    // Mint everything before creating the session, so a failed faucet never
+    // leaves a half-funded session behind.
+    mint_balances(router, &mints, "importing").await?;
+
+    let session_id = state
+        .session_manager
+        .create_session()
+        .await
+        .map_err(|e| ApiError::Internal(format!("Failed to create session: {}", e)))?;
+    let session_arc = state
+        .session_manager
+        .get_session(&session_id)
+        .await
+        .ok_or_else(|| ApiError::Internal("Session creation failed".into()))?;
+
+    let mut session = session_arc.write().await;
+    for (token, _, amount) in &mints {
+        session.balances.add(token, *amount);
+        session.record_faucet_draw(token, *amount);
+    }
+    session.checkpoint = bundle.checkpoint;
+    session.swap_history = bundle.swap_history;
+
+    Ok(Json(ImportSessionResponse {
+        success: true,
+        session_id,
+        source_session_id: bundle.source_session_id,
+        balances: BalanceInfo::from(&session.balances),
+        swap_count: session.swap_history.len(),
+        warnings,
+    }))
+}
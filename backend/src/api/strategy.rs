@@ -0,0 +1,292 @@
+//! Strategy backtest endpoint
+//!
+//! Runs a sequence of conditional swaps against a session. Before each
+//! condition and each portfolio valuation the pools involved are re-read from
+//! the router VM, so later steps see the liquidity consumed by earlier ones
+//! (and by any other session's swaps in between).
+
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::api::orderbook::refresh_books_from_vm;
+use crate::api::session::BalanceInfo;
+use crate::api::swap::{swap_once, SwapRequest, SwapResponse};
+use crate::api::AppState;
+use crate::sandbox::orderbook_builder::SandboxOrderbook;
+use crate::sandbox::state_loader::PoolId;
+use crate::sandbox::swap_executor::{TradingSession, UserBalances};
+use crate::types::{ApiError, ApiResult};
+use crate::units;
+
+/// Upper bound on steps per run to keep a single request bounded
+const MAX_STRATEGY_STEPS: usize = 50;
+
+#[derive(Debug, Deserialize)]
+pub struct StrategyRunRequest {
+    pub session_id: String,
+    pub steps: Vec<StrategyStep>,
+    /// Abort remaining steps after the first failed swap (default: true)
+    #[serde(default = "default_stop_on_error")]
+    pub stop_on_error: bool,
+}
+
+fn default_stop_on_error() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StrategyStep {
+    /// Swap only runs when this holds; unconditional when omitted
+    pub condition: Option<PriceCondition>,
+    pub from_token: String,
+    pub to_token: String,
+    /// Amount in smallest unit of `from_token`
+    pub amount: String,
+    pub pool: Option<String>,
+}
+
+/// `mid_price(pool) <op> price`, using the pool's book as it stands in the VM
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PriceCondition {
+    pub pool: String,
+    pub op: ComparisonOp,
+    pub price: f64,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComparisonOp {
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+impl ComparisonOp {
+    fn holds(self, lhs: f64, rhs: f64) -> bool {
+        match self {
+            ComparisonOp::Gt => lhs > rhs,
+            ComparisonOp::Gte => lhs >= rhs,
+            ComparisonOp::Lt => lhs < rhs,
+            ComparisonOp::Lte => lhs <= rhs,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct StrategyStepResult {
+    pub step: usize,
+    /// "executed", "skipped", "failed", or "not_run" (after an earlier failure)
+    pub status: String,
+    /// Mid price the condition was evaluated against
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub observed_mid_price: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub swap: Option<SwapResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StrategyRunResponse {
+    pub success: bool,
+    pub session_id: String,
+    pub steps_executed: usize,
+    pub steps_skipped: usize,
+    pub steps_failed: usize,
+    pub results: Vec<StrategyStepResult>,
+    pub balances_before: BalanceInfo,
+    pub balances_after: BalanceInfo,
+    /// Portfolio value in USDC at session mid prices before the first step
+    pub value_before_usdc: f64,
+    /// Portfolio value in USDC at session mid prices after the last step
+    pub value_after_usdc: f64,
+    pub pnl_usdc: f64,
+}
+
+/// POST /api/strategy/run - Execute conditional swap steps sequentially against a session
+pub async fn run_strategy(
+    State(state): State<AppState>,
+    Json(req): Json<StrategyRunRequest>,
+) -> ApiResult<Json<StrategyRunResponse>> {
+    if req.steps.is_empty() {
+        return Err(ApiError::BadRequest("steps must not be empty".into()));
+    }
+    if req.steps.len() > MAX_STRATEGY_STEPS {
+        return Err(ApiError::BadRequest(format!(
+            "At most {} steps are allowed per run",
+            MAX_STRATEGY_STEPS
+        )));
+    }
+    // Validate conditions up front so a typo doesn't leave a half-run strategy
+    let mut condition_pools = Vec::with_capacity(req.steps.len());
+    for (i, step) in req.steps.iter().enumerate() {
+        let pool_id = match &step.condition {
+            Some(cond) => Some(PoolId::from_str(&cond.pool).ok_or_else(|| {
+                ApiError::BadRequest(format!("Step {}: invalid condition pool '{}'", i, cond.pool))
+            })?),
+            None => None,
+        };
+        condition_pools.push(pool_id);
+    }
+
+    let session_arc = state
+        .session_manager
+        .get_session(&req.session_id)
+        .await
+        .ok_or_else(|| ApiError::NotFound(format!("Session not found: {}", req.session_id)))?;
+    let debug_symbol = state.debug_pool.read().await.token_symbol.clone();
+
+    refresh_session_books(&state, &session_arc, &req.session_id).await;
+    let (balances_before, value_before_usdc) = {
+        let session = session_arc.read().await;
+        let value = portfolio_value_usdc(&session.balances, &session.orderbooks, &debug_symbol);
+        (session.balances.clone(), value)
+    };
+
+    let mut results = Vec::with_capacity(req.steps.len());
+    let mut halted = false;
+    for (i, (step, condition_pool)) in req.steps.into_iter().zip(condition_pools).enumerate() {
+        if halted {
+            results.push(StrategyStepResult {
+                step: i,
+                status: "not_run".to_string(),
+                observed_mid_price: None,
+                swap: None,
+                error: None,
+            });
+            continue;
+        }
+
+        let mut observed_mid_price = None;
+        if let (Some(cond), Some(pool_id)) = (&step.condition, condition_pool) {
+            refresh_books_from_vm(&state, Some(&req.session_id), &[pool_id]).await;
+            let mid = {
+                let session = session_arc.read().await;
+                session.orderbooks.get(&pool_id).and_then(|ob| ob.mid_price())
+            };
+            observed_mid_price = mid;
+            let holds = mid.is_some_and(|m| cond.op.holds(m, cond.price));
+            if !holds {
+                results.push(StrategyStepResult {
+                    step: i,
+                    status: "skipped".to_string(),
+                    observed_mid_price,
+                    swap: None,
+                    error: mid
+                        .is_none()
+                        .then(|| format!("No mid price for {}", pool_id.display_name())),
+                });
+                continue;
+            }
+        }
+
+        let swap_req = SwapRequest {
+            session_id: req.session_id.clone(),
+            pool: step.pool,
+            from_token: step.from_token,
+            to_token: step.to_token,
+            from_type: None,
+            to_type: None,
+            amount: step.amount,
+            sender: None,
+            auto_top_up_deep: false,
+            min_output_amount: None,
+            min_intermediate_amount: None,
+            swap_mode: None,
+            clock_timestamp_ms: None,
+            deep_fee_budget: None,
+            simulate: None,
+            idempotency_key: None,
+        };
+        match swap_once(&state, &swap_req).await {
+            Ok(swap) if swap.success => results.push(StrategyStepResult {
+                step: i,
+                status: "executed".to_string(),
+                observed_mid_price,
+                swap: Some(swap),
+                error: None,
+            }),
+            // The session could not apply the swap's result
+            Ok(swap) => {
+                results.push(StrategyStepResult {
+                    step: i,
+                    status: "failed".to_string(),
+                    observed_mid_price,
+                    error: swap.error.clone(),
+                    swap: Some(swap),
+                });
+                halted = req.stop_on_error;
+            }
+            Err(e) => {
+                results.push(StrategyStepResult {
+                    step: i,
+                    status: "failed".to_string(),
+                    observed_mid_price,
+                    swap: None,
+                    error: Some(e.to_string()),
+                });
+                halted = req.stop_on_error;
+            }
+        }
+    }
+
+    refresh_session_books(&state, &session_arc, &req.session_id).await;
+    let session = session_arc.read().await;
+    let value_after_usdc = portfolio_value_usdc(&session.balances, &session.orderbooks, &debug_symbol);
+    let count = |status: &str| results.iter().filter(|r| r.status == status).count();
+
+    Ok(Json(StrategyRunResponse {
+        success: true,
+        session_id: req.session_id,
+        steps_executed: count("executed"),
+        steps_skipped: count("skipped"),
+        steps_failed: count("failed"),
+        balances_before: BalanceInfo::from(&balances_before),
+        balances_after: BalanceInfo::from(&session.balances),
+        value_before_usdc,
+        value_after_usdc,
+        pnl_usdc: value_after_usdc - value_before_usdc,
+        results,
+    }))
+}
+
+/// Re-read every book the session holds from the router VM before marking
+/// its portfolio
+async fn refresh_session_books(
+    state: &AppState,
+    session_arc: &Arc<RwLock<TradingSession>>,
+    session_id: &str,
+) {
+    let pools: Vec<PoolId> = {
+        let session = session_arc.read().await;
+        session.orderbooks.keys().copied().collect()
+    };
+    refresh_books_from_vm(state, Some(session_id), &pools).await;
+}
+
+/// Mark session balances to USDC using orderbook mid prices.
+///
+/// Tokens without a priced pool contribute zero.
+fn portfolio_value_usdc(
+    balances: &UserBalances,
+    orderbooks: &HashMap<PoolId, SandboxOrderbook>,
+    debug_symbol: &str,
+) -> f64 {
+    let mid = |pool_id: PoolId| {
+        orderbooks
+            .get(&pool_id)
+            .and_then(|ob| ob.mid_price())
+            .unwrap_or(0.0)
+    };
+    let debug_balance = balances.get(debug_symbol);
+
+    units::to_human(balances.usdc, units::USDC_DECIMALS)
+        + units::to_human(balances.sui, units::SUI_DECIMALS) * mid(PoolId::SuiUsdc)
+        + units::to_human(balances.wal, units::WAL_DECIMALS) * mid(PoolId::WalUsdc)
+        + units::to_human(balances.deep, units::DEEP_DECIMALS) * mid(PoolId::DeepUsdc)
+        + units::to_human(debug_balance, units::DEBUG_TOKEN_DECIMALS) * mid(PoolId::DebugUsdc)
+}
@@ -0,0 +1,2569 @@
+//! Swap execution endpoints using Move VM
+//!
+//! Provides swap quotes and execution using MoveVM quote PTBs.
+//! Supports direct pool routes and cross-pool two-hop routes
+//! via the router thread (e.g., SUI -> USDC -> WAL). Three-hop routes
+//! (`ROUTER_MAX_HOPS=3`) can be quoted but not executed.
+
+use axum::{
+    extract::{Query, State},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+
+use crate::api::access_log;
+use crate::api::balance::{mint_within_allowance, FaucetMint, DEEP_TYPE};
+use crate::api::debug::sync_debug_state;
+use crate::api::orderbook::{invalid_pool_message, refresh_books_from_vm, OrderbookProvenance};
+use crate::api::trade_tape::{self, TapePool};
+use crate::api::{router_error, AppState};
+use crate::sandbox::deepbook_errors::DeepBookAbort;
+use crate::sandbox::orderbook_builder::{BookWalk, SandboxOrderbook};
+use crate::sandbox::router::{
+    min_single_hop_input, min_two_hop_input, BatchQuote, BatchQuoteInput, BatchQuoteRoute,
+    RouteHop, RouterHandle, SingleHopQuote, SwapEvent, SwapGasRoute, ThreeHopQuote, TwoHopQuote,
+    TwoHopSecondLegFailed, TypeRoute, EXACT_OUT_UNFILLABLE,
+};
+use crate::sandbox::state_loader::PoolId;
+use crate::sandbox::swap_executor::{
+    CommandEffect, CommandInfo, EventInfo, IdempotencyCheck, PtbExecution, UserBalances,
+};
+use crate::types::{ApiError, ApiResult};
+use crate::units;
+
+mod quote;
+mod route;
+
+pub use quote::{
+    get_quote, get_quote_batch, get_reverse_quote, get_two_sided_quote, validate_quote,
+};
+pub(crate) use route::{
+    candidate_paths, max_route_hops_from_env, normalize_token, pool_pairs,
+    preferred_pools_from_env, PoolPairs,
+};
+
+use quote::*;
+use route::*;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SwapRequest {
+    pub session_id: String,
+    pub pool: Option<String>,
+    #[serde(default)]
+    pub from_token: String,
+    #[serde(default)]
+    pub to_token: String,
+    /// Full input coin type (e.g. `0x2::sui::SUI`); overrides `from_token` when set
+    pub from_type: Option<String>,
+    /// Full output coin type; must be set together with `from_type`
+    pub to_type: Option<String>,
+    /// Amount in smallest unit (MIST for SUI, 6 decimals for USDC), or a
+    /// decimal token amount such as "1.5"; underscores are ignored
+    pub amount: String,
+    /// Optional VM sender override for this swap
+    pub sender: Option<String>,
+    /// Faucet any DEEP shortfall for the estimated fee before executing
+    #[serde(default)]
+    pub auto_top_up_deep: bool,
+    /// Minimum output in smallest units; the swap aborts instead of filling
+    /// below it (two-hop: bounds the final output)
+    pub min_output_amount: Option<String>,
+    /// Two-hop only: minimum shared quote coin (smallest units) out of the first leg
+    pub min_intermediate_amount: Option<String>,
+    /// "exact_in" (default) or "exact_out"; with exact_out, `amount` is the
+    /// output to receive and the input is solved for (direct routes only)
+    pub swap_mode: Option<String>,
+    /// Execute at this VM clock timestamp (ms) instead of the synthetic clock
+    pub clock_timestamp_ms: Option<u64>,
+    /// Most DEEP the swap may spend on fees (smallest units); defaults to the
+    /// session's whole DEEP balance
+    pub deep_fee_budget: Option<String>,
+    /// Run the swap PTB, then roll the VM back and leave the session untouched
+    pub simulate: Option<bool>,
+    /// Retries with the same key replay the first response instead of
+    /// swapping again (`IDEMPOTENCY_TTL_SECS`)
+    pub idempotency_key: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SwapResponse {
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub input_token: String,
+    pub output_token: String,
+    pub input_amount: String,
+    pub input_amount_human: f64,
+    pub output_amount: String,
+    pub output_amount_human: f64,
+    /// The whole `input_amount` was consumed (the VM refunded no input)
+    pub fully_filled: bool,
+    /// Input the VM refunded unfilled, to resubmit elsewhere; all of
+    /// `input_amount` when the swap failed
+    pub remaining_input: String,
+    pub effective_price: f64,
+    /// Effective price computed exactly from raw integer amounts
+    pub effective_price_str: String,
+    pub price_impact_bps: u32,
+    pub gas_used: String,
+    pub execution_time_ms: u64,
+    pub execution_method: String,
+    pub message: String,
+    pub ptb_execution: PtbExecutionInfo,
+    pub balances_after: BalancesAfter,
+    /// "direct" for single-pool, "two_hop" for cross-pool
+    pub route_type: String,
+    /// Shared quote coin amount for two-hop routes (human-readable)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub intermediate_amount: Option<f64>,
+    /// DEEP minted before the swap when `auto_top_up_deep` was requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deep_top_up: Option<DeepTopUp>,
+    /// More than one pool could serve this pair; `PREFERRED_POOLS` picked one
+    pub route_ambiguous: bool,
+    /// How an executed two-hop route ran
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub two_hop_execution: Option<TwoHopExecution>,
+    /// Per-leg fill status of an executed two-hop route
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub two_hop_legs: Option<TwoHopLegs>,
+    /// Target and solved input of an exact-output swap
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exact_out: Option<ExactOut>,
+    /// The swap ran with `simulate`: VM state and session balances are
+    /// unchanged, and `balances_after` is what executing it would leave
+    pub simulated: bool,
+}
+
+/// How an exact-output swap sized its input
+#[derive(Debug, Clone, Serialize)]
+pub struct ExactOut {
+    /// Requested output; the executed output is at least this
+    pub target_output: String,
+    pub target_output_human: f64,
+    /// Smallest input the pool quoted at or above the target
+    pub required_input: String,
+    pub required_input_human: f64,
+    /// Quote PTBs the input search ran
+    pub quote_iterations: u32,
+}
+
+/// How much of each two-hop leg's input was consumed.
+///
+/// A leg is fully filled when nothing of its input was refunded. The second
+/// leg's input is whatever quote coin the first leg produced, so a partial first
+/// leg shrinks the second leg rather than showing up as its refund.
+#[derive(Debug, Clone, Serialize)]
+pub struct TwoHopLegs {
+    pub first_leg_fully_filled: bool,
+    /// Input token requested for leg 1
+    pub first_leg_input: String,
+    /// Input token refunded by leg 1
+    pub first_leg_refund: String,
+    pub first_leg_refund_human: f64,
+    pub second_leg_fully_filled: bool,
+    /// Quote coin produced by leg 1 and fed into leg 2
+    pub second_leg_input: String,
+    /// Quote coin refunded by leg 2
+    pub second_leg_refund: String,
+    pub second_leg_refund_human: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TwoHopExecution {
+    /// Atomic PTB executions tried (`SWAP_TWO_HOP_ATOMIC_RETRIES` + 1 at most)
+    pub atomic_attempts: u32,
+    /// "atomic" or "sequential" (two single-hop PTBs after every atomic attempt failed)
+    pub mode: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeepTopUp {
+    pub estimated_fee: String,
+    pub estimated_fee_human: f64,
+    pub minted: String,
+    pub minted_human: f64,
+}
+
+/// Swap request executed with a minimum output derived from a fresh quote
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProtectedSwapRequest {
+    #[serde(flatten)]
+    pub swap: SwapRequest,
+    /// Slippage tolerance in basis points (50 = 0.5%); defaults to `SWAP_SLIPPAGE_BPS`
+    pub slippage_bps: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProtectedSwapResponse {
+    #[serde(flatten)]
+    pub swap: SwapResponse,
+    /// Tolerance applied to the quote
+    pub slippage_bps: u32,
+    pub quoted_output: String,
+    pub quoted_output_human: f64,
+    /// Minimum output enforced on execution
+    pub min_out: String,
+    pub min_out_human: f64,
+    /// Executed output relative to the quote in basis points (negative = worse)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_vs_quote_bps: Option<f64>,
+}
+
+/// Quote taken just before a protected swap and the bound derived from it
+struct SlippageGuard {
+    quoted_output: u64,
+    min_out: u64,
+}
+
+impl SlippageGuard {
+    /// `quoted_output` less `slippage_bps`, or `min_output_amount` when the
+    /// caller asked for a stricter bound; the bound reported is the one enforced.
+    fn new(quoted_output: u64, slippage_bps: u32, min_output_amount: u64) -> Self {
+        Self {
+            quoted_output,
+            min_out: units::min_out_with_slippage(quoted_output, slippage_bps)
+                .max(min_output_amount),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PtbExecutionInfo {
+    pub commands: Vec<CommandDetail>,
+    pub status: String,
+    pub effects_digest: Option<String>,
+    pub events: Vec<EventDetail>,
+    pub summary: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CommandDetail {
+    pub index: usize,
+    pub command_type: String,
+    pub package: String,
+    pub module: String,
+    pub function: String,
+    pub type_args: Vec<String>,
+    pub description: String,
+    /// Success, return values, and mutated objects from the executed PTB
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effects: Option<CommandEffect>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EventDetail {
+    pub event_type: String,
+    pub data: serde_json::Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BalancesAfter {
+    pub sui: String,
+    pub sui_human: f64,
+    pub usdc: String,
+    pub usdc_human: f64,
+    pub deep: String,
+    pub deep_human: f64,
+    pub wal: String,
+    pub wal_human: f64,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub custom: HashMap<String, String>,
+}
+
+impl From<&UserBalances> for BalancesAfter {
+    fn from(b: &UserBalances) -> Self {
+        Self {
+            sui: b.sui.to_string(),
+            sui_human: units::to_human(b.sui, units::SUI_DECIMALS),
+            usdc: b.usdc.to_string(),
+            usdc_human: units::to_human(b.usdc, units::USDC_DECIMALS),
+            deep: b.deep.to_string(),
+            deep_human: units::to_human(b.deep, units::DEEP_DECIMALS),
+            wal: b.wal.to_string(),
+            wal_human: units::to_human(b.wal, units::WAL_DECIMALS),
+            custom: b
+                .custom
+                .iter()
+                .map(|(symbol, amount)| (symbol.clone(), amount.to_string()))
+                .collect(),
+        }
+    }
+}
+
+/// Fail fast with 503 when startup loaded no pool state at all.
+///
+/// Otherwise the request would fail further down with a less obvious
+/// missing-router or missing-pool error.
+async fn ensure_pools_available(state: &AppState) -> ApiResult<()> {
+    if state.pool_registry.read().await.loaded_pools().is_empty() {
+        return Err(ApiError::ServiceUnavailable(
+            "No pools available: no pool state files were loaded at startup (see GET /health/detailed)"
+                .into(),
+        ));
+    }
+    Ok(())
+}
+
+fn three_hop_execution_unsupported() -> ApiError {
+    ApiError::BadRequest(
+        "Three-hop routes can be quoted but not executed; swap each leg separately".into(),
+    )
+}
+
+/// Fast-fail with 503 if any pool on the route has an open circuit breaker.
+async fn check_circuits(state: &AppState, pools: &[PoolId]) -> ApiResult<()> {
+    let mut breakers = state.circuit_breakers.write().await;
+    for (i, pool_id) in pools.iter().enumerate() {
+        if let Err(e) = breakers.check(*pool_id) {
+            // Release any half-open probe slots already taken for earlier legs.
+            for admitted in &pools[..i] {
+                breakers.record_neutral(*admitted);
+            }
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+/// Whether a failed VM operation counts against the pools' circuit breakers.
+///
+/// Internal errors, router timeouts and DeepBook aborts caused by the pool's
+/// state (see [`DeepBookAbort::is_client_error`]) count; client errors are
+/// neutral.
+fn is_circuit_failure(err: &ApiError) -> bool {
+    match err {
+        ApiError::Internal(_) | ApiError::RouterTimeout(_) => true,
+        ApiError::DeepBook { deepbook_code, .. } => {
+            DeepBookAbort::from_code(deepbook_code).is_some_and(|abort| !abort.is_client_error())
+        }
+        _ => false,
+    }
+}
+
+/// Feed a VM operation outcome into the circuit breakers for `pools`.
+async fn record_circuit_outcome<T>(state: &AppState, pools: &[PoolId], result: &ApiResult<T>) {
+    let mut breakers = state.circuit_breakers.write().await;
+    for pool_id in pools {
+        match result {
+            Ok(_) => breakers.record_success(*pool_id),
+            Err(e) if is_circuit_failure(e) => breakers.record_failure(*pool_id),
+            Err(_) => breakers.record_neutral(*pool_id),
+        }
+    }
+}
+
+/// `DeepBook` API error for an abort on `target` (a pool or route)
+fn deepbook_error(abort: DeepBookAbort, target: &str) -> ApiError {
+    ApiError::DeepBook {
+        deepbook_code: abort.code(),
+        message: format!("{} ({})", abort.message(), target),
+    }
+}
+
+/// [`deepbook_error`] when a router failure is a known DeepBook abort
+fn deepbook_abort_error(err_text: &str, target: &str) -> Option<ApiError> {
+    DeepBookAbort::classify(err_text).map(|abort| deepbook_error(abort, target))
+}
+
+/// Response payload of a swap event: its decoded fields when the router
+/// decoded it, otherwise `{ "bcs": <hex> }`.
+fn event_data(event: &SwapEvent) -> serde_json::Value {
+    event
+        .decoded
+        .clone()
+        .unwrap_or_else(|| serde_json::json!({ "bcs": event.data_hex }))
+}
+
+/// Add the fills in a swap's events to the trade tape of the pools it touched.
+async fn record_trades(state: &AppState, pools: &[PoolId], events: &[SwapEvent]) {
+    let tape_pools: Vec<TapePool> = {
+        let debug_pool = state.debug_pool.read().await;
+        let registry = state.pool_registry.read().await;
+        pools
+            .iter()
+            .filter_map(|&pool_id| {
+                if pool_id.is_debug() {
+                    let info = debug_pool
+                        .pools
+                        .iter()
+                        .find(|info| info.pool_id == pool_id)?;
+                    Some(TapePool {
+                        pool_id,
+                        object_id: info.pool_object_id.clone(),
+                        base_decimals: info.config.token_decimals,
+                        quote_decimals: units::USDC_DECIMALS,
+                    })
+                } else {
+                    let config = registry.config(pool_id);
+                    Some(TapePool {
+                        pool_id,
+                        object_id: config.pool_wrapper,
+                        base_decimals: config.base_decimals,
+                        quote_decimals: config.quote_decimals,
+                    })
+                }
+            })
+            .collect()
+    };
+    for (pool_id, quote_quantity) in trade_tape::quote_volumes(&tape_pools, events) {
+        state.metrics.add_pool_volume(pool_id, quote_quantity);
+    }
+    state
+        .trade_tape
+        .write()
+        .await
+        .record_events(&tape_pools, events);
+}
+
+/// Default protected-swap tolerance when `SWAP_SLIPPAGE_BPS` is unset (0.5%)
+const DEFAULT_SLIPPAGE_BPS: u32 = 50;
+
+/// Parse `SWAP_SLIPPAGE_BPS`, falling back to 50 bps on missing or invalid values.
+pub(crate) fn default_slippage_bps_from_env() -> u32 {
+    match std::env::var("SWAP_SLIPPAGE_BPS") {
+        Ok(raw) => match raw.trim().parse::<u32>() {
+            Ok(bps) if bps <= units::BPS_DENOMINATOR => bps,
+            _ => {
+                tracing::warn!(
+                    "Ignoring invalid SWAP_SLIPPAGE_BPS '{}'; using {} bps",
+                    raw,
+                    DEFAULT_SLIPPAGE_BPS
+                );
+                DEFAULT_SLIPPAGE_BPS
+            }
+        },
+        Err(_) => DEFAULT_SLIPPAGE_BPS,
+    }
+}
+
+/// Fractional digits kept in exact price strings (truncated, not rounded)
+const EXACT_PRICE_DECIMALS: usize = 18;
+
+/// Exact decimal string for `(num / 10^num_decimals) / (den / 10^den_decimals)`.
+///
+/// Uses integer long division on the raw amounts so no float rounding is
+/// introduced; trailing zeros are trimmed. Returns "0" when `den` is zero.
+fn exact_ratio_str(num: u64, num_decimals: u8, den: u64, den_decimals: u8) -> String {
+    if den == 0 {
+        return "0".to_string();
+    }
+    let numerator = num as u128 * 10u128.pow(den_decimals as u32);
+    let denominator = den as u128 * 10u128.pow(num_decimals as u32);
+
+    let mut out = (numerator / denominator).to_string();
+    let mut remainder = numerator % denominator;
+    if remainder == 0 {
+        return out;
+    }
+    let mut fraction = String::with_capacity(EXACT_PRICE_DECIMALS);
+    for _ in 0..EXACT_PRICE_DECIMALS {
+        remainder *= 10;
+        fraction.push(char::from(b'0' + (remainder / denominator) as u8));
+        remainder %= denominator;
+        if remainder == 0 {
+            break;
+        }
+    }
+    let fraction = fraction.trim_end_matches('0');
+    if !fraction.is_empty() {
+        out.push('.');
+        out.push_str(fraction);
+    }
+    out
+}
+
+/// Exact USDC-per-base price for a direct swap (sell: out/in, buy: in/out).
+fn single_hop_price_str(
+    is_sell: bool,
+    input: u64,
+    input_decimals: u8,
+    output: u64,
+    output_decimals: u8,
+) -> String {
+    if is_sell {
+        exact_ratio_str(output, output_decimals, input, input_decimals)
+    } else {
+        exact_ratio_str(input, input_decimals, output, output_decimals)
+    }
+}
+
+/// Message suffix for a swap the VM only partially filled
+fn partial_fill_note(input_refund: u64, from: &str, debug_symbol: &str) -> String {
+    format!(
+        ". Partially filled: {:.4} {} ({} raw) of the input was returned unfilled",
+        units::to_human(input_refund, get_decimals(from, debug_symbol)),
+        from,
+        input_refund
+    )
+}
+
+/// Price of the first pool's base in units of the second pool's base, from
+/// the two pools' USDC mids; 0 when either mid is missing.
+pub(super) fn two_hop_mid(first_mid: f64, second_mid: f64) -> f64 {
+    if first_mid > 0.0 && second_mid > 0.0 {
+        first_mid / second_mid
+    } else {
+        0.0
+    }
+}
+
+async fn ensure_debug_pool_and_sync(state: &AppState, router: &RouterHandle) -> ApiResult<()> {
+    // Pool setup spaces its DEEP price points on the synthetic clock, so it
+    // never runs at a request's pinned `clock_timestamp_ms`.
+    let info = router
+        .at_clock(None)
+        .ensure_debug_pool()
+        .await
+        .map_err(router_error(|e| {
+            ApiError::Internal(format!("Failed to ensure debug pool: {}", e))
+        }))?;
+    sync_debug_state(state, &info).await;
+    Ok(())
+}
+
+/// POST /api/swap - Execute a swap in a session
+pub async fn execute_swap(
+    State(state): State<AppState>,
+    Json(req): Json<SwapRequest>,
+) -> ApiResult<Response> {
+    let fingerprint = request_fingerprint("swap", &req);
+    with_idempotency(&state, &req, fingerprint, swap_once(&state, &req)).await
+}
+
+/// Execute a plain swap without idempotency handling, for in-process callers
+/// such as strategy runs.
+pub async fn swap_once(state: &AppState, req: &SwapRequest) -> ApiResult<SwapResponse> {
+    run_swap(state, req, None)
+        .await
+        .map(|(response, _)| response)
+}
+
+/// POST /api/swap/protected - Quote, then execute with a slippage-bounded min_out
+///
+/// The swap aborts rather than fill below `quote * (1 - slippage_bps / 10_000)`.
+pub async fn execute_protected_swap(
+    State(state): State<AppState>,
+    Json(req): Json<ProtectedSwapRequest>,
+) -> ApiResult<Response> {
+    let fingerprint = request_fingerprint("swap/protected", &req);
+    with_idempotency(
+        &state,
+        &req.swap,
+        fingerprint,
+        run_protected_swap(&state, &req),
+    )
+    .await
+}
+
+/// Header set on a response replayed for a repeated idempotency key
+const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// Identifies a swap request for idempotency checks: the endpoint plus the full body
+fn request_fingerprint<T: Serialize>(endpoint: &str, req: &T) -> String {
+    format!(
+        "{}:{}",
+        endpoint,
+        serde_json::to_string(req).unwrap_or_default()
+    )
+}
+
+/// Run `swap` once per `(session_id, idempotency_key)`.
+///
+/// Without a key the swap just runs. The first request under a key runs and
+/// its successful response is cached; repeats replay it with an
+/// `idempotent-replayed: true` header. Errors are not cached, so the request
+/// can be retried under the same key.
+async fn with_idempotency<T, F>(
+    state: &AppState,
+    req: &SwapRequest,
+    fingerprint: String,
+    swap: F,
+) -> ApiResult<Response>
+where
+    T: Serialize,
+    F: Future<Output = ApiResult<T>>,
+{
+    let Some(key) = req.idempotency_key.as_deref() else {
+        return swap.await.map(|response| Json(response).into_response());
+    };
+    if key.trim().is_empty() {
+        return Err(ApiError::BadRequest(
+            "idempotency_key must not be empty".into(),
+        ));
+    }
+
+    let check = state
+        .session_manager
+        .begin_idempotent(&req.session_id, key, &fingerprint)
+        .await
+        .map_err(|e| ApiError::Conflict(e.to_string()))?;
+    if let IdempotencyCheck::Replay(response) = check {
+        return Ok(([(IDEMPOTENT_REPLAYED_HEADER, "true")], Json(response)).into_response());
+    }
+
+    let result = swap.await;
+    let cached = result
+        .as_ref()
+        .ok()
+        .and_then(|response| serde_json::to_value(response).ok());
+    state
+        .session_manager
+        .finish_idempotent(&req.session_id, key, cached)
+        .await;
+    result.map(|response| Json(response).into_response())
+}
+
+/// Tolerance for a protected swap: the request's, else `SWAP_SLIPPAGE_BPS`
+fn protected_slippage_bps(requested: Option<u32>, default_bps: u32) -> ApiResult<u32> {
+    let slippage_bps = requested.unwrap_or(default_bps);
+    if slippage_bps > units::BPS_DENOMINATOR {
+        return Err(ApiError::BadRequest(format!(
+            "slippage_bps must be at most {}",
+            units::BPS_DENOMINATOR
+        )));
+    }
+    Ok(slippage_bps)
+}
+
+async fn run_protected_swap(
+    state: &AppState,
+    req: &ProtectedSwapRequest,
+) -> ApiResult<ProtectedSwapResponse> {
+    let slippage_bps = protected_slippage_bps(req.slippage_bps, state.default_slippage_bps)?;
+    let (response, guard) = run_swap(state, &req.swap, Some(slippage_bps)).await?;
+    let guard = guard.ok_or_else(|| {
+        ApiError::Internal("Protected swap executed without a slippage quote".into())
+    })?;
+
+    let debug_symbol = state.debug_pool.read().await.token_symbol.clone();
+    let output_decimals = get_decimals(&response.output_token, &debug_symbol);
+    let output_vs_quote_bps = if response.success && guard.quoted_output > 0 {
+        let executed: u64 = response.output_amount.parse().unwrap_or(0);
+        Some(
+            (executed as f64 - guard.quoted_output as f64) / guard.quoted_output as f64
+                * units::BPS_DENOMINATOR as f64,
+        )
+    } else {
+        None
+    };
+
+    Ok(ProtectedSwapResponse {
+        swap: response,
+        slippage_bps,
+        quoted_output: guard.quoted_output.to_string(),
+        quoted_output_human: units::to_human(guard.quoted_output, output_decimals),
+        min_out: guard.min_out.to_string(),
+        min_out_human: units::to_human(guard.min_out, output_decimals),
+        output_vs_quote_bps,
+    })
+}
+
+/// Shared swap flow for the plain and protected endpoints, counted in
+/// `/metrics`.
+async fn run_swap(
+    state: &AppState,
+    req: &SwapRequest,
+    slippage_bps: Option<u32>,
+) -> ApiResult<(SwapResponse, Option<SlippageGuard>)> {
+    let result = swap_with_guard(state, req, slippage_bps).await;
+    match &result {
+        Ok((response, _)) if response.success => state.metrics.record_swap(&response.route_type),
+        Ok(_) => state.metrics.record_swap_failure("EXECUTION_FAILED"),
+        Err(e) => state.metrics.record_swap_error(e),
+    }
+    result
+}
+
+/// With `slippage_bps` set, the route is quoted right before execution and the
+/// swap enforces the derived min_out. An explicit `min_output_amount` is
+/// enforced too; when both apply the stricter bound wins. Otherwise min_out is
+/// 0 (unbounded).
+async fn swap_with_guard(
+    state: &AppState,
+    req: &SwapRequest,
+    slippage_bps: Option<u32>,
+) -> ApiResult<(SwapResponse, Option<SlippageGuard>)> {
+    let start = std::time::Instant::now();
+    let clocked = state.at_clock(req.clock_timestamp_ms);
+    let state = &clocked;
+
+    // Validate request
+    if req.session_id.is_empty() {
+        return Err(ApiError::BadRequest("session_id required".into()));
+    }
+    ensure_pools_available(state).await?;
+
+    // Determine route (optional explicit pool override for direct swaps)
+    let (from, to, route, route_ambiguous) = resolve_route(
+        state,
+        &req.from_token,
+        &req.to_token,
+        req.from_type.as_deref(),
+        req.to_type.as_deref(),
+        req.pool.as_deref(),
+    )
+    .await?;
+    access_log::record_route(&route.pools(), route.route_type());
+    let debug_symbol = state.debug_pool.read().await.token_symbol.clone();
+
+    // Get session
+    let session_arc = state
+        .session_manager
+        .get_session(&req.session_id)
+        .await
+        .ok_or_else(|| ApiError::NotFound(format!("Session not found: {}", req.session_id)))?;
+
+    let SwapOptions {
+        amount,
+        exact_out,
+        min_output_amount,
+        min_intermediate_amount,
+        deep_fee_budget,
+        simulate,
+    } = validate_swap_request(req, &route, &from, &to, &debug_symbol, slippage_bps)?;
+
+    let pools = route.pools();
+    check_circuits(state, &pools).await?;
+
+    if let Some(budget) = deep_fee_budget {
+        if let Err(e) = check_deep_fee_budget(state, &route, &from, amount, budget).await {
+            let failed: ApiResult<(SwapResponse, Option<SlippageGuard>)> = Err(e);
+            record_circuit_outcome(state, &pools, &failed).await;
+            return failed;
+        }
+    }
+
+    let deep_top_up = if req.auto_top_up_deep {
+        match auto_top_up_deep(
+            state,
+            &req.session_id,
+            &session_arc,
+            &route,
+            &from,
+            amount,
+            &req.sender,
+        )
+        .await
+        {
+            Ok(top_up) => top_up,
+            Err(e) => {
+                let failed: ApiResult<(SwapResponse, Option<SlippageGuard>)> = Err(e);
+                record_circuit_outcome(state, &pools, &failed).await;
+                return failed;
+            }
+        }
+    } else {
+        None
+    };
+
+    let guard = match slippage_bps {
+        Some(bps) => match quote_route_output(state, &route, &from, amount).await {
+            Ok(quoted_output) => Some(SlippageGuard::new(quoted_output, bps, min_output_amount)),
+            Err(e) => {
+                let failed: ApiResult<(SwapResponse, Option<SlippageGuard>)> = Err(e);
+                record_circuit_outcome(state, &pools, &failed).await;
+                return failed;
+            }
+        },
+        None => None,
+    };
+    let min_out = guard.as_ref().map_or(min_output_amount, |g| g.min_out);
+
+    let result = match route {
+        Route::SinglePool(pool_id) => {
+            execute_single_pool_swap(
+                state,
+                session_arc,
+                pool_id,
+                &from,
+                &to,
+                &debug_symbol,
+                amount,
+                min_out,
+                exact_out,
+                deep_fee_budget,
+                req.sender.clone(),
+                simulate,
+                start,
+            )
+            .await
+        }
+        Route::TwoHop {
+            first_pool,
+            second_pool,
+        } => {
+            execute_two_hop_swap(
+                state,
+                session_arc,
+                first_pool,
+                second_pool,
+                &from,
+                &to,
+                &debug_symbol,
+                amount,
+                min_intermediate_amount,
+                min_out,
+                deep_fee_budget,
+                req.sender.clone(),
+                simulate,
+                start,
+            )
+            .await
+        }
+        Route::ThreeHop { .. } => Err(three_hop_execution_unsupported()),
+    };
+    record_circuit_outcome(state, &pools, &result).await;
+    if matches!(&result, Ok(Json(response)) if response.success && !response.simulated) {
+        refresh_books_from_vm(state, Some(&req.session_id), &pools).await;
+        state
+            .session_manager
+            .notify_pool_update(Some(&req.session_id), &pools);
+    }
+    result.map(|Json(mut response)| {
+        response.deep_top_up = deep_top_up;
+        response.route_ambiguous = route_ambiguous;
+        (response, guard)
+    })
+}
+
+/// Request fields parsed and cross-checked by [`validate_swap_request`]
+struct SwapOptions {
+    /// Input amount, or the output to receive with `exact_out`
+    amount: u64,
+    exact_out: bool,
+    min_output_amount: u64,
+    min_intermediate_amount: u64,
+    deep_fee_budget: Option<u64>,
+    simulate: bool,
+}
+
+/// Parse a swap request's amounts and mode and reject combinations `route`
+/// cannot execute, before any DEEP top-up, quote, or PTB runs.
+fn validate_swap_request(
+    req: &SwapRequest,
+    route: &Route,
+    from: &str,
+    to: &str,
+    debug_symbol: &str,
+    slippage_bps: Option<u32>,
+) -> ApiResult<SwapOptions> {
+    let min_output_amount: u64 = match req.min_output_amount.as_deref() {
+        Some(raw) => raw
+            .parse()
+            .map_err(|_| ApiError::BadRequest("Invalid min_output_amount".into()))?,
+        None => 0,
+    };
+    let min_intermediate_amount: u64 = match req.min_intermediate_amount.as_deref() {
+        Some(raw) => raw
+            .parse()
+            .map_err(|_| ApiError::BadRequest("Invalid min_intermediate_amount".into()))?,
+        None => 0,
+    };
+    let deep_fee_budget: Option<u64> = match req.deep_fee_budget.as_deref() {
+        Some(raw) => Some(
+            raw.parse()
+                .map_err(|_| ApiError::BadRequest("Invalid deep_fee_budget".into()))?,
+        ),
+        None => None,
+    };
+    let exact_out = match req.swap_mode.as_deref().map(str::trim) {
+        None | Some("") | Some("exact_in") => false,
+        Some("exact_out") => true,
+        Some(other) => {
+            return Err(ApiError::BadRequest(format!(
+                "Invalid swap_mode '{}'. Expected exact_in or exact_out",
+                other
+            )))
+        }
+    };
+    // exact_out amounts are denominated in the output token
+    let amount =
+        parse_request_amount(&req.amount, if exact_out { to } else { from }, debug_symbol)?;
+    if matches!(route, Route::ThreeHop { .. }) {
+        return Err(three_hop_execution_unsupported());
+    }
+    if req.min_intermediate_amount.is_some() && !matches!(route, Route::TwoHop { .. }) {
+        return Err(ApiError::BadRequest(
+            "min_intermediate_amount only applies to two-hop swaps".into(),
+        ));
+    }
+    let simulate = req.simulate.unwrap_or(false);
+    if simulate && req.auto_top_up_deep {
+        return Err(ApiError::BadRequest(
+            "auto_top_up_deep is not supported with simulate; fund DEEP with /api/faucet first"
+                .into(),
+        ));
+    }
+    if exact_out {
+        if !matches!(route, Route::SinglePool(_)) {
+            return Err(ApiError::BadRequest(
+                "swap_mode exact_out is only supported for direct (single-pool) swaps".into(),
+            ));
+        }
+        if slippage_bps.is_some() || req.min_output_amount.is_some() {
+            return Err(ApiError::BadRequest(
+                "swap_mode exact_out already enforces amount as the minimum output; it cannot be combined with min_output_amount or /api/swap/protected".into(),
+            ));
+        }
+        if req.auto_top_up_deep {
+            return Err(ApiError::BadRequest(
+                "auto_top_up_deep is not supported with swap_mode exact_out".into(),
+            ));
+        }
+        if deep_fee_budget.is_some() {
+            return Err(ApiError::BadRequest(
+                "deep_fee_budget is not supported with swap_mode exact_out".into(),
+            ));
+        }
+    }
+
+    Ok(SwapOptions {
+        amount,
+        exact_out,
+        min_output_amount,
+        min_intermediate_amount,
+        deep_fee_budget,
+        simulate,
+    })
+}
+
+/// Raw output the router quotes for `amount` along `route`.
+///
+/// Used as the reference for protected swaps; a zero quote is rejected since
+/// it would leave min_out unbounded.
+async fn quote_route_output(
+    state: &AppState,
+    route: &Route,
+    from: &str,
+    amount: u64,
+) -> ApiResult<u64> {
+    let router = state.router.as_ref().ok_or_else(|| {
+        ApiError::ServiceUnavailable("MoveVM router is not initialized for slippage quoting".into())
+    })?;
+    if route.pools().contains(&PoolId::DebugUsdc) {
+        ensure_debug_pool_and_sync(state, router).await?;
+    }
+
+    let quote_err = |e: anyhow::Error| ApiError::Internal(format!("Slippage quote failed: {}", e));
+    let quoted_output = match *route {
+        Route::SinglePool(pool_id) => {
+            router
+                .quote_single_hop(
+                    pool_id,
+                    amount,
+                    pool_pairs(state).await.is_sell_base(pool_id, from),
+                )
+                .await
+                .map_err(router_error(quote_err))?
+                .output_amount
+        }
+        Route::TwoHop {
+            first_pool,
+            second_pool,
+        } => {
+            router
+                .quote_two_hop(first_pool, second_pool, amount)
+                .await
+                .map_err(router_error(quote_err))?
+                .final_output
+        }
+        Route::ThreeHop { hops } => {
+            router
+                .quote_three_hop(hops, amount)
+                .await
+                .map_err(router_error(quote_err))?
+                .final_output
+        }
+    };
+    if quoted_output == 0 {
+        return Err(ApiError::BadRequest(
+            "Quote returned no output; cannot derive a slippage-protected min_out".into(),
+        ));
+    }
+    Ok(quoted_output)
+}
+
+/// DEEP fee the router quotes for swapping `amount` along `route`.
+async fn estimate_deep_fee(
+    state: &AppState,
+    router: &RouterHandle,
+    route: &Route,
+    from: &str,
+    amount: u64,
+) -> ApiResult<u64> {
+    if route.pools().contains(&PoolId::DebugUsdc) {
+        ensure_debug_pool_and_sync(state, router).await?;
+    }
+
+    let estimate_err =
+        |e: anyhow::Error| ApiError::Internal(format!("DEEP fee estimate failed: {}", e));
+    let estimated_fee = match *route {
+        Route::SinglePool(pool_id) => {
+            router
+                .quote_single_hop(
+                    pool_id,
+                    amount,
+                    pool_pairs(state).await.is_sell_base(pool_id, from),
+                )
+                .await
+                .map_err(router_error(estimate_err))?
+                .deep_fee
+        }
+        Route::TwoHop {
+            first_pool,
+            second_pool,
+        } => {
+            let first = router
+                .quote_single_hop(first_pool, amount, true)
+                .await
+                .map_err(router_error(estimate_err))?;
+            let second = router
+                .quote_single_hop(second_pool, first.output_amount, false)
+                .await
+                .map_err(router_error(estimate_err))?;
+            first.deep_fee.saturating_add(second.deep_fee)
+        }
+        Route::ThreeHop { hops } => {
+            router
+                .quote_three_hop(hops, amount)
+                .await
+                .map_err(router_error(estimate_err))?
+                .deep_fee
+        }
+    };
+    Ok(estimated_fee)
+}
+
+/// Reject a swap whose estimated DEEP fee exceeds `deep_fee_budget`.
+///
+/// Whitelisted pools quote a zero fee, so any budget passes on them.
+async fn check_deep_fee_budget(
+    state: &AppState,
+    route: &Route,
+    from: &str,
+    amount: u64,
+    deep_fee_budget: u64,
+) -> ApiResult<()> {
+    let router = state.router.as_ref().ok_or_else(|| {
+        ApiError::ServiceUnavailable(
+            "MoveVM router is not initialized for DEEP fee estimation".into(),
+        )
+    })?;
+    let estimated_fee = estimate_deep_fee(state, router, route, from, amount).await?;
+    if estimated_fee > deep_fee_budget {
+        return Err(ApiError::BadRequest(format!(
+            "deep_fee_budget {} is {} short of the estimated DEEP fee {} for this swap",
+            deep_fee_budget,
+            estimated_fee - deep_fee_budget,
+            estimated_fee
+        )));
+    }
+    Ok(())
+}
+
+/// Estimate the DEEP fee for `route` and faucet any shortfall into the session.
+///
+/// The shortfall is charged to the session's DEEP faucet allowance like a
+/// `/api/faucet` call, so top-ups cannot mint past `FAUCET_DAILY_CAP`.
+/// Returns `None` when the session already holds enough DEEP.
+async fn auto_top_up_deep(
+    state: &AppState,
+    session_id: &str,
+    session_arc: &std::sync::Arc<tokio::sync::RwLock<crate::sandbox::swap_executor::TradingSession>>,
+    route: &Route,
+    from: &str,
+    amount: u64,
+    sender: &Option<String>,
+) -> ApiResult<Option<DeepTopUp>> {
+    let router = state.router.as_ref().ok_or_else(|| {
+        ApiError::ServiceUnavailable(
+            "MoveVM router is not initialized for DEEP fee estimation".into(),
+        )
+    })?;
+    let estimated_fee = estimate_deep_fee(state, router, route, from, amount).await?;
+
+    let current_deep = session_arc.read().await.balances.deep;
+    if current_deep >= estimated_fee {
+        return Ok(None);
+    }
+    let shortfall = estimated_fee - current_deep;
+
+    let mint = FaucetMint {
+        session_id,
+        token: "DEEP",
+        coin_type: DEEP_TYPE,
+        amount: shortfall,
+        decimals: units::DEEP_DECIMALS,
+        sender: sender.clone(),
+    };
+    let minted = mint_within_allowance(state, router, mint, |e| {
+        ApiError::Internal(format!("DEEP auto top-up faucet failed: {}", e))
+    })
+    .await
+    .map_err(|e| match e {
+        ApiError::BadRequest(message) => {
+            ApiError::BadRequest(format!("DEEP auto top-up: {}", message))
+        }
+        other => other,
+    })?
+    .amount;
+    {
+        let mut session = session_arc.write().await;
+        session.balances.add("DEEP", minted);
+        session.record_faucet_draw("DEEP", minted);
+    }
+    tracing::info!(
+        "Auto top-up minted {} DEEP (estimated fee {}, balance was {})",
+        minted,
+        estimated_fee,
+        current_deep
+    );
+
+    Ok(Some(DeepTopUp {
+        estimated_fee: estimated_fee.to_string(),
+        estimated_fee_human: units::to_human(estimated_fee, units::DEEP_DECIMALS),
+        minted: minted.to_string(),
+        minted_human: units::to_human(minted, units::DEEP_DECIMALS),
+    }))
+}
+
+/// Reject `amount` of `from` when it is below `min_input`, the smallest input
+/// DeepBook fills on `route`, instead of letting the PTB abort in the VM.
+fn check_min_swap_input(
+    amount: u64,
+    min_input: Option<u64>,
+    from: &str,
+    debug_symbol: &str,
+    route: &str,
+) -> ApiResult<()> {
+    match min_input {
+        Some(min_input) if amount < min_input => {
+            let decimals = get_decimals(from, debug_symbol);
+            Err(ApiError::BadRequest(format!(
+                "Swap amount {} {} ({}) is below the minimum order size on {}; send at least {} ({} {})",
+                amount,
+                from,
+                units::to_human(amount, decimals),
+                route,
+                min_input,
+                units::to_human(min_input, decimals),
+                from
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// DEEP split from the reserve for a swap's fees: the session's balance,
+/// capped at the request's `deep_fee_budget`.
+fn capped_deep_budget(deep_balance: u64, deep_fee_budget: Option<u64>) -> u64 {
+    deep_fee_budget.map_or(deep_balance, |budget| budget.min(deep_balance))
+}
+
+/// Execute a single-pool swap with a real MoveVM pool::swap_exact_* PTB.
+async fn execute_single_pool_swap(
+    state: &AppState,
+    session_arc: std::sync::Arc<tokio::sync::RwLock<crate::sandbox::swap_executor::TradingSession>>,
+    pool_id: PoolId,
+    from: &str,
+    to: &str,
+    debug_symbol: &str,
+    amount: u64,
+    min_out: u64,
+    exact_out: bool,
+    deep_fee_budget: Option<u64>,
+    sender: Option<String>,
+    simulate: bool,
+    start: std::time::Instant,
+) -> ApiResult<Json<SwapResponse>> {
+    let is_sell = pool_pairs(state).await.is_sell_base(pool_id, from);
+    let router = state.router.as_ref().ok_or_else(|| {
+        ApiError::ServiceUnavailable(
+            "MoveVM router is not initialized for single-hop quoting".into(),
+        )
+    })?;
+
+    if pool_id == PoolId::DebugUsdc {
+        ensure_debug_pool_and_sync(state, router).await?;
+    }
+
+    // Exact-out inputs are solved by the router, which stays within the pool's sizes.
+    if !exact_out {
+        match router.pool_params(pool_id).await {
+            Ok(params) => {
+                let min_input = {
+                    let session = session_arc.read().await;
+                    let asks = session
+                        .orderbooks
+                        .get(&pool_id)
+                        .map_or(&[][..], |ob| &ob.asks[..]);
+                    min_single_hop_input(&params, is_sell, asks)
+                };
+                check_min_swap_input(
+                    amount,
+                    min_input,
+                    from,
+                    debug_symbol,
+                    pool_id.display_name(),
+                )?;
+            }
+            Err(e) => tracing::debug!(
+                "Skipping minimum size check on {}: {}",
+                pool_id.display_name(),
+                e
+            ),
+        }
+    }
+
+    // Read mid price and DEEP balance without holding lock across await.
+    let (mid_price, deep_budget) = {
+        let session = session_arc.read().await;
+        let mid = session
+            .orderbooks
+            .get(&pool_id)
+            .and_then(|ob| ob.mid_price())
+            .unwrap_or(0.0);
+        (
+            mid,
+            capped_deep_budget(session.balances.deep, deep_fee_budget),
+        )
+    };
+
+    let swap_error = |e: anyhow::Error, min_out: u64| {
+        let err_text = e.to_string();
+        match DeepBookAbort::classify(&err_text) {
+            Some(abort @ DeepBookAbort::MinimumOutputNotMet) => ApiError::DeepBook {
+                deepbook_code: abort.code(),
+                message: format!(
+                    "Slippage exceeded: swap output on {} fell below min_out {}; the swap was not executed",
+                    pool_id.display_name(),
+                    min_out
+                ),
+            },
+            Some(abort) => deepbook_error(abort, pool_id.display_name()),
+            None if err_text.starts_with(EXACT_OUT_UNFILLABLE) => ApiError::BadRequest(err_text),
+            None => ApiError::Internal(format!(
+                "MoveVM single-hop swap failed for {}: {}",
+                pool_id.display_name(),
+                err_text
+            )),
+        }
+    };
+    // With exact_out, `amount` is the target output: the router solves for the
+    // input (capped at the session balance) and `amount` becomes that input.
+    let (vm_swap, amount, exact_out) = if exact_out {
+        let max_input = session_arc.read().await.balances.get(from);
+        let result = router
+            .execute_exact_out_swap(
+                pool_id,
+                amount,
+                max_input,
+                deep_budget,
+                is_sell,
+                sender,
+                simulate,
+            )
+            .await
+            .map_err(router_error(|e| swap_error(e, amount)))?;
+        let exact_out = ExactOut {
+            target_output: amount.to_string(),
+            target_output_human: units::to_human(amount, get_decimals(to, debug_symbol)),
+            required_input: result.input_amount.to_string(),
+            required_input_human: units::to_human(
+                result.input_amount,
+                get_decimals(from, debug_symbol),
+            ),
+            quote_iterations: result.quote_iterations,
+        };
+        (result.swap, result.input_amount, Some(exact_out))
+    } else {
+        let vm_swap = router
+            .execute_single_hop_swap(
+                pool_id,
+                amount,
+                deep_budget,
+                is_sell,
+                min_out,
+                sender,
+                simulate,
+            )
+            .await
+            .map_err(router_error(|e| swap_error(e, min_out)))?;
+        (vm_swap, amount, None)
+    };
+    if !simulate {
+        record_trades(state, &[pool_id], &vm_swap.events).await;
+    }
+    if vm_swap.output_amount == 0 {
+        return Err(ApiError::BadRequest(format!(
+            "No output returned by MoveVM swap for {}",
+            pool_id.display_name()
+        )));
+    }
+
+    let consumed_input = amount.saturating_sub(vm_swap.input_refund);
+    let input_human = units::to_human(consumed_input, get_decimals(from, debug_symbol));
+    let output_human = units::to_human(vm_swap.output_amount, get_decimals(to, debug_symbol));
+    let effective_price = if is_sell {
+        if input_human > 0.0 {
+            output_human / input_human
+        } else {
+            0.0
+        }
+    } else if output_human > 0.0 {
+        input_human / output_human
+    } else {
+        0.0
+    };
+    let effective_price_str = single_hop_price_str(
+        is_sell,
+        consumed_input,
+        get_decimals(from, debug_symbol),
+        vm_swap.output_amount,
+        get_decimals(to, debug_symbol),
+    );
+
+    let price_impact_bps = units::price_impact_bps(mid_price, effective_price, is_sell);
+
+    // Refunds go back into the reserves unless the join failed and the
+    // router fell back to transferring them to the sender.
+    let (refund_module, refund_function) = if vm_swap.reserve_reconciled {
+        ("coin", "join")
+    } else {
+        ("transfer", "public_transfer")
+    };
+    let mut commands = vec![
+        CommandInfo {
+            index: 0,
+            command_type: "MoveCall".to_string(),
+            package: "0x2".to_string(),
+            module: "coin".to_string(),
+            function: "split".to_string(),
+            type_args: vec![],
+        },
+        CommandInfo {
+            index: 1,
+            command_type: "MoveCall".to_string(),
+            package: "0x2".to_string(),
+            module: "coin".to_string(),
+            function: "split".to_string(),
+            type_args: vec![],
+        },
+        CommandInfo {
+            index: 2,
+            command_type: "MoveCall".to_string(),
+            package: "0x2c8d603bc51326b8c13cef9dd07031a408a48dddb541963357661df5d3204809"
+                .to_string(),
+            module: "pool".to_string(),
+            function: if is_sell {
+                "swap_exact_base_for_quote".to_string()
+            } else {
+                "swap_exact_quote_for_base".to_string()
+            },
+            type_args: vec![],
+        },
+        CommandInfo {
+            index: 3,
+            command_type: "MoveCall".to_string(),
+            package: "0x2".to_string(),
+            module: "coin".to_string(),
+            function: "value".to_string(),
+            type_args: vec![],
+        },
+        CommandInfo {
+            index: 4,
+            command_type: "MoveCall".to_string(),
+            package: "0x2".to_string(),
+            module: "coin".to_string(),
+            function: "value".to_string(),
+            type_args: vec![],
+        },
+        CommandInfo {
+            index: 5,
+            command_type: "MoveCall".to_string(),
+            package: "0x2".to_string(),
+            module: "coin".to_string(),
+            function: "value".to_string(),
+            type_args: vec![],
+        },
+        CommandInfo {
+            index: 6,
+            command_type: "MoveCall".to_string(),
+            package: "0x2".to_string(),
+            module: refund_module.to_string(),
+            function: refund_function.to_string(),
+            type_args: vec![],
+        },
+        CommandInfo {
+            index: 7,
+            command_type: "MoveCall".to_string(),
+            package: "0x2".to_string(),
+            module: refund_module.to_string(),
+            function: refund_function.to_string(),
+            type_args: vec![],
+        },
+        CommandInfo {
+            index: 8,
+            command_type: "MoveCall".to_string(),
+            package: "0x2".to_string(),
+            module: "transfer".to_string(),
+            function: "public_transfer".to_string(),
+            type_args: vec![],
+        },
+    ];
+    let mut mutated_objects = vec![
+        pool_id.display_name().to_string(),
+        format!("VMReserveCoin<{}>", from),
+    ];
+    if vm_swap.deep_fee_required {
+        mutated_objects.push("VMReserveCoin<DEEP>".to_string());
+    } else {
+        // Whitelisted pool: coin::zero replaces the DEEP split, the DEEP refund
+        // read is dropped, and coin::destroy_zero replaces the DEEP join.
+        commands[1].function = "zero".to_string();
+        commands[7].module = "coin".to_string();
+        commands[7].function = "destroy_zero".to_string();
+        commands.remove(5);
+        for (index, cmd) in commands.iter_mut().enumerate() {
+            cmd.index = index;
+        }
+    }
+    let events: Vec<EventInfo> = vm_swap
+        .events
+        .iter()
+        .map(|e| EventInfo {
+            event_type: e.event_type.clone(),
+            data: event_data(e),
+        })
+        .collect();
+    let ptb_execution = PtbExecution {
+        commands,
+        status: "Success".to_string(),
+        effects_digest: None,
+        events,
+        created_objects: vm_swap.created_objects.clone(),
+        mutated_objects,
+        deleted_objects: vec![],
+        command_effects: vm_swap.command_effects.clone(),
+    };
+
+    let mut session = session_arc.write().await;
+    // A simulated swap is applied and undone, so `balances_after` still
+    // shows what executing it would leave.
+    let undo = simulate.then(|| session.swap_undo());
+    let execution_time = start.elapsed().as_millis() as u64;
+    let result = session.apply_vm_swap(
+        from,
+        to,
+        "direct",
+        amount,
+        vm_swap.input_refund,
+        deep_budget,
+        vm_swap.deep_refund,
+        vm_swap.output_amount,
+        effective_price,
+        vm_swap.gas_used,
+        execution_time,
+        ptb_execution,
+    );
+    // Refunds transferred to the sender left the reserves along with the fill.
+    if result.is_ok() && !vm_swap.reserve_reconciled {
+        session.record_swap_draw(from, vm_swap.input_refund);
+        if vm_swap.deep_fee_required {
+            session.record_swap_draw("DEEP", vm_swap.deep_refund);
+        }
+    }
+    if let Some(undo) = undo {
+        session.undo_swap(undo);
+    }
+
+    match result {
+        Ok(swap_result) => {
+            let input_human = units::to_human(consumed_input, get_decimals(from, debug_symbol));
+            let output_human = units::to_human(swap_result.output_amount, get_decimals(to, debug_symbol));
+            let requested_input_human = units::to_human(amount, get_decimals(from, debug_symbol));
+
+            let mut message = format!(
+                "Successfully traded {:.4} {} (requested {:.4}) for {:.4} {} @ ${:.6}",
+                input_human,
+                from,
+                requested_input_human,
+                output_human,
+                to,
+                swap_result.effective_price
+            );
+            if vm_swap.input_refund > 0 {
+                message.push_str(&partial_fill_note(vm_swap.input_refund, from, debug_symbol));
+            }
+            if !vm_swap.reserve_reconciled {
+                message.push_str(
+                    " (reserve reconciliation failed: refunds were transferred to the sender instead of rejoining the reserve coins)",
+                );
+            }
+
+            let commands: Vec<CommandDetail> = swap_result
+                .ptb_execution
+                .commands
+                .iter()
+                .map(|cmd| {
+                    // Position in the DEEP-funded layout, which has an extra
+                    // DEEP refund read at index 5.
+                    let layout_index = if !vm_swap.deep_fee_required && cmd.index >= 5 {
+                        cmd.index + 1
+                    } else {
+                        cmd.index
+                    };
+                    let description = match cmd.function.as_str() {
+                        "split" => match layout_index {
+                            0 => format!("Split {} input coin from VM reserve", from),
+                            1 => "Split DEEP fee coin from VM reserve".to_string(),
+                            _ => "Split coin from VM reserve".to_string(),
+                        },
+                        "zero" => {
+                            "Create empty DEEP coin (whitelisted pool charges no fees)".to_string()
+                        }
+                        "destroy_zero" => "Destroy the unused empty DEEP coin".to_string(),
+                        "swap_exact_base_for_quote" => {
+                            format!("Execute DeepBook market sell: {} -> {}", from, to)
+                        }
+                        "swap_exact_quote_for_base" => {
+                            format!("Execute DeepBook market buy: {} -> {}", from, to)
+                        }
+                        "value" => match layout_index {
+                            3 => format!("Read {} output amount from VM return coin", to),
+                            4 => format!("Read {} refund amount from VM return coin", from),
+                            5 => "Read DEEP refund amount from VM return coin".to_string(),
+                            _ => "Read coin amount from VM return object".to_string(),
+                        },
+                        "join" => match layout_index {
+                            6 => format!("Join {} refund back into VM reserve", from),
+                            7 => "Join DEEP refund back into VM reserve".to_string(),
+                            _ => "Join refund coin back into VM reserve".to_string(),
+                        },
+                        "public_transfer" => match layout_index {
+                            8 => format!("Transfer {} output coin to sender", to),
+                            _ => "Transfer returned coin to sender".to_string(),
+                        },
+                        _ => format!("{}::{}", cmd.module, cmd.function),
+                    };
+                    CommandDetail {
+                        index: cmd.index,
+                        command_type: cmd.command_type.clone(),
+                        package: cmd.package.clone(),
+                        module: cmd.module.clone(),
+                        function: cmd.function.clone(),
+                        type_args: cmd.type_args.clone(),
+                        description,
+                        effects: swap_result
+                            .ptb_execution
+                            .command_effects
+                            .iter()
+                            .find(|effect| effect.index == cmd.index)
+                            .cloned(),
+                    }
+                })
+                .collect();
+
+            let summary = format!(
+                "PTB executed {} commands via MoveVM: reserve coin splits -> pool::swap_exact_* on {} -> coin::value(...) -> refund joins -> output transfer.",
+                commands.len(),
+                pool_id.display_name()
+            );
+
+            Ok(Json(SwapResponse {
+                success: true,
+                error: None,
+                input_token: from.to_string(),
+                output_token: to.to_string(),
+                input_amount: amount.to_string(),
+                input_amount_human: units::to_human(amount, get_decimals(from, debug_symbol)),
+                output_amount: swap_result.output_amount.to_string(),
+                output_amount_human: output_human,
+                fully_filled: vm_swap.input_refund == 0,
+                remaining_input: vm_swap.input_refund.to_string(),
+                effective_price: swap_result.effective_price,
+                effective_price_str,
+                price_impact_bps,
+                gas_used: swap_result.gas_used.to_string(),
+                execution_time_ms: execution_time,
+                execution_method: "Move VM DeepBook PTB Execution".to_string(),
+                message,
+                ptb_execution: PtbExecutionInfo {
+                    commands,
+                    status: swap_result.ptb_execution.status,
+                    effects_digest: swap_result.ptb_execution.effects_digest,
+                    events: swap_result
+                        .ptb_execution
+                        .events
+                        .iter()
+                        .map(|e| EventDetail {
+                            event_type: e.event_type.clone(),
+                            data: e.data.clone(),
+                        })
+                        .collect(),
+                    summary,
+                },
+                balances_after: BalancesAfter::from(&swap_result.balances_after),
+                route_type: "direct".to_string(),
+                intermediate_amount: None,
+                deep_top_up: None,
+                route_ambiguous: false,
+                two_hop_execution: None,
+                two_hop_legs: None,
+                exact_out,
+                simulated: simulate,
+            }))
+        }
+        Err(e) => {
+            let execution_time = start.elapsed().as_millis() as u64;
+            Ok(Json(SwapResponse {
+                success: false,
+                error: Some(e.to_string()),
+                input_token: from.to_string(),
+                output_token: to.to_string(),
+                input_amount: amount.to_string(),
+                input_amount_human: units::to_human(amount, get_decimals(from, debug_symbol)),
+                output_amount: "0".to_string(),
+                output_amount_human: 0.0,
+                fully_filled: false,
+                remaining_input: amount.to_string(),
+                effective_price: 0.0,
+                effective_price_str: "0".to_string(),
+                price_impact_bps: 0,
+                gas_used: "0".to_string(),
+                execution_time_ms: execution_time,
+                execution_method: "Move VM DeepBook PTB Execution".to_string(),
+                message: format!("Swap failed: {}", e),
+                ptb_execution: PtbExecutionInfo {
+                    commands: vec![],
+                    status: "Failed".to_string(),
+                    effects_digest: None,
+                    events: vec![],
+                    summary: format!("Transaction aborted: {}", e),
+                },
+                balances_after: BalancesAfter::from(&session.balances),
+                route_type: "direct".to_string(),
+                intermediate_amount: None,
+                deep_top_up: None,
+                route_ambiguous: false,
+                two_hop_execution: None,
+                two_hop_legs: None,
+                exact_out,
+                simulated: simulate,
+            }))
+        }
+    }
+}
+
+/// Execute a two-hop swap: from_token -> shared quote coin -> to_token.
+/// Runs a real chained MoveVM PTB with two DeepBook pool::swap_exact_* calls.
+/// `min_intermediate` bounds the quote leg and `min_out` the final output.
+async fn execute_two_hop_swap(
+    state: &AppState,
+    session_arc: std::sync::Arc<tokio::sync::RwLock<crate::sandbox::swap_executor::TradingSession>>,
+    first_pool: PoolId,
+    second_pool: PoolId,
+    from: &str,
+    to: &str,
+    debug_symbol: &str,
+    amount: u64,
+    min_intermediate: u64,
+    min_out: u64,
+    deep_fee_budget: Option<u64>,
+    sender: Option<String>,
+    simulate: bool,
+    start: std::time::Instant,
+) -> ApiResult<Json<SwapResponse>> {
+    let router = state.router.as_ref().ok_or_else(|| {
+        ApiError::ServiceUnavailable("MoveVM router is not initialized for two-hop quoting".into())
+    })?;
+
+    if first_pool == PoolId::DebugUsdc || second_pool == PoolId::DebugUsdc {
+        ensure_debug_pool_and_sync(state, router).await?;
+    }
+
+    match tokio::try_join!(
+        router.pool_params(first_pool),
+        router.pool_params(second_pool)
+    ) {
+        Ok((first_params, second_params)) => {
+            let min_input = {
+                let session = session_arc.read().await;
+                let levels = |pool_id: PoolId, bids: bool| {
+                    session.orderbooks.get(&pool_id).map_or(&[][..], |ob| {
+                        if bids {
+                            &ob.bids[..]
+                        } else {
+                            &ob.asks[..]
+                        }
+                    })
+                };
+                min_two_hop_input(
+                    &first_params,
+                    levels(first_pool, true),
+                    &second_params,
+                    levels(second_pool, false),
+                )
+            };
+            check_min_swap_input(
+                amount,
+                min_input,
+                from,
+                debug_symbol,
+                &format!(
+                    "{} -> {}",
+                    first_pool.display_name(),
+                    second_pool.display_name()
+                ),
+            )?;
+        }
+        Err(e) => tracing::debug!("Skipping two-hop minimum size check: {}", e),
+    }
+
+    // Ensure both pools exist and compute mids without holding lock across await.
+    let (first_mid, second_mid, deep_budget) = {
+        let session = session_arc.read().await;
+        (
+            session
+                .orderbooks
+                .get(&first_pool)
+                .and_then(|ob| ob.mid_price())
+                .unwrap_or(0.0),
+            session
+                .orderbooks
+                .get(&second_pool)
+                .and_then(|ob| ob.mid_price())
+                .unwrap_or(0.0),
+            capped_deep_budget(session.balances.deep, deep_fee_budget),
+        )
+    };
+
+    let vm_swap = router
+        .execute_two_hop_swap(
+            first_pool,
+            second_pool,
+            amount,
+            deep_budget,
+            min_intermediate,
+            min_out,
+            sender,
+            simulate,
+        )
+        .await;
+    if let Err(e) = &vm_swap {
+        if let Some(failed) = e.downcast_ref::<TwoHopSecondLegFailed>() {
+            return Err(settle_two_hop_first_leg(
+                state,
+                &session_arc,
+                failed,
+                from,
+                debug_symbol,
+                amount,
+                deep_budget,
+                simulate,
+                start,
+            )
+            .await);
+        }
+    }
+    let vm_swap = vm_swap.map_err(router_error(|e| {
+            let err_text = e.to_string();
+            let route = format!(
+                "{} -> {}",
+                first_pool.display_name(),
+                second_pool.display_name()
+            );
+            match DeepBookAbort::classify(&err_text) {
+                Some(abort @ DeepBookAbort::MinimumOutputNotMet) if min_intermediate > 0 => {
+                    ApiError::DeepBook {
+                        deepbook_code: abort.code(),
+                        message: format!(
+                            "Slippage exceeded: two-hop swap fell below min_intermediate_amount {} or min_out {}; the swap was not executed ({}).",
+                            min_intermediate, min_out, route
+                        ),
+                    }
+                }
+                Some(abort @ DeepBookAbort::MinimumOutputNotMet) => ApiError::DeepBook {
+                    deepbook_code: abort.code(),
+                    message: format!(
+                        "Slippage exceeded: two-hop swap output fell below min_out {}; the swap was not executed ({}).",
+                        min_out, route
+                    ),
+                },
+                Some(abort @ DeepBookAbort::QuantityTooSmall) => ApiError::DeepBook {
+                    deepbook_code: abort.code(),
+                    message: format!(
+                        "Two-hop swap amount is too small for DeepBook execution on at least one leg; increase input amount and retry ({}).",
+                        route
+                    ),
+                },
+                Some(abort) => deepbook_error(abort, &route),
+                None => ApiError::Internal(format!(
+                    "MoveVM two-hop swap failed ({}): {}",
+                    route, err_text
+                )),
+            }
+        }))?;
+    if !simulate {
+        record_trades(state, &[first_pool, second_pool], &vm_swap.events).await;
+    }
+    if vm_swap.output_amount == 0 {
+        return Err(ApiError::BadRequest(
+            "No output returned by MoveVM two-hop swap".into(),
+        ));
+    }
+
+    // Calculate effective price and impact
+    let from_decimals = get_decimals(from, debug_symbol);
+    let to_decimals = get_decimals(to, debug_symbol);
+    let consumed_input = amount.saturating_sub(vm_swap.input_refund);
+    let input_human = units::to_human(consumed_input, from_decimals);
+    let output_human = units::to_human(vm_swap.output_amount, to_decimals);
+    let intermediate = pool_pairs(state).await.quote_symbol(first_pool);
+    let intermediate_decimals = get_decimals(&intermediate, debug_symbol);
+    let intermediate_human = units::to_human(vm_swap.intermediate_amount, intermediate_decimals);
+
+    let effective_price = if input_human > 0.0 {
+        output_human / input_human
+    } else {
+        0.0
+    };
+    let effective_price_str = exact_ratio_str(
+        vm_swap.output_amount,
+        to_decimals,
+        consumed_input,
+        from_decimals,
+    );
+
+    // Estimate price impact from both legs using session orderbooks
+    let mid_price = two_hop_mid(first_mid, second_mid);
+    let price_impact_bps = units::price_impact_bps(mid_price, effective_price, true);
+
+    let commands = vec![
+        CommandInfo {
+            index: 0,
+            command_type: "MoveCall".to_string(),
+            package: "0x2".to_string(),
+            module: "coin".to_string(),
+            function: "split".to_string(),
+            type_args: vec![],
+        },
+        CommandInfo {
+            index: 1,
+            command_type: "MoveCall".to_string(),
+            package: "0x2".to_string(),
+            module: "coin".to_string(),
+            function: "split".to_string(),
+            type_args: vec![],
+        },
+        CommandInfo {
+            index: 2,
+            command_type: "MoveCall".to_string(),
+            package: "0x2c8d603bc51326b8c13cef9dd07031a408a48dddb541963357661df5d3204809"
+                .to_string(),
+            module: "pool".to_string(),
+            function: "swap_exact_base_for_quote".to_string(),
+            type_args: vec![],
+        },
+        CommandInfo {
+            index: 3,
+            command_type: "MoveCall".to_string(),
+            package: "0x2".to_string(),
+            module: "coin".to_string(),
+            function: "value".to_string(),
+            type_args: vec![],
+        },
+        CommandInfo {
+            index: 4,
+            command_type: "MoveCall".to_string(),
+            package: "0x2c8d603bc51326b8c13cef9dd07031a408a48dddb541963357661df5d3204809"
+                .to_string(),
+            module: "pool".to_string(),
+            function: "swap_exact_quote_for_base".to_string(),
+            type_args: vec![],
+        },
+        CommandInfo {
+            index: 5,
+            command_type: "MoveCall".to_string(),
+            package: "0x2".to_string(),
+            module: "coin".to_string(),
+            function: "value".to_string(),
+            type_args: vec![],
+        },
+        CommandInfo {
+            index: 6,
+            command_type: "MoveCall".to_string(),
+            package: "0x2".to_string(),
+            module: "coin".to_string(),
+            function: "value".to_string(),
+            type_args: vec![],
+        },
+        CommandInfo {
+            index: 7,
+            command_type: "MoveCall".to_string(),
+            package: "0x2".to_string(),
+            module: "coin".to_string(),
+            function: "value".to_string(),
+            type_args: vec![],
+        },
+        CommandInfo {
+            index: 8,
+            command_type: "MoveCall".to_string(),
+            package: "0x2".to_string(),
+            module: "coin".to_string(),
+            function: "value".to_string(),
+            type_args: vec![],
+        },
+        CommandInfo {
+            index: 9,
+            command_type: "MoveCall".to_string(),
+            package: "0x2".to_string(),
+            module: "coin".to_string(),
+            function: "join".to_string(),
+            type_args: vec![],
+        },
+        CommandInfo {
+            index: 10,
+            command_type: "MoveCall".to_string(),
+            package: "0x2".to_string(),
+            module: "coin".to_string(),
+            function: "join".to_string(),
+            type_args: vec![],
+        },
+        CommandInfo {
+            index: 11,
+            command_type: "MoveCall".to_string(),
+            package: "0x2".to_string(),
+            module: "coin".to_string(),
+            function: "join".to_string(),
+            type_args: vec![],
+        },
+        CommandInfo {
+            index: 12,
+            command_type: "MoveCall".to_string(),
+            package: "0x2".to_string(),
+            module: "transfer".to_string(),
+            function: "public_transfer".to_string(),
+            type_args: vec![],
+        },
+    ];
+    let events: Vec<EventInfo> = vm_swap
+        .events
+        .iter()
+        .map(|e| EventInfo {
+            event_type: e.event_type.clone(),
+            data: event_data(e),
+        })
+        .collect();
+    let ptb_execution = PtbExecution {
+        commands,
+        status: "Success".to_string(),
+        effects_digest: None,
+        events,
+        created_objects: vm_swap.created_objects.clone(),
+        mutated_objects: vec![
+            first_pool.display_name().to_string(),
+            second_pool.display_name().to_string(),
+            format!("VMReserveCoin<{}>", from),
+            format!("VMReserveCoin<{}>", intermediate),
+            "VMReserveCoin<DEEP>".to_string(),
+        ],
+        deleted_objects: vec![],
+        command_effects: vm_swap.command_effects.clone(),
+    };
+
+    let mut session = session_arc.write().await;
+    // A simulated swap is applied and undone, so `balances_after` still
+    // shows what executing it would leave.
+    let undo = simulate.then(|| session.swap_undo());
+    let execution_time = start.elapsed().as_millis() as u64;
+    let result = session.apply_vm_swap(
+        from,
+        to,
+        "two_hop",
+        amount,
+        vm_swap.input_refund,
+        deep_budget,
+        vm_swap.deep_refund,
+        vm_swap.output_amount,
+        effective_price,
+        vm_swap.gas_used,
+        execution_time,
+        ptb_execution,
+    );
+    if let Some(undo) = undo {
+        session.undo_swap(undo);
+    }
+
+    match result {
+        Ok(swap_result) => {
+            let requested_input_human = units::to_human(amount, get_decimals(from, debug_symbol));
+
+            let mut message = format!(
+                "Successfully traded {:.4} {} (requested {:.4}) -> {:.2} {} -> {:.4} {} (two-hop)",
+                input_human,
+                from,
+                requested_input_human,
+                intermediate_human,
+                intermediate,
+                output_human,
+                to
+            );
+            if vm_swap.input_refund > 0 {
+                message.push_str(&partial_fill_note(vm_swap.input_refund, from, debug_symbol));
+            }
+
+            let commands: Vec<CommandDetail> = swap_result
+                .ptb_execution
+                .commands
+                .iter()
+                .map(|cmd| {
+                    let description = match cmd.function.as_str() {
+                        "split" => match cmd.index {
+                            0 => format!("Split {} input coin from VM reserve", from),
+                            1 => "Split DEEP fee coin from VM reserve".to_string(),
+                            _ => "Split coin from VM reserve".to_string(),
+                        },
+                        "swap_exact_base_for_quote" => {
+                            format!("Execute first leg: {} -> {}", from, intermediate)
+                        }
+                        "swap_exact_quote_for_base" => {
+                            format!("Execute second leg: {} -> {}", intermediate, to)
+                        }
+                        "value" => match cmd.index {
+                            3 => format!("Read intermediate {} output from leg 1", intermediate),
+                            5 => format!("Read {} output amount from leg 2", to),
+                            6 => format!("Read {} refund amount from leg 1", from),
+                            7 => format!("Read {} refund amount from leg 2", intermediate),
+                            8 => "Read DEEP refund amount from leg 2".to_string(),
+                            _ => "Read coin amount from VM return object".to_string(),
+                        },
+                        "join" => match cmd.index {
+                            9 => format!("Join {} refund back into VM reserve", from),
+                            10 => format!("Join {} refund back into VM reserve", intermediate),
+                            11 => "Join DEEP refund back into VM reserve".to_string(),
+                            _ => "Join refund coin back into VM reserve".to_string(),
+                        },
+                        "public_transfer" => match cmd.index {
+                            12 => format!("Transfer {} output coin to sender", to),
+                            _ => "Transfer returned coin to sender".to_string(),
+                        },
+                        _ => format!("{}::{}", cmd.module, cmd.function),
+                    };
+                    CommandDetail {
+                        index: cmd.index,
+                        command_type: cmd.command_type.clone(),
+                        package: cmd.package.clone(),
+                        module: cmd.module.clone(),
+                        function: cmd.function.clone(),
+                        type_args: cmd.type_args.clone(),
+                        description,
+                        effects: swap_result
+                            .ptb_execution
+                            .command_effects
+                            .iter()
+                            .find(|effect| effect.index == cmd.index)
+                            .cloned(),
+                    }
+                })
+                .collect();
+
+            let summary = if vm_swap.sequential_fallback {
+                format!(
+                    "Atomic PTB failed after {} attempt(s); executed as two sequential single-hop MoveVM PTBs: {} -> {}, then {} -> {}.",
+                    vm_swap.atomic_attempts, from, intermediate, intermediate, to
+                )
+            } else {
+                format!(
+                    "PTB executed {} commands via MoveVM: reserve coin splits -> pool::swap_exact_base_for_quote({} -> {}) -> pool::swap_exact_quote_for_base({} -> {}) -> coin::value(...) -> refund joins -> output transfer.",
+                    commands.len(), from, intermediate, intermediate, to
+                )
+            };
+            let two_hop_execution = TwoHopExecution {
+                atomic_attempts: vm_swap.atomic_attempts,
+                mode: if vm_swap.sequential_fallback {
+                    "sequential".to_string()
+                } else {
+                    "atomic".to_string()
+                },
+            };
+
+            Ok(Json(SwapResponse {
+                success: true,
+                error: None,
+                input_token: from.to_string(),
+                output_token: to.to_string(),
+                input_amount: amount.to_string(),
+                input_amount_human: units::to_human(amount, from_decimals),
+                output_amount: swap_result.output_amount.to_string(),
+                output_amount_human: output_human,
+                fully_filled: vm_swap.input_refund == 0,
+                remaining_input: vm_swap.input_refund.to_string(),
+                effective_price: swap_result.effective_price,
+                effective_price_str,
+                price_impact_bps,
+                gas_used: swap_result.gas_used.to_string(),
+                execution_time_ms: execution_time,
+                execution_method: "Move VM Two-Hop Pool PTB Execution".to_string(),
+                message,
+                ptb_execution: PtbExecutionInfo {
+                    commands,
+                    status: swap_result.ptb_execution.status,
+                    effects_digest: swap_result.ptb_execution.effects_digest,
+                    events: swap_result
+                        .ptb_execution
+                        .events
+                        .iter()
+                        .map(|e| EventDetail {
+                            event_type: e.event_type.clone(),
+                            data: e.data.clone(),
+                        })
+                        .collect(),
+                    summary,
+                },
+                balances_after: BalancesAfter::from(&swap_result.balances_after),
+                route_type: "two_hop".to_string(),
+                intermediate_amount: Some(intermediate_human),
+                deep_top_up: None,
+                route_ambiguous: false,
+                two_hop_execution: Some(two_hop_execution),
+                two_hop_legs: Some(TwoHopLegs {
+                    first_leg_fully_filled: vm_swap.input_refund == 0,
+                    first_leg_input: amount.to_string(),
+                    first_leg_refund: vm_swap.input_refund.to_string(),
+                    first_leg_refund_human: units::to_human(vm_swap.input_refund, from_decimals),
+                    second_leg_fully_filled: vm_swap.quote_refund == 0,
+                    second_leg_input: vm_swap.intermediate_amount.to_string(),
+                    second_leg_refund: vm_swap.quote_refund.to_string(),
+                    second_leg_refund_human: units::to_human(
+                        vm_swap.quote_refund,
+                        intermediate_decimals,
+                    ),
+                }),
+                exact_out: None,
+                simulated: simulate,
+            }))
+        }
+        Err(e) => {
+            let execution_time = start.elapsed().as_millis() as u64;
+            Ok(Json(SwapResponse {
+                success: false,
+                error: Some(e.to_string()),
+                input_token: from.to_string(),
+                output_token: to.to_string(),
+                input_amount: amount.to_string(),
+                input_amount_human: units::to_human(amount, get_decimals(from, debug_symbol)),
+                output_amount: "0".to_string(),
+                output_amount_human: 0.0,
+                fully_filled: false,
+                remaining_input: amount.to_string(),
+                effective_price: 0.0,
+                effective_price_str: "0".to_string(),
+                price_impact_bps: 0,
+                gas_used: "0".to_string(),
+                execution_time_ms: execution_time,
+                execution_method: "Move VM Two-Hop Pool PTB Execution".to_string(),
+                message: format!("Two-hop swap failed: {}", e),
+                ptb_execution: PtbExecutionInfo {
+                    commands: vec![],
+                    status: "Failed".to_string(),
+                    effects_digest: None,
+                    events: vec![],
+                    summary: format!("Two-hop transaction aborted: {}", e),
+                },
+                balances_after: BalancesAfter::from(&session.balances),
+                route_type: "two_hop".to_string(),
+                intermediate_amount: None,
+                deep_top_up: None,
+                route_ambiguous: false,
+                two_hop_execution: None,
+                two_hop_legs: None,
+                exact_out: None,
+                simulated: simulate,
+            }))
+        }
+    }
+}
+
+/// Account for a sequential two-hop fallback that committed leg 1 before leg 2
+/// failed: the session is settled as a direct swap into the first pool's quote
+/// coin and the returned error tells the caller how much of it they now hold.
+async fn settle_two_hop_first_leg(
+    state: &AppState,
+    session_arc: &std::sync::Arc<
+        tokio::sync::RwLock<crate::sandbox::swap_executor::TradingSession>,
+    >,
+    failed: &TwoHopSecondLegFailed,
+    from: &str,
+    debug_symbol: &str,
+    amount: u64,
+    deep_budget: u64,
+    simulate: bool,
+    start: std::time::Instant,
+) -> ApiError {
+    let leg = &failed.first_leg;
+    let route = format!(
+        "{} -> {}",
+        failed.from_pool.display_name(),
+        failed.to_pool.display_name()
+    );
+    let intermediate = pool_pairs(state).await.quote_symbol(failed.from_pool);
+    let intermediate_human =
+        units::to_human(leg.output_amount, get_decimals(&intermediate, debug_symbol));
+    let consumed_input = amount.saturating_sub(leg.input_refund);
+    let input_human = units::to_human(consumed_input, get_decimals(from, debug_symbol));
+
+    let message = if simulate {
+        format!(
+            "Two-hop swap leg 2 failed on the sequential fallback ({}); executed, leg 1 would have left you holding {:.6} {} ({} raw) for {:.4} {}. Leg 2 error: {}",
+            route, intermediate_human, intermediate, leg.output_amount, input_human, from, failed.error
+        )
+    } else {
+        record_trades(state, &[failed.from_pool], &leg.events).await;
+
+        let effective_price = if input_human > 0.0 {
+            intermediate_human / input_human
+        } else {
+            0.0
+        };
+        let ptb_execution = PtbExecution {
+            commands: vec![CommandInfo {
+                index: 0,
+                command_type: "MoveCall".to_string(),
+                package: "0x2c8d603bc51326b8c13cef9dd07031a408a48dddb541963357661df5d3204809"
+                    .to_string(),
+                module: "pool".to_string(),
+                function: "swap_exact_base_for_quote".to_string(),
+                type_args: vec![],
+            }],
+            status: "Success".to_string(),
+            effects_digest: None,
+            events: leg
+                .events
+                .iter()
+                .map(|e| EventInfo {
+                    event_type: e.event_type.clone(),
+                    data: event_data(e),
+                })
+                .collect(),
+            created_objects: leg.created_objects.clone(),
+            mutated_objects: vec![
+                failed.from_pool.display_name().to_string(),
+                format!("VMReserveCoin<{}>", from),
+                format!("VMReserveCoin<{}>", intermediate),
+            ],
+            deleted_objects: vec![],
+            command_effects: leg.command_effects.clone(),
+        };
+
+        let mut session = session_arc.write().await;
+        let result = session.apply_vm_swap(
+            from,
+            &intermediate,
+            "direct",
+            amount,
+            leg.input_refund,
+            deep_budget,
+            leg.deep_refund,
+            leg.output_amount,
+            effective_price,
+            leg.gas_used,
+            start.elapsed().as_millis() as u64,
+            ptb_execution,
+        );
+        match result {
+            Ok(_) => {
+                if !leg.reserve_reconciled {
+                    session.record_swap_draw(from, leg.input_refund);
+                    if leg.deep_fee_required {
+                        session.record_swap_draw("DEEP", leg.deep_refund);
+                    }
+                }
+                format!(
+                    "Two-hop swap leg 2 failed on the sequential fallback ({}) after leg 1 committed: you now hold {:.6} {} ({} raw) from {:.4} {}. Leg 2 error: {}",
+                    route, intermediate_human, intermediate, leg.output_amount, input_human, from, failed.error
+                )
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "Could not apply committed two-hop leg 1 ({}) to the session: {}",
+                    route,
+                    e
+                );
+                format!(
+                    "Two-hop swap leg 2 failed on the sequential fallback ({}) after leg 1 committed {:.6} {} ({} raw) in the VM, but the session could not be updated ({}). Leg 2 error: {}",
+                    route, intermediate_human, intermediate, leg.output_amount, e, failed.error
+                )
+            }
+        }
+    };
+
+    match DeepBookAbort::classify(&failed.error) {
+        Some(abort) => ApiError::DeepBook {
+            deepbook_code: abort.code(),
+            message,
+        },
+        None => ApiError::Internal(message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// SUI -> USDC swap of 1.5 SUI, with `fields` overriding the defaults
+    fn swap_request(fields: serde_json::Value) -> SwapRequest {
+        let mut body = json!({
+            "session_id": "session-a",
+            "from_token": "SUI",
+            "to_token": "USDC",
+            "amount": "1.5",
+        });
+        body.as_object_mut()
+            .unwrap()
+            .extend(fields.as_object().unwrap().clone());
+        serde_json::from_value(body).unwrap()
+    }
+
+    fn bad_request<T>(result: ApiResult<T>) -> String {
+        match result {
+            Err(ApiError::BadRequest(message)) => message,
+            Err(e) => panic!("unexpected error: {}", e),
+            Ok(_) => panic!("expected the request to be rejected"),
+        }
+    }
+
+    const DIRECT: Route = Route::SinglePool(PoolId::SuiUsdc);
+    const TWO_HOP: Route = Route::TwoHop {
+        first_pool: PoolId::SuiUsdc,
+        second_pool: PoolId::WalUsdc,
+    };
+
+    #[test]
+    fn test_validate_swap_request_parses_amounts_per_mode() {
+        let validate = |fields, route: &Route, to| {
+            validate_swap_request(&swap_request(fields), route, "SUI", to, "DBG", None)
+        };
+
+        let options = validate(json!({}), &DIRECT, "USDC").unwrap();
+        assert_eq!(options.amount, 1_500_000_000);
+        assert!(!options.exact_out && !options.simulate);
+        assert_eq!(
+            (options.min_output_amount, options.deep_fee_budget),
+            (0, None)
+        );
+
+        // exact_out amounts are in the output token
+        let options = validate(json!({"swap_mode": "exact_out"}), &DIRECT, "USDC").unwrap();
+        assert!(options.exact_out);
+        assert_eq!(options.amount, 1_500_000);
+
+        let options = validate(
+            json!({"min_intermediate_amount": "25", "min_output_amount": "7"}),
+            &TWO_HOP,
+            "WAL",
+        )
+        .unwrap();
+        assert_eq!(options.min_intermediate_amount, 25);
+        assert_eq!(options.min_output_amount, 7);
+    }
+
+    #[test]
+    fn test_validate_swap_request_rejects_incompatible_options() {
+        let reject = |fields, route: &Route, slippage_bps| {
+            bad_request(validate_swap_request(
+                &swap_request(fields),
+                route,
+                "SUI",
+                "USDC",
+                "DBG",
+                slippage_bps,
+            ))
+        };
+
+        for (fields, route, slippage_bps, expected) in [
+            (json!({"amount": "1.5x"}), DIRECT, None, "Invalid amount"),
+            (
+                json!({"min_output_amount": "abc"}),
+                DIRECT,
+                None,
+                "Invalid min_output_amount",
+            ),
+            (
+                json!({"deep_fee_budget": "-1"}),
+                DIRECT,
+                None,
+                "Invalid deep_fee_budget",
+            ),
+            (
+                json!({"swap_mode": "market"}),
+                DIRECT,
+                None,
+                "Invalid swap_mode 'market'",
+            ),
+            (
+                json!({"min_intermediate_amount": "1"}),
+                DIRECT,
+                None,
+                "only applies to two-hop swaps",
+            ),
+            (
+                json!({"simulate": true, "auto_top_up_deep": true}),
+                DIRECT,
+                None,
+                "not supported with simulate",
+            ),
+            (
+                json!({"swap_mode": "exact_out"}),
+                TWO_HOP,
+                None,
+                "only supported for direct",
+            ),
+            (
+                json!({"swap_mode": "exact_out"}),
+                DIRECT,
+                Some(50),
+                "already enforces amount",
+            ),
+            (
+                json!({"swap_mode": "exact_out", "deep_fee_budget": "10"}),
+                DIRECT,
+                None,
+                "deep_fee_budget is not supported",
+            ),
+        ] {
+            let message = reject(fields, &route, slippage_bps);
+            assert!(message.contains(expected), "{}", message);
+        }
+
+        let hop = RouteHop {
+            pool_id: PoolId::SuiUsdc,
+            is_sell_base: true,
+        };
+        let message = reject(json!({}), &Route::ThreeHop { hops: [hop; 3] }, None);
+        assert!(
+            message.contains("can be quoted but not executed"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn test_slippage_guard_enforces_the_stricter_bound() {
+        let guard = SlippageGuard::new(10_000, 50, 0);
+        assert_eq!((guard.quoted_output, guard.min_out), (10_000, 9_950));
+        let guard = SlippageGuard::new(10_000, 50, 9_990);
+        assert_eq!((guard.quoted_output, guard.min_out), (10_000, 9_990));
+        assert_eq!(SlippageGuard::new(10_000, 10_000, 0).min_out, 0);
+
+        assert_eq!(protected_slippage_bps(None, 50).unwrap(), 50);
+        assert_eq!(protected_slippage_bps(Some(0), 50).unwrap(), 0);
+        assert_eq!(protected_slippage_bps(Some(10_000), 50).unwrap(), 10_000);
+        let message = bad_request(protected_slippage_bps(Some(10_001), 50));
+        assert_eq!(message, "slippage_bps must be at most 10000");
+    }
+
+    #[test]
+    fn test_check_min_swap_input_rejects_amounts_below_the_pool_minimum() {
+        check_min_swap_input(1_000, Some(1_000), "SUI", "DBG", "sui_usdc").unwrap();
+        check_min_swap_input(1, None, "SUI", "DBG", "sui_usdc").unwrap();
+
+        let message = bad_request(check_min_swap_input(
+            999,
+            Some(1_000),
+            "SUI",
+            "DBG",
+            "sui_usdc",
+        ));
+        assert!(
+            message.contains("below the minimum order size on sui_usdc; send at least 1000"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn test_circuit_failures_exclude_caller_mistakes() {
+        let deepbook = |abort: DeepBookAbort| ApiError::DeepBook {
+            deepbook_code: abort.code(),
+            message: String::new(),
+        };
+        assert!(is_circuit_failure(&ApiError::Internal("vm".into())));
+        assert!(is_circuit_failure(&ApiError::RouterTimeout("slow".into())));
+        assert!(is_circuit_failure(&deepbook(
+            DeepBookAbort::InsufficientLiquidity
+        )));
+        assert!(!is_circuit_failure(&deepbook(
+            DeepBookAbort::MinimumOutputNotMet
+        )));
+        assert!(!is_circuit_failure(&ApiError::BadRequest("amount".into())));
+    }
+
+    #[tokio::test]
+    async fn test_with_idempotency_replays_and_rejects_reused_keys() {
+        let state = AppState::for_tests();
+        let req = swap_request(json!({"idempotency_key": "k1"}));
+        let respond = |n: u64| async move { Ok::<_, ApiError>(json!({ "n": n })) };
+        let body = |response: Response| async move {
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            serde_json::from_slice::<serde_json::Value>(&bytes).unwrap()
+        };
+
+        let first = with_idempotency(&state, &req, "swap:a".into(), respond(1))
+            .await
+            .unwrap();
+        assert!(first.headers().get(IDEMPOTENT_REPLAYED_HEADER).is_none());
+        assert_eq!(body(first).await, json!({"n": 1}));
+
+        let replayed = with_idempotency(&state, &req, "swap:a".into(), respond(2))
+            .await
+            .unwrap();
+        assert_eq!(replayed.headers()[IDEMPOTENT_REPLAYED_HEADER], "true");
+        assert_eq!(body(replayed).await, json!({"n": 1}));
+
+        let err = with_idempotency(&state, &req, "swap:b".into(), respond(3))
+            .await
+            .map(|_| ())
+            .unwrap_err();
+        assert!(matches!(err, ApiError::Conflict(_)), "{}", err);
+
+        // A failed swap is not cached, so the key can be retried
+        let req = swap_request(json!({"idempotency_key": "k2"}));
+        let failed = async { Err::<serde_json::Value, _>(ApiError::Internal("vm".into())) };
+        assert!(with_idempotency(&state, &req, "swap:a".into(), failed)
+            .await
+            .is_err());
+        let retried = with_idempotency(&state, &req, "swap:a".into(), respond(4))
+            .await
+            .unwrap();
+        assert_eq!(body(retried).await, json!({"n": 4}));
+
+        let req = swap_request(json!({"idempotency_key": "  "}));
+        let message =
+            bad_request(with_idempotency(&state, &req, "swap:a".into(), respond(5)).await);
+        assert_eq!(message, "idempotency_key must not be empty");
+    }
+
+    #[tokio::test]
+    async fn test_swap_once_validates_before_touching_the_vm() {
+        let state = AppState::for_tests();
+
+        let message =
+            bad_request(swap_once(&state, &swap_request(json!({"session_id": ""}))).await);
+        assert_eq!(message, "session_id required");
+
+        // No pool state was loaded, so nothing can be routed
+        let err = swap_once(&state, &swap_request(json!({})))
+            .await
+            .map(|_| ())
+            .unwrap_err();
+        assert!(matches!(err, ApiError::ServiceUnavailable(_)), "{}", err);
+    }
+}
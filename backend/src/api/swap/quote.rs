@@ -0,0 +1,1892 @@
+//! Quote endpoints
+//!
+//! Forward, reverse, batch, two-sided, and mainnet-validated quotes. Each
+//! runs as a rolled-back PTB, so quoting never changes pool state.
+
+use super::*;
+
+#[derive(Debug, Deserialize)]
+pub struct QuoteRequest {
+    pub pool: Option<String>,
+    #[serde(default)]
+    pub from_token: String,
+    #[serde(default)]
+    pub to_token: String,
+    /// Full input coin type (e.g. `0x2::sui::SUI`); overrides `from_token` when set
+    pub from_type: Option<String>,
+    /// Full output coin type; must be set together with `from_type`
+    pub to_type: Option<String>,
+    /// Input amount; accepts the same forms as `SwapRequest::amount`
+    pub amount: String,
+    /// Optional session_id to quote against session-specific orderbook (reflects consumed liquidity)
+    pub session_id: Option<String>,
+    /// Also report the output in raw/human units and the price in both directions
+    #[serde(default)]
+    pub denominations: bool,
+    /// Quote at this VM clock timestamp (ms) instead of the synthetic clock
+    pub clock_timestamp_ms: Option<u64>,
+    /// Walk the orderbook levels in memory to fill `levels_consumed`,
+    /// `orders_matched` and `fully_fillable`
+    pub detailed: Option<bool>,
+    /// Dry-run the swap in MoveVM and report its gas in `estimated_gas`
+    /// (single-pool and two-hop routes)
+    pub include_gas: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QuoteResponse {
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub pool: String,
+    pub input_token: String,
+    pub output_token: String,
+    pub input_amount: String,
+    pub input_amount_human: f64,
+    pub estimated_output: String,
+    pub estimated_output_human: f64,
+    pub effective_price: f64,
+    /// Effective price computed exactly from raw integer amounts
+    pub effective_price_str: String,
+    pub mid_price: f64,
+    /// Raw DeepBook price of the fill snapped to the pool's tick size (down
+    /// for sells, up for buys), usable as a limit order `price`; direct
+    /// routes only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tick_rounded_price: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tick_rounded_price_human: Option<f64>,
+    /// Ticks between `tick_rounded_price` and the mid (negative below it)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price_ticks: Option<f64>,
+    pub price_impact_bps: u32,
+    /// Price levels the input crosses, summed over hops (`detailed` only, else 0)
+    pub levels_consumed: usize,
+    /// Resting orders the input matches, summed over hops (`detailed` only, else 0)
+    pub orders_matched: usize,
+    /// With `detailed`, whether the in-memory books hold enough liquidity for
+    /// every hop; otherwise whether the VM quoted a non-zero output
+    pub fully_fillable: bool,
+    pub route: String,
+    /// "direct" for single-pool, "two_hop" or "three_hop" for cross-pool
+    pub route_type: String,
+    /// Intermediate (shared quote coin) amount for two-hop routes (human-readable)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub intermediate_amount: Option<f64>,
+    /// Output of every hop before the last, in route order (multi-hop only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub intermediate_amounts: Option<Vec<IntermediateAmount>>,
+    /// Checkpoint/build metadata of the pool state this quote reflects
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<OrderbookProvenance>,
+    /// More than one pool could serve this pair; `PREFERRED_POOLS` picked one
+    pub route_ambiguous: bool,
+    /// Output and reciprocal prices, when `denominations` was requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub denominations: Option<QuoteDenominations>,
+    /// Routes compared when no explicit pool was given and several exist, best first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub alternatives: Vec<RouteQuote>,
+    /// Gas a real swap of this quote would use, when `include_gas` was requested
+    /// and the dry run succeeded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_gas: Option<String>,
+}
+
+/// One candidate route scored by the best-route selector
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteQuote {
+    /// Token path, e.g. "WAL -> USDC -> SUI"
+    pub route: String,
+    pub route_type: String,
+    pub pools: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_output: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_output_human: Option<f64>,
+    /// The route the quote was taken on
+    pub selected: bool,
+    /// Why this route could not be quoted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Amount handed from one hop of a multi-hop quote to the next
+#[derive(Debug, Clone, Serialize)]
+pub struct IntermediateAmount {
+    pub token: String,
+    pub amount: String,
+    pub amount_human: f64,
+}
+
+/// Quote output in several denominations, independent of which side is USDC
+#[derive(Debug, Serialize)]
+pub struct QuoteDenominations {
+    pub output_raw: String,
+    pub output_human: f64,
+    /// Output tokens received per input token (e.g. USDC per SUI when selling SUI)
+    pub output_per_input: f64,
+    pub output_per_input_str: String,
+    /// Input tokens spent per output token (e.g. SUI per USDC when selling SUI)
+    pub input_per_output: f64,
+    pub input_per_output_str: String,
+}
+
+impl QuoteDenominations {
+    fn new(input: u64, input_decimals: u8, output: u64, output_decimals: u8) -> Self {
+        let input_human = units::to_human(input, input_decimals);
+        let output_human = units::to_human(output, output_decimals);
+        Self {
+            output_raw: output.to_string(),
+            output_human,
+            output_per_input: if input_human > 0.0 {
+                output_human / input_human
+            } else {
+                0.0
+            },
+            output_per_input_str: exact_ratio_str(output, output_decimals, input, input_decimals),
+            input_per_output: if output_human > 0.0 {
+                input_human / output_human
+            } else {
+                0.0
+            },
+            input_per_output_str: exact_ratio_str(input, input_decimals, output, output_decimals),
+        }
+    }
+}
+
+/// Query parameters for the two-sided quote endpoint
+#[derive(Debug, Deserialize)]
+pub struct TwoSidedQuoteQuery {
+    /// Pool to quote (e.g. sui_usdc, debug_usdc or a POOLS_MANIFEST key)
+    pub pool: String,
+    /// Base amount in smallest units
+    pub amount: String,
+}
+
+/// One direction of a two-sided quote
+#[derive(Debug, Serialize)]
+pub struct QuoteLeg {
+    pub input_token: String,
+    pub output_token: String,
+    pub input_amount: String,
+    pub output_amount: String,
+    pub output_amount_human: f64,
+    /// Quote coin per base token
+    pub effective_price: f64,
+    pub effective_price_str: String,
+    pub deep_fee: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TwoSidedQuoteResponse {
+    pub success: bool,
+    pub pool: String,
+    pub base_amount: String,
+    pub base_amount_human: f64,
+    /// Sell `base_amount` base for the pool's quote coin
+    pub sell: QuoteLeg,
+    /// Buy base back with the quote proceeds of `sell`
+    pub buy: QuoteLeg,
+    /// (buy price - sell price) / midpoint of the two, in basis points
+    pub implied_spread_bps: f64,
+    /// Base lost selling and immediately buying back (smallest units)
+    pub round_trip_loss: String,
+    pub round_trip_loss_human: f64,
+    pub round_trip_cost_bps: f64,
+}
+
+/// Query parameters for the quote validation endpoint
+#[derive(Debug, Deserialize)]
+pub struct ValidateQuoteQuery {
+    /// Pool to quote (sui_usdc, wal_usdc, deep_usdc)
+    pub pool: String,
+    /// Input amount in smallest units
+    pub amount: String,
+    /// "sell" (base -> quote) or "buy" (quote -> base)
+    pub side: String,
+}
+
+/// One pool quote, from the simulation or from mainnet
+#[derive(Debug, Serialize)]
+pub struct ValidatedQuote {
+    pub output_amount: String,
+    pub output_amount_human: f64,
+    pub deep_fee: String,
+    /// Checkpoint of the state the quote ran against (mainnet: latest at quote time)
+    pub checkpoint: Option<u64>,
+}
+
+/// How far the simulated quote is from the mainnet one
+#[derive(Debug, Serialize)]
+pub struct QuoteDivergence {
+    /// Simulated minus mainnet output (smallest units, signed)
+    pub output_diff: String,
+    pub output_diff_human: f64,
+    /// `output_diff` relative to the mainnet output, in basis points
+    pub output_diff_bps: Option<f64>,
+    /// Simulated minus mainnet DEEP fee (signed)
+    pub deep_fee_diff: String,
+    /// Mainnet checkpoints elapsed since the simulated state was exported
+    pub checkpoint_lag: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QuoteValidationResponse {
+    pub success: bool,
+    pub pool: String,
+    pub side: String,
+    pub input_token: String,
+    pub output_token: String,
+    pub input_amount: String,
+    pub simulated: ValidatedQuote,
+    pub mainnet: ValidatedQuote,
+    pub divergence: QuoteDivergence,
+    pub rpc_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QuoteCacheQuery {
+    /// Run the quote in MoveVM even if a cached result is fresh
+    #[serde(default)]
+    pub no_cache: bool,
+}
+
+/// POST /api/swap/quote - Get a quote without executing
+pub async fn get_quote(
+    State(state): State<AppState>,
+    Query(cache): Query<QuoteCacheQuery>,
+    Json(req): Json<QuoteRequest>,
+) -> ApiResult<Json<QuoteResponse>> {
+    let state = state.at_clock(req.clock_timestamp_ms);
+    let state = if cache.no_cache {
+        state.without_quote_cache()
+    } else {
+        state
+    };
+    ensure_pools_available(&state).await?;
+
+    // Determine route
+    let (from, to, route, route_ambiguous) = resolve_route(
+        &state,
+        &req.from_token,
+        &req.to_token,
+        req.from_type.as_deref(),
+        req.to_type.as_deref(),
+        req.pool.as_deref(),
+    )
+    .await?;
+    let debug_symbol = state.debug_pool.read().await.token_symbol.clone();
+    let amount = parse_request_amount(&req.amount, &from, &debug_symbol)?;
+
+    // Without an explicit pool or coin types, quote every candidate route and
+    // take the one with the highest output.
+    let (route, alternatives) = if req.pool.is_none() && req.from_type.is_none() {
+        select_best_route(&state, &from, &to, amount, route).await?
+    } else {
+        (route, Vec::new())
+    };
+
+    let pools = route.pools();
+    let route_type = route.route_type();
+    access_log::record_route(&pools, route_type);
+    check_circuits(&state, &pools).await?;
+
+    let result = match route {
+        Route::SinglePool(pool_id) => {
+            get_single_pool_quote(&state, pool_id, &from, &to, &debug_symbol, amount, &req).await
+        }
+        Route::TwoHop {
+            first_pool,
+            second_pool,
+        } => {
+            get_two_hop_quote(
+                &state,
+                first_pool,
+                second_pool,
+                &from,
+                &to,
+                &debug_symbol,
+                amount,
+                &req,
+            )
+            .await
+        }
+        Route::ThreeHop { hops } => {
+            get_three_hop_quote(&state, hops, &from, &to, &debug_symbol, amount, &req).await
+        }
+    };
+    record_circuit_outcome(&state, &pools, &result).await;
+    if result.is_ok() {
+        state.metrics.record_quote(route_type);
+    }
+    let provenance = route_provenance(&state, &pools, req.session_id.as_deref()).await;
+    result.map(|Json(mut response)| {
+        response.provenance = provenance;
+        response.route_ambiguous = route_ambiguous;
+        response.alternatives = alternatives;
+        Json(response)
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReverseQuoteRequest {
+    pub pool: Option<String>,
+    #[serde(default)]
+    pub from_token: String,
+    #[serde(default)]
+    pub to_token: String,
+    /// Output to receive, in the output token's smallest units
+    pub desired_output: String,
+    /// Quote at this VM clock timestamp (ms) instead of the synthetic clock
+    pub clock_timestamp_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReverseQuoteResponse {
+    pub success: bool,
+    pub pool: String,
+    pub input_token: String,
+    pub output_token: String,
+    pub desired_output: String,
+    /// Smallest input whose quote yields at least `desired_output`
+    pub input_amount: String,
+    pub input_amount_human: f64,
+    /// Output quoted for `input_amount`; lot-size rounding can put it above `desired_output`
+    pub output_amount: String,
+    pub output_amount_human: f64,
+    pub deep_fee: String,
+    /// Quote coin per base token
+    pub effective_price: f64,
+    pub mid_price: f64,
+    pub price_impact_bps: u32,
+    /// Quote PTBs run while searching for `input_amount`
+    pub quote_iterations: u32,
+}
+
+/// POST /api/swap/quote/reverse - Input needed to receive an exact output on a direct route
+pub async fn get_reverse_quote(
+    State(state): State<AppState>,
+    Json(req): Json<ReverseQuoteRequest>,
+) -> ApiResult<Json<ReverseQuoteResponse>> {
+    let state = state.at_clock(req.clock_timestamp_ms);
+    ensure_pools_available(&state).await?;
+
+    let desired_output: u64 = req
+        .desired_output
+        .parse()
+        .map_err(|_| ApiError::BadRequest("Invalid desired_output".into()))?;
+    if desired_output == 0 {
+        return Err(ApiError::BadRequest(
+            "desired_output must be greater than zero".into(),
+        ));
+    }
+
+    let (from, to, route, _) = resolve_route(
+        &state,
+        &req.from_token,
+        &req.to_token,
+        None,
+        None,
+        req.pool.as_deref(),
+    )
+    .await?;
+    let Route::SinglePool(pool_id) = route else {
+        return Err(ApiError::BadRequest(format!(
+            "Reverse quotes support direct (single-pool) routes only; {} -> {} is {}",
+            from,
+            to,
+            route.route_type()
+        )));
+    };
+    access_log::record_route(&[pool_id], route.route_type());
+    check_circuits(&state, &[pool_id]).await?;
+
+    let router = state.router.as_ref().ok_or_else(|| {
+        ApiError::ServiceUnavailable("MoveVM router is not initialized for reverse quoting".into())
+    })?;
+    if pool_id == PoolId::DebugUsdc {
+        ensure_debug_pool_and_sync(&state, router).await?;
+    }
+    let debug_symbol = state.debug_pool.read().await.token_symbol.clone();
+
+    let is_sell = pool_pairs(&state).await.is_sell_base(pool_id, &from);
+    let result = router
+        .reverse_quote(pool_id, desired_output, is_sell)
+        .await
+        .map_err(router_error(|e| {
+            let err_text = e.to_string();
+            if err_text.starts_with(EXACT_OUT_UNFILLABLE) {
+                ApiError::BadRequest(err_text)
+            } else {
+                deepbook_abort_error(&err_text, pool_id.display_name()).unwrap_or_else(|| {
+                    ApiError::Internal(format!(
+                        "MoveVM reverse quote failed for {}: {}",
+                        pool_id.display_name(),
+                        err_text
+                    ))
+                })
+            }
+        }));
+    record_circuit_outcome(&state, &[pool_id], &result).await;
+    let quote = result?;
+    state.metrics.record_quote(route.route_type());
+
+    let from_decimals = get_decimals(&from, &debug_symbol);
+    let to_decimals = get_decimals(&to, &debug_symbol);
+    let input_human = units::to_human(quote.input_amount, from_decimals);
+    let output_human = units::to_human(quote.output_amount, to_decimals);
+    let effective_price = if is_sell {
+        output_human / input_human
+    } else {
+        input_human / output_human
+    };
+    let mid_price = state
+        .orderbooks
+        .read()
+        .await
+        .get(&pool_id)
+        .and_then(|ob| ob.mid_price())
+        .unwrap_or(0.0);
+
+    Ok(Json(ReverseQuoteResponse {
+        success: true,
+        pool: pool_id.display_name().to_string(),
+        input_token: from,
+        output_token: to,
+        desired_output: desired_output.to_string(),
+        input_amount: quote.input_amount.to_string(),
+        input_amount_human: input_human,
+        output_amount: quote.output_amount.to_string(),
+        output_amount_human: output_human,
+        deep_fee: quote.deep_fee.to_string(),
+        effective_price,
+        mid_price,
+        price_impact_bps: units::price_impact_bps(mid_price, effective_price, is_sell),
+        quote_iterations: quote.quote_iterations,
+    }))
+}
+
+/// Most quotes accepted by one `/api/swap/quote/batch` call
+const MAX_BATCH_QUOTES: usize = 256;
+
+/// One entry of a `/api/swap/quote/batch` response, in request order
+#[derive(Debug, Serialize)]
+pub struct BatchQuoteItem {
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quote: Option<QuoteResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl From<ApiResult<QuoteResponse>> for BatchQuoteItem {
+    fn from(result: ApiResult<QuoteResponse>) -> Self {
+        match result {
+            Ok(quote) => Self {
+                success: true,
+                quote: Some(quote),
+                error: None,
+            },
+            Err(e) => Self {
+                success: false,
+                quote: None,
+                error: Some(e.to_string()),
+            },
+        }
+    }
+}
+
+/// A batched quote request resolved to the routes it will be quoted on
+struct BatchQuotePlan {
+    from: String,
+    to: String,
+    amount: u64,
+    clock_timestamp_ms: Option<u64>,
+    route_ambiguous: bool,
+    default_route: Route,
+    /// Candidate paths to compare; empty when only `default_route` is quoted
+    paths: Vec<(Route, Vec<RouteHop>)>,
+}
+
+impl BatchQuotePlan {
+    fn routes(&self) -> Vec<Route> {
+        if self.paths.is_empty() {
+            vec![self.default_route]
+        } else {
+            self.paths.iter().map(|(route, _)| *route).collect()
+        }
+    }
+
+    fn batch_inputs(&self, pairs: &PoolPairs) -> Vec<BatchQuoteInput> {
+        self.routes()
+            .into_iter()
+            .map(|route| {
+                let batch_route = match route {
+                    Route::SinglePool(pool_id) => BatchQuoteRoute::SingleHop {
+                        pool_id,
+                        is_sell_base: pairs.is_sell_base(pool_id, &self.from),
+                    },
+                    Route::TwoHop {
+                        first_pool,
+                        second_pool,
+                    } => BatchQuoteRoute::TwoHop {
+                        from_pool: first_pool,
+                        to_pool: second_pool,
+                    },
+                    Route::ThreeHop { hops } => BatchQuoteRoute::ThreeHop { hops },
+                };
+                BatchQuoteInput {
+                    route: batch_route,
+                    input_amount: self.amount,
+                    clock_timestamp_ms: self.clock_timestamp_ms,
+                }
+            })
+            .collect()
+    }
+}
+
+/// POST /api/swap/quote/batch - Quote many requests in one router round trip
+///
+/// Every request is resolved exactly as `/api/swap/quote` would resolve it
+/// (including best-route candidates when no pool is given), then all routes
+/// are quoted in a single `BatchQuote` on the router thread at one clock
+/// timestamp, or at an item's own `clock_timestamp_ms`. Results keep request
+/// order; a failed item does not fail the batch.
+pub async fn get_quote_batch(
+    State(state): State<AppState>,
+    Json(reqs): Json<Vec<QuoteRequest>>,
+) -> ApiResult<Json<Vec<BatchQuoteItem>>> {
+    if reqs.len() > MAX_BATCH_QUOTES {
+        return Err(ApiError::BadRequest(format!(
+            "Batch has {} quotes; at most {} are allowed",
+            reqs.len(),
+            MAX_BATCH_QUOTES
+        )));
+    }
+    ensure_pools_available(&state).await?;
+    let router = state.router.as_ref().ok_or_else(|| {
+        ApiError::ServiceUnavailable("MoveVM router is not initialized for batch quoting".into())
+    })?;
+
+    let mut plans = Vec::with_capacity(reqs.len());
+    for req in &reqs {
+        plans.push(plan_batch_quote(&state, req).await);
+    }
+
+    let touches_debug_pool = plans.iter().flatten().any(|plan| {
+        plan.routes()
+            .iter()
+            .any(|route| route.pools().contains(&PoolId::DebugUsdc))
+    });
+    if touches_debug_pool {
+        if let Err(e) = ensure_debug_pool_and_sync(&state, router).await {
+            let msg = match e {
+                ApiError::Internal(msg) => msg,
+                other => other.to_string(),
+            };
+            for plan in plans.iter_mut() {
+                let on_debug_pool = plan.as_ref().is_ok_and(|plan| {
+                    plan.routes()
+                        .iter()
+                        .any(|route| route.pools().contains(&PoolId::DebugUsdc))
+                });
+                if on_debug_pool {
+                    *plan = Err(ApiError::Internal(msg.clone()));
+                }
+            }
+        }
+    }
+    let pairs = pool_pairs(&state).await;
+
+    let batch: Vec<BatchQuoteInput> = plans
+        .iter()
+        .flatten()
+        .flat_map(|plan| plan.batch_inputs(&pairs))
+        .collect();
+    let mut outcomes = router
+        .quote_batch(batch)
+        .await
+        .map_err(router_error(|e| {
+            ApiError::Internal(format!("MoveVM batch quote failed: {}", e))
+        }))?
+        .into_iter();
+
+    let mut items = Vec::with_capacity(reqs.len());
+    for (req, plan) in reqs.iter().zip(plans) {
+        let result = match plan {
+            Ok(plan) => {
+                let plan_outcomes: Vec<_> = outcomes.by_ref().take(plan.routes().len()).collect();
+                batch_quote_response(&state, req, &pairs, plan, plan_outcomes).await
+            }
+            Err(e) => Err(e),
+        };
+        items.push(BatchQuoteItem::from(result));
+    }
+    Ok(Json(items))
+}
+
+/// Resolve one batched request to its candidate routes without quoting.
+async fn plan_batch_quote(state: &AppState, req: &QuoteRequest) -> ApiResult<BatchQuotePlan> {
+    let (from, to, default_route, route_ambiguous) = resolve_route(
+        state,
+        &req.from_token,
+        &req.to_token,
+        req.from_type.as_deref(),
+        req.to_type.as_deref(),
+        req.pool.as_deref(),
+    )
+    .await?;
+    let pairs = pool_pairs(state).await;
+    let amount = parse_request_amount(&req.amount, &from, pairs.debug_symbol())?;
+
+    let mut paths = Vec::new();
+    if req.pool.is_none() && req.from_type.is_none() {
+        let candidates = candidate_paths(
+            &from,
+            &to,
+            &pairs,
+            &state.preferred_pools,
+            state.max_route_hops,
+        );
+        // Same rule as `select_best_route`: a lone candidate is not compared.
+        if candidates.len() >= 2 {
+            paths = candidates
+                .into_iter()
+                .filter_map(|path| Route::from_path(&path).map(|route| (route, path)))
+                .collect();
+        }
+    }
+
+    Ok(BatchQuotePlan {
+        from,
+        to,
+        amount,
+        clock_timestamp_ms: req.clock_timestamp_ms,
+        route_ambiguous,
+        default_route,
+        paths,
+    })
+}
+
+/// Pick the best of a plan's quoted routes and build its `QuoteResponse`.
+async fn batch_quote_response(
+    state: &AppState,
+    req: &QuoteRequest,
+    pairs: &PoolPairs,
+    plan: BatchQuotePlan,
+    outcomes: Vec<anyhow::Result<BatchQuote>>,
+) -> ApiResult<QuoteResponse> {
+    let mut outcomes: Vec<Result<BatchQuote, String>> = plan
+        .routes()
+        .iter()
+        .zip(outcomes)
+        .map(|(route, outcome)| {
+            outcome.map_err(|e| {
+                let pools: Vec<&str> = route.pools().iter().map(|p| p.display_name()).collect();
+                format!(
+                    "MoveVM {} quote failed ({}): {}",
+                    route.route_type(),
+                    pools.join(" -> "),
+                    e
+                )
+            })
+        })
+        .collect();
+
+    let (route, alternatives, outcome) = if plan.paths.is_empty() {
+        let outcome = outcomes
+            .pop()
+            .ok_or_else(|| ApiError::Internal("Router returned no batch quote".into()))?;
+        (plan.default_route, Vec::new(), outcome)
+    } else {
+        let to_decimals = get_decimals(&plan.to, pairs.debug_symbol());
+        let scored = plan
+            .paths
+            .iter()
+            .zip(&outcomes)
+            .map(|((route, path), outcome)| {
+                let output = outcome
+                    .as_ref()
+                    .map(BatchQuote::output_amount)
+                    .map_err(String::clone);
+                let quote = route_quote(&plan.from, path, *route, &output, to_decimals, pairs);
+                (*route, quote, output.ok())
+            })
+            .collect();
+        let (best, alternatives) = pick_best_route(scored, plan.default_route);
+        // Same choice as `pick_best_route`: highest output, earliest on ties
+        let best_idx = outcomes
+            .iter()
+            .enumerate()
+            .filter_map(|(i, outcome)| outcome.as_ref().ok().map(|q| (i, q.output_amount())))
+            .fold(None, |best: Option<(usize, u64)>, (i, output)| match best {
+                Some((_, best_output)) if best_output >= output => best,
+                _ => Some((i, output)),
+            })
+            .map(|(i, _)| i);
+        let outcome = match best_idx {
+            Some(idx) => outcomes.swap_remove(idx),
+            None => Err(outcomes
+                .into_iter()
+                .find_map(Result::err)
+                .unwrap_or_else(|| "No candidate route could be quoted".to_string())),
+        };
+        (best, alternatives, outcome)
+    };
+
+    let pools = route.pools();
+    check_circuits(state, &pools).await?;
+    let result = match (route, outcome) {
+        (Route::SinglePool(pool_id), Ok(BatchQuote::SingleHop(quote))) => {
+            Ok(single_pool_quote_response(
+                state,
+                pool_id,
+                &plan.from,
+                &plan.to,
+                pairs.debug_symbol(),
+                plan.amount,
+                req,
+                &quote,
+            )
+            .await)
+        }
+        (
+            Route::TwoHop {
+                first_pool,
+                second_pool,
+            },
+            Ok(BatchQuote::TwoHop(quote)),
+        ) => Ok(two_hop_quote_response(
+            state,
+            first_pool,
+            second_pool,
+            &plan.from,
+            &plan.to,
+            pairs.debug_symbol(),
+            plan.amount,
+            req,
+            &quote,
+        )
+        .await),
+        (Route::ThreeHop { hops }, Ok(BatchQuote::ThreeHop(quote))) => {
+            Ok(three_hop_quote_response(
+                state,
+                hops,
+                &plan.from,
+                &plan.to,
+                pairs.debug_symbol(),
+                plan.amount,
+                req,
+                &quote,
+            )
+            .await)
+        }
+        (_, Ok(_)) => Err(ApiError::Internal(
+            "Router returned a quote for a different route type".into(),
+        )),
+        (_, Err(e)) => {
+            let pool_names: Vec<&str> = pools.iter().map(|p| p.display_name()).collect();
+            Err(deepbook_abort_error(&e, &pool_names.join(" -> ")).unwrap_or(ApiError::Internal(e)))
+        }
+    };
+    record_circuit_outcome(state, &pools, &result).await;
+
+    let provenance = route_provenance(state, &pools, req.session_id.as_deref()).await;
+    result.map(|mut response| {
+        response.provenance = provenance;
+        response.route_ambiguous = plan.route_ambiguous;
+        response.alternatives = alternatives;
+        response
+    })
+}
+
+/// Single-hop quotes memoized for one request.
+///
+/// Candidate routes often share legs (every WAL -> X two-hop starts by
+/// selling WAL into USDC), so each `(pool, input, side)` is quoted once.
+struct SubQuoteCache<'a> {
+    router: &'a RouterHandle,
+    outputs: HashMap<(PoolId, u64, bool), Result<u64, String>>,
+}
+
+impl<'a> SubQuoteCache<'a> {
+    fn new(router: &'a RouterHandle) -> Self {
+        Self {
+            router,
+            outputs: HashMap::new(),
+        }
+    }
+
+    async fn hop_output(&mut self, hop: RouteHop, amount: u64) -> Result<u64, String> {
+        let key = (hop.pool_id, amount, hop.is_sell_base);
+        if let Some(cached) = self.outputs.get(&key) {
+            return cached.clone();
+        }
+        let output = self
+            .router
+            .quote_single_hop(hop.pool_id, amount, hop.is_sell_base)
+            .await
+            .map(|quote| quote.output_amount)
+            .map_err(|e| format!("{} quote failed: {}", hop.pool_id.display_name(), e));
+        self.outputs.insert(key, output.clone());
+        output
+    }
+
+    /// Output of `path` for `amount`, chaining each hop into the next
+    async fn path_output(&mut self, path: &[RouteHop], amount: u64) -> Result<u64, String> {
+        let mut amount = amount;
+        for hop in path {
+            amount = self.hop_output(*hop, amount).await?;
+        }
+        Ok(amount)
+    }
+}
+
+/// Quote every candidate route for `from` -> `to` and pick the highest output.
+///
+/// With a single candidate nothing is quoted and no alternatives are listed.
+/// Candidates are the single, two-hop and (with `ROUTER_MAX_HOPS=3`) three-hop
+/// paths over distinct pools; ties keep the earlier (shorter, preferred)
+/// route. Falls back to `default_route` when no candidate could be quoted, so
+/// its own quote error is reported. Returns the chosen route and every
+/// candidate, best first.
+async fn select_best_route(
+    state: &AppState,
+    from: &str,
+    to: &str,
+    amount: u64,
+    default_route: Route,
+) -> ApiResult<(Route, Vec<RouteQuote>)> {
+    let pairs = pool_pairs(state).await;
+    let paths = candidate_paths(
+        from,
+        to,
+        &pairs,
+        &state.preferred_pools,
+        state.max_route_hops,
+    );
+    // Nothing to compare; skip the extra VM quotes.
+    if paths.len() < 2 {
+        return Ok((default_route, Vec::new()));
+    }
+    let Some(router) = state.router.as_ref() else {
+        return Ok((default_route, Vec::new()));
+    };
+    if paths
+        .iter()
+        .flatten()
+        .any(|hop| hop.pool_id == PoolId::DebugUsdc)
+    {
+        ensure_debug_pool_and_sync(state, router).await?;
+    }
+
+    let to_decimals = get_decimals(to, pairs.debug_symbol());
+    let mut cache = SubQuoteCache::new(router);
+    let mut scored: Vec<(Route, RouteQuote, Option<u64>)> = Vec::new();
+    for path in &paths {
+        let Some(route) = Route::from_path(path) else {
+            continue;
+        };
+        let output = cache.path_output(path, amount).await;
+        let quote = route_quote(from, path, route, &output, to_decimals, &pairs);
+        scored.push((route, quote, output.ok()));
+    }
+    Ok(pick_best_route(scored, default_route))
+}
+
+/// Score entry for one candidate path
+fn route_quote(
+    from: &str,
+    path: &[RouteHop],
+    route: Route,
+    output: &Result<u64, String>,
+    to_decimals: u8,
+    pairs: &PoolPairs,
+) -> RouteQuote {
+    let mut tokens = vec![from.to_string()];
+    tokens.extend(path.iter().map(|hop| hop_output_token(hop, pairs)));
+    RouteQuote {
+        route: tokens.join(" -> "),
+        route_type: route.route_type().to_string(),
+        pools: path
+            .iter()
+            .map(|hop| hop.pool_id.display_name().to_string())
+            .collect(),
+        estimated_output: output.as_ref().ok().map(u64::to_string),
+        estimated_output_human: output
+            .as_ref()
+            .ok()
+            .map(|out| units::to_human(*out, to_decimals)),
+        selected: false,
+        error: output.as_ref().err().cloned(),
+    }
+}
+
+/// Highest-output candidate, or `default_route` when none could be quoted
+fn pick_best_route(
+    mut scored: Vec<(Route, RouteQuote, Option<u64>)>,
+    default_route: Route,
+) -> (Route, Vec<RouteQuote>) {
+    // Stable sort: equal outputs keep candidate order; failed quotes go last.
+    scored.sort_by(|a, b| b.2.cmp(&a.2));
+    if !matches!(scored.first(), Some((_, _, Some(_)))) {
+        let alternatives = scored.into_iter().map(|(_, quote, _)| quote).collect();
+        return (default_route, alternatives);
+    }
+    let mut best_route = default_route;
+    let mut alternatives = Vec::with_capacity(scored.len());
+    for (i, (route, mut quote, _)) in scored.into_iter().enumerate() {
+        if i == 0 {
+            best_route = route;
+            quote.selected = true;
+        }
+        alternatives.push(quote);
+    }
+    (best_route, alternatives)
+}
+
+/// GET /api/orderbook/two-sided-quote - Quote both directions of a pool for one size.
+///
+/// Sells `amount` base for the quote coin and buys back with the proceeds in one router
+/// call, reporting both effective prices, the implied spread, and round-trip cost.
+pub async fn get_two_sided_quote(
+    State(state): State<AppState>,
+    Query(query): Query<TwoSidedQuoteQuery>,
+) -> ApiResult<Json<TwoSidedQuoteResponse>> {
+    let pool_id = PoolId::from_str(&query.pool)
+        .ok_or_else(|| ApiError::BadRequest(invalid_pool_message(&query.pool, true)))?;
+    let amount: u64 = query
+        .amount
+        .parse()
+        .map_err(|_| ApiError::BadRequest("Invalid amount".into()))?;
+    if amount == 0 {
+        return Err(ApiError::BadRequest(
+            "amount must be greater than zero".into(),
+        ));
+    }
+    ensure_pools_available(&state).await?;
+
+    let router = state.router.as_ref().ok_or_else(|| {
+        ApiError::ServiceUnavailable(
+            "MoveVM router is not initialized for two-sided quoting".into(),
+        )
+    })?;
+    if pool_id == PoolId::DebugUsdc {
+        ensure_debug_pool_and_sync(&state, router).await?;
+    }
+
+    let pools = [pool_id];
+    check_circuits(&state, &pools).await?;
+    let result = router
+        .quote_two_sided(pool_id, amount)
+        .await
+        .map_err(router_error(|e| {
+            let err_text = e.to_string();
+            deepbook_abort_error(&err_text, pool_id.display_name()).unwrap_or_else(|| {
+                ApiError::Internal(format!(
+                    "MoveVM two-sided quote failed for {}: {}",
+                    pool_id.display_name(),
+                    err_text
+                ))
+            })
+        }));
+    record_circuit_outcome(&state, &pools, &result).await;
+    let quote = result?;
+
+    let pairs = pool_pairs(&state).await;
+    let base = pairs.base_symbol(pool_id);
+    let quote_symbol = pairs.quote_symbol(pool_id);
+    let base_decimals = get_decimals(&base, pairs.debug_symbol());
+    let quote_decimals = get_decimals(&quote_symbol, pairs.debug_symbol());
+
+    let quote_out = quote.sell.output_amount;
+    let base_back = quote.buy.output_amount;
+    let sell_price =
+        units::to_human(quote_out, quote_decimals) / units::to_human(amount, base_decimals);
+    let buy_price = if base_back > 0 {
+        units::to_human(quote_out, quote_decimals) / units::to_human(base_back, base_decimals)
+    } else {
+        0.0
+    };
+    // Both legs trade the same quote coin, so the spread reduces to 2(a - b)/(a + b)
+    let implied_spread_bps = if base_back > 0 {
+        2.0 * (amount as f64 - base_back as f64) / (amount as f64 + base_back as f64) * 10_000.0
+    } else {
+        0.0
+    };
+    let round_trip_loss = amount.saturating_sub(base_back);
+
+    Ok(Json(TwoSidedQuoteResponse {
+        success: true,
+        pool: pool_id.display_name().to_string(),
+        base_amount: amount.to_string(),
+        base_amount_human: units::to_human(amount, base_decimals),
+        sell: QuoteLeg {
+            input_token: base.clone(),
+            output_token: quote_symbol.clone(),
+            input_amount: amount.to_string(),
+            output_amount: quote_out.to_string(),
+            output_amount_human: units::to_human(quote_out, quote_decimals),
+            effective_price: sell_price,
+            effective_price_str: single_hop_price_str(
+                true,
+                amount,
+                base_decimals,
+                quote_out,
+                quote_decimals,
+            ),
+            deep_fee: quote.sell.deep_fee.to_string(),
+        },
+        buy: QuoteLeg {
+            input_token: quote_symbol,
+            output_token: base,
+            input_amount: quote_out.to_string(),
+            output_amount: base_back.to_string(),
+            output_amount_human: units::to_human(base_back, base_decimals),
+            effective_price: buy_price,
+            effective_price_str: single_hop_price_str(
+                false,
+                quote_out,
+                quote_decimals,
+                base_back,
+                base_decimals,
+            ),
+            deep_fee: quote.buy.deep_fee.to_string(),
+        },
+        implied_spread_bps,
+        round_trip_loss: round_trip_loss.to_string(),
+        round_trip_loss_human: units::to_human(round_trip_loss, base_decimals),
+        round_trip_cost_bps: round_trip_loss as f64 / amount as f64 * 10_000.0,
+    }))
+}
+
+/// GET /api/validate/quote - Compare a simulated pool quote with live mainnet.
+///
+/// Runs the same `pool::get_*_quantity_out` view in the local MoveVM and via
+/// mainnet dev-inspect, and reports both quotes and their divergence.
+pub async fn validate_quote(
+    State(state): State<AppState>,
+    Query(query): Query<ValidateQuoteQuery>,
+) -> ApiResult<Json<QuoteValidationResponse>> {
+    let pool_id = PoolId::from_str(&query.pool)
+        .filter(|pool_id| !pool_id.is_debug())
+        .ok_or_else(|| {
+            ApiError::BadRequest(format!(
+                "Invalid pool '{}'. Valid pools: sui_usdc, wal_usdc, deep_usdc (debug_usdc has no mainnet counterpart)",
+                query.pool
+            ))
+        })?;
+    let amount: u64 = query
+        .amount
+        .parse()
+        .map_err(|_| ApiError::BadRequest("Invalid amount".into()))?;
+    if amount == 0 {
+        return Err(ApiError::BadRequest(
+            "amount must be greater than zero".into(),
+        ));
+    }
+    let is_sell_base = match query.side.to_ascii_lowercase().as_str() {
+        "sell" => true,
+        "buy" => false,
+        _ => {
+            return Err(ApiError::BadRequest(format!(
+                "Invalid side '{}'. Expected sell (base -> quote) or buy (quote -> base)",
+                query.side
+            )))
+        }
+    };
+
+    let router = state.router.as_ref().ok_or_else(|| {
+        ApiError::ServiceUnavailable("MoveVM router is not initialized for quote validation".into())
+    })?;
+    let pools = [pool_id];
+    check_circuits(&state, &pools).await?;
+    let config = state.pool_registry.read().await.config(pool_id);
+    let (simulated, mainnet) = tokio::join!(
+        router.quote_single_hop(pool_id, amount, is_sell_base),
+        state
+            .mainnet_quoter
+            .quote_single_hop(&config, amount, is_sell_base),
+    );
+    let simulated = simulated.map_err(router_error(|e| {
+        let err_text = e.to_string();
+        deepbook_abort_error(&err_text, pool_id.display_name()).unwrap_or_else(|| {
+            ApiError::Internal(format!(
+                "MoveVM quote failed for {}: {}",
+                pool_id.display_name(),
+                err_text
+            ))
+        })
+    }));
+    record_circuit_outcome(&state, &pools, &simulated).await;
+    let simulated = simulated?;
+    let mainnet = mainnet.map_err(|e| {
+        ApiError::Internal(format!(
+            "Mainnet quote via {} failed for {}: {}",
+            state.mainnet_quoter.rpc_url(),
+            pool_id.display_name(),
+            e
+        ))
+    })?;
+
+    let base = config.base_symbol();
+    let quote = config.quote_symbol();
+    let (input_token, output_token) = if is_sell_base {
+        (base, quote)
+    } else {
+        (quote, base)
+    };
+    let output_decimals = get_decimals(&output_token, "");
+    let simulated_checkpoint = route_provenance(&state, &pools, None)
+        .await
+        .map(|provenance| provenance.checkpoint);
+
+    let output_diff = simulated.output_amount as i128 - mainnet.output_amount as i128;
+    let output_diff_bps = (mainnet.output_amount > 0)
+        .then(|| output_diff as f64 / mainnet.output_amount as f64 * 10_000.0);
+    let deep_fee_diff = simulated.deep_fee as i128 - mainnet.deep_fee as i128;
+    let checkpoint_lag = match (simulated_checkpoint, mainnet.checkpoint) {
+        (Some(simulated), Some(mainnet)) => Some(mainnet.saturating_sub(simulated)),
+        _ => None,
+    };
+
+    Ok(Json(QuoteValidationResponse {
+        success: true,
+        pool: pool_id.display_name().to_string(),
+        side: if is_sell_base { "sell" } else { "buy" }.to_string(),
+        input_token,
+        input_amount: amount.to_string(),
+        simulated: ValidatedQuote {
+            output_amount: simulated.output_amount.to_string(),
+            output_amount_human: units::to_human(simulated.output_amount, output_decimals),
+            deep_fee: simulated.deep_fee.to_string(),
+            checkpoint: simulated_checkpoint,
+        },
+        mainnet: ValidatedQuote {
+            output_amount: mainnet.output_amount.to_string(),
+            output_amount_human: units::to_human(mainnet.output_amount, output_decimals),
+            deep_fee: mainnet.deep_fee.to_string(),
+            checkpoint: mainnet.checkpoint,
+        },
+        divergence: QuoteDivergence {
+            output_diff: output_diff.to_string(),
+            output_diff_human: output_diff as f64 / 10f64.powi(output_decimals as i32),
+            output_diff_bps,
+            deep_fee_diff: deep_fee_diff.to_string(),
+            checkpoint_lag,
+        },
+        output_token,
+        rpc_url: state.mainnet_quoter.rpc_url().to_string(),
+    }))
+}
+
+/// Provenance of the most recent checkpoint among the orderbooks on a route.
+///
+/// Uses the session's orderbooks when `session_id` resolves, else the global ones.
+async fn route_provenance(
+    state: &AppState,
+    pools: &[PoolId],
+    session_id: Option<&str>,
+) -> Option<OrderbookProvenance> {
+    let latest = |books: &HashMap<PoolId, SandboxOrderbook>, source: &str| {
+        pools
+            .iter()
+            .filter_map(|pool_id| books.get(pool_id))
+            .max_by_key(|ob| ob.checkpoint)
+            .map(|ob| OrderbookProvenance::from_orderbook(ob, source))
+    };
+    if let Some(sid) = session_id {
+        if let Some(session_arc) = state.session_manager.get_session(sid).await {
+            let session = session_arc.read().await;
+            return latest(&session.orderbooks, "session");
+        }
+    }
+    let orderbooks = state.orderbooks.read().await;
+    latest(&orderbooks, "global")
+}
+
+/// In-memory book walk over `hops` (`(pool, is_sell_base, input)`), summed.
+///
+/// Only computed for `detailed` quotes. Uses the session's orderbooks when
+/// `session_id` resolves, else the global ones; a missing book is not fillable.
+async fn detailed_book_walk(
+    state: &AppState,
+    req: &QuoteRequest,
+    hops: &[(PoolId, bool, u64)],
+) -> Option<BookWalk> {
+    if !req.detailed.unwrap_or(false) {
+        return None;
+    }
+    let walk = |books: &HashMap<PoolId, SandboxOrderbook>| {
+        hops.iter().fold(
+            BookWalk {
+                levels_consumed: 0,
+                orders_matched: 0,
+                fully_fillable: true,
+            },
+            |acc, &(pool_id, is_sell_base, input)| match books.get(&pool_id) {
+                Some(ob) => {
+                    let hop = ob.walk(is_sell_base, input);
+                    BookWalk {
+                        levels_consumed: acc.levels_consumed + hop.levels_consumed,
+                        orders_matched: acc.orders_matched + hop.orders_matched,
+                        fully_fillable: acc.fully_fillable && hop.fully_fillable,
+                    }
+                }
+                None => BookWalk {
+                    fully_fillable: false,
+                    ..acc
+                },
+            },
+        )
+    };
+    if let Some(ref sid) = req.session_id {
+        if let Some(session_arc) = state.session_manager.get_session(sid).await {
+            let session = session_arc.read().await;
+            return Some(walk(&session.orderbooks));
+        }
+    }
+    let orderbooks = state.orderbooks.read().await;
+    Some(walk(&orderbooks))
+}
+
+/// Gas estimate for `include_gas` quotes; a failed dry run is logged and omitted.
+async fn quote_gas_estimate(
+    state: &AppState,
+    req: &QuoteRequest,
+    route: SwapGasRoute,
+    amount: u64,
+) -> Option<String> {
+    if !req.include_gas.unwrap_or(false) {
+        return None;
+    }
+    let router = state.router.as_ref()?;
+    match router.estimate_swap_gas(route, amount).await {
+        Ok(gas_used) => Some(gas_used.to_string()),
+        Err(e) => {
+            tracing::warn!("Gas estimate failed for {:?}: {}", route, e);
+            None
+        }
+    }
+}
+
+/// Quote for a single-pool swap using MoveVM quote calls.
+async fn get_single_pool_quote(
+    state: &AppState,
+    pool_id: PoolId,
+    from: &str,
+    to: &str,
+    debug_symbol: &str,
+    amount: u64,
+    req: &QuoteRequest,
+) -> ApiResult<Json<QuoteResponse>> {
+    let is_sell = pool_pairs(state).await.is_sell_base(pool_id, from);
+    let router = state.router.as_ref().ok_or_else(|| {
+        ApiError::ServiceUnavailable(
+            "MoveVM router is not initialized for single-hop quoting".into(),
+        )
+    })?;
+
+    if pool_id == PoolId::DebugUsdc {
+        ensure_debug_pool_and_sync(state, router).await?;
+    }
+
+    let vm_quote = router
+        .quote_single_hop(pool_id, amount, is_sell)
+        .await
+        .map_err(router_error(|e| {
+            let err_text = e.to_string();
+            deepbook_abort_error(&err_text, pool_id.display_name()).unwrap_or_else(|| {
+                ApiError::Internal(format!(
+                    "MoveVM single-hop quote failed for {}: {}",
+                    pool_id.display_name(),
+                    err_text
+                ))
+            })
+        }))?;
+    Ok(Json(
+        single_pool_quote_response(
+            state,
+            pool_id,
+            from,
+            to,
+            debug_symbol,
+            amount,
+            req,
+            &vm_quote,
+        )
+        .await,
+    ))
+}
+
+/// Build the response for a single-pool quote the router already computed
+async fn single_pool_quote_response(
+    state: &AppState,
+    pool_id: PoolId,
+    from: &str,
+    to: &str,
+    debug_symbol: &str,
+    amount: u64,
+    req: &QuoteRequest,
+    vm_quote: &SingleHopQuote,
+) -> QuoteResponse {
+    let is_sell = pool_pairs(state).await.is_sell_base(pool_id, from);
+    let mid_price = if let Some(ref sid) = req.session_id {
+        if let Some(session_arc) = state.session_manager.get_session(sid).await {
+            let session = session_arc.read().await;
+            session
+                .orderbooks
+                .get(&pool_id)
+                .and_then(|ob| ob.mid_price())
+                .unwrap_or(0.0)
+        } else {
+            let orderbooks = state.orderbooks.read().await;
+            orderbooks
+                .get(&pool_id)
+                .and_then(|ob| ob.mid_price())
+                .unwrap_or(0.0)
+        }
+    } else {
+        let orderbooks = state.orderbooks.read().await;
+        orderbooks
+            .get(&pool_id)
+            .and_then(|ob| ob.mid_price())
+            .unwrap_or(0.0)
+    };
+
+    let input_human = units::to_human(amount, get_decimals(from, debug_symbol));
+    let output_human = units::to_human(vm_quote.output_amount, get_decimals(to, debug_symbol));
+
+    let effective_price = if is_sell {
+        if input_human > 0.0 {
+            output_human / input_human
+        } else {
+            0.0
+        }
+    } else if output_human > 0.0 {
+        input_human / output_human
+    } else {
+        0.0
+    };
+    let effective_price_str = single_hop_price_str(
+        is_sell,
+        amount,
+        get_decimals(from, debug_symbol),
+        vm_quote.output_amount,
+        get_decimals(to, debug_symbol),
+    );
+
+    let price_impact_bps = units::price_impact_bps(mid_price, effective_price, is_sell);
+    let tick_price = tick_rounded_price(
+        state,
+        pool_id,
+        is_sell,
+        amount,
+        vm_quote.output_amount,
+        mid_price,
+        debug_symbol,
+    )
+    .await;
+    let book_walk = detailed_book_walk(state, req, &[(pool_id, is_sell, amount)]).await;
+    let estimated_gas = quote_gas_estimate(
+        state,
+        req,
+        SwapGasRoute::SingleHop {
+            pool_id,
+            is_sell_base: is_sell,
+        },
+        amount,
+    )
+    .await;
+
+    QuoteResponse {
+        success: true,
+        error: None,
+        pool: pool_id.display_name().to_string(),
+        input_token: from.to_string(),
+        output_token: to.to_string(),
+        input_amount: amount.to_string(),
+        input_amount_human: input_human,
+        estimated_output: vm_quote.output_amount.to_string(),
+        estimated_output_human: output_human,
+        effective_price,
+        effective_price_str,
+        mid_price,
+        tick_rounded_price: tick_price.map(|tick| tick.raw),
+        tick_rounded_price_human: tick_price.map(|tick| tick.human),
+        price_ticks: tick_price.and_then(|tick| tick.ticks_from_mid),
+        price_impact_bps,
+        levels_consumed: book_walk.map_or(0, |walk| walk.levels_consumed),
+        orders_matched: book_walk.map_or(0, |walk| walk.orders_matched),
+        fully_fillable: book_walk.map_or(vm_quote.output_amount > 0, |walk| walk.fully_fillable),
+        route: format!("{} -> DeepBook {} -> {}", from, pool_id.display_name(), to),
+        route_type: "direct".to_string(),
+        intermediate_amount: None,
+        intermediate_amounts: None,
+        provenance: None,
+        route_ambiguous: false,
+        denominations: req.denominations.then(|| {
+            QuoteDenominations::new(
+                amount,
+                get_decimals(from, debug_symbol),
+                vm_quote.output_amount,
+                get_decimals(to, debug_symbol),
+            )
+        }),
+        alternatives: Vec::new(),
+        estimated_gas,
+    }
+}
+
+/// A direct fill's price on the pool's tick grid
+#[derive(Debug, Clone, Copy)]
+struct TickRoundedPrice {
+    raw: u64,
+    human: f64,
+    ticks_from_mid: Option<f64>,
+}
+
+/// Snap a direct quote's fill price to the pool's `tick_size`.
+///
+/// `None` without a router, when the pool params cannot be read, or for an
+/// empty fill.
+async fn tick_rounded_price(
+    state: &AppState,
+    pool_id: PoolId,
+    is_sell: bool,
+    input: u64,
+    output: u64,
+    mid_price: f64,
+    debug_symbol: &str,
+) -> Option<TickRoundedPrice> {
+    let params = match state.router.as_ref()?.pool_params(pool_id).await {
+        Ok(params) => params,
+        Err(e) => {
+            tracing::debug!("No tick size for {}: {}", pool_id.display_name(), e);
+            return None;
+        }
+    };
+    let (base_amount, quote_amount) = if is_sell {
+        (input, output)
+    } else {
+        (output, input)
+    };
+    let raw = units::round_to_tick(
+        units::raw_fill_price(base_amount, quote_amount)?,
+        params.tick_size,
+        is_sell,
+    );
+
+    let pairs = pool_pairs(state).await;
+    let base_decimals = get_decimals(&pairs.base_symbol(pool_id), debug_symbol);
+    let quote_decimals = get_decimals(&pairs.quote_symbol(pool_id), debug_symbol);
+    let divisor = units::price_divisor(base_decimals, quote_decimals);
+    let ticks_from_mid = (mid_price > 0.0 && params.tick_size > 0)
+        .then(|| (raw as f64 - mid_price * divisor) / params.tick_size as f64);
+    Some(TickRoundedPrice {
+        raw,
+        human: raw as f64 / divisor,
+        ticks_from_mid,
+    })
+}
+
+/// Quote for a two-hop swap: from_token -> shared quote coin -> to_token
+async fn get_two_hop_quote(
+    state: &AppState,
+    first_pool: PoolId,
+    second_pool: PoolId,
+    from: &str,
+    to: &str,
+    debug_symbol: &str,
+    amount: u64,
+    req: &QuoteRequest,
+) -> ApiResult<Json<QuoteResponse>> {
+    let router = state.router.as_ref().ok_or_else(|| {
+        ApiError::ServiceUnavailable("MoveVM router is not initialized for two-hop quoting".into())
+    })?;
+    if first_pool == PoolId::DebugUsdc || second_pool == PoolId::DebugUsdc {
+        ensure_debug_pool_and_sync(state, router).await?;
+    }
+    let router_quote = router
+        .quote_two_hop(first_pool, second_pool, amount)
+        .await
+        .map_err(router_error(|e| {
+            let err_text = e.to_string();
+            let route = format!(
+                "{} -> {}",
+                first_pool.display_name(),
+                second_pool.display_name()
+            );
+            deepbook_abort_error(&err_text, &route).unwrap_or_else(|| {
+                ApiError::Internal(format!(
+                    "MoveVM router two-hop quote failed ({}): {}",
+                    route, err_text
+                ))
+            })
+        }))?;
+    Ok(Json(
+        two_hop_quote_response(
+            state,
+            first_pool,
+            second_pool,
+            from,
+            to,
+            debug_symbol,
+            amount,
+            req,
+            &router_quote,
+        )
+        .await,
+    ))
+}
+
+/// Build the response for a two-hop quote the router already computed
+async fn two_hop_quote_response(
+    state: &AppState,
+    first_pool: PoolId,
+    second_pool: PoolId,
+    from: &str,
+    to: &str,
+    debug_symbol: &str,
+    amount: u64,
+    req: &QuoteRequest,
+    router_quote: &TwoHopQuote,
+) -> QuoteResponse {
+    // Estimate mid price from orderbooks.
+    let (first_mid, second_mid) = if let Some(ref sid) = req.session_id {
+        if let Some(session_arc) = state.session_manager.get_session(sid).await {
+            let session = session_arc.read().await;
+            let first_mid = session
+                .orderbooks
+                .get(&first_pool)
+                .and_then(|ob| ob.mid_price())
+                .unwrap_or(0.0);
+            let second_mid = session
+                .orderbooks
+                .get(&second_pool)
+                .and_then(|ob| ob.mid_price())
+                .unwrap_or(0.0);
+            (first_mid, second_mid)
+        } else {
+            let orderbooks = state.orderbooks.read().await;
+            let first_mid = orderbooks
+                .get(&first_pool)
+                .and_then(|ob| ob.mid_price())
+                .unwrap_or(0.0);
+            let second_mid = orderbooks
+                .get(&second_pool)
+                .and_then(|ob| ob.mid_price())
+                .unwrap_or(0.0);
+            (first_mid, second_mid)
+        }
+    } else {
+        let orderbooks = state.orderbooks.read().await;
+        let first_mid = orderbooks
+            .get(&first_pool)
+            .and_then(|ob| ob.mid_price())
+            .unwrap_or(0.0);
+        let second_mid = orderbooks
+            .get(&second_pool)
+            .and_then(|ob| ob.mid_price())
+            .unwrap_or(0.0);
+        (first_mid, second_mid)
+    };
+
+    let from_decimals = get_decimals(from, debug_symbol);
+    let to_decimals = get_decimals(to, debug_symbol);
+    let input_human = units::to_human(amount, from_decimals);
+    let output_human = units::to_human(router_quote.final_output, to_decimals);
+    let intermediate_token = pool_pairs(state).await.quote_symbol(first_pool);
+    let intermediate_human = units::to_human(
+        router_quote.intermediate_amount,
+        get_decimals(&intermediate_token, debug_symbol),
+    );
+
+    let effective_price = if input_human > 0.0 {
+        output_human / input_human
+    } else {
+        0.0
+    };
+    let effective_price_str = exact_ratio_str(
+        router_quote.final_output,
+        to_decimals,
+        amount,
+        from_decimals,
+    );
+
+    let mid_price = two_hop_mid(first_mid, second_mid);
+
+    let price_impact_bps = units::price_impact_bps(mid_price, effective_price, true);
+    let book_walk = detailed_book_walk(
+        state,
+        req,
+        &[
+            (first_pool, true, amount),
+            (second_pool, false, router_quote.intermediate_amount),
+        ],
+    )
+    .await;
+    let estimated_gas = quote_gas_estimate(
+        state,
+        req,
+        SwapGasRoute::TwoHop {
+            from_pool: first_pool,
+            to_pool: second_pool,
+        },
+        amount,
+    )
+    .await;
+
+    QuoteResponse {
+        success: true,
+        error: None,
+        pool: format!(
+            "{} + {}",
+            first_pool.display_name(),
+            second_pool.display_name()
+        ),
+        input_token: from.to_string(),
+        output_token: to.to_string(),
+        input_amount: amount.to_string(),
+        input_amount_human: input_human,
+        estimated_output: router_quote.final_output.to_string(),
+        estimated_output_human: output_human,
+        effective_price,
+        effective_price_str,
+        mid_price,
+        tick_rounded_price: None,
+        tick_rounded_price_human: None,
+        price_ticks: None,
+        price_impact_bps,
+        levels_consumed: book_walk.map_or(0, |walk| walk.levels_consumed),
+        orders_matched: book_walk.map_or(0, |walk| walk.orders_matched),
+        fully_fillable: book_walk.map_or(router_quote.final_output > 0, |walk| walk.fully_fillable),
+        route: format!(
+            "{} -> DeepBook {} -> {} -> DeepBook {} -> {}",
+            from,
+            first_pool.display_name(),
+            intermediate_token,
+            second_pool.display_name(),
+            to
+        ),
+        route_type: "two_hop".to_string(),
+        intermediate_amount: Some(intermediate_human),
+        intermediate_amounts: Some(vec![IntermediateAmount {
+            token: intermediate_token,
+            amount: router_quote.intermediate_amount.to_string(),
+            amount_human: intermediate_human,
+        }]),
+        provenance: None,
+        route_ambiguous: false,
+        denominations: req.denominations.then(|| {
+            QuoteDenominations::new(
+                amount,
+                from_decimals,
+                router_quote.final_output,
+                to_decimals,
+            )
+        }),
+        alternatives: Vec::new(),
+        estimated_gas,
+    }
+}
+
+/// Quote a three-hop route by chaining pool views in the router thread.
+///
+/// The mid price is the product of each hop's mid (divided for buy legs).
+async fn get_three_hop_quote(
+    state: &AppState,
+    hops: [RouteHop; 3],
+    from: &str,
+    to: &str,
+    debug_symbol: &str,
+    amount: u64,
+    req: &QuoteRequest,
+) -> ApiResult<Json<QuoteResponse>> {
+    let router = state.router.as_ref().ok_or_else(|| {
+        ApiError::ServiceUnavailable(
+            "MoveVM router is not initialized for three-hop quoting".into(),
+        )
+    })?;
+    if hops.iter().any(|hop| hop.pool_id == PoolId::DebugUsdc) {
+        ensure_debug_pool_and_sync(state, router).await?;
+    }
+    let router_quote = router
+        .quote_three_hop(hops, amount)
+        .await
+        .map_err(router_error(|e| {
+            let pool_names: Vec<&str> = hops.iter().map(|hop| hop.pool_id.display_name()).collect();
+            let route = pool_names.join(" -> ");
+            let err_text = e.to_string();
+            deepbook_abort_error(&err_text, &route).unwrap_or_else(|| {
+                ApiError::Internal(format!(
+                    "MoveVM router three-hop quote failed ({}): {}",
+                    route, err_text
+                ))
+            })
+        }))?;
+    Ok(Json(
+        three_hop_quote_response(
+            state,
+            hops,
+            from,
+            to,
+            debug_symbol,
+            amount,
+            req,
+            &router_quote,
+        )
+        .await,
+    ))
+}
+
+/// Build the response for a three-hop quote the router already computed
+async fn three_hop_quote_response(
+    state: &AppState,
+    hops: [RouteHop; 3],
+    from: &str,
+    to: &str,
+    debug_symbol: &str,
+    amount: u64,
+    req: &QuoteRequest,
+    router_quote: &ThreeHopQuote,
+) -> QuoteResponse {
+    let pool_names: Vec<&str> = hops.iter().map(|hop| hop.pool_id.display_name()).collect();
+    let mids: Vec<f64> = {
+        let session_arc = match req.session_id {
+            Some(ref sid) => state.session_manager.get_session(sid).await,
+            None => None,
+        };
+        match session_arc {
+            Some(session_arc) => {
+                let session = session_arc.read().await;
+                hops.iter()
+                    .map(|hop| {
+                        session
+                            .orderbooks
+                            .get(&hop.pool_id)
+                            .and_then(|ob| ob.mid_price())
+                            .unwrap_or(0.0)
+                    })
+                    .collect()
+            }
+            None => {
+                let orderbooks = state.orderbooks.read().await;
+                hops.iter()
+                    .map(|hop| {
+                        orderbooks
+                            .get(&hop.pool_id)
+                            .and_then(|ob| ob.mid_price())
+                            .unwrap_or(0.0)
+                    })
+                    .collect()
+            }
+        }
+    };
+    let mid_price = if mids.iter().all(|mid| *mid > 0.0) {
+        hops.iter().zip(&mids).fold(1.0, |acc, (hop, mid)| {
+            if hop.is_sell_base {
+                acc * mid
+            } else {
+                acc / mid
+            }
+        })
+    } else {
+        0.0
+    };
+
+    let from_decimals = get_decimals(from, debug_symbol);
+    let to_decimals = get_decimals(to, debug_symbol);
+    let input_human = units::to_human(amount, from_decimals);
+    let output_human = units::to_human(router_quote.final_output, to_decimals);
+
+    // Every hop but the last hands its output to the next one
+    let pairs = pool_pairs(state).await;
+    let intermediate_amounts: Vec<IntermediateAmount> = hops
+        .iter()
+        .zip(&router_quote.hop_outputs)
+        .take(hops.len() - 1)
+        .map(|(hop, output)| {
+            let token = hop_output_token(hop, &pairs);
+            IntermediateAmount {
+                amount: output.to_string(),
+                amount_human: units::to_human(*output, get_decimals(&token, debug_symbol)),
+                token,
+            }
+        })
+        .collect();
+
+    let effective_price = if input_human > 0.0 {
+        output_human / input_human
+    } else {
+        0.0
+    };
+    let effective_price_str = exact_ratio_str(
+        router_quote.final_output,
+        to_decimals,
+        amount,
+        from_decimals,
+    );
+    let price_impact_bps = units::price_impact_bps(mid_price, effective_price, true);
+
+    // Each hop spends the previous hop's output
+    let hop_inputs: Vec<(PoolId, bool, u64)> = hops
+        .iter()
+        .zip(std::iter::once(&amount).chain(&router_quote.hop_outputs))
+        .map(|(hop, input)| (hop.pool_id, hop.is_sell_base, *input))
+        .collect();
+    let book_walk = detailed_book_walk(state, req, &hop_inputs).await;
+
+    let mut route = from.to_string();
+    for (hop, intermediate) in hops.iter().zip(&intermediate_amounts) {
+        route.push_str(&format!(
+            " -> DeepBook {} -> {}",
+            hop.pool_id.display_name(),
+            intermediate.token
+        ));
+    }
+    route.push_str(&format!(
+        " -> DeepBook {} -> {}",
+        hops[2].pool_id.display_name(),
+        to
+    ));
+
+    QuoteResponse {
+        success: true,
+        error: None,
+        pool: pool_names.join(" + "),
+        input_token: from.to_string(),
+        output_token: to.to_string(),
+        input_amount: amount.to_string(),
+        input_amount_human: input_human,
+        estimated_output: router_quote.final_output.to_string(),
+        estimated_output_human: output_human,
+        effective_price,
+        effective_price_str,
+        mid_price,
+        tick_rounded_price: None,
+        tick_rounded_price_human: None,
+        price_ticks: None,
+        price_impact_bps,
+        levels_consumed: book_walk.map_or(0, |walk| walk.levels_consumed),
+        orders_matched: book_walk.map_or(0, |walk| walk.orders_matched),
+        fully_fillable: book_walk.map_or(router_quote.final_output > 0, |walk| walk.fully_fillable),
+        route,
+        route_type: "three_hop".to_string(),
+        intermediate_amount: None,
+        intermediate_amounts: Some(intermediate_amounts),
+        provenance: None,
+        route_ambiguous: false,
+        denominations: req.denominations.then(|| {
+            QuoteDenominations::new(
+                amount,
+                from_decimals,
+                router_quote.final_output,
+                to_decimals,
+            )
+        }),
+        alternatives: Vec::new(),
+        estimated_gas: None,
+    }
+}
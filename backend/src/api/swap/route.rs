@@ -0,0 +1,784 @@
+//! Route resolution
+//!
+//! Maps a request's tokens (or explicit type strings and pool) to the pools a
+//! swap or quote runs through, honouring `PREFERRED_POOLS` and
+//! `ROUTER_MAX_HOPS`.
+
+use super::*;
+
+/// Route classification for a swap
+#[derive(Clone, Copy)]
+pub(super) enum Route {
+    /// Direct single-pool swap (e.g., SUI <-> USDC)
+    SinglePool(PoolId),
+    /// Two-hop swap via a shared quote coin (e.g., SUI -> USDC -> WAL)
+    TwoHop {
+        first_pool: PoolId,
+        second_pool: PoolId,
+    },
+    /// Three pools chained through two intermediates (quote-only),
+    /// e.g. WAL -> USDC -> SUI -> DEEP
+    ThreeHop { hops: [RouteHop; 3] },
+}
+
+impl Route {
+    /// Pools touched by this route
+    pub(super) fn pools(&self) -> Vec<PoolId> {
+        match self {
+            Route::SinglePool(pool_id) => vec![*pool_id],
+            Route::TwoHop {
+                first_pool,
+                second_pool,
+            } => vec![*first_pool, *second_pool],
+            Route::ThreeHop { hops } => hops.iter().map(|hop| hop.pool_id).collect(),
+        }
+    }
+
+    /// `route_type` reported in quote and swap responses
+    pub(super) fn route_type(&self) -> &'static str {
+        match self {
+            Route::SinglePool(_) => "direct",
+            Route::TwoHop { .. } => "two_hop",
+            Route::ThreeHop { .. } => "three_hop",
+        }
+    }
+
+    /// Route for a path from `candidate_paths`; two-hop routes must sell
+    /// into the shared quote coin and buy out of it.
+    pub(super) fn from_path(path: &[RouteHop]) -> Option<Route> {
+        match *path {
+            [hop] => Some(Route::SinglePool(hop.pool_id)),
+            [first, second] if first.is_sell_base && !second.is_sell_base => Some(Route::TwoHop {
+                first_pool: first.pool_id,
+                second_pool: second.pool_id,
+            }),
+            [first, second, third] => Some(Route::ThreeHop {
+                hops: [first, second, third],
+            }),
+            _ => None,
+        }
+    }
+}
+
+fn is_debug_token(token: &str, debug_symbol: &str) -> bool {
+    let t = token.to_uppercase();
+    let debug = debug_symbol.to_uppercase();
+    t == "DBG" || t == "DEBUG" || t == debug
+}
+
+/// Base and quote symbol of every pool, which routing and swap direction
+/// follow; see [`pool_pairs`].
+#[derive(Debug, Clone)]
+pub(crate) struct PoolPairs {
+    debug_symbol: String,
+    pairs: Vec<(PoolId, String, String)>,
+}
+
+impl PoolPairs {
+    /// The pairs the pools are labelled with (`BASE/QUOTE`, see
+    /// [`PoolId::display_name`]), the debug pool trading `debug_symbol`
+    /// against USDC.
+    pub(crate) fn builtin(debug_symbol: &str) -> Self {
+        let mut pairs: Vec<(PoolId, String, String)> = PoolId::all()
+            .into_iter()
+            .filter_map(|pool_id| {
+                let (base, quote) = pool_id.display_name().split_once('/')?;
+                Some((pool_id, base.to_uppercase(), quote.to_uppercase()))
+            })
+            .collect();
+        pairs.push((
+            PoolId::DebugUsdc,
+            debug_symbol.to_uppercase(),
+            "USDC".to_string(),
+        ));
+        Self {
+            debug_symbol: debug_symbol.to_string(),
+            pairs,
+        }
+    }
+
+    /// Replace the pair `pool_id` trades, adding the pool if it has none
+    pub(crate) fn with_pair(mut self, pool_id: PoolId, base: &str, quote: &str) -> Self {
+        let (base, quote) = (base.to_uppercase(), quote.to_uppercase());
+        match self.pairs.iter_mut().find(|(id, _, _)| *id == pool_id) {
+            Some((_, pool_base, pool_quote)) => {
+                *pool_base = base;
+                *pool_quote = quote;
+            }
+            None => self.pairs.push((pool_id, base, quote)),
+        }
+        self
+    }
+
+    pub(crate) fn debug_symbol(&self) -> &str {
+        &self.debug_symbol
+    }
+
+    fn pair(&self, pool_id: PoolId) -> (&str, &str) {
+        self.pairs
+            .iter()
+            .find(|(id, _, _)| *id == pool_id)
+            .map(|(_, base, quote)| (base.as_str(), quote.as_str()))
+            .expect("every pool has a pair")
+    }
+
+    /// Symbol of the asset `pool_id` trades as base
+    pub(crate) fn base_symbol(&self, pool_id: PoolId) -> String {
+        self.pair(pool_id).0.to_string()
+    }
+
+    /// Symbol of the asset `pool_id` quotes prices in
+    pub(crate) fn quote_symbol(&self, pool_id: PoolId) -> String {
+        self.pair(pool_id).1.to_string()
+    }
+
+    /// Whether swapping `from` in `pool_id` sells the pool's base
+    pub(crate) fn is_sell_base(&self, pool_id: PoolId, from: &str) -> bool {
+        self.pair(pool_id).1 != from
+    }
+
+    /// Quote coin of the first pool that trades `base` as its base coin
+    pub(crate) fn quote_for_base(&self, base: &str) -> Option<String> {
+        self.pairs
+            .iter()
+            .find(|(_, pool_base, _)| pool_base == base)
+            .map(|(_, _, quote)| quote.clone())
+    }
+}
+
+/// Pairs of the pools as loaded: a state file holding another pair (e.g.
+/// SUI/DEEP) replaces the one its pool id names, and every created debug
+/// pool trades its token against USDC.
+pub(crate) async fn pool_pairs(state: &AppState) -> PoolPairs {
+    let (debug_symbol, debug_pools) = {
+        let debug = state.debug_pool.read().await;
+        (debug.token_symbol.clone(), debug.pools.clone())
+    };
+    let registry = state.pool_registry.read().await;
+    let pairs = registry
+        .loaded_pools()
+        .into_iter()
+        .filter(|pool_id| !pool_id.is_debug())
+        .fold(PoolPairs::builtin(&debug_symbol), |pairs, pool_id| {
+            let config = registry.config(pool_id);
+            pairs.with_pair(pool_id, &config.base_symbol(), &config.quote_symbol())
+        });
+    debug_pools.iter().fold(pairs, |pairs, info| {
+        pairs.with_pair(info.pool_id, &info.token_symbol, "USDC")
+    })
+}
+
+/// All pools that trade `from`/`to` directly, in either direction.
+///
+/// A state file loaded for another pool's pair gives that pair a second
+/// pool; `select_pool` picks between them.
+fn candidate_pools(from: &str, to: &str, pairs: &PoolPairs) -> Vec<PoolId> {
+    let from = normalize_token(from, pairs.debug_symbol());
+    let to = normalize_token(to, pairs.debug_symbol());
+    pairs
+        .pairs
+        .iter()
+        .filter(|(_, base, quote)| {
+            (*base == from && *quote == to) || (*base == to && *quote == from)
+        })
+        .map(|(pool_id, _, _)| *pool_id)
+        .collect()
+}
+
+/// Pick a pool from `candidates`, favouring the earliest entry in `preferred`.
+///
+/// Returns the pool and whether the choice was ambiguous (several candidates).
+fn select_pool(candidates: &[PoolId], preferred: &[PoolId]) -> Option<(PoolId, bool)> {
+    let ambiguous = candidates.len() > 1;
+    let chosen = preferred
+        .iter()
+        .find(|pool_id| candidates.contains(pool_id))
+        .or_else(|| candidates.first())?;
+    Some((*chosen, ambiguous))
+}
+
+/// Hops a pool offers as `(hop, input token, output token)`: selling base, then buying it
+fn pool_hops(pool_id: PoolId, pairs: &PoolPairs) -> [(RouteHop, String, String); 2] {
+    let base = pairs.base_symbol(pool_id);
+    let quote = pairs.quote_symbol(pool_id);
+    [
+        (
+            RouteHop {
+                pool_id,
+                is_sell_base: true,
+            },
+            base.clone(),
+            quote.clone(),
+        ),
+        (
+            RouteHop {
+                pool_id,
+                is_sell_base: false,
+            },
+            quote,
+            base,
+        ),
+    ]
+}
+
+/// Token a hop hands to the next one (or to the caller, on the last hop)
+pub(super) fn hop_output_token(hop: &RouteHop, pairs: &PoolPairs) -> String {
+    if hop.is_sell_base {
+        pairs.quote_symbol(hop.pool_id)
+    } else {
+        pairs.base_symbol(hop.pool_id)
+    }
+}
+
+/// Every path from `from` to `to` of at most `max_hops` legs over distinct pools.
+///
+/// Shorter paths come first; within a length, pools are tried in `preferred`
+/// order, then in declaration order.
+pub(crate) fn candidate_paths(
+    from: &str,
+    to: &str,
+    pairs: &PoolPairs,
+    preferred: &[PoolId],
+    max_hops: usize,
+) -> Vec<Vec<RouteHop>> {
+    let mut pools: Vec<PoolId> = preferred
+        .iter()
+        .copied()
+        .filter(|pool_id| pairs.pairs.iter().any(|(id, _, _)| id == pool_id))
+        .collect();
+    for (pool_id, _, _) in &pairs.pairs {
+        if !pools.contains(pool_id) {
+            pools.push(*pool_id);
+        }
+    }
+    let hops: Vec<(RouteHop, String, String)> = pools
+        .iter()
+        .flat_map(|pool_id| pool_hops(*pool_id, pairs))
+        .collect();
+
+    let mut paths = Vec::new();
+    let mut frontier: Vec<(Vec<RouteHop>, &str)> = vec![(Vec::new(), from)];
+    for _ in 0..max_hops {
+        let mut next = Vec::new();
+        for (path, token) in &frontier {
+            for (hop, hop_in, hop_out) in &hops {
+                if hop_in != token || path.iter().any(|prev| prev.pool_id == hop.pool_id) {
+                    continue;
+                }
+                let mut extended = path.clone();
+                extended.push(*hop);
+                if hop_out == to {
+                    paths.push(extended);
+                } else if hop_out != from {
+                    next.push((extended, hop_out.as_str()));
+                }
+            }
+        }
+        frontier = next;
+    }
+    paths
+}
+
+/// First three-hop path from `from` to `to` that uses three distinct pools.
+fn find_three_hop_route(
+    from: &str,
+    to: &str,
+    pairs: &PoolPairs,
+    preferred: &[PoolId],
+) -> Option<[RouteHop; 3]> {
+    candidate_paths(from, to, pairs, preferred, 3)
+        .into_iter()
+        .find(|path| path.len() == 3)
+        .map(|path| [path[0], path[1], path[2]])
+}
+
+/// Determine the route for a swap, including two- and three-hop routes.
+///
+/// Routes longer than `max_hops` are never returned; three-hop routes are
+/// only searched for when no shorter route exists.
+/// Returns the route and whether any leg had more than one candidate pool.
+/// Callers reject same-token swaps first; this distinguishes a missing pool
+/// from two tokens that would both route through the same pool.
+fn determine_route(
+    from: &str,
+    to: &str,
+    pairs: &PoolPairs,
+    preferred: &[PoolId],
+    max_hops: usize,
+) -> ApiResult<(Route, bool)> {
+    let from_symbol = normalize_token(from, pairs.debug_symbol());
+    let to_symbol = normalize_token(to, pairs.debug_symbol());
+    let no_route = || {
+        let three_hop = if max_hops >= 3 {
+            find_three_hop_route(&from_symbol, &to_symbol, pairs, preferred)
+        } else {
+            None
+        };
+        three_hop
+            .map(|hops| (Route::ThreeHop { hops }, false))
+            .ok_or_else(|| {
+                ApiError::BadRequest(format!(
+                    "No route found for {} -> {} within {} hop(s)",
+                    from, to, max_hops
+                ))
+            })
+    };
+
+    // A pool trading the pair directly makes it a single-pool swap
+    let candidates = candidate_pools(&from_symbol, &to_symbol, pairs);
+    if let Some((pool_id, ambiguous)) = select_pool(&candidates, preferred) {
+        return Ok((Route::SinglePool(pool_id), ambiguous));
+    }
+
+    // Otherwise sell `from` for a pool's quote coin and buy `to` with it
+    if max_hops < 2 {
+        if candidate_paths(&from_symbol, &to_symbol, pairs, preferred, MAX_ROUTE_HOPS).is_empty() {
+            return no_route();
+        }
+        return Err(ApiError::BadRequest(format!(
+            "{} -> {} needs a multi-hop route but ROUTER_MAX_HOPS is {}",
+            from, to, max_hops
+        )));
+    }
+    let two_hop = candidate_paths(&from_symbol, &to_symbol, pairs, preferred, 2)
+        .iter()
+        .filter_map(|path| Route::from_path(path))
+        .find(|route| matches!(route, Route::TwoHop { .. }));
+    if let Some(route) = two_hop {
+        return Ok((route, false));
+    }
+
+    // Selling into the quote and buying back in the same pool is just a round trip
+    let round_trip_pool = pairs
+        .pairs
+        .iter()
+        .find(|(_, base, _)| *base == from_symbol && *base == to_symbol);
+    if let Some((pool_id, _, quote)) = round_trip_pool {
+        return Err(ApiError::BadRequest(format!(
+            "{} and {} both route through {}; a two-hop swap through a single pool \
+             would only round-trip via {} and is not supported \
+             (use /api/orderbook/two-sided-quote to price a round trip)",
+            from,
+            to,
+            pool_id.display_name(),
+            quote
+        )));
+    }
+
+    no_route()
+}
+
+/// Parse `PREFERRED_POOLS` (comma-separated pool ids, highest priority first).
+///
+/// Unknown entries are logged and skipped.
+pub(crate) fn preferred_pools_from_env() -> Vec<PoolId> {
+    let Ok(raw) = std::env::var("PREFERRED_POOLS") else {
+        return Vec::new();
+    };
+    raw.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let pool_id = PoolId::from_str(entry);
+            if pool_id.is_none() {
+                tracing::warn!("Ignoring unknown pool '{}' in PREFERRED_POOLS", entry);
+            }
+            pool_id
+        })
+        .collect()
+}
+
+/// Default route length when `ROUTER_MAX_HOPS` is unset
+const DEFAULT_MAX_ROUTE_HOPS: usize = 2;
+
+/// Longest route the quote endpoint can price
+const MAX_ROUTE_HOPS: usize = 3;
+
+/// Parse `ROUTER_MAX_HOPS` (1-3), falling back to 2 on missing or invalid values.
+pub(crate) fn max_route_hops_from_env() -> usize {
+    match std::env::var("ROUTER_MAX_HOPS") {
+        Ok(raw) => match raw.trim().parse::<usize>() {
+            Ok(hops) if (1..=MAX_ROUTE_HOPS).contains(&hops) => hops,
+            _ => {
+                tracing::warn!(
+                    "Ignoring invalid ROUTER_MAX_HOPS '{}' (expected 1-{}); using {}",
+                    raw,
+                    MAX_ROUTE_HOPS,
+                    DEFAULT_MAX_ROUTE_HOPS
+                );
+                DEFAULT_MAX_ROUTE_HOPS
+            }
+        },
+        Err(_) => DEFAULT_MAX_ROUTE_HOPS,
+    }
+}
+
+pub(super) fn get_decimals(token: &str, debug_symbol: &str) -> u8 {
+    if is_debug_token(token, debug_symbol) {
+        return units::DEBUG_TOKEN_DECIMALS;
+    }
+    units::token_decimals(token).unwrap_or(units::DEFAULT_DECIMALS)
+}
+
+/// Parse a request `amount` denominated in `token`; see [`units::parse_amount`].
+pub(super) fn parse_request_amount(
+    amount: &str,
+    token: &str,
+    debug_symbol: &str,
+) -> ApiResult<u64> {
+    units::parse_amount(amount, get_decimals(token, debug_symbol)).map_err(ApiError::BadRequest)
+}
+
+pub(crate) fn normalize_token(token: &str, debug_symbol: &str) -> String {
+    let upper = token.to_uppercase();
+    if is_debug_token(&upper, debug_symbol) {
+        debug_symbol.to_uppercase()
+    } else {
+        upper
+    }
+}
+
+/// Resolve normalized token symbols and route for a swap or quote request.
+///
+/// When `from_type`/`to_type` are provided, symbol normalization is bypassed
+/// and the pool is resolved by matching the type parameters of loaded pools.
+/// The final flag reports whether several pools could serve the pair.
+pub(super) async fn resolve_route(
+    state: &AppState,
+    from_token: &str,
+    to_token: &str,
+    from_type: Option<&str>,
+    to_type: Option<&str>,
+    pool: Option<&str>,
+) -> ApiResult<(String, String, Route, bool)> {
+    let (from_type, to_type) = match (from_type, to_type) {
+        (Some(f), Some(t)) => (f, t),
+        (None, None) => {
+            let pairs = pool_pairs(state).await;
+            let from = normalize_token(from_token, pairs.debug_symbol());
+            let to = normalize_token(to_token, pairs.debug_symbol());
+            if from == to {
+                return Err(ApiError::BadRequest(format!(
+                    "Cannot swap same token: {} -> {}",
+                    from, to
+                )));
+            }
+
+            // Explicit pool overrides route detection
+            let (route, ambiguous) = if let Some(p) = pool {
+                let pool_id = PoolId::from_str(p)
+                    .ok_or_else(|| ApiError::BadRequest(format!("Invalid pool: {}", p)))?;
+                (Route::SinglePool(pool_id), false)
+            } else {
+                determine_route(
+                    &from,
+                    &to,
+                    &pairs,
+                    &state.preferred_pools,
+                    state.max_route_hops,
+                )?
+            };
+            return Ok((from, to, route, ambiguous));
+        }
+        _ => {
+            return Err(ApiError::BadRequest(
+                "from_type and to_type must be provided together".into(),
+            ))
+        }
+    };
+
+    let router = state.router.as_ref().ok_or_else(|| {
+        ApiError::ServiceUnavailable("MoveVM router is not initialized for type resolution".into())
+    })?;
+
+    // The debug pool is only registered with the router once created.
+    let debug_type = state.debug_pool.read().await.token_type.clone();
+    if from_type.trim() == debug_type || to_type.trim() == debug_type {
+        ensure_debug_pool_and_sync(state, router).await?;
+    }
+
+    let type_route = router
+        .resolve_type_route(from_type.to_string(), to_type.to_string())
+        .await
+        .map_err(router_error(|e| ApiError::BadRequest(e.to_string())))?
+        .ok_or_else(|| {
+            ApiError::BadRequest(format!(
+                "No pool found for types {} -> {}",
+                from_type, to_type
+            ))
+        })?;
+
+    let pairs = pool_pairs(state).await;
+    let resolved = match type_route {
+        TypeRoute::Direct {
+            pool_id,
+            is_sell_base,
+        } => {
+            if let Some(p) = pool {
+                if PoolId::from_str(p) != Some(pool_id) {
+                    return Err(ApiError::BadRequest(format!(
+                        "Pool {} does not match types {} -> {}",
+                        p, from_type, to_type
+                    )));
+                }
+            }
+            let base = pairs.base_symbol(pool_id);
+            let quote = pairs.quote_symbol(pool_id);
+            let (from, to) = if is_sell_base {
+                (base, quote)
+            } else {
+                (quote, base)
+            };
+            (from, to, Route::SinglePool(pool_id), false)
+        }
+        TypeRoute::TwoHop { from_pool, to_pool } => {
+            if pool.is_some() {
+                return Err(ApiError::BadRequest(
+                    "Explicit pool is not supported for two-hop type routes".into(),
+                ));
+            }
+            (
+                pairs.base_symbol(from_pool),
+                pairs.base_symbol(to_pool),
+                Route::TwoHop {
+                    first_pool: from_pool,
+                    second_pool: to_pool,
+                },
+                false,
+            )
+        }
+    };
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEBUG_SYMBOL: &str = "TEST";
+
+    fn bad_request(result: ApiResult<(Route, bool)>) -> String {
+        match result {
+            Err(ApiError::BadRequest(message)) => message,
+            Err(e) => panic!("unexpected error: {}", e),
+            Ok(_) => panic!("expected the route to be rejected"),
+        }
+    }
+
+    #[test]
+    fn test_determine_route_picks_direct_and_two_hop_routes() {
+        let pairs = PoolPairs::builtin(DEBUG_SYMBOL);
+        let route = |from, to| determine_route(from, to, &pairs, &[], 2).unwrap();
+
+        assert!(matches!(
+            route("SUI", "USDC"),
+            (Route::SinglePool(PoolId::SuiUsdc), false)
+        ));
+        assert!(matches!(
+            route("usdc", "wal"),
+            (Route::SinglePool(PoolId::WalUsdc), false)
+        ));
+        assert!(matches!(
+            route("test", "USDC"),
+            (Route::SinglePool(PoolId::DebugUsdc), false)
+        ));
+        assert!(matches!(
+            route("SUI", "WAL"),
+            (
+                Route::TwoHop {
+                    first_pool: PoolId::SuiUsdc,
+                    second_pool: PoolId::WalUsdc,
+                },
+                false
+            )
+        ));
+    }
+
+    #[test]
+    fn test_determine_route_rejects_unroutable_pairs() {
+        let pairs = PoolPairs::builtin(DEBUG_SYMBOL);
+        let err = bad_request(determine_route("SUI", "WAL", &pairs, &[], 1));
+        assert!(err.contains("ROUTER_MAX_HOPS is 1"), "{}", err);
+
+        let err = bad_request(determine_route("SUI", "FOO", &pairs, &[], 2));
+        assert_eq!(err, "No route found for SUI -> FOO within 2 hop(s)");
+        let err = bad_request(determine_route("USDC", "FOO", &pairs, &[], 2));
+        assert_eq!(err, "No route found for USDC -> FOO within 2 hop(s)");
+
+        // Both aliases of the debug token sit on the same pool
+        let err = bad_request(determine_route("DBG", "TEST", &pairs, &[], 2));
+        assert!(err.contains("both route through"), "{}", err);
+    }
+
+    #[test]
+    fn test_select_pool_prefers_configured_pools() {
+        let candidates = [PoolId::SuiUsdc, PoolId::DeepUsdc];
+        assert_eq!(
+            select_pool(&candidates, &[PoolId::DeepUsdc]),
+            Some((PoolId::DeepUsdc, true))
+        );
+        assert_eq!(
+            select_pool(&candidates, &[PoolId::WalUsdc]),
+            Some((PoolId::SuiUsdc, true))
+        );
+        assert_eq!(
+            select_pool(&[PoolId::SuiUsdc], &[]),
+            Some((PoolId::SuiUsdc, false))
+        );
+        assert_eq!(select_pool(&[], &[PoolId::SuiUsdc]), None);
+    }
+
+    #[test]
+    fn test_candidate_paths_sell_into_usdc_then_buy() {
+        let pairs = PoolPairs::builtin(DEBUG_SYMBOL);
+        let paths = candidate_paths("SUI", "WAL", &pairs, &[], 2);
+        assert_eq!(
+            paths,
+            vec![vec![
+                RouteHop {
+                    pool_id: PoolId::SuiUsdc,
+                    is_sell_base: true,
+                },
+                RouteHop {
+                    pool_id: PoolId::WalUsdc,
+                    is_sell_base: false,
+                },
+            ]]
+        );
+        assert!(candidate_paths("SUI", "WAL", &pairs, &[], 1).is_empty());
+        assert_eq!(hop_output_token(&paths[0][0], &pairs), "USDC");
+        assert_eq!(hop_output_token(&paths[0][1], &pairs), "WAL");
+    }
+
+    #[test]
+    fn test_routes_follow_loaded_pairs() {
+        // DEEP/USDC's state file holds a DEEP/SUI pool
+        let pairs = PoolPairs::builtin(DEBUG_SYMBOL).with_pair(PoolId::DeepUsdc, "deep", "sui");
+        assert_eq!(pairs.quote_symbol(PoolId::DeepUsdc), "SUI");
+        assert!(pairs.is_sell_base(PoolId::DeepUsdc, "DEEP"));
+        assert!(!pairs.is_sell_base(PoolId::DeepUsdc, "SUI"));
+
+        let route = |from, to, max_hops| determine_route(from, to, &pairs, &[], max_hops);
+        assert!(matches!(
+            route("SUI", "DEEP", 2).unwrap(),
+            (Route::SinglePool(PoolId::DeepUsdc), false)
+        ));
+        // DEEP is no longer quoted in USDC: USDC -> DEEP would buy SUI, then
+        // buy DEEP with it, which is not a sell-then-buy two-hop swap
+        let err = bad_request(route("USDC", "DEEP", 2));
+        assert_eq!(err, "No route found for USDC -> DEEP within 2 hop(s)");
+        assert_eq!(
+            candidate_paths("USDC", "DEEP", &pairs, &[], 2),
+            vec![vec![
+                RouteHop {
+                    pool_id: PoolId::SuiUsdc,
+                    is_sell_base: false,
+                },
+                RouteHop {
+                    pool_id: PoolId::DeepUsdc,
+                    is_sell_base: false,
+                },
+            ]]
+        );
+        // WAL and DEEP no longer share a quote coin for a two-hop swap
+        assert!(matches!(
+            route("WAL", "DEEP", 3).unwrap(),
+            (Route::ThreeHop { .. }, false)
+        ));
+    }
+
+    #[test]
+    fn test_routes_include_manifest_pools() {
+        // A POOLS_MANIFEST pool with no built-in id
+        let sui_deep = PoolId::register("sui_deep_route_test").unwrap();
+        let pairs = PoolPairs::builtin(DEBUG_SYMBOL).with_pair(sui_deep, "sui", "deep");
+        assert_eq!(pairs.base_symbol(sui_deep), "SUI");
+        assert_eq!(pairs.quote_for_base("SUI").as_deref(), Some("USDC"));
+
+        let route = |from, to| determine_route(from, to, &pairs, &[sui_deep], 2);
+        assert!(matches!(
+            route("SUI", "DEEP").unwrap(),
+            (Route::SinglePool(pool_id), false) if pool_id == sui_deep
+        ));
+        assert!(matches!(
+            route("DEEP", "SUI").unwrap(),
+            (Route::SinglePool(pool_id), false) if pool_id == sui_deep
+        ));
+    }
+
+    #[test]
+    fn test_normalize_token_and_request_amount_decimals() {
+        assert_eq!(normalize_token("dbg", DEBUG_SYMBOL), "TEST");
+        assert_eq!(normalize_token("Debug", DEBUG_SYMBOL), "TEST");
+        assert_eq!(normalize_token("sui", DEBUG_SYMBOL), "SUI");
+
+        assert_eq!(
+            parse_request_amount("1.5", "SUI", DEBUG_SYMBOL).unwrap(),
+            1_500_000_000
+        );
+        assert_eq!(
+            parse_request_amount("1.5", "USDC", DEBUG_SYMBOL).unwrap(),
+            1_500_000
+        );
+        assert_eq!(
+            parse_request_amount("1500", "USDC", DEBUG_SYMBOL).unwrap(),
+            1_500
+        );
+        assert!(matches!(
+            parse_request_amount("-1", "SUI", DEBUG_SYMBOL),
+            Err(ApiError::BadRequest(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_route_validates_tokens_types_and_pools() {
+        let state = AppState::for_tests();
+        let resolve = |from: &'static str, to: &'static str, pool: Option<&'static str>| {
+            let state = state.clone();
+            async move { resolve_route(&state, from, to, None, None, pool).await }
+        };
+
+        let (from, to, route, ambiguous) = resolve("sui", "wal", None).await.unwrap();
+        assert_eq!((from.as_str(), to.as_str()), ("SUI", "WAL"));
+        assert!(matches!(route, Route::TwoHop { .. }));
+        assert!(!ambiguous);
+
+        let (_, _, route, _) = resolve("SUI", "USDC", Some("sui_usdc")).await.unwrap();
+        assert!(matches!(route, Route::SinglePool(PoolId::SuiUsdc)));
+
+        let err = resolve("sui", "SUI", None).await.map(|_| ()).unwrap_err();
+        assert!(
+            err.to_string().contains("Cannot swap same token"),
+            "{}",
+            err
+        );
+        let err = resolve("SUI", "USDC", Some("nope"))
+            .await
+            .map(|_| ())
+            .unwrap_err();
+        assert!(err.to_string().contains("Invalid pool: nope"), "{}", err);
+
+        let err = resolve_route(&state, "", "", Some("0x2::sui::SUI"), None, None)
+            .await
+            .map(|_| ())
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("must be provided together"),
+            "{}",
+            err
+        );
+        // Type routes are resolved by the router, which this state lacks
+        let err = resolve_route(
+            &state,
+            "",
+            "",
+            Some("0x2::sui::SUI"),
+            Some("0xdba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7::usdc::USDC"),
+            None,
+        )
+        .await
+        .map(|_| ())
+        .unwrap_err();
+        assert!(matches!(err, ApiError::ServiceUnavailable(_)), "{}", err);
+    }
+}
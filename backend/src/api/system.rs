@@ -1,9 +1,16 @@
 //! System-level diagnostic endpoints.
 
-use axum::{extract::State, Json};
+use axum::{extract::State, http::header, response::IntoResponse, Json};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
-use crate::api::AppState;
-use crate::sandbox::router::RouterStartupCheckReport;
+use crate::api::{router_error, AppState, DebugPoolState};
+use crate::sandbox::router::{
+    EpochAdvanceReport, RouterClockStatus, RouterContractInfo, RouterHealthCheckReport,
+    RouterPackageCheck, RouterReserveCoinCheck, RouterSnapshotInfo, RouterStartupCheckReport,
+};
+use crate::sandbox::router_snapshot::snapshot_path_from_env;
+use crate::sandbox::state_loader::{PoolId, RegistrySummary};
 use crate::types::{ApiError, ApiResult};
 
 /// GET /api/startup-check - Return fail-fast startup self-check diagnostics.
@@ -13,13 +20,535 @@ pub async fn get_startup_check(
     let router = state
         .router
         .as_ref()
-        .ok_or_else(|| ApiError::Internal("MoveVM router is not initialized".into()))?;
+        .ok_or_else(|| ApiError::ServiceUnavailable("MoveVM router is not initialized".into()))?;
+
+    let report = router.startup_check().await.map_err(router_error(|e| {
+        ApiError::Internal(format!("Failed to query startup-check: {}", e))
+    }))?;
+
+    Ok(Json(report))
+}
+
+/// POST /api/router/startup-check - Re-run the startup self-check against the live VM.
+///
+/// Replaces the cached report served by `GET /api/startup-check`. A failing
+/// check is returned with `ok: false` rather than as an error.
+pub async fn refresh_startup_check(
+    State(state): State<AppState>,
+) -> ApiResult<Json<RouterStartupCheckReport>> {
+    let router = state
+        .router
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("MoveVM router is not initialized".into()))?;
+
+    let report = router
+        .refresh_startup_check()
+        .await
+        .map_err(router_error(|e| {
+            ApiError::Internal(format!("Failed to refresh startup-check: {}", e))
+        }))?;
+
+    Ok(Json(report))
+}
+
+/// POST /api/router/reset - Rebuild router pool state from the original state files.
+///
+/// Discards every swap, order and faucet mutation in the VM. The debug pool
+/// is gone afterwards and must be re-created with `POST /api/debug/pool`.
+pub async fn reset_router(
+    State(state): State<AppState>,
+) -> ApiResult<Json<RouterStartupCheckReport>> {
+    let router = state
+        .router
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("MoveVM router is not initialized".into()))?;
+
+    let report = router.reset().await.map_err(router_error(|e| {
+        ApiError::Internal(format!("Failed to reset router: {}", e))
+    }))?;
+    *state.debug_pool.write().await = DebugPoolState::default();
+
+    Ok(Json(report))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetEpochRequest {
+    pub epoch: u64,
+}
+
+/// POST /api/router/epoch - Advance the simulation epoch.
+///
+/// Pools get a zero `history::Volumes` entry for the new epoch, so fee and
+/// volume accounting after the bump runs against the current trade params.
+pub async fn set_router_epoch(
+    State(state): State<AppState>,
+    Json(req): Json<SetEpochRequest>,
+) -> ApiResult<Json<EpochAdvanceReport>> {
+    let router = state
+        .router
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("MoveVM router is not initialized".into()))?;
 
     let report = router
-        .startup_check()
+        .set_epoch(req.epoch)
         .await
-        .map_err(|e| ApiError::Internal(format!("Failed to query startup-check: {}", e)))?;
+        .map_err(router_error(|e| {
+            ApiError::BadRequest(format!("Failed to set epoch: {}", e))
+        }))?;
 
     Ok(Json(report))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SnapshotRequest {
+    /// Snapshot file; defaults to `SNAPSHOT_PATH`
+    pub path: Option<String>,
+}
+
+fn snapshot_path(req: &SnapshotRequest) -> ApiResult<PathBuf> {
+    match req
+        .path
+        .as_deref()
+        .map(str::trim)
+        .filter(|path| !path.is_empty())
+    {
+        Some(path) => Ok(PathBuf::from(path)),
+        None => snapshot_path_from_env()
+            .ok_or_else(|| ApiError::BadRequest("Provide path or set SNAPSHOT_PATH".into())),
+    }
+}
+
+/// POST /api/router/snapshot/save - Write the router VM environment to a snapshot file.
+pub async fn save_router_snapshot(
+    State(state): State<AppState>,
+    Json(req): Json<SnapshotRequest>,
+) -> ApiResult<Json<RouterSnapshotInfo>> {
+    let path = snapshot_path(&req)?;
+    let router = state
+        .router
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("MoveVM router is not initialized".into()))?;
+
+    let info = router.save_snapshot(path).await.map_err(router_error(|e| {
+        ApiError::Internal(format!("Failed to save router snapshot: {}", e))
+    }))?;
+    Ok(Json(info))
+}
+
+/// POST /api/router/snapshot/load - Replace the router VM environment with a snapshot.
+///
+/// As with a reset, the debug pool must be re-created afterwards.
+pub async fn load_router_snapshot(
+    State(state): State<AppState>,
+    Json(req): Json<SnapshotRequest>,
+) -> ApiResult<Json<RouterSnapshotInfo>> {
+    let path = snapshot_path(&req)?;
+    if !path.exists() {
+        return Err(ApiError::BadRequest(format!(
+            "Snapshot file not found: {}",
+            path.display()
+        )));
+    }
+    let router = state
+        .router
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("MoveVM router is not initialized".into()))?;
+
+    let info = router.load_snapshot(path).await.map_err(router_error(|e| {
+        ApiError::BadRequest(format!("Failed to load router snapshot: {}", e))
+    }))?;
+    *state.debug_pool.write().await = DebugPoolState::default();
+
+    Ok(Json(info))
+}
+
+/// GET /api/router/contract - Router contract address, modules, and build method.
+pub async fn get_router_contract(
+    State(state): State<AppState>,
+) -> ApiResult<Json<RouterContractInfo>> {
+    let router = state
+        .router
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("MoveVM router is not initialized".into()))?;
+
+    let contract = router.router_contract().await.map_err(router_error(|e| {
+        ApiError::Internal(format!("Failed to query router contract: {}", e))
+    }))?;
+
+    Ok(Json(contract))
+}
+
+/// Two-hop route to probe
+#[derive(Debug, Deserialize)]
+pub struct HealthCheckPair {
+    pub from_pool: String,
+    pub to_pool: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HealthCheckRequest {
+    /// Routes tried in order; defaults to `ROUTER_HEALTH_CHECK_PAIRS`
+    pub pairs: Option<Vec<HealthCheckPair>>,
+    /// Input sizes in smallest units; defaults to `ROUTER_HEALTH_CHECK_AMOUNTS`
+    pub amounts: Option<Vec<String>>,
+}
+
+/// POST /api/router/health-check - Run the two-hop quote health check on demand.
+///
+/// Stops at the first successful probe; a failed check is reported with
+/// `passed: false` rather than as an error.
+pub async fn run_router_health_check(
+    State(state): State<AppState>,
+    Json(req): Json<HealthCheckRequest>,
+) -> ApiResult<Json<RouterHealthCheckReport>> {
+    let parse_pool = |id: &str| {
+        PoolId::from_str(id).ok_or_else(|| ApiError::BadRequest(format!("Invalid pool: {}", id)))
+    };
+    let pairs = req
+        .pairs
+        .map(|pairs| {
+            if pairs.is_empty() {
+                return Err(ApiError::BadRequest("pairs must not be empty".into()));
+            }
+            pairs
+                .iter()
+                .map(|pair| {
+                    let from_pool = parse_pool(&pair.from_pool)?;
+                    let to_pool = parse_pool(&pair.to_pool)?;
+                    if from_pool == to_pool {
+                        return Err(ApiError::BadRequest(format!(
+                            "Health check pair must use two different pools: {}",
+                            from_pool.as_str()
+                        )));
+                    }
+                    Ok((from_pool, to_pool))
+                })
+                .collect::<ApiResult<Vec<_>>>()
+        })
+        .transpose()?;
+    let amounts = req
+        .amounts
+        .map(|amounts| {
+            if amounts.is_empty() {
+                return Err(ApiError::BadRequest("amounts must not be empty".into()));
+            }
+            amounts
+                .iter()
+                .map(|raw| match raw.parse::<u64>() {
+                    Ok(amount) if amount > 0 => Ok(amount),
+                    _ => Err(ApiError::BadRequest(format!("Invalid amount: {}", raw))),
+                })
+                .collect::<ApiResult<Vec<_>>>()
+        })
+        .transpose()?;
+
+    let router = state
+        .router
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("MoveVM router is not initialized".into()))?;
+
+    let report = router
+        .health_check(pairs, amounts)
+        .await
+        .map_err(router_error(|e| {
+            ApiError::Internal(format!("Failed to run router health check: {}", e))
+        }))?;
+
+    Ok(Json(report))
+}
+
+#[derive(Debug, Serialize)]
+pub struct SenderResponse {
+    pub sender: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_sender: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetSenderRequest {
+    pub sender: String,
+}
+
+/// GET /api/router/sender - Return the current VM sender address.
+pub async fn get_router_sender(State(state): State<AppState>) -> ApiResult<Json<SenderResponse>> {
+    let router = state
+        .router
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("MoveVM router is not initialized".into()))?;
+
+    let sender = router.sender().await.map_err(router_error(|e| {
+        ApiError::Internal(format!("Failed to query VM sender: {}", e))
+    }))?;
+
+    Ok(Json(SenderResponse {
+        sender,
+        previous_sender: None,
+    }))
+}
+
+/// POST /api/router/sender - Set the default VM sender for swaps and faucets.
+pub async fn set_router_sender(
+    State(state): State<AppState>,
+    Json(req): Json<SetSenderRequest>,
+) -> ApiResult<Json<SenderResponse>> {
+    let router = state
+        .router
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("MoveVM router is not initialized".into()))?;
+
+    let previous_sender = router
+        .set_sender(req.sender)
+        .await
+        .map_err(router_error(|e| ApiError::BadRequest(e.to_string())))?;
+    let sender = router.sender().await.map_err(router_error(|e| {
+        ApiError::Internal(format!("Failed to query VM sender: {}", e))
+    }))?;
+
+    Ok(Json(SenderResponse {
+        sender,
+        previous_sender: Some(previous_sender),
+    }))
+}
+
+/// GET /api/router/clock - Synthetic VM clock timestamp, step, and frozen flag.
+pub async fn get_router_clock(State(state): State<AppState>) -> ApiResult<Json<RouterClockStatus>> {
+    let router = state
+        .router
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("MoveVM router is not initialized".into()))?;
+
+    let status = router.clock_status().await.map_err(router_error(|e| {
+        ApiError::Internal(format!("Failed to query VM clock: {}", e))
+    }))?;
+    Ok(Json(status))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetClockRequest {
+    /// Freeze (`true`) or resume (`false`) the per-input clock advance
+    pub frozen: Option<bool>,
+    /// Move the clock forward by this many milliseconds
+    pub advance_ms: Option<u64>,
+}
+
+/// POST /api/router/clock - Freeze/resume the synthetic clock or advance it manually.
+pub async fn set_router_clock(
+    State(state): State<AppState>,
+    Json(req): Json<SetClockRequest>,
+) -> ApiResult<Json<RouterClockStatus>> {
+    if req.frozen.is_none() && req.advance_ms.is_none() {
+        return Err(ApiError::BadRequest(
+            "Provide frozen and/or advance_ms".into(),
+        ));
+    }
+    let router = state
+        .router
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("MoveVM router is not initialized".into()))?;
+
+    let status = router
+        .set_clock(req.frozen, req.advance_ms)
+        .await
+        .map_err(router_error(|e| {
+            ApiError::Internal(format!("Failed to update VM clock: {}", e))
+        }))?;
+    Ok(Json(status))
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReserveStatusResponse {
+    pub reserve_coins: Vec<RouterReserveCoinCheck>,
+}
+
+/// GET /api/router/reserves - Live VM reserve coins with bootstrap checkpoint provenance.
+pub async fn get_reserve_status(
+    State(state): State<AppState>,
+) -> ApiResult<Json<ReserveStatusResponse>> {
+    let router = state
+        .router
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("MoveVM router is not initialized".into()))?;
+
+    let reserve_coins = router.reserve_status().await.map_err(router_error(|e| {
+        ApiError::Internal(format!("Failed to query reserve status: {}", e))
+    }))?;
+
+    Ok(Json(ReserveStatusResponse { reserve_coins }))
+}
+
+/// Per-pool MoveVM orderbook build status captured at startup.
+#[derive(Debug, Serialize)]
+pub struct OrderbookBuildStatus {
+    pub pool_id: PoolId,
+    pub pool_name: String,
+    pub built: bool,
+    pub bid_levels: usize,
+    pub ask_levels: usize,
+    pub mid_price: Option<f64>,
+    pub checkpoint: Option<u64>,
+    pub skipped_filled_orders: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FullStatusResponse {
+    pub ok: bool,
+    pub router_initialized: bool,
+    pub startup_check: Option<RouterStartupCheckReport>,
+    pub orderbooks: Vec<OrderbookBuildStatus>,
+    pub reserve_coins: Vec<RouterReserveCoinCheck>,
+    pub packages: Vec<RouterPackageCheck>,
+    pub pool_registry: RegistrySummary,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DetailedHealthResponse {
+    /// "ok", or "degraded" when pools, orderbooks or the router are missing
+    pub status: &'static str,
+    pub pools_loaded: usize,
+    pub pools_expected: usize,
+    pub orderbooks_built: usize,
+    pub router_initialized: bool,
+    /// `ok` of the router's last startup self-check (false without a router)
+    pub startup_check_ok: bool,
+    /// Router initialized and its startup self-check passed: quotes are served
+    pub router_ready: bool,
+    pub issues: Vec<String>,
+}
+
+/// GET /health/detailed - Cheap liveness summary that names a degraded startup.
+///
+/// Unlike `/api/status/full` this never calls into the router thread.
+pub async fn get_detailed_health(State(state): State<AppState>) -> Json<DetailedHealthResponse> {
+    let (pools_expected, pools_loaded) = {
+        let registry = state.pool_registry.read().await;
+        (
+            registry.expected_pools().len(),
+            registry.loaded_pools().len(),
+        )
+    };
+    let orderbooks_built = state.orderbooks.read().await.len();
+    let router_initialized = state.router.is_some();
+    let startup_check_ok = state
+        .router
+        .as_ref()
+        .is_some_and(|router| router.startup_check_ok());
+
+    let mut issues = Vec::new();
+    if pools_loaded == 0 {
+        issues.push("No pools loaded: no pool state files were found at startup".to_string());
+    } else if pools_loaded < pools_expected {
+        issues.push(format!(
+            "Only {}/{} pools loaded",
+            pools_loaded, pools_expected
+        ));
+    }
+    if orderbooks_built < pools_loaded {
+        issues.push(format!(
+            "Only {}/{} orderbooks built",
+            orderbooks_built, pools_loaded
+        ));
+    }
+    if !router_initialized {
+        issues.push("MoveVM router is not initialized".to_string());
+    } else if !startup_check_ok {
+        issues.push("Router startup self-check failed".to_string());
+    }
+
+    Json(DetailedHealthResponse {
+        status: if issues.is_empty() { "ok" } else { "degraded" },
+        pools_loaded,
+        pools_expected,
+        orderbooks_built,
+        router_initialized,
+        startup_check_ok,
+        router_ready: router_initialized && startup_check_ok,
+        issues,
+    })
+}
+
+/// GET /metrics - Quote/swap counters and router latency in Prometheus text format.
+pub async fn get_metrics(State(state): State<AppState>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.metrics.render(),
+    )
+}
+
+/// GET /api/status/full - Aggregate startup diagnostics into one document.
+///
+/// Combines the router startup self-check, per-pool orderbook build status,
+/// reserve coin values, loaded packages, and the pool registry summary.
+pub async fn get_full_status(State(state): State<AppState>) -> ApiResult<Json<FullStatusResponse>> {
+    let mut errors = Vec::new();
+
+    let startup_check = match state.router.as_ref() {
+        Some(router) => match router.startup_check().await {
+            Ok(report) => Some(report),
+            Err(e) => {
+                errors.push(format!("Failed to query startup-check: {}", e));
+                None
+            }
+        },
+        None => {
+            errors.push("MoveVM router is not initialized".to_string());
+            None
+        }
+    };
+
+    let orderbooks = {
+        let expected = state.pool_registry.read().await.expected_pools();
+        let orderbooks = state.orderbooks.read().await;
+        expected
+            .iter()
+            .map(|pool_id| match orderbooks.get(pool_id) {
+                Some(ob) => OrderbookBuildStatus {
+                    pool_id: *pool_id,
+                    pool_name: pool_id.display_name().to_string(),
+                    built: true,
+                    bid_levels: ob.bids.len(),
+                    ask_levels: ob.asks.len(),
+                    mid_price: ob.mid_price(),
+                    checkpoint: Some(ob.checkpoint),
+                    skipped_filled_orders: ob.skipped_filled_orders,
+                },
+                None => OrderbookBuildStatus {
+                    pool_id: *pool_id,
+                    pool_name: pool_id.display_name().to_string(),
+                    built: false,
+                    bid_levels: 0,
+                    ask_levels: 0,
+                    mid_price: None,
+                    checkpoint: None,
+                    skipped_filled_orders: 0,
+                },
+            })
+            .collect::<Vec<_>>()
+    };
+    for status in orderbooks.iter().filter(|s| !s.built) {
+        errors.push(format!("Orderbook not built for {}", status.pool_name));
+    }
+
+    let pool_registry = state.pool_registry.read().await.summary();
+
+    let (reserve_coins, packages) = match startup_check.as_ref() {
+        Some(report) => {
+            errors.extend(report.errors.iter().cloned());
+            (report.reserve_coins.clone(), report.packages.clone())
+        }
+        None => (Vec::new(), Vec::new()),
+    };
+
+    let ok = startup_check.as_ref().map(|r| r.ok).unwrap_or(false) && errors.is_empty();
+
+    Ok(Json(FullStatusResponse {
+        ok,
+        router_initialized: state.router.is_some(),
+        startup_check,
+        orderbooks,
+        reserve_coins,
+        packages,
+        pool_registry,
+        errors,
+    }))
+}
@@ -0,0 +1,227 @@
+//! Per-pool recent trades decoded from DeepBook `OrderFilled` events
+//!
+//! Swap handlers feed the events of every executed PTB into [`TradeTape`],
+//! which keeps the newest fills per pool in a ring buffer sized by
+//! `TRADE_TAPE_CAPACITY`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+use crate::sandbox::router::SwapEvent;
+use crate::sandbox::state_loader::PoolId;
+use crate::units;
+
+const DEFAULT_CAPACITY: usize = 200;
+
+/// One taker fill against a resting order
+#[derive(Debug, Clone, Serialize)]
+pub struct Trade {
+    /// Quote per base (human-readable)
+    pub price: f64,
+    /// Raw DeepBook price
+    pub price_raw: String,
+    /// Base quantity filled (human-readable)
+    pub quantity: f64,
+    pub quantity_raw: String,
+    pub quote_quantity_raw: String,
+    /// Taker side: "buy" (taker bid) or "sell" (taker ask)
+    pub side: String,
+    /// VM clock timestamp of the fill
+    pub timestamp_ms: u64,
+    /// Taker balance manager id
+    pub taker: String,
+    pub maker_order_id: String,
+}
+
+/// BCS layout of DeepBook `order_info::OrderFilled`
+#[derive(Debug, Deserialize)]
+struct OrderFilledBcs {
+    pool_id: [u8; 32],
+    maker_order_id: u128,
+    _taker_order_id: u128,
+    _maker_client_order_id: u64,
+    _taker_client_order_id: u64,
+    price: u64,
+    taker_is_bid: bool,
+    _taker_fee: u64,
+    _taker_fee_is_deep: bool,
+    _maker_fee: u64,
+    _maker_fee_is_deep: bool,
+    base_quantity: u64,
+    quote_quantity: u64,
+    _maker_balance_manager_id: [u8; 32],
+    taker_balance_manager_id: [u8; 32],
+    timestamp: u64,
+}
+
+/// `OrderFilled` as emitted by package versions without the `*_fee_is_deep` flags
+#[derive(Debug, Deserialize)]
+struct LegacyOrderFilledBcs {
+    pool_id: [u8; 32],
+    maker_order_id: u128,
+    _taker_order_id: u128,
+    _maker_client_order_id: u64,
+    _taker_client_order_id: u64,
+    price: u64,
+    taker_is_bid: bool,
+    _taker_fee: u64,
+    _maker_fee: u64,
+    base_quantity: u64,
+    quote_quantity: u64,
+    _maker_balance_manager_id: [u8; 32],
+    taker_balance_manager_id: [u8; 32],
+    timestamp: u64,
+}
+
+impl From<LegacyOrderFilledBcs> for OrderFilledBcs {
+    fn from(legacy: LegacyOrderFilledBcs) -> Self {
+        Self {
+            pool_id: legacy.pool_id,
+            maker_order_id: legacy.maker_order_id,
+            _taker_order_id: legacy._taker_order_id,
+            _maker_client_order_id: legacy._maker_client_order_id,
+            _taker_client_order_id: legacy._taker_client_order_id,
+            price: legacy.price,
+            taker_is_bid: legacy.taker_is_bid,
+            _taker_fee: legacy._taker_fee,
+            _taker_fee_is_deep: false,
+            _maker_fee: legacy._maker_fee,
+            _maker_fee_is_deep: false,
+            base_quantity: legacy.base_quantity,
+            quote_quantity: legacy.quote_quantity,
+            _maker_balance_manager_id: legacy._maker_balance_manager_id,
+            taker_balance_manager_id: legacy.taker_balance_manager_id,
+            timestamp: legacy.timestamp,
+        }
+    }
+}
+
+fn decode_order_filled(event: &SwapEvent) -> Option<OrderFilledBcs> {
+    if !event.event_type.ends_with("::order_info::OrderFilled") {
+        return None;
+    }
+    let data = hex::decode(&event.data_hex).ok()?;
+    bcs::from_bytes::<OrderFilledBcs>(&data).ok().or_else(|| {
+        bcs::from_bytes::<LegacyOrderFilledBcs>(&data)
+            .ok()
+            .map(Into::into)
+    })
+}
+
+/// Decoded `OrderFilled` events paired with the entry in `pools` whose object
+/// id matches the event's `pool_id`; fills from other pools are skipped.
+fn matched_fills<'a>(
+    pools: &'a [TapePool],
+    events: &'a [SwapEvent],
+) -> impl Iterator<Item = (&'a TapePool, OrderFilledBcs)> + 'a {
+    events
+        .iter()
+        .filter_map(decode_order_filled)
+        .filter_map(|fill| {
+            let event_pool_id = hex_id(&fill.pool_id);
+            let pool = pools
+                .iter()
+                .find(|pool| same_object_id(&pool.object_id, &event_pool_id))?;
+            Some((pool, fill))
+        })
+}
+
+/// Raw quote quantity of each fill in `events`, attributed like
+/// [`TradeTape::record_events`].
+pub fn quote_volumes<'a>(
+    pools: &'a [TapePool],
+    events: &'a [SwapEvent],
+) -> impl Iterator<Item = (PoolId, u64)> + 'a {
+    matched_fills(pools, events).map(|(pool, fill)| (pool.pool_id, fill.quote_quantity))
+}
+
+fn hex_id(bytes: &[u8; 32]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+/// Compare object ids regardless of `0x` prefix, case and leading zeros
+fn same_object_id(a: &str, b: &str) -> bool {
+    let normalize = |id: &str| {
+        id.trim_start_matches("0x")
+            .trim_start_matches('0')
+            .to_ascii_lowercase()
+    };
+    normalize(a) == normalize(b)
+}
+
+/// A pool a swap touched, with what is needed to attribute and scale its fills
+#[derive(Debug, Clone)]
+pub struct TapePool {
+    pub pool_id: PoolId,
+    pub object_id: String,
+    pub base_decimals: u8,
+    pub quote_decimals: u8,
+}
+
+/// Recent-trades ring buffers for every pool.
+#[derive(Debug)]
+pub struct TradeTape {
+    capacity: usize,
+    pools: HashMap<PoolId, VecDeque<Trade>>,
+}
+
+impl Default for TradeTape {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl TradeTape {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            pools: HashMap::new(),
+        }
+    }
+
+    /// Build from `TRADE_TAPE_CAPACITY`, falling back to the default.
+    pub fn from_env() -> Self {
+        let capacity = std::env::var("TRADE_TAPE_CAPACITY")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(DEFAULT_CAPACITY);
+        Self::new(capacity)
+    }
+
+    /// Append the `OrderFilled` events of one executed swap.
+    ///
+    /// Fills are attributed to the entry in `pools` whose object id matches
+    /// the event's `pool_id`; fills from other pools are ignored.
+    pub fn record_events(&mut self, pools: &[TapePool], events: &[SwapEvent]) {
+        for (pool, fill) in matched_fills(pools, events) {
+            let trade = Trade {
+                price: units::scale_price(fill.price, pool.base_decimals, pool.quote_decimals),
+                price_raw: fill.price.to_string(),
+                quantity: units::to_human(fill.base_quantity, pool.base_decimals),
+                quantity_raw: fill.base_quantity.to_string(),
+                quote_quantity_raw: fill.quote_quantity.to_string(),
+                side: if fill.taker_is_bid { "buy" } else { "sell" }.to_string(),
+                timestamp_ms: fill.timestamp,
+                taker: hex_id(&fill.taker_balance_manager_id),
+                maker_order_id: fill.maker_order_id.to_string(),
+            };
+            let tape = self.pools.entry(pool.pool_id).or_default();
+            if tape.len() == self.capacity {
+                tape.pop_front();
+            }
+            tape.push_back(trade);
+        }
+    }
+
+    /// Newest-first trades for a pool, at most `limit`.
+    pub fn recent(&self, pool_id: PoolId, limit: usize) -> Vec<Trade> {
+        self.pools
+            .get(&pool_id)
+            .map(|tape| tape.iter().rev().take(limit).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
@@ -3,5 +3,7 @@
 //! Re-exports modules for use in examples and tests.
 
 pub mod api;
+pub mod metrics;
 pub mod sandbox;
 pub mod types;
+pub mod units;
@@ -3,20 +3,25 @@
 //! HTTP API server wrapping sui-sandbox for forked mainnet PTB execution.
 //! Builds MoveVM orderbooks at startup from Snowflake checkpoint 240M data.
 
-use axum::{routing::get, Router};
+use axum::{
+    http::{HeaderName, HeaderValue},
+    middleware,
+    routing::get,
+    Router,
+};
 use std::collections::HashMap;
-use std::net::SocketAddr;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tower_http::cors::{Any, CorsLayer};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use deepbook_sandbox_backend::api;
-use deepbook_sandbox_backend::sandbox::orderbook_builder::{OrderbookBuilder, SandboxOrderbook};
-use deepbook_sandbox_backend::sandbox::router;
-use deepbook_sandbox_backend::sandbox::state_loader::{
-    DeepBookConfig, PoolId, PoolRegistry, StateLoader,
+use deepbook_sandbox_backend::api::{self, access_log, access_log::AccessLogFormat};
+use deepbook_sandbox_backend::sandbox::orderbook_builder::{
+    build_orderbook_from_file, SandboxOrderbook,
 };
+use deepbook_sandbox_backend::sandbox::router::{self, RouterHandle};
+use deepbook_sandbox_backend::sandbox::state_loader::{PoolId, PoolManifest, PoolRegistry};
 use deepbook_sandbox_backend::sandbox::swap_executor::SessionManager;
 
 #[tokio::main]
@@ -37,19 +42,54 @@ async fn main() {
     // Session manager is created after orderbooks are built (needs global orderbooks)
     // See below after MoveVM orderbook construction
 
-    // Define pool state files (relative to working directory)
-    // Using validated checkpoint 240M state files
-    let pool_files = [
-        (PoolId::SuiUsdc, "./data/sui_usdc_state_cp240M.jsonl"),
-        (PoolId::WalUsdc, "./data/wal_usdc_state_cp240M.jsonl"),
-        (PoolId::DeepUsdc, "./data/deep_usdc_state_cp240M.jsonl"),
-    ];
+    // Pool state files (relative to working directory): the validated
+    // checkpoint 240M files unless POOLS_MANIFEST lists others
+    let manifest = match PoolManifest::from_env() {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            tracing::error!("Invalid POOLS_MANIFEST: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let pool_files = manifest.state_files();
+    pool_registry
+        .write()
+        .await
+        .set_expected_pools(pool_files.iter().map(|(pool_id, _)| *pool_id).collect());
+
+    // With no state files at all there is nothing to build; serve in a
+    // degraded mode so /health/detailed can explain why instead of exiting.
+    if !pool_files
+        .iter()
+        .any(|(_, file_path)| std::path::Path::new(file_path).exists())
+    {
+        tracing::error!("==============================================================");
+        tracing::error!(
+            "NO POOLS LOADED: none of the {} pool state files exist",
+            pool_files.len()
+        );
+        for (pool_id, file_path) in &pool_files {
+            tracing::error!(
+                "  missing {} state file: {}",
+                pool_id.display_name(),
+                file_path
+            );
+        }
+        tracing::error!("Starting DEGRADED: no orderbooks, no MoveVM router.");
+        tracing::error!("Swap and quote endpoints return 503; see GET /health/detailed.");
+        tracing::error!("==============================================================");
+
+        let orderbooks = Arc::new(RwLock::new(HashMap::new()));
+        let session_manager = Arc::new(SessionManager::new(HashMap::new()));
+        serve(pool_registry, session_manager, orderbooks, None).await;
+        return;
+    }
 
     // Load all pool states (required for startup)
     {
         let mut registry = pool_registry.write().await;
-        for (pool_id, file_path) in &pool_files {
-            let path = std::path::Path::new(file_path);
+        for (pool_id, entry) in &manifest.pools {
+            let path = std::path::Path::new(&entry.state_file);
             if !path.exists() {
                 tracing::error!(
                     "{} state file not found: {}",
@@ -78,6 +118,15 @@ async fn main() {
                     std::process::exit(1);
                 }
             }
+            if let Some(Err(e)) = registry.get_mut(*pool_id).map(|l| entry.resolve_loaded(l)) {
+                tracing::error!(
+                    "{} state file {} cannot be used as listed in POOLS_MANIFEST: {}",
+                    pool_id.display_name(),
+                    path.display(),
+                    e
+                );
+                std::process::exit(1);
+            }
         }
 
         // Log summary and enforce complete pool coverage.
@@ -120,13 +169,9 @@ async fn main() {
         let pool_data: Vec<(PoolId, String)> = loaded_pools
             .iter()
             .filter_map(|pool_id| {
-                let file_path = match pool_id {
-                    PoolId::SuiUsdc => Some("./data/sui_usdc_state_cp240M.jsonl"),
-                    PoolId::WalUsdc => Some("./data/wal_usdc_state_cp240M.jsonl"),
-                    PoolId::DeepUsdc => Some("./data/deep_usdc_state_cp240M.jsonl"),
-                    PoolId::DebugUsdc => None,
-                };
-                file_path.map(|p| (*pool_id, p.to_string()))
+                registry
+                    .state_file(*pool_id)
+                    .map(|p| (*pool_id, p.display().to_string()))
             })
             .collect();
         drop(registry);
@@ -159,11 +204,12 @@ async fn main() {
         tracing::info!("MoveVM orderbooks built: {} pools ready", map.len());
         for (pool_id, ob) in &map {
             tracing::info!(
-                "  {} - {} bids, {} asks, mid=${:.6}",
+                "  {} - {} bids, {} asks, mid=${:.6}, skipped_filled={}",
                 pool_id.display_name(),
                 ob.bids.len(),
                 ob.asks.len(),
-                ob.mid_price().unwrap_or(0.0)
+                ob.mid_price().unwrap_or(0.0),
+                ob.skipped_filled_orders
             );
         }
         Arc::new(RwLock::new(map))
@@ -181,10 +227,7 @@ async fn main() {
 
     // Spawn router thread for cross-pool MoveVM quotes
     let router_handle = {
-        let pool_files_for_router: Vec<(PoolId, String)> = pool_files
-            .iter()
-            .map(|(id, path)| (*id, path.to_string()))
-            .collect();
+        let pool_files_for_router: Vec<(PoolId, String)> = pool_files.clone();
 
         tracing::info!("Spawning router thread for MoveVM quote execution...");
         let (handle, ready_rx) = router::spawn_router_thread(pool_files_for_router);
@@ -233,47 +276,113 @@ async fn main() {
         startup_report.reserve_coins.len()
     );
 
+    serve(
+        pool_registry,
+        session_manager,
+        orderbooks,
+        Some(router_handle),
+    )
+    .await;
+}
+
+/// Build the HTTP app and serve it until shutdown.
+async fn serve(
+    pool_registry: Arc<RwLock<PoolRegistry>>,
+    session_manager: Arc<SessionManager>,
+    orderbooks: api::SharedOrderbooks,
+    router_handle: Option<RouterHandle>,
+) {
     // Build router
     let app = Router::new()
         .route("/health", get(health_check))
-        .nest(
-            "/api",
-            api::router(
-                pool_registry,
-                session_manager,
-                orderbooks,
-                Some(router_handle),
-            ),
-        )
+        .merge(api::router(
+            pool_registry,
+            session_manager,
+            orderbooks,
+            router_handle,
+        ))
         .layer(
             CorsLayer::new()
-                .allow_origin(Any)
+                .allow_origin(cors_allow_origin())
                 .allow_methods(Any)
-                .allow_headers(Any),
-        );
+                .allow_headers(Any)
+                .expose_headers([
+                    axum::http::header::ETAG,
+                    HeaderName::from_static(access_log::REQUEST_ID_HEADER),
+                ]),
+        )
+        .layer(middleware::from_fn_with_state(
+            AccessLogFormat::from_env(),
+            access_log::access_log,
+        ));
 
     // Start server
-    let addr = SocketAddr::from(([0, 0, 0, 0], 3001));
+    let addr = listen_addr_from_env();
     tracing::info!("Starting server on {}", addr);
     tracing::info!("API endpoints:");
     tracing::info!("  GET  /health                  - Health check");
+    tracing::info!("  GET  /health/detailed         - Pools loaded, router status, ok/degraded");
+    tracing::info!("  GET  /metrics                 - Prometheus counters and router latency");
     tracing::info!("  GET  /api/startup-check       - Router startup self-check report");
+    tracing::info!("  POST /api/router/startup-check - Re-run the self-check against the live VM");
+    tracing::info!("  GET  /api/status/full         - Consolidated startup/health report");
+    tracing::info!("  GET  /api/router/reserves     - VM reserve coins + source checkpoints");
+    tracing::info!("  GET  /api/router/contract     - Router contract address, modules, build method");
+    tracing::info!("  POST /api/router/reset        - Rebuild router pool state from the state files");
+    tracing::info!("  POST /api/router/epoch        - Advance the simulation epoch");
+    tracing::info!("  POST /api/router/snapshot/save - Write the router VM state to a snapshot file");
+    tracing::info!("  POST /api/router/snapshot/load - Restore the router VM state from a snapshot file");
+    tracing::info!("  GET  /api/router/clock        - Synthetic VM clock timestamp, step, frozen flag");
+    tracing::info!("  POST /api/router/clock        - Freeze/resume or manually advance the VM clock");
+    tracing::info!("  POST /api/router/health-check - Probe two-hop quote routes on demand");
+    tracing::info!("  GET  /api/router/sender       - Get current VM sender address");
+    tracing::info!("  POST /api/router/sender       - Set VM sender address");
     tracing::info!("  POST /api/session             - Create new trading session");
     tracing::info!("  GET  /api/session/:id         - Get session info & balances");
     tracing::info!("  GET  /api/session/:id/history - Get swap history");
     tracing::info!("  POST /api/session/:id/reset   - Reset session to initial state");
+    tracing::info!("  GET  /api/session/:id/account - Settled/owed balances and rebates for a balance manager");
+    tracing::info!("  GET  /api/session/:id/orders  - Open orders of the session's balance manager");
+    tracing::info!("  GET  /api/session/:id/export  - Export session balances/history as a JSON bundle");
+    tracing::info!("  POST /api/session/import      - Recreate a session from an export bundle");
     tracing::info!("  GET  /api/balance/:session_id - Get token balances");
     tracing::info!("  POST /api/faucet              - Fund session via local MoveVM faucet PTB");
     tracing::info!("  POST /api/swap                - Execute swap (requires session_id)");
     tracing::info!("  POST /api/swap/quote          - Get swap quote (supports cross-pool routes)");
+    tracing::info!("  POST /api/swap/quote/batch    - Quote an array of requests in one router round trip");
+    tracing::info!("  POST /api/swap/quote/reverse  - Smallest input that yields an exact output (direct routes)");
+    tracing::info!("  POST /api/order/limit         - Place a limit order from the session's balance manager");
+    tracing::info!("  POST /api/order/cancel        - Cancel a resting order and refund the session");
+    tracing::info!("  POST /api/strategy/run        - Run conditional swap steps against a session");
     tracing::info!("  POST /api/debug/pool          - Create+seed DBG/USDC debug pool in local VM");
     tracing::info!("  GET  /api/debug/pools         - List created debug pools");
+    tracing::info!("  GET  /api/debug/pool/iter-orders - Decoded debug pool order page (?bids=&limit=)");
+    tracing::info!("  POST /api/debug/pool/prime-deep-price - Re-run debug pool deep_price bootstrap");
+    tracing::info!("  POST /api/debug/pool/:id/resync - Force pool dynamic-field reconciliation");
+    tracing::info!("  GET  /api/debug/object/:id    - Raw BCS of a VM object (?parent= for dynamic fields)");
     tracing::info!("  GET  /api/pools               - List available pools");
+    tracing::info!("  GET  /api/pools/:id/volumes   - Historic epoch volumes, fees, and trade params");
+    tracing::info!("  GET  /api/pools/:id/state     - Decoded PoolInner state: params, vault, accounts, volumes");
+    tracing::info!("  GET  /api/pools/:id/deep-price - DEEP per asset for fee estimates");
     tracing::info!("  GET  /api/orderbook           - Get orderbook snapshot");
     tracing::info!("  GET  /api/orderbook/depth     - Get Binance-style depth");
+    tracing::info!("  GET  /api/orderbook/depth/aggregate - Depth merged across direct + two-hop routes");
     tracing::info!("  GET  /api/orderbook/stats     - Get pool statistics");
-
-    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+    tracing::info!("  GET  /api/orderbook/ws        - WebSocket: orderbook snapshot after each swap");
+    tracing::info!("  GET  /api/orderbook/trades    - Recent fills decoded from swap OrderFilled events");
+    tracing::info!("  GET  /api/orderbook/orders    - Paginated resting orders decoded via iter_orders");
+    tracing::info!("  GET  /api/orderbook/two-sided-quote - Buy+sell quotes, spread, round-trip cost");
+    tracing::info!("  POST /api/orderbook/rebuild-all - Rebuild every global orderbook from state files");
+    tracing::info!("  POST /api/orderbook/reload    - Rebuild chosen pools' orderbooks from new state files");
+    tracing::info!("  GET  /api/validate/quote      - Compare a simulated quote with live mainnet");
+
+    let listener = match tokio::net::TcpListener::bind(addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            tracing::error!("Failed to bind {}: {}", addr, e);
+            std::process::exit(1);
+        }
+    };
     axum::serve(listener, app).await.unwrap();
 }
 
@@ -281,19 +390,86 @@ async fn health_check() -> &'static str {
     "ok"
 }
 
+const DEFAULT_BIND_ADDR: IpAddr = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+const DEFAULT_PORT: u16 = 3001;
+
+/// Listen address from `BIND_ADDR` (an IP) and `PORT`, each falling back to
+/// `0.0.0.0:3001` when unset or invalid.
+fn listen_addr_from_env() -> SocketAddr {
+    let ip = match std::env::var("BIND_ADDR") {
+        Ok(raw) => raw.trim().parse::<IpAddr>().unwrap_or_else(|_| {
+            tracing::warn!(
+                "Invalid BIND_ADDR={:?}, defaulting to {}",
+                raw,
+                DEFAULT_BIND_ADDR
+            );
+            DEFAULT_BIND_ADDR
+        }),
+        Err(_) => DEFAULT_BIND_ADDR,
+    };
+    let port = match std::env::var("PORT") {
+        Ok(raw) => raw.trim().parse::<u16>().unwrap_or_else(|_| {
+            tracing::warn!("Invalid PORT={:?}, defaulting to {}", raw, DEFAULT_PORT);
+            DEFAULT_PORT
+        }),
+        Err(_) => DEFAULT_PORT,
+    };
+    SocketAddr::new(ip, port)
+}
+
+/// CORS origins from `CORS_ALLOWED_ORIGINS` (comma-separated), or any origin
+/// when unset. An invalid origin stops startup rather than widening access.
+fn cors_allow_origin() -> AllowOrigin {
+    let raw = std::env::var("CORS_ALLOWED_ORIGINS").unwrap_or_default();
+    if raw.trim().is_empty() {
+        tracing::warn!("CORS: CORS_ALLOWED_ORIGINS is unset, allowing any origin");
+        return AllowOrigin::any();
+    }
+
+    let mut origins = Vec::new();
+    for origin in raw.split(',').map(str::trim).filter(|o| !o.is_empty()) {
+        match parse_cors_origin(origin) {
+            Ok(value) => origins.push(value),
+            Err(e) => {
+                tracing::error!("Invalid CORS_ALLOWED_ORIGINS entry {:?}: {}", origin, e);
+                std::process::exit(1);
+            }
+        }
+    }
+    tracing::info!(
+        "CORS: restricting origins to {}",
+        origins
+            .iter()
+            .filter_map(|o| o.to_str().ok())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    AllowOrigin::list(origins)
+}
+
+/// An origin is `scheme://host[:port]` with no path, as browsers send it.
+fn parse_cors_origin(origin: &str) -> Result<HeaderValue, String> {
+    let origin = origin.trim_end_matches('/');
+    let host = origin
+        .strip_prefix("https://")
+        .or_else(|| origin.strip_prefix("http://"))
+        .ok_or("expected an http:// or https:// origin")?;
+    let invalid = |c: char| matches!(c, '/' | '?' | '#' | '*') || c.is_whitespace();
+    if host.is_empty() || host.contains(invalid) {
+        return Err("expected scheme://host[:port] without a path or wildcard".to_string());
+    }
+    HeaderValue::from_str(origin).map_err(|e| e.to_string())
+}
+
 /// Build MoveVM orderbooks for all pools (runs in blocking thread)
 ///
-/// Creates an OrderbookBuilder per pool, loads packages via gRPC,
-/// loads pool state from JSONL files, and calls iter_orders to build
-/// the orderbook. Returns the SandboxOrderbook results (Send+Sync).
+/// Each pool gets its own OrderbookBuilder and runtime via
+/// `build_orderbook_from_file`. Returns the SandboxOrderbook results (Send+Sync).
 fn build_movevm_orderbooks(
     pool_data: &[(PoolId, String)],
 ) -> anyhow::Result<HashMap<PoolId, SandboxOrderbook>> {
     let mut results = HashMap::new();
 
-    // We need a tokio runtime handle for the async gRPC calls inside
-    // load_packages_from_grpc. Since we're in spawn_blocking, we use
-    // a new runtime for the async portions.
     for (pool_id, file_path) in pool_data {
         let path = std::path::Path::new(file_path);
         if !path.exists() {
@@ -310,27 +486,8 @@ fn build_movevm_orderbooks(
             pool_id.display_name()
         );
 
-        // Each pool gets its own builder + runtime (OrderbookBuilder is not Send)
-        let rt = tokio::runtime::Runtime::new()?;
-
-        let mut builder = OrderbookBuilder::new()?;
-        rt.block_on(builder.load_packages_from_grpc())?;
-
-        let config = DeepBookConfig::for_pool(*pool_id);
-        let pool_wrapper = config.pool_wrapper.clone();
-
-        let mut loader = StateLoader::with_config(config);
-        loader
-            .load_from_file(path)
-            .map_err(|e| anyhow::anyhow!("Failed to load {}: {}", file_path, e))?;
-
-        let stats = loader.stats();
-
-        // Load pool state into the simulation environment
-        builder.load_pool_state(&loader, *pool_id)?;
-
         // Build the orderbook via iter_orders PTB execution
-        match builder.build_orderbook(*pool_id, &pool_wrapper, stats.max_checkpoint) {
+        match build_orderbook_from_file(*pool_id, path) {
             Ok(orderbook) => {
                 tracing::info!(
                     "  {} built: {} bids, {} asks, mid=${:.6}",
@@ -349,10 +506,6 @@ fn build_movevm_orderbooks(
                 );
             }
         }
-
-        // Drop builder and runtime before next pool
-        drop(builder);
-        drop(rt);
     }
 
     Ok(results)
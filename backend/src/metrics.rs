@@ -0,0 +1,327 @@
+//! Prometheus counters for quotes, swaps and router latency
+//!
+//! [`Metrics`] is shared between the API handlers (via `AppState`) and the
+//! router thread (via `RouterHandle`). Every label set is fixed up front, so
+//! recording is a handful of relaxed atomic adds; only swap failures, keyed by
+//! error code, sit behind a mutex. [`Metrics::render`] produces the Prometheus
+//! text exposition format served at `GET /metrics`.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::sandbox::state_loader::PoolId;
+use crate::types::ApiError;
+
+/// Route types reported by quotes and swaps (`Route::route_type`)
+const ROUTE_TYPES: [&str; 3] = ["direct", "two_hop", "three_hop"];
+
+/// Pools tracked for volume, including the debug pool
+const POOLS: [PoolId; 4] = [
+    PoolId::SuiUsdc,
+    PoolId::WalUsdc,
+    PoolId::DeepUsdc,
+    PoolId::DebugUsdc,
+];
+
+/// Upper bounds (seconds) of the router latency histogram buckets
+const LATENCY_BUCKETS: [f64; 12] = [
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Router thread operations with a latency histogram
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouterOp {
+    QuoteSingleHop,
+    QuoteTwoHop,
+    QuoteThreeHop,
+    QuoteReverse,
+    QuoteTwoSided,
+    QuoteBatch,
+    ExecuteSingleHop,
+    ExecuteExactOut,
+    ExecuteTwoHop,
+}
+
+impl RouterOp {
+    const ALL: [RouterOp; 9] = [
+        RouterOp::QuoteSingleHop,
+        RouterOp::QuoteTwoHop,
+        RouterOp::QuoteThreeHop,
+        RouterOp::QuoteReverse,
+        RouterOp::QuoteTwoSided,
+        RouterOp::QuoteBatch,
+        RouterOp::ExecuteSingleHop,
+        RouterOp::ExecuteExactOut,
+        RouterOp::ExecuteTwoHop,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RouterOp::QuoteSingleHop => "quote_single_hop",
+            RouterOp::QuoteTwoHop => "quote_two_hop",
+            RouterOp::QuoteThreeHop => "quote_three_hop",
+            RouterOp::QuoteReverse => "quote_reverse",
+            RouterOp::QuoteTwoSided => "quote_two_sided",
+            RouterOp::QuoteBatch => "quote_batch",
+            RouterOp::ExecuteSingleHop => "execute_single_hop",
+            RouterOp::ExecuteExactOut => "execute_exact_out",
+            RouterOp::ExecuteTwoHop => "execute_two_hop",
+        }
+    }
+}
+
+#[derive(Default)]
+struct Histogram {
+    /// Non-cumulative count per bucket; rendered cumulatively
+    buckets: [AtomicU64; LATENCY_BUCKETS.len()],
+    sum_micros: AtomicU64,
+    count: AtomicU64,
+}
+
+impl Histogram {
+    fn observe(&self, elapsed: Duration) {
+        let secs = elapsed.as_secs_f64();
+        if let Some(idx) = LATENCY_BUCKETS.iter().position(|&bound| secs <= bound) {
+            self.buckets[idx].fetch_add(1, Ordering::Relaxed);
+        }
+        self.sum_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Process-wide metrics registry
+#[derive(Default)]
+pub struct Metrics {
+    quotes_total: [AtomicU64; ROUTE_TYPES.len()],
+    swaps_total: [AtomicU64; ROUTE_TYPES.len()],
+    swap_failures_total: Mutex<BTreeMap<&'static str, u64>>,
+    /// Raw USDC units filled per pool
+    pool_volume_usdc: [AtomicU64; POOLS.len()],
+    router_latency: [Histogram; RouterOp::ALL.len()],
+    /// Single-hop quote cache lookups, split into `[misses, hits]`
+    quote_cache_lookups: [AtomicU64; 2],
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a served quote; unknown route types are ignored.
+    pub fn record_quote(&self, route_type: &str) {
+        if let Some(idx) = ROUTE_TYPES.iter().position(|&t| t == route_type) {
+            self.quotes_total[idx].fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Count a successful swap; unknown route types are ignored.
+    pub fn record_swap(&self, route_type: &str) {
+        if let Some(idx) = ROUTE_TYPES.iter().position(|&t| t == route_type) {
+            self.swaps_total[idx].fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Count a swap rejected with `err`, keyed by its `deepbook_code` or
+    /// error code.
+    pub fn record_swap_error(&self, err: &ApiError) {
+        self.record_swap_failure(
+            err.deepbook_code()
+                .unwrap_or_else(|| err.status_and_code().1),
+        );
+    }
+
+    /// Count a failed swap under `reason`.
+    pub fn record_swap_failure(&self, reason: &'static str) {
+        let mut failures = self
+            .swap_failures_total
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        *failures.entry(reason).or_default() += 1;
+    }
+
+    /// Add filled quote (USDC) volume to a pool.
+    pub fn add_pool_volume(&self, pool_id: PoolId, quote_amount: u64) {
+        if let Some(idx) = POOLS.iter().position(|&p| p == pool_id) {
+            self.pool_volume_usdc[idx].fetch_add(quote_amount, Ordering::Relaxed);
+        }
+    }
+
+    /// Count a single-hop quote cache lookup.
+    pub fn record_quote_cache_lookup(&self, hit: bool) {
+        self.quote_cache_lookups[usize::from(hit)].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record how long the router thread spent on one request.
+    pub fn observe_router(&self, op: RouterOp, elapsed: Duration) {
+        if let Some(idx) = RouterOp::ALL.iter().position(|&o| o == op) {
+            self.router_latency[idx].observe(elapsed);
+        }
+    }
+
+    /// Prometheus text exposition format (version 0.0.4)
+    pub fn render(&self) -> String {
+        let mut out = String::with_capacity(4096);
+        // Writing to a String cannot fail.
+        let _ = self.write_to(&mut out);
+        out
+    }
+
+    fn write_to(&self, out: &mut String) -> std::fmt::Result {
+        writeln!(
+            out,
+            "# HELP sandbox_quotes_total Quotes served, by route type"
+        )?;
+        writeln!(out, "# TYPE sandbox_quotes_total counter")?;
+        for (route_type, count) in ROUTE_TYPES.iter().zip(&self.quotes_total) {
+            writeln!(
+                out,
+                "sandbox_quotes_total{{route_type=\"{}\"}} {}",
+                route_type,
+                count.load(Ordering::Relaxed)
+            )?;
+        }
+
+        writeln!(
+            out,
+            "# HELP sandbox_swaps_total Successful swaps, by route type"
+        )?;
+        writeln!(out, "# TYPE sandbox_swaps_total counter")?;
+        for (route_type, count) in ROUTE_TYPES.iter().zip(&self.swaps_total) {
+            writeln!(
+                out,
+                "sandbox_swaps_total{{route_type=\"{}\"}} {}",
+                route_type,
+                count.load(Ordering::Relaxed)
+            )?;
+        }
+
+        writeln!(
+            out,
+            "# HELP sandbox_swap_failures_total Failed swaps, by deepbook_code or error code"
+        )?;
+        writeln!(out, "# TYPE sandbox_swap_failures_total counter")?;
+        let failures = self
+            .swap_failures_total
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        for (reason, count) in failures.iter() {
+            writeln!(
+                out,
+                "sandbox_swap_failures_total{{reason=\"{}\"}} {}",
+                reason, count
+            )?;
+        }
+        drop(failures);
+
+        writeln!(
+            out,
+            "# HELP sandbox_pool_volume_usdc_total Filled quote volume per pool, in raw USDC units"
+        )?;
+        writeln!(out, "# TYPE sandbox_pool_volume_usdc_total counter")?;
+        for (pool_id, volume) in POOLS.iter().zip(&self.pool_volume_usdc) {
+            writeln!(
+                out,
+                "sandbox_pool_volume_usdc_total{{pool=\"{}\"}} {}",
+                pool_id.as_str(),
+                volume.load(Ordering::Relaxed)
+            )?;
+        }
+
+        writeln!(
+            out,
+            "# HELP sandbox_quote_cache_lookups_total Single-hop quote cache lookups, by result"
+        )?;
+        writeln!(out, "# TYPE sandbox_quote_cache_lookups_total counter")?;
+        for (result, count) in ["miss", "hit"].iter().zip(&self.quote_cache_lookups) {
+            writeln!(
+                out,
+                "sandbox_quote_cache_lookups_total{{result=\"{}\"}} {}",
+                result,
+                count.load(Ordering::Relaxed)
+            )?;
+        }
+
+        writeln!(
+            out,
+            "# HELP sandbox_router_request_duration_seconds Router thread time per quote or swap"
+        )?;
+        writeln!(
+            out,
+            "# TYPE sandbox_router_request_duration_seconds histogram"
+        )?;
+        for (op, histogram) in RouterOp::ALL.iter().zip(&self.router_latency) {
+            let op = op.as_str();
+            let mut cumulative = 0;
+            for (bound, bucket) in LATENCY_BUCKETS.iter().zip(&histogram.buckets) {
+                cumulative += bucket.load(Ordering::Relaxed);
+                writeln!(
+                    out,
+                    "sandbox_router_request_duration_seconds_bucket{{op=\"{}\",le=\"{}\"}} {}",
+                    op, bound, cumulative
+                )?;
+            }
+            let count = histogram.count.load(Ordering::Relaxed);
+            writeln!(
+                out,
+                "sandbox_router_request_duration_seconds_bucket{{op=\"{}\",le=\"+Inf\"}} {}",
+                op, count
+            )?;
+            writeln!(
+                out,
+                "sandbox_router_request_duration_seconds_sum{{op=\"{}\"}} {}",
+                op,
+                histogram.sum_micros.load(Ordering::Relaxed) as f64 / 1e6
+            )?;
+            writeln!(
+                out,
+                "sandbox_router_request_duration_seconds_count{{op=\"{}\"}} {}",
+                op, count
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_counts_and_cumulative_buckets() {
+        let metrics = Metrics::new();
+        metrics.record_quote("two_hop");
+        metrics.record_swap("direct");
+        metrics.record_swap("unknown");
+        metrics.record_swap_error(&ApiError::DeepBook {
+            deepbook_code: "MIN_OUT_NOT_MET",
+            message: String::new(),
+        });
+        metrics.record_swap_error(&ApiError::Internal(String::new()));
+        metrics.add_pool_volume(PoolId::SuiUsdc, 1_500_000);
+        metrics.record_quote_cache_lookup(true);
+        metrics.observe_router(RouterOp::ExecuteSingleHop, Duration::from_millis(3));
+        metrics.observe_router(RouterOp::ExecuteSingleHop, Duration::from_secs(30));
+
+        let text = metrics.render();
+        assert!(text.contains("sandbox_quotes_total{route_type=\"two_hop\"} 1\n"));
+        assert!(text.contains("sandbox_swaps_total{route_type=\"direct\"} 1\n"));
+        assert!(text.contains("sandbox_quote_cache_lookups_total{result=\"hit\"} 1\n"));
+        assert!(text.contains("sandbox_quote_cache_lookups_total{result=\"miss\"} 0\n"));
+        assert!(text.contains("sandbox_swap_failures_total{reason=\"MIN_OUT_NOT_MET\"} 1\n"));
+        assert!(text.contains("sandbox_swap_failures_total{reason=\"INTERNAL_ERROR\"} 1\n"));
+        assert!(text.contains("sandbox_pool_volume_usdc_total{pool=\"sui_usdc\"} 1500000\n"));
+        assert!(text.contains(
+            "sandbox_router_request_duration_seconds_bucket{op=\"execute_single_hop\",le=\"0.001\"} 0\n"
+        ));
+        assert!(text.contains(
+            "sandbox_router_request_duration_seconds_bucket{op=\"execute_single_hop\",le=\"10\"} 1\n"
+        ));
+        assert!(text.contains(
+            "sandbox_router_request_duration_seconds_bucket{op=\"execute_single_hop\",le=\"+Inf\"} 2\n"
+        ));
+    }
+}
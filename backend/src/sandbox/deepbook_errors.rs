@@ -0,0 +1,253 @@
+//! Structured DeepBook aborts
+//!
+//! PTB failures come back from the VM as a `raw_error` string. [`parse_move_abort`]
+//! pulls the aborting `module::function`, the abort code and the VM sub-status
+//! out of it, and [`DeepBookAbort::classify`] maps the DeepBook codes clients
+//! can act on to a stable `deepbook_code` and a human message.
+
+/// DeepBook modules whose frame is preferred when an error names several functions
+const DEEPBOOK_MODULES: &[&str] = &[
+    "pool",
+    "book",
+    "order_info",
+    "order",
+    "balance_manager",
+    "vault",
+    "state",
+    "account",
+    "big_vector",
+];
+
+/// The parts of a Move abort that `raw_error` exposes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MoveAbortInfo {
+    pub module: Option<String>,
+    pub function: Option<String>,
+    /// Move abort code: the `MoveAbort(.., code)` value, or the VM sub-status
+    /// when the major status is `ABORTED`
+    pub abort_code: Option<u64>,
+    pub sub_status: Option<u64>,
+}
+
+/// Parse a PTB `raw_error`; `None` when it is not a Move abort.
+pub fn parse_move_abort(raw_error: &str) -> Option<MoveAbortInfo> {
+    let move_abort_code = raw_error
+        .find("MoveAbort(")
+        .and_then(|start| trailing_abort_code(&raw_error[start + "MoveAbort(".len()..]));
+    let sub_status = number_after(raw_error, "sub_status: Some(");
+    let aborted = raw_error.contains("ABORTED");
+    if move_abort_code.is_none() && !aborted {
+        return None;
+    }
+
+    let (module, function) = match module_function_path(raw_error) {
+        Some((module, function)) => (Some(module), Some(function)),
+        None => (
+            quoted_after(raw_error, "Identifier(\""),
+            quoted_after(raw_error, "function_name: Some(\""),
+        ),
+    };
+    Some(MoveAbortInfo {
+        module,
+        function,
+        abort_code: move_abort_code.or(sub_status.filter(|_| aborted)),
+        sub_status,
+    })
+}
+
+/// DeepBook aborts with a stable client-facing code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeepBookAbort {
+    /// `pool::EMinimumQuantityOutNotMet`
+    MinimumOutputNotMet,
+    /// `pool::EInvalidQuantityIn`
+    QuantityTooSmall,
+    /// `book::EEmptyOrderbook`, `order_info::EFOKOrderCannotBeFullyFilled`
+    InsufficientLiquidity,
+    /// `order_info::EOrderBelowMinimumSize`, `book::EOrderBelowMinimumSize`
+    BelowMinimumSize,
+    /// `order_info::ESelfMatchingCancelTaker`
+    SelfMatch,
+    /// `order::EOrderExpired`, `order_info::EInvalidExpireTimestamp`
+    OrderExpired,
+}
+
+impl DeepBookAbort {
+    const ALL: [Self; 6] = [
+        Self::MinimumOutputNotMet,
+        Self::QuantityTooSmall,
+        Self::InsufficientLiquidity,
+        Self::BelowMinimumSize,
+        Self::SelfMatch,
+        Self::OrderExpired,
+    ];
+
+    /// Map a parsed abort to a known DeepBook error.
+    pub fn from_abort(abort: &MoveAbortInfo) -> Option<Self> {
+        match (abort.module.as_deref()?, abort.abort_code?) {
+            ("pool", 12) => Some(Self::MinimumOutputNotMet),
+            ("pool", 6) => Some(Self::QuantityTooSmall),
+            ("book", 2) | ("order_info", 6) => Some(Self::InsufficientLiquidity),
+            ("book", 5) | ("order_info", 1) => Some(Self::BelowMinimumSize),
+            ("order_info", 8) => Some(Self::SelfMatch),
+            ("order", 1) | ("order_info", 3) => Some(Self::OrderExpired),
+            _ => None,
+        }
+    }
+
+    /// Classify a PTB `raw_error` (or an error message wrapping one).
+    pub fn classify(raw_error: &str) -> Option<Self> {
+        parse_move_abort(raw_error).and_then(|abort| Self::from_abort(&abort))
+    }
+
+    /// Inverse of [`DeepBookAbort::code`]
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|abort| abort.code() == code)
+    }
+
+    /// Whether the request caused the abort (its size, bounds or expiry).
+    ///
+    /// An empty or drained book says something about the pool itself, so
+    /// repeated ones count toward the pool's circuit breaker.
+    pub fn is_client_error(self) -> bool {
+        !matches!(self, Self::InsufficientLiquidity)
+    }
+
+    /// Stable code returned to clients as `deepbook_code`
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::MinimumOutputNotMet => "MIN_OUT_NOT_MET",
+            Self::QuantityTooSmall => "QUANTITY_TOO_SMALL",
+            Self::InsufficientLiquidity => "INSUFFICIENT_LIQUIDITY",
+            Self::BelowMinimumSize => "BELOW_MIN_SIZE",
+            Self::SelfMatch => "SELF_MATCH",
+            Self::OrderExpired => "ORDER_EXPIRED",
+        }
+    }
+
+    pub fn message(self) -> &'static str {
+        match self {
+            Self::MinimumOutputNotMet => "Output fell below min_out",
+            Self::QuantityTooSmall => {
+                "Amount is too small for DeepBook execution; increase the input amount and retry"
+            }
+            Self::InsufficientLiquidity => "Not enough resting liquidity to fill the order",
+            Self::BelowMinimumSize => "Order quantity is below the pool's minimum size",
+            Self::SelfMatch => "Order would match against the same balance manager",
+            Self::OrderExpired => "Order is expired or its expiry is already past",
+        }
+    }
+}
+
+/// Last comma-separated value of a `MoveAbort(location, code)` argument list
+fn trailing_abort_code(args: &str) -> Option<u64> {
+    let mut depth = 0usize;
+    for (idx, c) in args.char_indices() {
+        match c {
+            '(' | '{' | '[' => depth += 1,
+            ')' | '}' | ']' if depth > 0 => depth -= 1,
+            ')' => {
+                let (_, code) = args[..idx].rsplit_once(',')?;
+                return code.trim().parse().ok();
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn number_after(text: &str, marker: &str) -> Option<u64> {
+    let start = text.find(marker)? + marker.len();
+    let digits: String = text[start..]
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect();
+    digits.parse().ok()
+}
+
+fn quoted_after(text: &str, marker: &str) -> Option<String> {
+    let start = text.find(marker)? + marker.len();
+    let end = text[start..].find('"')?;
+    Some(text[start..start + end].to_string())
+}
+
+/// `module::function` from a `0x..::module::function` path, preferring
+/// DeepBook modules. Type names (`0x2::sui::SUI`) are skipped.
+fn module_function_path(text: &str) -> Option<(String, String)> {
+    let is_ident = |s: &str| {
+        s.starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+            && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    };
+    let pairs: Vec<(&str, &str)> = text
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == ':'))
+        .filter(|token| token.contains("::"))
+        .flat_map(|token| {
+            let segments: Vec<&str> = token.split("::").collect();
+            segments
+                .windows(2)
+                .map(|pair| (pair[0], pair[1]))
+                .collect::<Vec<_>>()
+        })
+        .filter(|(module, function)| is_ident(module) && is_ident(function))
+        .collect();
+    pairs
+        .iter()
+        .find(|(module, _)| DEEPBOOK_MODULES.contains(module))
+        .or_else(|| pairs.first())
+        .map(|(module, function)| (module.to_string(), function.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_vm_error_with_sub_status() {
+        let raw = "swap failed: VMError { major_status: ABORTED, sub_status: Some(12) } \
+                   at 0xabc::pool::swap_exact_quantity<0x2::sui::SUI, 0xdef::usdc::USDC>";
+        let abort = parse_move_abort(raw).expect("abort");
+        assert_eq!(abort.module.as_deref(), Some("pool"));
+        assert_eq!(abort.function.as_deref(), Some("swap_exact_quantity"));
+        assert_eq!(abort.abort_code, Some(12));
+        assert_eq!(abort.sub_status, Some(12));
+        assert_eq!(
+            DeepBookAbort::classify(raw),
+            Some(DeepBookAbort::MinimumOutputNotMet)
+        );
+    }
+
+    #[test]
+    fn test_parse_move_abort_location() {
+        let raw = "MoveAbort(MoveLocation { module: ModuleId { address: 0xabc, \
+                   name: Identifier(\"order_info\") }, function: 4, instruction: 9, \
+                   function_name: Some(\"assert_execution\") }, 8) in command 2";
+        let abort = parse_move_abort(raw).expect("abort");
+        assert_eq!(abort.module.as_deref(), Some("order_info"));
+        assert_eq!(abort.function.as_deref(), Some("assert_execution"));
+        assert_eq!(abort.abort_code, Some(8));
+        assert_eq!(abort.sub_status, None);
+        assert_eq!(DeepBookAbort::classify(raw), Some(DeepBookAbort::SelfMatch));
+    }
+
+    #[test]
+    fn test_codes_round_trip_and_liquidity_is_a_pool_fault() {
+        for abort in DeepBookAbort::ALL {
+            assert_eq!(DeepBookAbort::from_code(abort.code()), Some(abort));
+        }
+        assert_eq!(DeepBookAbort::from_code("NOT_A_CODE"), None);
+        assert!(!DeepBookAbort::InsufficientLiquidity.is_client_error());
+        assert!(DeepBookAbort::MinimumOutputNotMet.is_client_error());
+    }
+
+    #[test]
+    fn test_non_aborts_and_unknown_codes_are_unclassified() {
+        assert_eq!(parse_move_abort("Unknown error"), None);
+        assert_eq!(
+            parse_move_abort("CommandArgumentError { arg_idx: 1, kind: TypeMismatch }"),
+            None
+        );
+        let raw = "ABORTED sub_status: Some(99) in 0xabc::pool::swap_exact_quantity";
+        assert!(parse_move_abort(raw).is_some());
+        assert_eq!(DeepBookAbort::classify(raw), None);
+    }
+}
@@ -0,0 +1,296 @@
+//! Live mainnet DeepBook quotes via JSON-RPC dev-inspect
+//!
+//! Runs the same `pool::get_quote_quantity_out` / `pool::get_base_quantity_out`
+//! view the router calls locally, but against current mainnet state, so the
+//! forked simulation can be checked against reality. The PTB is BCS-encoded
+//! by hand (only the shapes a pool view needs) and sent to
+//! `sui_devInspectTransactionBlock` on `SUI_RPC_URL`.
+
+use anyhow::{anyhow, Result};
+use base64::Engine;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::language_storage::TypeTag;
+use serde::ser::{SerializeStructVariant, Serializer};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::str::FromStr;
+
+use crate::sandbox::state_loader::DeepBookConfig;
+
+const DEFAULT_RPC_URL: &str = "https://fullnode.mainnet.sui.io:443";
+const CLOCK_OBJECT_ID: &str = "0x6";
+const CLOCK_INITIAL_SHARED_VERSION: u64 = 1;
+/// Dev-inspect sender; any address can call read-only pool views
+const INSPECT_SENDER: &str = "0x0000000000000000000000000000000000000000000000000000000000000000";
+
+/// BCS mirror of Sui's `TransactionKind` (only the PTB variant, index 0)
+#[derive(Serialize)]
+enum TransactionKind {
+    ProgrammableTransaction(ProgrammableTransaction),
+}
+
+#[derive(Serialize)]
+struct ProgrammableTransaction {
+    inputs: Vec<CallArg>,
+    commands: Vec<PtbCommand>,
+}
+
+/// BCS mirror of `CallArg`: `Pure` is index 0, `Object` index 1
+#[derive(Serialize)]
+enum CallArg {
+    Pure(Vec<u8>),
+    Object(SharedObjectArg),
+}
+
+/// `ObjectArg::SharedObject` (variant index 1)
+struct SharedObjectArg {
+    id: AccountAddress,
+    initial_shared_version: u64,
+    mutable: bool,
+}
+
+impl Serialize for SharedObjectArg {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut variant = serializer.serialize_struct_variant("ObjectArg", 1, "SharedObject", 3)?;
+        variant.serialize_field("id", &self.id)?;
+        variant.serialize_field("initial_shared_version", &self.initial_shared_version)?;
+        variant.serialize_field("mutable", &self.mutable)?;
+        variant.end()
+    }
+}
+
+/// BCS mirror of `Command` (only `MoveCall`, index 0)
+#[derive(Serialize)]
+enum PtbCommand {
+    MoveCall(ProgrammableMoveCall),
+}
+
+#[derive(Serialize)]
+struct ProgrammableMoveCall {
+    package: AccountAddress,
+    module: String,
+    function: String,
+    type_arguments: Vec<TypeTag>,
+    arguments: Vec<InputArgument>,
+}
+
+/// `Argument::Input(u16)` (variant index 1)
+struct InputArgument(u16);
+
+impl Serialize for InputArgument {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_variant("Argument", 1, "Input", &self.0)
+    }
+}
+
+/// A pool view quote evaluated against live mainnet state
+#[derive(Debug, Clone)]
+pub struct MainnetQuote {
+    pub output_amount: u64,
+    /// DEEP fee the pool would charge for this trade
+    pub deep_fee: u64,
+    /// Latest mainnet checkpoint seen right after the quote (best effort)
+    pub checkpoint: Option<u64>,
+}
+
+/// JSON-RPC client for quoting DeepBook pools on mainnet.
+#[derive(Debug, Clone)]
+pub struct MainnetQuoter {
+    http: reqwest::Client,
+    rpc_url: String,
+}
+
+impl Default for MainnetQuoter {
+    fn default() -> Self {
+        Self::new(DEFAULT_RPC_URL.to_string())
+    }
+}
+
+impl MainnetQuoter {
+    pub fn new(rpc_url: String) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            rpc_url,
+        }
+    }
+
+    /// Build from `SUI_RPC_URL`, falling back to the public mainnet fullnode.
+    pub fn from_env() -> Self {
+        match std::env::var("SUI_RPC_URL") {
+            Ok(url) if !url.trim().is_empty() => Self::new(url.trim().to_string()),
+            _ => Self::default(),
+        }
+    }
+
+    pub fn rpc_url(&self) -> &str {
+        &self.rpc_url
+    }
+
+    /// Quote `input_amount` on the mainnet pool `config` describes (its
+    /// wrapper id and coin types, as loaded) via dev-inspect.
+    ///
+    /// Mirrors the router's local single-hop quote: `is_sell_base = true`
+    /// calls `pool::get_quote_quantity_out`, otherwise
+    /// `pool::get_base_quantity_out`.
+    pub async fn quote_single_hop(
+        &self,
+        config: &DeepBookConfig,
+        input_amount: u64,
+        is_sell_base: bool,
+    ) -> Result<MainnetQuote> {
+        let pool_id = config.pool_id;
+        if pool_id.is_debug() {
+            return Err(anyhow!(
+                "{} only exists in the local VM",
+                pool_id.display_name()
+            ));
+        }
+        let (base_type, quote_type) = (&config.base_type, &config.quote_type);
+        let function_name = if is_sell_base {
+            "get_quote_quantity_out"
+        } else {
+            "get_base_quantity_out"
+        };
+
+        let pool_version = self.initial_shared_version(&config.pool_wrapper).await?;
+        let tx = TransactionKind::ProgrammableTransaction(ProgrammableTransaction {
+            inputs: vec![
+                CallArg::Object(SharedObjectArg {
+                    id: AccountAddress::from_hex_literal(&config.pool_wrapper)?,
+                    initial_shared_version: pool_version,
+                    mutable: false,
+                }),
+                CallArg::Pure(bcs::to_bytes(&input_amount)?),
+                CallArg::Object(SharedObjectArg {
+                    id: AccountAddress::from_hex_literal(CLOCK_OBJECT_ID)?,
+                    initial_shared_version: CLOCK_INITIAL_SHARED_VERSION,
+                    mutable: false,
+                }),
+            ],
+            commands: vec![PtbCommand::MoveCall(ProgrammableMoveCall {
+                package: AccountAddress::from_hex_literal(&config.package)?,
+                module: "pool".to_string(),
+                function: function_name.to_string(),
+                type_arguments: vec![
+                    TypeTag::from_str(base_type)?,
+                    TypeTag::from_str(quote_type)?,
+                ],
+                arguments: vec![InputArgument(0), InputArgument(1), InputArgument(2)],
+            })],
+        });
+        let tx_bytes = base64::engine::general_purpose::STANDARD.encode(bcs::to_bytes(&tx)?);
+
+        let result = self
+            .call(
+                "sui_devInspectTransactionBlock",
+                json!([INSPECT_SENDER, tx_bytes, null, null]),
+            )
+            .await?;
+        if let Some(error) = result.get("error").and_then(Value::as_str) {
+            return Err(anyhow!(
+                "mainnet dev-inspect of pool::{} failed for {}: {}",
+                function_name,
+                pool_id.display_name(),
+                error
+            ));
+        }
+        let return_values = result
+            .pointer("/results/0/returnValues")
+            .and_then(Value::as_array)
+            .ok_or_else(|| anyhow!("No return values from mainnet pool::{}", function_name))?;
+
+        let rv0 = parse_u64_return_value(return_values, 0)?;
+        let rv1 = parse_u64_return_value(return_values, 1)?;
+        let rv2 = parse_u64_return_value(return_values, 2)?;
+        let output_amount = if is_sell_base {
+            // get_quote_quantity_out returns (base_left, quote_out, deep_fee)
+            rv1
+        } else {
+            // get_base_quantity_out returns (base_out, quote_left, deep_fee)
+            rv0
+        };
+
+        let checkpoint = match self.latest_checkpoint().await {
+            Ok(checkpoint) => Some(checkpoint),
+            Err(e) => {
+                tracing::warn!("Mainnet quote: failed to read latest checkpoint: {}", e);
+                None
+            }
+        };
+
+        Ok(MainnetQuote {
+            output_amount,
+            deep_fee: rv2,
+            checkpoint,
+        })
+    }
+
+    /// `initial_shared_version` of a shared mainnet object
+    async fn initial_shared_version(&self, object_id: &str) -> Result<u64> {
+        let result = self
+            .call("sui_getObject", json!([object_id, { "showOwner": true }]))
+            .await?;
+        let version = result
+            .pointer("/data/owner/Shared/initial_shared_version")
+            .ok_or_else(|| anyhow!("Object {} is not shared on mainnet", object_id))?;
+        json_u64(version).ok_or_else(|| {
+            anyhow!(
+                "Invalid initial_shared_version for {}: {}",
+                object_id,
+                version
+            )
+        })
+    }
+
+    async fn latest_checkpoint(&self) -> Result<u64> {
+        let result = self
+            .call("sui_getLatestCheckpointSequenceNumber", json!([]))
+            .await?;
+        json_u64(&result).ok_or_else(|| anyhow!("Invalid checkpoint sequence number: {}", result))
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let response: Value = self
+            .http
+            .post(&self.rpc_url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": params,
+            }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if let Some(error) = response.get("error") {
+            return Err(anyhow!("{} returned an error: {}", method, error));
+        }
+        response
+            .get("result")
+            .cloned()
+            .ok_or_else(|| anyhow!("{} returned no result", method))
+    }
+}
+
+/// JSON-RPC encodes u64s as either numbers or decimal strings
+fn json_u64(value: &Value) -> Option<u64> {
+    value
+        .as_u64()
+        .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+}
+
+/// Decode the `idx`th dev-inspect return value (`[bytes, type]`) as a BCS u64
+fn parse_u64_return_value(return_values: &[Value], idx: usize) -> Result<u64> {
+    let bytes: Vec<u8> = return_values
+        .get(idx)
+        .and_then(|rv| rv.get(0))
+        .and_then(Value::as_array)
+        .ok_or_else(|| anyhow!("Missing return value {}", idx))?
+        .iter()
+        .map(|b| b.as_u64().and_then(|b| u8::try_from(b).ok()))
+        .collect::<Option<_>>()
+        .ok_or_else(|| anyhow!("Return value {} is not a byte array", idx))?;
+    Ok(bcs::from_bytes(&bytes)?)
+}
@@ -6,9 +6,16 @@
 //! - Converting JSON to BCS using bytecode layouts
 //! - Managing SimulationEnvironment instances per session
 //! - Calling DeepBook view functions via Move VM
+//! - Mapping DeepBook abort codes to structured errors
+//! - Quoting the same views against live mainnet for fidelity checks
+//! - Persisting the router environment to snapshot files
 
+pub mod deepbook_errors;
+pub mod mainnet_quote;
 pub mod orderbook_builder;
+pub mod quote_cache;
 pub mod router;
+pub mod router_snapshot;
 pub mod snowflake_bcs;
 pub mod state_loader;
 pub mod swap_executor;
@@ -10,6 +10,7 @@ use move_core_types::identifier::Identifier;
 use move_core_types::language_storage::{StructTag, TypeTag};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 use std::str::FromStr;
 
 use sui_sandbox_core::fetcher::GrpcFetcher;
@@ -17,8 +18,10 @@ use sui_sandbox_core::ptb::{Argument, Command, InputValue, ObjectInput};
 use sui_sandbox_core::simulation::state::FetcherConfig;
 use sui_sandbox_core::simulation::SimulationEnvironment;
 
+use super::router::{DebugPoolCreateConfig, VmBookLevels};
 use super::snowflake_bcs::JsonToBcsConverter;
-use super::state_loader::{ExportedObject, PoolId, StateLoader};
+use super::state_loader::{DeepBookConfig, ExportedObject, PoolId, StateLoader};
+use crate::units;
 
 // Note: gRPC is only used for package loading, not for fetching missing slices
 // All pool state should come from Snowflake data
@@ -34,8 +37,6 @@ const WAL_TYPE: &str =
     "0x356a26eb9e012a68958082340d4c4116e7f55615cf27affcff209cf0ae544f59::wal::WAL";
 const DEEP_TYPE: &str =
     "0xdeeb7a4662eec9f2f3def03fb937a663dddaa2e215b8078a284d026b7946c270::deep::DEEP";
-const DEBUG_TYPE: &str =
-    "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa::debug_token::DEBUG_TOKEN";
 
 /// Order from DeepBook (decoded by Move VM)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,12 +57,12 @@ impl DecodedOrder {
 
     /// Price in human-readable format (assumes 6 decimal quote)
     pub fn price_usd(&self, quote_decimals: u8) -> f64 {
-        self.price as f64 / 10f64.powi(quote_decimals as i32)
+        units::to_human(self.price, quote_decimals)
     }
 
     /// Quantity in human-readable format
     pub fn quantity_human(&self, base_decimals: u8) -> f64 {
-        self.remaining_quantity() as f64 / 10f64.powi(base_decimals as i32)
+        units::to_human(self.remaining_quantity(), base_decimals)
     }
 }
 
@@ -73,6 +74,25 @@ pub struct PriceLevel {
     pub order_count: usize,
 }
 
+/// Ordering applied to price levels when returning an orderbook view
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LevelSort {
+    PriceAsc,
+    PriceDesc,
+    QuantityDesc,
+}
+
+/// Liquidity a market order would take, from walking the price levels in memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BookWalk {
+    pub levels_consumed: usize,
+    /// Orders at a partially consumed level are prorated by quantity, rounded up
+    pub orders_matched: usize,
+    /// The book holds enough liquidity for the whole input
+    pub fully_fillable: bool,
+}
+
 /// Complete orderbook built from sui-sandbox execution
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SandboxOrderbook {
@@ -82,6 +102,17 @@ pub struct SandboxOrderbook {
     pub checkpoint: u64,
     pub base_decimals: u8,
     pub quote_decimals: u8,
+    /// Symbols of the pool's coins as loaded, e.g. `SUI` and `USDC`
+    #[serde(default)]
+    pub base_symbol: String,
+    #[serde(default)]
+    pub quote_symbol: String,
+    /// Fully-filled orders (remaining == 0) excluded from the price levels
+    #[serde(default)]
+    pub skipped_filled_orders: usize,
+    /// Wall-clock time the book was built from checkpoint state (unix ms)
+    #[serde(default)]
+    pub built_at_unix_ms: u64,
 }
 
 impl SandboxOrderbook {
@@ -94,9 +125,7 @@ impl SandboxOrderbook {
 
     /// Public accessor for the price divisor
     pub fn price_divisor_value(&self) -> f64 {
-        // USDC quote decimals (10^6) * normalization factor (10^(9 - base_decimals))
-        let normalization = 10f64.powi(9 - self.base_decimals as i32);
-        1_000_000.0 * normalization
+        units::price_divisor(self.base_decimals, self.quote_decimals)
     }
 
     pub fn mid_price(&self) -> Option<f64> {
@@ -131,6 +160,116 @@ impl SandboxOrderbook {
         let spread = best_ask.abs_diff(best_bid);
         Some(spread * 10000 / mid)
     }
+
+    /// Cheap content hash over the levels and checkpoint metadata.
+    ///
+    /// Changes whenever a swap consumes liquidity, so it can back HTTP ETags.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        self.pool_id.as_str().hash(&mut hasher);
+        self.checkpoint.hash(&mut hasher);
+        self.built_at_unix_ms.hash(&mut hasher);
+        self.skipped_filled_orders.hash(&mut hasher);
+        for (side, levels) in [(0u8, &self.bids), (1u8, &self.asks)] {
+            side.hash(&mut hasher);
+            levels.len().hash(&mut hasher);
+            for level in levels {
+                level.price.hash(&mut hasher);
+                level.total_quantity.hash(&mut hasher);
+                level.order_count.hash(&mut hasher);
+            }
+        }
+        hasher.finish()
+    }
+
+    /// Walk the book with a market order of `input` (smallest units).
+    ///
+    /// Selling base consumes bid quantity directly; buying base spends
+    /// `input` USDC on asks at `quantity * price / 1e9` per level. Lot-size
+    /// rounding and fees are ignored.
+    pub fn walk(&self, is_sell_base: bool, input: u64) -> BookWalk {
+        let levels = if is_sell_base { &self.bids } else { &self.asks };
+        let mut remaining = input as u128;
+        let mut levels_consumed = 0;
+        let mut orders_matched = 0;
+        for level in levels {
+            if remaining == 0 {
+                break;
+            }
+            let level_size = if is_sell_base {
+                level.total_quantity as u128
+            } else {
+                level.total_quantity as u128 * level.price as u128 / 1_000_000_000
+            };
+            if level_size == 0 {
+                continue;
+            }
+            let taken = remaining.min(level_size);
+            remaining -= taken;
+            levels_consumed += 1;
+            orders_matched += (level.order_count as u128 * taken).div_ceil(level_size) as usize;
+        }
+        BookWalk {
+            levels_consumed,
+            orders_matched,
+            fully_fillable: remaining == 0,
+        }
+    }
+
+    /// Bid levels reordered by `sort` (default: price descending) and truncated to `limit`
+    pub fn bids_view(&self, sort: Option<LevelSort>, limit: Option<usize>) -> Vec<PriceLevel> {
+        Self::level_view(&self.bids, sort, limit)
+    }
+
+    /// Ask levels reordered by `sort` (default: price ascending) and truncated to `limit`
+    pub fn asks_view(&self, sort: Option<LevelSort>, limit: Option<usize>) -> Vec<PriceLevel> {
+        Self::level_view(&self.asks, sort, limit)
+    }
+
+    /// Replace both sides with levels read back from the router VM, keeping
+    /// the pool's decimals and source checkpoint.
+    pub fn apply_vm_levels(&mut self, levels: &VmBookLevels) {
+        self.bids = levels.bids.clone();
+        self.asks = levels.asks.clone();
+        self.skipped_filled_orders = levels.skipped_filled_orders;
+        self.built_at_unix_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+    }
+
+    /// Copy of this orderbook with both sides sorted and truncated.
+    ///
+    /// Best bid/ask derived from the view are only meaningful with the
+    /// default ordering; use the accessors on the original book for those.
+    pub fn view(&self, sort: Option<LevelSort>, limit: Option<usize>) -> SandboxOrderbook {
+        SandboxOrderbook {
+            bids: self.bids_view(sort, limit),
+            asks: self.asks_view(sort, limit),
+            ..self.clone()
+        }
+    }
+
+    fn level_view(
+        levels: &[PriceLevel],
+        sort: Option<LevelSort>,
+        limit: Option<usize>,
+    ) -> Vec<PriceLevel> {
+        let mut view = levels.to_vec();
+        // Stable sorts keep the natural side ordering as the tie-breaker
+        match sort {
+            Some(LevelSort::PriceAsc) => view.sort_by(|a, b| a.price.cmp(&b.price)),
+            Some(LevelSort::PriceDesc) => view.sort_by(|a, b| b.price.cmp(&a.price)),
+            Some(LevelSort::QuantityDesc) => {
+                view.sort_by(|a, b| b.total_quantity.cmp(&a.total_quantity))
+            }
+            None => {}
+        }
+        if let Some(limit) = limit {
+            view.truncate(limit);
+        }
+        view
+    }
 }
 
 /// Builder that uses sui-sandbox to construct orderbooks
@@ -143,6 +282,8 @@ pub struct OrderbookBuilder {
     bcs_converter: JsonToBcsConverter,
     /// Track missing slice names for debugging
     missing_slices: Vec<(String, u64)>, // (parent_uid, slice_name)
+    /// Pool wrapper id -> (base type, quote type) from the loaded state
+    pool_coin_types: HashMap<String, (String, String)>,
 }
 
 impl OrderbookBuilder {
@@ -155,6 +296,7 @@ impl OrderbookBuilder {
             pool_cache: HashMap::new(),
             bcs_converter: JsonToBcsConverter::new(),
             missing_slices: Vec::new(),
+            pool_coin_types: HashMap::new(),
         })
     }
 
@@ -281,17 +423,17 @@ impl OrderbookBuilder {
                         )
                     })?;
 
-                // Build the Pool type tag
-                let (base_type, quote_type) = match pool_id {
-                    PoolId::SuiUsdc => (SUI_TYPE, USDC_TYPE),
-                    PoolId::WalUsdc => (WAL_TYPE, USDC_TYPE),
-                    PoolId::DeepUsdc => (DEEP_TYPE, USDC_TYPE),
-                    PoolId::DebugUsdc => (DEBUG_TYPE, USDC_TYPE),
+                // Build the Pool type tag from the pair the state file holds
+                let (base_type, quote_type) = match loader.pool_coin_types() {
+                    Some(coin_types) => coin_types,
+                    None => default_coin_types(pool_id)?,
                 };
 
-                let pool_type = build_pool_type_tag(base_type, quote_type)?;
+                let pool_type = build_pool_type_tag(&base_type, &quote_type)?;
                 self.pool_cache
                     .insert(pool_wrapper_id.clone(), (bcs_bytes, pool_type, obj.version));
+                self.pool_coin_types
+                    .insert(pool_wrapper_id.clone(), (base_type, quote_type));
             }
         }
 
@@ -589,18 +731,27 @@ impl OrderbookBuilder {
             return Err(anyhow!("Packages not loaded. Call load_packages_* first"));
         }
 
-        let (base_type, quote_type, base_decimals, quote_decimals) = match pool_id {
-            PoolId::SuiUsdc => (SUI_TYPE, USDC_TYPE, 9u8, 6u8),
-            PoolId::WalUsdc => (WAL_TYPE, USDC_TYPE, 9u8, 6u8),
-            PoolId::DeepUsdc => (DEEP_TYPE, USDC_TYPE, 6u8, 6u8),
-            PoolId::DebugUsdc => (DEBUG_TYPE, USDC_TYPE, 9u8, 6u8),
+        let (base_type, quote_type) = match self.pool_coin_types.get(pool_object_id) {
+            Some((base_type, quote_type)) => (base_type.clone(), quote_type.clone()),
+            None => default_coin_types(pool_id)?,
+        };
+        let decimals = |coin_type: &str| {
+            units::decimals_for_coin_type(coin_type).ok_or_else(|| {
+                anyhow!(
+                    "{}: no decimals known for {}; set them in POOLS_MANIFEST",
+                    pool_id.display_name(),
+                    coin_type
+                )
+            })
         };
+        let base_decimals = decimals(&base_type)?;
+        let quote_decimals = decimals(&quote_type)?;
 
         // Get bids
         let bid_orders = self.call_iter_orders(
             pool_object_id,
-            base_type,
-            quote_type,
+            &base_type,
+            &quote_type,
             true, // bids
             1000, // limit
         )?;
@@ -608,15 +759,25 @@ impl OrderbookBuilder {
         // Get asks
         let ask_orders = self.call_iter_orders(
             pool_object_id,
-            base_type,
-            quote_type,
+            &base_type,
+            &quote_type,
             false, // asks
             1000,  // limit
         )?;
 
-        // Aggregate to price levels
-        let bids = Self::aggregate_orders(&bid_orders, true);
-        let asks = Self::aggregate_orders(&ask_orders, false);
+        // Aggregate to price levels, dropping exhausted orders
+        let (bids, skipped_bids) = Self::aggregate_orders_with_stats(&bid_orders, true);
+        let (asks, skipped_asks) = Self::aggregate_orders_with_stats(&ask_orders, false);
+        let skipped_filled_orders = skipped_bids + skipped_asks;
+        if skipped_filled_orders > 0 {
+            tracing::debug!(
+                "{}: skipped {} fully-filled orders ({} bids, {} asks)",
+                pool_id.display_name(),
+                skipped_filled_orders,
+                skipped_bids,
+                skipped_asks
+            );
+        }
 
         Ok(SandboxOrderbook {
             pool_id,
@@ -625,6 +786,13 @@ impl OrderbookBuilder {
             checkpoint,
             base_decimals,
             quote_decimals,
+            base_symbol: units::coin_type_symbol(&base_type),
+            quote_symbol: units::coin_type_symbol(&quote_type),
+            skipped_filled_orders,
+            built_at_unix_ms: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
         })
     }
 
@@ -816,11 +984,19 @@ impl OrderbookBuilder {
 
     /// Aggregate orders into price levels
     fn aggregate_orders(orders: &[DecodedOrder], is_bid: bool) -> Vec<PriceLevel> {
+        Self::aggregate_orders_with_stats(orders, is_bid).0
+    }
+
+    /// Aggregate orders into price levels, returning the number of
+    /// fully-filled orders (zero remaining quantity) that were skipped
+    fn aggregate_orders_with_stats(orders: &[DecodedOrder], is_bid: bool) -> (Vec<PriceLevel>, usize) {
         let mut levels: HashMap<u64, (u64, usize)> = HashMap::new();
+        let mut skipped = 0;
 
         for order in orders {
             let remaining = order.remaining_quantity();
             if remaining == 0 {
+                skipped += 1;
                 continue;
             }
 
@@ -845,11 +1021,65 @@ impl OrderbookBuilder {
             result.sort_by(|a, b| a.price.cmp(&b.price));
         }
 
-        result
+        (result, skipped)
     }
 }
 
 /// Build Pool<BaseAsset, QuoteAsset> TypeTag
+/// Build one pool's orderbook from a state file with a dedicated builder.
+///
+/// Blocking: creates its own runtime for package loading, so call it from
+/// `spawn_blocking` or a plain thread. `OrderbookBuilder` is not `Send`, but
+/// the returned book is.
+pub fn build_orderbook_from_file(
+    pool_id: PoolId,
+    path: &std::path::Path,
+) -> Result<SandboxOrderbook> {
+    build_orderbook_and_loader_from_file(pool_id, path).map(|(ob, _)| ob)
+}
+
+/// [`build_orderbook_from_file`], also returning the loaded pool state so the
+/// caller can replace a registry entry with the file the book came from.
+pub fn build_orderbook_and_loader_from_file(
+    pool_id: PoolId,
+    path: &std::path::Path,
+) -> Result<(SandboxOrderbook, StateLoader)> {
+    let rt = tokio::runtime::Runtime::new()?;
+
+    let mut builder = OrderbookBuilder::new()?;
+    rt.block_on(builder.load_packages_from_grpc())?;
+
+    let mut loader = StateLoader::with_config(DeepBookConfig::for_pool(pool_id));
+    loader
+        .load_from_file(path)
+        .map_err(|e| anyhow!("Failed to load {}: {}", path.display(), e))?;
+    let stats = loader.stats();
+    // The loader adopts the file's pool when it is not the configured one
+    let pool_wrapper = loader.config().pool_wrapper.clone();
+
+    builder.load_pool_state(&loader, pool_id)?;
+    let ob = builder.build_orderbook(pool_id, &pool_wrapper, stats.max_checkpoint)?;
+    Ok((ob, loader))
+}
+
+/// Coin types a built-in pool is assumed to trade when its state file does
+/// not say; a manifest pool has only its state file to go by
+fn default_coin_types(pool_id: PoolId) -> Result<(String, String)> {
+    let base_type = match pool_id {
+        PoolId::SuiUsdc => SUI_TYPE.to_string(),
+        PoolId::WalUsdc => WAL_TYPE.to_string(),
+        PoolId::DeepUsdc => DEEP_TYPE.to_string(),
+        PoolId::DebugUsdc => DebugPoolCreateConfig::default().token_type(),
+        _ => {
+            return Err(anyhow!(
+                "{} state file has no Pool<Base, Quote> object",
+                pool_id.display_name()
+            ))
+        }
+    };
+    Ok((base_type, USDC_TYPE.to_string()))
+}
+
 pub fn build_pool_type_tag(base_type: &str, quote_type: &str) -> Result<TypeTag> {
     let base_tag = TypeTag::from_str(base_type)?;
     let quote_tag = TypeTag::from_str(quote_type)?;
@@ -943,4 +1173,158 @@ mod tests {
         assert_eq!(levels[1].price, 999_000);
         assert_eq!(levels[1].total_quantity, 300);
     }
+
+    #[test]
+    fn test_aggregate_orders_skips_fully_filled() {
+        let orders = vec![
+            DecodedOrder {
+                order_id: 0,
+                price: 2_000_000,
+                quantity: 100,
+                filled_quantity: 100, // Fully filled
+                is_bid: false,
+                expire_timestamp: 0,
+            },
+            DecodedOrder {
+                order_id: 1,
+                price: 2_000_000,
+                quantity: 400,
+                filled_quantity: 150, // Partially filled
+                is_bid: false,
+                expire_timestamp: 0,
+            },
+            DecodedOrder {
+                order_id: 2,
+                price: 2_100_000,
+                quantity: 50,
+                filled_quantity: 60, // Overfilled, saturates to zero
+                is_bid: false,
+                expire_timestamp: 0,
+            },
+            DecodedOrder {
+                order_id: 3,
+                price: 2_200_000,
+                quantity: 80,
+                filled_quantity: 0,
+                is_bid: false,
+                expire_timestamp: 0,
+            },
+        ];
+
+        let (levels, skipped) = OrderbookBuilder::aggregate_orders_with_stats(&orders, false);
+
+        assert_eq!(skipped, 2);
+        // The 2.1 level only held an exhausted order, so it must not appear
+        assert_eq!(levels.len(), 2);
+        assert_eq!(levels[0].price, 2_000_000);
+        assert_eq!(levels[0].total_quantity, 250); // only 400-150 remains
+        assert_eq!(levels[0].order_count, 1);
+        assert_eq!(levels[1].price, 2_200_000);
+        assert_eq!(levels[1].total_quantity, 80);
+    }
+
+    #[test]
+    fn test_level_view_sort_and_limit() {
+        let level = |price, total_quantity| PriceLevel {
+            price,
+            total_quantity,
+            order_count: 1,
+        };
+        let ob = SandboxOrderbook {
+            pool_id: PoolId::SuiUsdc,
+            bids: vec![level(1_000, 5), level(990, 50), level(980, 20)],
+            asks: vec![level(1_010, 30), level(1_020, 10), level(1_030, 40)],
+            checkpoint: 0,
+            base_decimals: 9,
+            quote_decimals: 6,
+            base_symbol: "SUI".to_string(),
+            quote_symbol: "USDC".to_string(),
+            skipped_filled_orders: 0,
+            built_at_unix_ms: 0,
+        };
+
+        // Default ordering is preserved, only truncated
+        let bids = ob.bids_view(None, Some(2));
+        assert_eq!(bids.iter().map(|l| l.price).collect::<Vec<_>>(), vec![1_000, 990]);
+
+        let bids = ob.bids_view(Some(LevelSort::PriceAsc), None);
+        assert_eq!(bids.iter().map(|l| l.price).collect::<Vec<_>>(), vec![980, 990, 1_000]);
+
+        let asks = ob.asks_view(Some(LevelSort::PriceDesc), Some(1));
+        assert_eq!(asks.iter().map(|l| l.price).collect::<Vec<_>>(), vec![1_030]);
+
+        let view = ob.view(Some(LevelSort::QuantityDesc), Some(2));
+        assert_eq!(view.bids.iter().map(|l| l.price).collect::<Vec<_>>(), vec![990, 980]);
+        assert_eq!(view.asks.iter().map(|l| l.price).collect::<Vec<_>>(), vec![1_030, 1_010]);
+    }
+
+    #[test]
+    fn test_walk_counts_levels_and_orders() {
+        let level = |price, total_quantity, order_count| PriceLevel {
+            price,
+            total_quantity,
+            order_count,
+        };
+        let ob = SandboxOrderbook {
+            pool_id: PoolId::SuiUsdc,
+            bids: vec![level(2_000_000_000, 100, 2), level(1_000_000_000, 300, 3)],
+            asks: vec![level(3_000_000_000, 100, 4), level(4_000_000_000, 50, 1)],
+            checkpoint: 0,
+            base_decimals: 9,
+            quote_decimals: 6,
+            base_symbol: "SUI".to_string(),
+            quote_symbol: "USDC".to_string(),
+            skipped_filled_orders: 0,
+            built_at_unix_ms: 0,
+        };
+
+        // Sell 250 base: all of the best bid, half of the second (1.5 -> 2 orders)
+        let walk = ob.walk(true, 250);
+        assert_eq!((walk.levels_consumed, walk.orders_matched), (2, 4));
+        assert!(walk.fully_fillable);
+
+        // Spend 300 quote: exactly the best ask level (100 base at 3.0)
+        let walk = ob.walk(false, 300);
+        assert_eq!((walk.levels_consumed, walk.orders_matched), (1, 4));
+        assert!(walk.fully_fillable);
+
+        // More quote than both ask levels hold (300 + 200)
+        let walk = ob.walk(false, 600);
+        assert_eq!((walk.levels_consumed, walk.orders_matched), (2, 5));
+        assert!(!walk.fully_fillable);
+
+        assert_eq!(ob.walk(true, 0).levels_consumed, 0);
+    }
+
+    #[test]
+    fn test_content_hash_tracks_levels() {
+        let level = |price, total_quantity| PriceLevel {
+            price,
+            total_quantity,
+            order_count: 1,
+        };
+        let ob = SandboxOrderbook {
+            pool_id: PoolId::SuiUsdc,
+            bids: vec![level(1_000, 5)],
+            asks: vec![level(1_010, 30)],
+            checkpoint: 42,
+            base_decimals: 9,
+            quote_decimals: 6,
+            base_symbol: "SUI".to_string(),
+            quote_symbol: "USDC".to_string(),
+            skipped_filled_orders: 0,
+            built_at_unix_ms: 0,
+        };
+        assert_eq!(ob.content_hash(), ob.clone().content_hash());
+
+        // Consuming liquidity changes the hash
+        let mut consumed = ob.clone();
+        consumed.asks[0].total_quantity = 20;
+        assert_ne!(ob.content_hash(), consumed.content_hash());
+
+        // Moving a level across sides changes the hash
+        let mut swapped = ob.clone();
+        std::mem::swap(&mut swapped.bids, &mut swapped.asks);
+        assert_ne!(ob.content_hash(), swapped.content_hash());
+    }
 }
@@ -0,0 +1,251 @@
+//! Short-lived cache for single-hop router quotes
+//!
+//! Router state only changes when a request mutates a pool (a swap, a limit
+//! order, debug pool seeding, a reset, ...), so repeating a quote for the same
+//! pool, size and side re-runs an identical PTB. [`QuoteCache`] keeps those
+//! results for `QUOTE_CACHE_TTL_MS`. The router thread invalidates a pool
+//! before running any request that may mutate it; every invalidation bumps the
+//! pool's generation, so a quote that started before the change is not cached
+//! after it.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::state_loader::PoolId;
+
+/// Default lifetime of a cached quote
+pub const DEFAULT_QUOTE_CACHE_TTL_MS: u64 = 1_000;
+
+/// Entries kept before expired ones are swept (and, failing that, all dropped)
+const MAX_QUOTE_CACHE_ENTRIES: usize = 4_096;
+
+/// Lookups between hit-rate log lines
+const HIT_RATE_LOG_INTERVAL: u64 = 1_000;
+
+/// What a cached quote was computed for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct QuoteCacheKey {
+    pub pool_id: PoolId,
+    pub input_amount: u64,
+    pub is_sell_base: bool,
+    /// Pinned VM clock of the request; `None` for the synthetic clock
+    pub clock_ms: Option<u64>,
+}
+
+/// Token taken before a quote runs; the result is only cached if the pool
+/// was not invalidated in between.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuoteCacheGeneration {
+    all: u64,
+    pool: u64,
+}
+
+struct CacheState<T> {
+    entries: HashMap<QuoteCacheKey, (Instant, T)>,
+    /// Bumped by `invalidate_all`
+    generation: u64,
+    /// Bumped per pool by `invalidate`
+    pool_generations: HashMap<PoolId, u64>,
+    /// Hits and lookups since the last hit-rate log line
+    window_hits: u64,
+    window_lookups: u64,
+}
+
+/// TTL cache of quotes keyed by [`QuoteCacheKey`]
+pub struct QuoteCache<T> {
+    /// `None` disables caching
+    ttl: Option<Duration>,
+    state: Mutex<CacheState<T>>,
+}
+
+impl<T: Clone> QuoteCache<T> {
+    pub fn new(ttl: Option<Duration>) -> Self {
+        Self {
+            ttl,
+            state: Mutex::new(CacheState {
+                entries: HashMap::new(),
+                generation: 0,
+                pool_generations: HashMap::new(),
+                window_hits: 0,
+                window_lookups: 0,
+            }),
+        }
+    }
+
+    /// Cache with `QUOTE_CACHE_TTL_MS` (default 1000); 0 disables it.
+    pub fn from_env() -> Self {
+        let ttl_ms = match std::env::var("QUOTE_CACHE_TTL_MS") {
+            Ok(raw) => raw.trim().parse::<u64>().unwrap_or_else(|_| {
+                tracing::warn!(
+                    "Invalid QUOTE_CACHE_TTL_MS={:?}, defaulting to {}",
+                    raw,
+                    DEFAULT_QUOTE_CACHE_TTL_MS
+                );
+                DEFAULT_QUOTE_CACHE_TTL_MS
+            }),
+            Err(_) => DEFAULT_QUOTE_CACHE_TTL_MS,
+        };
+        Self::new((ttl_ms > 0).then(|| Duration::from_millis(ttl_ms)))
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.ttl.is_some()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheState<T>> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// A fresh cached quote for `key`, counting the lookup toward the hit rate
+    pub fn get(&self, key: &QuoteCacheKey) -> Option<T> {
+        let ttl = self.ttl?;
+        let mut state = self.lock();
+        let hit = match state.entries.get(key) {
+            Some((cached_at, quote)) if cached_at.elapsed() < ttl => Some(quote.clone()),
+            Some(_) => {
+                state.entries.remove(key);
+                None
+            }
+            None => None,
+        };
+
+        state.window_lookups += 1;
+        if hit.is_some() {
+            state.window_hits += 1;
+        }
+        if state.window_lookups >= HIT_RATE_LOG_INTERVAL {
+            tracing::info!(
+                "Quote cache: {:.1}% hit rate over the last {} lookups (ttl={}ms, entries={})",
+                state.window_hits as f64 * 100.0 / state.window_lookups as f64,
+                state.window_lookups,
+                ttl.as_millis(),
+                state.entries.len()
+            );
+            state.window_hits = 0;
+            state.window_lookups = 0;
+        }
+        hit
+    }
+
+    /// Generation to pass to [`QuoteCache::insert`] for a quote on `pool_id`
+    pub fn generation(&self, pool_id: PoolId) -> QuoteCacheGeneration {
+        self.lock().generation_of(pool_id)
+    }
+
+    /// Cache `quote` unless its pool was invalidated since `generation`.
+    pub fn insert(&self, key: QuoteCacheKey, generation: QuoteCacheGeneration, quote: T) {
+        let Some(ttl) = self.ttl else {
+            return;
+        };
+        let mut state = self.lock();
+        if state.generation_of(key.pool_id) != generation {
+            return;
+        }
+        if state.entries.len() >= MAX_QUOTE_CACHE_ENTRIES {
+            state
+                .entries
+                .retain(|_, (cached_at, _)| cached_at.elapsed() < ttl);
+            if state.entries.len() >= MAX_QUOTE_CACHE_ENTRIES {
+                state.entries.clear();
+            }
+        }
+        state.entries.insert(key, (Instant::now(), quote));
+    }
+
+    /// Drop every cached quote on `pools`.
+    pub fn invalidate(&self, pools: &[PoolId]) {
+        let mut state = self.lock();
+        for pool_id in pools {
+            *state.pool_generations.entry(*pool_id).or_default() += 1;
+        }
+        state.entries.retain(|key, _| !pools.contains(&key.pool_id));
+    }
+
+    /// Drop every cached quote.
+    pub fn invalidate_all(&self) {
+        let mut state = self.lock();
+        state.generation += 1;
+        state.entries.clear();
+    }
+}
+
+impl<T> CacheState<T> {
+    fn generation_of(&self, pool_id: PoolId) -> QuoteCacheGeneration {
+        QuoteCacheGeneration {
+            all: self.generation,
+            pool: self.pool_generations.get(&pool_id).copied().unwrap_or(0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(pool_id: PoolId, input_amount: u64) -> QuoteCacheKey {
+        QuoteCacheKey {
+            pool_id,
+            input_amount,
+            is_sell_base: true,
+            clock_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_quote_cache_hits_until_invalidated() {
+        let cache = QuoteCache::new(Some(Duration::from_secs(60)));
+        let sui = key(PoolId::SuiUsdc, 1_000);
+        let wal = key(PoolId::WalUsdc, 1_000);
+
+        assert_eq!(cache.get(&sui), None);
+        cache.insert(sui, cache.generation(PoolId::SuiUsdc), 42u64);
+        cache.insert(wal, cache.generation(PoolId::WalUsdc), 7u64);
+        assert_eq!(cache.get(&sui), Some(42));
+        assert_eq!(
+            cache.get(&QuoteCacheKey {
+                is_sell_base: false,
+                ..sui
+            }),
+            None
+        );
+
+        cache.invalidate(&[PoolId::SuiUsdc]);
+        assert_eq!(cache.get(&sui), None);
+        assert_eq!(cache.get(&wal), Some(7));
+
+        cache.invalidate_all();
+        assert_eq!(cache.get(&wal), None);
+    }
+
+    #[test]
+    fn test_quote_cache_rejects_quotes_from_before_an_invalidation() {
+        let cache = QuoteCache::new(Some(Duration::from_secs(60)));
+        let sui = key(PoolId::SuiUsdc, 1_000);
+
+        let generation = cache.generation(PoolId::SuiUsdc);
+        cache.invalidate(&[PoolId::SuiUsdc]);
+        cache.insert(sui, generation, 42u64);
+        assert_eq!(cache.get(&sui), None);
+
+        let generation = cache.generation(PoolId::SuiUsdc);
+        cache.invalidate_all();
+        cache.insert(sui, generation, 42u64);
+        assert_eq!(cache.get(&sui), None);
+    }
+
+    #[test]
+    fn test_quote_cache_expires_and_can_be_disabled() {
+        let cache = QuoteCache::new(Some(Duration::ZERO));
+        let sui = key(PoolId::SuiUsdc, 1_000);
+        cache.insert(sui, cache.generation(PoolId::SuiUsdc), 42u64);
+        assert_eq!(cache.get(&sui), None);
+
+        let disabled = QuoteCache::new(None);
+        assert!(!disabled.is_enabled());
+        disabled.insert(sui, disabled.generation(PoolId::SuiUsdc), 42u64);
+        assert_eq!(disabled.get(&sui), None);
+    }
+}
@@ -0,0 +1,1933 @@
+//! Debug pool
+//!
+//! Publishes a debug token, creates its treasury, reserve coin and
+//! TOKEN/USDC pool in the VM, seeds the pool's order ladder and primes its
+//! DEEP price, plus the debug logging used while doing so. Each token symbol
+//! gets its own pool, so several debug pools can run side by side.
+
+use super::*;
+
+/// Metadata for an on-demand debug pool.
+#[derive(Debug, Clone)]
+pub struct DebugPoolInfo {
+    /// `PoolId::DebugUsdc` for the environment's first debug pool, a
+    /// `PoolId::register_debug` id for the others
+    pub pool_id: PoolId,
+    pub pool_object_id: String,
+    pub token_symbol: String,
+    pub token_type: String,
+    pub config: DebugPoolCreateConfig,
+}
+
+/// Configurable parameters for creating/seeding the debug pool in local VM.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DebugPoolCreateConfig {
+    pub token_symbol: String,
+    pub token_name: String,
+    pub token_description: String,
+    pub token_icon_url: String,
+    pub token_decimals: u8,
+    pub tick_size: u64,
+    pub lot_size: u64,
+    pub min_size: u64,
+    pub whitelisted_pool: bool,
+    pub pay_with_deep: bool,
+    pub bid_price: u64,
+    pub ask_price: u64,
+    pub bid_quantity: u64,
+    pub ask_quantity: u64,
+    /// Split evenly across the ask seed orders
+    pub base_liquidity: u64,
+    /// Split evenly across the bid seed orders
+    pub quote_liquidity: u64,
+    /// DEEP deposited with each seed order
+    pub deep_fee_budget: u64,
+    /// Bid seed orders, stepping down from `bid_price`
+    #[serde(default = "default_seed_levels")]
+    pub bid_levels: u32,
+    /// Ask seed orders, stepping up from `ask_price`
+    #[serde(default = "default_seed_levels")]
+    pub ask_levels: u32,
+    /// Gap between seed levels, in ticks
+    #[serde(default = "default_seed_price_step")]
+    pub price_step: u64,
+    /// Owners of the seed orders' balance managers, cycled per seed order;
+    /// empty seeds every order from `DEBUG_POOL_MAKER_SENDER`
+    #[serde(default)]
+    pub maker_senders: Vec<String>,
+}
+
+fn default_seed_levels() -> u32 {
+    1
+}
+
+fn default_seed_price_step() -> u64 {
+    1
+}
+
+impl Default for DebugPoolCreateConfig {
+    fn default() -> Self {
+        Self {
+            token_symbol: "DBG".to_string(),
+            token_name: "Debug Token".to_string(),
+            token_description: "Local VM debug token for DeepBook sandbox flows".to_string(),
+            token_icon_url: String::new(),
+            token_decimals: 9,
+            tick_size: DEBUG_POOL_TICK_SIZE,
+            lot_size: DEBUG_POOL_LOT_SIZE,
+            min_size: DEBUG_POOL_MIN_SIZE,
+            whitelisted_pool: DEBUG_POOL_WHITELISTED,
+            pay_with_deep: DEBUG_POOL_PAY_WITH_DEEP,
+            bid_price: DEBUG_POOL_BID_PRICE,
+            ask_price: DEBUG_POOL_ASK_PRICE,
+            bid_quantity: DEBUG_POOL_BID_QTY,
+            ask_quantity: DEBUG_POOL_ASK_QTY,
+            base_liquidity: DEBUG_POOL_BASE_LIQUIDITY,
+            quote_liquidity: DEBUG_POOL_USDC_LIQUIDITY,
+            deep_fee_budget: DEBUG_POOL_DEEP_FEE_BUDGET,
+            bid_levels: default_seed_levels(),
+            ask_levels: default_seed_levels(),
+            price_step: default_seed_price_step(),
+            maker_senders: Vec::new(),
+        }
+    }
+}
+
+/// One seed order of the debug pool ladder and what its balance manager
+/// deposits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct SeedOrder {
+    pub(super) is_bid: bool,
+    pub(super) price: u64,
+    pub(super) quantity: u64,
+    pub(super) base_deposit: u64,
+    pub(super) quote_deposit: u64,
+}
+
+impl DebugPoolCreateConfig {
+    /// Coin type of the debug token this config creates
+    pub fn token_type(&self) -> String {
+        debug_coin_type(&self.token_symbol)
+    }
+
+    /// Seed orders, asks first, each level `price_step * tick_size` further
+    /// from mid. Asks split `base_liquidity` and bids split `quote_liquidity`.
+    pub(super) fn seed_ladder(&self) -> Vec<SeedOrder> {
+        let step = self.price_step.saturating_mul(self.tick_size);
+        let asks = (0..self.ask_levels).map(|level| SeedOrder {
+            is_bid: false,
+            price: self.ask_price + step * u64::from(level),
+            quantity: self.ask_quantity,
+            base_deposit: self.base_liquidity / u64::from(self.ask_levels),
+            quote_deposit: 0,
+        });
+        let bids = (0..self.bid_levels).map(|level| SeedOrder {
+            is_bid: true,
+            price: self.bid_price - step * u64::from(level),
+            quantity: self.bid_quantity,
+            base_deposit: 0,
+            quote_deposit: self.quote_liquidity / u64::from(self.bid_levels),
+        });
+        asks.chain(bids).collect()
+    }
+
+    /// Maker addresses seed orders cycle through, in order
+    pub fn seed_makers(&self) -> Result<Vec<AccountAddress>> {
+        if self.maker_senders.is_empty() {
+            return Ok(vec![AccountAddress::from_hex_literal(
+                DEBUG_POOL_MAKER_SENDER,
+            )?]);
+        }
+        self.maker_senders
+            .iter()
+            .map(|sender| {
+                AccountAddress::from_hex_literal(sender.trim())
+                    .map_err(|e| anyhow!("invalid maker sender {:?}: {}", sender, e))
+            })
+            .collect()
+    }
+}
+
+/// Package the `debug_token` module is published at for `symbol`: the prefix
+/// byte, then the symbol's ASCII bytes right-aligned and zero-padded, so every
+/// debug token symbol gets its own `DEBUG_TOKEN` type.
+pub(super) fn debug_token_package_addr(symbol: &str) -> AccountAddress {
+    let symbol = symbol.trim().to_uppercase();
+    let len = symbol.len().min(AccountAddress::LENGTH - 1);
+    let mut bytes = [0u8; AccountAddress::LENGTH];
+    bytes[0] = DEBUG_TOKEN_ADDRESS_PREFIX;
+    bytes[AccountAddress::LENGTH - len..].copy_from_slice(&symbol.as_bytes()[..len]);
+    AccountAddress::new(bytes)
+}
+
+/// Coin type of the debug token `symbol`
+pub fn debug_coin_type(symbol: &str) -> String {
+    format!(
+        "{}::debug_token::DEBUG_TOKEN",
+        debug_token_package_addr(symbol).to_hex_literal()
+    )
+}
+
+/// `TreasuryCap` type of the debug token `symbol`
+pub(super) fn debug_treasury_type(symbol: &str) -> String {
+    format!("0x2::coin::TreasuryCap<{}>", debug_coin_type(symbol))
+}
+
+/// Config of the debug token `coin_type`: its pool's, or the default one so
+/// the DBG token can be minted before any pool uses it
+pub(super) fn debug_config_for_coin_type(
+    state: &RouterEnvState,
+    coin_type: &str,
+) -> Option<DebugPoolCreateConfig> {
+    state
+        .debug_pools
+        .values()
+        .map(|info| &info.config)
+        .find(|config| config.token_type() == coin_type)
+        .cloned()
+        .or_else(|| {
+            let default = DebugPoolCreateConfig::default();
+            (default.token_type() == coin_type).then_some(default)
+        })
+}
+
+/// Coin types of every debug token: the default DBG token and each created
+/// pool's
+pub(super) fn debug_token_types(state: &RouterEnvState) -> Vec<String> {
+    let mut types = vec![DebugPoolCreateConfig::default().token_type()];
+    for info in state.debug_pools.values() {
+        if !types.contains(&info.token_type) {
+            types.push(info.token_type.clone());
+        }
+    }
+    types
+}
+
+/// Error when a deep_price point at `point_ms` would be too close to `previous_ms`.
+pub(super) fn deep_price_spacing_error(
+    previous_ms: Option<u64>,
+    point_ms: u64,
+    step_ms: u64,
+) -> Option<anyhow::Error> {
+    let previous_ms = previous_ms?;
+    let gap = point_ms.saturating_sub(previous_ms);
+    if gap > DEEP_PRICE_MIN_SPACING_MS {
+        return None;
+    }
+    Some(anyhow!(
+        "deep_price point at {}ms is only {}ms after the previous point at {}ms; DeepBook requires \
+         more than {}ms between points (ROUTER_CLOCK_STEP_MS={})",
+        point_ms,
+        gap,
+        previous_ms,
+        DEEP_PRICE_MIN_SPACING_MS,
+        step_ms
+    ))
+}
+
+fn ensure_debug_admin_cap(state: &mut RouterEnvState) -> Result<()> {
+    let admin_cap_addr = AccountAddress::from_hex_literal(DEBUG_ADMIN_CAP_ID)?;
+    if state.env.get_object(&admin_cap_addr).is_some() {
+        return Ok(());
+    }
+
+    // DeepbookAdminCap has a single UID field, encoded as its object id bytes.
+    let mut bcs_bytes = Vec::with_capacity(AccountAddress::LENGTH);
+    bcs_bytes.extend_from_slice(admin_cap_addr.as_ref());
+
+    state.env.load_object_from_data(
+        DEBUG_ADMIN_CAP_ID,
+        bcs_bytes,
+        Some(&format!("{}::registry::DeepbookAdminCap", DEEPBOOK_PACKAGE)),
+        false,
+        false,
+        1,
+    )?;
+
+    tracing::info!(
+        "Router: synthesized DeepBook admin cap for debug pool creation ({})",
+        DEBUG_ADMIN_CAP_ID
+    );
+    Ok(())
+}
+
+/// Publish the router's `debug_token` module at the package address of
+/// `symbol`, once per environment, and return that address.
+///
+/// The module only references the framework, so rewriting its self address
+/// is enough to give the copy its own `DEBUG_TOKEN` type.
+pub(super) fn ensure_debug_token_package(
+    state: &mut RouterEnvState,
+    symbol: &str,
+) -> Result<AccountAddress> {
+    let package_addr = debug_token_package_addr(symbol);
+    if state.debug_token_packages.contains(&package_addr) {
+        return Ok(package_addr);
+    }
+
+    let bytecode = state
+        .snapshot_packages
+        .iter()
+        .find(|package| package.address == ROUTER_PACKAGE_ADDR)
+        .and_then(|package| {
+            package
+                .modules
+                .iter()
+                .find(|(name, _)| name == "debug_token")
+        })
+        .map(|(_, bytecode)| bytecode.clone())
+        .ok_or_else(|| anyhow!("Router package has no debug_token module to publish"))?;
+    let mut module = CompiledModule::deserialize_with_defaults(&bytecode)
+        .map_err(|e| anyhow!("Failed to deserialize debug_token module: {:?}", e))?;
+    let self_address = module.address_identifiers[module.self_module_handle().address.0 as usize];
+    for address in module.address_identifiers.iter_mut() {
+        if *address == self_address {
+            *address = package_addr;
+        }
+    }
+    let mut relocated = Vec::new();
+    module
+        .serialize_with_version(module.version, &mut relocated)
+        .map_err(|e| anyhow!("Failed to serialize debug_token module: {:?}", e))?;
+
+    state.env.deploy_package_at_address(
+        &package_addr.to_hex_literal(),
+        vec![("debug_token".to_string(), relocated)],
+    )?;
+    state.debug_token_packages.insert(package_addr);
+    tracing::info!(
+        "Router: published debug_token for {} at {}",
+        symbol,
+        package_addr.to_hex_literal()
+    );
+    Ok(package_addr)
+}
+
+pub(super) fn ensure_debug_treasury(
+    state: &mut RouterEnvState,
+    token_cfg: &DebugPoolCreateConfig,
+) -> Result<AccountAddress> {
+    if let Some(existing) = state
+        .debug_treasury_ids
+        .get(&token_cfg.token_symbol)
+        .copied()
+    {
+        if state.env.get_object(&existing).is_some() {
+            return Ok(existing);
+        }
+        state.debug_treasury_ids.remove(&token_cfg.token_symbol);
+    }
+
+    let treasury_type = debug_treasury_type(&token_cfg.token_symbol);
+    let treasury_tag = TypeTag::from_str(&treasury_type)?;
+    if let Some(existing) = state
+        .env
+        .list_objects()
+        .into_iter()
+        .find(|obj| obj.type_tag == treasury_tag)
+        .map(|obj| obj.id)
+    {
+        state
+            .debug_treasury_ids
+            .insert(token_cfg.token_symbol.clone(), existing);
+        return Ok(existing);
+    }
+
+    let token_addr = ensure_debug_token_package(state, &token_cfg.token_symbol)?;
+    let result = state.env.execute_ptb(
+        vec![
+            InputValue::Object(coin_registry_shared_input(state, true)?),
+            InputValue::Pure(bcs::to_bytes(&token_cfg.token_decimals)?),
+            InputValue::Pure(bcs::to_bytes(&token_cfg.token_symbol.as_bytes().to_vec())?),
+            InputValue::Pure(bcs::to_bytes(&token_cfg.token_name.as_bytes().to_vec())?),
+            InputValue::Pure(bcs::to_bytes(
+                &token_cfg.token_description.as_bytes().to_vec(),
+            )?),
+            InputValue::Pure(bcs::to_bytes(
+                &token_cfg.token_icon_url.as_bytes().to_vec(),
+            )?),
+        ],
+        vec![Command::MoveCall {
+            package: token_addr,
+            module: Identifier::new("debug_token")?,
+            function: Identifier::new("init_for_router")?,
+            type_args: vec![],
+            args: vec![
+                Argument::Input(0),
+                Argument::Input(1),
+                Argument::Input(2),
+                Argument::Input(3),
+                Argument::Input(4),
+                Argument::Input(5),
+            ],
+        }],
+    );
+
+    if !result.success {
+        return Err(anyhow!(
+            "debug treasury init failed: {}",
+            result
+                .raw_error
+                .unwrap_or_else(|| "Unknown error".to_string())
+        ));
+    }
+    let effects = result
+        .effects
+        .as_ref()
+        .ok_or_else(|| anyhow!("Missing PTB effects for debug treasury init"))?;
+    sync_dynamic_field_entries(state, effects);
+    tracing::info!(
+        "Router: debug treasury init effects created={} mutated={} object_changes={}",
+        effects.created.len(),
+        effects.mutated.len(),
+        effects.object_changes.len()
+    );
+
+    // init_for_router returns TreasuryCap<DEBUG_TOKEN>; sandbox currently does not
+    // always surface it in object_changes, so recover from command return bytes.
+    let treasury_from_return = effects
+        .return_values
+        .first()
+        .and_then(|values| values.first())
+        .cloned();
+
+    let treasury_id = if let Some(cap_bytes) = treasury_from_return {
+        if cap_bytes.len() < AccountAddress::LENGTH {
+            return Err(anyhow!(
+                "debug treasury init returned short TreasuryCap bytes: {}",
+                cap_bytes.len()
+            ));
+        }
+        let mut id_bytes = [0u8; AccountAddress::LENGTH];
+        id_bytes.copy_from_slice(&cap_bytes[..AccountAddress::LENGTH]);
+        let treasury_id = AccountAddress::new(id_bytes);
+        if state.env.get_object(&treasury_id).is_none() {
+            state.env.load_object_from_data(
+                &treasury_id.to_hex_literal(),
+                cap_bytes,
+                Some(&treasury_type),
+                false,
+                false,
+                1,
+            )?;
+        }
+        treasury_id
+    } else {
+        find_created_object_id_by_type(effects, &treasury_type)
+            .or_else(|| {
+                state
+                    .env
+                    .list_objects()
+                    .into_iter()
+                    .find(|obj| obj.type_tag == treasury_tag)
+                    .map(|obj| obj.id)
+            })
+            .ok_or_else(|| {
+                let matching: Vec<String> = state
+                    .env
+                    .list_objects()
+                    .into_iter()
+                    .filter(|obj| obj.type_tag.to_string().contains("::debug_token::"))
+                    .map(|obj| format!("{}:{}", obj.id, obj.type_tag))
+                    .collect();
+                anyhow!(
+                    "Could not locate debug treasury cap object after init_for_router (debug objects in env: [{}])",
+                    matching.join(", ")
+                )
+            })?
+    };
+
+    state
+        .debug_treasury_ids
+        .insert(token_cfg.token_symbol.clone(), treasury_id);
+    tracing::info!(
+        "Router: {} debug treasury ready in VM at {}",
+        token_cfg.token_symbol,
+        treasury_id.to_hex_literal()
+    );
+    Ok(treasury_id)
+}
+
+pub(super) fn debug_treasury_shared_input(
+    state: &RouterEnvState,
+    treasury_id: AccountAddress,
+    symbol: &str,
+) -> Result<ObjectInput> {
+    let treasury_obj = state
+        .env
+        .get_object(&treasury_id)
+        .ok_or_else(|| anyhow!("Debug treasury cap object missing in env: {}", treasury_id))?;
+
+    Ok(ObjectInput::Owned {
+        id: treasury_id,
+        bytes: treasury_obj.bcs_bytes.clone(),
+        type_tag: Some(TypeTag::from_str(&debug_treasury_type(symbol))?),
+        version: Some(treasury_obj.version),
+    })
+}
+
+pub(super) fn mint_debug_reserve_coin(
+    state: &mut RouterEnvState,
+    config: &DebugPoolCreateConfig,
+    amount: u64,
+) -> Result<AccountAddress> {
+    let treasury_id = ensure_debug_treasury(state, config)?;
+    let debug_type = config.token_type();
+    let sui_framework_addr = AccountAddress::from_hex_literal(SUI_FRAMEWORK_PACKAGE)?;
+
+    let inputs = vec![
+        InputValue::Object(debug_treasury_shared_input(
+            state,
+            treasury_id,
+            &config.token_symbol,
+        )?),
+        InputValue::Pure(bcs::to_bytes(&amount)?),
+    ];
+    let commands = vec![
+        Command::MoveCall {
+            package: sui_framework_addr,
+            module: Identifier::new("coin")?,
+            function: Identifier::new("mint")?,
+            type_args: vec![TypeTag::from_str(&debug_type)?],
+            args: vec![Argument::Input(0), Argument::Input(1)],
+        },
+        Command::MoveCall {
+            package: sui_framework_addr,
+            module: Identifier::new("coin")?,
+            function: Identifier::new("value")?,
+            type_args: vec![TypeTag::from_str(&debug_type)?],
+            args: vec![Argument::Result(0)],
+        },
+    ];
+
+    let result = state.env.execute_ptb(inputs, commands);
+    if !result.success {
+        return Err(anyhow!(
+            "debug reserve mint failed: {}",
+            result
+                .raw_error
+                .unwrap_or_else(|| "Unknown error".to_string())
+        ));
+    }
+    let effects = result
+        .effects
+        .as_ref()
+        .ok_or_else(|| anyhow!("Missing PTB effects for debug reserve mint"))?;
+    sync_dynamic_field_entries(state, effects);
+
+    let minted = parse_u64_command_return(effects, 1, 0, "debug_minted_amount")?;
+    if minted != amount {
+        return Err(anyhow!(
+            "debug reserve mint mismatch: requested {}, minted {}",
+            amount,
+            minted
+        ));
+    }
+
+    let debug_coin_type = coin_object_type(&debug_type);
+    let reserve_id = find_created_object_id_by_type(effects, &debug_coin_type)
+        .ok_or_else(|| anyhow!("Could not locate created DEBUG coin from mint PTB effects"))?;
+
+    if state.env.get_object(&reserve_id).is_none() {
+        if let Some(bytes) = effects.created_object_bytes.get(&reserve_id) {
+            state.env.load_object_from_data(
+                &reserve_id.to_hex_literal(),
+                bytes.clone(),
+                Some(&debug_coin_type),
+                false,
+                false,
+                1,
+            )?;
+        }
+    }
+
+    state.coin_reserve_cache.insert(debug_type, reserve_id);
+    tracing::info!(
+        "Router: {} reserve minted in VM at {} (amount={})",
+        config.token_symbol,
+        reserve_id.to_hex_literal(),
+        amount
+    );
+    Ok(reserve_id)
+}
+
+pub(super) fn log_debug_pool_snapshot(
+    state: &mut RouterEnvState,
+    pool_id: PoolId,
+    context: &str,
+) -> Result<()> {
+    let deepbook_addr = AccountAddress::from_hex_literal(DEEPBOOK_PACKAGE)?;
+    let debug_tag = TypeTag::from_str(&pool_types(state, pool_id).0)?;
+    let usdc_tag = TypeTag::from_str(USDC_TYPE)?;
+    let ticks: u64 = 5;
+
+    let inputs = vec![
+        InputValue::Object(pool_shared_input(state, pool_id, false)?),
+        InputValue::Pure(bcs::to_bytes(&ticks)?),
+        InputValue::Object(state.next_clock_input()?),
+    ];
+
+    let commands = vec![
+        Command::MoveCall {
+            package: deepbook_addr,
+            module: Identifier::new("pool")?,
+            function: Identifier::new("pool_book_params")?,
+            type_args: vec![debug_tag.clone(), usdc_tag.clone()],
+            args: vec![Argument::Input(0)],
+        },
+        Command::MoveCall {
+            package: deepbook_addr,
+            module: Identifier::new("pool")?,
+            function: Identifier::new("whitelisted")?,
+            type_args: vec![debug_tag.clone(), usdc_tag.clone()],
+            args: vec![Argument::Input(0)],
+        },
+        Command::MoveCall {
+            package: deepbook_addr,
+            module: Identifier::new("pool")?,
+            function: Identifier::new("registered_pool")?,
+            type_args: vec![debug_tag.clone(), usdc_tag.clone()],
+            args: vec![Argument::Input(0)],
+        },
+        Command::MoveCall {
+            package: deepbook_addr,
+            module: Identifier::new("pool")?,
+            function: Identifier::new("vault_balances")?,
+            type_args: vec![debug_tag.clone(), usdc_tag.clone()],
+            args: vec![Argument::Input(0)],
+        },
+        Command::MoveCall {
+            package: deepbook_addr,
+            module: Identifier::new("pool")?,
+            function: Identifier::new("get_level2_ticks_from_mid")?,
+            type_args: vec![debug_tag, usdc_tag],
+            args: vec![Argument::Input(0), Argument::Input(1), Argument::Input(2)],
+        },
+    ];
+
+    let result = state.env.execute_ptb(inputs, commands);
+    if !result.success {
+        return Err(anyhow!(
+            "debug snapshot PTB failed ({}): {}",
+            context,
+            result
+                .raw_error
+                .unwrap_or_else(|| "Unknown error".to_string())
+        ));
+    }
+
+    let effects = result
+        .effects
+        .as_ref()
+        .ok_or_else(|| anyhow!("Missing PTB effects for debug snapshot ({})", context))?;
+    sync_dynamic_field_entries(state, effects);
+
+    let tick_size = parse_u64_command_return(effects, 0, 0, "tick_size")?;
+    let lot_size = parse_u64_command_return(effects, 0, 1, "lot_size")?;
+    let min_size = parse_u64_command_return(effects, 0, 2, "min_size")?;
+    let whitelisted = parse_bool_command_return(effects, 1, 0, "whitelisted")?;
+    let registered_pool = parse_bool_command_return(effects, 2, 0, "registered_pool")?;
+    let vault_base = parse_u64_command_return(effects, 3, 0, "vault_base")?;
+    let vault_quote = parse_u64_command_return(effects, 3, 1, "vault_quote")?;
+    let vault_deep = parse_u64_command_return(effects, 3, 2, "vault_deep")?;
+
+    let bid_prices = parse_vec_u64_command_return(effects, 4, 0, "bid_prices")?;
+    let bid_quantities = parse_vec_u64_command_return(effects, 4, 1, "bid_quantities")?;
+    let ask_prices = parse_vec_u64_command_return(effects, 4, 2, "ask_prices")?;
+    let ask_quantities = parse_vec_u64_command_return(effects, 4, 3, "ask_quantities")?;
+    let iter_bids = fetch_iter_orders(state, pool_id, true, 10, None)?;
+    let iter_asks = fetch_iter_orders(state, pool_id, false, 10, None)?;
+    let first_order = |page: &OrderPage| {
+        page.orders.first().map(|order| {
+            (
+                order.order_id.clone(),
+                order.price,
+                order.quantity,
+                order.filled_quantity,
+                order_status_label(order.status),
+            )
+        })
+    };
+
+    tracing::info!(
+        "Router: debug snapshot {} [{}] whitelisted={}, registered_pool={}, tick_size={}, lot_size={}, min_size={}, vault(base={}, quote={}, deep={}), l2_bid_levels={}, l2_ask_levels={}, l2_best_bid={:?}/{:?}, l2_best_ask={:?}/{:?}, iter_bid_count={}, iter_ask_count={}, iter_first_bid={:?}, iter_first_ask={:?}, iter_has_next_bid={}, iter_has_next_ask={}",
+        pool_id.display_name(),
+        context,
+        whitelisted,
+        registered_pool,
+        tick_size,
+        lot_size,
+        min_size,
+        vault_base,
+        vault_quote,
+        vault_deep,
+        bid_prices.len(),
+        ask_prices.len(),
+        bid_prices.first(),
+        bid_quantities.first(),
+        ask_prices.first(),
+        ask_quantities.first(),
+        iter_bids.orders.len(),
+        iter_asks.orders.len(),
+        first_order(&iter_bids),
+        first_order(&iter_asks),
+        iter_bids.has_next_page,
+        iter_asks.has_next_page
+    );
+
+    Ok(())
+}
+
+fn log_debug_order_lookup(
+    state: &mut RouterEnvState,
+    pool_id: PoolId,
+    context: &str,
+    order_id: u128,
+) -> Result<()> {
+    let deepbook_addr = AccountAddress::from_hex_literal(DEEPBOOK_PACKAGE)?;
+    let debug_tag = TypeTag::from_str(&pool_types(state, pool_id).0)?;
+    let usdc_tag = TypeTag::from_str(USDC_TYPE)?;
+
+    let inputs = vec![
+        InputValue::Object(pool_shared_input(state, pool_id, false)?),
+        InputValue::Pure(bcs::to_bytes(&order_id)?),
+    ];
+    let commands = vec![
+        Command::MoveCall {
+            package: deepbook_addr,
+            module: Identifier::new("pool")?,
+            function: Identifier::new("get_order")?,
+            type_args: vec![debug_tag, usdc_tag],
+            args: vec![Argument::Input(0), Argument::Input(1)],
+        },
+        Command::MoveCall {
+            package: deepbook_addr,
+            module: Identifier::new("order")?,
+            function: Identifier::new("price")?,
+            type_args: vec![],
+            args: vec![Argument::NestedResult(0, 0)],
+        },
+        Command::MoveCall {
+            package: deepbook_addr,
+            module: Identifier::new("order")?,
+            function: Identifier::new("quantity")?,
+            type_args: vec![],
+            args: vec![Argument::NestedResult(0, 0)],
+        },
+        Command::MoveCall {
+            package: deepbook_addr,
+            module: Identifier::new("order")?,
+            function: Identifier::new("filled_quantity")?,
+            type_args: vec![],
+            args: vec![Argument::NestedResult(0, 0)],
+        },
+        Command::MoveCall {
+            package: deepbook_addr,
+            module: Identifier::new("order")?,
+            function: Identifier::new("status")?,
+            type_args: vec![],
+            args: vec![Argument::NestedResult(0, 0)],
+        },
+        Command::MoveCall {
+            package: deepbook_addr,
+            module: Identifier::new("order")?,
+            function: Identifier::new("expire_timestamp")?,
+            type_args: vec![],
+            args: vec![Argument::NestedResult(0, 0)],
+        },
+    ];
+
+    let result = state.env.execute_ptb(inputs, commands);
+    if !result.success {
+        if let Some(ctx) = result.error_context.as_ref() {
+            tracing::warn!(
+                "Router: debug get_order error_context [{}]: {:?}",
+                context,
+                ctx
+            );
+        }
+        if let Some(snapshot) = result.state_at_failure.as_ref() {
+            tracing::warn!(
+                "Router: debug get_order state_at_failure [{}]: dynamic_fields_accessed={:?}",
+                context,
+                snapshot.dynamic_fields_accessed
+            );
+        }
+        return Err(anyhow!(
+            "debug get_order lookup failed [{}] for order_id {}: {}",
+            context,
+            order_id,
+            result
+                .raw_error
+                .unwrap_or_else(|| "Unknown error".to_string())
+        ));
+    }
+
+    let effects = result
+        .effects
+        .as_ref()
+        .ok_or_else(|| anyhow!("Missing PTB effects for debug get_order lookup"))?;
+    let price = parse_u64_command_return(effects, 1, 0, "order.price")?;
+    let quantity = parse_u64_command_return(effects, 2, 0, "order.quantity")?;
+    let filled_quantity = parse_u64_command_return(effects, 3, 0, "order.filled_quantity")?;
+    let status = parse_u8_command_return(effects, 4, 0, "order.status")?;
+    let expire_timestamp = parse_u64_command_return(effects, 5, 0, "order.expire_timestamp")?;
+    tracing::info!(
+        "Router: debug get_order [{}] order_id={} price={} qty={} filled={} status={} ({}) expire={}",
+        context,
+        order_id,
+        price,
+        quantity,
+        filled_quantity,
+        status,
+        order_status_label(status),
+        expire_timestamp
+    );
+
+    Ok(())
+}
+
+/// The environment's first debug pool (`PoolId::DebugUsdc`), created with
+/// the default config if no debug pool exists yet
+pub(super) fn ensure_debug_pool(state: &mut RouterEnvState) -> Result<DebugPoolInfo> {
+    if let Some(existing) = state
+        .debug_pools
+        .values()
+        .find(|info| info.pool_id == PoolId::DebugUsdc)
+    {
+        return Ok(existing.clone());
+    }
+
+    ensure_debug_pool_with_config(state, DebugPoolCreateConfig::default())
+}
+
+pub(super) fn ensure_debug_pool_with_config(
+    state: &mut RouterEnvState,
+    mut config: DebugPoolCreateConfig,
+) -> Result<DebugPoolInfo> {
+    config.token_symbol = config.token_symbol.trim().to_uppercase();
+    config.token_name = config.token_name.trim().to_string();
+    config.token_description = config.token_description.trim().to_string();
+    config.token_icon_url = config.token_icon_url.trim().to_string();
+    config.token_decimals = 9;
+
+    if config.token_symbol.is_empty() {
+        return Err(anyhow!("token_symbol is required"));
+    }
+    if config.token_symbol.len() > 12 {
+        return Err(anyhow!("token_symbol must be <= 12 chars"));
+    }
+    if config.token_name.is_empty() {
+        config.token_name = config.token_symbol.clone();
+    }
+    if config.token_name.len() > 64 {
+        return Err(anyhow!("token_name must be <= 64 chars"));
+    }
+    if config.token_description.len() > 256 {
+        return Err(anyhow!("token_description must be <= 256 chars"));
+    }
+    validate_debug_pool_config(&config)?;
+    if let Some(existing) = state.debug_pools.get(&config.token_symbol) {
+        if existing.config != config {
+            return Err(anyhow!(
+                "debug pool already exists with token_symbol={} and different config; reset the router to apply new debug pool config",
+                existing.token_symbol
+            ));
+        }
+        return Ok(existing.clone());
+    }
+
+    let pool_id = if state
+        .debug_pools
+        .values()
+        .any(|info| info.pool_id == PoolId::DebugUsdc)
+    {
+        PoolId::register_debug(&config.token_symbol).map_err(|e| anyhow!(e))?
+    } else {
+        PoolId::DebugUsdc
+    };
+
+    tracing::info!(
+        "Router: creating debug pool {}/USDC in local VM...",
+        config.token_symbol
+    );
+    create_debug_pool(state, pool_id, &config)?;
+    let entry = state
+        .pool_cache
+        .get(&pool_id)
+        .ok_or_else(|| anyhow!("Debug pool missing from router cache after creation"))?;
+    let info = DebugPoolInfo {
+        pool_id,
+        pool_object_id: entry.pool_addr.to_hex_literal(),
+        token_symbol: config.token_symbol.clone(),
+        token_type: config.token_type(),
+        config: config.clone(),
+    };
+    // Registered before seeding, so the seed orders can mint its reserve
+    state
+        .debug_pools
+        .insert(config.token_symbol.clone(), info.clone());
+    seed_debug_pool_orderbook(state, pool_id, &config)?;
+
+    tracing::info!(
+        "Router: debug pool {} ready at {} (type {})",
+        pool_id.as_str(),
+        info.pool_object_id,
+        info.token_type
+    );
+    Ok(info)
+}
+
+/// Check a debug pool config against the constraints DeepBook enforces when
+/// creating the pool and placing the seed orders.
+///
+/// Catching these up front gives a specific message instead of a Move abort
+/// from the create PTB.
+pub(super) fn validate_debug_pool_config(config: &DebugPoolCreateConfig) -> Result<()> {
+    let invalid = |msg: String| anyhow!("{}: {}", INVALID_DEBUG_POOL_CONFIG, msg);
+
+    for (name, value) in [
+        ("tick_size", config.tick_size),
+        ("lot_size", config.lot_size),
+        ("min_size", config.min_size),
+    ] {
+        if value == 0 || !is_power_of_ten(value) {
+            return Err(invalid(format!(
+                "{} must be a power of ten, got {}",
+                name, value
+            )));
+        }
+    }
+    if config.lot_size < DEEPBOOK_MIN_LOT_SIZE {
+        return Err(invalid(format!(
+            "lot_size must be at least {}, got {}",
+            DEEPBOOK_MIN_LOT_SIZE, config.lot_size
+        )));
+    }
+    if config.min_size % config.lot_size != 0 {
+        return Err(invalid(format!(
+            "lot_size ({}) must divide min_size ({})",
+            config.lot_size, config.min_size
+        )));
+    }
+
+    for (name, price) in [
+        ("bid_price", config.bid_price),
+        ("ask_price", config.ask_price),
+    ] {
+        if price == 0 || price % config.tick_size != 0 {
+            return Err(invalid(format!(
+                "{} ({}) must be a positive multiple of tick_size ({})",
+                name, price, config.tick_size
+            )));
+        }
+    }
+    if config.bid_price >= config.ask_price {
+        return Err(invalid(format!(
+            "bid_price ({}) must be below ask_price ({}) so the seed orders do not cross",
+            config.bid_price, config.ask_price
+        )));
+    }
+
+    for (name, quantity) in [
+        ("bid_quantity", config.bid_quantity),
+        ("ask_quantity", config.ask_quantity),
+    ] {
+        if quantity < config.min_size || quantity % config.lot_size != 0 {
+            return Err(invalid(format!(
+                "{} ({}) must be a multiple of lot_size ({}) and at least min_size ({})",
+                name, quantity, config.lot_size, config.min_size
+            )));
+        }
+    }
+
+    for (name, levels) in [
+        ("bid_levels", config.bid_levels),
+        ("ask_levels", config.ask_levels),
+    ] {
+        if levels == 0 || levels > MAX_DEBUG_SEED_LEVELS {
+            return Err(invalid(format!(
+                "{} must be between 1 and {}, got {}",
+                name, MAX_DEBUG_SEED_LEVELS, levels
+            )));
+        }
+    }
+    if config.price_step == 0 {
+        return Err(invalid("price_step must be at least 1 tick".into()));
+    }
+    if config.maker_senders.len() > MAX_DEBUG_SEED_LEVELS as usize {
+        return Err(invalid(format!(
+            "maker_senders may list at most {} addresses, got {}",
+            MAX_DEBUG_SEED_LEVELS,
+            config.maker_senders.len()
+        )));
+    }
+    config.seed_makers().map_err(|e| invalid(e.to_string()))?;
+    let ladder_span = |levels: u32| {
+        config
+            .price_step
+            .checked_mul(config.tick_size)
+            .and_then(|step| step.checked_mul(u64::from(levels - 1)))
+    };
+    match ladder_span(config.bid_levels) {
+        Some(span) if span < config.bid_price => {}
+        _ => {
+            return Err(invalid(format!(
+                "bid ladder of {} levels, {} ticks apart, falls to zero below bid_price ({})",
+                config.bid_levels, config.price_step, config.bid_price
+            )))
+        }
+    }
+    if ladder_span(config.ask_levels)
+        .and_then(|span| config.ask_price.checked_add(span))
+        .is_none()
+    {
+        return Err(invalid(format!(
+            "ask ladder of {} levels, {} ticks apart, overflows above ask_price ({})",
+            config.ask_levels, config.price_step, config.ask_price
+        )));
+    }
+
+    // The top bid level locks the most quote; every ask level locks the same base.
+    let (_, bid_quote) = limit_order_deposits(true, config.bid_price, config.bid_quantity);
+    let quote_per_bid = config.quote_liquidity / u64::from(config.bid_levels);
+    if bid_quote > quote_per_bid {
+        return Err(invalid(format!(
+            "quote_liquidity ({}) split across {} bid levels does not cover the seed bid ({} USDC units)",
+            config.quote_liquidity, config.bid_levels, bid_quote
+        )));
+    }
+    let base_per_ask = config.base_liquidity / u64::from(config.ask_levels);
+    if config.ask_quantity > base_per_ask {
+        return Err(invalid(format!(
+            "base_liquidity ({}) split across {} ask levels does not cover the seed ask ({})",
+            config.base_liquidity, config.ask_levels, config.ask_quantity
+        )));
+    }
+    Ok(())
+}
+
+fn is_power_of_ten(mut value: u64) -> bool {
+    while value >= 10 && value % 10 == 0 {
+        value /= 10;
+    }
+    value == 1
+}
+
+fn create_debug_pool(
+    state: &mut RouterEnvState,
+    pool_id: PoolId,
+    config: &DebugPoolCreateConfig,
+) -> Result<()> {
+    let deepbook_addr = AccountAddress::from_hex_literal(DEEPBOOK_PACKAGE)?;
+    let debug_type = config.token_type();
+    let debug_tag = TypeTag::from_str(&debug_type)?;
+    let usdc_tag = TypeTag::from_str(USDC_TYPE)?;
+    let pool_type = build_pool_type_tag(&debug_type, USDC_TYPE)?;
+    let existing_pool_ids: HashSet<AccountAddress> = state
+        .env
+        .list_objects()
+        .into_iter()
+        .filter(|obj| obj.type_tag == pool_type)
+        .map(|obj| obj.id)
+        .collect();
+    ensure_debug_admin_cap(state)?;
+
+    let inputs = vec![
+        // Input 0: DeepBook Registry (shared mutable)
+        InputValue::Object(registry_shared_input(state, true)?),
+        // Input 1: tick_size
+        InputValue::Pure(bcs::to_bytes(&config.tick_size)?),
+        // Input 2: lot_size
+        InputValue::Pure(bcs::to_bytes(&config.lot_size)?),
+        // Input 3: min_size
+        InputValue::Pure(bcs::to_bytes(&config.min_size)?),
+        // Input 4: whitelisted_pool
+        InputValue::Pure(bcs::to_bytes(&config.whitelisted_pool)?),
+        // Input 5: stable_pool
+        InputValue::Pure(bcs::to_bytes(&false)?),
+        // Input 6: admin cap
+        InputValue::Object(admin_cap_input(state)?),
+    ];
+
+    let commands = vec![Command::MoveCall {
+        package: deepbook_addr,
+        module: Identifier::new("pool")?,
+        function: Identifier::new("create_pool_admin")?,
+        type_args: vec![debug_tag, usdc_tag],
+        args: vec![
+            Argument::Input(0),
+            Argument::Input(1),
+            Argument::Input(2),
+            Argument::Input(3),
+            Argument::Input(4),
+            Argument::Input(5),
+            Argument::Input(6),
+        ],
+    }];
+
+    let result = state.env.execute_ptb(inputs, commands);
+    if !result.success {
+        return Err(anyhow!(
+            "debug pool creation failed: {}",
+            result
+                .raw_error
+                .unwrap_or_else(|| "Unknown error".to_string())
+        ));
+    }
+    let effects = result
+        .effects
+        .as_ref()
+        .ok_or_else(|| anyhow!("Missing effects from debug pool creation"))?;
+    sync_dynamic_field_entries(state, effects);
+    {
+        tracing::info!(
+            "Router: debug pool create effects -> created={}, dynamic_fields={}",
+            effects.created.len(),
+            effects.dynamic_field_entries.len()
+        );
+        for created_id in &effects.created {
+            if let Some(obj) = state.env.get_object(created_id) {
+                tracing::info!(
+                    "Router: created object {} type {} (shared={})",
+                    created_id,
+                    obj.type_tag,
+                    obj.is_shared
+                );
+            } else {
+                tracing::warn!(
+                    "Router: created object {} not present in env after PTB",
+                    created_id
+                );
+            }
+        }
+    }
+
+    let pool_addr = effects
+        .return_values
+        .first()
+        .and_then(|values| values.first())
+        .and_then(|bytes| {
+            bcs::from_bytes::<AccountAddress>(bytes).ok().or_else(|| {
+                if bytes.len() >= AccountAddress::LENGTH {
+                    let mut raw = [0u8; AccountAddress::LENGTH];
+                    raw.copy_from_slice(&bytes[..AccountAddress::LENGTH]);
+                    Some(AccountAddress::new(raw))
+                } else {
+                    None
+                }
+            })
+        })
+        .ok_or_else(|| anyhow!("Failed to decode debug pool id from PTB return values"))?;
+
+    if let Some(wrapper_bytes) = effects.created_object_bytes.get(&pool_addr) {
+        if state.env.get_object(&pool_addr).is_some() {
+            state
+                .env
+                .set_object_bytes(pool_addr, wrapper_bytes.clone())
+                .map_err(|e| {
+                    anyhow!(
+                        "failed updating created DBG/USDC pool wrapper {} bytes: {}",
+                        pool_addr,
+                        e
+                    )
+                })?;
+        } else {
+            state.env.load_object_from_data(
+                &pool_addr.to_hex_literal(),
+                wrapper_bytes.clone(),
+                Some(&format!(
+                    "{}::pool::Pool<{},{}>",
+                    DEEPBOOK_PACKAGE, debug_type, USDC_TYPE
+                )),
+                true,
+                false,
+                0,
+            )?;
+            tracing::info!(
+                "Router: loaded DBG/USDC pool wrapper {} directly from create effects",
+                pool_addr
+            );
+        }
+    }
+
+    // Some sandbox versions fail to materialize the shared pool wrapper object even
+    // when the create PTB succeeds. Recover by synthesizing the wrapper from the
+    // returned pool ID and the created PoolInner dynamic-field parent.
+    if state.env.get_object(&pool_addr).is_none() {
+        let pool_inner_parent = effects.dynamic_field_entries.iter().find_map(
+            |((parent_id, _child_id), (type_tag, _bytes))| {
+                let tag = type_tag.to_string();
+                if tag.contains("::pool::PoolInner<")
+                    && tag.contains(&debug_type)
+                    && tag.contains(USDC_TYPE)
+                {
+                    Some(*parent_id)
+                } else {
+                    None
+                }
+            },
+        );
+
+        if let Some(inner_parent) = pool_inner_parent {
+            let mut wrapper_bytes = Vec::with_capacity(AccountAddress::LENGTH * 2 + 8);
+            // Pool.id: UID
+            wrapper_bytes.extend_from_slice(pool_addr.as_ref());
+            // Pool.inner.id: UID
+            wrapper_bytes.extend_from_slice(inner_parent.as_ref());
+            // Pool.inner.version
+            wrapper_bytes.extend_from_slice(&1_u64.to_le_bytes());
+
+            state.env.load_object_from_data(
+                &pool_addr.to_hex_literal(),
+                wrapper_bytes,
+                Some(&format!(
+                    "{}::pool::Pool<{},{}>",
+                    DEEPBOOK_PACKAGE, debug_type, USDC_TYPE
+                )),
+                true,
+                false,
+                1,
+            )?;
+            tracing::info!(
+                "Router: synthesized missing DBG/USDC pool wrapper at {} (inner={})",
+                pool_addr,
+                inner_parent
+            );
+        }
+    }
+
+    if state.env.get_object(&pool_addr).is_none() {
+        return Err(anyhow!(
+            "Could not locate DBG/USDC pool object after creation ({})",
+            pool_addr
+        ));
+    }
+
+    if existing_pool_ids.contains(&pool_addr) {
+        tracing::info!(
+            "Router: reusing existing DBG/USDC pool object {}",
+            pool_addr
+        );
+    }
+
+    state.pool_cache.insert(
+        pool_id,
+        PoolCacheEntry {
+            pool_addr,
+            pool_type,
+            base_type: debug_type.clone(),
+            quote_type: USDC_TYPE.to_string(),
+            accounts_table: None,
+            history_table: None,
+            history_epoch: None,
+            whitelisted: None,
+            params: None,
+        },
+    );
+
+    Ok(())
+}
+
+pub(super) fn prime_debug_pool_deep_price(
+    state: &mut RouterEnvState,
+    pool_id: PoolId,
+) -> Result<DeepPriceBootstrapReport> {
+    let deepbook_addr = AccountAddress::from_hex_literal(DEEPBOOK_PACKAGE)?;
+    let debug_tag = TypeTag::from_str(&pool_types(state, pool_id).0)?;
+    let usdc_tag = TypeTag::from_str(USDC_TYPE)?;
+    let mut last_err: Option<anyhow::Error> = None;
+    let mut failed_references: Vec<String> = Vec::new();
+
+    // Try multiple reference pools; different DeepBook versions may accept
+    // different base assets for bootstrapping order deep price.
+    for reference_pool in [PoolId::DeepUsdc, PoolId::SuiUsdc, PoolId::WalUsdc] {
+        let (ref_base_type, ref_quote_type) = pool_types(state, reference_pool);
+        let ref_base_tag = TypeTag::from_str(&ref_base_type)?;
+        let ref_quote_tag = TypeTag::from_str(&ref_quote_type)?;
+        let mut points_added = 0usize;
+        for _attempt in 0..3 {
+            let point_ms = state.clock_now_ms();
+            if let Some(err) = deep_price_spacing_error(
+                state.last_deep_price_point_ms,
+                point_ms,
+                state.clock_step_ms,
+            ) {
+                tracing::warn!("Router: {}", err);
+                if points_added == 0 {
+                    last_err = Some(err);
+                }
+                break;
+            }
+            let add_inputs = vec![
+                // Input 0: target debug pool
+                InputValue::Object(pool_shared_input(state, pool_id, true)?),
+                // Input 1: reference pool, as loaded
+                InputValue::Object(pool_shared_input(state, reference_pool, false)?),
+                // Input 2: clock
+                InputValue::Object(state.next_clock_input()?),
+            ];
+
+            let add_commands = vec![Command::MoveCall {
+                package: deepbook_addr,
+                module: Identifier::new("pool")?,
+                function: Identifier::new("add_deep_price_point")?,
+                type_args: vec![
+                    debug_tag.clone(),
+                    usdc_tag.clone(),
+                    ref_base_tag.clone(),
+                    ref_quote_tag.clone(),
+                ],
+                args: vec![Argument::Input(0), Argument::Input(1), Argument::Input(2)],
+            }];
+
+            let add_result = state.env.execute_ptb(add_inputs, add_commands);
+            if !add_result.success {
+                let raw_error = add_result
+                    .raw_error
+                    .unwrap_or_else(|| "Unknown error".to_string());
+                let spacing_hint =
+                    if raw_error.contains("deep_price") && raw_error.contains("ABORTED") {
+                        format!(
+                            " (rejected by deep_price; points need more than {}ms spacing, \
+                             ROUTER_CLOCK_STEP_MS={})",
+                            DEEP_PRICE_MIN_SPACING_MS, state.clock_step_ms
+                        )
+                    } else {
+                        String::new()
+                    };
+                let err = anyhow!(
+                    "add_deep_price_point via {} failed{}: {}",
+                    reference_pool.display_name(),
+                    spacing_hint,
+                    raw_error
+                );
+                tracing::warn!("Router: {}", err);
+                if points_added == 0 {
+                    last_err = Some(err);
+                }
+                break;
+            }
+            if let Some(effects) = add_result.effects.as_ref() {
+                sync_dynamic_field_entries(state, effects);
+            }
+            state.last_deep_price_point_ms = Some(point_ms);
+            points_added += 1;
+        }
+        if points_added == 0 {
+            if let Some(err) = last_err.as_ref() {
+                failed_references.push(err.to_string());
+            }
+            continue;
+        }
+
+        // Read in a separate PTB so shared-object writes are definitely visible.
+        let read_inputs = vec![InputValue::Object(pool_shared_input(
+            state, pool_id, false,
+        )?)];
+        let read_commands = vec![
+            // 0) Read current order deep price snapshot from debug pool.
+            Command::MoveCall {
+                package: deepbook_addr,
+                module: Identifier::new("pool")?,
+                function: Identifier::new("get_order_deep_price")?,
+                type_args: vec![debug_tag.clone(), usdc_tag.clone()],
+                args: vec![Argument::Input(0)],
+            },
+            // 1) Extract `deep_per_asset` from OrderDeepPrice.
+            Command::MoveCall {
+                package: deepbook_addr,
+                module: Identifier::new("deep_price")?,
+                function: Identifier::new("deep_per_asset")?,
+                type_args: vec![],
+                args: vec![Argument::NestedResult(0, 0)],
+            },
+        ];
+
+        let result = state.env.execute_ptb(read_inputs, read_commands);
+        if !result.success {
+            let err = anyhow!(
+                "debug pool deep_price bootstrap read failed after {}: {}",
+                reference_pool.display_name(),
+                result
+                    .raw_error
+                    .unwrap_or_else(|| "Unknown error".to_string())
+            );
+            tracing::warn!("Router: {}", err);
+            failed_references.push(err.to_string());
+            last_err = Some(err);
+            continue;
+        }
+        if let Some(read_effects) = result.effects.as_ref() {
+            sync_dynamic_field_entries(state, read_effects);
+        }
+
+        let effects = result
+            .effects
+            .as_ref()
+            .ok_or_else(|| anyhow!("Missing PTB effects for debug deep_price bootstrap"))?;
+        let deep_per_asset = parse_u64_command_return(effects, 1, 0, "deep_per_asset")?;
+        if deep_per_asset > 0 {
+            tracing::info!(
+                "Router: deep_price bootstrap succeeded via {} (points={}, deep_per_asset={})",
+                reference_pool.display_name(),
+                points_added,
+                deep_per_asset
+            );
+            return Ok(DeepPriceBootstrapReport {
+                reference_pool: reference_pool.display_name().to_string(),
+                points_added,
+                deep_per_asset,
+                failed_references,
+            });
+        }
+
+        let err = anyhow!(
+            "deep_price bootstrap via {} returned zero deep_per_asset",
+            reference_pool.display_name()
+        );
+        tracing::warn!("Router: {}", err);
+        failed_references.push(err.to_string());
+        last_err = Some(err);
+    }
+
+    Err(last_err.unwrap_or_else(|| anyhow!("deep_price bootstrap failed for all reference pools")))
+}
+
+fn seed_debug_pool_orderbook(
+    state: &mut RouterEnvState,
+    pool_id: PoolId,
+    config: &DebugPoolCreateConfig,
+) -> Result<()> {
+    let deepbook_addr = AccountAddress::from_hex_literal(DEEPBOOK_PACKAGE)?;
+    let sui_framework_addr = AccountAddress::from_hex_literal(SUI_FRAMEWORK_PACKAGE)?;
+
+    let debug_type = config.token_type();
+    let debug_tag = TypeTag::from_str(&debug_type)?;
+    let usdc_tag = TypeTag::from_str(USDC_TYPE)?;
+    let deep_tag = TypeTag::from_str(DEEP_TYPE)?;
+    let bm_tag = TypeTag::from_str(&format!(
+        "{}::balance_manager::BalanceManager",
+        DEEPBOOK_PACKAGE
+    ))?;
+    if config.whitelisted_pool || !config.pay_with_deep {
+        tracing::info!(
+            "Router: skipping deep_price bootstrap (whitelisted={}, pay_with_deep={})",
+            config.whitelisted_pool,
+            config.pay_with_deep
+        );
+    } else {
+        let report = prime_debug_pool_deep_price(state, pool_id)?;
+        tracing::info!(
+            "Router: primed debug deep_price using {} reference (deep_per_asset={})",
+            report.reference_pool,
+            report.deep_per_asset
+        );
+    }
+
+    let original_sender = state.env.sender();
+    let makers = config.seed_makers()?;
+
+    let seed_result = (|| -> Result<()> {
+        // Runs as the order's maker, which keeps the balance manager the PTB
+        // creates and funds.
+        let place_seed_order = |state: &mut RouterEnvState,
+                                client_order_id: u64,
+                                order: &SeedOrder|
+         -> Result<()> {
+            let SeedOrder {
+                is_bid,
+                price,
+                quantity,
+                base_deposit,
+                quote_deposit,
+            } = *order;
+            let recipient = state.env.sender().to_vec();
+            let expiry_ms = state
+                .clock_now_ms()
+                .saturating_add(DEBUG_ORDER_EXPIRY_TTL_MS);
+
+            let inputs = vec![
+                // 0) debug pool (shared mutable)
+                InputValue::Object(pool_shared_input(state, pool_id, true)?),
+                // 1) DBG reserve coin
+                InputValue::Object(reserve_coin_input(state, &debug_type)?),
+                // 2) USDC reserve coin
+                InputValue::Object(reserve_coin_input(state, USDC_TYPE)?),
+                // 3) DEEP reserve coin
+                InputValue::Object(reserve_coin_input(state, DEEP_TYPE)?),
+                // 4) client_order_id
+                InputValue::Pure(bcs::to_bytes(&client_order_id)?),
+                // 5) order_type = no_restriction
+                InputValue::Pure(bcs::to_bytes(&0_u8)?),
+                // 6) self_matching_option = allowed
+                InputValue::Pure(bcs::to_bytes(&0_u8)?),
+                // 7) price
+                InputValue::Pure(bcs::to_bytes(&price)?),
+                // 8) quantity
+                InputValue::Pure(bcs::to_bytes(&quantity)?),
+                // 9) is_bid
+                InputValue::Pure(bcs::to_bytes(&is_bid)?),
+                // 10) pay_with_deep
+                InputValue::Pure(bcs::to_bytes(&config.pay_with_deep)?),
+                // 11) expiry
+                InputValue::Pure(bcs::to_bytes(&expiry_ms)?),
+                // 12) clock
+                InputValue::Object(state.next_clock_input()?),
+                // 13) recipient to keep balance manager alive
+                InputValue::Pure(recipient),
+                // 14) DBG liquidity amount
+                InputValue::Pure(bcs::to_bytes(&base_deposit)?),
+                // 15) USDC liquidity amount
+                InputValue::Pure(bcs::to_bytes(&quote_deposit)?),
+                // 16) DEEP fee amount
+                InputValue::Pure(bcs::to_bytes(&config.deep_fee_budget)?),
+            ];
+
+            let commands = vec![
+                // 0) split DBG liquidity from reserve
+                Command::MoveCall {
+                    package: sui_framework_addr,
+                    module: Identifier::new("coin")?,
+                    function: Identifier::new("split")?,
+                    type_args: vec![debug_tag.clone()],
+                    args: vec![Argument::Input(1), Argument::Input(14)],
+                },
+                // 1) split USDC liquidity from reserve
+                Command::MoveCall {
+                    package: sui_framework_addr,
+                    module: Identifier::new("coin")?,
+                    function: Identifier::new("split")?,
+                    type_args: vec![usdc_tag.clone()],
+                    args: vec![Argument::Input(2), Argument::Input(15)],
+                },
+                // 2) split DEEP fee budget from reserve
+                Command::MoveCall {
+                    package: sui_framework_addr,
+                    module: Identifier::new("coin")?,
+                    function: Identifier::new("split")?,
+                    type_args: vec![deep_tag.clone()],
+                    args: vec![Argument::Input(3), Argument::Input(16)],
+                },
+                // 3) create balance manager
+                Command::MoveCall {
+                    package: deepbook_addr,
+                    module: Identifier::new("balance_manager")?,
+                    function: Identifier::new("new")?,
+                    type_args: vec![],
+                    args: vec![],
+                },
+                // 4) generate owner trade proof
+                Command::MoveCall {
+                    package: deepbook_addr,
+                    module: Identifier::new("balance_manager")?,
+                    function: Identifier::new("generate_proof_as_owner")?,
+                    type_args: vec![],
+                    args: vec![Argument::NestedResult(3, 0)],
+                },
+                // 5) deposit DBG
+                Command::MoveCall {
+                    package: deepbook_addr,
+                    module: Identifier::new("balance_manager")?,
+                    function: Identifier::new("deposit")?,
+                    type_args: vec![debug_tag.clone()],
+                    args: vec![Argument::NestedResult(3, 0), Argument::Result(0)],
+                },
+                // 6) deposit USDC
+                Command::MoveCall {
+                    package: deepbook_addr,
+                    module: Identifier::new("balance_manager")?,
+                    function: Identifier::new("deposit")?,
+                    type_args: vec![usdc_tag.clone()],
+                    args: vec![Argument::NestedResult(3, 0), Argument::Result(1)],
+                },
+                // 7) deposit DEEP
+                Command::MoveCall {
+                    package: deepbook_addr,
+                    module: Identifier::new("balance_manager")?,
+                    function: Identifier::new("deposit")?,
+                    type_args: vec![deep_tag.clone()],
+                    args: vec![Argument::NestedResult(3, 0), Argument::Result(2)],
+                },
+                // 8) place limit order
+                Command::MoveCall {
+                    package: deepbook_addr,
+                    module: Identifier::new("pool")?,
+                    function: Identifier::new("place_limit_order")?,
+                    type_args: vec![debug_tag.clone(), usdc_tag.clone()],
+                    args: vec![
+                        Argument::Input(0),
+                        Argument::NestedResult(3, 0),
+                        Argument::NestedResult(4, 0),
+                        Argument::Input(4),
+                        Argument::Input(5),
+                        Argument::Input(6),
+                        Argument::Input(7),
+                        Argument::Input(8),
+                        Argument::Input(9),
+                        Argument::Input(10),
+                        Argument::Input(11),
+                        Argument::Input(12),
+                    ],
+                },
+                // 9) read order_info.order_id
+                Command::MoveCall {
+                    package: deepbook_addr,
+                    module: Identifier::new("order_info")?,
+                    function: Identifier::new("order_id")?,
+                    type_args: vec![],
+                    args: vec![Argument::NestedResult(8, 0)],
+                },
+                // 10) read order_info.price
+                Command::MoveCall {
+                    package: deepbook_addr,
+                    module: Identifier::new("order_info")?,
+                    function: Identifier::new("price")?,
+                    type_args: vec![],
+                    args: vec![Argument::NestedResult(8, 0)],
+                },
+                // 11) read order_info.original_quantity
+                Command::MoveCall {
+                    package: deepbook_addr,
+                    module: Identifier::new("order_info")?,
+                    function: Identifier::new("original_quantity")?,
+                    type_args: vec![],
+                    args: vec![Argument::NestedResult(8, 0)],
+                },
+                // 12) read order_info.executed_quantity
+                Command::MoveCall {
+                    package: deepbook_addr,
+                    module: Identifier::new("order_info")?,
+                    function: Identifier::new("executed_quantity")?,
+                    type_args: vec![],
+                    args: vec![Argument::NestedResult(8, 0)],
+                },
+                // 13) read order_info.cumulative_quote_quantity
+                Command::MoveCall {
+                    package: deepbook_addr,
+                    module: Identifier::new("order_info")?,
+                    function: Identifier::new("cumulative_quote_quantity")?,
+                    type_args: vec![],
+                    args: vec![Argument::NestedResult(8, 0)],
+                },
+                // 14) read order_info.status
+                Command::MoveCall {
+                    package: deepbook_addr,
+                    module: Identifier::new("order_info")?,
+                    function: Identifier::new("status")?,
+                    type_args: vec![],
+                    args: vec![Argument::NestedResult(8, 0)],
+                },
+                // 15) read order_info.order_inserted
+                Command::MoveCall {
+                    package: deepbook_addr,
+                    module: Identifier::new("order_info")?,
+                    function: Identifier::new("order_inserted")?,
+                    type_args: vec![],
+                    args: vec![Argument::NestedResult(8, 0)],
+                },
+                // 16) read pool vault balances after order placement.
+                Command::MoveCall {
+                    package: deepbook_addr,
+                    module: Identifier::new("pool")?,
+                    function: Identifier::new("vault_balances")?,
+                    type_args: vec![debug_tag.clone(), usdc_tag.clone()],
+                    args: vec![Argument::Input(0)],
+                },
+                // 17) transfer balance manager out so it persists.
+                Command::MoveCall {
+                    package: sui_framework_addr,
+                    module: Identifier::new("transfer")?,
+                    function: Identifier::new("public_transfer")?,
+                    type_args: vec![bm_tag.clone()],
+                    args: vec![Argument::NestedResult(3, 0), Argument::Input(13)],
+                },
+            ];
+
+            let result = state.env.execute_ptb(inputs, commands);
+            if !result.success {
+                return Err(anyhow!(
+                    "debug pool {} seed order failed: {}",
+                    if is_bid { "bid" } else { "ask" },
+                    result
+                        .raw_error
+                        .unwrap_or_else(|| "Unknown error".to_string())
+                ));
+            }
+            let effects = result.effects.as_ref().ok_or_else(|| {
+                anyhow!(
+                    "Missing PTB effects for debug {} seed",
+                    if is_bid { "bid" } else { "ask" }
+                )
+            })?;
+            let log_detail = state.debug_log_sampler.sample_routine();
+            if log_detail {
+                tracing::info!(
+                    "Router: debug {} seed effects mutated={}, created={}, dynamic_fields={}",
+                    if is_bid { "bid" } else { "ask" },
+                    effects.mutated.len(),
+                    effects.created.len(),
+                    effects.dynamic_field_entries.len()
+                );
+                for id in &effects.mutated {
+                    let type_hint = state
+                        .env
+                        .get_object(id)
+                        .map(|obj| obj.type_tag.to_string())
+                        .unwrap_or_else(|| "<missing>".to_string());
+                    let bytes_len = effects
+                        .mutated_object_bytes
+                        .get(id)
+                        .map(|bytes| bytes.len())
+                        .unwrap_or(0);
+                    tracing::info!(
+                        "Router: debug {} seed mutated id={} type_hint={} bytes={}",
+                        if is_bid { "bid" } else { "ask" },
+                        id,
+                        type_hint,
+                        bytes_len
+                    );
+                }
+                for id in &effects.created {
+                    let type_hint = state
+                        .env
+                        .get_object(id)
+                        .map(|obj| obj.type_tag.to_string())
+                        .unwrap_or_else(|| "<missing>".to_string());
+                    let bytes_len = effects
+                        .created_object_bytes
+                        .get(id)
+                        .map(|bytes| bytes.len())
+                        .unwrap_or(0);
+                    tracing::info!(
+                        "Router: debug {} seed created id={} type_hint={} bytes={}",
+                        if is_bid { "bid" } else { "ask" },
+                        id,
+                        type_hint,
+                        bytes_len
+                    );
+                }
+            }
+            let created_slice_fields: Vec<(
+                AccountAddress,
+                Option<AccountAddress>,
+                Option<AccountAddress>,
+                Option<u64>,
+                bool,
+            )> = effects
+                .object_changes
+                .iter()
+                .filter_map(|change| match change {
+                    sui_sandbox_core::ptb::ObjectChange::Created {
+                        id,
+                        owner,
+                        object_type: Some(type_tag),
+                    } if type_tag.to_string().contains("big_vector::Slice") => {
+                        let parent = parse_parent_from_owner_debug(owner);
+                        let effect_parent = effects.dynamic_field_entries.iter().find_map(
+                            |((parent_id, child_id), _)| (child_id == id).then_some(*parent_id),
+                        );
+                        let key = effects
+                            .created_object_bytes
+                            .get(id)
+                            .and_then(|bytes| parse_dynamic_field_u64_name(bytes));
+                        let present_in_effect_fields = effects
+                            .dynamic_field_entries
+                            .iter()
+                            .any(|((_, child_id), _)| child_id == id);
+                        Some((*id, parent, effect_parent, key, present_in_effect_fields))
+                    }
+                    _ => None,
+                })
+                .collect();
+            if log_detail && !created_slice_fields.is_empty() {
+                tracing::info!(
+                    "Router: debug {} seed created slice fields {:?}",
+                    if is_bid { "bid" } else { "ask" },
+                    created_slice_fields
+                );
+            }
+            let placed_order_id = parse_u128_command_return(effects, 9, 0, "order_info.order_id")?;
+            let order_price = parse_u64_command_return(effects, 10, 0, "order_info.price")?;
+            let original_quantity =
+                parse_u64_command_return(effects, 11, 0, "order_info.original_quantity")?;
+            let executed_quantity =
+                parse_u64_command_return(effects, 12, 0, "order_info.executed_quantity")?;
+            let remaining_quantity = original_quantity.saturating_sub(executed_quantity);
+            let cumulative_quote_quantity =
+                parse_u64_command_return(effects, 13, 0, "order_info.cumulative_quote_quantity")?;
+            let order_status = parse_u8_command_return(effects, 14, 0, "order_info.status")?;
+            let order_inserted = parse_bool_command_return(effects, 15, 0, "order_info.inserted")?;
+            let vault_base_after = parse_u64_command_return(effects, 16, 0, "vault_base_after")?;
+            let vault_quote_after = parse_u64_command_return(effects, 16, 1, "vault_quote_after")?;
+            let vault_deep_after = parse_u64_command_return(effects, 16, 2, "vault_deep_after")?;
+            if log_detail {
+                tracing::info!(
+                    "Router: debug {} seed order_info order_id={}, price={}, original_qty={}, executed_qty={}, cumulative_quote_qty={}, status={} ({}), inserted={}, vault_after(base={}, quote={}, deep={})",
+                    if is_bid { "bid" } else { "ask" },
+                    placed_order_id,
+                    order_price,
+                    original_quantity,
+                    executed_quantity,
+                    cumulative_quote_quantity,
+                    order_status,
+                    order_status_label(order_status),
+                    order_inserted,
+                    vault_base_after,
+                    vault_quote_after,
+                    vault_deep_after
+                );
+                if let Some(pool_entry) = state.pool_cache.get(&pool_id) {
+                    if let Some(pool_obj) = state.env.get_object(&pool_entry.pool_addr) {
+                        if pool_obj.bcs_bytes.len() >= 72 {
+                            let mut inner_parent_bytes = [0u8; AccountAddress::LENGTH];
+                            inner_parent_bytes.copy_from_slice(&pool_obj.bcs_bytes[32..64]);
+                            let inner_parent = AccountAddress::new(inner_parent_bytes);
+                            let mut inner_version_bytes = [0u8; 8];
+                            inner_version_bytes.copy_from_slice(&pool_obj.bcs_bytes[64..72]);
+                            let inner_version = u64::from_le_bytes(inner_version_bytes);
+                            let matching_inner_fields: Vec<(AccountAddress, String, Option<u64>)> =
+                                effects
+                                    .dynamic_field_entries
+                                    .iter()
+                                    .filter(|((parent_id, _), (type_tag, _))| {
+                                        *parent_id == inner_parent
+                                            && type_tag.to_string().contains("::pool::PoolInner<")
+                                    })
+                                    .map(|((_, child_id), (type_tag, bytes))| {
+                                        (
+                                            *child_id,
+                                            type_tag.to_string(),
+                                            parse_dynamic_field_u64_name(bytes),
+                                        )
+                                    })
+                                    .collect();
+                            if !matching_inner_fields.is_empty() {
+                                tracing::info!(
+                                    "Router: debug {} seed inner parent {} wrapper_version={} fields_in_effects={:?}",
+                                    if is_bid { "bid" } else { "ask" },
+                                    inner_parent,
+                                    inner_version,
+                                    matching_inner_fields
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+            sync_dynamic_field_entries(state, effects);
+            for (_child_id, _owner_parent, effect_parent, key, _present_in_effect_fields) in
+                &created_slice_fields
+            {
+                let (Some(parent), Some(slice_key)) = (*effect_parent, *key) else {
+                    continue;
+                };
+                if let Err(e) = patch_pool_big_vector_header_from_created_slice(
+                    state, pool_id, parent, slice_key,
+                ) {
+                    tracing::warn!(
+                        "Router: failed patching debug BigVector header from slice parent={} key={}: {}",
+                        parent,
+                        slice_key,
+                        e
+                    );
+                }
+            }
+            if order_inserted && remaining_quantity > 0 {
+                let (add_base, add_quote) = if is_bid {
+                    (0_u64, scaled_mul_floor(remaining_quantity, order_price))
+                } else {
+                    (remaining_quantity, 0_u64)
+                };
+                if let Err(e) =
+                    patch_pool_vault_tail_for_seed(state, pool_id, add_base, add_quote, 0)
+                {
+                    tracing::warn!(
+                        "Router: failed patching debug vault tail (is_bid={}, add_base={}, add_quote={}): {}",
+                        is_bid,
+                        add_base,
+                        add_quote,
+                        e
+                    );
+                }
+            }
+            if log_detail && !created_slice_fields.is_empty() {
+                let mut registered = Vec::new();
+                for (child_id, owner_parent, effect_parent, key, _present_in_effect_fields) in
+                    &created_slice_fields
+                {
+                    let exists_via_owner = owner_parent
+                        .and_then(|parent_id| state.env.get_dynamic_field(parent_id, *child_id))
+                        .is_some();
+                    let exists_via_effect = effect_parent
+                        .and_then(|parent_id| state.env.get_dynamic_field(parent_id, *child_id))
+                        .is_some();
+                    registered.push((
+                        *child_id,
+                        *owner_parent,
+                        *effect_parent,
+                        *key,
+                        exists_via_owner,
+                        exists_via_effect,
+                    ));
+                }
+                tracing::info!(
+                    "Router: debug {} seed slice registration after sync {:?}",
+                    if is_bid { "bid" } else { "ask" },
+                    registered
+                );
+            }
+            if log_detail && order_inserted {
+                if let Err(e) = log_debug_order_lookup(
+                    state,
+                    pool_id,
+                    if is_bid {
+                        "post-bid-seed"
+                    } else {
+                        "post-ask-seed"
+                    },
+                    placed_order_id,
+                ) {
+                    tracing::warn!("Router: debug get_order lookup failed: {}", e);
+                }
+            }
+            Ok(())
+        };
+
+        let ladder = config.seed_ladder();
+        for (idx, order) in ladder.iter().enumerate() {
+            state.env.set_sender(makers[idx % makers.len()]);
+            place_seed_order(state, idx as u64 + 1, order)?;
+            if idx + 1 == config.ask_levels as usize && state.debug_log_sampler.sample_routine() {
+                log_debug_pool_snapshot(state, pool_id, "after-ask-seed")?;
+            }
+        }
+        if state.debug_log_sampler.sample_routine() {
+            log_debug_pool_snapshot(state, pool_id, "post-seed")?;
+        }
+
+        // Every seed order should rest: the ladder sides never cross.
+        for (bids, expected) in [(true, config.bid_levels), (false, config.ask_levels)] {
+            let page = fetch_iter_orders(
+                state,
+                pool_id,
+                bids,
+                u64::from(MAX_DEBUG_SEED_LEVELS) + 1,
+                None,
+            )?;
+            if page.orders.len() != expected as usize {
+                return Err(anyhow!(
+                    "debug pool seeded {} {} orders but iter_orders found {}",
+                    expected,
+                    if bids { "bid" } else { "ask" },
+                    page.orders.len()
+                ));
+            }
+        }
+
+        Ok(())
+    })();
+
+    state.env.set_sender(original_sender);
+    seed_result
+}
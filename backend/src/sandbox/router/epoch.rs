@@ -0,0 +1,371 @@
+//! Pool epochs and volume history
+//!
+//! Synthesizes the `History` volume fields a pool needs for its current
+//! epoch, advances pools to a new epoch, and reads per-epoch volumes back.
+
+use super::*;
+
+pub(super) fn extract_history_table_id(loader: &StateLoader) -> Option<String> {
+    loader.all_objects().find_map(|obj| {
+        if !obj.object_type.contains("pool::PoolInner") {
+            return None;
+        }
+
+        obj.object_json
+            .get("value")
+            .and_then(|value| value.get("state"))
+            .and_then(|state| state.get("history"))
+            .and_then(|history| history.get("historic_volumes"))
+            .and_then(|hv| hv.get("id"))
+            .and_then(|id| id.get("id"))
+            .and_then(|id| id.as_str())
+            .map(|id| id.to_string())
+    })
+}
+
+pub(super) fn extract_pool_epoch(loader: &StateLoader) -> Option<u64> {
+    loader.all_objects().find_map(|obj| {
+        if !obj.object_type.contains("pool::PoolInner") {
+            return None;
+        }
+
+        obj.object_json
+            .get("value")
+            .and_then(|value| value.get("state"))
+            .and_then(|state| state.get("history"))
+            .and_then(|history| history.get("epoch"))
+            .and_then(|epoch| epoch.as_str())
+            .and_then(|epoch| epoch.parse::<u64>().ok())
+    })
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TradeParamsSnapshot {
+    taker_fee: u64,
+    maker_fee: u64,
+    stake_required: u64,
+}
+
+#[derive(Debug, Clone)]
+struct HistorySynthesisContext {
+    table_id: String,
+    history_epoch: u64,
+    trade_params: TradeParamsSnapshot,
+}
+
+pub(super) fn synthesize_history_volume_fields_for_router(
+    env: &mut SimulationEnvironment,
+    bcs_converter: &mut JsonToBcsConverter,
+    loader: &StateLoader,
+) -> Result<usize> {
+    let Some(ctx) = extract_history_synthesis_context(loader) else {
+        return Ok(0);
+    };
+
+    let table_addr = AccountAddress::from_hex_literal(&ctx.table_id)?;
+
+    let mut existing_child_ids = HashSet::new();
+    for obj in loader.all_objects() {
+        if obj.owner_address.as_deref() == Some(ctx.table_id.as_str())
+            && obj.object_type.contains("dynamic_field::Field")
+        {
+            if let Ok(child_id) = AccountAddress::from_hex_literal(&obj.object_id) {
+                existing_child_ids.insert(child_id);
+            }
+        }
+    }
+
+    let mut epochs = HashSet::new();
+    epochs.insert(ctx.history_epoch);
+
+    for obj in loader.all_objects() {
+        if !(obj.object_type.contains("big_vector::Slice")
+            && obj.object_type.contains("order::Order"))
+        {
+            continue;
+        }
+
+        let Some(vals) = obj
+            .object_json
+            .get("value")
+            .and_then(|value| value.get("vals"))
+            .and_then(|vals| vals.as_array())
+        else {
+            continue;
+        };
+
+        for order in vals {
+            let Some(epoch_str) = order.get("epoch").and_then(|v| v.as_str()) else {
+                continue;
+            };
+            if let Ok(epoch) = epoch_str.parse::<u64>() {
+                epochs.insert(epoch);
+            }
+        }
+    }
+
+    let mut epochs_sorted: Vec<u64> = epochs.into_iter().collect();
+    epochs_sorted.sort_unstable();
+
+    let mut synthesized = 0usize;
+    for epoch in epochs_sorted {
+        let child_id = history_volumes_child_id(table_addr, epoch)?;
+        if existing_child_ids.contains(&child_id) {
+            continue;
+        }
+
+        set_synthetic_history_volumes(
+            env,
+            bcs_converter,
+            table_addr,
+            child_id,
+            epoch,
+            ctx.trade_params,
+        )?;
+        existing_child_ids.insert(child_id);
+        synthesized += 1;
+    }
+
+    Ok(synthesized)
+}
+
+/// Child id of the `history.historic_volumes` entry for `epoch`
+fn history_volumes_child_id(table_addr: AccountAddress, epoch: u64) -> Result<AccountAddress> {
+    let key_bytes =
+        bcs::to_bytes(&epoch).map_err(|e| anyhow!("Failed to encode history epoch key: {}", e))?;
+    derive_dynamic_field_id(table_addr, &TypeTag::U64, &key_bytes)
+        .map_err(|e| anyhow!("Failed to derive history dynamic field ID: {}", e))
+}
+
+/// Write a zero `history::Volumes` entry for `epoch` carrying `trade_params`.
+fn set_synthetic_history_volumes(
+    env: &mut SimulationEnvironment,
+    bcs_converter: &mut JsonToBcsConverter,
+    table_addr: AccountAddress,
+    child_id: AccountAddress,
+    epoch: u64,
+    trade_params: TradeParamsSnapshot,
+) -> Result<()> {
+    let field_type = format!(
+        "0x2::dynamic_field::Field<u64, {}::history::Volumes>",
+        DEEPBOOK_PACKAGE
+    );
+    let field_tag = SimulationEnvironment::parse_type_string(&field_type)
+        .ok_or_else(|| anyhow!("Failed to parse type: {}", field_type))?;
+
+    let field_json = json!({
+        "id": { "id": child_id.to_hex_literal() },
+        "name": epoch.to_string(),
+        "value": {
+            "total_volume": "0",
+            "total_staked_volume": "0",
+            "total_fees_collected": { "base": "0", "quote": "0", "deep": "0" },
+            "historic_median": "0",
+            "trade_params": {
+                "taker_fee": trade_params.taker_fee.to_string(),
+                "maker_fee": trade_params.maker_fee.to_string(),
+                "stake_required": trade_params.stake_required.to_string()
+            }
+        }
+    });
+
+    let field_bytes = bcs_converter
+        .convert(&field_type, &field_json)
+        .map_err(|e| {
+            anyhow!(
+                "Failed to encode synthetic history dynamic field for epoch {}: {}",
+                epoch,
+                e
+            )
+        })?;
+
+    env.set_dynamic_field(table_addr, child_id, field_tag, field_bytes);
+    Ok(())
+}
+
+/// Advance the simulation epoch, synthesizing each pool's `history::Volumes`
+/// entry for the new epoch from its current trade params.
+///
+/// Trade params are read before the epoch moves, so the entries match what
+/// `pool_trade_params` reports.
+pub(super) fn set_epoch(state: &mut RouterEnvState, epoch: u64) -> Result<EpochAdvanceReport> {
+    let previous_epoch = state.env.config_mut().epoch;
+    if epoch < previous_epoch {
+        return Err(anyhow!(
+            "epoch {} is below the current epoch {}",
+            epoch,
+            previous_epoch
+        ));
+    }
+
+    let mut pools: Vec<(PoolId, AccountAddress)> = state
+        .pool_cache
+        .iter()
+        .filter_map(|(pool_id, entry)| entry.history_table.map(|table| (*pool_id, table)))
+        .collect();
+    pools.sort_by_key(|(pool_id, _)| pool_id.as_str());
+
+    let mut pending = Vec::new();
+    for (pool_id, table_addr) in pools {
+        let child_id = history_volumes_child_id(table_addr, epoch)?;
+        if state.env.get_dynamic_field(table_addr, child_id).is_some() {
+            continue;
+        }
+        let trade_params = read_pool_state(state, pool_id)?.trade_params;
+        pending.push((pool_id, table_addr, child_id, trade_params));
+    }
+
+    let mut synthesized_pools = Vec::new();
+    for (pool_id, table_addr, child_id, trade_params) in pending {
+        set_synthetic_history_volumes(
+            &mut state.env,
+            &mut state.bcs_converter,
+            table_addr,
+            child_id,
+            epoch,
+            TradeParamsSnapshot {
+                taker_fee: trade_params.taker_fee,
+                maker_fee: trade_params.maker_fee,
+                stake_required: trade_params.stake_required,
+            },
+        )?;
+        synthesized_pools.push(pool_id.as_str().to_string());
+    }
+
+    state.env.config_mut().epoch = epoch;
+    state.epoch = Some(epoch);
+    tracing::info!(
+        "Router: advanced simulation epoch {} -> {} (synthesized history for {:?})",
+        previous_epoch,
+        epoch,
+        synthesized_pools
+    );
+    Ok(EpochAdvanceReport {
+        previous_epoch,
+        epoch,
+        synthesized_pools,
+    })
+}
+
+/// BCS layout of `dynamic_field::Field<u64, history::Volumes>`
+#[derive(Debug, Deserialize)]
+struct VolumesFieldBcs {
+    _id: AccountAddress,
+    _name: u64,
+    value: VolumesBcs,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct VolumesBcs {
+    pub(super) total_volume: u128,
+    pub(super) total_staked_volume: u128,
+    pub(super) total_fees_collected: AccountBalances,
+    pub(super) historic_median: u128,
+    pub(super) trade_params: TradeParams,
+}
+
+pub(super) fn decode_volumes_field(bytes: &[u8]) -> Result<VolumesBcs> {
+    bcs::from_bytes::<VolumesFieldBcs>(bytes)
+        .map(|field| field.value)
+        .map_err(|e| anyhow!("Failed to decode history volumes field: {}", e))
+}
+
+/// Decode a pool's `history.historic_volumes` entry for an epoch.
+///
+/// Forked state usually only has synthesized (zero) entries; live mainnet
+/// state may carry real volumes and fees.
+pub(super) fn read_epoch_volumes(
+    state: &RouterEnvState,
+    pool_id: PoolId,
+    epoch: Option<u64>,
+) -> Result<RouterEpochVolumes> {
+    let entry = state
+        .pool_cache
+        .get(&pool_id)
+        .ok_or_else(|| anyhow!("{} is not loaded in the router", pool_id.display_name()))?;
+    let history_table = entry.history_table.ok_or_else(|| {
+        anyhow!(
+            "{} has no known history.historic_volumes table",
+            pool_id.display_name()
+        )
+    })?;
+    let epoch = epoch.or(entry.history_epoch).ok_or_else(|| {
+        anyhow!(
+            "No epoch given and {} has no known history epoch",
+            pool_id.display_name()
+        )
+    })?;
+
+    let key_bytes = bcs::to_bytes(&epoch)?;
+    let child_id = derive_dynamic_field_id(history_table, &TypeTag::U64, &key_bytes)?;
+
+    let mut volumes = RouterEpochVolumes {
+        pool: pool_id.as_str().to_string(),
+        epoch,
+        exists: false,
+        total_volume: "0".to_string(),
+        total_staked_volume: "0".to_string(),
+        total_fees_collected: AccountBalances::default(),
+        historic_median: "0".to_string(),
+        trade_params: None,
+    };
+    let Some((_, field_bytes)) = state.env.get_dynamic_field(history_table, child_id) else {
+        return Ok(volumes);
+    };
+
+    let decoded = decode_volumes_field(field_bytes)?;
+    volumes.exists = true;
+    volumes.total_volume = decoded.total_volume.to_string();
+    volumes.total_staked_volume = decoded.total_staked_volume.to_string();
+    volumes.total_fees_collected = decoded.total_fees_collected;
+    volumes.historic_median = decoded.historic_median.to_string();
+    volumes.trade_params = Some(decoded.trade_params);
+    Ok(volumes)
+}
+
+fn extract_history_synthesis_context(loader: &StateLoader) -> Option<HistorySynthesisContext> {
+    loader.all_objects().find_map(|obj| {
+        if !obj.object_type.contains("pool::PoolInner") {
+            return None;
+        }
+
+        let value = obj.object_json.get("value")?;
+        let state = value.get("state")?;
+        let history = state.get("history")?;
+        let governance = state.get("governance")?;
+        let trade_params = governance.get("trade_params")?;
+
+        let table_id = history
+            .get("historic_volumes")
+            .and_then(|hv| hv.get("id"))
+            .and_then(|id| id.get("id"))
+            .and_then(|id| id.as_str())?
+            .to_string();
+        let history_epoch = history
+            .get("epoch")
+            .and_then(|epoch| epoch.as_str())
+            .and_then(|epoch| epoch.parse::<u64>().ok())?;
+        let taker_fee = trade_params
+            .get("taker_fee")
+            .and_then(|v| v.as_str())
+            .and_then(|v| v.parse::<u64>().ok())?;
+        let maker_fee = trade_params
+            .get("maker_fee")
+            .and_then(|v| v.as_str())
+            .and_then(|v| v.parse::<u64>().ok())?;
+        let stake_required = trade_params
+            .get("stake_required")
+            .and_then(|v| v.as_str())
+            .and_then(|v| v.parse::<u64>().ok())?;
+
+        Some(HistorySynthesisContext {
+            table_id,
+            history_epoch,
+            trade_params: TradeParamsSnapshot {
+                taker_fee,
+                maker_fee,
+                stake_required,
+            },
+        })
+    })
+}
@@ -0,0 +1,153 @@
+//! VM faucet
+//!
+//! Splits faucet coins off the VM reserve coins and transfers them to the
+//! requesting sender.
+
+use super::*;
+
+/// Coin types `execute_vm_faucet` can split from a VM reserve coin.
+///
+/// Checkpoint-backed reserves count only once bootstrapped; debug tokens are
+/// minted from their treasuries, so they are always available.
+fn faucet_coin_types(state: &RouterEnvState) -> Vec<String> {
+    [SUI_TYPE, USDC_TYPE, WAL_TYPE, DEEP_TYPE]
+        .into_iter()
+        .filter(|coin_type| state.coin_reserve_cache.contains_key(*coin_type))
+        .map(str::to_string)
+        .chain(debug_token_types(state))
+        .collect()
+}
+
+pub(super) fn execute_vm_faucet(
+    state: &mut RouterEnvState,
+    coin_type: &str,
+    amount: u64,
+) -> Result<VmFaucetResult> {
+    let supported = faucet_coin_types(state);
+    if !supported.iter().any(|known| known == coin_type) {
+        return Err(anyhow!(
+            "{} {}. Supported coin types: {}",
+            UNSUPPORTED_FAUCET_COIN_TYPE,
+            coin_type,
+            supported.join(", ")
+        ));
+    }
+
+    let sui_framework_addr = AccountAddress::from_hex_literal(SUI_FRAMEWORK_PACKAGE)?;
+    let coin_tag = TypeTag::from_str(coin_type)?;
+    let coin_obj_tag = TypeTag::from_str(&format!("0x2::coin::Coin<{}>", coin_type))?;
+    let recipient = state.env.sender().to_vec();
+
+    // Debug tokens are minted from their treasury, so their faucet needs no
+    // seeded reserve and is not bounded by what the reserve has left.
+    let debug_config = debug_config_for_coin_type(state, coin_type);
+    let mint_from_treasury = debug_config.is_some();
+    let source_input = if let Some(config) = debug_config {
+        let treasury_id = ensure_debug_treasury(state, &config)?;
+        debug_treasury_shared_input(state, treasury_id, &config.token_symbol)?
+    } else {
+        reserve_coin_input(state, coin_type)?
+    };
+    let inputs = vec![
+        InputValue::Object(source_input),
+        InputValue::Pure(bcs::to_bytes(&amount)?),
+        InputValue::Pure(recipient),
+    ];
+
+    let commands = vec![
+        // Split faucet amount from a persistent VM reserve coin (or mint it).
+        Command::MoveCall {
+            package: sui_framework_addr,
+            module: Identifier::new("coin")?,
+            function: Identifier::new(if mint_from_treasury { "mint" } else { "split" })?,
+            type_args: vec![coin_tag.clone()],
+            args: vec![Argument::Input(0), Argument::Input(1)],
+        },
+        // Read returned coin value for verification.
+        Command::MoveCall {
+            package: sui_framework_addr,
+            module: Identifier::new("coin")?,
+            function: Identifier::new("value")?,
+            type_args: vec![coin_tag],
+            args: vec![Argument::Result(0)],
+        },
+        // Transfer faucet coin to sender in VM so object lifecycle is legitimate.
+        Command::MoveCall {
+            package: sui_framework_addr,
+            module: Identifier::new("transfer")?,
+            function: Identifier::new("public_transfer")?,
+            type_args: vec![coin_obj_tag.clone()],
+            args: vec![Argument::Result(0), Argument::Input(2)],
+        },
+    ];
+
+    let result = state.env.execute_ptb(inputs, commands);
+    if !result.success {
+        return Err(anyhow!(
+            "vm faucet split/transfer failed for {}: {}",
+            coin_type,
+            result
+                .raw_error
+                .unwrap_or_else(|| "Unknown error".to_string())
+        ));
+    }
+
+    let effects = result
+        .effects
+        .as_ref()
+        .ok_or_else(|| anyhow!("Missing PTB effects for vm faucet"))?;
+    if mint_from_treasury {
+        sync_dynamic_field_entries(state, effects);
+    }
+    let minted_amount = parse_u64_command_return(effects, 1, 0, "faucet_amount")?;
+    if minted_amount != amount {
+        return Err(anyhow!(
+            "VM faucet amount mismatch for {}: requested {}, minted {}",
+            coin_type,
+            amount,
+            minted_amount
+        ));
+    }
+
+    let created_objects = effects
+        .created
+        .iter()
+        .map(|id| {
+            let object = state.env.get_object(id);
+            let type_tag = object.map(|obj| &obj.type_tag);
+            let value = if type_tag == Some(&coin_obj_tag) {
+                Some(minted_amount)
+            } else if type_tag.is_some_and(|tag| tag.to_string().contains("::coin::Coin<")) {
+                effects
+                    .created_object_bytes
+                    .get(id)
+                    .and_then(|bytes| parse_coin_value_from_bcs(bytes))
+            } else {
+                None
+            };
+            VmCreatedObject {
+                id: id.to_hex_literal(),
+                type_tag: type_tag.map_or_else(|| "unknown".to_string(), |tag| tag.to_string()),
+                value,
+            }
+        })
+        .collect::<Vec<_>>();
+    if !created_objects
+        .iter()
+        .any(|obj| obj.value == Some(minted_amount))
+    {
+        tracing::warn!(
+            "VM faucet for {} created no {} coin; created objects: {:?}",
+            coin_type,
+            coin_obj_tag,
+            created_objects
+        );
+    }
+
+    Ok(VmFaucetResult {
+        amount: minted_amount,
+        gas_used: effects.gas_used,
+        created_objects,
+        events: collect_swap_events(&mut state.bcs_converter, effects),
+    })
+}
@@ -0,0 +1,7598 @@
+//! Quote router using MoveVM
+//!
+//! Dedicated thread that owns a SimulationEnvironment with all pool states loaded.
+//! Executes DeepBook quote PTBs (single-hop) and router PTBs (two-hop) on demand
+//! via mpsc channels.
+
+use anyhow::{anyhow, Result};
+use move_binary_format::CompiledModule;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::TypeTag;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+use tracing;
+
+use sui_sandbox_core::fetcher::GrpcFetcher;
+use sui_sandbox_core::ptb::{Argument, Command, InputValue, ObjectInput};
+use sui_sandbox_core::simulation::state::FetcherConfig;
+use sui_sandbox_core::simulation::SimulationEnvironment;
+use sui_sandbox_core::tx_replay::derive_dynamic_field_id;
+use sui_transport::grpc::{GrpcObject, GrpcOwner};
+
+use crate::metrics::{Metrics, RouterOp};
+
+use super::deepbook_errors::DeepBookAbort;
+use super::orderbook_builder::{build_pool_type_tag, PriceLevel};
+use super::quote_cache::{QuoteCache, QuoteCacheKey};
+use super::router_snapshot::{
+    self, RouterSnapshot, SnapshotDynamicField, SnapshotObject, SnapshotPackage,
+    SnapshotPackageCheck, SnapshotPool, SnapshotReserveCoin, SnapshotRouterContract,
+};
+use super::snowflake_bcs::JsonToBcsConverter;
+use super::state_loader::{DeepBookConfig, ExportedObject, PoolId, StateLoader};
+use super::swap_executor::CommandEffect;
+
+mod debug_pool;
+mod epoch;
+mod faucet;
+mod orders;
+mod quotes;
+mod snapshot;
+
+pub use debug_pool::{debug_coin_type, DebugPoolCreateConfig, DebugPoolInfo};
+pub use orders::{limit_order_deposits, order_status_label, DecodedOrder, OrderPage, VmBookLevels};
+pub use quotes::{min_single_hop_input, min_two_hop_input};
+
+use debug_pool::*;
+use epoch::*;
+use faucet::*;
+use orders::*;
+use quotes::*;
+use snapshot::*;
+
+// DeepBook V3 Package
+const DEEPBOOK_PACKAGE: &str = "0x2c8d603bc51326b8c13cef9dd07031a408a48dddb541963357661df5d3204809";
+
+// Type tags for assets
+const SUI_TYPE: &str = "0x2::sui::SUI";
+const USDC_TYPE: &str =
+    "0xdba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7::usdc::USDC";
+const WAL_TYPE: &str =
+    "0x356a26eb9e012a68958082340d4c4116e7f55615cf27affcff209cf0ae544f59::wal::WAL";
+const DEEP_TYPE: &str =
+    "0xdeeb7a4662eec9f2f3def03fb937a663dddaa2e215b8078a284d026b7946c270::deep::DEEP";
+/// First byte of every debug token package address (see `debug_token_package_addr`)
+const DEBUG_TOKEN_ADDRESS_PREFIX: u8 = 0xdb;
+const DEEPBOOK_REGISTRY_ID: &str =
+    "0xaf16199a2dff736e9f07a845f23c5da6df6f756eddb631aed9d24a93efc4549d";
+const COIN_REGISTRY_OBJECT_ID: &str = "0xc";
+
+// Synthetic addresses for router deployment
+const ROUTER_PACKAGE_ADDR: &str =
+    "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+const CLOCK_OBJECT_ID: &str = "0x6";
+const SUI_FRAMEWORK_PACKAGE: &str = "0x2";
+const OBJECT_ID_TYPE: &str = "0x2::object::ID";
+/// Error prefix for faucet requests whose coin type has no VM reserve
+pub const UNSUPPORTED_FAUCET_COIN_TYPE: &str = "Unsupported faucet coin type";
+/// Error prefix for exact-output swaps the book (or input cap) cannot fill
+pub const EXACT_OUT_UNFILLABLE: &str = "Exact-output target not fillable";
+/// Error prefix for cancelling an order that is no longer on the book
+pub const ORDER_NOT_FOUND: &str = "Order not found or already filled";
+/// Error prefix for cancelling through a balance manager that does not own the order
+pub const ORDER_NOT_OWNED: &str = "Order is owned by a different balance manager";
+/// Error prefix for debug pool configs that violate DeepBook's pool/order constraints
+pub const INVALID_DEBUG_POOL_CONFIG: &str = "Invalid debug pool config";
+/// Smallest lot size `pool::create_pool_admin` accepts
+const DEEPBOOK_MIN_LOT_SIZE: u64 = 1_000;
+const DEBUG_ADMIN_CAP_ID: &str =
+    "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaab";
+const DEBUG_POOL_TICK_SIZE: u64 = 1_000;
+const DEBUG_POOL_LOT_SIZE: u64 = 1_000;
+const DEBUG_POOL_MIN_SIZE: u64 = 10_000;
+const DEBUG_POOL_WHITELISTED: bool = true;
+const DEBUG_POOL_BID_PRICE: u64 = 900_000; // $0.90
+const DEBUG_POOL_ASK_PRICE: u64 = 1_100_000; // $1.10
+const DEBUG_POOL_BID_QTY: u64 = 100_000_000_000; // 100 DBG @ 9 decimals
+const DEBUG_POOL_ASK_QTY: u64 = 100_000_000_000; // 100 DBG @ 9 decimals
+const DEBUG_POOL_USDC_LIQUIDITY: u64 = 200_000_000; // 200 USDC
+const DEBUG_POOL_BASE_LIQUIDITY: u64 = 200_000_000_000; // 200 DBG
+const DEBUG_POOL_DEEP_FEE_BUDGET: u64 = 100_000_000; // 100 DEEP
+const DEBUG_POOL_PAY_WITH_DEEP: bool = false;
+/// Most seed orders the debug pool places per side
+const MAX_DEBUG_SEED_LEVELS: u32 = 50;
+const RESERVE_COIN_SEED_AMOUNT: u64 = 100_000_000_000_000_000; // shared VM reserve per coin type
+/// Checkpoints scanned for reserve coins unless `RESERVE_SCAN_WINDOW` says otherwise
+const MAINNET_RESERVE_SCAN_WINDOW: u64 = 150;
+const SYNTHETIC_CLOCK_START_MS: u64 = 1_770_000_000_000; // ~2026 timestamp
+const SYNTHETIC_CLOCK_STEP_MS: u64 = 61_000; // > DeepBook min 60s spacing for deep_price points
+/// DeepBook `deep_price::MIN_DURATION_BETWEEN_DATA_POINTS_MS`; points must be strictly further apart
+const DEEP_PRICE_MIN_SPACING_MS: u64 = 60_000;
+const DEFAULT_TWO_HOP_ATOMIC_RETRIES: u32 = 1;
+const DEBUG_ORDER_EXPIRY_TTL_MS: u64 = 86_400_000; // 1 day
+const DEBUG_POOL_MAKER_SENDER: &str =
+    "0xbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+
+/// Result of a two-hop quote from the MoveVM router
+#[derive(Debug, Clone)]
+pub struct TwoHopQuote {
+    pub final_output: u64,
+    pub intermediate_amount: u64,
+}
+
+/// Swap route executed by a gas dry run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapGasRoute {
+    SingleHop { pool_id: PoolId, is_sell_base: bool },
+    TwoHop { from_pool: PoolId, to_pool: PoolId },
+}
+
+/// One leg of a multi-hop route: the pool and which side of it is sold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RouteHop {
+    pub pool_id: PoolId,
+    pub is_sell_base: bool,
+}
+
+impl RouteHop {
+    /// Coin types this leg consumes and produces
+    fn io_types(&self, state: &RouterEnvState) -> (String, String) {
+        let (base_type, quote_type) = pool_types(state, self.pool_id);
+        if self.is_sell_base {
+            (base_type, quote_type)
+        } else {
+            (quote_type, base_type)
+        }
+    }
+}
+
+/// Result of a three-hop quote chained through MoveVM pool views
+#[derive(Debug, Clone)]
+pub struct ThreeHopQuote {
+    pub final_output: u64,
+    /// Output of each hop in route order; the last entry is `final_output`
+    pub hop_outputs: Vec<u64>,
+    /// DEEP fees summed across all hops
+    pub deep_fee: u64,
+}
+
+/// Result of a single-hop quote from MoveVM DeepBook pool calls
+#[derive(Debug, Clone)]
+pub struct SingleHopQuote {
+    pub output_amount: u64,
+    /// DEEP fee the pool would charge for this trade
+    pub deep_fee: u64,
+}
+
+/// Smallest single-hop input that reaches a desired output
+#[derive(Debug, Clone)]
+pub struct ReverseQuote {
+    pub input_amount: u64,
+    /// Output quoted for `input_amount`; at least the desired output
+    pub output_amount: u64,
+    /// DEEP fee the pool would charge at `input_amount`
+    pub deep_fee: u64,
+    /// Quote PTBs run while searching for `input_amount`
+    pub quote_iterations: u32,
+}
+
+/// Route of one quote in a [`RouterHandle::quote_batch`] call
+#[derive(Debug, Clone, Copy)]
+pub enum BatchQuoteRoute {
+    SingleHop { pool_id: PoolId, is_sell_base: bool },
+    TwoHop { from_pool: PoolId, to_pool: PoolId },
+    ThreeHop { hops: [RouteHop; 3] },
+}
+
+/// One quote in a [`RouterHandle::quote_batch`] call
+#[derive(Debug, Clone, Copy)]
+pub struct BatchQuoteInput {
+    pub route: BatchQuoteRoute,
+    pub input_amount: u64,
+    /// Quote at this clock timestamp instead of the batch's shared one
+    pub clock_timestamp_ms: Option<u64>,
+}
+
+/// Result of one batched quote, matching its [`BatchQuoteRoute`] variant
+#[derive(Debug, Clone)]
+pub enum BatchQuote {
+    SingleHop(SingleHopQuote),
+    TwoHop(TwoHopQuote),
+    ThreeHop(ThreeHopQuote),
+}
+
+impl BatchQuote {
+    /// Output of the whole route
+    pub fn output_amount(&self) -> u64 {
+        match self {
+            BatchQuote::SingleHop(quote) => quote.output_amount,
+            BatchQuote::TwoHop(quote) => quote.final_output,
+            BatchQuote::ThreeHop(quote) => quote.final_output,
+        }
+    }
+}
+
+/// Both directions of a pool quoted in one router call.
+///
+/// `sell` quotes `base_amount` base -> USDC; `buy` spends that USDC output
+/// back into base. Comparing `buy.output_amount` with `base_amount` gives the
+/// round-trip cost of crossing the spread at this size.
+#[derive(Debug, Clone)]
+pub struct TwoSidedQuote {
+    pub base_amount: u64,
+    pub sell: SingleHopQuote,
+    pub buy: SingleHopQuote,
+}
+
+/// Event emitted during swap execution (BCS payload is hex-encoded).
+#[derive(Debug, Clone)]
+pub struct SwapEvent {
+    pub event_type: String,
+    pub data_hex: String,
+    /// Payload decoded into JSON fields, for the types in `DECODED_EVENT_TYPES`
+    pub decoded: Option<serde_json::Value>,
+}
+
+/// Result of a single-hop swap executed in MoveVM.
+#[derive(Debug, Clone)]
+pub struct SingleHopSwapResult {
+    pub output_amount: u64,
+    pub input_refund: u64,
+    pub deep_refund: u64,
+    pub gas_used: u64,
+    pub events: Vec<SwapEvent>,
+    /// False when refunds were transferred to the sender instead of being
+    /// joined back into the reserve coins (see `SWAP_REFUND_JOIN_FALLBACK`)
+    pub reserve_reconciled: bool,
+    /// False on whitelisted pools, where no DEEP was split from the reserve
+    pub deep_fee_required: bool,
+    pub created_objects: Vec<String>,
+    pub command_effects: Vec<CommandEffect>,
+}
+
+/// Result of an exact-output single-hop swap executed in MoveVM.
+#[derive(Debug, Clone)]
+pub struct ExactOutSwapResult {
+    /// Smallest input whose quote reached the target output
+    pub input_amount: u64,
+    /// Quote PTBs run while searching for `input_amount`
+    pub quote_iterations: u32,
+    pub swap: SingleHopSwapResult,
+}
+
+/// Result of a two-hop swap executed in MoveVM.
+#[derive(Debug, Clone)]
+pub struct TwoHopSwapResult {
+    pub output_amount: u64,
+    pub intermediate_amount: u64,
+    pub input_refund: u64,
+    pub quote_refund: u64,
+    pub deep_refund: u64,
+    pub gas_used: u64,
+    pub events: Vec<SwapEvent>,
+    pub created_objects: Vec<String>,
+    /// Per-command results of the atomic PTB; empty for the sequential fallback
+    pub command_effects: Vec<CommandEffect>,
+    /// Atomic PTB executions tried, including the successful one
+    pub atomic_attempts: u32,
+    /// True when every atomic attempt failed and two single-hop PTBs ran instead
+    pub sequential_fallback: bool,
+}
+
+/// An object created by a VM faucet PTB.
+#[derive(Debug, Clone, Serialize)]
+pub struct VmCreatedObject {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub type_tag: String,
+    /// Coin value in smallest units; `None` for objects that are not coins
+    pub value: Option<u64>,
+}
+
+/// Result of VM-backed faucet execution.
+#[derive(Debug, Clone)]
+pub struct VmFaucetResult {
+    pub amount: u64,
+    pub gas_used: u64,
+    /// The faucet coin (valued by the PTB's `coin::value`) and any byproducts
+    pub created_objects: Vec<VmCreatedObject>,
+    pub events: Vec<SwapEvent>,
+}
+
+/// A limit order to place through a session's balance manager.
+#[derive(Debug, Clone)]
+pub struct LimitOrderParams {
+    pub pool_id: PoolId,
+    /// DeepBook price (quote units per base unit, scaled by 1e9)
+    pub price: u64,
+    /// Base quantity in smallest units
+    pub quantity: u64,
+    pub is_bid: bool,
+    /// VM clock expiry in ms; `None` never expires
+    pub expire_timestamp: Option<u64>,
+    pub client_order_id: u64,
+    /// DEEP deposited for fees on pools that charge them
+    pub deep_amount: u64,
+}
+
+/// Result of a limit order placed in MoveVM.
+#[derive(Debug, Clone)]
+pub struct LimitOrderResult {
+    pub order_id: u128,
+    pub balance_manager_id: String,
+    /// True when this order created the session's balance manager
+    pub balance_manager_created: bool,
+    pub price: u64,
+    pub original_quantity: u64,
+    pub executed_quantity: u64,
+    pub cumulative_quote_quantity: u64,
+    /// DeepBook `order_info.status`
+    pub status: u8,
+    /// False when the order filled (or was cancelled) without resting on the book
+    pub inserted: bool,
+    /// Amounts split from the VM reserves into the balance manager
+    pub base_deposit: u64,
+    pub quote_deposit: u64,
+    pub deep_deposit: u64,
+    pub gas_used: u64,
+    pub events: Vec<SwapEvent>,
+}
+
+/// Result of cancelling a resting order in MoveVM.
+#[derive(Debug, Clone)]
+pub struct CancelOrderResult {
+    /// The order as it rested on the book right before the cancel
+    pub order: DecodedOrder,
+    pub balance_manager_id: String,
+    /// Withdrawn from the balance manager back into the VM reserves: the
+    /// unfilled remainder plus any settled fills and unused fee deposits
+    pub base_refund: u64,
+    pub quote_refund: u64,
+    /// DEEP refunded; on DEEP-based pools this is included in `base_refund`
+    pub deep_refund: u64,
+    pub gas_used: u64,
+    pub events: Vec<SwapEvent>,
+}
+
+impl CancelOrderResult {
+    /// Base, quote and DEEP refunds, in that order
+    pub fn refunds(&self) -> [u64; 3] {
+        [self.base_refund, self.quote_refund, self.deep_refund]
+    }
+}
+
+/// Balances withdrawn from a session's balance manager after settling its
+/// account in a pool.
+#[derive(Debug, Clone)]
+pub struct WithdrawSettledResult {
+    pub balance_manager_id: String,
+    /// Withdrawn into the VM reserves: the account's settled balances plus
+    /// anything the balance manager already held
+    pub base_amount: u64,
+    pub quote_amount: u64,
+    /// DEEP withdrawn; on DEEP-based pools this is included in `base_amount`
+    pub deep_amount: u64,
+    pub gas_used: u64,
+    pub events: Vec<SwapEvent>,
+}
+
+/// An open order that `cancel_all_orders` could not cancel
+#[derive(Debug, Clone)]
+pub struct CancelOrderFailure {
+    pub pool_id: PoolId,
+    /// Order id as listed by the balance manager
+    pub order_id: String,
+    pub error: String,
+}
+
+/// Result of cancelling every open order of a session's balance manager
+#[derive(Debug, Clone)]
+pub struct CancelAllOrdersResult {
+    /// `None` when the session has never placed a limit order
+    pub balance_manager_id: Option<String>,
+    pub cancelled: Vec<(PoolId, CancelOrderResult)>,
+    pub failed: Vec<CancelOrderFailure>,
+}
+
+/// Route resolved from explicit coin type strings against loaded pools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeRoute {
+    /// A single pool whose base/quote match the requested pair.
+    Direct { pool_id: PoolId, is_sell_base: bool },
+    /// Two pools sharing the same quote asset (A -> Q -> B).
+    TwoHop { from_pool: PoolId, to_pool: PoolId },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RouterSharedObjectCheck {
+    pub name: String,
+    pub object_id: String,
+    pub present: bool,
+    pub is_shared: bool,
+    pub version: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RouterPackageCheck {
+    pub name: String,
+    pub package_id: String,
+    pub loaded: bool,
+    pub module_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RouterReserveCoinCheck {
+    pub coin_type: String,
+    pub object_id: Option<String>,
+    pub present: bool,
+    pub version: Option<u64>,
+    pub value: Option<u64>,
+    /// Mainnet checkpoint the reserve coin was bootstrapped from
+    pub source_checkpoint: Option<u64>,
+    /// Coin value observed at `source_checkpoint`
+    pub source_value: Option<u64>,
+    /// Object version observed at `source_checkpoint`
+    pub source_version: Option<u64>,
+}
+
+/// Two-hop routes and probe sizes exercised by the router health check.
+///
+/// Pairs are tried in order, each with every amount, until one quote succeeds.
+#[derive(Debug, Clone, Serialize)]
+pub struct RouterHealthCheckConfig {
+    pub pairs: Vec<(PoolId, PoolId)>,
+    pub probe_amounts: Vec<u64>,
+}
+
+impl Default for RouterHealthCheckConfig {
+    fn default() -> Self {
+        Self {
+            // Prefer SUI -> WAL path, then SUI -> DEEP, then WAL -> DEEP.
+            pairs: vec![
+                (PoolId::SuiUsdc, PoolId::WalUsdc),
+                (PoolId::SuiUsdc, PoolId::DeepUsdc),
+                (PoolId::WalUsdc, PoolId::DeepUsdc),
+            ],
+            // DeepBook can abort on dust-sized quote amounts. Probe with practical sizes.
+            probe_amounts: vec![5_000_000_000, 1_000_000_000, 500_000_000, 100_000_000],
+        }
+    }
+}
+
+impl RouterHealthCheckConfig {
+    /// Defaults overridden by `ROUTER_HEALTH_CHECK_PAIRS` (`from:to` pool ids,
+    /// comma-separated) and `ROUTER_HEALTH_CHECK_AMOUNTS` (comma-separated).
+    fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Ok(raw) = std::env::var("ROUTER_HEALTH_CHECK_PAIRS") {
+            match parse_health_check_pairs(&raw) {
+                Ok(pairs) if !pairs.is_empty() => config.pairs = pairs,
+                Ok(_) => {}
+                Err(e) => tracing::warn!(
+                    "Router: invalid ROUTER_HEALTH_CHECK_PAIRS={:?} ({}), using defaults",
+                    raw,
+                    e
+                ),
+            }
+        }
+        if let Ok(raw) = std::env::var("ROUTER_HEALTH_CHECK_AMOUNTS") {
+            let amounts: Result<Vec<u64>, _> = raw
+                .split(',')
+                .map(str::trim)
+                .filter(|entry| !entry.is_empty())
+                .map(str::parse::<u64>)
+                .collect();
+            match amounts {
+                Ok(amounts) if !amounts.is_empty() => config.probe_amounts = amounts,
+                _ => tracing::warn!(
+                    "Router: invalid ROUTER_HEALTH_CHECK_AMOUNTS={:?}, using defaults",
+                    raw
+                ),
+            }
+        }
+        config
+    }
+}
+
+/// Parse `from:to` pool id pairs separated by commas.
+fn parse_health_check_pairs(raw: &str) -> Result<Vec<(PoolId, PoolId)>> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (from, to) = entry
+                .split_once(':')
+                .ok_or_else(|| anyhow!("expected from_pool:to_pool, got '{}'", entry))?;
+            let parse = |id: &str| {
+                PoolId::from_str(id.trim()).ok_or_else(|| anyhow!("unknown pool '{}'", id))
+            };
+            Ok((parse(from)?, parse(to)?))
+        })
+        .collect()
+}
+
+/// One `quote_two_hop` probe made by the router health check
+#[derive(Debug, Clone, Serialize)]
+pub struct RouterHealthProbe {
+    pub from_pool: PoolId,
+    pub to_pool: PoolId,
+    pub amount: u64,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub final_output: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub intermediate_amount: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Synthetic VM clock state
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct RouterClockStatus {
+    /// Timestamp the next clock input will carry
+    pub now_ms: u64,
+    /// Advance per clock input while not frozen (`ROUTER_CLOCK_STEP_MS`)
+    pub step_ms: u64,
+    /// Every clock input carries `now_ms` until advanced manually
+    pub frozen: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RouterHealthCheckReport {
+    pub passed: bool,
+    pub checked_at_unix_ms: u64,
+    /// Probes in the order they ran; stops after the first success
+    pub probes: Vec<RouterHealthProbe>,
+    /// Pairs skipped because a pool's state is not loaded in the router
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped_pairs: Vec<(PoolId, PoolId)>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RouterStartupCheckReport {
+    pub ok: bool,
+    pub checked_at_unix_ms: u64,
+    pub router_package_deployed: bool,
+    pub router_health_check_passed: bool,
+    pub shared_objects: Vec<RouterSharedObjectCheck>,
+    pub reserve_coins: Vec<RouterReserveCoinCheck>,
+    pub packages: Vec<RouterPackageCheck>,
+    pub errors: Vec<String>,
+}
+
+/// Outcome of compiling and deploying the router Move contract at startup
+#[derive(Debug, Clone, Serialize)]
+pub struct RouterContractInfo {
+    pub address: String,
+    pub deployed: bool,
+    /// Compiled module names, sorted
+    pub modules: Vec<String>,
+    /// "mainnet" (`--environment mainnet`) or "default" (fallback build)
+    pub build_method: String,
+    /// Why the mainnet build failed, when the default build was used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mainnet_build_error: Option<String>,
+    pub deployed_at_unix_ms: u64,
+}
+
+/// A router snapshot file written or restored by `RouterHandle`
+#[derive(Debug, Clone, Serialize)]
+pub struct RouterSnapshotInfo {
+    pub path: String,
+    pub format_version: u32,
+    pub size_bytes: u64,
+    pub created_at_unix_ms: u64,
+    pub packages: usize,
+    pub objects: usize,
+    pub dynamic_fields: usize,
+    /// Synthetic clock timestamp stored in the snapshot
+    pub clock_now_ms: u64,
+}
+
+/// Base/quote/DEEP amounts as tracked by a DeepBook `account::Balances`
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct AccountBalances {
+    pub base: u64,
+    pub quote: u64,
+    pub deep: u64,
+}
+
+/// A balance manager's DeepBook `Account` within one pool
+#[derive(Debug, Clone, Serialize)]
+pub struct RouterAccountSnapshot {
+    pub pool: String,
+    pub balance_manager_id: String,
+    /// False when the pool has no account entry for this balance manager
+    pub exists: bool,
+    pub epoch: u64,
+    pub open_orders: usize,
+    pub taker_volume: String,
+    pub maker_volume: String,
+    pub active_stake: u64,
+    pub inactive_stake: u64,
+    /// Withdrawable once settled into the balance manager
+    pub settled_balances: AccountBalances,
+    /// Owed to the pool on the next settlement
+    pub owed_balances: AccountBalances,
+    pub unclaimed_rebates: AccountBalances,
+}
+
+/// Open orders of a session's balance manager across the router's pools
+#[derive(Debug, Clone)]
+pub struct SessionOpenOrders {
+    /// `None` when the session has never placed a limit order
+    pub balance_manager_id: Option<String>,
+    /// Orders listed in the manager's `Account.open_orders`, as `pool::get_order` returns them
+    pub orders: Vec<(PoolId, DecodedOrder)>,
+    /// Order ids listed as open that `pool::get_order` could not find
+    pub missing: Vec<(PoolId, u128)>,
+}
+
+/// DeepBook `governance::TradeParams` in effect for an epoch
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct TradeParams {
+    pub taker_fee: u64,
+    pub maker_fee: u64,
+    pub stake_required: u64,
+}
+
+/// A pool's `history::Volumes` entry for one epoch
+#[derive(Debug, Clone, Serialize)]
+pub struct RouterEpochVolumes {
+    pub pool: String,
+    pub epoch: u64,
+    /// False when `historic_volumes` has no entry for this epoch
+    pub exists: bool,
+    pub total_volume: String,
+    pub total_staked_volume: String,
+    pub total_fees_collected: AccountBalances,
+    pub historic_median: String,
+    pub trade_params: Option<TradeParams>,
+}
+
+/// DeepBook `book::Book` sizing parameters
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct BookParams {
+    pub tick_size: u64,
+    pub lot_size: u64,
+    pub min_size: u64,
+}
+
+/// Sizing, fee and whitelist parameters of a pool, as `/api/pools` reports them
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct PoolParams {
+    pub tick_size: u64,
+    pub lot_size: u64,
+    pub min_size: u64,
+    /// Whitelisted pools charge no fees, so swaps need no DEEP
+    pub whitelisted: bool,
+    pub taker_fee: u64,
+    pub maker_fee: u64,
+}
+
+/// A pool's current `OrderDeepPrice`, which converts the traded asset into
+/// the DEEP charged as fees
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolDeepPrice {
+    pub pool: String,
+    /// Whitelisted pools charge no fees, so no DEEP fee applies
+    pub whitelisted: bool,
+    pub deep_fee_applies: bool,
+    /// Whether `deep_per_asset` prices the base (true) or quote asset; `None`
+    /// on whitelisted pools
+    pub asset_is_base: Option<bool>,
+    /// DEEP per unit of that asset, scaled by 1e9; `None` on whitelisted pools
+    pub deep_per_asset: Option<u64>,
+    pub taker_fee: u64,
+    pub maker_fee: u64,
+}
+
+/// Key `PoolInner` state of a loaded pool, decoded from VM view calls and
+/// dynamic fields
+#[derive(Debug, Clone, Serialize)]
+pub struct RouterPoolState {
+    pub pool: String,
+    pub pool_object_id: String,
+    pub whitelisted: bool,
+    pub registered_pool: bool,
+    pub stable_pool: bool,
+    pub book_params: BookParams,
+    /// Trade params currently in effect (`governance.trade_params`)
+    pub trade_params: TradeParams,
+    /// `vault.base_balance` / `quote_balance` / `deep_balance`
+    pub vault_balances: AccountBalances,
+    /// `state.history.epoch` as loaded
+    pub history_epoch: Option<u64>,
+    /// Entries in `state.accounts`, when the table id is known
+    pub account_count: Option<usize>,
+    /// `history.historic_volumes` entry for `history_epoch`
+    pub current_volumes: Option<RouterEpochVolumes>,
+}
+
+/// Outcome of a forced pool dynamic-field reconciliation pass
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolResyncReport {
+    pub pool: String,
+    pub pool_object_id: String,
+    pub inner_parent_id: Option<String>,
+    pub inner_version_before: Option<u64>,
+    pub inner_version_after: Option<u64>,
+    /// Wrapper `inner.version` was bumped to the latest PoolInner dynamic field
+    pub version_reconciled: bool,
+    /// PoolInner child id re-derived from the (possibly updated) wrapper version
+    pub inner_child_id: Option<String>,
+    pub inner_child_found: bool,
+    /// Child object bytes were re-synced from the dynamic-field entry
+    pub inner_object_refreshed: bool,
+    /// Dynamic-field type tag was normalized
+    pub inner_type_normalized: bool,
+    pub changed: bool,
+}
+
+/// Outcome of a debug pool `deep_price` bootstrap
+#[derive(Debug, Clone, Serialize)]
+pub struct DeepPriceBootstrapReport {
+    /// Reference pool whose `add_deep_price_point` produced a usable price
+    pub reference_pool: String,
+    pub points_added: usize,
+    /// `deep_per_asset` read back from the debug pool's order deep price
+    pub deep_per_asset: u64,
+    /// Why each reference pool tried before it failed
+    pub failed_references: Vec<String>,
+}
+
+/// Outcome of advancing the simulation epoch
+#[derive(Debug, Clone, Serialize)]
+pub struct EpochAdvanceReport {
+    pub previous_epoch: u64,
+    pub epoch: u64,
+    /// Pools that got a synthesized `history::Volumes` entry for `epoch`
+    pub synthesized_pools: Vec<String>,
+}
+
+impl Default for RouterStartupCheckReport {
+    fn default() -> Self {
+        Self {
+            ok: false,
+            checked_at_unix_ms: 0,
+            router_package_deployed: false,
+            router_health_check_passed: false,
+            shared_objects: Vec::new(),
+            reserve_coins: Vec::new(),
+            packages: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+}
+
+/// Request sent to the router thread
+enum RouterRequest {
+    TwoHop {
+        from_pool: PoolId,
+        to_pool: PoolId,
+        input_amount: u64,
+        response_tx: oneshot::Sender<Result<TwoHopQuote>>,
+    },
+    ThreeHop {
+        hops: [RouteHop; 3],
+        input_amount: u64,
+        response_tx: oneshot::Sender<Result<ThreeHopQuote>>,
+    },
+    SingleHop {
+        pool_id: PoolId,
+        input_amount: u64,
+        is_sell_base: bool,
+        response_tx: oneshot::Sender<Result<SingleHopQuote>>,
+    },
+    ReverseQuote {
+        pool_id: PoolId,
+        desired_output: u64,
+        is_sell_base: bool,
+        response_tx: oneshot::Sender<Result<ReverseQuote>>,
+    },
+    TwoSided {
+        pool_id: PoolId,
+        base_amount: u64,
+        response_tx: oneshot::Sender<Result<TwoSidedQuote>>,
+    },
+    BatchQuote {
+        quotes: Vec<BatchQuoteInput>,
+        response_tx: oneshot::Sender<Vec<Result<BatchQuote>>>,
+    },
+    ExecuteSingleHop {
+        pool_id: PoolId,
+        input_amount: u64,
+        deep_amount: u64,
+        is_sell_base: bool,
+        min_out: u64,
+        simulate: bool,
+        sender: Option<String>,
+        response_tx: oneshot::Sender<Result<SingleHopSwapResult>>,
+    },
+    ExecuteExactOut {
+        pool_id: PoolId,
+        output_amount: u64,
+        max_input: u64,
+        deep_amount: u64,
+        is_sell_base: bool,
+        simulate: bool,
+        sender: Option<String>,
+        response_tx: oneshot::Sender<Result<ExactOutSwapResult>>,
+    },
+    ExecuteTwoHop {
+        from_pool: PoolId,
+        to_pool: PoolId,
+        input_amount: u64,
+        deep_amount: u64,
+        min_intermediate: u64,
+        min_final: u64,
+        simulate: bool,
+        sender: Option<String>,
+        response_tx: oneshot::Sender<Result<TwoHopSwapResult>>,
+    },
+    EstimateSwapGas {
+        route: SwapGasRoute,
+        input_amount: u64,
+        response_tx: oneshot::Sender<Result<u64>>,
+    },
+    EnsureDebugPool {
+        response_tx: oneshot::Sender<Result<DebugPoolInfo>>,
+    },
+    PrimeDebugDeepPrice {
+        pool_id: PoolId,
+        response_tx: oneshot::Sender<Result<DeepPriceBootstrapReport>>,
+    },
+    EnsureDebugPoolWithConfig {
+        config: DebugPoolCreateConfig,
+        response_tx: oneshot::Sender<Result<DebugPoolInfo>>,
+    },
+    VmFaucet {
+        coin_type: String,
+        amount: u64,
+        sender: Option<String>,
+        response_tx: oneshot::Sender<Result<VmFaucetResult>>,
+    },
+    PlaceLimitOrder {
+        session_id: String,
+        params: LimitOrderParams,
+        response_tx: oneshot::Sender<Result<LimitOrderResult>>,
+    },
+    CancelOrder {
+        session_id: String,
+        pool_id: PoolId,
+        balance_manager_id: Option<String>,
+        order_id: u128,
+        response_tx: oneshot::Sender<Result<CancelOrderResult>>,
+    },
+    CancelAllOrders {
+        session_id: String,
+        response_tx: oneshot::Sender<Result<CancelAllOrdersResult>>,
+    },
+    WithdrawSettled {
+        session_id: String,
+        pool_id: PoolId,
+        response_tx: oneshot::Sender<Result<WithdrawSettledResult>>,
+    },
+    GetSender {
+        response_tx: oneshot::Sender<Result<String>>,
+    },
+    ReserveStatus {
+        response_tx: oneshot::Sender<Result<Vec<RouterReserveCoinCheck>>>,
+    },
+    SetSender {
+        sender: String,
+        response_tx: oneshot::Sender<Result<String>>,
+    },
+    StartupCheck {
+        response_tx: oneshot::Sender<Result<RouterStartupCheckReport>>,
+    },
+    RefreshStartupCheck {
+        response_tx: oneshot::Sender<Result<RouterStartupCheckReport>>,
+    },
+    Reset {
+        response_tx: oneshot::Sender<Result<RouterStartupCheckReport>>,
+    },
+    SetEpoch {
+        epoch: u64,
+        response_tx: oneshot::Sender<Result<EpochAdvanceReport>>,
+    },
+    SaveSnapshot {
+        path: PathBuf,
+        response_tx: oneshot::Sender<Result<RouterSnapshotInfo>>,
+    },
+    LoadSnapshot {
+        path: PathBuf,
+        response_tx: oneshot::Sender<Result<RouterSnapshotInfo>>,
+    },
+    ClockStatus {
+        response_tx: oneshot::Sender<Result<RouterClockStatus>>,
+    },
+    SetClock {
+        frozen: Option<bool>,
+        advance_ms: Option<u64>,
+        response_tx: oneshot::Sender<Result<RouterClockStatus>>,
+    },
+    RouterContract {
+        response_tx: oneshot::Sender<Result<RouterContractInfo>>,
+    },
+    ResolveTypeRoute {
+        from_type: String,
+        to_type: String,
+        response_tx: oneshot::Sender<Result<Option<TypeRoute>>>,
+    },
+    ResyncPool {
+        pool_id: PoolId,
+        response_tx: oneshot::Sender<Result<PoolResyncReport>>,
+    },
+    IterOrders {
+        pool_id: PoolId,
+        bids: bool,
+        limit: u64,
+        start_order_id: Option<u128>,
+        response_tx: oneshot::Sender<Result<OrderPage>>,
+    },
+    BookLevels {
+        pool_id: PoolId,
+        response_tx: oneshot::Sender<Result<VmBookLevels>>,
+    },
+    ReadAccount {
+        pool_id: PoolId,
+        balance_manager_id: String,
+        response_tx: oneshot::Sender<Result<RouterAccountSnapshot>>,
+    },
+    InspectObject {
+        object_id: String,
+        parent_id: Option<String>,
+        response_tx: oneshot::Sender<Result<Option<RawVmObject>>>,
+    },
+    SessionOpenOrders {
+        session_id: String,
+        response_tx: oneshot::Sender<Result<SessionOpenOrders>>,
+    },
+    ReadEpochVolumes {
+        pool_id: PoolId,
+        epoch: Option<u64>,
+        response_tx: oneshot::Sender<Result<RouterEpochVolumes>>,
+    },
+    ReadPoolState {
+        pool_id: PoolId,
+        response_tx: oneshot::Sender<Result<RouterPoolState>>,
+    },
+    PoolParams {
+        pool_id: PoolId,
+        response_tx: oneshot::Sender<Result<PoolParams>>,
+    },
+    ReadDeepPrice {
+        pool_id: PoolId,
+        response_tx: oneshot::Sender<Result<PoolDeepPrice>>,
+    },
+    HealthCheck {
+        pairs: Option<Vec<(PoolId, PoolId)>>,
+        probe_amounts: Option<Vec<u64>>,
+        response_tx: oneshot::Sender<Result<RouterHealthCheckReport>>,
+    },
+    /// Run `request` with every clock input pinned at `timestamp_ms`
+    AtClock {
+        timestamp_ms: u64,
+        request: Box<RouterRequest>,
+    },
+}
+
+/// Default for `ROUTER_TIMEOUT_MS`
+const DEFAULT_ROUTER_TIMEOUT_MS: u64 = 120_000;
+
+/// How long a handler waits for the router thread (`ROUTER_TIMEOUT_MS`, `0`
+/// waits forever)
+fn router_timeout_from_env() -> Option<Duration> {
+    let timeout_ms = match std::env::var("ROUTER_TIMEOUT_MS") {
+        Ok(raw) => match raw.trim().parse::<u64>() {
+            Ok(timeout_ms) => timeout_ms,
+            Err(_) => {
+                tracing::warn!(
+                    "Invalid ROUTER_TIMEOUT_MS={:?}, defaulting to {}",
+                    raw,
+                    DEFAULT_ROUTER_TIMEOUT_MS
+                );
+                DEFAULT_ROUTER_TIMEOUT_MS
+            }
+        },
+        Err(_) => DEFAULT_ROUTER_TIMEOUT_MS,
+    };
+    (timeout_ms > 0).then(|| Duration::from_millis(timeout_ms))
+}
+
+/// A router call that got no response within `ROUTER_TIMEOUT_MS`
+#[derive(Debug, Clone, Copy)]
+pub struct RouterTimeout {
+    pub timeout_ms: u64,
+}
+
+impl std::fmt::Display for RouterTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "router timed out after {} ms", self.timeout_ms)
+    }
+}
+
+impl std::error::Error for RouterTimeout {}
+
+/// A sequential two-hop fallback whose first leg committed before the second
+/// one failed, leaving the sender holding the intermediate USDC
+#[derive(Debug, Clone)]
+pub struct TwoHopSecondLegFailed {
+    pub from_pool: PoolId,
+    pub to_pool: PoolId,
+    /// The committed A -> USDC leg; `output_amount` is the USDC now held
+    pub first_leg: SingleHopSwapResult,
+    /// Raw VM error of the USDC -> B leg
+    pub error: String,
+}
+
+impl std::fmt::Display for TwoHopSecondLegFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "two-hop sequential fallback ({} -> {}): leg 1 returned {} raw USDC but leg 2 failed: {}",
+            self.from_pool.display_name(),
+            self.to_pool.display_name(),
+            self.first_leg.output_amount,
+            self.error
+        )
+    }
+}
+
+impl std::error::Error for TwoHopSecondLegFailed {}
+
+impl RouterRequest {
+    /// Latency histogram this request is recorded under, if any
+    fn metrics_op(&self) -> Option<RouterOp> {
+        match self {
+            RouterRequest::SingleHop { .. } => Some(RouterOp::QuoteSingleHop),
+            RouterRequest::TwoHop { .. } => Some(RouterOp::QuoteTwoHop),
+            RouterRequest::ThreeHop { .. } => Some(RouterOp::QuoteThreeHop),
+            RouterRequest::ReverseQuote { .. } => Some(RouterOp::QuoteReverse),
+            RouterRequest::TwoSided { .. } => Some(RouterOp::QuoteTwoSided),
+            RouterRequest::BatchQuote { .. } => Some(RouterOp::QuoteBatch),
+            RouterRequest::ExecuteSingleHop { .. } => Some(RouterOp::ExecuteSingleHop),
+            RouterRequest::ExecuteExactOut { .. } => Some(RouterOp::ExecuteExactOut),
+            RouterRequest::ExecuteTwoHop { .. } => Some(RouterOp::ExecuteTwoHop),
+            _ => None,
+        }
+    }
+
+    /// Cached quotes this request may make stale. Simulated swaps roll back,
+    /// and the remaining requests only read pool state.
+    fn quote_cache_invalidation(&self) -> QuoteCacheInvalidation {
+        match self {
+            RouterRequest::ExecuteSingleHop {
+                pool_id, simulate, ..
+            }
+            | RouterRequest::ExecuteExactOut {
+                pool_id, simulate, ..
+            } if !simulate => QuoteCacheInvalidation::Pools(vec![*pool_id]),
+            RouterRequest::ExecuteTwoHop {
+                from_pool,
+                to_pool,
+                simulate,
+                ..
+            } if !simulate => QuoteCacheInvalidation::Pools(vec![*from_pool, *to_pool]),
+            RouterRequest::PlaceLimitOrder { params, .. } => {
+                QuoteCacheInvalidation::Pools(vec![params.pool_id])
+            }
+            RouterRequest::CancelOrder { pool_id, .. }
+            | RouterRequest::WithdrawSettled { pool_id, .. }
+            | RouterRequest::ResyncPool { pool_id, .. } => {
+                QuoteCacheInvalidation::Pools(vec![*pool_id])
+            }
+            RouterRequest::EnsureDebugPool { .. } => {
+                QuoteCacheInvalidation::Pools(vec![PoolId::DebugUsdc])
+            }
+            RouterRequest::PrimeDebugDeepPrice { pool_id, .. } => {
+                QuoteCacheInvalidation::Pools(vec![*pool_id])
+            }
+            // The pool a config names only gets its id once it is created
+            RouterRequest::EnsureDebugPoolWithConfig { .. } => QuoteCacheInvalidation::All,
+            RouterRequest::Reset { .. }
+            | RouterRequest::LoadSnapshot { .. }
+            | RouterRequest::SetEpoch { .. }
+            | RouterRequest::SetClock { .. }
+            | RouterRequest::CancelAllOrders { .. } => QuoteCacheInvalidation::All,
+            _ => QuoteCacheInvalidation::None,
+        }
+    }
+}
+
+/// Cached quotes a router request may make stale
+enum QuoteCacheInvalidation {
+    None,
+    Pools(Vec<PoolId>),
+    All,
+}
+
+/// A request on the router channel and the instant its caller stops waiting
+struct QueuedRequest {
+    request: RouterRequest,
+    deadline: Option<Instant>,
+    claim: Arc<RequestClaim>,
+}
+
+/// Whether a queued request was started by the router thread or given up on
+/// by its caller. Whichever side moves it out of "queued" first wins, so a
+/// swap is never executed after its caller has stopped waiting for it.
+#[derive(Default)]
+struct RequestClaim(AtomicU8);
+
+impl RequestClaim {
+    const QUEUED: u8 = 0;
+    const STARTED: u8 = 1;
+    const ABANDONED: u8 = 2;
+
+    /// Router thread: claim the request for execution; false when the
+    /// caller already gave up on it
+    fn start(&self) -> bool {
+        self.0
+            .compare_exchange(
+                Self::QUEUED,
+                Self::STARTED,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            )
+            .is_ok()
+    }
+
+    /// Caller: give up on a request the router thread has not started;
+    /// false when it is already executing
+    fn abandon(&self) -> bool {
+        self.0
+            .compare_exchange(
+                Self::QUEUED,
+                Self::ABANDONED,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            )
+            .is_ok()
+    }
+}
+
+/// Handle for communicating with the router thread (Send+Sync)
+#[derive(Clone)]
+pub struct RouterHandle {
+    tx: mpsc::Sender<QueuedRequest>,
+    /// Clock timestamp pinned for every request sent through this handle
+    clock_ms: Option<u64>,
+    /// Longest wait for a response; `None` waits forever
+    timeout: Option<Duration>,
+    /// `ok` of the current environment's startup self-check, kept in sync by
+    /// the router thread so readers need not queue behind other requests
+    startup_check_ok: Arc<AtomicBool>,
+    /// Registry the router thread records request latencies into
+    metrics: Arc<Metrics>,
+    /// Single-hop quotes, invalidated by the router thread before any request
+    /// that mutates a pool (`QUOTE_CACHE_TTL_MS`)
+    quote_cache: Arc<QuoteCache<SingleHopQuote>>,
+    /// False for handles from [`RouterHandle::without_quote_cache`]
+    use_quote_cache: bool,
+}
+
+impl RouterHandle {
+    /// Handle whose requests build clock inputs at exactly `timestamp_ms`
+    /// instead of advancing the synthetic clock; `None` restores the
+    /// auto-advancing clock.
+    pub fn at_clock(&self, timestamp_ms: Option<u64>) -> RouterHandle {
+        RouterHandle {
+            tx: self.tx.clone(),
+            clock_ms: timestamp_ms,
+            timeout: self.timeout,
+            startup_check_ok: self.startup_check_ok.clone(),
+            metrics: self.metrics.clone(),
+            quote_cache: self.quote_cache.clone(),
+            use_quote_cache: self.use_quote_cache,
+        }
+    }
+
+    /// Handle whose single-hop quotes always run in MoveVM, neither reading
+    /// nor filling the quote cache.
+    pub fn without_quote_cache(&self) -> RouterHandle {
+        RouterHandle {
+            use_quote_cache: false,
+            ..self.clone()
+        }
+    }
+
+    /// Metrics registry shared with the router thread
+    pub fn metrics(&self) -> Arc<Metrics> {
+        self.metrics.clone()
+    }
+
+    /// Whether the current environment passed its startup self-check, read
+    /// without a round trip to the router thread.
+    pub fn startup_check_ok(&self) -> bool {
+        self.startup_check_ok.load(Ordering::Relaxed)
+    }
+
+    fn send(&self, request: RouterRequest) -> Result<()> {
+        self.enqueue(request).map(|_| ())
+    }
+
+    /// Queue `request`, returning the claim the router thread takes when it
+    /// starts executing it
+    fn enqueue(&self, request: RouterRequest) -> Result<Arc<RequestClaim>> {
+        let request = match self.clock_ms {
+            Some(timestamp_ms) => RouterRequest::AtClock {
+                timestamp_ms,
+                request: Box::new(request),
+            },
+            None => request,
+        };
+        let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+        let claim = Arc::new(RequestClaim::default());
+        self.tx
+            .send(QueuedRequest {
+                request,
+                deadline,
+                claim: claim.clone(),
+            })
+            .map_err(|_| anyhow!("Router thread has shut down"))?;
+        Ok(claim)
+    }
+
+    /// Wait for the router thread's response, failing with [`RouterTimeout`]
+    /// after `ROUTER_TIMEOUT_MS`.
+    ///
+    /// A request still queued at its deadline is dropped by the router
+    /// thread; one already executing runs to completion and its result is
+    /// discarded. Swaps use [`RouterHandle::recv_committed`] instead.
+    async fn recv<T>(&self, response_rx: oneshot::Receiver<T>) -> Result<T> {
+        let response = match self.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, response_rx)
+                .await
+                .map_err(|_| RouterTimeout {
+                    timeout_ms: timeout.as_millis() as u64,
+                })?,
+            None => response_rx.await,
+        };
+        response.map_err(|_| anyhow!("Router thread dropped response channel"))
+    }
+
+    /// Wait for a swap's response. Like [`RouterHandle::recv`], a swap still
+    /// queued at `ROUTER_TIMEOUT_MS` is abandoned and fails with
+    /// [`RouterTimeout`]; one the router thread has already started commits
+    /// in the VM, so it is waited out and its result returned for the caller
+    /// to settle the session with.
+    async fn recv_committed<T>(
+        &self,
+        claim: &RequestClaim,
+        mut response_rx: oneshot::Receiver<T>,
+    ) -> Result<T> {
+        if let Some(timeout) = self.timeout {
+            match tokio::time::timeout(timeout, &mut response_rx).await {
+                Ok(response) => {
+                    return response.map_err(|_| anyhow!("Router thread dropped response channel"))
+                }
+                Err(_) if claim.abandon() => {
+                    return Err(RouterTimeout {
+                        timeout_ms: timeout.as_millis() as u64,
+                    }
+                    .into())
+                }
+                Err(_) => {
+                    tracing::warn!(
+                        "Router: swap still executing after {}ms, waiting for it to commit",
+                        timeout.as_millis()
+                    );
+                }
+            }
+        }
+        response_rx
+            .await
+            .map_err(|_| anyhow!("Router thread dropped response channel"))
+    }
+
+    /// Request a single-hop quote from the router thread.
+    ///
+    /// `is_sell_base = true` means base -> USDC quote via
+    /// `pool::get_quote_quantity_out`.
+    /// `is_sell_base = false` means USDC -> base quote via
+    /// `pool::get_base_quantity_out`.
+    ///
+    /// Served from the quote cache when an identical quote ran within
+    /// `QUOTE_CACHE_TTL_MS` and no request has mutated the pool since.
+    pub async fn quote_single_hop(
+        &self,
+        pool_id: PoolId,
+        input_amount: u64,
+        is_sell_base: bool,
+    ) -> Result<SingleHopQuote> {
+        let cache = (self.use_quote_cache && self.quote_cache.is_enabled()).then(|| {
+            let key = QuoteCacheKey {
+                pool_id,
+                input_amount,
+                is_sell_base,
+                clock_ms: self.clock_ms,
+            };
+            (key, self.quote_cache.generation(pool_id))
+        });
+        if let Some((key, _)) = &cache {
+            let cached = self.quote_cache.get(key);
+            self.metrics.record_quote_cache_lookup(cached.is_some());
+            if let Some(quote) = cached {
+                return Ok(quote);
+            }
+        }
+
+        let (response_tx, response_rx) = oneshot::channel();
+
+        self.send(RouterRequest::SingleHop {
+            pool_id,
+            input_amount,
+            is_sell_base,
+            response_tx,
+        })?;
+
+        let quote = self.recv(response_rx).await??;
+        if let Some((key, generation)) = cache {
+            self.quote_cache.insert(key, generation, quote.clone());
+        }
+        Ok(quote)
+    }
+
+    /// Find the smallest single-hop input whose quote yields at least
+    /// `desired_output`, with the output actually quoted at that input.
+    ///
+    /// Errors starting with `EXACT_OUT_UNFILLABLE` mean the pool cannot
+    /// deliver `desired_output` at any input size.
+    pub async fn reverse_quote(
+        &self,
+        pool_id: PoolId,
+        desired_output: u64,
+        is_sell_base: bool,
+    ) -> Result<ReverseQuote> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        self.send(RouterRequest::ReverseQuote {
+            pool_id,
+            desired_output,
+            is_sell_base,
+            response_tx,
+        })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Quote base -> USDC for `base_amount`, then USDC -> base for the
+    /// proceeds, in a single router-thread round trip.
+    pub async fn quote_two_sided(&self, pool_id: PoolId, base_amount: u64) -> Result<TwoSidedQuote> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        self.send(RouterRequest::TwoSided {
+            pool_id,
+            base_amount,
+            response_tx,
+        })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Request a two-hop quote from the router thread
+    pub async fn quote_two_hop(
+        &self,
+        from_pool: PoolId,
+        to_pool: PoolId,
+        input_amount: u64,
+    ) -> Result<TwoHopQuote> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        self.send(RouterRequest::TwoHop {
+            from_pool,
+            to_pool,
+            input_amount,
+            response_tx,
+        })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Request a three-hop quote (e.g. WAL -> USDC -> SUI -> DEEP) from the router thread
+    pub async fn quote_three_hop(
+        &self,
+        hops: [RouteHop; 3],
+        input_amount: u64,
+    ) -> Result<ThreeHopQuote> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        self.send(RouterRequest::ThreeHop {
+            hops,
+            input_amount,
+            response_tx,
+        })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Quote every item in one router-thread round trip.
+    ///
+    /// Results are in input order; one failed quote does not fail the others.
+    pub async fn quote_batch(
+        &self,
+        quotes: Vec<BatchQuoteInput>,
+    ) -> Result<Vec<Result<BatchQuote>>> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        self.send(RouterRequest::BatchQuote {
+            quotes,
+            response_tx,
+        })?;
+
+        self.recv(response_rx).await
+    }
+
+    /// Execute a direct swap through MoveVM pool::swap_exact_*.
+    ///
+    /// The swap aborts when it would return less than `min_out` (0 disables
+    /// the check). `sender` optionally overrides the VM sender for this swap
+    /// only. With `simulate`, every VM change the swap made is rolled back.
+    pub async fn execute_single_hop_swap(
+        &self,
+        pool_id: PoolId,
+        input_amount: u64,
+        deep_amount: u64,
+        is_sell_base: bool,
+        min_out: u64,
+        sender: Option<String>,
+        simulate: bool,
+    ) -> Result<SingleHopSwapResult> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        let claim = self.enqueue(RouterRequest::ExecuteSingleHop {
+            pool_id,
+            input_amount,
+            deep_amount,
+            is_sell_base,
+            min_out,
+            simulate,
+            sender,
+            response_tx,
+        })?;
+
+        self.recv_committed(&claim, response_rx).await?
+    }
+
+    /// Execute a direct swap that delivers at least `output_amount`.
+    ///
+    /// The router searches for the smallest input (capped at `max_input` and
+    /// the VM reserve) whose quote reaches `output_amount`, then swaps it with
+    /// `output_amount` as min_out. Errors starting with `EXACT_OUT_UNFILLABLE`
+    /// report the largest output the cap allows. `simulate` rolls the swap back.
+    pub async fn execute_exact_out_swap(
+        &self,
+        pool_id: PoolId,
+        output_amount: u64,
+        max_input: u64,
+        deep_amount: u64,
+        is_sell_base: bool,
+        sender: Option<String>,
+        simulate: bool,
+    ) -> Result<ExactOutSwapResult> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        let claim = self.enqueue(RouterRequest::ExecuteExactOut {
+            pool_id,
+            output_amount,
+            max_input,
+            deep_amount,
+            is_sell_base,
+            simulate,
+            sender,
+            response_tx,
+        })?;
+
+        self.recv_committed(&claim, response_rx).await?
+    }
+
+    /// Execute a two-hop swap through MoveVM (A -> USDC -> B).
+    ///
+    /// `min_intermediate` bounds the USDC out of leg 1 and `min_final` the B
+    /// output of leg 2 (0 disables either check). `sender` optionally
+    /// overrides the VM sender for this swap only. `simulate` rolls the swap
+    /// back. If the sequential fallback commits leg 1 and leg 2 then fails,
+    /// the error is a [`TwoHopSecondLegFailed`].
+    pub async fn execute_two_hop_swap(
+        &self,
+        from_pool: PoolId,
+        to_pool: PoolId,
+        input_amount: u64,
+        deep_amount: u64,
+        min_intermediate: u64,
+        min_final: u64,
+        sender: Option<String>,
+        simulate: bool,
+    ) -> Result<TwoHopSwapResult> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        let claim = self.enqueue(RouterRequest::ExecuteTwoHop {
+            from_pool,
+            to_pool,
+            input_amount,
+            deep_amount,
+            min_intermediate,
+            min_final,
+            simulate,
+            sender,
+            response_tx,
+        })?;
+
+        self.recv_committed(&claim, response_rx).await?
+    }
+
+    /// Gas a swap of `input_amount` along `route` would use, measured by
+    /// executing it and rolling the environment back.
+    pub async fn estimate_swap_gas(&self, route: SwapGasRoute, input_amount: u64) -> Result<u64> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::EstimateSwapGas {
+            route,
+            input_amount,
+            response_tx,
+        })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Ensure the first debug pool (`PoolId::DebugUsdc`, DBG/USDC unless
+    /// created with another config) exists and is seeded in the VM.
+    pub async fn ensure_debug_pool(&self) -> Result<DebugPoolInfo> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::EnsureDebugPool { response_tx })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Re-run a debug pool's `deep_price` bootstrap against the reference pools.
+    pub async fn prime_debug_deep_price(
+        &self,
+        pool_id: PoolId,
+    ) -> Result<DeepPriceBootstrapReport> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::PrimeDebugDeepPrice {
+            pool_id,
+            response_tx,
+        })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Ensure the debug pool for `config.token_symbol` exists with
+    /// caller-provided config. Each symbol gets its own pool, so pools for
+    /// other symbols are left as they are.
+    ///
+    /// If the symbol's pool already exists with different config, this returns
+    /// an error because DeepBook allows only one pool per token pair in this VM
+    /// runtime. Reset the router to reconfigure.
+    pub async fn ensure_debug_pool_with_config(
+        &self,
+        config: DebugPoolCreateConfig,
+    ) -> Result<DebugPoolInfo> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::EnsureDebugPoolWithConfig {
+            config,
+            response_tx,
+        })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Split and transfer a faucet coin via real MoveVM PTB execution.
+    ///
+    /// `sender` optionally overrides the VM sender (and faucet recipient).
+    pub async fn vm_faucet(
+        &self,
+        coin_type: String,
+        amount: u64,
+        sender: Option<String>,
+    ) -> Result<VmFaucetResult> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::VmFaucet {
+            coin_type,
+            amount,
+            sender,
+            response_tx,
+        })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Place a limit order through the balance manager owned by `session_id`.
+    ///
+    /// The session's first order creates the balance manager; later orders reuse it.
+    pub async fn place_limit_order(
+        &self,
+        session_id: String,
+        params: LimitOrderParams,
+    ) -> Result<LimitOrderResult> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::PlaceLimitOrder {
+            session_id,
+            params,
+            response_tx,
+        })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Cancel a resting order placed through `session_id`'s balance manager
+    /// and withdraw the refunds back into the VM reserves.
+    ///
+    /// `balance_manager_id`, when given, must be the session's.
+    pub async fn cancel_order(
+        &self,
+        session_id: String,
+        pool_id: PoolId,
+        balance_manager_id: Option<String>,
+        order_id: u128,
+    ) -> Result<CancelOrderResult> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::CancelOrder {
+            session_id,
+            pool_id,
+            balance_manager_id,
+            order_id,
+            response_tx,
+        })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Cancel every open order of `session_id`'s balance manager, reporting
+    /// the orders that failed to cancel alongside the cancelled ones.
+    pub async fn cancel_all_orders(&self, session_id: String) -> Result<CancelAllOrdersResult> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::CancelAllOrders {
+            session_id,
+            response_tx,
+        })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Settle `session_id`'s account in `pool_id` into its balance manager and
+    /// withdraw the manager's balances back into the VM reserves.
+    pub async fn withdraw_settled(
+        &self,
+        session_id: String,
+        pool_id: PoolId,
+    ) -> Result<WithdrawSettledResult> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::WithdrawSettled {
+            session_id,
+            pool_id,
+            response_tx,
+        })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Return live reserve coin status including bootstrap checkpoint provenance.
+    pub async fn reserve_status(&self) -> Result<Vec<RouterReserveCoinCheck>> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::ReserveStatus { response_tx })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Return the current VM sender address.
+    pub async fn sender(&self) -> Result<String> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::GetSender { response_tx })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Set the default VM sender address used by swaps and faucets.
+    ///
+    /// Returns the previous sender address.
+    pub async fn set_sender(&self, sender: String) -> Result<String> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::SetSender {
+            sender,
+            response_tx,
+        })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Return the synthetic clock's current timestamp, step, and frozen flag.
+    pub async fn clock_status(&self) -> Result<RouterClockStatus> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::ClockStatus { response_tx })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Freeze/unfreeze the synthetic clock and/or advance it by `advance_ms`.
+    pub async fn set_clock(
+        &self,
+        frozen: Option<bool>,
+        advance_ms: Option<u64>,
+    ) -> Result<RouterClockStatus> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::SetClock {
+            frozen,
+            advance_ms,
+            response_tx,
+        })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Return the router startup self-check report.
+    pub async fn startup_check(&self) -> Result<RouterStartupCheckReport> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::StartupCheck { response_tx })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Re-run the startup self-check against the live environment and cache
+    /// the new report. A failing check is returned with `ok: false`.
+    pub async fn refresh_startup_check(&self) -> Result<RouterStartupCheckReport> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::RefreshStartupCheck { response_tx })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Rebuild the VM environment from the original pool state files and
+    /// redeploy the router contract, discarding every swap, order, faucet
+    /// draw and the debug pool. Returns the fresh startup self-check report.
+    ///
+    /// The router thread swaps the environment in between requests, so each
+    /// request sees either the old or the new state. If the rebuild fails
+    /// the current environment is kept.
+    pub async fn reset(&self) -> Result<RouterStartupCheckReport> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::Reset { response_tx })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Move the simulation epoch forward to `epoch`.
+    ///
+    /// Every loaded pool gets a zero `history::Volumes` entry for the new
+    /// epoch, carrying its current trade params, when it has none yet.
+    pub async fn set_epoch(&self, epoch: u64) -> Result<EpochAdvanceReport> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::SetEpoch { epoch, response_tx })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Write the VM environment to a snapshot file at `path`.
+    ///
+    /// The debug pool and session balance managers are not captured.
+    pub async fn save_snapshot(&self, path: PathBuf) -> Result<RouterSnapshotInfo> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::SaveSnapshot { path, response_tx })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Replace the VM environment with the snapshot at `path`.
+    ///
+    /// Like [`RouterHandle::reset`], the swap happens between requests and a
+    /// failed load keeps the current environment; the debug pool must be
+    /// re-created afterwards.
+    pub async fn load_snapshot(&self, path: PathBuf) -> Result<RouterSnapshotInfo> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::LoadSnapshot { path, response_tx })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Router contract deployment details recorded at startup.
+    pub async fn router_contract(&self) -> Result<RouterContractInfo> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::RouterContract { response_tx })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Resolve a route by matching full coin type strings against the type
+    /// parameters of pools loaded in the router.
+    ///
+    /// Returns `Ok(None)` when no loaded pool (or pool pair) matches.
+    pub async fn resolve_type_route(
+        &self,
+        from_type: String,
+        to_type: String,
+    ) -> Result<Option<TypeRoute>> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::ResolveTypeRoute {
+            from_type,
+            to_type,
+            response_tx,
+        })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Read a balance manager's settled/owed balances and rebates in a pool.
+    pub async fn read_account(
+        &self,
+        pool_id: PoolId,
+        balance_manager_id: String,
+    ) -> Result<RouterAccountSnapshot> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::ReadAccount {
+            pool_id,
+            balance_manager_id,
+            response_tx,
+        })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// List the open orders of a session's balance manager in every pool.
+    pub async fn session_open_orders(&self, session_id: String) -> Result<SessionOpenOrders> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::SessionOpenOrders {
+            session_id,
+            response_tx,
+        })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Read a pool's historic volumes for an epoch (defaults to the pool's current epoch).
+    pub async fn read_epoch_volumes(
+        &self,
+        pool_id: PoolId,
+        epoch: Option<u64>,
+    ) -> Result<RouterEpochVolumes> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::ReadEpochVolumes {
+            pool_id,
+            epoch,
+            response_tx,
+        })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Decode a pool's key `PoolInner` state in one read-only PTB.
+    pub async fn read_pool_state(&self, pool_id: PoolId) -> Result<RouterPoolState> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::ReadPoolState {
+            pool_id,
+            response_tx,
+        })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// A pool's book sizing, trade fees and whitelist status, cached by the
+    /// router after the first read.
+    pub async fn pool_params(&self, pool_id: PoolId) -> Result<PoolParams> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::PoolParams {
+            pool_id,
+            response_tx,
+        })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// A pool's current order deep price and whether it pays DEEP fees at all.
+    pub async fn read_deep_price(&self, pool_id: PoolId) -> Result<PoolDeepPrice> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::ReadDeepPrice {
+            pool_id,
+            response_tx,
+        })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Force a full dynamic-field reconciliation pass on a loaded pool.
+    pub async fn resync_pool(&self, pool_id: PoolId) -> Result<PoolResyncReport> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::ResyncPool {
+            pool_id,
+            response_tx,
+        })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Decode one page of a pool's resting bids or asks.
+    pub async fn iter_orders(
+        &self,
+        pool_id: PoolId,
+        bids: bool,
+        limit: u64,
+        start_order_id: Option<u128>,
+    ) -> Result<OrderPage> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::IterOrders {
+            pool_id,
+            bids,
+            limit,
+            start_order_id,
+            response_tx,
+        })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Every resting order on `pool_id` aggregated into price levels, as the
+    /// book stands in the VM after the swaps and orders committed so far.
+    pub async fn book_levels(&self, pool_id: PoolId) -> Result<VmBookLevels> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::BookLevels {
+            pool_id,
+            response_tx,
+        })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Raw BCS of a loaded object, or of a dynamic field when `parent_id` is set.
+    pub async fn inspect_object(
+        &self,
+        object_id: String,
+        parent_id: Option<String>,
+    ) -> Result<Option<RawVmObject>> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::InspectObject {
+            object_id,
+            parent_id,
+            response_tx,
+        })?;
+
+        self.recv(response_rx).await?
+    }
+
+    /// Run the two-hop quote health check now.
+    ///
+    /// `pairs` and `probe_amounts` override the configured values for this run only.
+    pub async fn health_check(
+        &self,
+        pairs: Option<Vec<(PoolId, PoolId)>>,
+        probe_amounts: Option<Vec<u64>>,
+    ) -> Result<RouterHealthCheckReport> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.send(RouterRequest::HealthCheck {
+            pairs,
+            probe_amounts,
+            response_tx,
+        })?;
+
+        self.recv(response_rx).await?
+    }
+}
+
+/// Spawn the router thread and return a handle for communication.
+///
+/// The thread:
+/// 1. Creates a SimulationEnvironment
+/// 2. Loads all packages via gRPC
+/// 3. Loads all pool states from JSONL files
+/// 4. Creates a synthetic Clock object
+/// 5. Compiles and deploys the router contract
+/// 6. Executes a local-VM router health check
+/// 7. Signals ready
+/// 8. Loops processing quote requests
+pub fn spawn_router_thread(
+    pool_files: Vec<(PoolId, String)>,
+) -> (RouterHandle, oneshot::Receiver<Result<()>>) {
+    let (tx, rx) = mpsc::channel::<QueuedRequest>();
+    let (ready_tx, ready_rx) = oneshot::channel::<Result<()>>();
+    let startup_check_ok = Arc::new(AtomicBool::new(false));
+    let metrics = Arc::new(Metrics::new());
+    let quote_cache = Arc::new(QuoteCache::from_env());
+
+    let thread_startup_check_ok = startup_check_ok.clone();
+    let thread_metrics = metrics.clone();
+    let thread_quote_cache = quote_cache.clone();
+    std::thread::spawn(move || {
+        router_thread_main(
+            rx,
+            ready_tx,
+            pool_files,
+            thread_startup_check_ok,
+            thread_metrics,
+            thread_quote_cache,
+        );
+    });
+
+    (
+        RouterHandle {
+            tx,
+            clock_ms: None,
+            timeout: router_timeout_from_env(),
+            startup_check_ok,
+            metrics,
+            quote_cache,
+            use_quote_cache: true,
+        },
+        ready_rx,
+    )
+}
+
+fn router_thread_main(
+    rx: mpsc::Receiver<QueuedRequest>,
+    ready_tx: oneshot::Sender<Result<()>>,
+    pool_files: Vec<(PoolId, String)>,
+    startup_check_ok: Arc<AtomicBool>,
+    metrics: Arc<Metrics>,
+    quote_cache: Arc<QuoteCache<SingleHopQuote>>,
+) {
+    let result = setup_router_env(&pool_files);
+
+    match result {
+        Ok(mut env_state) => {
+            startup_check_ok.store(env_state.startup_check.ok, Ordering::Relaxed);
+            let _ = ready_tx.send(Ok(()));
+            tracing::info!("Router thread ready, processing quote requests");
+
+            // Process requests
+            while let Ok(QueuedRequest {
+                request: req,
+                deadline,
+                claim,
+            }) = rx.recv()
+            {
+                if deadline.is_some_and(|deadline| Instant::now() >= deadline) || !claim.start() {
+                    tracing::warn!("Router: dropping a request that timed out while queued");
+                    continue;
+                }
+                let req = match req {
+                    RouterRequest::AtClock {
+                        timestamp_ms,
+                        request,
+                    } => {
+                        env_state.clock_override_ms = Some(timestamp_ms);
+                        *request
+                    }
+                    req => {
+                        env_state.clock_override_ms = None;
+                        req
+                    }
+                };
+                // Invalidate before running, so a caller that sees this
+                // request's response can no longer read a stale quote.
+                match req.quote_cache_invalidation() {
+                    QuoteCacheInvalidation::None => {}
+                    QuoteCacheInvalidation::Pools(pools) => quote_cache.invalidate(&pools),
+                    QuoteCacheInvalidation::All => quote_cache.invalidate_all(),
+                }
+                let timed = req.metrics_op().map(|op| (op, Instant::now()));
+                match req {
+                    RouterRequest::TwoHop {
+                        from_pool,
+                        to_pool,
+                        input_amount,
+                        response_tx,
+                    } => {
+                        let result =
+                            execute_two_hop_quote(&mut env_state, from_pool, to_pool, input_amount);
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::ThreeHop {
+                        hops,
+                        input_amount,
+                        response_tx,
+                    } => {
+                        let result = execute_three_hop_quote(&mut env_state, hops, input_amount);
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::SingleHop {
+                        pool_id,
+                        input_amount,
+                        is_sell_base,
+                        response_tx,
+                    } => {
+                        let result = execute_single_hop_quote(
+                            &mut env_state,
+                            pool_id,
+                            input_amount,
+                            is_sell_base,
+                        );
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::ReverseQuote {
+                        pool_id,
+                        desired_output,
+                        is_sell_base,
+                        response_tx,
+                    } => {
+                        let result = execute_reverse_quote(
+                            &mut env_state,
+                            pool_id,
+                            desired_output,
+                            is_sell_base,
+                        );
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::TwoSided {
+                        pool_id,
+                        base_amount,
+                        response_tx,
+                    } => {
+                        let result = execute_two_sided_quote(&mut env_state, pool_id, base_amount);
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::BatchQuote {
+                        quotes,
+                        response_tx,
+                    } => {
+                        let results = execute_batch_quote(&mut env_state, &quotes);
+                        let _ = response_tx.send(results);
+                    }
+                    RouterRequest::ExecuteSingleHop {
+                        pool_id,
+                        input_amount,
+                        deep_amount,
+                        is_sell_base,
+                        min_out,
+                        simulate,
+                        sender,
+                        response_tx,
+                    } => {
+                        let result =
+                            with_sender_override(&mut env_state, sender.as_deref(), |state| {
+                                with_rollback(state, simulate, |state| {
+                                    execute_single_hop_swap(
+                                        state,
+                                        pool_id,
+                                        input_amount,
+                                        deep_amount,
+                                        is_sell_base,
+                                        min_out,
+                                    )
+                                })
+                            });
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::ExecuteExactOut {
+                        pool_id,
+                        output_amount,
+                        max_input,
+                        deep_amount,
+                        is_sell_base,
+                        simulate,
+                        sender,
+                        response_tx,
+                    } => {
+                        let result =
+                            with_sender_override(&mut env_state, sender.as_deref(), |state| {
+                                with_rollback(state, simulate, |state| {
+                                    execute_exact_out_swap(
+                                        state,
+                                        pool_id,
+                                        output_amount,
+                                        max_input,
+                                        deep_amount,
+                                        is_sell_base,
+                                    )
+                                })
+                            });
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::ExecuteTwoHop {
+                        from_pool,
+                        to_pool,
+                        input_amount,
+                        deep_amount,
+                        min_intermediate,
+                        min_final,
+                        simulate,
+                        sender,
+                        response_tx,
+                    } => {
+                        let result =
+                            with_sender_override(&mut env_state, sender.as_deref(), |state| {
+                                with_rollback(state, simulate, |state| {
+                                    execute_two_hop_swap(
+                                        state,
+                                        from_pool,
+                                        to_pool,
+                                        input_amount,
+                                        deep_amount,
+                                        min_intermediate,
+                                        min_final,
+                                    )
+                                })
+                            });
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::EstimateSwapGas {
+                        route,
+                        input_amount,
+                        response_tx,
+                    } => {
+                        let result = estimate_swap_gas(&mut env_state, route, input_amount);
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::EnsureDebugPool { response_tx } => {
+                        let result = ensure_debug_pool(&mut env_state);
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::PrimeDebugDeepPrice {
+                        pool_id,
+                        response_tx,
+                    } => {
+                        let result = if env_state
+                            .debug_pools
+                            .values()
+                            .any(|info| info.pool_id == pool_id)
+                        {
+                            prime_debug_pool_deep_price(&mut env_state, pool_id)
+                        } else {
+                            Err(anyhow!(
+                                "Debug pool {} has not been created",
+                                pool_id.as_str()
+                            ))
+                        };
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::EnsureDebugPoolWithConfig {
+                        config,
+                        response_tx,
+                    } => {
+                        let result = ensure_debug_pool_with_config(&mut env_state, config);
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::VmFaucet {
+                        coin_type,
+                        amount,
+                        sender,
+                        response_tx,
+                    } => {
+                        let result =
+                            with_sender_override(&mut env_state, sender.as_deref(), |state| {
+                                execute_vm_faucet(state, &coin_type, amount)
+                            });
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::PlaceLimitOrder {
+                        session_id,
+                        params,
+                        response_tx,
+                    } => {
+                        let result = place_limit_order(&mut env_state, &session_id, &params);
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::CancelOrder {
+                        session_id,
+                        pool_id,
+                        balance_manager_id,
+                        order_id,
+                        response_tx,
+                    } => {
+                        let result = cancel_order(
+                            &mut env_state,
+                            &session_id,
+                            pool_id,
+                            balance_manager_id.as_deref(),
+                            order_id,
+                        );
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::CancelAllOrders {
+                        session_id,
+                        response_tx,
+                    } => {
+                        let result = cancel_all_orders(&mut env_state, &session_id);
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::WithdrawSettled {
+                        session_id,
+                        pool_id,
+                        response_tx,
+                    } => {
+                        let result = withdraw_settled(&mut env_state, &session_id, pool_id);
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::ReserveStatus { response_tx } => {
+                        let _ = response_tx.send(Ok(reserve_coin_checks(&env_state)));
+                    }
+                    RouterRequest::GetSender { response_tx } => {
+                        let _ = response_tx.send(Ok(env_state.env.sender().to_hex_literal()));
+                    }
+                    RouterRequest::SetSender {
+                        sender,
+                        response_tx,
+                    } => {
+                        let result = parse_sender_address(&sender).map(|addr| {
+                            let previous = env_state.env.sender();
+                            env_state.env.set_sender(addr);
+                            tracing::info!(
+                                "Router: VM sender changed {} -> {}",
+                                previous.to_hex_literal(),
+                                addr.to_hex_literal()
+                            );
+                            previous.to_hex_literal()
+                        });
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::ClockStatus { response_tx } => {
+                        let _ = response_tx.send(Ok(env_state.clock_status()));
+                    }
+                    RouterRequest::SetClock {
+                        frozen,
+                        advance_ms,
+                        response_tx,
+                    } => {
+                        if let Some(frozen) = frozen {
+                            env_state.clock_frozen = frozen;
+                        }
+                        if let Some(advance_ms) = advance_ms {
+                            env_state.next_clock_timestamp_ms =
+                                env_state.next_clock_timestamp_ms.saturating_add(advance_ms);
+                        }
+                        let status = env_state.clock_status();
+                        tracing::info!(
+                            "Router: clock set to {}ms (frozen={})",
+                            status.now_ms,
+                            status.frozen
+                        );
+                        let _ = response_tx.send(Ok(status));
+                    }
+                    RouterRequest::StartupCheck { response_tx } => {
+                        let _ = response_tx.send(Ok(env_state.startup_check.clone()));
+                    }
+                    RouterRequest::RefreshStartupCheck { response_tx } => {
+                        let result = startup_self_check_report(&mut env_state).map(|report| {
+                            if !report.ok {
+                                tracing::warn!(
+                                    "Router: startup self-check refresh failed: {}",
+                                    report.errors.join(" | ")
+                                );
+                            }
+                            startup_check_ok.store(report.ok, Ordering::Relaxed);
+                            env_state.startup_check = report.clone();
+                            report
+                        });
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::SetEpoch { epoch, response_tx } => {
+                        let result = set_epoch(&mut env_state, epoch);
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::Reset { response_tx } => {
+                        tracing::info!("Router: resetting environment from pool state files");
+                        let result = match setup_router_env(&pool_files) {
+                            Ok(fresh) => {
+                                env_state = fresh;
+                                startup_check_ok
+                                    .store(env_state.startup_check.ok, Ordering::Relaxed);
+                                tracing::info!("Router: environment reset complete");
+                                Ok(env_state.startup_check.clone())
+                            }
+                            Err(e) => {
+                                tracing::warn!(
+                                    "Router: reset failed, keeping current environment: {}",
+                                    e
+                                );
+                                Err(e)
+                            }
+                        };
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::SaveSnapshot { path, response_tx } => {
+                        let result = write_router_snapshot(&env_state, &pool_files, &path);
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::LoadSnapshot { path, response_tx } => {
+                        let result = match restore_router_snapshot(&path, &pool_files) {
+                            Ok((fresh, info)) => {
+                                env_state = fresh;
+                                startup_check_ok
+                                    .store(env_state.startup_check.ok, Ordering::Relaxed);
+                                Ok(info)
+                            }
+                            Err(e) => {
+                                tracing::warn!(
+                                    "Router: snapshot load failed, keeping current environment: {}",
+                                    e
+                                );
+                                Err(e)
+                            }
+                        };
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::RouterContract { response_tx } => {
+                        let _ = response_tx.send(Ok(env_state.router_contract.clone()));
+                    }
+                    RouterRequest::ResolveTypeRoute {
+                        from_type,
+                        to_type,
+                        response_tx,
+                    } => {
+                        let result = resolve_type_route(&env_state, &from_type, &to_type);
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::ResyncPool {
+                        pool_id,
+                        response_tx,
+                    } => {
+                        let result = resync_pool_state(&mut env_state, pool_id);
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::IterOrders {
+                        pool_id,
+                        bids,
+                        limit,
+                        start_order_id,
+                        response_tx,
+                    } => {
+                        let result =
+                            fetch_iter_orders(&mut env_state, pool_id, bids, limit, start_order_id);
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::BookLevels {
+                        pool_id,
+                        response_tx,
+                    } => {
+                        let _ = response_tx.send(read_book_levels(&mut env_state, pool_id));
+                    }
+                    RouterRequest::InspectObject {
+                        object_id,
+                        parent_id,
+                        response_tx,
+                    } => {
+                        let result = inspect_object(&env_state, &object_id, parent_id.as_deref());
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::ReadAccount {
+                        pool_id,
+                        balance_manager_id,
+                        response_tx,
+                    } => {
+                        let result = read_account_snapshot(&env_state, pool_id, &balance_manager_id);
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::SessionOpenOrders {
+                        session_id,
+                        response_tx,
+                    } => {
+                        let result = session_open_orders(&mut env_state, &session_id);
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::ReadEpochVolumes {
+                        pool_id,
+                        epoch,
+                        response_tx,
+                    } => {
+                        let result = read_epoch_volumes(&env_state, pool_id, epoch);
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::ReadPoolState {
+                        pool_id,
+                        response_tx,
+                    } => {
+                        let result = read_pool_state(&mut env_state, pool_id);
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::PoolParams {
+                        pool_id,
+                        response_tx,
+                    } => {
+                        let result = read_pool_params(&mut env_state, pool_id);
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::ReadDeepPrice {
+                        pool_id,
+                        response_tx,
+                    } => {
+                        let result = read_deep_price(&mut env_state, pool_id);
+                        let _ = response_tx.send(result);
+                    }
+                    RouterRequest::HealthCheck {
+                        pairs,
+                        probe_amounts,
+                        response_tx,
+                    } => {
+                        let mut config = env_state.health_check.clone();
+                        if let Some(pairs) = pairs {
+                            config.pairs = pairs;
+                        }
+                        if let Some(probe_amounts) = probe_amounts {
+                            config.probe_amounts = probe_amounts;
+                        }
+                        let report = run_health_probes(&mut env_state, &config);
+                        let _ = response_tx.send(Ok(report));
+                    }
+                    RouterRequest::AtClock { .. } => {
+                        tracing::warn!("Router: ignoring nested clock override");
+                    }
+                }
+                if let Some((op, started)) = timed {
+                    metrics.observe_router(op, started.elapsed());
+                }
+            }
+
+            tracing::info!("Router thread shutting down (channel closed)");
+        }
+        Err(e) => {
+            tracing::error!("Router thread setup failed: {}", e);
+            let _ = ready_tx.send(Err(e));
+        }
+    }
+}
+
+/// Internal state for the router environment
+struct RouterEnvState {
+    env: SimulationEnvironment,
+    pool_cache: HashMap<PoolId, PoolCacheEntry>,
+    coin_reserve_cache: HashMap<String, AccountAddress>,
+    /// Bootstrap provenance for entries in `coin_reserve_cache`, keyed by coin type
+    reserve_provenance: HashMap<String, ReserveCoinProvenance>,
+    /// Debug token treasury caps, keyed by token symbol
+    debug_treasury_ids: HashMap<String, AccountAddress>,
+    /// Packages `ensure_debug_token_package` published in this environment
+    debug_token_packages: HashSet<AccountAddress>,
+    router_deployed: bool,
+    router_contract: RouterContractInfo,
+    startup_check: RouterStartupCheckReport,
+    next_clock_timestamp_ms: u64,
+    /// Timestamp pinned by the request being processed (`RouterHandle::at_clock`);
+    /// clock inputs use it and leave `next_clock_timestamp_ms` untouched
+    clock_override_ms: Option<u64>,
+    /// Timestamp of the last `add_deep_price_point` that succeeded, so
+    /// spacing is checked across requests and explicit timestamps
+    last_deep_price_point_ms: Option<u64>,
+    /// Synthetic clock advance per clock input (`ROUTER_CLOCK_STEP_MS`)
+    clock_step_ms: u64,
+    /// Skip the per-input advance so every clock input repeats the same
+    /// timestamp (`ROUTER_CLOCK_FROZEN`)
+    clock_frozen: bool,
+    /// Fund single-hop swaps on whitelisted pools with a zero DEEP coin
+    /// (`SWAP_SKIP_DEEP_FOR_WHITELISTED`)
+    skip_deep_for_whitelisted: bool,
+    /// Extra atomic two-hop attempts before the sequential fallback
+    /// (`SWAP_TWO_HOP_ATOMIC_RETRIES`)
+    two_hop_atomic_retries: u32,
+    health_check: RouterHealthCheckConfig,
+    /// Created debug pools, keyed by token symbol
+    debug_pools: HashMap<String, DebugPoolInfo>,
+    debug_log_sampler: DebugLogSampler,
+    refund_join_policy: RefundJoinPolicy,
+    pool_version_check: PoolVersionCheck,
+    loaded_packages: Vec<RouterPackageCheck>,
+    /// Balance managers created by limit orders, keyed by session id
+    session_balance_managers: HashMap<String, SessionBalanceManager>,
+    /// Packages deployed at setup, kept for snapshots
+    snapshot_packages: Vec<SnapshotPackage>,
+    /// Every parent a dynamic field was set under, so snapshots can list them
+    dynamic_field_parents: HashSet<AccountAddress>,
+    /// Simulation epoch set from the loaded pool states
+    epoch: Option<u64>,
+    /// Layouts of the fetched packages, used to decode event payloads
+    bcs_converter: JsonToBcsConverter,
+}
+
+/// A session's limit-order balance manager and the VM address that owns it
+#[derive(Debug, Clone, Copy)]
+struct SessionBalanceManager {
+    id: AccountAddress,
+    owner: AccountAddress,
+}
+
+/// Sampling control for verbose debug-pool diagnostics.
+///
+/// Configured via `ROUTER_DEBUG_SAMPLE_RATE`:
+/// - `1` (default): log every operation
+/// - `N > 1`: log every Nth routine operation and every Nth anomaly
+/// - `0`: suppress routine detail; anomalies are still logged
+///
+/// The first anomaly is always logged so failures keep their diagnostics.
+/// What a single-hop swap does when joining refunds back into the reserve
+/// coins fails, configured via `SWAP_REFUND_JOIN_FALLBACK`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RefundJoinPolicy {
+    /// Fail the swap with a reserve-reconciliation error (default)
+    Error,
+    /// Re-run the swap transferring refunds to the sender instead
+    Transfer,
+}
+
+impl RefundJoinPolicy {
+    fn from_env() -> Self {
+        match std::env::var("SWAP_REFUND_JOIN_FALLBACK") {
+            Ok(raw) => match raw.trim().to_ascii_lowercase().as_str() {
+                "error" | "" => RefundJoinPolicy::Error,
+                "transfer" => RefundJoinPolicy::Transfer,
+                _ => {
+                    tracing::warn!(
+                        "Router: invalid SWAP_REFUND_JOIN_FALLBACK={:?}, defaulting to error",
+                        raw
+                    );
+                    RefundJoinPolicy::Error
+                }
+            },
+            Err(_) => RefundJoinPolicy::Error,
+        }
+    }
+}
+
+/// How pool inputs are checked against the PoolInner dynamic fields,
+/// configured via `POOL_VERSION_CHECK`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PoolVersionCheck {
+    /// Refuse to build the input when versions diverge (default)
+    Error,
+    /// Log the divergence and use the wrapper as-is
+    Warn,
+    /// Skip the check
+    Off,
+}
+
+impl PoolVersionCheck {
+    fn from_env() -> Self {
+        match std::env::var("POOL_VERSION_CHECK") {
+            Ok(raw) => match raw.trim().to_ascii_lowercase().as_str() {
+                "error" | "" => PoolVersionCheck::Error,
+                "warn" => PoolVersionCheck::Warn,
+                "off" => PoolVersionCheck::Off,
+                _ => {
+                    tracing::warn!(
+                        "Router: invalid POOL_VERSION_CHECK={:?}, defaulting to error",
+                        raw
+                    );
+                    PoolVersionCheck::Error
+                }
+            },
+            Err(_) => PoolVersionCheck::Error,
+        }
+    }
+}
+
+/// Parse `ROUTER_CLOCK_STEP_MS`, defaulting to `SYNTHETIC_CLOCK_STEP_MS`.
+///
+/// Steps at or below DeepBook's deep_price spacing are accepted, but the
+/// debug pool can then only record one deep_price point per reference pool.
+fn clock_step_from_env() -> u64 {
+    let step = match std::env::var("ROUTER_CLOCK_STEP_MS") {
+        Ok(raw) => match raw.trim().parse::<u64>() {
+            Ok(step) if step > 0 => step,
+            _ => {
+                tracing::warn!(
+                    "Router: invalid ROUTER_CLOCK_STEP_MS={:?}, defaulting to {}",
+                    raw,
+                    SYNTHETIC_CLOCK_STEP_MS
+                );
+                SYNTHETIC_CLOCK_STEP_MS
+            }
+        },
+        Err(_) => SYNTHETIC_CLOCK_STEP_MS,
+    };
+    if step <= DEEP_PRICE_MIN_SPACING_MS {
+        tracing::warn!(
+            "Router: ROUTER_CLOCK_STEP_MS={} does not exceed DeepBook's {}ms deep_price spacing; \
+             consecutive deep_price points will be rejected",
+            step,
+            DEEP_PRICE_MIN_SPACING_MS
+        );
+    }
+    step
+}
+
+/// Parse `ROUTER_CLOCK_FROZEN` (`true`/`1` to freeze), defaulting to an advancing clock.
+///
+/// A frozen clock makes quotes and swaps repeatable regardless of how many
+/// operations ran before them, but consecutive deep_price points then need
+/// a manual advance via `POST /api/router/clock`.
+fn clock_frozen_from_env() -> bool {
+    let frozen = match std::env::var("ROUTER_CLOCK_FROZEN") {
+        Ok(raw) => match raw.trim().to_ascii_lowercase().as_str() {
+            "1" | "true" => true,
+            "0" | "false" | "" => false,
+            _ => {
+                tracing::warn!(
+                    "Router: invalid ROUTER_CLOCK_FROZEN={:?}, defaulting to false",
+                    raw
+                );
+                false
+            }
+        },
+        Err(_) => false,
+    };
+    if frozen {
+        tracing::warn!(
+            "Router: synthetic clock frozen (ROUTER_CLOCK_FROZEN); deep_price points need a manual clock advance"
+        );
+    }
+    frozen
+}
+
+/// Parse `SWAP_SKIP_DEEP_FOR_WHITELISTED`, defaulting to enabled.
+fn skip_deep_for_whitelisted_from_env() -> bool {
+    match std::env::var("SWAP_SKIP_DEEP_FOR_WHITELISTED") {
+        Ok(raw) => match raw.trim().to_ascii_lowercase().as_str() {
+            "1" | "true" | "" => true,
+            "0" | "false" => false,
+            _ => {
+                tracing::warn!(
+                    "Router: invalid SWAP_SKIP_DEEP_FOR_WHITELISTED={:?}, defaulting to true",
+                    raw
+                );
+                true
+            }
+        },
+        Err(_) => true,
+    }
+}
+
+/// Parse `RESERVE_SCAN_WINDOW`, defaulting to `MAINNET_RESERVE_SCAN_WINDOW` checkpoints.
+fn reserve_scan_window_from_env() -> u64 {
+    match std::env::var("RESERVE_SCAN_WINDOW") {
+        Ok(raw) => match raw.trim().parse::<u64>() {
+            Ok(window) if window > 0 => window,
+            _ => {
+                tracing::warn!(
+                    "Router: invalid RESERVE_SCAN_WINDOW={:?}, defaulting to {}",
+                    raw,
+                    MAINNET_RESERVE_SCAN_WINDOW
+                );
+                MAINNET_RESERVE_SCAN_WINDOW
+            }
+        },
+        Err(_) => MAINNET_RESERVE_SCAN_WINDOW,
+    }
+}
+
+/// Checkpoint pinned by `RESERVE_CHECKPOINT`; reserves are read from it alone
+/// instead of scanning recent checkpoints.
+fn reserve_checkpoint_from_env() -> Option<u64> {
+    match std::env::var("RESERVE_CHECKPOINT") {
+        Ok(raw) if raw.trim().is_empty() => None,
+        Ok(raw) => match raw.trim().parse::<u64>() {
+            Ok(checkpoint) => Some(checkpoint),
+            Err(_) => {
+                tracing::warn!(
+                    "Router: invalid RESERVE_CHECKPOINT={:?}, scanning recent checkpoints instead",
+                    raw
+                );
+                None
+            }
+        },
+        Err(_) => None,
+    }
+}
+
+/// Parse `SWAP_TWO_HOP_ATOMIC_RETRIES`, defaulting to `DEFAULT_TWO_HOP_ATOMIC_RETRIES`.
+fn two_hop_atomic_retries_from_env() -> u32 {
+    match std::env::var("SWAP_TWO_HOP_ATOMIC_RETRIES") {
+        Ok(raw) => raw.trim().parse::<u32>().unwrap_or_else(|_| {
+            tracing::warn!(
+                "Router: invalid SWAP_TWO_HOP_ATOMIC_RETRIES={:?}, defaulting to {}",
+                raw,
+                DEFAULT_TWO_HOP_ATOMIC_RETRIES
+            );
+            DEFAULT_TWO_HOP_ATOMIC_RETRIES
+        }),
+        Err(_) => DEFAULT_TWO_HOP_ATOMIC_RETRIES,
+    }
+}
+
+#[derive(Debug, Clone)]
+struct DebugLogSampler {
+    sample_rate: u64,
+    routine_count: u64,
+    anomaly_count: u64,
+}
+
+impl DebugLogSampler {
+    fn from_env() -> Self {
+        let sample_rate = match std::env::var("ROUTER_DEBUG_SAMPLE_RATE") {
+            Ok(raw) => raw.trim().parse::<u64>().unwrap_or_else(|_| {
+                tracing::warn!(
+                    "Router: invalid ROUTER_DEBUG_SAMPLE_RATE={:?}, defaulting to 1",
+                    raw
+                );
+                1
+            }),
+            Err(_) => 1,
+        };
+        Self {
+            sample_rate,
+            routine_count: 0,
+            anomaly_count: 0,
+        }
+    }
+
+    /// Whether a routine (non-anomalous) operation should emit detailed logs.
+    fn sample_routine(&mut self) -> bool {
+        if self.sample_rate == 0 {
+            return false;
+        }
+        let count = self.routine_count;
+        self.routine_count = self.routine_count.wrapping_add(1);
+        count % self.sample_rate == 0
+    }
+
+    /// Whether an anomaly (zero output, failed quote) should emit detailed logs.
+    fn sample_anomaly(&mut self) -> bool {
+        let rate = self.sample_rate.max(1);
+        let count = self.anomaly_count;
+        self.anomaly_count = self.anomaly_count.wrapping_add(1);
+        count % rate == 0
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ReserveCoinCandidate {
+    object_id: String,
+    version: u64,
+    type_string: String,
+    bcs: Vec<u8>,
+    value: u64,
+    checkpoint: u64,
+}
+
+/// Where a reserve coin in `coin_reserve_cache` was sourced from on mainnet.
+#[derive(Debug, Clone)]
+struct ReserveCoinProvenance {
+    checkpoint: u64,
+    value: u64,
+    version: u64,
+}
+
+struct PoolCacheEntry {
+    pool_addr: AccountAddress,
+    pool_type: TypeTag,
+    /// Base coin type of `pool_type`, as the router's constant when it is one
+    base_type: String,
+    /// Quote coin type of `pool_type`, as the router's constant when it is one
+    quote_type: String,
+    /// `PoolInner.state.accounts` table id, when known from loaded state
+    accounts_table: Option<AccountAddress>,
+    /// `PoolInner.state.history.historic_volumes` table id, when known
+    history_table: Option<AccountAddress>,
+    /// `PoolInner.state.history.epoch` at load time
+    history_epoch: Option<u64>,
+    /// `pool::whitelisted`, cached on the first swap that needs it
+    whitelisted: Option<bool>,
+    /// Book and trade parameters, cached on the first `PoolParams` request
+    params: Option<PoolParams>,
+}
+
+impl RouterEnvState {
+    fn next_clock_input(&mut self) -> Result<ObjectInput> {
+        if let Some(timestamp_ms) = self.clock_override_ms {
+            return self.clock_input_at(timestamp_ms);
+        }
+        let timestamp_ms = self.next_clock_timestamp_ms;
+        if !self.clock_frozen {
+            self.next_clock_timestamp_ms = self
+                .next_clock_timestamp_ms
+                .saturating_add(self.clock_step_ms);
+        }
+        build_clock_input(timestamp_ms)
+    }
+
+    /// Clock input at exactly `timestamp_ms`, without advancing the synthetic clock
+    fn clock_input_at(&self, timestamp_ms: u64) -> Result<ObjectInput> {
+        build_clock_input(timestamp_ms)
+    }
+
+    fn clock_now_ms(&self) -> u64 {
+        self.clock_override_ms
+            .unwrap_or(self.next_clock_timestamp_ms)
+    }
+
+    fn clock_status(&self) -> RouterClockStatus {
+        RouterClockStatus {
+            now_ms: self.next_clock_timestamp_ms,
+            step_ms: self.clock_step_ms,
+            frozen: self.clock_frozen,
+        }
+    }
+}
+
+/// Build the router environment, restoring it from `SNAPSHOT_PATH` when that
+/// file holds a valid snapshot of the same pool files.
+///
+/// Otherwise runs the full bootstrap and, with `SNAPSHOT_PATH` set, writes
+/// the result there for the next boot.
+fn setup_router_env(pool_files: &[(PoolId, String)]) -> Result<RouterEnvState> {
+    let snapshot_path = router_snapshot::snapshot_path_from_env();
+    if let Some(path) = snapshot_path.as_deref().filter(|path| path.exists()) {
+        match restore_router_snapshot(path, pool_files) {
+            Ok((state, _)) => return Ok(state),
+            Err(e) => tracing::warn!(
+                "Router: ignoring snapshot {}, running full bootstrap: {}",
+                path.display(),
+                e
+            ),
+        }
+    }
+
+    let state = bootstrap_router_env(pool_files)?;
+    if let Some(path) = snapshot_path {
+        match write_router_snapshot(&state, pool_files, &path) {
+            Ok(info) => tracing::info!(
+                "Router: wrote snapshot {} ({} bytes, {} objects, {} dynamic fields)",
+                info.path,
+                info.size_bytes,
+                info.objects,
+                info.dynamic_fields
+            ),
+            Err(e) => tracing::warn!("Router: failed to write snapshot {}: {}", path.display(), e),
+        }
+    }
+    Ok(state)
+}
+
+fn bootstrap_router_env(pool_files: &[(PoolId, String)]) -> Result<RouterEnvState> {
+    tracing::info!("Router thread: creating SimulationEnvironment...");
+    let mut env = SimulationEnvironment::new()?;
+    let mut bcs_converter = JsonToBcsConverter::new();
+
+    // Create a tokio runtime for async gRPC calls
+    let rt = tokio::runtime::Runtime::new()?;
+
+    // Load packages via gRPC
+    tracing::info!("Router thread: loading packages via gRPC...");
+    let grpc = rt.block_on(async { sui_transport::grpc::GrpcClient::mainnet().await })?;
+
+    // Configure auto-fetch for missing packages
+    let fetcher = GrpcFetcher::mainnet();
+    let config = FetcherConfig::mainnet();
+    env.set_fetcher(Box::new(fetcher));
+    env.set_fetcher_config(config);
+
+    let packages_to_fetch = [
+        ("0x1", "Move Stdlib"),
+        ("0x2", "Sui Framework"),
+        (DEEPBOOK_PACKAGE, "DeepBook V3"),
+        (USDC_TYPE.split("::").next().unwrap(), "USDC"),
+        (WAL_TYPE.split("::").next().unwrap(), "WAL"),
+        (DEEP_TYPE.split("::").next().unwrap(), "DEEP"),
+        (
+            "0xe0917b74a5912e4ad186ac634e29c922ab83903f71af7500969f9411706f9b9a",
+            "Upgrade Service",
+        ),
+        (
+            "0xecf47609d7da919ea98e7fd04f6e0648a0a79b337aaad373fa37aac8febf19c8",
+            "Treasury",
+        ),
+    ];
+
+    let mut loaded_packages = Vec::new();
+    let mut snapshot_packages = Vec::new();
+    for (pkg_id, name) in &packages_to_fetch {
+        let mut module_count = 0;
+        if let Ok(Some(obj)) = rt.block_on(grpc.get_object(pkg_id)) {
+            if let Some(modules) = obj.package_modules {
+                let bytecode_list: Vec<Vec<u8>> =
+                    modules.iter().map(|(_, bytes)| bytes.clone()).collect();
+                if let Err(e) = bcs_converter.add_modules_from_bytes(&bytecode_list) {
+                    tracing::warn!("Router: failed to add {} to BCS converter: {}", name, e);
+                }
+                module_count = modules.len();
+                snapshot_packages.push(SnapshotPackage {
+                    address: pkg_id.to_string(),
+                    modules: modules.clone(),
+                });
+                env.deploy_package_at_address(pkg_id, modules)?;
+                tracing::info!("Router: loaded {} ({})", name, pkg_id);
+            }
+        }
+        loaded_packages.push(RouterPackageCheck {
+            name: name.to_string(),
+            package_id: pkg_id.to_string(),
+            loaded: module_count > 0,
+            module_count,
+        });
+    }
+
+    // Debug pool creation needs DeepBook's shared Registry object.
+    // Load it up front so ensure_debug_pool can run fully in local VM.
+    load_grpc_object_into_env(
+        &mut env,
+        &rt,
+        &grpc,
+        COIN_REGISTRY_OBJECT_ID,
+        "Sui Coin Registry",
+    )?;
+    load_grpc_object_into_env(
+        &mut env,
+        &rt,
+        &grpc,
+        DEEPBOOK_REGISTRY_ID,
+        "DeepBook Registry",
+    )?;
+    let registry_inner_id = load_registry_inner_dynamic_field(&mut env, &rt, &grpc)?;
+    let mut dynamic_field_parents = HashSet::from([registry_inner_id]);
+
+    // Load all pool states
+    let mut pool_cache = HashMap::new();
+    let mut target_epoch: Option<u64> = None;
+    for (pool_id, file_path) in pool_files {
+        let path = Path::new(file_path);
+        if !path.exists() {
+            tracing::warn!(
+                "Router: skipping {} - file not found: {}",
+                pool_id.display_name(),
+                file_path
+            );
+            continue;
+        }
+
+        let mut loader = StateLoader::with_config(DeepBookConfig::for_pool(*pool_id));
+        loader
+            .load_from_file(path)
+            .map_err(|e| anyhow!("Router: failed to load {}: {}", file_path, e))?;
+        // The loader adopts the state file's own pool and coin types
+        let pool_wrapper_id = loader.config().pool_wrapper.clone();
+
+        if let Some(pool_epoch) = extract_pool_epoch(&loader) {
+            target_epoch = Some(target_epoch.map_or(pool_epoch, |current| current.max(pool_epoch)));
+        }
+
+        // Load objects into simulation environment
+        for obj in loader.all_objects() {
+            if let Some(owner_addr) = &obj.owner_address {
+                if obj.object_type.contains("dynamic_field::Field") {
+                    load_dynamic_field_for_router(&mut env, &mut bcs_converter, obj, owner_addr)?;
+                    dynamic_field_parents.insert(AccountAddress::from_hex_literal(owner_addr)?);
+                    continue;
+                }
+            }
+            load_object_for_router(&mut env, &mut bcs_converter, obj)?;
+        }
+
+        let synthesized_accounts =
+            synthesize_account_dynamic_fields_for_router(&mut env, &mut bcs_converter, &loader)?;
+        if synthesized_accounts > 0 {
+            tracing::info!(
+                "Router: synthesized {} state.accounts dynamic fields for {}",
+                synthesized_accounts,
+                pool_id.display_name()
+            );
+        }
+
+        let synthesized_history =
+            synthesize_history_volume_fields_for_router(&mut env, &mut bcs_converter, &loader)?;
+        if synthesized_history > 0 {
+            tracing::info!(
+                "Router: synthesized {} history.historic_volumes fields for {}",
+                synthesized_history,
+                pool_id.display_name()
+            );
+        }
+
+        // Cache pool entry for PTB construction
+        if loader.get_object(&pool_wrapper_id).is_some() {
+            let base_type = canonical_coin_type(&loader.config().base_type);
+            let quote_type = canonical_coin_type(&loader.config().quote_type);
+
+            let pool_type = build_pool_type_tag(&base_type, &quote_type)?;
+            let pool_addr = AccountAddress::from_hex_literal(&pool_wrapper_id)?;
+            let accounts_table = extract_accounts_table_id(&loader)
+                .and_then(|id| AccountAddress::from_hex_literal(&id).ok());
+            let history_table = extract_history_table_id(&loader)
+                .and_then(|id| AccountAddress::from_hex_literal(&id).ok());
+            dynamic_field_parents.extend(accounts_table.into_iter().chain(history_table));
+            pool_cache.insert(
+                *pool_id,
+                PoolCacheEntry {
+                    pool_addr,
+                    pool_type,
+                    base_type,
+                    quote_type,
+                    accounts_table,
+                    history_table,
+                    history_epoch: extract_pool_epoch(&loader),
+                    whitelisted: None,
+                    params: None,
+                },
+            );
+        }
+
+        tracing::info!("Router: loaded {} pool state", pool_id.display_name());
+    }
+
+    if let Some(epoch) = target_epoch {
+        env.config_mut().epoch = epoch;
+        tracing::info!("Router: set simulation epoch to {}", epoch);
+    }
+
+    // Create synthetic Clock object at 0x6
+    create_clock_object(&mut env, SYNTHETIC_CLOCK_START_MS)?;
+
+    // Compile and deploy router contract for two-hop quotes.
+    let (router_contract, router_modules) = deploy_router_contract(&mut env)?;
+    snapshot_packages.push(SnapshotPackage {
+        address: ROUTER_PACKAGE_ADDR.to_string(),
+        modules: router_modules,
+    });
+
+    let mut state = new_router_env_state(
+        env,
+        pool_cache,
+        router_contract,
+        loaded_packages,
+        bcs_converter,
+    );
+    state.snapshot_packages = snapshot_packages;
+    state.dynamic_field_parents = dynamic_field_parents;
+    state.epoch = target_epoch;
+
+    bootstrap_mainnet_reserve_coins(&mut state, &rt, &grpc)?;
+
+    // Explicit startup self-check. This must pass before backend starts.
+    let report = run_startup_self_check(&mut state)?;
+    state.startup_check = report;
+
+    Ok(state)
+}
+
+/// Router state around a loaded environment, with env-configured settings
+/// and the synthetic clock at its start.
+fn new_router_env_state(
+    env: SimulationEnvironment,
+    pool_cache: HashMap<PoolId, PoolCacheEntry>,
+    router_contract: RouterContractInfo,
+    loaded_packages: Vec<RouterPackageCheck>,
+    bcs_converter: JsonToBcsConverter,
+) -> RouterEnvState {
+    let state = RouterEnvState {
+        env,
+        pool_cache,
+        coin_reserve_cache: HashMap::new(),
+        reserve_provenance: HashMap::new(),
+        debug_treasury_ids: HashMap::new(),
+        debug_token_packages: HashSet::new(),
+        router_deployed: router_contract.deployed,
+        router_contract,
+        startup_check: RouterStartupCheckReport::default(),
+        next_clock_timestamp_ms: SYNTHETIC_CLOCK_START_MS,
+        clock_override_ms: None,
+        last_deep_price_point_ms: None,
+        clock_step_ms: clock_step_from_env(),
+        clock_frozen: clock_frozen_from_env(),
+        two_hop_atomic_retries: two_hop_atomic_retries_from_env(),
+        skip_deep_for_whitelisted: skip_deep_for_whitelisted_from_env(),
+        health_check: RouterHealthCheckConfig::from_env(),
+        debug_pools: HashMap::new(),
+        debug_log_sampler: DebugLogSampler::from_env(),
+        refund_join_policy: RefundJoinPolicy::from_env(),
+        pool_version_check: PoolVersionCheck::from_env(),
+        loaded_packages,
+        session_balance_managers: HashMap::new(),
+        snapshot_packages: Vec::new(),
+        dynamic_field_parents: HashSet::new(),
+        epoch: None,
+        bcs_converter,
+    };
+    if state.debug_log_sampler.sample_rate != 1 {
+        tracing::info!(
+            "Router: debug log sample rate set to {}",
+            state.debug_log_sampler.sample_rate
+        );
+    }
+    state
+}
+
+/// An object or dynamic field exactly as the VM holds it
+#[derive(Debug, Clone, Serialize)]
+pub struct RawVmObject {
+    pub id: String,
+    /// Parent object for dynamic fields
+    pub parent_id: Option<String>,
+    pub type_tag: String,
+    /// Dynamic fields carry no version or ownership of their own
+    pub version: Option<u64>,
+    pub is_shared: Option<bool>,
+    pub bcs_len: usize,
+    pub bcs_hex: String,
+}
+
+/// Look up an object, or the dynamic field `object_id` under `parent_id`.
+///
+/// `Ok(None)` means the ids parsed but nothing is loaded under them.
+fn inspect_object(
+    state: &RouterEnvState,
+    object_id: &str,
+    parent_id: Option<&str>,
+) -> Result<Option<RawVmObject>> {
+    let parse = |id: &str| {
+        AccountAddress::from_hex_literal(id).map_err(|e| anyhow!("Invalid object id {}: {}", id, e))
+    };
+    let id = parse(object_id)?;
+    let Some(parent_id) = parent_id else {
+        return Ok(state.env.get_object(&id).map(|obj| RawVmObject {
+            id: id.to_hex_literal(),
+            parent_id: None,
+            type_tag: obj.type_tag.to_string(),
+            version: Some(obj.version),
+            is_shared: Some(obj.is_shared),
+            bcs_len: obj.bcs_bytes.len(),
+            bcs_hex: hex::encode(&obj.bcs_bytes),
+        }));
+    };
+    let parent = parse(parent_id)?;
+    Ok(state
+        .env
+        .get_dynamic_field(parent, id)
+        .map(|(type_tag, bytes)| RawVmObject {
+            id: id.to_hex_literal(),
+            parent_id: Some(parent.to_hex_literal()),
+            type_tag: type_tag.to_string(),
+            version: None,
+            is_shared: None,
+            bcs_len: bytes.len(),
+            bcs_hex: hex::encode(bytes),
+        }))
+}
+
+fn load_grpc_object_into_env(
+    env: &mut SimulationEnvironment,
+    rt: &tokio::runtime::Runtime,
+    grpc: &sui_transport::grpc::GrpcClient,
+    object_id: &str,
+    object_name: &str,
+) -> Result<()> {
+    let object_addr = AccountAddress::from_hex_literal(object_id)?;
+    if env.get_object(&object_addr).is_some() {
+        return Ok(());
+    }
+
+    let object = rt
+        .block_on(grpc.get_object(object_id))?
+        .ok_or_else(|| anyhow!("{} not found via gRPC: {}", object_name, object_id))?;
+
+    let bcs_bytes = object
+        .bcs
+        .ok_or_else(|| anyhow!("{} missing BCS payload: {}", object_name, object_id))?;
+    let type_string = object.type_string.clone();
+    let owner = object.owner.clone();
+    let is_shared = matches!(owner, GrpcOwner::Shared { .. });
+    let is_immutable = matches!(owner, GrpcOwner::Immutable);
+    let version = object.version;
+
+    if let GrpcOwner::Object(parent_id_hex) = owner {
+        let parent_id = AccountAddress::from_hex_literal(&parent_id_hex)?;
+        let child_id = AccountAddress::from_hex_literal(object_id)?;
+        let field_type = type_string
+            .as_ref()
+            .ok_or_else(|| anyhow!("{} missing type string: {}", object_name, object_id))?;
+        let field_type_tag = SimulationEnvironment::parse_type_string(field_type)
+            .ok_or_else(|| anyhow!("Failed to parse field type {}", field_type))?;
+        env.set_dynamic_field(parent_id, child_id, field_type_tag, bcs_bytes);
+    } else {
+        env.load_object_from_data(
+            object_id,
+            bcs_bytes,
+            type_string.as_deref(),
+            is_shared,
+            is_immutable,
+            version,
+        )?;
+    }
+
+    tracing::info!(
+        "Router: loaded {} ({}, version={})",
+        object_name,
+        object_id,
+        version
+    );
+
+    Ok(())
+}
+
+/// Load the Registry's current `RegistryInner` field and return its parent id.
+fn load_registry_inner_dynamic_field(
+    env: &mut SimulationEnvironment,
+    rt: &tokio::runtime::Runtime,
+    grpc: &sui_transport::grpc::GrpcClient,
+) -> Result<AccountAddress> {
+    let registry_addr = AccountAddress::from_hex_literal(DEEPBOOK_REGISTRY_ID)?;
+    let registry_obj = env
+        .get_object(&registry_addr)
+        .ok_or_else(|| anyhow!("Registry object missing in env: {}", registry_addr))?;
+
+    if registry_obj.bcs_bytes.len() < 72 {
+        return Err(anyhow!(
+            "Registry object BCS too short ({}), expected at least 72 bytes",
+            registry_obj.bcs_bytes.len()
+        ));
+    }
+
+    let mut inner_id_bytes = [0u8; AccountAddress::LENGTH];
+    inner_id_bytes.copy_from_slice(&registry_obj.bcs_bytes[32..64]);
+    let inner_id = AccountAddress::new(inner_id_bytes);
+
+    let mut version_bytes = [0u8; 8];
+    version_bytes.copy_from_slice(&registry_obj.bcs_bytes[64..72]);
+    let current_version = u64::from_le_bytes(version_bytes);
+
+    let key_bytes = bcs::to_bytes(&current_version)?;
+    let child_id = derive_dynamic_field_id(inner_id, &TypeTag::U64, &key_bytes)
+        .map_err(|e| anyhow!("Failed to derive registry inner dynamic field id: {}", e))?;
+    let child_id_hex = child_id.to_hex_literal();
+
+    load_grpc_object_into_env(
+        env,
+        rt,
+        grpc,
+        &child_id_hex,
+        "DeepBook RegistryInner dynamic field",
+    )?;
+
+    Ok(inner_id)
+}
+
+fn coin_object_type(coin_type: &str) -> String {
+    format!("0x2::coin::Coin<{}>", coin_type)
+}
+
+fn normalize_type_string(type_string: &str) -> String {
+    type_string.replace(' ', "")
+}
+
+fn parse_coin_value_from_bcs(bcs: &[u8]) -> Option<u64> {
+    if bcs.len() < 40 {
+        return None;
+    }
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&bcs[32..40]);
+    Some(u64::from_le_bytes(bytes))
+}
+
+fn find_reserve_candidate(
+    object: GrpcObject,
+    expected_coin_object_tag: &TypeTag,
+    checkpoint: u64,
+) -> Option<ReserveCoinCandidate> {
+    let bcs = object.bcs?;
+    let type_string = object.type_string?;
+    let observed_tag = TypeTag::from_str(&type_string).ok()?;
+    if &observed_tag != expected_coin_object_tag {
+        return None;
+    }
+    if !matches!(object.owner, GrpcOwner::Address(_)) {
+        return None;
+    }
+    let value = parse_coin_value_from_bcs(&bcs)?;
+    Some(ReserveCoinCandidate {
+        object_id: object.object_id,
+        version: object.version,
+        type_string,
+        bcs,
+        value,
+        checkpoint,
+    })
+}
+
+fn bootstrap_mainnet_reserve_coins(
+    state: &mut RouterEnvState,
+    rt: &tokio::runtime::Runtime,
+    grpc: &sui_transport::grpc::GrpcClient,
+) -> Result<()> {
+    let reserve_types = [SUI_TYPE, USDC_TYPE, WAL_TYPE, DEEP_TYPE];
+    let mut candidates: HashMap<&'static str, ReserveCoinCandidate> = HashMap::new();
+    let expected_types: HashMap<&'static str, TypeTag> = reserve_types
+        .iter()
+        .map(|coin_type| {
+            let coin_obj = coin_object_type(coin_type);
+            let tag = TypeTag::from_str(&coin_obj)
+                .map_err(|e| anyhow!("Invalid reserve coin type tag {}: {}", coin_obj, e))?;
+            Ok((*coin_type, tag))
+        })
+        .collect::<Result<HashMap<_, _>>>()?;
+
+    let mut collect = |objects: Vec<GrpcObject>, checkpoint: u64| {
+        for object in objects {
+            for coin_type in reserve_types {
+                let Some(expected) = expected_types.get(coin_type) else {
+                    continue;
+                };
+                let Some(candidate) = find_reserve_candidate(object.clone(), expected, checkpoint)
+                else {
+                    continue;
+                };
+                let replace = candidates
+                    .get(coin_type)
+                    .map(|existing| candidate.value > existing.value)
+                    .unwrap_or(true);
+                if replace {
+                    candidates.insert(coin_type, candidate);
+                }
+            }
+        }
+    };
+
+    let searched = if let Some(pinned) = reserve_checkpoint_from_env() {
+        tracing::info!(
+            "Router: bootstrapping VM reserve coins from pinned checkpoint {} (RESERVE_CHECKPOINT)",
+            pinned
+        );
+        let cp = rt
+            .block_on(grpc.get_checkpoint(pinned))
+            .map_err(|e| {
+                anyhow!(
+                    "Router reserve bootstrap failed: could not fetch pinned checkpoint {} (RESERVE_CHECKPOINT): {}",
+                    pinned,
+                    e
+                )
+            })?
+            .ok_or_else(|| {
+                anyhow!(
+                    "Router reserve bootstrap failed: pinned checkpoint {} (RESERVE_CHECKPOINT) is not available",
+                    pinned
+                )
+            })?;
+        collect(cp.objects, pinned);
+        format!("pinned checkpoint {} (RESERVE_CHECKPOINT)", pinned)
+    } else {
+        let window = reserve_scan_window_from_env();
+        let service_info = rt.block_on(grpc.get_service_info())?;
+        let latest = service_info.checkpoint_height;
+        let start = latest.saturating_sub(window);
+
+        tracing::info!(
+            "Router: bootstrapping VM reserve coins from checkpoints {}..={} (latest={})",
+            start,
+            latest,
+            latest
+        );
+
+        for checkpoint in (start..=latest).rev() {
+            let cp_opt = match rt.block_on(grpc.get_checkpoint(checkpoint)) {
+                Ok(cp) => cp,
+                Err(e) => {
+                    tracing::warn!(
+                        "Router: skipping checkpoint {} during reserve bootstrap: {}",
+                        checkpoint,
+                        e
+                    );
+                    continue;
+                }
+            };
+
+            let Some(cp) = cp_opt else {
+                continue;
+            };
+            collect(cp.objects, checkpoint);
+        }
+        format!("the last {} checkpoints", window)
+    };
+
+    let missing: Vec<&str> = reserve_types
+        .iter()
+        .copied()
+        .filter(|coin_type| !candidates.contains_key(coin_type))
+        .collect();
+    if !missing.is_empty() {
+        return Err(anyhow!(
+            "Router reserve bootstrap failed: missing checkpoint coin objects for [{}] in {}",
+            missing.join(", "),
+            searched
+        ));
+    }
+
+    for coin_type in reserve_types {
+        let candidate = candidates
+            .remove(coin_type)
+            .ok_or_else(|| anyhow!("Missing reserve candidate for {}", coin_type))?;
+        let reserve_id = AccountAddress::from_hex_literal(&candidate.object_id)?;
+        if state.env.get_object(&reserve_id).is_none() {
+            state.env.load_object_from_data(
+                &candidate.object_id,
+                candidate.bcs.clone(),
+                Some(&candidate.type_string),
+                false,
+                false,
+                candidate.version,
+            )?;
+        }
+        state
+            .coin_reserve_cache
+            .insert(coin_type.to_string(), reserve_id);
+        state.reserve_provenance.insert(
+            coin_type.to_string(),
+            ReserveCoinProvenance {
+                checkpoint: candidate.checkpoint,
+                value: candidate.value,
+                version: candidate.version,
+            },
+        );
+        tracing::info!(
+            "Router: checkpoint-backed reserve loaded for {} at {} (value={}, version={}, checkpoint={})",
+            coin_type,
+            reserve_id,
+            candidate.value,
+            candidate.version,
+            candidate.checkpoint
+        );
+    }
+
+    Ok(())
+}
+
+/// Base and quote coin types of a pool: those of the loaded `Pool<Base, Quote>`,
+/// or the pair a built-in pool id names when it is not loaded.
+///
+/// A manifest pool that is not loaded has no types; calls built for it fail
+/// to parse them.
+fn pool_types(state: &RouterEnvState, pool_id: PoolId) -> (String, String) {
+    if let Some(entry) = state.pool_cache.get(&pool_id) {
+        return (entry.base_type.clone(), entry.quote_type.clone());
+    }
+    let (base_type, quote_type) = match pool_id {
+        PoolId::SuiUsdc => (SUI_TYPE, USDC_TYPE),
+        PoolId::WalUsdc => (WAL_TYPE, USDC_TYPE),
+        PoolId::DeepUsdc => (DEEP_TYPE, USDC_TYPE),
+        PoolId::DebugUsdc => {
+            return (
+                DebugPoolCreateConfig::default().token_type(),
+                USDC_TYPE.to_string(),
+            )
+        }
+        _ => ("", ""),
+    };
+    (base_type.to_string(), quote_type.to_string())
+}
+
+/// `coin_type` spelled as the router's own constant when it names one of the
+/// reserve coins (e.g. `0x0…02::sui::SUI` becomes `0x2::sui::SUI`), so loaded
+/// pool types key `coin_reserve_cache` like the built-in ones.
+fn canonical_coin_type(coin_type: &str) -> String {
+    let Ok(tag) = TypeTag::from_str(coin_type) else {
+        return coin_type.to_string();
+    };
+    [SUI_TYPE, USDC_TYPE, WAL_TYPE, DEEP_TYPE]
+        .into_iter()
+        .find(|known| TypeTag::from_str(known).is_ok_and(|known| known == tag))
+        .unwrap_or(coin_type)
+        .to_string()
+}
+
+fn parse_sender_address(sender: &str) -> Result<AccountAddress> {
+    AccountAddress::from_hex_literal(sender.trim())
+        .map_err(|e| anyhow!("Invalid sender address {}: {}", sender, e))
+}
+
+/// Run `f` with the VM sender temporarily set to `sender`, restoring the
+/// previous sender afterwards (mirrors the save/restore used when seeding).
+fn with_sender_override<T>(
+    state: &mut RouterEnvState,
+    sender: Option<&str>,
+    f: impl FnOnce(&mut RouterEnvState) -> Result<T>,
+) -> Result<T> {
+    let Some(sender) = sender else {
+        return f(state);
+    };
+    let override_sender = parse_sender_address(sender)?;
+    let original_sender = state.env.sender();
+    state.env.set_sender(override_sender);
+    let result = f(state);
+    state.env.set_sender(original_sender);
+    result
+}
+
+/// Run `f`, then with `rollback` put every VM object, dynamic field and the
+/// synthetic clock back to how they were before it ran.
+///
+/// `f` still sees and returns its real effects (outputs, events, gas).
+/// Objects it created stay behind unreferenced.
+fn with_rollback<T>(
+    state: &mut RouterEnvState,
+    rollback: bool,
+    f: impl FnOnce(&mut RouterEnvState) -> Result<T>,
+) -> Result<T> {
+    if !rollback {
+        return f(state);
+    }
+    let objects = env_objects(state);
+    let dynamic_fields = env_dynamic_fields(state);
+    let next_clock_timestamp_ms = state.next_clock_timestamp_ms;
+    let result = f(state);
+    state.next_clock_timestamp_ms = next_clock_timestamp_ms;
+    rollback_env(state, &objects, &dynamic_fields)?;
+    result
+}
+
+fn pool_type_params(pool_type: &TypeTag) -> Option<(&TypeTag, &TypeTag)> {
+    match pool_type {
+        TypeTag::Struct(tag) if tag.type_params.len() == 2 => {
+            Some((&tag.type_params[0], &tag.type_params[1]))
+        }
+        _ => None,
+    }
+}
+
+fn resolve_type_route(
+    state: &RouterEnvState,
+    from_type: &str,
+    to_type: &str,
+) -> Result<Option<TypeRoute>> {
+    let from_tag = TypeTag::from_str(from_type.trim())
+        .map_err(|e| anyhow!("Invalid from_type {}: {}", from_type, e))?;
+    let to_tag = TypeTag::from_str(to_type.trim())
+        .map_err(|e| anyhow!("Invalid to_type {}: {}", to_type, e))?;
+    if from_tag == to_tag {
+        return Err(anyhow!("from_type and to_type must differ"));
+    }
+
+    let mut pools: Vec<(PoolId, &TypeTag, &TypeTag)> = state
+        .pool_cache
+        .iter()
+        .filter_map(|(pool_id, entry)| {
+            pool_type_params(&entry.pool_type).map(|(base, quote)| (*pool_id, base, quote))
+        })
+        .collect();
+    pools.sort_by_key(|(pool_id, _, _)| pool_id.as_str());
+
+    for (pool_id, base, quote) in &pools {
+        if **base == from_tag && **quote == to_tag {
+            return Ok(Some(TypeRoute::Direct {
+                pool_id: *pool_id,
+                is_sell_base: true,
+            }));
+        }
+        if **base == to_tag && **quote == from_tag {
+            return Ok(Some(TypeRoute::Direct {
+                pool_id: *pool_id,
+                is_sell_base: false,
+            }));
+        }
+    }
+
+    for (from_pool, from_base, from_quote) in &pools {
+        if **from_base != from_tag {
+            continue;
+        }
+        if let Some((to_pool, _, _)) = pools
+            .iter()
+            .find(|(_, base, quote)| **base == to_tag && *quote == *from_quote)
+        {
+            return Ok(Some(TypeRoute::TwoHop {
+                from_pool: *from_pool,
+                to_pool: *to_pool,
+            }));
+        }
+    }
+
+    Ok(None)
+}
+
+fn sync_dynamic_field_entries(
+    state: &mut RouterEnvState,
+    effects: &sui_sandbox_core::ptb::TransactionEffects,
+) {
+    let mut object_bytes_synced = 0usize;
+    for (object_id, bytes) in &effects.created_object_bytes {
+        if state.env.get_object(object_id).is_some()
+            && state.env.set_object_bytes(*object_id, bytes.clone()).is_ok()
+        {
+            object_bytes_synced += 1;
+        }
+    }
+    for (object_id, bytes) in &effects.mutated_object_bytes {
+        if state.env.get_object(object_id).is_some()
+            && state.env.set_object_bytes(*object_id, bytes.clone()).is_ok()
+        {
+            object_bytes_synced += 1;
+        }
+    }
+
+    for ((parent_id, child_id), (type_tag, bytes)) in &effects.dynamic_field_entries {
+        let corrected_type_tag = normalize_dynamic_field_type_tag(type_tag);
+        state
+            .env
+            .set_dynamic_field(*parent_id, *child_id, corrected_type_tag, bytes.clone());
+        state.dynamic_field_parents.insert(*parent_id);
+        if state.env.get_object(child_id).is_some()
+            && state.env.set_object_bytes(*child_id, bytes.clone()).is_ok()
+        {
+            object_bytes_synced += 1;
+        }
+    }
+
+    // Some sandbox builds do not fully mirror dynamic field updates in
+    // `dynamic_field_entries`, but the created/mutated field objects still appear
+    // in object_changes with Owner::Object(parent). Backfill those entries.
+    let mut backfilled = 0usize;
+    for change in &effects.object_changes {
+        match change {
+            sui_sandbox_core::ptb::ObjectChange::Created {
+                id,
+                owner,
+                object_type: Some(type_tag),
+            } => {
+                if !type_tag.to_string().contains("::dynamic_field::Field<") {
+                    continue;
+                }
+                let Some(parent_id) = parse_parent_from_owner_debug(owner) else {
+                    continue;
+                };
+                if let Some(bytes) = effects.created_object_bytes.get(id) {
+                    let corrected_type_tag = normalize_dynamic_field_type_tag(type_tag);
+                    state
+                        .env
+                        .set_dynamic_field(parent_id, *id, corrected_type_tag, bytes.clone());
+                    state.dynamic_field_parents.insert(parent_id);
+                    if state.env.get_object(id).is_some()
+                        && state.env.set_object_bytes(*id, bytes.clone()).is_ok()
+                    {
+                        object_bytes_synced += 1;
+                    }
+                    backfilled += 1;
+                }
+            }
+            sui_sandbox_core::ptb::ObjectChange::Mutated {
+                id,
+                owner,
+                object_type: Some(type_tag),
+            } => {
+                if !type_tag.to_string().contains("::dynamic_field::Field<") {
+                    continue;
+                }
+                let Some(parent_id) = parse_parent_from_owner_debug(owner) else {
+                    continue;
+                };
+                if let Some(bytes) = effects.mutated_object_bytes.get(id) {
+                    let corrected_type_tag = normalize_dynamic_field_type_tag(type_tag);
+                    state
+                        .env
+                        .set_dynamic_field(parent_id, *id, corrected_type_tag, bytes.clone());
+                    state.dynamic_field_parents.insert(parent_id);
+                    if state.env.get_object(id).is_some()
+                        && state.env.set_object_bytes(*id, bytes.clone()).is_ok()
+                    {
+                        object_bytes_synced += 1;
+                    }
+                    backfilled += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut reconciled = 0usize;
+    let pool_ids: Vec<PoolId> = state.pool_cache.keys().copied().collect();
+    for pool_id in pool_ids {
+        match reconcile_pool_inner_version_from_dynamic_fields(state, pool_id) {
+            Ok(true) => reconciled += 1,
+            Ok(false) => {}
+            Err(e) => tracing::warn!(
+                "Router: failed to reconcile {} pool wrapper version: {}",
+                pool_id.display_name(),
+                e
+            ),
+        }
+    }
+
+    // Work around a sandbox gap: mutated dynamic-field child objects may be present
+    // in `mutated_object_bytes` without an updated entry in `dynamic_field_entries`.
+    // Refresh PoolInner children explicitly so order-book mutations persist across PTBs.
+    let mut refreshed = 0usize;
+    for pool_entry in state.pool_cache.values() {
+        let Some(pool_obj) = state.env.get_object(&pool_entry.pool_addr) else {
+            continue;
+        };
+        if pool_obj.bcs_bytes.len() < 72 {
+            continue;
+        }
+
+        let mut inner_parent_bytes = [0u8; AccountAddress::LENGTH];
+        inner_parent_bytes.copy_from_slice(&pool_obj.bcs_bytes[32..64]);
+        let inner_parent = AccountAddress::new(inner_parent_bytes);
+
+        let mut version_bytes = [0u8; 8];
+        version_bytes.copy_from_slice(&pool_obj.bcs_bytes[64..72]);
+        let inner_version = u64::from_le_bytes(version_bytes);
+
+        let Ok(key_bytes) = bcs::to_bytes(&inner_version) else {
+            continue;
+        };
+        let Ok(inner_child) = derive_dynamic_field_id(inner_parent, &TypeTag::U64, &key_bytes)
+        else {
+            continue;
+        };
+
+        let Some(mutated_bytes) = effects.mutated_object_bytes.get(&inner_child) else {
+            continue;
+        };
+        let Some((type_tag, _existing_bytes)) = state
+            .env
+            .get_dynamic_field(inner_parent, inner_child)
+            .cloned()
+        else {
+            continue;
+        };
+
+        state
+            .env
+            .set_dynamic_field(inner_parent, inner_child, type_tag, mutated_bytes.clone());
+        refreshed += 1;
+    }
+
+    if refreshed > 0 {
+        tracing::info!(
+            "Router: refreshed {} PoolInner dynamic-field children from mutated_object_bytes",
+            refreshed
+        );
+    }
+    if object_bytes_synced > 0 {
+        tracing::info!(
+            "Router: synchronized {} object byte snapshots from PTB effects",
+            object_bytes_synced
+        );
+    }
+    if reconciled > 0 {
+        tracing::info!(
+            "Router: reconciled {} pool wrapper inner versions from dynamic fields",
+            reconciled
+        );
+    }
+    if backfilled > 0 {
+        tracing::info!(
+            "Router: backfilled {} dynamic fields from object_changes",
+            backfilled
+        );
+    }
+}
+
+fn normalize_dynamic_field_type_tag(type_tag: &TypeTag) -> TypeTag {
+    let type_str = type_tag.to_string();
+    if !type_str.contains("::dynamic_field::Field<u64, vector<") || !type_str.contains(DEEPBOOK_PACKAGE) {
+        return type_tag.clone();
+    }
+
+    let Some(vector_start) = type_str.find("vector<") else {
+        return type_tag.clone();
+    };
+    let element_start = vector_start + "vector<".len();
+    let remaining = &type_str[element_start..];
+
+    let mut depth = 1usize;
+    let mut element_end = None;
+    for (idx, ch) in remaining.char_indices() {
+        match ch {
+            '<' => depth += 1,
+            '>' => {
+                depth -= 1;
+                if depth == 0 {
+                    element_end = Some(idx);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let Some(element_end) = element_end else {
+        return type_tag.clone();
+    };
+
+    let element_type = &remaining[..element_end];
+    let prefix = &type_str[..vector_start];
+    let suffix = &type_str[element_start + element_end + 1..];
+    let corrected = format!(
+        "{}{}::big_vector::Slice<{}>{}",
+        prefix, DEEPBOOK_PACKAGE, element_type, suffix
+    );
+
+    TypeTag::from_str(&corrected).unwrap_or_else(|_| type_tag.clone())
+}
+
+fn parse_parent_from_owner_debug(owner: &impl std::fmt::Debug) -> Option<AccountAddress> {
+    let owner_debug = format!("{:?}", owner);
+    if let Some(object_owner) = owner_debug
+        .strip_prefix("Object(")
+        .and_then(|raw| raw.strip_suffix(')'))
+        .map(str::trim)
+    {
+        let normalized = if object_owner.starts_with("0x") {
+            object_owner.to_string()
+        } else {
+            format!("0x{}", object_owner)
+        };
+        if let Ok(addr) = AccountAddress::from_hex_literal(&normalized) {
+            return Some(addr);
+        }
+    }
+
+    // Fallback: parse the first `0x...` token from debug output.
+    let start = owner_debug.find("0x")?;
+    let hex_tail = &owner_debug[start + 2..];
+    let hex_len = hex_tail
+        .chars()
+        .take_while(|c| c.is_ascii_hexdigit())
+        .count();
+    if hex_len == 0 {
+        return None;
+    }
+
+    let candidate = format!("0x{}", &hex_tail[..hex_len]);
+    AccountAddress::from_hex_literal(&candidate).ok()
+}
+
+fn parse_dynamic_field_u64_name(field_bytes: &[u8]) -> Option<u64> {
+    // Field<K, V> BCS layout starts with UID (32 bytes) followed by `name: K`.
+    if field_bytes.len() < 40 {
+        return None;
+    }
+
+    let mut key_bytes = [0u8; 8];
+    key_bytes.copy_from_slice(&field_bytes[32..40]);
+    Some(u64::from_le_bytes(key_bytes))
+}
+
+fn patch_pool_big_vector_header_from_created_slice(
+    state: &mut RouterEnvState,
+    pool_id: PoolId,
+    big_vector_parent: AccountAddress,
+    slice_key: u64,
+) -> Result<bool> {
+    let pool_addr = match state.pool_cache.get(&pool_id) {
+        Some(entry) => entry.pool_addr,
+        None => return Ok(false),
+    };
+    let pool_obj = match state.env.get_object(&pool_addr) {
+        Some(obj) => obj,
+        None => return Ok(false),
+    };
+    if pool_obj.bcs_bytes.len() < 72 {
+        return Ok(false);
+    }
+
+    let mut inner_parent_bytes = [0u8; AccountAddress::LENGTH];
+    inner_parent_bytes.copy_from_slice(&pool_obj.bcs_bytes[32..64]);
+    let inner_parent = AccountAddress::new(inner_parent_bytes);
+    let mut version_bytes = [0u8; 8];
+    version_bytes.copy_from_slice(&pool_obj.bcs_bytes[64..72]);
+    let inner_version = u64::from_le_bytes(version_bytes);
+    let key_bytes = bcs::to_bytes(&inner_version)?;
+    let inner_child = derive_dynamic_field_id(inner_parent, &TypeTag::U64, &key_bytes)?;
+
+    let Some((field_type, field_bytes)) = state.env.get_dynamic_field(inner_parent, inner_child).cloned()
+    else {
+        return Ok(false);
+    };
+    if field_bytes.len() < 40 {
+        return Ok(false);
+    }
+
+    let mut patched_field_bytes = field_bytes.clone();
+    let value_bytes = &mut patched_field_bytes[40..];
+    let parent_raw = big_vector_parent.as_ref();
+    let mut patched = false;
+    let mut idx = 0usize;
+    while idx + AccountAddress::LENGTH <= value_bytes.len() {
+        if &value_bytes[idx..idx + AccountAddress::LENGTH] != parent_raw {
+            idx += 1;
+            continue;
+        }
+        // BigVector layout:
+        // id (32), depth (1), length (8), max_slice_size (8), max_fan_out (8), root_id (8), last_id (8)
+        if idx + 73 > value_bytes.len() {
+            break;
+        }
+        let length_off = idx + 33;
+        let root_id_off = idx + 57;
+        let last_id_off = idx + 65;
+
+        let mut length_bytes = [0u8; 8];
+        length_bytes.copy_from_slice(&value_bytes[length_off..length_off + 8]);
+        let current_length = u64::from_le_bytes(length_bytes);
+
+        let mut root_bytes = [0u8; 8];
+        root_bytes.copy_from_slice(&value_bytes[root_id_off..root_id_off + 8]);
+        let current_root = u64::from_le_bytes(root_bytes);
+
+        let mut last_bytes = [0u8; 8];
+        last_bytes.copy_from_slice(&value_bytes[last_id_off..last_id_off + 8]);
+        let current_last = u64::from_le_bytes(last_bytes);
+
+        let new_length = current_length.max(1);
+        let new_root = if current_root == 0 {
+            slice_key
+        } else {
+            current_root
+        };
+        let new_last = current_last.max(slice_key);
+
+        value_bytes[length_off..length_off + 8].copy_from_slice(&new_length.to_le_bytes());
+        value_bytes[root_id_off..root_id_off + 8].copy_from_slice(&new_root.to_le_bytes());
+        value_bytes[last_id_off..last_id_off + 8].copy_from_slice(&new_last.to_le_bytes());
+
+        state
+            .env
+            .set_dynamic_field(inner_parent, inner_child, field_type.clone(), patched_field_bytes.clone());
+        if state.env.get_object(&inner_child).is_some() {
+            state
+                .env
+                .set_object_bytes(inner_child, patched_field_bytes.clone())
+                .map_err(|e| anyhow!("failed patching PoolInner bytes {}: {}", inner_child, e))?;
+        }
+
+        tracing::info!(
+            "Router: patched {} BigVector header parent={} key={} length {}->{} root {}->{} last {}->{}",
+            pool_id.display_name(),
+            big_vector_parent,
+            slice_key,
+            current_length,
+            new_length,
+            current_root,
+            new_root,
+            current_last,
+            new_last
+        );
+        patched = true;
+        break;
+    }
+
+    Ok(patched)
+}
+
+fn scaled_mul_floor(lhs: u64, rhs: u64) -> u64 {
+    ((lhs as u128 * rhs as u128) / 1_000_000_000u128) as u64
+}
+
+fn scaled_mul_ceil(lhs: u64, rhs: u64) -> u64 {
+    (lhs as u128 * rhs as u128).div_ceil(1_000_000_000u128) as u64
+}
+
+fn patch_pool_vault_tail_for_seed(
+    state: &mut RouterEnvState,
+    pool_id: PoolId,
+    add_base: u64,
+    add_quote: u64,
+    add_deep: u64,
+) -> Result<bool> {
+    if add_base == 0 && add_quote == 0 && add_deep == 0 {
+        return Ok(false);
+    }
+
+    let pool_addr = match state.pool_cache.get(&pool_id) {
+        Some(entry) => entry.pool_addr,
+        None => return Ok(false),
+    };
+    let pool_obj = match state.env.get_object(&pool_addr) {
+        Some(obj) => obj,
+        None => return Ok(false),
+    };
+    if pool_obj.bcs_bytes.len() < 72 {
+        return Ok(false);
+    }
+
+    let mut inner_parent_bytes = [0u8; AccountAddress::LENGTH];
+    inner_parent_bytes.copy_from_slice(&pool_obj.bcs_bytes[32..64]);
+    let inner_parent = AccountAddress::new(inner_parent_bytes);
+    let mut version_bytes = [0u8; 8];
+    version_bytes.copy_from_slice(&pool_obj.bcs_bytes[64..72]);
+    let inner_version = u64::from_le_bytes(version_bytes);
+    let key_bytes = bcs::to_bytes(&inner_version)?;
+    let inner_child = derive_dynamic_field_id(inner_parent, &TypeTag::U64, &key_bytes)?;
+
+    let Some((field_type, field_bytes)) = state.env.get_dynamic_field(inner_parent, inner_child).cloned()
+    else {
+        return Ok(false);
+    };
+    if field_bytes.len() < 40 + 43 {
+        return Ok(false);
+    }
+
+    let mut patched_field_bytes = field_bytes.clone();
+    let value_bytes = &mut patched_field_bytes[40..];
+    let value_len = value_bytes.len();
+    let vault_start = value_len - 43;
+    let deep_price_base_vec_len_off = value_len - 19;
+    let deep_price_quote_vec_len_off = value_len - 10;
+    let registered_pool_off = value_len - 1;
+
+    // This tail layout assumption matches an empty DeepPrice:
+    // [vault base/quote/deep (24)] [vec_len=0][cum_base=0][vec_len=0][cum_quote=0][registered_pool]
+    if value_bytes[deep_price_base_vec_len_off] != 0
+        || value_bytes[deep_price_quote_vec_len_off] != 0
+        || value_bytes[registered_pool_off] > 1
+    {
+        return Ok(false);
+    }
+
+    let read_u64 = |buf: &[u8], off: usize| -> u64 {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&buf[off..off + 8]);
+        u64::from_le_bytes(bytes)
+    };
+
+    let base_off = vault_start;
+    let quote_off = vault_start + 8;
+    let deep_off = vault_start + 16;
+    let old_base = read_u64(value_bytes, base_off);
+    let old_quote = read_u64(value_bytes, quote_off);
+    let old_deep = read_u64(value_bytes, deep_off);
+
+    let new_base = old_base.saturating_add(add_base);
+    let new_quote = old_quote.saturating_add(add_quote);
+    let new_deep = old_deep.saturating_add(add_deep);
+
+    value_bytes[base_off..base_off + 8].copy_from_slice(&new_base.to_le_bytes());
+    value_bytes[quote_off..quote_off + 8].copy_from_slice(&new_quote.to_le_bytes());
+    value_bytes[deep_off..deep_off + 8].copy_from_slice(&new_deep.to_le_bytes());
+
+    state
+        .env
+        .set_dynamic_field(inner_parent, inner_child, field_type.clone(), patched_field_bytes.clone());
+    if state.env.get_object(&inner_child).is_some() {
+        state
+            .env
+            .set_object_bytes(inner_child, patched_field_bytes.clone())
+            .map_err(|e| anyhow!("failed patching PoolInner vault bytes {}: {}", inner_child, e))?;
+    }
+
+    tracing::info!(
+        "Router: patched {} vault tail base {}->{} quote {}->{} deep {}->{}",
+        pool_id.display_name(),
+        old_base,
+        new_base,
+        old_quote,
+        new_quote,
+        old_deep,
+        new_deep
+    );
+    Ok(true)
+}
+
+fn reconcile_pool_inner_version_from_dynamic_fields(
+    state: &mut RouterEnvState,
+    pool_id: PoolId,
+) -> Result<bool> {
+    let pool_addr = match state.pool_cache.get(&pool_id) {
+        Some(entry) => entry.pool_addr,
+        None => return Ok(false),
+    };
+    let pool_obj = match state.env.get_object(&pool_addr) {
+        Some(obj) => obj,
+        None => return Ok(false),
+    };
+
+    if pool_obj.bcs_bytes.len() < 72 {
+        return Ok(false);
+    }
+
+    let mut parent_bytes = [0u8; AccountAddress::LENGTH];
+    parent_bytes.copy_from_slice(&pool_obj.bcs_bytes[32..64]);
+    let inner_parent = AccountAddress::new(parent_bytes);
+
+    let mut current_version_bytes = [0u8; 8];
+    current_version_bytes.copy_from_slice(&pool_obj.bcs_bytes[64..72]);
+    let current_version = u64::from_le_bytes(current_version_bytes);
+
+    let Some(latest_version) = latest_pool_inner_field_version(state, pool_id, inner_parent) else {
+        return Ok(false);
+    };
+    if latest_version <= current_version {
+        return Ok(false);
+    }
+
+    let mut patched = pool_obj.bcs_bytes.clone();
+    patched[64..72].copy_from_slice(&latest_version.to_le_bytes());
+    state
+        .env
+        .set_object_bytes(pool_addr, patched)
+        .map_err(|e| anyhow!("failed updating pool wrapper bytes for {}: {}", pool_addr, e))?;
+
+    tracing::info!(
+        "Router: patched {} wrapper inner.version {} -> {}",
+        pool_id.display_name(),
+        current_version,
+        latest_version
+    );
+    Ok(true)
+}
+
+/// Highest `PoolInner` version key among the dynamic fields of `inner_parent`.
+fn latest_pool_inner_field_version(
+    state: &RouterEnvState,
+    pool_id: PoolId,
+    inner_parent: AccountAddress,
+) -> Option<u64> {
+    let (base_type, quote_type) = pool_types(state, pool_id);
+    let expected_inner = format!("::pool::PoolInner<{},{}>", base_type, quote_type);
+
+    let mut latest_version = None::<u64>;
+    for (_child_id, type_tag, bytes) in state.env.get_dynamic_fields_for_parent(inner_parent) {
+        let type_str = type_tag.to_string().replace(' ', "");
+        if !type_str.contains("::dynamic_field::Field<u64,") {
+            continue;
+        }
+        if !type_str.contains(&expected_inner) {
+            continue;
+        }
+        let Some(version_key) = parse_dynamic_field_u64_name(bytes) else {
+            continue;
+        };
+        latest_version = Some(latest_version.map_or(version_key, |v| v.max(version_key)));
+    }
+    latest_version
+}
+
+/// Check that a pool wrapper's `inner.version` names the latest PoolInner
+/// dynamic field before it is handed to the VM.
+///
+/// A stale version makes DeepBook load an outdated (or missing) PoolInner,
+/// which otherwise surfaces as an opaque dynamic-field abort mid-PTB.
+fn check_pool_inner_version(
+    state: &RouterEnvState,
+    pool_id: PoolId,
+    pool_addr: AccountAddress,
+) -> Result<()> {
+    if state.pool_version_check == PoolVersionCheck::Off {
+        return Ok(());
+    }
+    let Some((inner_parent, wrapper_version)) = pool_wrapper_inner_ref(state, pool_addr) else {
+        return Ok(());
+    };
+    // No PoolInner fields loaded yet, so there is nothing to compare against.
+    let Some(latest_version) = latest_pool_inner_field_version(state, pool_id, inner_parent) else {
+        return Ok(());
+    };
+    if wrapper_version == latest_version {
+        return Ok(());
+    }
+
+    let message = format!(
+        "{} pool version desync: wrapper inner.version is {} but the latest PoolInner \
+         dynamic field is version {}; resync with POST /api/debug/pool/{}/resync",
+        pool_id.display_name(),
+        wrapper_version,
+        latest_version,
+        pool_id.as_str()
+    );
+    match state.pool_version_check {
+        PoolVersionCheck::Error => Err(anyhow!(message)),
+        _ => {
+            tracing::warn!("Router: {}", message);
+            Ok(())
+        }
+    }
+}
+
+/// Read `(inner.id, inner.version)` from a pool wrapper object's BCS bytes.
+fn pool_wrapper_inner_ref(state: &RouterEnvState, pool_addr: AccountAddress) -> Option<(AccountAddress, u64)> {
+    let pool_obj = state.env.get_object(&pool_addr)?;
+    if pool_obj.bcs_bytes.len() < 72 {
+        return None;
+    }
+    let mut parent_bytes = [0u8; AccountAddress::LENGTH];
+    parent_bytes.copy_from_slice(&pool_obj.bcs_bytes[32..64]);
+    let mut version_bytes = [0u8; 8];
+    version_bytes.copy_from_slice(&pool_obj.bcs_bytes[64..72]);
+    Some((AccountAddress::new(parent_bytes), u64::from_le_bytes(version_bytes)))
+}
+
+/// Manual recovery for drifted pool state: reconcile the wrapper version,
+/// re-derive the PoolInner child id, and resync that child from its
+/// dynamic-field entry. Same steps `sync_dynamic_field_entries` runs after
+/// each PTB, but without relying on transaction effects.
+fn resync_pool_state(state: &mut RouterEnvState, pool_id: PoolId) -> Result<PoolResyncReport> {
+    let pool_addr = state
+        .pool_cache
+        .get(&pool_id)
+        .map(|entry| entry.pool_addr)
+        .ok_or_else(|| anyhow!("{} is not loaded in the router", pool_id.display_name()))?;
+
+    let before = pool_wrapper_inner_ref(state, pool_addr);
+    let version_reconciled = reconcile_pool_inner_version_from_dynamic_fields(state, pool_id)?;
+    let after = pool_wrapper_inner_ref(state, pool_addr);
+
+    let mut report = PoolResyncReport {
+        pool: pool_id.as_str().to_string(),
+        pool_object_id: pool_addr.to_hex_literal(),
+        inner_parent_id: after.map(|(parent, _)| parent.to_hex_literal()),
+        inner_version_before: before.map(|(_, version)| version),
+        inner_version_after: after.map(|(_, version)| version),
+        version_reconciled,
+        inner_child_id: None,
+        inner_child_found: false,
+        inner_object_refreshed: false,
+        inner_type_normalized: false,
+        changed: version_reconciled,
+    };
+
+    let Some((inner_parent, inner_version)) = after else {
+        tracing::warn!(
+            "Router: resync of {} found no readable pool wrapper object",
+            pool_id.display_name()
+        );
+        return Ok(report);
+    };
+
+    let key_bytes = bcs::to_bytes(&inner_version)?;
+    let inner_child = derive_dynamic_field_id(inner_parent, &TypeTag::U64, &key_bytes)?;
+    report.inner_child_id = Some(inner_child.to_hex_literal());
+
+    let Some((type_tag, field_bytes)) = state.env.get_dynamic_field(inner_parent, inner_child).cloned()
+    else {
+        tracing::warn!(
+            "Router: resync of {} found no PoolInner dynamic field for version {}",
+            pool_id.display_name(),
+            inner_version
+        );
+        return Ok(report);
+    };
+    report.inner_child_found = true;
+
+    let normalized = normalize_dynamic_field_type_tag(&type_tag);
+    if normalized != type_tag {
+        state
+            .env
+            .set_dynamic_field(inner_parent, inner_child, normalized, field_bytes.clone());
+        report.inner_type_normalized = true;
+    }
+
+    let object_stale = state
+        .env
+        .get_object(&inner_child)
+        .is_some_and(|obj| obj.bcs_bytes != field_bytes);
+    if object_stale {
+        state
+            .env
+            .set_object_bytes(inner_child, field_bytes)
+            .map_err(|e| anyhow!("failed refreshing PoolInner bytes {}: {}", inner_child, e))?;
+        report.inner_object_refreshed = true;
+    }
+
+    report.changed |= report.inner_type_normalized || report.inner_object_refreshed;
+    tracing::info!(
+        "Router: resynced {} (version {:?} -> {:?}, reconciled={}, refreshed={}, normalized={})",
+        pool_id.display_name(),
+        report.inner_version_before,
+        report.inner_version_after,
+        report.version_reconciled,
+        report.inner_object_refreshed,
+        report.inner_type_normalized
+    );
+    Ok(report)
+}
+
+fn build_clock_input(timestamp_ms: u64) -> Result<ObjectInput> {
+    let clock_addr = AccountAddress::from_hex_literal(CLOCK_OBJECT_ID)?;
+    let mut clock_bytes = Vec::new();
+    clock_bytes.extend_from_slice(clock_addr.as_ref());
+    clock_bytes.extend_from_slice(&timestamp_ms.to_le_bytes());
+
+    Ok(ObjectInput::Shared {
+        id: clock_addr,
+        bytes: clock_bytes,
+        type_tag: Some(TypeTag::from_str("0x2::clock::Clock")?),
+        version: Some(1),
+        mutable: false,
+    })
+}
+
+fn parse_u64_return(return_values: &[Vec<u8>], idx: usize, field_name: &str) -> Result<u64> {
+    let bytes = return_values
+        .get(idx)
+        .ok_or_else(|| anyhow!("Missing {} return value", field_name))?;
+
+    if bytes.len() < 8 {
+        return Err(anyhow!(
+            "Invalid {} bytes length: {}",
+            field_name,
+            bytes.len()
+        ));
+    }
+
+    let mut value_bytes = [0u8; 8];
+    value_bytes.copy_from_slice(&bytes[..8]);
+    Ok(u64::from_le_bytes(value_bytes))
+}
+
+fn parse_u128_return(return_values: &[Vec<u8>], idx: usize, field_name: &str) -> Result<u128> {
+    let bytes = return_values
+        .get(idx)
+        .ok_or_else(|| anyhow!("Missing {} return value", field_name))?;
+
+    if bytes.len() < 16 {
+        return Err(anyhow!(
+            "Invalid {} bytes length: {}",
+            field_name,
+            bytes.len()
+        ));
+    }
+
+    let mut value_bytes = [0u8; 16];
+    value_bytes.copy_from_slice(&bytes[..16]);
+    Ok(u128::from_le_bytes(value_bytes))
+}
+
+fn parse_u8_return(return_values: &[Vec<u8>], idx: usize, field_name: &str) -> Result<u8> {
+    let bytes = return_values
+        .get(idx)
+        .ok_or_else(|| anyhow!("Missing {} return value", field_name))?;
+    let value = bytes
+        .first()
+        .copied()
+        .ok_or_else(|| anyhow!("Invalid {} bytes length: {}", field_name, bytes.len()))?;
+    Ok(value)
+}
+
+fn parse_bool_return(return_values: &[Vec<u8>], idx: usize, field_name: &str) -> Result<bool> {
+    Ok(parse_u8_return(return_values, idx, field_name)? != 0)
+}
+
+fn parse_u64_command_return(
+    effects: &sui_sandbox_core::ptb::TransactionEffects,
+    command_idx: usize,
+    value_idx: usize,
+    field_name: &str,
+) -> Result<u64> {
+    let command_returns = effects
+        .return_values
+        .get(command_idx)
+        .ok_or_else(|| anyhow!("Missing return values for command {}", command_idx))?;
+    parse_u64_return(command_returns, value_idx, field_name)
+}
+
+fn parse_u8_command_return(
+    effects: &sui_sandbox_core::ptb::TransactionEffects,
+    command_idx: usize,
+    value_idx: usize,
+    field_name: &str,
+) -> Result<u8> {
+    let command_returns = effects
+        .return_values
+        .get(command_idx)
+        .ok_or_else(|| anyhow!("Missing return values for command {}", command_idx))?;
+    parse_u8_return(command_returns, value_idx, field_name)
+}
+
+fn parse_u128_command_return(
+    effects: &sui_sandbox_core::ptb::TransactionEffects,
+    command_idx: usize,
+    value_idx: usize,
+    field_name: &str,
+) -> Result<u128> {
+    let command_returns = effects
+        .return_values
+        .get(command_idx)
+        .ok_or_else(|| anyhow!("Missing return values for command {}", command_idx))?;
+    parse_u128_return(command_returns, value_idx, field_name)
+}
+
+fn parse_bool_command_return(
+    effects: &sui_sandbox_core::ptb::TransactionEffects,
+    command_idx: usize,
+    value_idx: usize,
+    field_name: &str,
+) -> Result<bool> {
+    let command_returns = effects
+        .return_values
+        .get(command_idx)
+        .ok_or_else(|| anyhow!("Missing return values for command {}", command_idx))?;
+    parse_bool_return(command_returns, value_idx, field_name)
+}
+
+fn parse_vec_u64_command_return(
+    effects: &sui_sandbox_core::ptb::TransactionEffects,
+    command_idx: usize,
+    value_idx: usize,
+    field_name: &str,
+) -> Result<Vec<u64>> {
+    let command_returns = effects
+        .return_values
+        .get(command_idx)
+        .ok_or_else(|| anyhow!("Missing return values for command {}", command_idx))?;
+    let bytes = command_returns
+        .get(value_idx)
+        .ok_or_else(|| anyhow!("Missing {} return value", field_name))?;
+    bcs::from_bytes::<Vec<u64>>(bytes)
+        .map_err(|e| anyhow!("Failed to decode {} return value as vector<u64>: {}", field_name, e))
+}
+
+fn pool_shared_input(
+    state: &RouterEnvState,
+    pool_id: PoolId,
+    mutable: bool,
+) -> Result<ObjectInput> {
+    let pool_entry = state
+        .pool_cache
+        .get(&pool_id)
+        .ok_or_else(|| anyhow!("Pool {} not loaded in router", pool_id.display_name()))?;
+    let pool_obj = state
+        .env
+        .get_object(&pool_entry.pool_addr)
+        .ok_or_else(|| anyhow!("Pool object missing in env: {}", pool_entry.pool_addr))?;
+    check_pool_inner_version(state, pool_id, pool_entry.pool_addr)?;
+
+    Ok(ObjectInput::Shared {
+        id: pool_entry.pool_addr,
+        bytes: pool_obj.bcs_bytes.clone(),
+        type_tag: Some(pool_entry.pool_type.clone()),
+        version: Some(pool_obj.version),
+        mutable,
+    })
+}
+
+fn registry_shared_input(state: &RouterEnvState, mutable: bool) -> Result<ObjectInput> {
+    let registry_addr = AccountAddress::from_hex_literal(DEEPBOOK_REGISTRY_ID)?;
+    let registry_obj = state
+        .env
+        .get_object(&registry_addr)
+        .ok_or_else(|| anyhow!("Registry object missing in env: {}", registry_addr))?;
+
+    Ok(ObjectInput::Shared {
+        id: registry_addr,
+        bytes: registry_obj.bcs_bytes.clone(),
+        type_tag: Some(TypeTag::from_str(&format!(
+            "{}::registry::Registry",
+            DEEPBOOK_PACKAGE
+        ))?),
+        version: Some(registry_obj.version),
+        mutable,
+    })
+}
+
+fn coin_registry_shared_input(state: &RouterEnvState, mutable: bool) -> Result<ObjectInput> {
+    let registry_addr = AccountAddress::from_hex_literal(COIN_REGISTRY_OBJECT_ID)?;
+    let registry_obj = state
+        .env
+        .get_object(&registry_addr)
+        .ok_or_else(|| anyhow!("Coin registry object missing in env: {}", registry_addr))?;
+
+    Ok(ObjectInput::Shared {
+        id: registry_addr,
+        bytes: registry_obj.bcs_bytes.clone(),
+        type_tag: Some(TypeTag::from_str("0x2::coin_registry::CoinRegistry")?),
+        version: Some(registry_obj.version),
+        mutable,
+    })
+}
+
+fn admin_cap_input(state: &RouterEnvState) -> Result<ObjectInput> {
+    let admin_cap_addr = AccountAddress::from_hex_literal(DEBUG_ADMIN_CAP_ID)?;
+    let admin_cap_obj = state.env.get_object(&admin_cap_addr).ok_or_else(|| {
+        anyhow!(
+            "DeepBook admin cap object missing in env: {}",
+            admin_cap_addr
+        )
+    })?;
+
+    Ok(ObjectInput::ImmRef {
+        id: admin_cap_addr,
+        bytes: admin_cap_obj.bcs_bytes.clone(),
+        type_tag: Some(TypeTag::from_str(&format!(
+            "{}::registry::DeepbookAdminCap",
+            DEEPBOOK_PACKAGE
+        ))?),
+        version: Some(admin_cap_obj.version),
+    })
+}
+
+fn find_created_object_id_by_type(
+    effects: &sui_sandbox_core::ptb::TransactionEffects,
+    expected_type: &str,
+) -> Option<AccountAddress> {
+    let expected_normalized = normalize_type_string(expected_type);
+    effects.object_changes.iter().find_map(|change| match change {
+        sui_sandbox_core::ptb::ObjectChange::Created {
+            id,
+            object_type: Some(type_tag),
+            ..
+        } => {
+            let observed = normalize_type_string(&type_tag.to_string());
+            (observed == expected_normalized).then_some(*id)
+        }
+        _ => None,
+    })
+}
+
+fn reserve_coin_input(state: &mut RouterEnvState, coin_type: &str) -> Result<ObjectInput> {
+    let reserve_id = if let Some(existing) = state.coin_reserve_cache.get(coin_type) {
+        *existing
+    } else if let Some(config) = debug_config_for_coin_type(state, coin_type) {
+        mint_debug_reserve_coin(state, &config, RESERVE_COIN_SEED_AMOUNT)?
+    } else {
+        return Err(anyhow!(
+            "VM reserve coin missing for {}. Expected checkpoint-backed reserve bootstrap during setup.",
+            coin_type
+        ));
+    };
+
+    let reserve_obj = state
+        .env
+        .get_object(&reserve_id)
+        .ok_or_else(|| anyhow!("VM reserve coin missing in env: {}", reserve_id))?;
+
+    Ok(ObjectInput::Owned {
+        id: reserve_id,
+        bytes: reserve_obj.bcs_bytes.clone(),
+        type_tag: Some(reserve_obj.type_tag.clone()),
+        version: Some(reserve_obj.version),
+    })
+}
+
+/// DeepBook events whose payload is decoded into JSON; others keep only the hex
+const DECODED_EVENT_TYPES: [&str; 2] = ["::order_info::OrderFilled", "::order_info::OrderPlaced"];
+
+fn collect_swap_events(
+    bcs_converter: &mut JsonToBcsConverter,
+    effects: &sui_sandbox_core::ptb::TransactionEffects,
+) -> Vec<SwapEvent> {
+    effects
+        .events
+        .iter()
+        .map(|event| SwapEvent {
+            event_type: event.type_tag.clone(),
+            data_hex: hex::encode(&event.data),
+            decoded: decode_swap_event(bcs_converter, &event.type_tag, &event.data),
+        })
+        .collect()
+}
+
+/// Decode an event payload with the bytecode layout of its type, or `None`
+/// when the type is not in `DECODED_EVENT_TYPES` or its layout does not fit.
+fn decode_swap_event(
+    bcs_converter: &mut JsonToBcsConverter,
+    event_type: &str,
+    data: &[u8],
+) -> Option<serde_json::Value> {
+    if !DECODED_EVENT_TYPES
+        .iter()
+        .any(|suffix| event_type.ends_with(suffix))
+    {
+        return None;
+    }
+    match bcs_converter.decode(event_type, data) {
+        Ok(decoded) => Some(decoded),
+        Err(e) => {
+            tracing::debug!("Router: failed to decode {} event: {}", event_type, e);
+            None
+        }
+    }
+}
+
+/// Create a synthetic Clock object at address 0x6
+fn create_clock_object(env: &mut SimulationEnvironment, timestamp_ms: u64) -> Result<()> {
+    // Clock struct in BCS: UID (32 bytes) + timestamp_ms (u64)
+    // UID is the object ID padded to 32 bytes
+    let clock_addr = AccountAddress::from_hex_literal(CLOCK_OBJECT_ID)?;
+    let mut bcs_bytes = Vec::new();
+    bcs_bytes.extend_from_slice(clock_addr.as_ref()); // UID = 32 bytes
+    bcs_bytes.extend_from_slice(&timestamp_ms.to_le_bytes());
+
+    env.load_object_from_data(
+        CLOCK_OBJECT_ID,
+        bcs_bytes,
+        Some("0x2::clock::Clock"),
+        true,  // shared
+        false, // not immutable
+        1,     // version
+    )?;
+
+    tracing::info!("Router: created synthetic Clock at 0x6");
+    Ok(())
+}
+
+/// Deploy the router contract from compiled bytecode, returning the deployed modules
+fn deploy_router_contract(
+    env: &mut SimulationEnvironment,
+) -> Result<(RouterContractInfo, Vec<(String, Vec<u8>)>)> {
+    // Build the router contract
+    let router_dir = resolve_router_contract_dir()?;
+
+    tracing::info!("Router: compiling router contract...");
+
+    // Compile against mainnet dependency addresses so router bytecode links to
+    // the same DeepBook package loaded into the simulation environment.
+    // Fall back to default build for older CLI/environment setups.
+    let mainnet_build = run_sui_move_build(
+        &router_dir,
+        &["move", "build", "--environment", "mainnet", "--force"],
+    );
+    let mut build_method = "mainnet";
+    let mut mainnet_build_error = None;
+    if let Err(mainnet_err) = mainnet_build {
+        tracing::warn!(
+            "Router: `sui move build --environment mainnet` failed, trying default build:\n{}",
+            mainnet_err
+        );
+        run_sui_move_build(&router_dir, &["move", "build", "--force"]).map_err(|fallback_err| {
+            anyhow!(
+                "Router compile failed for both mainnet and default builds.\nMainnet build error:\n{}\nFallback build error:\n{}",
+                mainnet_err,
+                fallback_err
+            )
+        })?;
+        build_method = "default";
+        mainnet_build_error = Some(mainnet_err.to_string());
+    }
+    tracing::info!("Router: contract compiled successfully ({} build)", build_method);
+
+    // Read compiled bytecode from build directory
+    let build_dir = router_dir.join("build/DeepBookRouter/bytecode_modules");
+    let mut modules = Vec::new();
+
+    if build_dir.exists() {
+        for entry in std::fs::read_dir(&build_dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "mv") {
+                let module_name = path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("unknown")
+                    .to_string();
+                let bytecode = std::fs::read(&path)?;
+                tracing::info!(
+                    "Router: loaded module '{}' ({} bytes)",
+                    module_name,
+                    bytecode.len()
+                );
+                modules.push((module_name, bytecode));
+            }
+        }
+    }
+
+    if modules.is_empty() {
+        return Err(anyhow!(
+            "No compiled modules found in {}",
+            build_dir.display()
+        ));
+    }
+
+    let mut module_names: Vec<String> = modules.iter().map(|(name, _)| name.clone()).collect();
+    module_names.sort();
+
+    // Deploy at a synthetic address
+    env.deploy_package_at_address(ROUTER_PACKAGE_ADDR, modules.clone())?;
+    tracing::info!(
+        "Router: deployed router contract at {}",
+        ROUTER_PACKAGE_ADDR
+    );
+
+    let info = RouterContractInfo {
+        address: ROUTER_PACKAGE_ADDR.to_string(),
+        deployed: true,
+        modules: module_names,
+        build_method: build_method.to_string(),
+        mainnet_build_error,
+        deployed_at_unix_ms: now_unix_ms(),
+    };
+    Ok((info, modules))
+}
+
+fn resolve_router_contract_dir() -> Result<PathBuf> {
+    // Primary resolution based on crate location (works regardless of process cwd).
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let rooted_router_dir = manifest_dir.join("../contracts/router");
+    if rooted_router_dir.exists() {
+        return Ok(rooted_router_dir);
+    }
+
+    // Backwards-compatible fallbacks for ad-hoc runs.
+    let cwd_router_dir = Path::new("./contracts/router");
+    if cwd_router_dir.exists() {
+        return Ok(cwd_router_dir.to_path_buf());
+    }
+
+    let parent_router_dir = Path::new("../contracts/router");
+    if parent_router_dir.exists() {
+        return Ok(parent_router_dir.to_path_buf());
+    }
+
+    Err(anyhow!(
+        "Router contract directory not found. Checked: {}, ./contracts/router, ../contracts/router",
+        rooted_router_dir.display()
+    ))
+}
+
+fn run_sui_move_build(router_dir: &Path, args: &[&str]) -> Result<()> {
+    let output = std::process::Command::new("sui")
+        .args(args)
+        .current_dir(router_dir)
+        .output()
+        .map_err(|e| anyhow!("Failed to run `sui {}`: {}", args.join(" "), e))?;
+
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(anyhow!(
+        "`sui {}` failed (status: {}).\nstdout:\n{}\nstderr:\n{}",
+        args.join(" "),
+        output
+            .status
+            .code()
+            .map(|c| c.to_string())
+            .unwrap_or_else(|| "signal".to_string()),
+        stdout,
+        stderr
+    ))
+}
+
+fn run_router_health_check(state: &mut RouterEnvState) -> Result<()> {
+    let config = state.health_check.clone();
+    let report = run_health_probes(state, &config);
+    if report.passed {
+        return Ok(());
+    }
+
+    match report.probes.last() {
+        Some(probe) => Err(anyhow!(
+            "Router health check failed for {} -> {} (probe={}): {}",
+            probe.from_pool.display_name(),
+            probe.to_pool.display_name(),
+            probe.amount,
+            probe.error.as_deref().unwrap_or("unknown error")
+        )),
+        None => Err(anyhow!(
+            "Router health check could not run: at least two pool states are required"
+        )),
+    }
+}
+
+/// Quote each configured pair at each probe amount until one succeeds.
+fn run_health_probes(
+    state: &mut RouterEnvState,
+    config: &RouterHealthCheckConfig,
+) -> RouterHealthCheckReport {
+    let mut probes = Vec::new();
+    let mut skipped_pairs = Vec::new();
+
+    'pairs: for &(from_pool, to_pool) in &config.pairs {
+        if !state.pool_cache.contains_key(&from_pool) || !state.pool_cache.contains_key(&to_pool) {
+            skipped_pairs.push((from_pool, to_pool));
+            continue;
+        }
+
+        for &amount in &config.probe_amounts {
+            let probe = match execute_two_hop_quote(state, from_pool, to_pool, amount) {
+                Ok(quote) => RouterHealthProbe {
+                    from_pool,
+                    to_pool,
+                    amount,
+                    success: true,
+                    final_output: Some(quote.final_output),
+                    intermediate_amount: Some(quote.intermediate_amount),
+                    error: None,
+                },
+                Err(e) => RouterHealthProbe {
+                    from_pool,
+                    to_pool,
+                    amount,
+                    success: false,
+                    final_output: None,
+                    intermediate_amount: None,
+                    error: Some(e.to_string()),
+                },
+            };
+            let passed = probe.success;
+            probes.push(probe);
+            if passed {
+                tracing::info!(
+                    "Router: health check passed via quote_two_hop ({} -> {}, probe={})",
+                    from_pool.display_name(),
+                    to_pool.display_name(),
+                    amount
+                );
+                break 'pairs;
+            }
+        }
+    }
+
+    RouterHealthCheckReport {
+        passed: probes.last().is_some_and(|p| p.success),
+        checked_at_unix_ms: now_unix_ms(),
+        probes,
+        skipped_pairs,
+    }
+}
+
+fn now_unix_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Current VM status and bootstrap provenance of each checkpoint-backed reserve coin.
+fn reserve_coin_checks(state: &RouterEnvState) -> Vec<RouterReserveCoinCheck> {
+    [SUI_TYPE, USDC_TYPE, WAL_TYPE, DEEP_TYPE]
+        .into_iter()
+        .map(|coin_type| {
+            let reserve_id = state.coin_reserve_cache.get(coin_type).copied();
+            let reserve_obj = reserve_id.and_then(|id| state.env.get_object(&id));
+            let provenance = state.reserve_provenance.get(coin_type);
+            RouterReserveCoinCheck {
+                coin_type: coin_type.to_string(),
+                object_id: reserve_id.map(|id| id.to_hex_literal()),
+                present: reserve_obj.is_some(),
+                version: reserve_obj.map(|obj| obj.version),
+                value: reserve_obj.and_then(|obj| parse_coin_value_from_bcs(&obj.bcs_bytes)),
+                source_checkpoint: provenance.map(|p| p.checkpoint),
+                source_value: provenance.map(|p| p.value),
+                source_version: provenance.map(|p| p.version),
+            }
+        })
+        .collect()
+}
+
+/// Startup self-check that must pass before the environment is used.
+fn run_startup_self_check(state: &mut RouterEnvState) -> Result<RouterStartupCheckReport> {
+    let report = startup_self_check_report(state)?;
+    if report.ok {
+        tracing::info!("Router startup self-check passed");
+        return Ok(report);
+    }
+
+    Err(anyhow!(
+        "Router startup self-check failed: {}",
+        report.errors.join(" | ")
+    ))
+}
+
+/// Check shared objects, reserve coins, the router deployment and a probe
+/// two-hop quote; failures are listed in the report rather than returned.
+fn startup_self_check_report(state: &mut RouterEnvState) -> Result<RouterStartupCheckReport> {
+    let mut errors = Vec::new();
+
+    if !state.router_deployed {
+        errors.push("Router package deployment flag is false".to_string());
+    }
+
+    let mut shared_objects = Vec::new();
+    for (name, object_id) in [
+        ("Sui Coin Registry", COIN_REGISTRY_OBJECT_ID),
+        ("DeepBook Registry", DEEPBOOK_REGISTRY_ID),
+        ("Clock", CLOCK_OBJECT_ID),
+    ] {
+        let addr = AccountAddress::from_hex_literal(object_id)?;
+        let obj = state.env.get_object(&addr);
+        let present = obj.is_some();
+        let is_shared = obj.map(|o| o.is_shared).unwrap_or(false);
+        let version = obj.map(|o| o.version);
+
+        if !present {
+            errors.push(format!(
+                "Missing required shared object in VM: {} ({})",
+                name, object_id
+            ));
+        } else if !is_shared {
+            errors.push(format!(
+                "Required object is not shared in VM: {} ({})",
+                name, object_id
+            ));
+        }
+
+        shared_objects.push(RouterSharedObjectCheck {
+            name: name.to_string(),
+            object_id: object_id.to_string(),
+            present,
+            is_shared,
+            version,
+        });
+    }
+
+    let reserve_coins = reserve_coin_checks(state);
+    for check in &reserve_coins {
+        let coin_type = &check.coin_type;
+        if check.object_id.is_none() {
+            errors.push(format!(
+                "Reserve bootstrap missing entry for coin type {}",
+                coin_type
+            ));
+        } else if !check.present {
+            errors.push(format!(
+                "Reserve bootstrap object missing in VM for coin type {}",
+                coin_type
+            ));
+        } else if check.value.unwrap_or(0) == 0 {
+            errors.push(format!(
+                "Reserve coin value is zero for coin type {}",
+                coin_type
+            ));
+        }
+    }
+
+    let router_health_check_passed = match run_router_health_check(state) {
+        Ok(()) => true,
+        Err(e) => {
+            errors.push(format!("Router health check failed: {}", e));
+            false
+        }
+    };
+
+    Ok(RouterStartupCheckReport {
+        ok: errors.is_empty() && state.router_deployed && router_health_check_passed,
+        checked_at_unix_ms: now_unix_ms(),
+        router_package_deployed: state.router_deployed,
+        router_health_check_passed,
+        shared_objects,
+        reserve_coins,
+        packages: state.loaded_packages.clone(),
+        errors,
+    })
+}
+
+/// Single-hop swap PTB commands that join refunds back into the reserves
+const SINGLE_HOP_REFUND_JOIN_COMMANDS: [usize; 2] = [6, 7];
+/// Refund join of a single-hop swap on a fee-free pool (no DEEP split)
+const SINGLE_HOP_NO_DEEP_REFUND_JOIN_COMMANDS: [usize; 1] = [5];
+
+/// Whether swaps on `pool_id` need a DEEP fee coin split from the reserve.
+///
+/// Whitelisted pools charge no trading fees. Their status is read once via
+/// `pool::whitelisted` and cached; `SWAP_SKIP_DEEP_FOR_WHITELISTED=false`
+/// always funds DEEP.
+fn pool_requires_deep(state: &mut RouterEnvState, pool_id: PoolId) -> Result<bool> {
+    if !state.skip_deep_for_whitelisted {
+        return Ok(true);
+    }
+    let entry = state
+        .pool_cache
+        .get(&pool_id)
+        .ok_or_else(|| anyhow!("Pool {} not loaded in router", pool_id.display_name()))?;
+    if let Some(whitelisted) = entry.whitelisted {
+        return Ok(!whitelisted);
+    }
+
+    let deepbook_addr = AccountAddress::from_hex_literal(DEEPBOOK_PACKAGE)?;
+    let (base_type, quote_type) = pool_types(state, pool_id);
+    let inputs = vec![InputValue::Object(pool_shared_input(
+        state, pool_id, false,
+    )?)];
+    let commands = vec![Command::MoveCall {
+        package: deepbook_addr,
+        module: Identifier::new("pool")?,
+        function: Identifier::new("whitelisted")?,
+        type_args: vec![
+            TypeTag::from_str(&base_type)?,
+            TypeTag::from_str(&quote_type)?,
+        ],
+        args: vec![Argument::Input(0)],
+    }];
+    let result = state.env.execute_ptb(inputs, commands);
+    if !result.success {
+        return Err(anyhow!(
+            "pool::whitelisted failed for {}: {}",
+            pool_id.display_name(),
+            result
+                .raw_error
+                .unwrap_or_else(|| "Unknown error".to_string())
+        ));
+    }
+    let effects = result
+        .effects
+        .as_ref()
+        .ok_or_else(|| anyhow!("Missing PTB effects for pool::whitelisted"))?;
+    let whitelisted = parse_bool_command_return(effects, 0, 0, "whitelisted")?;
+
+    if let Some(entry) = state.pool_cache.get_mut(&pool_id) {
+        entry.whitelisted = Some(whitelisted);
+    }
+    if whitelisted {
+        tracing::info!(
+            "Router: {} is whitelisted; swaps skip the DEEP fee split",
+            pool_id.display_name()
+        );
+    }
+    Ok(!whitelisted)
+}
+
+/// Object inputs each command takes mutably (mutable shared or owned).
+///
+/// Must be computed before `execute_ptb`, which consumes the inputs.
+fn command_object_inputs(inputs: &[InputValue], commands: &[Command]) -> Vec<Vec<String>> {
+    commands
+        .iter()
+        .map(|command| {
+            let Command::MoveCall { args, .. } = command else {
+                return Vec::new();
+            };
+            args.iter()
+                .filter_map(|arg| match arg {
+                    Argument::Input(idx) => match inputs.get(*idx as usize) {
+                        Some(InputValue::Object(ObjectInput::Shared {
+                            id, mutable: true, ..
+                        }))
+                        | Some(InputValue::Object(ObjectInput::Owned { id, .. })) => {
+                            Some(id.to_hex_literal())
+                        }
+                        _ => None,
+                    },
+                    _ => None,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Decode a command return value for the layouts swap PTBs produce.
+fn decode_return_value(bytes: &[u8]) -> Option<serde_json::Value> {
+    match bytes.len() {
+        8 => bcs::from_bytes::<u64>(bytes).ok().map(|v| json!(v.to_string())),
+        16 => bcs::from_bytes::<u128>(bytes).ok().map(|v| json!(v.to_string())),
+        // Coin<T> { id: UID, balance: Balance<T> }
+        40 => bcs::from_bytes::<(AccountAddress, u64)>(bytes)
+            .ok()
+            .map(|(id, value)| json!({ "coin_id": id.to_hex_literal(), "value": value.to_string() })),
+        _ => None,
+    }
+}
+
+/// Pair each command of a successful PTB with its return values.
+///
+/// PTBs are atomic, so every command of a successful execution succeeded.
+fn collect_command_effects(
+    effects: &sui_sandbox_core::ptb::TransactionEffects,
+    command_objects: Vec<Vec<String>>,
+) -> Vec<CommandEffect> {
+    command_objects
+        .into_iter()
+        .enumerate()
+        .map(|(index, mutated_objects)| {
+            let returns = effects
+                .return_values
+                .get(index)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            CommandEffect {
+                index,
+                success: true,
+                return_values: returns.iter().map(hex::encode).collect(),
+                decoded_return_values: returns.iter().map(|bytes| decode_return_value(bytes)).collect(),
+                mutated_objects,
+            }
+        })
+        .collect()
+}
+
+/// Whether a PTB error string attributes the failure to one of `commands`.
+///
+/// Matches the command index forms used by execution errors, e.g.
+/// "in command 6" or "command_index: 6".
+fn is_command_failure(raw_error: &str, commands: &[usize]) -> bool {
+    let lower = raw_error.to_ascii_lowercase();
+    commands.iter().any(|idx| {
+        [
+            format!("command {}", idx),
+            format!("command: {}", idx),
+            format!("command_index: {}", idx),
+            format!("command({})", idx),
+        ]
+        .iter()
+        .any(|pattern| {
+            lower.match_indices(pattern.as_str()).any(|(pos, m)| {
+                // Don't let "command 6" match "command 60"
+                !lower[pos + m.len()..].starts_with(|c: char| c.is_ascii_digit())
+            })
+        })
+    })
+}
+
+fn execute_single_hop_swap(
+    state: &mut RouterEnvState,
+    pool_id: PoolId,
+    input_amount: u64,
+    deep_amount: u64,
+    is_sell_base: bool,
+    min_out: u64,
+) -> Result<SingleHopSwapResult> {
+    let (base_type, quote_type) = pool_types(state, pool_id);
+    let base_tag = TypeTag::from_str(&base_type)?;
+    let quote_tag = TypeTag::from_str(&quote_type)?;
+    let (input_coin_type, output_coin_type, swap_fn, output_idx, refund_idx) = if is_sell_base {
+        (
+            base_type.as_str(),
+            quote_type.as_str(),
+            "swap_exact_base_for_quote",
+            1usize, // quote_out
+            0usize, // base_refund
+        )
+    } else {
+        (
+            quote_type.as_str(),
+            base_type.as_str(),
+            "swap_exact_quote_for_base",
+            0usize, // base_out
+            1usize, // quote_refund
+        )
+    };
+    let input_coin_tag = TypeTag::from_str(input_coin_type)?;
+    let output_coin_tag = TypeTag::from_str(output_coin_type)?;
+    let output_coin_obj_tag =
+        TypeTag::from_str(&format!("0x2::coin::Coin<{}>", output_coin_type))?;
+
+    let deepbook_addr = AccountAddress::from_hex_literal(DEEPBOOK_PACKAGE)?;
+    let sui_framework_addr = AccountAddress::from_hex_literal(SUI_FRAMEWORK_PACKAGE)?;
+
+    // Whitelisted pools charge no fees: the swap gets a zero DEEP coin instead
+    // of a reserve split, so the DEEP reserve is not an input at all.
+    let deep_required = pool_requires_deep(state, pool_id)?;
+    let deep_tag = TypeTag::from_str(DEEP_TYPE)?;
+
+    // DEEP inputs come last so the other indices don't depend on `deep_required`.
+    let build_inputs = |state: &mut RouterEnvState| -> Result<Vec<InputValue>> {
+        let recipient = state.env.sender().to_vec();
+        let mut inputs = vec![
+            InputValue::Object(pool_shared_input(state, pool_id, true)?),
+            InputValue::Object(reserve_coin_input(state, input_coin_type)?),
+            InputValue::Pure(bcs::to_bytes(&input_amount)?),
+            InputValue::Pure(bcs::to_bytes(&min_out)?),
+            InputValue::Object(state.next_clock_input()?),
+            InputValue::Pure(recipient),
+        ];
+        if deep_required {
+            inputs.push(InputValue::Object(reserve_coin_input(state, DEEP_TYPE)?));
+            inputs.push(InputValue::Pure(bcs::to_bytes(&deep_amount)?));
+        }
+        Ok(inputs)
+    };
+
+    // With `join_refunds = false`, the refund joins transfer the refunds to the
+    // sender instead of joining them into the reserves; indices are unchanged.
+    let build_commands = |join_refunds: bool| -> Result<Vec<Command>> {
+        let refund_command = |coin_type: &str,
+                              reserve_input: u16,
+                              result_idx: u16|
+         -> Result<Command> {
+            Ok(if join_refunds {
+                Command::MoveCall {
+                    package: sui_framework_addr,
+                    module: Identifier::new("coin")?,
+                    function: Identifier::new("join")?,
+                    type_args: vec![TypeTag::from_str(coin_type)?],
+                    args: vec![
+                        Argument::Input(reserve_input),
+                        Argument::NestedResult(2, result_idx),
+                    ],
+                }
+            } else {
+                Command::MoveCall {
+                    package: sui_framework_addr,
+                    module: Identifier::new("transfer")?,
+                    function: Identifier::new("public_transfer")?,
+                    type_args: vec![TypeTag::from_str(&format!(
+                        "0x2::coin::Coin<{}>",
+                        coin_type
+                    ))?],
+                    args: vec![Argument::NestedResult(2, result_idx), Argument::Input(5)],
+                }
+            })
+        };
+
+        let mut commands = vec![
+            // Create input coin via VM split from reserve.
+            Command::MoveCall {
+                package: sui_framework_addr,
+                module: Identifier::new("coin")?,
+                function: Identifier::new("split")?,
+                type_args: vec![input_coin_tag.clone()],
+                args: vec![Argument::Input(1), Argument::Input(2)],
+            },
+            if deep_required {
+                // Create DEEP fee coin via VM split from reserve.
+                Command::MoveCall {
+                    package: sui_framework_addr,
+                    module: Identifier::new("coin")?,
+                    function: Identifier::new("split")?,
+                    type_args: vec![deep_tag.clone()],
+                    args: vec![Argument::Input(6), Argument::Input(7)],
+                }
+            } else {
+                // Fee-free pool: pass an empty DEEP coin.
+                Command::MoveCall {
+                    package: sui_framework_addr,
+                    module: Identifier::new("coin")?,
+                    function: Identifier::new("zero")?,
+                    type_args: vec![deep_tag.clone()],
+                    args: vec![],
+                }
+            },
+            // Execute the actual swap in MoveVM.
+            Command::MoveCall {
+                package: deepbook_addr,
+                module: Identifier::new("pool")?,
+                function: Identifier::new(swap_fn)?,
+                type_args: vec![base_tag.clone(), quote_tag.clone()],
+                args: vec![
+                    Argument::Input(0), // pool
+                    Argument::Result(0), // input coin
+                    Argument::Result(1), // deep coin
+                    Argument::Input(3),  // min out
+                    Argument::Input(4),  // clock
+                ],
+            },
+            // Extract output amount from returned coin.
+            Command::MoveCall {
+                package: sui_framework_addr,
+                module: Identifier::new("coin")?,
+                function: Identifier::new("value")?,
+                type_args: vec![output_coin_tag.clone()],
+                args: vec![Argument::NestedResult(2, output_idx as u16)],
+            },
+            // Extract unspent input amount from returned input coin.
+            Command::MoveCall {
+                package: sui_framework_addr,
+                module: Identifier::new("coin")?,
+                function: Identifier::new("value")?,
+                type_args: vec![input_coin_tag.clone()],
+                args: vec![Argument::NestedResult(2, refund_idx as u16)],
+            },
+        ];
+        if deep_required {
+            // Extract any DEEP refund.
+            commands.push(Command::MoveCall {
+                package: sui_framework_addr,
+                module: Identifier::new("coin")?,
+                function: Identifier::new("value")?,
+                type_args: vec![deep_tag.clone()],
+                args: vec![Argument::NestedResult(2, 2)],
+            });
+        }
+        // Join input refund back into reserve.
+        commands.push(refund_command(input_coin_type, 1, refund_idx as u16)?);
+        commands.push(if deep_required {
+            // Join DEEP refund back into reserve.
+            refund_command(DEEP_TYPE, 6, 2)?
+        } else {
+            // No fee was charged, so the DEEP coin comes back empty.
+            Command::MoveCall {
+                package: sui_framework_addr,
+                module: Identifier::new("coin")?,
+                function: Identifier::new("destroy_zero")?,
+                type_args: vec![deep_tag.clone()],
+                args: vec![Argument::NestedResult(2, 2)],
+            }
+        });
+        // Transfer output coin so lifecycle is fully VM-driven.
+        commands.push(Command::MoveCall {
+            package: sui_framework_addr,
+            module: Identifier::new("transfer")?,
+            function: Identifier::new("public_transfer")?,
+            type_args: vec![output_coin_obj_tag.clone()],
+            args: vec![
+                Argument::NestedResult(2, output_idx as u16),
+                Argument::Input(5),
+            ],
+        });
+        Ok(commands)
+    };
+    let refund_join_commands: &[usize] = if deep_required {
+        &SINGLE_HOP_REFUND_JOIN_COMMANDS
+    } else {
+        &SINGLE_HOP_NO_DEEP_REFUND_JOIN_COMMANDS
+    };
+
+    let inputs = build_inputs(state)?;
+    let commands = build_commands(true)?;
+    let mut command_objects = command_object_inputs(&inputs, &commands);
+    let mut result = state.env.execute_ptb(inputs, commands);
+    let mut reserve_reconciled = true;
+    if !result.success {
+        let raw_error = result
+            .raw_error
+            .take()
+            .unwrap_or_else(|| "Unknown error".to_string());
+        if !is_command_failure(&raw_error, refund_join_commands) {
+            return Err(anyhow!(
+                "single-hop swap itself failed via pool::{} for {}: {}",
+                swap_fn,
+                pool_id.display_name(),
+                raw_error
+            ));
+        }
+        // PTBs are atomic: the fill executed but was rolled back with the join.
+        if state.refund_join_policy == RefundJoinPolicy::Error {
+            return Err(anyhow!(
+                "single-hop swap via pool::{} for {} succeeded but reserve reconciliation failed \
+                 (refund join aborted; PTB rolled back, pool and reserves unchanged): {}",
+                swap_fn,
+                pool_id.display_name(),
+                raw_error
+            ));
+        }
+
+        tracing::warn!(
+            "Router: refund join failed for {} swap, retrying with refunds transferred to sender: {}",
+            pool_id.display_name(),
+            raw_error
+        );
+        let inputs = build_inputs(state)?;
+        let commands = build_commands(false)?;
+        command_objects = command_object_inputs(&inputs, &commands);
+        result = state.env.execute_ptb(inputs, commands);
+        if !result.success {
+            return Err(anyhow!(
+                "single-hop swap itself failed via pool::{} for {} (refund-transfer fallback): {}",
+                swap_fn,
+                pool_id.display_name(),
+                result
+                    .raw_error
+                    .unwrap_or_else(|| "Unknown error".to_string())
+            ));
+        }
+        reserve_reconciled = false;
+    }
+
+    let effects = result
+        .effects
+        .as_ref()
+        .ok_or_else(|| anyhow!("Missing PTB effects for single-hop swap"))?;
+
+    let output_amount = parse_u64_command_return(effects, 3, 0, "output_amount")?;
+    let input_refund = parse_u64_command_return(effects, 4, 0, "input_refund")?;
+    // Without a DEEP split the whole budget stays in the reserve.
+    let deep_refund = if deep_required {
+        parse_u64_command_return(effects, 5, 0, "deep_refund")?
+    } else {
+        deep_amount
+    };
+    if pool_id.is_debug() {
+        tracing::info!(
+            "Router: debug single-hop swap {} output={}, input_refund={}, deep_refund={}, input={}, deep_in={}",
+            swap_fn,
+            output_amount,
+            input_refund,
+            deep_refund,
+            input_amount,
+            deep_amount
+        );
+    }
+
+    Ok(SingleHopSwapResult {
+        output_amount,
+        input_refund,
+        deep_refund,
+        gas_used: effects.gas_used,
+        events: collect_swap_events(&mut state.bcs_converter, effects),
+        reserve_reconciled,
+        deep_fee_required: deep_required,
+        created_objects: effects.created.iter().map(|id| id.to_hex_literal()).collect(),
+        command_effects: collect_command_effects(effects, command_objects),
+    })
+}
+
+/// Find the smallest input whose quote reaches `output_amount`, then swap it.
+///
+/// Pool quotes grow monotonically with the input, so a binary search over
+/// `[0, min(max_input, reserve)]` takes at most ~64 quote PTBs. The quotes all
+/// use the timestamp the swap will see, and the swap enforces `output_amount`
+/// as min_out, so lot-size rounding can only deliver slightly more.
+fn execute_exact_out_swap(
+    state: &mut RouterEnvState,
+    pool_id: PoolId,
+    output_amount: u64,
+    max_input: u64,
+    deep_amount: u64,
+    is_sell_base: bool,
+) -> Result<ExactOutSwapResult> {
+    if output_amount == 0 {
+        return Err(anyhow!("exact-output target must be greater than zero"));
+    }
+    let (base_type, quote_type) = pool_types(state, pool_id);
+    let input_coin_type = if is_sell_base {
+        &base_type
+    } else {
+        &quote_type
+    };
+    // Creates the debug reserve on first use, so its balance can be read below.
+    reserve_coin_input(state, input_coin_type)?;
+    let reserve_value = state
+        .coin_reserve_cache
+        .get(input_coin_type)
+        .and_then(|id| state.env.get_object(id))
+        .and_then(|obj| parse_coin_value_from_bcs(&obj.bcs_bytes))
+        .ok_or_else(|| anyhow!("Could not read VM reserve balance for {}", input_coin_type))?;
+    let input_cap = max_input.min(reserve_value);
+
+    let clock_ms = state.clock_now_ms();
+    let mut quote_iterations = 0u32;
+    let mut quote = |state: &mut RouterEnvState, input: u64| -> Result<SingleHopQuote> {
+        quote_iterations += 1;
+        let clock = build_clock_input(clock_ms)?;
+        single_hop_quote_with_clock(state, pool_id, input, is_sell_base, clock)
+    };
+
+    let max_quote = if input_cap > 0 {
+        Some(quote(state, input_cap)?)
+    } else {
+        None
+    };
+    let max_quote = match max_quote {
+        Some(max_quote) if max_quote.output_amount >= output_amount => max_quote,
+        _ => {
+            return Err(anyhow!(
+                "{}: {} can deliver at most {} output for up to {} input (requested {})",
+                EXACT_OUT_UNFILLABLE,
+                pool_id.display_name(),
+                max_quote.map_or(0, |q| q.output_amount),
+                input_cap,
+                output_amount
+            ));
+        }
+    };
+
+    let (hi, _) = bisect_exact_out_input(state, &mut quote, output_amount, 0, input_cap, max_quote);
+
+    let swap =
+        execute_single_hop_swap(state, pool_id, hi, deep_amount, is_sell_base, output_amount)?;
+    Ok(ExactOutSwapResult {
+        input_amount: hi,
+        quote_iterations,
+        swap,
+    })
+}
+
+/// Execute a swap along `route` to read its `gas_used`, then undo it.
+///
+/// The swap runs at the current clock without advancing it, funded with the
+/// whole DEEP reserve and no min_out. Afterwards every object and dynamic
+/// field is put back to its captured bytes; objects the swap created (the
+/// output coin) stay behind unreferenced.
+fn estimate_swap_gas(
+    state: &mut RouterEnvState,
+    route: SwapGasRoute,
+    input_amount: u64,
+) -> Result<u64> {
+    // Creates the debug reserves on first use, outside the rolled-back run.
+    let first_hop = match route {
+        SwapGasRoute::SingleHop {
+            pool_id,
+            is_sell_base,
+        } => RouteHop {
+            pool_id,
+            is_sell_base,
+        },
+        SwapGasRoute::TwoHop { from_pool, .. } => RouteHop {
+            pool_id: from_pool,
+            is_sell_base: true,
+        },
+    };
+    let (input_coin_type, _) = first_hop.io_types(state);
+    reserve_coin_input(state, &input_coin_type)?;
+    let deep_amount = state
+        .coin_reserve_cache
+        .get(DEEP_TYPE)
+        .and_then(|id| state.env.get_object(id))
+        .and_then(|obj| parse_coin_value_from_bcs(&obj.bcs_bytes))
+        .ok_or_else(|| anyhow!("Could not read VM reserve balance for {}", DEEP_TYPE))?;
+
+    let clock_override = state.clock_override_ms;
+    state.clock_override_ms = Some(state.clock_now_ms());
+
+    let gas_used = with_rollback(state, true, |state| match route {
+        SwapGasRoute::SingleHop {
+            pool_id,
+            is_sell_base,
+        } => execute_single_hop_swap(state, pool_id, input_amount, deep_amount, is_sell_base, 0)
+            .map(|swap| swap.gas_used),
+        SwapGasRoute::TwoHop { from_pool, to_pool } => {
+            execute_two_hop_swap(state, from_pool, to_pool, input_amount, deep_amount, 0, 0)
+                .map(|swap| swap.gas_used)
+        }
+    });
+
+    state.clock_override_ms = clock_override;
+    gas_used
+}
+
+/// Put every captured object and dynamic field back to its captured bytes.
+fn rollback_env(
+    state: &mut RouterEnvState,
+    objects: &[SnapshotObject],
+    dynamic_fields: &[SnapshotDynamicField],
+) -> Result<()> {
+    for object in objects {
+        let unchanged = match state.env.get_object(&object.id) {
+            Some(current) => current.bcs_bytes == object.bcs_bytes,
+            None => {
+                state.env.load_object_from_data(
+                    &object.id.to_hex_literal(),
+                    object.bcs_bytes.clone(),
+                    Some(&object.type_tag),
+                    object.is_shared,
+                    false,
+                    object.version,
+                )?;
+                true
+            }
+        };
+        if !unchanged {
+            state
+                .env
+                .set_object_bytes(object.id, object.bcs_bytes.clone())
+                .map_err(|e| anyhow!("Failed to roll back object {}: {}", object.id, e))?;
+        }
+    }
+    for field in dynamic_fields {
+        let type_tag = SimulationEnvironment::parse_type_string(&field.type_tag)
+            .ok_or_else(|| anyhow!("Failed to parse field type: {}", field.type_tag))?;
+        state
+            .env
+            .set_dynamic_field(field.parent, field.child, type_tag, field.bcs_bytes.clone());
+    }
+    Ok(())
+}
+
+/// DeepBook `pool::EMinimumQuantityOutNotMet` abort, raised when output < min_out
+pub fn is_min_out_abort(err_text: &str) -> bool {
+    DeepBookAbort::classify(err_text) == Some(DeepBookAbort::MinimumOutputNotMet)
+}
+
+/// Outcome of one atomic two-hop PTB execution
+enum AtomicTwoHopAttempt {
+    Executed(TwoHopSwapResult),
+    /// The PTB aborted; carries the raw VM error
+    Failed(String),
+}
+
+/// Execute a two-hop swap, preferring the atomic PTB.
+///
+/// A failed atomic PTB is retried up to `two_hop_atomic_retries` times after
+/// re-syncing both pools' dynamic fields, since transient failures usually
+/// come from stale PoolInner state. If every attempt fails, the route runs
+/// as two sequential single-hop PTBs under the same per-leg bounds. A min_out
+/// abort on either leg is deterministic, so it is returned immediately: the
+/// sequential path would commit the first leg before the second one aborts.
+fn execute_two_hop_swap(
+    state: &mut RouterEnvState,
+    from_pool: PoolId,
+    to_pool: PoolId,
+    input_amount: u64,
+    deep_amount: u64,
+    min_intermediate: u64,
+    min_final: u64,
+) -> Result<TwoHopSwapResult> {
+    let max_attempts = state.two_hop_atomic_retries.saturating_add(1);
+    let mut last_error = String::new();
+    for attempt in 1..=max_attempts {
+        match attempt_two_hop_swap_atomic(
+            state,
+            from_pool,
+            to_pool,
+            input_amount,
+            deep_amount,
+            min_intermediate,
+            min_final,
+        )? {
+            AtomicTwoHopAttempt::Executed(mut result) => {
+                result.atomic_attempts = attempt;
+                return Ok(result);
+            }
+            AtomicTwoHopAttempt::Failed(err) if is_min_out_abort(&err) => {
+                return Err(anyhow!(
+                    "two-hop swap execution failed ({} -> {}): {}",
+                    from_pool.display_name(),
+                    to_pool.display_name(),
+                    err
+                ));
+            }
+            AtomicTwoHopAttempt::Failed(err) => {
+                tracing::warn!(
+                    "Router: two-hop atomic PTB attempt {}/{} failed for {} -> {}: {}",
+                    attempt,
+                    max_attempts,
+                    from_pool.display_name(),
+                    to_pool.display_name(),
+                    err
+                );
+                last_error = err;
+            }
+        }
+        if attempt < max_attempts {
+            for pool_id in [from_pool, to_pool] {
+                if let Err(e) = resync_pool_state(state, pool_id) {
+                    tracing::warn!(
+                        "Router: resync of {} before atomic retry failed: {}",
+                        pool_id.display_name(),
+                        e
+                    );
+                }
+            }
+        }
+    }
+
+    // Keep execution VM-native by falling back to two sequential single-hop VM swaps.
+    tracing::warn!(
+        "Router: two-hop atomic PTB failed {} time(s) for {} -> {}. Falling back to sequential VM hops.",
+        max_attempts,
+        from_pool.display_name(),
+        to_pool.display_name()
+    );
+    let mut result = execute_two_hop_swap_sequential_vm(
+        state,
+        from_pool,
+        to_pool,
+        input_amount,
+        deep_amount,
+        min_intermediate,
+        min_final,
+    )
+    .map_err(|e| {
+        // Leg 1 already committed; keep the typed error so callers can
+        // account for the USDC it returned.
+        if e.is::<TwoHopSecondLegFailed>() {
+            return e;
+        }
+        anyhow!(
+            "two-hop swap execution failed ({} -> {}): atomic PTB failed after {} attempt(s) ({}); sequential fallback failed: {}",
+            from_pool.display_name(),
+            to_pool.display_name(),
+            max_attempts,
+            last_error,
+            e
+        )
+    })?;
+    result.atomic_attempts = max_attempts;
+    result.sequential_fallback = true;
+    Ok(result)
+}
+
+fn attempt_two_hop_swap_atomic(
+    state: &mut RouterEnvState,
+    from_pool: PoolId,
+    to_pool: PoolId,
+    input_amount: u64,
+    deep_amount: u64,
+    min_intermediate: u64,
+    min_final: u64,
+) -> Result<AtomicTwoHopAttempt> {
+    let (a_type, q_type, b_type) = resolve_two_hop_types(state, from_pool, to_pool)?;
+    let a_tag = TypeTag::from_str(&a_type)?;
+    let q_tag = TypeTag::from_str(&q_type)?;
+    let b_tag = TypeTag::from_str(&b_type)?;
+    let b_coin_obj_tag = TypeTag::from_str(&format!("0x2::coin::Coin<{}>", b_type))?;
+
+    let deepbook_addr = AccountAddress::from_hex_literal(DEEPBOOK_PACKAGE)?;
+    let sui_framework_addr = AccountAddress::from_hex_literal(SUI_FRAMEWORK_PACKAGE)?;
+    let recipient = state.env.sender().to_vec();
+
+    let inputs = vec![
+        InputValue::Object(pool_shared_input(state, from_pool, true)?),
+        InputValue::Object(pool_shared_input(state, to_pool, true)?),
+        InputValue::Object(reserve_coin_input(state, a_type)?),
+        InputValue::Object(reserve_coin_input(state, q_type)?),
+        InputValue::Object(reserve_coin_input(state, DEEP_TYPE)?),
+        InputValue::Pure(bcs::to_bytes(&input_amount)?),
+        InputValue::Pure(bcs::to_bytes(&deep_amount)?),
+        InputValue::Pure(bcs::to_bytes(&min_intermediate)?),
+        InputValue::Object(state.next_clock_input()?),
+        InputValue::Pure(recipient),
+        InputValue::Pure(bcs::to_bytes(&min_final)?),
+    ];
+
+    let commands = vec![
+        // Create A input coin from reserve.
+        Command::MoveCall {
+            package: sui_framework_addr,
+            module: Identifier::new("coin")?,
+            function: Identifier::new("split")?,
+            type_args: vec![a_tag.clone()],
+            args: vec![Argument::Input(2), Argument::Input(5)],
+        },
+        // Create DEEP fee coin from reserve.
+        Command::MoveCall {
+            package: sui_framework_addr,
+            module: Identifier::new("coin")?,
+            function: Identifier::new("split")?,
+            type_args: vec![TypeTag::from_str(DEEP_TYPE)?],
+            args: vec![Argument::Input(4), Argument::Input(6)],
+        },
+        // Leg 1: A -> USDC
+        Command::MoveCall {
+            package: deepbook_addr,
+            module: Identifier::new("pool")?,
+            function: Identifier::new("swap_exact_base_for_quote")?,
+            type_args: vec![a_tag.clone(), q_tag.clone()],
+            args: vec![
+                Argument::Input(0), // first pool
+                Argument::Result(0), // input coin A
+                Argument::Result(1), // deep coin
+                Argument::Input(7),  // min intermediate USDC
+                Argument::Input(8),  // clock
+            ],
+        },
+        // Capture intermediate USDC from leg 1.
+        Command::MoveCall {
+            package: sui_framework_addr,
+            module: Identifier::new("coin")?,
+            function: Identifier::new("value")?,
+            type_args: vec![q_tag.clone()],
+            args: vec![Argument::NestedResult(2, 1)],
+        },
+        // Leg 2: USDC -> B
+        Command::MoveCall {
+            package: deepbook_addr,
+            module: Identifier::new("pool")?,
+            function: Identifier::new("swap_exact_quote_for_base")?,
+            type_args: vec![b_tag.clone(), q_tag.clone()],
+            args: vec![
+                Argument::Input(1),           // second pool
+                Argument::NestedResult(2, 1), // intermediate quote coin
+                Argument::NestedResult(2, 2), // deep coin from leg 1
+                Argument::Input(10),          // final min out
+                Argument::Input(8),           // clock
+            ],
+        },
+        // Extract B output.
+        Command::MoveCall {
+            package: sui_framework_addr,
+            module: Identifier::new("coin")?,
+            function: Identifier::new("value")?,
+            type_args: vec![b_tag],
+            args: vec![Argument::NestedResult(4, 0)],
+        },
+        // Extract A refund from leg 1.
+        Command::MoveCall {
+            package: sui_framework_addr,
+            module: Identifier::new("coin")?,
+            function: Identifier::new("value")?,
+            type_args: vec![a_tag.clone()],
+            args: vec![Argument::NestedResult(2, 0)],
+        },
+        // Extract quote refund from leg 2.
+        Command::MoveCall {
+            package: sui_framework_addr,
+            module: Identifier::new("coin")?,
+            function: Identifier::new("value")?,
+            type_args: vec![q_tag],
+            args: vec![Argument::NestedResult(4, 1)],
+        },
+        // Extract DEEP refund.
+        Command::MoveCall {
+            package: sui_framework_addr,
+            module: Identifier::new("coin")?,
+            function: Identifier::new("value")?,
+            type_args: vec![TypeTag::from_str(DEEP_TYPE)?],
+            args: vec![Argument::NestedResult(4, 2)],
+        },
+        // Join A refund back into reserve.
+        Command::MoveCall {
+            package: sui_framework_addr,
+            module: Identifier::new("coin")?,
+            function: Identifier::new("join")?,
+            type_args: vec![a_tag],
+            args: vec![Argument::Input(2), Argument::NestedResult(2, 0)],
+        },
+        // Join USDC refund from leg 2 back into reserve.
+        Command::MoveCall {
+            package: sui_framework_addr,
+            module: Identifier::new("coin")?,
+            function: Identifier::new("join")?,
+            type_args: vec![TypeTag::from_str(q_type)?],
+            args: vec![Argument::Input(3), Argument::NestedResult(4, 1)],
+        },
+        // Join DEEP refund from leg 2 back into reserve.
+        Command::MoveCall {
+            package: sui_framework_addr,
+            module: Identifier::new("coin")?,
+            function: Identifier::new("join")?,
+            type_args: vec![TypeTag::from_str(DEEP_TYPE)?],
+            args: vec![Argument::Input(4), Argument::NestedResult(4, 2)],
+        },
+        // Transfer B output so lifecycle is fully VM-driven.
+        Command::MoveCall {
+            package: sui_framework_addr,
+            module: Identifier::new("transfer")?,
+            function: Identifier::new("public_transfer")?,
+            type_args: vec![b_coin_obj_tag],
+            args: vec![Argument::NestedResult(4, 0), Argument::Input(9)],
+        },
+    ];
+
+    let command_objects = command_object_inputs(&inputs, &commands);
+    let result = state.env.execute_ptb(inputs, commands);
+    if !result.success {
+        return Ok(AtomicTwoHopAttempt::Failed(
+            result
+                .raw_error
+                .unwrap_or_else(|| "Unknown error".to_string()),
+        ));
+    }
+
+    let effects = result
+        .effects
+        .as_ref()
+        .ok_or_else(|| anyhow!("Missing PTB effects for two-hop swap"))?;
+
+    let intermediate_amount = parse_u64_command_return(effects, 3, 0, "intermediate_amount")?;
+    let output_amount = parse_u64_command_return(effects, 5, 0, "output_amount")?;
+    let input_refund = parse_u64_command_return(effects, 6, 0, "input_refund")?;
+    let quote_refund = parse_u64_command_return(effects, 7, 0, "quote_refund")?;
+    let deep_refund = parse_u64_command_return(effects, 8, 0, "deep_refund")?;
+
+    Ok(AtomicTwoHopAttempt::Executed(TwoHopSwapResult {
+        output_amount,
+        intermediate_amount,
+        input_refund,
+        quote_refund,
+        deep_refund,
+        gas_used: effects.gas_used,
+        events: collect_swap_events(&mut state.bcs_converter, effects),
+        created_objects: effects.created.iter().map(|id| id.to_hex_literal()).collect(),
+        command_effects: collect_command_effects(effects, command_objects),
+        atomic_attempts: 1,
+        sequential_fallback: false,
+    }))
+}
+
+/// Run a two-hop swap as two single-hop PTBs. Leg 1 commits on its own, so a
+/// leg 2 failure is returned as [`TwoHopSecondLegFailed`].
+fn execute_two_hop_swap_sequential_vm(
+    state: &mut RouterEnvState,
+    from_pool: PoolId,
+    to_pool: PoolId,
+    input_amount: u64,
+    deep_amount: u64,
+    min_intermediate: u64,
+    min_final: u64,
+) -> Result<TwoHopSwapResult> {
+    // Hop 1: A -> USDC (sell base)
+    let hop1 = execute_single_hop_swap(
+        state,
+        from_pool,
+        input_amount,
+        deep_amount,
+        true,
+        min_intermediate,
+    )?;
+    // Hop 2: USDC -> B (sell quote/base=false), using leftover DEEP from hop 1.
+    let hop2 = match execute_single_hop_swap(
+        state,
+        to_pool,
+        hop1.output_amount,
+        hop1.deep_refund,
+        false,
+        min_final,
+    ) {
+        Ok(hop2) => hop2,
+        Err(e) => {
+            return Err(TwoHopSecondLegFailed {
+                from_pool,
+                to_pool,
+                first_leg: hop1,
+                error: e.to_string(),
+            }
+            .into())
+        }
+    };
+
+    let mut events = hop1.events;
+    events.extend(hop2.events);
+
+    Ok(TwoHopSwapResult {
+        output_amount: hop2.output_amount,
+        intermediate_amount: hop1.output_amount,
+        input_refund: hop1.input_refund,
+        quote_refund: hop2.input_refund,
+        deep_refund: hop2.deep_refund,
+        gas_used: hop1.gas_used.saturating_add(hop2.gas_used),
+        events,
+        created_objects: hop1
+            .created_objects
+            .into_iter()
+            .chain(hop2.created_objects)
+            .collect(),
+        // Two separate PTBs don't line up with the atomic command layout
+        command_effects: Vec::new(),
+        atomic_attempts: 0,
+        sequential_fallback: true,
+    })
+}
+
+/// Resolve type arguments for a two-hop swap A -> Q -> B: sell the base of
+/// `from_pool` for its quote, then buy the base of `to_pool` with it. Both
+/// pools must share the quote coin.
+fn resolve_two_hop_types(
+    state: &RouterEnvState,
+    from_pool: PoolId,
+    to_pool: PoolId,
+) -> Result<(String, String, String)> {
+    let (a_type, q_type) = pool_types(state, from_pool);
+    let (b_type, to_quote_type) = pool_types(state, to_pool);
+    if q_type != to_quote_type {
+        return Err(anyhow!(
+            "{} and {} do not share a quote coin ({} vs {})",
+            from_pool.display_name(),
+            to_pool.display_name(),
+            q_type,
+            to_quote_type
+        ));
+    }
+    Ok((a_type, q_type, b_type))
+}
+
+// Helper functions that mirror OrderbookBuilder's object loading
+
+fn load_object_for_router(
+    env: &mut SimulationEnvironment,
+    bcs_converter: &mut JsonToBcsConverter,
+    obj: &ExportedObject,
+) -> Result<()> {
+    let bcs_bytes = bcs_converter
+        .convert(&obj.object_type, &obj.object_json)
+        .map_err(|e| {
+            anyhow!(
+                "Router BCS conversion failed for object {} (type: {}): {}",
+                obj.object_id,
+                obj.object_type,
+                e
+            )
+        })?;
+
+    let is_shared = obj.owner_type.as_deref() == Some("Shared");
+
+    env.load_object_from_data(
+        &obj.object_id,
+        bcs_bytes,
+        Some(&obj.object_type),
+        is_shared,
+        false,
+        obj.version,
+    )?;
+
+    Ok(())
+}
+
+fn load_dynamic_field_for_router(
+    env: &mut SimulationEnvironment,
+    bcs_converter: &mut JsonToBcsConverter,
+    obj: &ExportedObject,
+    parent_addr: &str,
+) -> Result<()> {
+    let parent_id = AccountAddress::from_hex_literal(parent_addr)?;
+    let child_id = AccountAddress::from_hex_literal(&obj.object_id)?;
+
+    let corrected_type = correct_bigvector_slice_type(&obj.object_type, &obj.object_json);
+
+    let full_type_tag = SimulationEnvironment::parse_type_string(&corrected_type)
+        .ok_or_else(|| anyhow!("Failed to parse field type: {}", corrected_type))?;
+
+    let bcs_bytes = bcs_converter
+        .convert(&corrected_type, &obj.object_json)
+        .map_err(|e| {
+            anyhow!(
+                "Router BCS conversion failed for dynamic field {} (type: {}): {}",
+                obj.object_id,
+                corrected_type,
+                e
+            )
+        })?;
+
+    env.set_dynamic_field(parent_id, child_id, full_type_tag, bcs_bytes);
+
+    Ok(())
+}
+
+/// Correct BigVector slice types (same logic as OrderbookBuilder)
+fn correct_bigvector_slice_type(type_str: &str, json: &serde_json::Value) -> String {
+    if type_str.contains("::dynamic_field::Field<u64, vector<")
+        && type_str.contains(DEEPBOOK_PACKAGE)
+    {
+        let is_inner_node = json
+            .get("value")
+            .and_then(|v| v.get("vals"))
+            .and_then(|vals| vals.as_array())
+            .map(|arr| arr.first().map(|v| v.is_string()).unwrap_or(true))
+            .unwrap_or(false);
+
+        let element_type = if is_inner_node {
+            "u64".to_string()
+        } else {
+            let vector_start = type_str.find("vector<").unwrap_or(0);
+            let element_type_start = vector_start + 7;
+            let remaining = &type_str[element_type_start..];
+            let mut depth = 1;
+            let mut element_end = 0;
+            for (i, c) in remaining.chars().enumerate() {
+                match c {
+                    '<' => depth += 1,
+                    '>' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            element_end = i;
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            remaining[..element_end].to_string()
+        };
+
+        let slice_type = format!("{}::big_vector::Slice<{}>", DEEPBOOK_PACKAGE, element_type);
+        let vector_start = type_str.find("vector<").unwrap_or(0);
+        let prefix = &type_str[..vector_start];
+        let element_type_start = vector_start + 7;
+        let remaining = &type_str[element_type_start..];
+        let mut depth = 1;
+        let mut element_end = 0;
+        for (i, c) in remaining.chars().enumerate() {
+            match c {
+                '<' => depth += 1,
+                '>' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        element_end = i;
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        let suffix = &type_str[element_type_start + element_end + 1..];
+        format!("{}{}{}", prefix, slice_type, suffix)
+    } else {
+        type_str.to_string()
+    }
+}
+
+fn synthesize_account_dynamic_fields_for_router(
+    env: &mut SimulationEnvironment,
+    bcs_converter: &mut JsonToBcsConverter,
+    loader: &StateLoader,
+) -> Result<usize> {
+    let Some(accounts_table_id) = extract_accounts_table_id(loader) else {
+        tracing::warn!(
+            "Router: {} missing state.accounts table; skipping account-field synthesis",
+            loader.config().pool_id.display_name()
+        );
+        return Ok(0);
+    };
+    let accounts_table_addr = AccountAddress::from_hex_literal(&accounts_table_id)?;
+
+    let account_field_type = format!(
+        "0x2::dynamic_field::Field<{}, {}::account::Account>",
+        OBJECT_ID_TYPE, DEEPBOOK_PACKAGE
+    );
+    let account_field_tag = SimulationEnvironment::parse_type_string(&account_field_type)
+        .ok_or_else(|| anyhow!("Failed to parse type: {}", account_field_type))?;
+    let object_id_tag = TypeTag::from_str(OBJECT_ID_TYPE)?;
+
+    let mut existing_child_ids = HashSet::new();
+    for obj in loader.all_objects() {
+        if obj.owner_address.as_deref() == Some(accounts_table_id.as_str())
+            && obj.object_type.contains("dynamic_field::Field")
+        {
+            if let Ok(child_id) = AccountAddress::from_hex_literal(&obj.object_id) {
+                existing_child_ids.insert(child_id);
+            }
+        }
+    }
+
+    let mut order_ids_by_balance_manager: HashMap<String, HashSet<u128>> = HashMap::new();
+    for obj in loader.all_objects() {
+        if !(obj.object_type.contains("big_vector::Slice")
+            && obj.object_type.contains("order::Order"))
+        {
+            continue;
+        }
+
+        let Some(vals) = obj
+            .object_json
+            .get("value")
+            .and_then(|value| value.get("vals"))
+            .and_then(|vals| vals.as_array())
+        else {
+            continue;
+        };
+
+        for order in vals {
+            let Some(balance_manager_id) = order.get("balance_manager_id").and_then(|v| v.as_str())
+            else {
+                continue;
+            };
+            let Some(order_id_str) = order.get("order_id").and_then(|v| v.as_str()) else {
+                continue;
+            };
+            let Ok(order_id) = order_id_str.parse::<u128>() else {
+                continue;
+            };
+
+            order_ids_by_balance_manager
+                .entry(balance_manager_id.to_string())
+                .or_default()
+                .insert(order_id);
+        }
+    }
+
+    let mut synthesized = 0usize;
+    for (balance_manager_id, mut order_ids) in order_ids_by_balance_manager {
+        let manager_addr = match AccountAddress::from_hex_literal(&balance_manager_id) {
+            Ok(addr) => addr,
+            Err(err) => {
+                tracing::warn!(
+                    "Router: skipping malformed balance_manager_id {}: {}",
+                    balance_manager_id,
+                    err
+                );
+                continue;
+            }
+        };
+
+        let key_bytes = bcs::to_bytes(&manager_addr)
+            .map_err(|e| anyhow!("Failed to encode synthetic account key: {}", e))?;
+
+        let child_id = derive_dynamic_field_id(accounts_table_addr, &object_id_tag, &key_bytes)
+            .map_err(|e| anyhow!("Failed to derive account dynamic field ID: {}", e))?;
+
+        if existing_child_ids.contains(&child_id) {
+            continue;
+        }
+
+        let mut open_orders: Vec<u128> = order_ids.drain().collect();
+        open_orders.sort_unstable();
+
+        let field_json = json!({
+            "id": { "id": child_id.to_hex_literal() },
+            "name": { "id": manager_addr.to_hex_literal() },
+            "value": {
+                "epoch": "0",
+                "open_orders": {
+                    "contents": open_orders
+                        .into_iter()
+                        .map(|order_id| serde_json::Value::String(order_id.to_string()))
+                        .collect::<Vec<_>>()
+                },
+                "taker_volume": "0",
+                "maker_volume": "0",
+                "active_stake": "0",
+                "inactive_stake": "0",
+                "created_proposal": false,
+                "voted_proposal": serde_json::Value::Null,
+                "unclaimed_rebates": { "base": "0", "quote": "0", "deep": "0" },
+                "settled_balances": { "base": "0", "quote": "0", "deep": "0" },
+                "owed_balances": { "base": "0", "quote": "0", "deep": "0" }
+            }
+        });
+
+        let field_bytes = bcs_converter
+            .convert(&account_field_type, &field_json)
+            .map_err(|e| {
+                anyhow!(
+                    "Failed to encode synthetic account dynamic field for {}: {}",
+                    manager_addr,
+                    e
+                )
+            })?;
+
+        env.set_dynamic_field(
+            accounts_table_addr,
+            child_id,
+            account_field_tag.clone(),
+            field_bytes,
+        );
+        existing_child_ids.insert(child_id);
+        synthesized += 1;
+    }
+
+    Ok(synthesized)
+}
+
+fn extract_accounts_table_id(loader: &StateLoader) -> Option<String> {
+    loader.all_objects().find_map(|obj| {
+        if !obj.object_type.contains("pool::PoolInner") {
+            return None;
+        }
+
+        obj.object_json
+            .get("value")
+            .and_then(|value| value.get("state"))
+            .and_then(|state| state.get("accounts"))
+            .and_then(|accounts| accounts.get("id"))
+            .and_then(|id| id.get("id"))
+            .and_then(|id| id.as_str())
+            .map(|id| id.to_string())
+    })
+}
+
+/// Read a pool's book sizing, trade fees and whitelist status.
+///
+/// The first read runs `pool_book_params`, `pool_trade_params` and
+/// `whitelisted` in one PTB; later reads return the copy cached on the pool's
+/// cache entry, which is replaced when the debug pool is recreated.
+fn read_pool_params(state: &mut RouterEnvState, pool_id: PoolId) -> Result<PoolParams> {
+    let entry = state
+        .pool_cache
+        .get(&pool_id)
+        .ok_or_else(|| anyhow!("{} is not loaded in the router", pool_id.display_name()))?;
+    if let Some(params) = entry.params {
+        return Ok(params);
+    }
+
+    let deepbook_addr = AccountAddress::from_hex_literal(DEEPBOOK_PACKAGE)?;
+    let (base_type, quote_type) = pool_types(state, pool_id);
+    let type_args = vec![
+        TypeTag::from_str(&base_type)?,
+        TypeTag::from_str(&quote_type)?,
+    ];
+    let pool_input = pool_shared_input(state, pool_id, false)?;
+    let inputs = vec![InputValue::Object(pool_input)];
+    let commands = ["pool_book_params", "pool_trade_params", "whitelisted"]
+        .into_iter()
+        .map(|function| {
+            Ok(Command::MoveCall {
+                package: deepbook_addr,
+                module: Identifier::new("pool")?,
+                function: Identifier::new(function)?,
+                type_args: type_args.clone(),
+                args: vec![Argument::Input(0)],
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let result = state.env.execute_ptb(inputs, commands);
+    if !result.success {
+        return Err(anyhow!(
+            "pool params PTB failed for {}: {}",
+            pool_id.display_name(),
+            result
+                .raw_error
+                .unwrap_or_else(|| "Unknown error".to_string())
+        ));
+    }
+    let effects = result
+        .effects
+        .as_ref()
+        .ok_or_else(|| anyhow!("Missing PTB effects for pool params read"))?;
+    sync_dynamic_field_entries(state, effects);
+
+    let params = PoolParams {
+        tick_size: parse_u64_command_return(effects, 0, 0, "tick_size")?,
+        lot_size: parse_u64_command_return(effects, 0, 1, "lot_size")?,
+        min_size: parse_u64_command_return(effects, 0, 2, "min_size")?,
+        taker_fee: parse_u64_command_return(effects, 1, 0, "taker_fee")?,
+        maker_fee: parse_u64_command_return(effects, 1, 1, "maker_fee")?,
+        whitelisted: parse_bool_command_return(effects, 2, 0, "whitelisted")?,
+    };
+    if let Some(entry) = state.pool_cache.get_mut(&pool_id) {
+        entry.whitelisted = Some(params.whitelisted);
+        entry.params = Some(params);
+    }
+    Ok(params)
+}
+
+/// Read a pool's `OrderDeepPrice` via `pool::get_order_deep_price`.
+///
+/// Whitelisted pools skip the read: they charge no fees, so their order deep
+/// price carries no DEEP conversion.
+fn read_deep_price(state: &mut RouterEnvState, pool_id: PoolId) -> Result<PoolDeepPrice> {
+    let params = read_pool_params(state, pool_id)?;
+    let mut deep_price = PoolDeepPrice {
+        pool: pool_id.as_str().to_string(),
+        whitelisted: params.whitelisted,
+        deep_fee_applies: !params.whitelisted,
+        asset_is_base: None,
+        deep_per_asset: None,
+        taker_fee: params.taker_fee,
+        maker_fee: params.maker_fee,
+    };
+    if params.whitelisted {
+        return Ok(deep_price);
+    }
+
+    let deepbook_addr = AccountAddress::from_hex_literal(DEEPBOOK_PACKAGE)?;
+    let (base_type, quote_type) = pool_types(state, pool_id);
+    let inputs = vec![InputValue::Object(pool_shared_input(
+        state, pool_id, false,
+    )?)];
+    let commands = vec![
+        Command::MoveCall {
+            package: deepbook_addr,
+            module: Identifier::new("pool")?,
+            function: Identifier::new("get_order_deep_price")?,
+            type_args: vec![
+                TypeTag::from_str(&base_type)?,
+                TypeTag::from_str(&quote_type)?,
+            ],
+            args: vec![Argument::Input(0)],
+        },
+        Command::MoveCall {
+            package: deepbook_addr,
+            module: Identifier::new("deep_price")?,
+            function: Identifier::new("asset_is_base")?,
+            type_args: vec![],
+            args: vec![Argument::NestedResult(0, 0)],
+        },
+        Command::MoveCall {
+            package: deepbook_addr,
+            module: Identifier::new("deep_price")?,
+            function: Identifier::new("deep_per_asset")?,
+            type_args: vec![],
+            args: vec![Argument::NestedResult(0, 0)],
+        },
+    ];
+
+    let result = state.env.execute_ptb(inputs, commands);
+    if !result.success {
+        return Err(anyhow!(
+            "deep price PTB failed for {}: {}",
+            pool_id.display_name(),
+            result
+                .raw_error
+                .unwrap_or_else(|| "Unknown error".to_string())
+        ));
+    }
+    let effects = result
+        .effects
+        .as_ref()
+        .ok_or_else(|| anyhow!("Missing PTB effects for deep price read"))?;
+    sync_dynamic_field_entries(state, effects);
+
+    deep_price.asset_is_base = Some(parse_bool_command_return(effects, 1, 0, "asset_is_base")?);
+    deep_price.deep_per_asset = Some(parse_u64_command_return(effects, 2, 0, "deep_per_asset")?);
+    Ok(deep_price)
+}
+
+/// Assemble a pool's key `PoolInner` state.
+///
+/// Scalar fields come from DeepBook view functions batched into one PTB; the
+/// account count and current-epoch volumes are read from dynamic fields.
+fn read_pool_state(state: &mut RouterEnvState, pool_id: PoolId) -> Result<RouterPoolState> {
+    let deepbook_addr = AccountAddress::from_hex_literal(DEEPBOOK_PACKAGE)?;
+    let (base_type, quote_type) = pool_types(state, pool_id);
+    let type_args = vec![
+        TypeTag::from_str(&base_type)?,
+        TypeTag::from_str(&quote_type)?,
+    ];
+
+    let pool_input = pool_shared_input(state, pool_id, false)?;
+    let inputs = vec![InputValue::Object(pool_input)];
+    let commands = [
+        "pool_book_params",
+        "pool_trade_params",
+        "whitelisted",
+        "registered_pool",
+        "stable_pool",
+        "vault_balances",
+    ]
+    .into_iter()
+    .map(|function| {
+        Ok(Command::MoveCall {
+            package: deepbook_addr,
+            module: Identifier::new("pool")?,
+            function: Identifier::new(function)?,
+            type_args: type_args.clone(),
+            args: vec![Argument::Input(0)],
+        })
+    })
+    .collect::<Result<Vec<_>>>()?;
+
+    let result = state.env.execute_ptb(inputs, commands);
+    if !result.success {
+        return Err(anyhow!(
+            "pool state PTB failed for {}: {}",
+            pool_id.display_name(),
+            result
+                .raw_error
+                .unwrap_or_else(|| "Unknown error".to_string())
+        ));
+    }
+    let effects = result
+        .effects
+        .as_ref()
+        .ok_or_else(|| anyhow!("Missing PTB effects for pool state read"))?;
+    sync_dynamic_field_entries(state, effects);
+
+    let book_params = BookParams {
+        tick_size: parse_u64_command_return(effects, 0, 0, "tick_size")?,
+        lot_size: parse_u64_command_return(effects, 0, 1, "lot_size")?,
+        min_size: parse_u64_command_return(effects, 0, 2, "min_size")?,
+    };
+    let trade_params = TradeParams {
+        taker_fee: parse_u64_command_return(effects, 1, 0, "taker_fee")?,
+        maker_fee: parse_u64_command_return(effects, 1, 1, "maker_fee")?,
+        stake_required: parse_u64_command_return(effects, 1, 2, "stake_required")?,
+    };
+    let whitelisted = parse_bool_command_return(effects, 2, 0, "whitelisted")?;
+    let registered_pool = parse_bool_command_return(effects, 3, 0, "registered_pool")?;
+    let stable_pool = parse_bool_command_return(effects, 4, 0, "stable_pool")?;
+    let vault_balances = AccountBalances {
+        base: parse_u64_command_return(effects, 5, 0, "vault_base")?,
+        quote: parse_u64_command_return(effects, 5, 1, "vault_quote")?,
+        deep: parse_u64_command_return(effects, 5, 2, "vault_deep")?,
+    };
+
+    let entry = state
+        .pool_cache
+        .get(&pool_id)
+        .ok_or_else(|| anyhow!("{} is not loaded in the router", pool_id.display_name()))?;
+    let account_count = entry.accounts_table.map(|table| {
+        state
+            .env
+            .get_dynamic_fields_for_parent(table)
+            .into_iter()
+            .count()
+    });
+    let current_volumes = match entry.history_epoch {
+        Some(epoch) => Some(read_epoch_volumes(state, pool_id, Some(epoch))?),
+        None => None,
+    };
+
+    Ok(RouterPoolState {
+        pool: pool_id.as_str().to_string(),
+        pool_object_id: entry.pool_addr.to_hex_literal(),
+        whitelisted,
+        registered_pool,
+        stable_pool,
+        book_params,
+        trade_params,
+        vault_balances,
+        history_epoch: entry.history_epoch,
+        account_count,
+        current_volumes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_coin_type_is_distinct_per_symbol() {
+        let dbg = debug_coin_type("DBG");
+        assert_eq!(
+            dbg,
+            format!("0xdb{}444247::debug_token::DEBUG_TOKEN", "0".repeat(56))
+        );
+        assert_eq!(debug_coin_type(" dbg "), dbg);
+        assert_ne!(debug_coin_type("TEST"), dbg);
+        assert_eq!(DebugPoolCreateConfig::default().token_type(), dbg);
+        assert_eq!(
+            debug_treasury_type("DBG"),
+            format!("0x2::coin::TreasuryCap<{}>", dbg)
+        );
+    }
+
+    #[test]
+    fn test_default_debug_pool_config_is_valid() {
+        validate_debug_pool_config(&DebugPoolCreateConfig::default()).unwrap();
+    }
+
+    #[test]
+    fn test_validate_debug_pool_config_rejects_bad_sizes_and_prices() {
+        let check = |edit: fn(&mut DebugPoolCreateConfig), expected: &str| {
+            let mut config = DebugPoolCreateConfig::default();
+            edit(&mut config);
+            let err = validate_debug_pool_config(&config)
+                .expect_err("config should be rejected")
+                .to_string();
+            assert!(err.starts_with(INVALID_DEBUG_POOL_CONFIG), "{}", err);
+            assert!(err.contains(expected), "{}", err);
+        };
+
+        check(|c| c.tick_size = 0, "tick_size must be a power of ten");
+        check(|c| c.lot_size = 100, "lot_size must be at least 1000");
+        check(|c| c.lot_size = 1_500, "lot_size must be a power of ten");
+        check(|c| c.min_size = 100, "must divide min_size");
+        check(|c| c.bid_price = 900_500, "bid_price (900500)");
+        check(|c| c.ask_price = c.bid_price, "must be below ask_price");
+        check(|c| c.ask_quantity = 100_000_000_500, "ask_quantity");
+        check(|c| c.bid_quantity = 1_000, "bid_quantity");
+        check(|c| c.quote_liquidity = 1, "quote_liquidity");
+        check(|c| c.base_liquidity = 1, "base_liquidity");
+        check(|c| c.bid_levels = 0, "bid_levels must be between 1 and");
+        check(|c| c.price_step = 0, "price_step");
+        check(
+            |c| c.maker_senders = vec!["0xbb".into(), "maker".into()],
+            "invalid maker sender \"maker\"",
+        );
+        check(
+            |c| {
+                c.bid_levels = 10;
+                c.price_step = 100;
+            },
+            "falls to zero",
+        );
+        check(|c| c.ask_levels = 3, "split across 3 ask levels");
+    }
+
+    #[test]
+    fn test_seed_ladder_steps_away_from_mid_and_splits_liquidity() {
+        let config = DebugPoolCreateConfig {
+            bid_levels: 3,
+            ask_levels: 2,
+            price_step: 5,
+            base_liquidity: 200_000_000_000,
+            quote_liquidity: 300_000_000,
+            ..DebugPoolCreateConfig::default()
+        };
+        validate_debug_pool_config(&config).unwrap();
+
+        let ladder = config.seed_ladder();
+        let asks: Vec<u64> = ladder
+            .iter()
+            .filter(|o| !o.is_bid)
+            .map(|o| o.price)
+            .collect();
+        let bids: Vec<u64> = ladder
+            .iter()
+            .filter(|o| o.is_bid)
+            .map(|o| o.price)
+            .collect();
+        assert_eq!(asks, vec![1_100_000, 1_105_000]);
+        assert_eq!(bids, vec![900_000, 895_000, 890_000]);
+        assert!(!ladder[0].is_bid, "asks are placed first");
+        assert_eq!(ladder[0].base_deposit, 100_000_000_000);
+        assert_eq!(ladder[0].quote_deposit, 0);
+        assert_eq!(ladder[2].quote_deposit, 100_000_000);
+        assert_eq!(ladder[2].base_deposit, 0);
+    }
+
+    #[test]
+    fn test_seed_makers_default_to_the_builtin_maker() {
+        let config = DebugPoolCreateConfig::default();
+        assert_eq!(
+            config.seed_makers().unwrap(),
+            vec![AccountAddress::from_hex_literal(DEBUG_POOL_MAKER_SENDER).unwrap()]
+        );
+
+        let config = DebugPoolCreateConfig {
+            maker_senders: vec!["0xb1".into(), " 0xb2 ".into()],
+            ..DebugPoolCreateConfig::default()
+        };
+        validate_debug_pool_config(&config).unwrap();
+        assert_eq!(
+            config.seed_makers().unwrap(),
+            vec![
+                AccountAddress::from_hex_literal("0xb1").unwrap(),
+                AccountAddress::from_hex_literal("0xb2").unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(feature = "router-fixture-tests"),
+        ignore = "needs the recorded router fixture"
+    )]
+    fn test_resolve_two_hop_types_follow_loaded_pools_from_fixtures() {
+        let mut state = fixture_router(&[PoolId::SuiUsdc, PoolId::WalUsdc, PoolId::DeepUsdc]);
+        let (a, q, b) = resolve_two_hop_types(&state, PoolId::SuiUsdc, PoolId::WalUsdc).unwrap();
+        assert_eq!(a, SUI_TYPE);
+        assert_eq!(q, USDC_TYPE);
+        assert_eq!(b, WAL_TYPE);
+
+        let (a, q, b) = resolve_two_hop_types(&state, PoolId::WalUsdc, PoolId::DeepUsdc).unwrap();
+        assert_eq!(a, WAL_TYPE);
+        assert_eq!(q, USDC_TYPE);
+        assert_eq!(b, DEEP_TYPE);
+
+        // A pool quoted in another coin cannot share the intermediate leg
+        state
+            .pool_cache
+            .get_mut(&PoolId::DeepUsdc)
+            .unwrap()
+            .quote_type = SUI_TYPE.to_string();
+        let err = resolve_two_hop_types(&state, PoolId::WalUsdc, PoolId::DeepUsdc).unwrap_err();
+        assert!(err.to_string().contains("do not share a quote coin"));
+    }
+
+    #[test]
+    fn test_deep_price_spacing_requires_strictly_more_than_min() {
+        let start = SYNTHETIC_CLOCK_START_MS;
+        assert!(deep_price_spacing_error(None, start, 1).is_none());
+        assert!(deep_price_spacing_error(
+            Some(start),
+            start + SYNTHETIC_CLOCK_STEP_MS,
+            SYNTHETIC_CLOCK_STEP_MS
+        )
+        .is_none());
+        let err = deep_price_spacing_error(
+            Some(start),
+            start + DEEP_PRICE_MIN_SPACING_MS,
+            DEEP_PRICE_MIN_SPACING_MS,
+        )
+        .expect("exactly 60s apart is rejected");
+        assert!(err.to_string().contains("ROUTER_CLOCK_STEP_MS=60000"));
+        // An explicit timestamp before the last point is never far enough apart.
+        assert!(deep_price_spacing_error(
+            Some(start + SYNTHETIC_CLOCK_STEP_MS),
+            start,
+            SYNTHETIC_CLOCK_STEP_MS
+        )
+        .is_some());
+    }
+
+    #[test]
+    fn test_parse_health_check_pairs() {
+        let pairs = parse_health_check_pairs("deep_usdc:sui_usdc, wal-usdc:deep_usdc").unwrap();
+        assert_eq!(
+            pairs,
+            vec![
+                (PoolId::DeepUsdc, PoolId::SuiUsdc),
+                (PoolId::WalUsdc, PoolId::DeepUsdc)
+            ]
+        );
+        assert!(parse_health_check_pairs("sui_usdc").is_err());
+        assert!(parse_health_check_pairs("sui_usdc:btc_usdc").is_err());
+    }
+
+    #[test]
+    fn test_is_command_failure_matches_exact_index() {
+        let joins = SINGLE_HOP_REFUND_JOIN_COMMANDS;
+        assert!(is_command_failure(
+            "CommandArgumentError { arg_idx: 1, kind: TypeMismatch } in command 6",
+            &joins
+        ));
+        assert!(is_command_failure("ExecutionError { command_index: 7, .. }", &joins));
+        assert!(!is_command_failure("MoveAbort(pool, 3) in command 2", &joins));
+        assert!(!is_command_failure("failure in command 60", &joins));
+        assert!(!is_command_failure("Unknown error", &joins));
+    }
+
+    #[test]
+    fn test_decode_account_field_reads_settled_owed_and_rebates() {
+        let balances = |base, quote, deep| AccountBalances { base, quote, deep };
+        let account = (
+            7u64,                               // epoch
+            vec![1u128, 2u128],                 // open_orders
+            500u128,                            // taker_volume
+            250u128,                            // maker_volume
+            10u64,                              // active_stake
+            0u64,                               // inactive_stake
+            false,                              // created_proposal
+            Some(AccountAddress::ONE),          // voted_proposal
+            balances(1, 2, 3),                  // unclaimed_rebates
+            balances(1_000, 2_000, 0),          // settled_balances
+            balances(0, 0, 40),                 // owed_balances
+        );
+        let bytes = bcs::to_bytes(&(AccountAddress::ZERO, AccountAddress::TWO, account)).unwrap();
+
+        let decoded = decode_account_field(&bytes).unwrap();
+        assert_eq!(decoded.epoch, 7);
+        assert_eq!(decoded.open_orders, vec![1, 2]);
+        assert_eq!(decoded.taker_volume, 500);
+        assert_eq!(decoded.maker_volume, 250);
+        assert_eq!(decoded.unclaimed_rebates.deep, 3);
+        assert_eq!(decoded.settled_balances.base, 1_000);
+        assert_eq!(decoded.settled_balances.quote, 2_000);
+        assert_eq!(decoded.owed_balances.deep, 40);
+
+        assert!(decode_account_field(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_decode_volumes_field_reads_fees_and_trade_params() {
+        let volumes = (
+            9_000u128, // total_volume
+            1_000u128, // total_staked_volume
+            AccountBalances { base: 5, quote: 6, deep: 7 },
+            42u128, // historic_median
+            TradeParams {
+                taker_fee: 1_000_000,
+                maker_fee: 500_000,
+                stake_required: 100,
+            },
+        );
+        let bytes = bcs::to_bytes(&(AccountAddress::ZERO, 12u64, volumes)).unwrap();
+
+        let decoded = decode_volumes_field(&bytes).unwrap();
+        assert_eq!(decoded.total_volume, 9_000);
+        assert_eq!(decoded.total_staked_volume, 1_000);
+        assert_eq!(decoded.total_fees_collected.deep, 7);
+        assert_eq!(decoded.historic_median, 42);
+        assert_eq!(decoded.trade_params.maker_fee, 500_000);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(feature = "router-fixture-tests"),
+        ignore = "needs the recorded router fixture"
+    )]
+    fn test_validate_route_hops_rejects_cycles_and_gaps_from_fixtures() {
+        let state = fixture_router(&[PoolId::SuiUsdc, PoolId::WalUsdc, PoolId::DeepUsdc]);
+        let sell = |pool_id| RouteHop {
+            pool_id,
+            is_sell_base: true,
+        };
+        let buy = |pool_id| RouteHop {
+            pool_id,
+            is_sell_base: false,
+        };
+
+        assert!(
+            validate_route_hops(&state, &[sell(PoolId::WalUsdc), buy(PoolId::SuiUsdc)]).is_ok()
+        );
+        // SUI/USDC twice: sell SUI, buy DEEP, then buy SUI back
+        let cycle = [
+            sell(PoolId::SuiUsdc),
+            buy(PoolId::DeepUsdc),
+            buy(PoolId::SuiUsdc),
+        ];
+        assert!(validate_route_hops(&state, &cycle)
+            .unwrap_err()
+            .to_string()
+            .contains("more than once"));
+        // WAL -> USDC cannot feed a leg that sells SUI
+        let gap = [
+            sell(PoolId::WalUsdc),
+            sell(PoolId::SuiUsdc),
+            buy(PoolId::DeepUsdc),
+        ];
+        assert!(validate_route_hops(&state, &gap)
+            .unwrap_err()
+            .to_string()
+            .contains("do not connect"));
+    }
+
+    #[test]
+    fn test_min_swap_input_per_pool() {
+        let level = |price, total_quantity| PriceLevel {
+            price,
+            total_quantity,
+            order_count: 1,
+        };
+        // (pool, tick_size, lot_size, min_size, best bid) at roughly mainnet sizing
+        let pools = [
+            (
+                PoolId::SuiUsdc,
+                1_000,
+                100_000_000,
+                1_000_000_000,
+                3_500_000,
+            ),
+            (PoolId::WalUsdc, 10, 100_000_000, 1_000_000_000, 400_000),
+            (PoolId::DeepUsdc, 10, 1_000_000, 10_000_000, 150_000_000),
+        ];
+        for (pool_id, tick_size, lot_size, min_size, price) in pools {
+            let params = PoolParams {
+                tick_size,
+                lot_size,
+                min_size,
+                whitelisted: false,
+                taker_fee: 1_000_000,
+                maker_fee: 500_000,
+            };
+            let ask = price + tick_size;
+            let asks = [level(ask, min_size / 2), level(ask + tick_size, min_size)];
+            let name = pool_id.display_name();
+
+            assert_eq!(
+                min_single_hop_input(&params, true, &[]),
+                Some(min_size),
+                "{}: selling base needs min_size",
+                name
+            );
+            // Half of min_size at the best ask, the rest one tick higher.
+            let expected_quote =
+                scaled_mul_ceil(ask, min_size / 2) + scaled_mul_ceil(ask + tick_size, min_size / 2);
+            assert_eq!(
+                min_single_hop_input(&params, false, &asks),
+                Some(expected_quote),
+                "{}: buying base needs the quote for min_size at the asks",
+                name
+            );
+            assert_eq!(
+                min_single_hop_input(&params, false, &asks[..1]),
+                None,
+                "{}: asks thinner than min_size give no minimum",
+                name
+            );
+
+            // Selling into a deep bid: the USDC leg minimum converts back into
+            // at least this pool's own min_size.
+            let bids = [level(price, 1_000 * min_size)];
+            let two_hop = min_two_hop_input(&params, &bids, &params, &asks)
+                .expect("deep books yield a minimum");
+            assert!(
+                two_hop >= min_size,
+                "{}: two-hop minimum below min_size",
+                name
+            );
+            assert_eq!(
+                two_hop % lot_size,
+                0,
+                "{}: two-hop minimum not lot-sized",
+                name
+            );
+            assert!(
+                scaled_mul_floor(price, two_hop) >= expected_quote,
+                "{}: two-hop minimum does not fund the second leg",
+                name
+            );
+            assert_eq!(min_two_hop_input(&params, &[], &params, &asks), None);
+        }
+    }
+
+    #[test]
+    fn test_parse_order_page_decodes_every_order() {
+        // Bit 127 = side (set for asks), bits 64-126 = price, bits 0-63 = sequence
+        let ask_id = (1u128 << 127) | (2_500_000u128 << 64) | 7;
+        let bid_id = (1_500_000u128 << 64) | 3;
+        let order = |order_id: u128, filled: u64, status: u8| {
+            (
+                AccountAddress::TWO,
+                order_id,
+                11u64,                // client_order_id
+                1_000u64,             // quantity
+                filled,               // filled_quantity
+                true,                 // fee_is_deep
+                (false, 25u64),       // order_deep_price
+                3u64,                 // epoch
+                status,               // status
+                1_700_000_000_000u64, // expire_timestamp
+            )
+        };
+        let bytes =
+            bcs::to_bytes(&(vec![order(bid_id, 0, 0), order(ask_id, 400, 1)], true)).unwrap();
+
+        let page = parse_order_page(&bytes).unwrap();
+        assert!(page.has_next_page);
+        assert_eq!(page.orders.len(), 2);
+        assert!(page.orders[0].is_bid);
+        assert_eq!(page.orders[0].price, 1_500_000);
+        assert_eq!(page.orders[0].order_id, bid_id.to_string());
+        assert!(!page.orders[1].is_bid);
+        assert_eq!(page.orders[1].price, 2_500_000);
+        assert_eq!(page.orders[1].filled_quantity, 400);
+        assert_eq!(page.orders[1].status, 1);
+        assert_eq!(order_status_label(page.orders[0].status), "live");
+        assert_eq!(
+            order_status_label(page.orders[1].status),
+            "partially_filled"
+        );
+        assert_eq!(order_status_label(4), "expired");
+        assert_eq!(order_status_label(9), "unknown");
+        assert_eq!(page.orders[1].deep_per_asset, 25);
+        assert_eq!(page.orders[1].expire_timestamp, 1_700_000_000_000);
+
+        assert!(parse_order_page(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    /// Checkpoint state files the router fixture was bootstrapped from
+    const FIXTURE_POOL_FILES: [(PoolId, &str); 3] = [
+        (PoolId::SuiUsdc, "data/sui_usdc_state_cp240M.jsonl"),
+        (PoolId::WalUsdc, "data/wal_usdc_state_cp240M.jsonl"),
+        (PoolId::DeepUsdc, "data/deep_usdc_state_cp240M.jsonl"),
+    ];
+    /// Router snapshot (packages, pool objects, reserve coins, router contract)
+    /// taken right after bootstrapping `FIXTURE_POOL_FILES`
+    const ROUTER_FIXTURE: &str = "data/fixtures/router_env_cp240M.snapshot";
+    /// Amounts the SUI -> USDC -> WAL swap produced against `ROUTER_FIXTURE`
+    const ROUTER_FIXTURE_SWAP: &str = "data/fixtures/router_env_cp240M.swap.json";
+    const FIXTURE_SWAP_INPUT: u64 = 10 * 1_000_000_000; // 10 SUI
+
+    #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+    struct FixtureSwapAmounts {
+        input_amount: u64,
+        intermediate_amount: u64,
+        output_amount: u64,
+    }
+
+    fn fixture_path(file: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join(file)
+    }
+
+    fn fixture_pool_files() -> Vec<(PoolId, String)> {
+        FIXTURE_POOL_FILES
+            .iter()
+            .map(|(pool_id, file)| (*pool_id, file.to_string()))
+            .collect()
+    }
+
+    /// Router environment restored from `ROUTER_FIXTURE` with only `pools` loaded.
+    ///
+    /// No fetcher is installed, so the run is offline and deterministic: an
+    /// object missing from the fixture fails the test instead of being
+    /// fetched from mainnet.
+    fn fixture_router(pools: &[PoolId]) -> RouterEnvState {
+        let path = fixture_path(ROUTER_FIXTURE);
+        let mut snapshot = router_snapshot::read_snapshot(&path).unwrap_or_else(|e| {
+            panic!(
+                "{}; record it with `cargo test record_router_fixture -- --ignored`",
+                e
+            )
+        });
+        assert_eq!(snapshot.pool_files, fixture_pool_files());
+        snapshot.pools.retain(|pool| pools.contains(&pool.pool_id));
+        router_env_from_snapshot(snapshot, false).expect("restore router fixture")
+    }
+
+    /// Re-record `ROUTER_FIXTURE` and `ROUTER_FIXTURE_SWAP` after the `data/`
+    /// state files, the pinned packages, or the router contract change.
+    #[test]
+    #[ignore = "bootstraps over mainnet gRPC and builds the router with the sui CLI"]
+    fn record_router_fixture() {
+        let pool_files = fixture_pool_files();
+        let bootstrap_files: Vec<(PoolId, String)> = pool_files
+            .iter()
+            .map(|(pool_id, file)| (*pool_id, fixture_path(file).display().to_string()))
+            .collect();
+        let state = bootstrap_router_env(&bootstrap_files).expect("router env bootstrap");
+        write_router_snapshot(&state, &pool_files, &fixture_path(ROUTER_FIXTURE))
+            .expect("write router fixture");
+
+        let mut state = fixture_router(&[PoolId::SuiUsdc, PoolId::WalUsdc]);
+        let swap = execute_two_hop_swap(
+            &mut state,
+            PoolId::SuiUsdc,
+            PoolId::WalUsdc,
+            FIXTURE_SWAP_INPUT,
+            DEBUG_POOL_DEEP_FEE_BUDGET,
+            0,
+            0,
+        )
+        .expect("two-hop swap");
+        let amounts = FixtureSwapAmounts {
+            input_amount: FIXTURE_SWAP_INPUT,
+            intermediate_amount: swap.intermediate_amount,
+            output_amount: swap.output_amount,
+        };
+        std::fs::write(
+            fixture_path(ROUTER_FIXTURE_SWAP),
+            serde_json::to_string_pretty(&amounts).unwrap() + "\n",
+        )
+        .expect("write fixture swap amounts");
+    }
+
+    /// End-to-end SUI -> USDC -> WAL swap against the checkpoint fixtures in `data/`.
+    #[test]
+    #[cfg_attr(
+        not(feature = "router-fixture-tests"),
+        ignore = "needs the recorded router fixture"
+    )]
+    fn test_two_hop_swap_sui_to_wal_from_fixtures() {
+        let mut state = fixture_router(&[PoolId::SuiUsdc, PoolId::WalUsdc]);
+        assert!(state.router_deployed);
+        let expected: FixtureSwapAmounts = serde_json::from_str(
+            &std::fs::read_to_string(fixture_path(ROUTER_FIXTURE_SWAP))
+                .expect("fixture swap amounts"),
+        )
+        .expect("parse fixture swap amounts");
+        assert_eq!(expected.input_amount, FIXTURE_SWAP_INPUT);
+
+        let input_amount = FIXTURE_SWAP_INPUT;
+        let first_hop = execute_single_hop_quote(&mut state, PoolId::SuiUsdc, input_amount, true)
+            .expect("first hop quote");
+        let quote = execute_two_hop_quote(&mut state, PoolId::SuiUsdc, PoolId::WalUsdc, input_amount)
+            .expect("two-hop quote");
+        assert!(quote.intermediate_amount > 0);
+        assert!(quote.final_output > 0);
+        // The router's intermediate USDC leg must match a direct SUI/USDC quote.
+        assert_eq!(quote.intermediate_amount, first_hop.output_amount);
+
+        let swap = execute_two_hop_swap(
+            &mut state,
+            PoolId::SuiUsdc,
+            PoolId::WalUsdc,
+            input_amount,
+            DEBUG_POOL_DEEP_FEE_BUDGET,
+            0,
+            0,
+        )
+        .expect("two-hop swap");
+        // Quote and execution run against the same fixture state, so they agree.
+        assert_eq!(swap.intermediate_amount, quote.intermediate_amount);
+        assert_eq!(swap.output_amount, quote.final_output);
+        assert_eq!(
+            FixtureSwapAmounts {
+                input_amount,
+                intermediate_amount: swap.intermediate_amount,
+                output_amount: swap.output_amount,
+            },
+            expected
+        );
+    }
+
+    /// Amounts worked out by hand from the debug pool's seeded levels, so they
+    /// do not depend on anything `record_router_fixture` wrote.
+    #[test]
+    #[cfg_attr(
+        not(feature = "router-fixture-tests"),
+        ignore = "needs the recorded router fixture"
+    )]
+    fn test_debug_pool_quotes_match_seeded_levels_from_fixtures() {
+        let mut state = fixture_router(&[PoolId::SuiUsdc]);
+        ensure_debug_pool(&mut state).expect("debug pool");
+
+        // 10 DBG against the 100 DBG bid at $0.90: 10 * 0.90 = 9 USDC. The
+        // pool is whitelisted, so no DEEP fee is charged.
+        let sell = execute_single_hop_quote(&mut state, PoolId::DebugUsdc, 10_000_000_000, true)
+            .expect("sell quote");
+        assert_eq!(sell.output_amount, 9_000_000);
+        assert_eq!(sell.deep_fee, 0);
+
+        // 11 USDC against the 100 DBG ask at $1.10: 11 / 1.10 = 10 DBG.
+        let buy = execute_single_hop_quote(&mut state, PoolId::DebugUsdc, 11_000_000, false)
+            .expect("buy quote");
+        assert_eq!(buy.output_amount, 10_000_000_000);
+        assert_eq!(buy.deep_fee, 0);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(feature = "router-fixture-tests"),
+        ignore = "needs the recorded router fixture"
+    )]
+    fn test_reverse_quote_returns_smallest_sufficient_input_from_fixtures() {
+        let mut state = fixture_router(&[PoolId::SuiUsdc]);
+        let clock = build_clock_input(state.clock_now_ms()).expect("clock");
+        let target = single_hop_quote_with_clock(
+            &mut state,
+            PoolId::SuiUsdc,
+            10 * 1_000_000_000,
+            true,
+            clock,
+        )
+        .expect("forward quote")
+        .output_amount;
+
+        let reverse = execute_reverse_quote(&mut state, PoolId::SuiUsdc, target, true)
+            .expect("reverse quote");
+        assert!(reverse.output_amount >= target);
+        assert!(reverse.input_amount <= 10 * 1_000_000_000);
+
+        let clock = build_clock_input(state.clock_now_ms()).expect("clock");
+        let below = single_hop_quote_with_clock(
+            &mut state,
+            PoolId::SuiUsdc,
+            reverse.input_amount - 1,
+            true,
+            clock,
+        )
+        .map(|quote| quote.output_amount)
+        .unwrap_or(0);
+        assert!(below < target, "one less input must fall short");
+
+        let err = execute_reverse_quote(&mut state, PoolId::SuiUsdc, u64::MAX, true)
+            .expect_err("no book fills u64::MAX");
+        assert!(err.to_string().starts_with(EXACT_OUT_UNFILLABLE), "{}", err);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(feature = "router-fixture-tests"),
+        ignore = "needs the recorded router fixture"
+    )]
+    fn test_batch_quote_matches_individual_quotes_from_fixtures() {
+        let mut state = fixture_router(&[PoolId::SuiUsdc, PoolId::WalUsdc]);
+        let input_amount = 10 * 1_000_000_000; // 10 SUI
+
+        let clock_before = state.clock_now_ms();
+        let results = execute_batch_quote(
+            &mut state,
+            &[
+                BatchQuoteInput {
+                    route: BatchQuoteRoute::SingleHop {
+                        pool_id: PoolId::SuiUsdc,
+                        is_sell_base: true,
+                    },
+                    input_amount,
+                    clock_timestamp_ms: None,
+                },
+                // The debug pool was never created, so this item fails alone.
+                BatchQuoteInput {
+                    route: BatchQuoteRoute::SingleHop {
+                        pool_id: PoolId::DebugUsdc,
+                        is_sell_base: true,
+                    },
+                    input_amount,
+                    clock_timestamp_ms: None,
+                },
+                BatchQuoteInput {
+                    route: BatchQuoteRoute::TwoHop {
+                        from_pool: PoolId::SuiUsdc,
+                        to_pool: PoolId::WalUsdc,
+                    },
+                    input_amount,
+                    clock_timestamp_ms: None,
+                },
+            ],
+        );
+        assert_eq!(state.clock_now_ms(), clock_before);
+        assert_eq!(results.len(), 3);
+        assert!(results[1].is_err());
+
+        let single = execute_single_hop_quote(&mut state, PoolId::SuiUsdc, input_amount, true)
+            .expect("single-hop quote");
+        let two_hop =
+            execute_two_hop_quote(&mut state, PoolId::SuiUsdc, PoolId::WalUsdc, input_amount)
+                .expect("two-hop quote");
+        match &results[0] {
+            Ok(BatchQuote::SingleHop(quote)) => {
+                assert_eq!(quote.output_amount, single.output_amount)
+            }
+            other => panic!("unexpected single-hop batch result: {:?}", other),
+        }
+        match &results[2] {
+            Ok(BatchQuote::TwoHop(quote)) => {
+                assert_eq!(quote.final_output, two_hop.final_output);
+                assert_eq!(quote.intermediate_amount, two_hop.intermediate_amount);
+            }
+            other => panic!("unexpected two-hop batch result: {:?}", other),
+        }
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(feature = "router-fixture-tests"),
+        ignore = "needs the recorded router fixture"
+    )]
+    fn test_single_hop_swap_enforces_min_out_from_fixtures() {
+        let mut state = fixture_router(&[PoolId::SuiUsdc]);
+        let input_amount = 10 * 1_000_000_000; // 10 SUI
+
+        let err = execute_single_hop_swap(
+            &mut state,
+            PoolId::SuiUsdc,
+            input_amount,
+            DEBUG_POOL_DEEP_FEE_BUDGET,
+            true,
+            u64::MAX,
+        )
+        .expect_err("swap below min_out must abort");
+        assert!(
+            is_min_out_abort(&err.to_string()),
+            "unexpected error: {}",
+            err
+        );
+
+        // The aborted PTB rolled back, so an unbounded swap still fills.
+        let swap = execute_single_hop_swap(
+            &mut state,
+            PoolId::SuiUsdc,
+            input_amount,
+            DEBUG_POOL_DEEP_FEE_BUDGET,
+            true,
+            0,
+        )
+        .expect("swap with min_out = 0");
+        assert!(swap.output_amount > 0);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(feature = "router-fixture-tests"),
+        ignore = "needs the recorded router fixture"
+    )]
+    fn test_simulated_swap_leaves_pool_and_clock_unchanged_from_fixtures() {
+        let mut state = fixture_router(&[PoolId::SuiUsdc]);
+        let input_amount = 10 * 1_000_000_000; // 10 SUI
+        let clock_before = state.next_clock_timestamp_ms;
+
+        let simulated = with_rollback(&mut state, true, |state| {
+            execute_single_hop_swap(
+                state,
+                PoolId::SuiUsdc,
+                input_amount,
+                DEBUG_POOL_DEEP_FEE_BUDGET,
+                true,
+                0,
+            )
+        })
+        .expect("simulated swap");
+        assert!(simulated.output_amount > 0);
+        assert_eq!(state.next_clock_timestamp_ms, clock_before);
+
+        // The real swap fills against the same book the simulation saw.
+        let executed = execute_single_hop_swap(
+            &mut state,
+            PoolId::SuiUsdc,
+            input_amount,
+            DEBUG_POOL_DEEP_FEE_BUDGET,
+            true,
+            0,
+        )
+        .expect("executed swap");
+        assert_eq!(executed.output_amount, simulated.output_amount);
+        assert_eq!(executed.input_refund, simulated.input_refund);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(feature = "router-fixture-tests"),
+        ignore = "needs the recorded router fixture"
+    )]
+    fn test_two_hop_swap_rejects_unreachable_min_out_from_fixtures() {
+        let mut state = fixture_router(&[PoolId::SuiUsdc, PoolId::WalUsdc]);
+
+        let err = execute_two_hop_swap(
+            &mut state,
+            PoolId::SuiUsdc,
+            PoolId::WalUsdc,
+            10 * 1_000_000_000,
+            DEBUG_POOL_DEEP_FEE_BUDGET,
+            0,
+            u64::MAX,
+        )
+        .expect_err("two-hop swap below min_out must abort");
+        assert!(
+            is_min_out_abort(&err.to_string()),
+            "unexpected error: {}",
+            err
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(feature = "router-fixture-tests"),
+        ignore = "needs the recorded router fixture"
+    )]
+    fn test_place_limit_order_bid_rests_on_debug_pool_from_fixtures() {
+        let mut state = fixture_router(&[PoolId::SuiUsdc]);
+        ensure_debug_pool(&mut state).expect("debug pool");
+
+        // One tick below the seeded bid, so the order rests instead of crossing.
+        let params = LimitOrderParams {
+            pool_id: PoolId::DebugUsdc,
+            price: DEBUG_POOL_BID_PRICE - DEBUG_POOL_TICK_SIZE,
+            quantity: 10 * DEBUG_POOL_MIN_SIZE,
+            is_bid: true,
+            expire_timestamp: None,
+            client_order_id: 7,
+            deep_amount: 0,
+        };
+        let first = place_limit_order(&mut state, "session-a", &params).expect("first bid");
+        assert!(first.balance_manager_created);
+        assert!(first.inserted);
+        assert_eq!(first.executed_quantity, 0);
+        assert_eq!(
+            first.quote_deposit,
+            scaled_mul_ceil(params.price, params.quantity)
+        );
+
+        let second = place_limit_order(&mut state, "session-a", &params).expect("second bid");
+        assert!(!second.balance_manager_created);
+        assert_eq!(second.balance_manager_id, first.balance_manager_id);
+
+        let page =
+            fetch_iter_orders(&mut state, PoolId::DebugUsdc, true, 100, None).expect("iter_orders");
+        for placed in [&first, &second] {
+            let order = page
+                .orders
+                .iter()
+                .find(|order| order.order_id == placed.order_id.to_string())
+                .expect("placed bid listed by iter_orders");
+            assert!(order.is_bid);
+            assert_eq!(order.price, params.price);
+            assert_eq!(order.quantity, params.quantity);
+            assert_eq!(order.client_order_id, 7);
+        }
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(feature = "router-fixture-tests"),
+        ignore = "needs the recorded router fixture"
+    )]
+    fn test_debug_faucet_mints_from_treasury_without_reserve_from_fixtures() {
+        let mut state = fixture_router(&[PoolId::SuiUsdc]);
+        let debug_type = DebugPoolCreateConfig::default().token_type();
+        assert!(!state.coin_reserve_cache.contains_key(&debug_type));
+
+        // More than a seeded reserve would ever hold
+        let amount = 2 * RESERVE_COIN_SEED_AMOUNT;
+        let minted = execute_vm_faucet(&mut state, &debug_type, amount).expect("debug faucet");
+        assert_eq!(minted.amount, amount);
+        assert!(!state.coin_reserve_cache.contains_key(&debug_type));
+
+        let again =
+            execute_vm_faucet(&mut state, &debug_type, amount).expect("second debug faucet");
+        assert_eq!(again.amount, amount);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(feature = "router-fixture-tests"),
+        ignore = "needs the recorded router fixture"
+    )]
+    fn test_debug_pools_per_symbol_from_fixtures() {
+        let mut state = fixture_router(&[PoolId::SuiUsdc]);
+        let primary = ensure_debug_pool(&mut state).expect("primary debug pool");
+        assert_eq!(primary.pool_id, PoolId::DebugUsdc);
+
+        let config = DebugPoolCreateConfig {
+            token_symbol: "foo".to_string(),
+            ..DebugPoolCreateConfig::default()
+        };
+        let foo = ensure_debug_pool_with_config(&mut state, config.clone()).expect("FOO pool");
+        assert_eq!(foo.pool_id, PoolId::register_debug("FOO").unwrap());
+        assert_ne!(foo.pool_object_id, primary.pool_object_id);
+        assert_eq!(
+            ensure_debug_pool_with_config(&mut state, config)
+                .expect("same config")
+                .pool_object_id,
+            foo.pool_object_id
+        );
+        assert_eq!(
+            ensure_debug_pool(&mut state).expect("primary").pool_id,
+            PoolId::DebugUsdc
+        );
+
+        for info in [&primary, &foo] {
+            let asks =
+                fetch_iter_orders(&mut state, info.pool_id, false, 100, None).expect("iter_orders");
+            assert_eq!(asks.orders.len(), 1);
+            execute_vm_faucet(&mut state, &info.token_type, 1_000_000_000).expect("debug faucet");
+        }
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(feature = "router-fixture-tests"),
+        ignore = "needs the recorded router fixture"
+    )]
+    fn test_cancel_order_refunds_and_rejects_missing_or_foreign_orders_from_fixtures() {
+        let mut state = fixture_router(&[PoolId::SuiUsdc]);
+        ensure_debug_pool(&mut state).expect("debug pool");
+
+        let params = LimitOrderParams {
+            pool_id: PoolId::DebugUsdc,
+            price: DEBUG_POOL_BID_PRICE - DEBUG_POOL_TICK_SIZE,
+            quantity: 10 * DEBUG_POOL_MIN_SIZE,
+            is_bid: true,
+            expire_timestamp: None,
+            client_order_id: 1,
+            deep_amount: 0,
+        };
+        let mine = place_limit_order(&mut state, "session-a", &params).expect("session-a bid");
+        let theirs = place_limit_order(&mut state, "session-b", &params).expect("session-b bid");
+
+        let err = cancel_order(
+            &mut state,
+            "session-a",
+            PoolId::DebugUsdc,
+            None,
+            theirs.order_id,
+        )
+        .expect_err("cancelling another balance manager's order must fail");
+        assert!(err.to_string().starts_with(ORDER_NOT_OWNED), "{}", err);
+
+        let cancelled = cancel_order(
+            &mut state,
+            "session-a",
+            PoolId::DebugUsdc,
+            None,
+            mine.order_id,
+        )
+        .expect("cancel own bid");
+        assert_eq!(cancelled.order.order_id, mine.order_id.to_string());
+        assert_eq!(cancelled.quote_refund, mine.quote_deposit);
+        assert_eq!(cancelled.base_refund, 0);
+
+        let page =
+            fetch_iter_orders(&mut state, PoolId::DebugUsdc, true, 100, None).expect("iter_orders");
+        assert!(page
+            .orders
+            .iter()
+            .all(|order| order.order_id != mine.order_id.to_string()));
+
+        let err = cancel_order(
+            &mut state,
+            "session-a",
+            PoolId::DebugUsdc,
+            None,
+            mine.order_id,
+        )
+        .expect_err("cancelling twice must fail");
+        assert!(err.to_string().starts_with(ORDER_NOT_FOUND), "{}", err);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(feature = "router-fixture-tests"),
+        ignore = "needs the recorded router fixture"
+    )]
+    fn test_cancel_all_orders_cancels_every_open_order_from_fixtures() {
+        let mut state = fixture_router(&[PoolId::SuiUsdc]);
+        ensure_debug_pool(&mut state).expect("debug pool");
+
+        let none = cancel_all_orders(&mut state, "session-a").expect("no balance manager yet");
+        assert!(none.balance_manager_id.is_none());
+        assert!(none.cancelled.is_empty() && none.failed.is_empty());
+
+        let mut placed = Vec::new();
+        for (client_order_id, ticks) in [(1, 1), (2, 2)] {
+            let params = LimitOrderParams {
+                pool_id: PoolId::DebugUsdc,
+                price: DEBUG_POOL_BID_PRICE - ticks * DEBUG_POOL_TICK_SIZE,
+                quantity: 10 * DEBUG_POOL_MIN_SIZE,
+                is_bid: true,
+                expire_timestamp: None,
+                client_order_id,
+                deep_amount: 0,
+            };
+            placed.push(place_limit_order(&mut state, "session-a", &params).expect("bid"));
+        }
+        let theirs = place_limit_order(
+            &mut state,
+            "session-b",
+            &LimitOrderParams {
+                pool_id: PoolId::DebugUsdc,
+                price: DEBUG_POOL_BID_PRICE - DEBUG_POOL_TICK_SIZE,
+                quantity: 10 * DEBUG_POOL_MIN_SIZE,
+                is_bid: true,
+                expire_timestamp: None,
+                client_order_id: 3,
+                deep_amount: 0,
+            },
+        )
+        .expect("session-b bid");
+
+        let result = cancel_all_orders(&mut state, "session-a").expect("cancel all");
+        assert!(result.failed.is_empty(), "{:?}", result.failed);
+        let mut cancelled: Vec<String> = result
+            .cancelled
+            .iter()
+            .map(|(pool_id, cancelled)| {
+                assert_eq!(*pool_id, PoolId::DebugUsdc);
+                cancelled.order.order_id.clone()
+            })
+            .collect();
+        let mut expected: Vec<String> = placed.iter().map(|p| p.order_id.to_string()).collect();
+        cancelled.sort();
+        expected.sort();
+        assert_eq!(cancelled, expected);
+        let refunded: u64 = result.cancelled.iter().map(|(_, c)| c.quote_refund).sum();
+        assert_eq!(
+            refunded,
+            placed.iter().map(|p| p.quote_deposit).sum::<u64>()
+        );
+
+        let open = session_open_orders(&mut state, "session-a").expect("open orders");
+        assert!(open.orders.is_empty());
+        let theirs_open = session_open_orders(&mut state, "session-b").expect("open orders");
+        assert_eq!(theirs_open.orders.len(), 1);
+        assert_eq!(
+            theirs_open.orders[0].1.order_id,
+            theirs.order_id.to_string()
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(feature = "router-fixture-tests"),
+        ignore = "needs the recorded router fixture"
+    )]
+    fn test_withdraw_settled_claims_filled_maker_order_from_fixtures() {
+        let mut state = fixture_router(&[PoolId::SuiUsdc]);
+        ensure_debug_pool(&mut state).expect("debug pool");
+
+        let err = withdraw_settled(&mut state, "session-a", PoolId::DebugUsdc)
+            .expect_err("no balance manager yet");
+        assert!(err.to_string().starts_with("Session "), "{}", err);
+
+        // Inside the spread, so a taker sell fills it before the seeded bids.
+        let params = LimitOrderParams {
+            pool_id: PoolId::DebugUsdc,
+            price: DEBUG_POOL_BID_PRICE + DEBUG_POOL_TICK_SIZE,
+            quantity: 10 * DEBUG_POOL_MIN_SIZE,
+            is_bid: true,
+            expire_timestamp: None,
+            client_order_id: 1,
+            deep_amount: 0,
+        };
+        let placed = place_limit_order(&mut state, "session-a", &params).expect("maker bid");
+        assert!(placed.inserted);
+
+        let taker =
+            execute_single_hop_swap(&mut state, PoolId::DebugUsdc, params.quantity, 0, true, 0)
+                .expect("taker sell");
+        assert!(taker.output_amount > 0);
+        let open = session_open_orders(&mut state, "session-a").expect("open orders");
+        assert!(open.orders.is_empty(), "maker bid should be filled");
+
+        let withdrawn =
+            withdraw_settled(&mut state, "session-a", PoolId::DebugUsdc).expect("withdraw");
+        assert_eq!(withdrawn.balance_manager_id, placed.balance_manager_id);
+        assert_eq!(withdrawn.base_amount, params.quantity);
+
+        // Everything was claimed, so a second withdrawal is empty.
+        let again =
+            withdraw_settled(&mut state, "session-a", PoolId::DebugUsdc).expect("withdraw again");
+        assert_eq!(
+            (again.base_amount, again.quote_amount, again.deep_amount),
+            (0, 0, 0)
+        );
+    }
+}
@@ -0,0 +1,1247 @@
+//! Limit orders and session balance managers
+//!
+//! Order placement and cancellation through per-session `BalanceManager`s,
+//! `iter_orders` page decoding and whole-book reads, and the account reads
+//! behind session open orders.
+
+use super::*;
+
+fn read_uleb128(cursor: &mut std::io::Cursor<&[u8]>) -> Result<u64> {
+    let mut value = 0u64;
+    let mut shift = 0u32;
+
+    loop {
+        let mut byte = [0u8; 1];
+        cursor
+            .read_exact(&mut byte)
+            .map_err(|e| anyhow!("Failed reading ULEB128: {}", e))?;
+        let b = byte[0];
+        value |= ((b & 0x7f) as u64) << shift;
+
+        if (b & 0x80) == 0 {
+            break;
+        }
+
+        shift += 7;
+        if shift >= 64 {
+            return Err(anyhow!("ULEB128 value too large"));
+        }
+    }
+
+    Ok(value)
+}
+
+fn read_u64_le(cursor: &mut std::io::Cursor<&[u8]>, field: &str) -> Result<u64> {
+    let mut bytes = [0u8; 8];
+    cursor
+        .read_exact(&mut bytes)
+        .map_err(|e| anyhow!("Failed reading {}: {}", field, e))?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_u128_le(cursor: &mut std::io::Cursor<&[u8]>, field: &str) -> Result<u128> {
+    let mut bytes = [0u8; 16];
+    cursor
+        .read_exact(&mut bytes)
+        .map_err(|e| anyhow!("Failed reading {}: {}", field, e))?;
+    Ok(u128::from_le_bytes(bytes))
+}
+
+/// Client-facing name of a DeepBook order status code, matching the
+/// `constants::live` .. `constants::expired` values the `order` module stores
+pub fn order_status_label(status: u8) -> &'static str {
+    match status {
+        0 => "live",
+        1 => "partially_filled",
+        2 => "filled",
+        3 => "canceled",
+        4 => "expired",
+        _ => "unknown",
+    }
+}
+
+/// One resting order decoded from an `order_query::iter_orders` page
+#[derive(Debug, Clone, Serialize)]
+pub struct DecodedOrder {
+    pub balance_manager_id: String,
+    /// u128 order id, as a decimal string
+    pub order_id: String,
+    pub is_bid: bool,
+    /// Raw DeepBook price, packed into bits 64..127 of the order id
+    pub price: u64,
+    pub client_order_id: u64,
+    pub quantity: u64,
+    pub filled_quantity: u64,
+    pub fee_is_deep: bool,
+    pub deep_price_asset_is_base: bool,
+    pub deep_per_asset: u64,
+    pub epoch: u64,
+    pub status: u8,
+    pub expire_timestamp: u64,
+}
+
+/// A decoded `order_query::OrderPage`
+#[derive(Debug, Clone, Serialize)]
+pub struct OrderPage {
+    pub orders: Vec<DecodedOrder>,
+    pub has_next_page: bool,
+}
+
+fn read_bool(cursor: &mut std::io::Cursor<&[u8]>, field: &str) -> Result<bool> {
+    let mut byte = [0u8; 1];
+    cursor
+        .read_exact(&mut byte)
+        .map_err(|e| anyhow!("Failed reading {}: {}", field, e))?;
+    Ok(byte[0] != 0)
+}
+
+/// Decode one BCS `order::Order`
+fn read_order(cursor: &mut std::io::Cursor<&[u8]>, idx: usize) -> Result<DecodedOrder> {
+    let mut balance_manager_id = [0u8; 32];
+    cursor
+        .read_exact(&mut balance_manager_id)
+        .map_err(|e| anyhow!("Failed reading order[{}].balance_manager_id: {}", idx, e))?;
+
+    let order_id = read_u128_le(cursor, "order_id")?;
+    let client_order_id = read_u64_le(cursor, "client_order_id")?;
+    let quantity = read_u64_le(cursor, "quantity")?;
+    let filled_quantity = read_u64_le(cursor, "filled_quantity")?;
+    let fee_is_deep = read_bool(cursor, "fee_is_deep")?;
+    let deep_price_asset_is_base = read_bool(cursor, "order_deep_price.asset_is_base")?;
+    let deep_per_asset = read_u64_le(cursor, "order_deep_price.deep_per_asset")?;
+    let epoch = read_u64_le(cursor, "epoch")?;
+
+    let mut status = [0u8; 1];
+    cursor
+        .read_exact(&mut status)
+        .map_err(|e| anyhow!("Failed reading order[{}].status: {}", idx, e))?;
+    let expire_timestamp = read_u64_le(cursor, "expire_timestamp")?;
+
+    // Bit 127 = side (set for asks), bits 64-126 = price, bits 0-63 = sequence
+    Ok(DecodedOrder {
+        balance_manager_id: AccountAddress::new(balance_manager_id).to_hex_literal(),
+        order_id: order_id.to_string(),
+        is_bid: order_id >> 127 == 0,
+        price: ((order_id >> 64) & ((1u128 << 63) - 1)) as u64,
+        client_order_id,
+        quantity,
+        filled_quantity,
+        fee_is_deep,
+        deep_price_asset_is_base,
+        deep_per_asset,
+        epoch,
+        status: status[0],
+        expire_timestamp,
+    })
+}
+
+pub(super) fn parse_order_page(bytes: &[u8]) -> Result<OrderPage> {
+    let mut cursor = std::io::Cursor::new(bytes);
+    let order_count = read_uleb128(&mut cursor)? as usize;
+
+    let mut orders = Vec::with_capacity(order_count);
+    for idx in 0..order_count {
+        orders.push(read_order(&mut cursor, idx)?);
+    }
+
+    let has_next_page = read_bool(&mut cursor, "has_next_page")?;
+    Ok(OrderPage {
+        orders,
+        has_next_page,
+    })
+}
+
+/// Page through a pool's resting orders via `order_query::iter_orders`.
+///
+/// `start_order_id` resumes after a previous page (pass the last order id).
+pub(super) fn fetch_iter_orders(
+    state: &mut RouterEnvState,
+    pool_id: PoolId,
+    bids: bool,
+    limit: u64,
+    start_order_id: Option<u128>,
+) -> Result<OrderPage> {
+    let deepbook_addr = AccountAddress::from_hex_literal(DEEPBOOK_PACKAGE)?;
+    let (base_type, quote_type) = pool_types(state, pool_id);
+    let base_tag = TypeTag::from_str(&base_type)?;
+    let quote_tag = TypeTag::from_str(&quote_type)?;
+
+    let inputs = vec![
+        InputValue::Object(pool_shared_input(state, pool_id, false)?),
+        InputValue::Pure(bcs::to_bytes(&start_order_id)?),
+        InputValue::Pure(bcs::to_bytes(&Option::<u128>::None)?),
+        InputValue::Pure(bcs::to_bytes(&Option::<u64>::None)?),
+        InputValue::Pure(bcs::to_bytes(&limit)?),
+        InputValue::Pure(bcs::to_bytes(&bids)?),
+    ];
+    let commands = vec![Command::MoveCall {
+        package: deepbook_addr,
+        module: Identifier::new("order_query")?,
+        function: Identifier::new("iter_orders")?,
+        type_args: vec![base_tag, quote_tag],
+        args: vec![
+            Argument::Input(0),
+            Argument::Input(1),
+            Argument::Input(2),
+            Argument::Input(3),
+            Argument::Input(4),
+            Argument::Input(5),
+        ],
+    }];
+
+    let result = state.env.execute_ptb(inputs, commands);
+    if !result.success {
+        return Err(anyhow!(
+            "{} iter_orders({}) failed: {}",
+            pool_id.display_name(),
+            if bids { "bids" } else { "asks" },
+            result
+                .raw_error
+                .unwrap_or_else(|| "Unknown error".to_string())
+        ));
+    }
+
+    let return_bytes = result
+        .effects
+        .as_ref()
+        .and_then(|effects| effects.return_values.first())
+        .and_then(|cmd_returns| cmd_returns.first().cloned())
+        .ok_or_else(|| {
+            anyhow!(
+                "No return values from {} iter_orders",
+                pool_id.display_name()
+            )
+        })?;
+
+    parse_order_page(&return_bytes)
+}
+
+/// Orders fetched per `iter_orders` call when reading a whole book side
+const BOOK_SIDE_PAGE_LIMIT: u64 = 500;
+
+/// A pool's resting orders aggregated into price levels, read from the VM
+#[derive(Debug, Clone)]
+pub struct VmBookLevels {
+    /// Sorted descending by price
+    pub bids: Vec<PriceLevel>,
+    /// Sorted ascending by price
+    pub asks: Vec<PriceLevel>,
+    /// Fully-filled orders (remaining == 0) excluded from the levels
+    pub skipped_filled_orders: usize,
+}
+
+/// Read both sides of `pool_id`'s book as they stand in the VM, so swaps and
+/// limit orders committed since the checkpoint build show up in the levels.
+pub(super) fn read_book_levels(
+    state: &mut RouterEnvState,
+    pool_id: PoolId,
+) -> Result<VmBookLevels> {
+    let (bids, skipped_bids) = read_book_side(state, pool_id, true)?;
+    let (asks, skipped_asks) = read_book_side(state, pool_id, false)?;
+    Ok(VmBookLevels {
+        bids,
+        asks,
+        skipped_filled_orders: skipped_bids + skipped_asks,
+    })
+}
+
+/// Page through one side of the book and aggregate it by price
+fn read_book_side(
+    state: &mut RouterEnvState,
+    pool_id: PoolId,
+    bids: bool,
+) -> Result<(Vec<PriceLevel>, usize)> {
+    let mut levels: std::collections::BTreeMap<u64, (u64, usize)> = Default::default();
+    let mut skipped = 0;
+    let mut cursor: Option<u128> = None;
+    loop {
+        let page = fetch_iter_orders(state, pool_id, bids, BOOK_SIDE_PAGE_LIMIT, cursor)?;
+        let mut last_order_id = None;
+        for order in &page.orders {
+            let order_id = order.order_id.parse::<u128>()?;
+            last_order_id = Some(order_id);
+            if Some(order_id) == cursor {
+                continue;
+            }
+            let remaining = order.quantity.saturating_sub(order.filled_quantity);
+            if remaining == 0 {
+                skipped += 1;
+                continue;
+            }
+            let level = levels.entry(order.price).or_insert((0, 0));
+            level.0 += remaining;
+            level.1 += 1;
+        }
+        match last_order_id {
+            Some(order_id) if page.has_next_page && Some(order_id) != cursor => {
+                cursor = Some(order_id)
+            }
+            _ => break,
+        }
+    }
+
+    let levels = levels
+        .into_iter()
+        .map(|(price, (total_quantity, order_count))| PriceLevel {
+            price,
+            total_quantity,
+            order_count,
+        });
+    // Bids descending, asks ascending, as in `SandboxOrderbook`
+    let levels = if bids {
+        levels.rev().collect()
+    } else {
+        levels.collect()
+    };
+    Ok((levels, skipped))
+}
+
+/// Place a limit order through the balance manager owned by `session_id`.
+///
+/// The session's first order creates a `BalanceManager`, transfers it to the
+/// current VM sender and remembers it; later orders pass it back in as an
+/// owned input and run as that owner. Each order deposits only what it
+/// locks: quote (`price * quantity`) for bids, base for asks, plus
+/// `deep_amount` on pools that charge DEEP fees.
+pub(super) fn place_limit_order(
+    state: &mut RouterEnvState,
+    session_id: &str,
+    params: &LimitOrderParams,
+) -> Result<LimitOrderResult> {
+    let existing = state.session_balance_managers.get(session_id).copied();
+    let original_sender = state.env.sender();
+    let owner = existing.map(|bm| bm.owner).unwrap_or(original_sender);
+    state.env.set_sender(owner);
+    let result = execute_limit_order_ptb(state, existing.map(|bm| bm.id), params);
+    state.env.set_sender(original_sender);
+    let (placed, balance_manager_id) = result?;
+
+    if existing.is_none() {
+        state.session_balance_managers.insert(
+            session_id.to_string(),
+            SessionBalanceManager {
+                id: balance_manager_id,
+                owner,
+            },
+        );
+        tracing::info!(
+            "Router: created balance manager {} for session {}",
+            balance_manager_id.to_hex_literal(),
+            session_id
+        );
+    }
+    Ok(placed)
+}
+
+/// Base and quote a limit order locks: `price * quantity` (rounded up) of
+/// quote for bids, `quantity` of base for asks.
+pub fn limit_order_deposits(is_bid: bool, price: u64, quantity: u64) -> (u64, u64) {
+    if is_bid {
+        (0, scaled_mul_ceil(price, quantity))
+    } else {
+        (quantity, 0)
+    }
+}
+
+fn execute_limit_order_ptb(
+    state: &mut RouterEnvState,
+    balance_manager: Option<AccountAddress>,
+    params: &LimitOrderParams,
+) -> Result<(LimitOrderResult, AccountAddress)> {
+    let pool_id = params.pool_id;
+    let deepbook_addr = AccountAddress::from_hex_literal(DEEPBOOK_PACKAGE)?;
+    let sui_framework_addr = AccountAddress::from_hex_literal(SUI_FRAMEWORK_PACKAGE)?;
+    let (base_type, quote_type) = pool_types(state, pool_id);
+    let base_tag = TypeTag::from_str(&base_type)?;
+    let quote_tag = TypeTag::from_str(&quote_type)?;
+    let bm_type = format!("{}::balance_manager::BalanceManager", DEEPBOOK_PACKAGE);
+    let bm_tag = TypeTag::from_str(&bm_type)?;
+
+    let expire_timestamp = params.expire_timestamp.unwrap_or(u64::MAX);
+    if expire_timestamp <= state.clock_now_ms() {
+        return Err(anyhow!(
+            "expire_timestamp {} is not after the VM clock ({})",
+            expire_timestamp,
+            state.clock_now_ms()
+        ));
+    }
+    let pay_with_deep = pool_requires_deep(state, pool_id)?;
+    let (base_deposit, quote_deposit) =
+        limit_order_deposits(params.is_bid, params.price, params.quantity);
+    let deep_deposit = if pay_with_deep { params.deep_amount } else { 0 };
+
+    let mut inputs = vec![
+        // 0) pool (shared mutable)
+        InputValue::Object(pool_shared_input(state, pool_id, true)?),
+        // 1) client_order_id
+        InputValue::Pure(bcs::to_bytes(&params.client_order_id)?),
+        // 2) order_type = no_restriction
+        InputValue::Pure(bcs::to_bytes(&0_u8)?),
+        // 3) self_matching_option = allowed
+        InputValue::Pure(bcs::to_bytes(&0_u8)?),
+        // 4) price
+        InputValue::Pure(bcs::to_bytes(&params.price)?),
+        // 5) quantity
+        InputValue::Pure(bcs::to_bytes(&params.quantity)?),
+        // 6) is_bid
+        InputValue::Pure(bcs::to_bytes(&params.is_bid)?),
+        // 7) pay_with_deep
+        InputValue::Pure(bcs::to_bytes(&pay_with_deep)?),
+        // 8) expiry
+        InputValue::Pure(bcs::to_bytes(&expire_timestamp)?),
+        // 9) clock
+        InputValue::Object(state.next_clock_input()?),
+    ];
+    // 10) existing balance manager (owned), or the recipient of a new one
+    match balance_manager {
+        Some(id) => {
+            let bm_obj = state
+                .env
+                .get_object(&id)
+                .ok_or_else(|| anyhow!("Session balance manager missing in env: {}", id))?;
+            inputs.push(InputValue::Object(ObjectInput::Owned {
+                id,
+                bytes: bm_obj.bcs_bytes.clone(),
+                type_tag: Some(bm_tag.clone()),
+                version: Some(bm_obj.version),
+            }));
+        }
+        None => inputs.push(InputValue::Pure(state.env.sender().to_vec())),
+    }
+
+    let mut commands = Vec::new();
+    if balance_manager.is_none() {
+        commands.push(Command::MoveCall {
+            package: deepbook_addr,
+            module: Identifier::new("balance_manager")?,
+            function: Identifier::new("new")?,
+            type_args: vec![],
+            args: vec![],
+        });
+    }
+    let bm_arg = || match balance_manager {
+        Some(_) => Argument::Input(10),
+        None => Argument::NestedResult(0, 0),
+    };
+    let proof_idx = commands.len() as u16;
+    commands.push(Command::MoveCall {
+        package: deepbook_addr,
+        module: Identifier::new("balance_manager")?,
+        function: Identifier::new("generate_proof_as_owner")?,
+        type_args: vec![],
+        args: vec![bm_arg()],
+    });
+
+    // Split each needed deposit from its reserve coin into the balance manager.
+    let deposits = [
+        (base_type, base_deposit),
+        (quote_type, quote_deposit),
+        (DEEP_TYPE, deep_deposit),
+    ];
+    for (coin_type, amount) in deposits {
+        if amount == 0 {
+            continue;
+        }
+        let coin_input = inputs.len() as u16;
+        inputs.push(InputValue::Object(reserve_coin_input(state, coin_type)?));
+        inputs.push(InputValue::Pure(bcs::to_bytes(&amount)?));
+        let coin_tag = TypeTag::from_str(coin_type)?;
+        let split_idx = commands.len() as u16;
+        commands.push(Command::MoveCall {
+            package: sui_framework_addr,
+            module: Identifier::new("coin")?,
+            function: Identifier::new("split")?,
+            type_args: vec![coin_tag.clone()],
+            args: vec![Argument::Input(coin_input), Argument::Input(coin_input + 1)],
+        });
+        commands.push(Command::MoveCall {
+            package: deepbook_addr,
+            module: Identifier::new("balance_manager")?,
+            function: Identifier::new("deposit")?,
+            type_args: vec![coin_tag],
+            args: vec![bm_arg(), Argument::Result(split_idx)],
+        });
+    }
+
+    let place_idx = commands.len() as u16;
+    commands.push(Command::MoveCall {
+        package: deepbook_addr,
+        module: Identifier::new("pool")?,
+        function: Identifier::new("place_limit_order")?,
+        type_args: vec![base_tag, quote_tag],
+        args: vec![
+            Argument::Input(0),
+            bm_arg(),
+            Argument::NestedResult(proof_idx, 0),
+            Argument::Input(1),
+            Argument::Input(2),
+            Argument::Input(3),
+            Argument::Input(4),
+            Argument::Input(5),
+            Argument::Input(6),
+            Argument::Input(7),
+            Argument::Input(8),
+            Argument::Input(9),
+        ],
+    });
+    // Read back order_info fields, in the order parsed below.
+    let info_idx = commands.len();
+    for function in [
+        "order_id",
+        "price",
+        "original_quantity",
+        "executed_quantity",
+        "cumulative_quote_quantity",
+        "status",
+        "order_inserted",
+    ] {
+        commands.push(Command::MoveCall {
+            package: deepbook_addr,
+            module: Identifier::new("order_info")?,
+            function: Identifier::new(function)?,
+            type_args: vec![],
+            args: vec![Argument::NestedResult(place_idx, 0)],
+        });
+    }
+    if balance_manager.is_none() {
+        // Transfer the new balance manager to the sender so it persists.
+        commands.push(Command::MoveCall {
+            package: sui_framework_addr,
+            module: Identifier::new("transfer")?,
+            function: Identifier::new("public_transfer")?,
+            type_args: vec![bm_tag],
+            args: vec![bm_arg(), Argument::Input(10)],
+        });
+    }
+
+    let result = state.env.execute_ptb(inputs, commands);
+    if !result.success {
+        return Err(anyhow!(
+            "place_limit_order failed on {}: {}",
+            pool_id.display_name(),
+            result
+                .raw_error
+                .unwrap_or_else(|| "Unknown error".to_string())
+        ));
+    }
+    let effects = result
+        .effects
+        .as_ref()
+        .ok_or_else(|| anyhow!("Missing PTB effects for place_limit_order"))?;
+
+    let order_id = parse_u128_command_return(effects, info_idx, 0, "order_info.order_id")?;
+    let price = parse_u64_command_return(effects, info_idx + 1, 0, "order_info.price")?;
+    let original_quantity =
+        parse_u64_command_return(effects, info_idx + 2, 0, "order_info.original_quantity")?;
+    let executed_quantity =
+        parse_u64_command_return(effects, info_idx + 3, 0, "order_info.executed_quantity")?;
+    let cumulative_quote_quantity = parse_u64_command_return(
+        effects,
+        info_idx + 4,
+        0,
+        "order_info.cumulative_quote_quantity",
+    )?;
+    let status = parse_u8_command_return(effects, info_idx + 5, 0, "order_info.status")?;
+    let inserted = parse_bool_command_return(effects, info_idx + 6, 0, "order_info.inserted")?;
+
+    let balance_manager_id = match balance_manager {
+        Some(id) => id,
+        None => {
+            let id = find_created_object_id_by_type(effects, &bm_type).ok_or_else(|| {
+                anyhow!("Could not locate created BalanceManager from place_limit_order effects")
+            })?;
+            if state.env.get_object(&id).is_none() {
+                if let Some(bytes) = effects.created_object_bytes.get(&id) {
+                    state.env.load_object_from_data(
+                        &id.to_hex_literal(),
+                        bytes.clone(),
+                        Some(&bm_type),
+                        false,
+                        false,
+                        1,
+                    )?;
+                }
+            }
+            id
+        }
+    };
+
+    // Same BigVector bookkeeping as the debug seed, so iter_orders sees the
+    // order when it opened a new slice.
+    let created_slices: Vec<(AccountAddress, u64)> = effects
+        .object_changes
+        .iter()
+        .filter_map(|change| match change {
+            sui_sandbox_core::ptb::ObjectChange::Created {
+                id,
+                object_type: Some(type_tag),
+                ..
+            } if type_tag.to_string().contains("big_vector::Slice") => {
+                let parent = effects.dynamic_field_entries.iter().find_map(
+                    |((parent_id, child_id), _)| (child_id == id).then_some(*parent_id),
+                )?;
+                let key = effects
+                    .created_object_bytes
+                    .get(id)
+                    .and_then(|bytes| parse_dynamic_field_u64_name(bytes))?;
+                Some((parent, key))
+            }
+            _ => None,
+        })
+        .collect();
+    sync_dynamic_field_entries(state, effects);
+    for (parent, slice_key) in created_slices {
+        if let Err(e) =
+            patch_pool_big_vector_header_from_created_slice(state, pool_id, parent, slice_key)
+        {
+            tracing::warn!(
+                "Router: failed patching {} BigVector header from slice parent={} key={}: {}",
+                pool_id.display_name(),
+                parent,
+                slice_key,
+                e
+            );
+        }
+    }
+
+    tracing::info!(
+        "Router: placed {} limit order {} on {} (price={}, qty={}, executed={}, inserted={})",
+        if params.is_bid { "bid" } else { "ask" },
+        order_id,
+        pool_id.display_name(),
+        price,
+        original_quantity,
+        executed_quantity,
+        inserted
+    );
+
+    Ok((
+        LimitOrderResult {
+            order_id,
+            balance_manager_id: balance_manager_id.to_hex_literal(),
+            balance_manager_created: balance_manager.is_none(),
+            price,
+            original_quantity,
+            executed_quantity,
+            cumulative_quote_quantity,
+            status,
+            inserted,
+            base_deposit,
+            quote_deposit,
+            deep_deposit,
+            gas_used: effects.gas_used,
+            events: collect_swap_events(&mut state.bcs_converter, effects),
+        },
+        balance_manager_id,
+    ))
+}
+
+/// Look up a resting order with `pool::get_order`.
+///
+/// Fails with [`ORDER_NOT_FOUND`] when the order is not on the book, which is
+/// also the case once it has been fully filled or cancelled.
+fn fetch_order(
+    state: &mut RouterEnvState,
+    pool_id: PoolId,
+    order_id: u128,
+) -> Result<DecodedOrder> {
+    let deepbook_addr = AccountAddress::from_hex_literal(DEEPBOOK_PACKAGE)?;
+    let (base_type, quote_type) = pool_types(state, pool_id);
+
+    let inputs = vec![
+        InputValue::Object(pool_shared_input(state, pool_id, false)?),
+        InputValue::Pure(bcs::to_bytes(&order_id)?),
+    ];
+    let commands = vec![Command::MoveCall {
+        package: deepbook_addr,
+        module: Identifier::new("pool")?,
+        function: Identifier::new("get_order")?,
+        type_args: vec![
+            TypeTag::from_str(&base_type)?,
+            TypeTag::from_str(&quote_type)?,
+        ],
+        args: vec![Argument::Input(0), Argument::Input(1)],
+    }];
+
+    let result = state.env.execute_ptb(inputs, commands);
+    if !result.success {
+        return Err(anyhow!(
+            "{}: order {} on {} ({})",
+            ORDER_NOT_FOUND,
+            order_id,
+            pool_id.display_name(),
+            result
+                .raw_error
+                .unwrap_or_else(|| "Unknown error".to_string())
+        ));
+    }
+    let bytes = result
+        .effects
+        .as_ref()
+        .and_then(|effects| effects.return_values.first())
+        .and_then(|values| values.first())
+        .ok_or_else(|| anyhow!("No return value from pool::get_order"))?;
+    read_order(&mut std::io::Cursor::new(bytes.as_slice()), 0)
+}
+
+/// Cancel `order_id` through the balance manager of `session_id`.
+///
+/// The order is looked up first, so a missing order ([`ORDER_NOT_FOUND`]) or
+/// one resting for another balance manager ([`ORDER_NOT_OWNED`]) fails with a
+/// clear error instead of a Move abort. The cancel PTB then withdraws all of
+/// the balance manager's base, quote and DEEP into the reserve coins, so the
+/// session can be credited with everything the order had locked.
+pub(super) fn cancel_order(
+    state: &mut RouterEnvState,
+    session_id: &str,
+    pool_id: PoolId,
+    balance_manager_id: Option<&str>,
+    order_id: u128,
+) -> Result<CancelOrderResult> {
+    let session_bm = state
+        .session_balance_managers
+        .get(session_id)
+        .copied()
+        .ok_or_else(|| {
+            anyhow!(
+                "Session {} has no balance manager; place a limit order first",
+                session_id
+            )
+        })?;
+    if let Some(requested) = balance_manager_id {
+        let requested = AccountAddress::from_hex_literal(requested.trim())
+            .map_err(|e| anyhow!("Invalid balance_manager_id {}: {}", requested, e))?;
+        if requested != session_bm.id {
+            return Err(anyhow!(
+                "Balance manager {} does not belong to session {}",
+                requested.to_hex_literal(),
+                session_id
+            ));
+        }
+    }
+
+    let order = fetch_order(state, pool_id, order_id)?;
+    if order.balance_manager_id != session_bm.id.to_hex_literal() {
+        return Err(anyhow!(
+            "{}: order {} rests for {}, not {}",
+            ORDER_NOT_OWNED,
+            order_id,
+            order.balance_manager_id,
+            session_bm.id.to_hex_literal()
+        ));
+    }
+
+    let original_sender = state.env.sender();
+    state.env.set_sender(session_bm.owner);
+    let result = execute_cancel_order_ptb(state, pool_id, session_bm.id, order_id);
+    state.env.set_sender(original_sender);
+    let (refunds, gas_used, events) = result?;
+
+    tracing::info!(
+        "Router: cancelled order {} on {} (refunds base={}, quote={}, deep={})",
+        order_id,
+        pool_id.display_name(),
+        refunds[0],
+        refunds[1],
+        refunds[2]
+    );
+    Ok(CancelOrderResult {
+        order,
+        balance_manager_id: session_bm.id.to_hex_literal(),
+        base_refund: refunds[0],
+        quote_refund: refunds[1],
+        deep_refund: refunds[2],
+        gas_used,
+        events,
+    })
+}
+
+/// Run the cancel PTB; returns the base, quote and DEEP withdrawn.
+fn execute_cancel_order_ptb(
+    state: &mut RouterEnvState,
+    pool_id: PoolId,
+    balance_manager_id: AccountAddress,
+    order_id: u128,
+) -> Result<([u64; 3], u64, Vec<SwapEvent>)> {
+    let deepbook_addr = AccountAddress::from_hex_literal(DEEPBOOK_PACKAGE)?;
+    let (base_type, quote_type) = pool_types(state, pool_id);
+    let bm_obj = state.env.get_object(&balance_manager_id).ok_or_else(|| {
+        anyhow!(
+            "Session balance manager missing in env: {}",
+            balance_manager_id
+        )
+    })?;
+    let bm_input = ObjectInput::Owned {
+        id: balance_manager_id,
+        bytes: bm_obj.bcs_bytes.clone(),
+        type_tag: Some(TypeTag::from_str(&format!(
+            "{}::balance_manager::BalanceManager",
+            DEEPBOOK_PACKAGE
+        ))?),
+        version: Some(bm_obj.version),
+    };
+
+    let mut inputs = vec![
+        // 0) pool (shared mutable)
+        InputValue::Object(pool_shared_input(state, pool_id, true)?),
+        // 1) session balance manager (owned)
+        InputValue::Object(bm_input),
+        // 2) order_id
+        InputValue::Pure(bcs::to_bytes(&order_id)?),
+        // 3) clock
+        InputValue::Object(state.next_clock_input()?),
+    ];
+    let mut commands = vec![
+        // 0) generate owner trade proof
+        Command::MoveCall {
+            package: deepbook_addr,
+            module: Identifier::new("balance_manager")?,
+            function: Identifier::new("generate_proof_as_owner")?,
+            type_args: vec![],
+            args: vec![Argument::Input(1)],
+        },
+        // 1) cancel order; the unfilled remainder settles into the balance manager
+        Command::MoveCall {
+            package: deepbook_addr,
+            module: Identifier::new("pool")?,
+            function: Identifier::new("cancel_order")?,
+            type_args: vec![
+                TypeTag::from_str(&base_type)?,
+                TypeTag::from_str(&quote_type)?,
+            ],
+            args: vec![
+                Argument::Input(0),
+                Argument::Input(1),
+                Argument::NestedResult(0, 0),
+                Argument::Input(2),
+                Argument::Input(3),
+            ],
+        },
+    ];
+
+    let withdrawals = push_withdraw_all(state, pool_id, 1, &mut inputs, &mut commands)?;
+
+    let result = state.env.execute_ptb(inputs, commands);
+    if !result.success {
+        return Err(anyhow!(
+            "cancel_order failed on {}: {}",
+            pool_id.display_name(),
+            result
+                .raw_error
+                .unwrap_or_else(|| "Unknown error".to_string())
+        ));
+    }
+    let effects = result
+        .effects
+        .as_ref()
+        .ok_or_else(|| anyhow!("Missing PTB effects for cancel_order"))?;
+
+    let refunds = withdrawn_amounts(effects, &withdrawals)?;
+    sync_dynamic_field_entries(state, effects);
+
+    Ok((
+        refunds,
+        effects.gas_used,
+        collect_swap_events(&mut state.bcs_converter, effects),
+    ))
+}
+
+/// Append commands that withdraw each of the pool's coin types from the
+/// balance manager at input `balance_manager_input`, read the coin's value
+/// and join it back into the reserve.
+///
+/// Each coin type is withdrawn once (DEEP is the base of DEEP/USDC). Returns
+/// each coin's slot (0 base, 1 quote, 2 DEEP) with the index of its
+/// `coin::value` command.
+fn push_withdraw_all(
+    state: &mut RouterEnvState,
+    pool_id: PoolId,
+    balance_manager_input: u16,
+    inputs: &mut Vec<InputValue>,
+    commands: &mut Vec<Command>,
+) -> Result<Vec<(usize, usize)>> {
+    let deepbook_addr = AccountAddress::from_hex_literal(DEEPBOOK_PACKAGE)?;
+    let sui_framework_addr = AccountAddress::from_hex_literal(SUI_FRAMEWORK_PACKAGE)?;
+    let (base_type, quote_type) = pool_types(state, pool_id);
+    let mut coin_types = vec![base_type, quote_type];
+    if !coin_types.iter().any(|coin_type| coin_type == DEEP_TYPE) {
+        coin_types.push(DEEP_TYPE.to_string());
+    }
+
+    let mut withdrawals = Vec::with_capacity(coin_types.len());
+    for (slot, coin_type) in coin_types.iter().enumerate() {
+        let coin_tag = TypeTag::from_str(coin_type)?;
+        let reserve_input = inputs.len() as u16;
+        inputs.push(InputValue::Object(reserve_coin_input(state, coin_type)?));
+        let withdraw_idx = commands.len() as u16;
+        commands.push(Command::MoveCall {
+            package: deepbook_addr,
+            module: Identifier::new("balance_manager")?,
+            function: Identifier::new("withdraw_all")?,
+            type_args: vec![coin_tag.clone()],
+            args: vec![Argument::Input(balance_manager_input)],
+        });
+        withdrawals.push((slot, commands.len()));
+        commands.push(Command::MoveCall {
+            package: sui_framework_addr,
+            module: Identifier::new("coin")?,
+            function: Identifier::new("value")?,
+            type_args: vec![coin_tag.clone()],
+            args: vec![Argument::Result(withdraw_idx)],
+        });
+        commands.push(Command::MoveCall {
+            package: sui_framework_addr,
+            module: Identifier::new("coin")?,
+            function: Identifier::new("join")?,
+            type_args: vec![coin_tag],
+            args: vec![
+                Argument::Input(reserve_input),
+                Argument::Result(withdraw_idx),
+            ],
+        });
+    }
+    Ok(withdrawals)
+}
+
+/// Base, quote and DEEP amounts read back from the commands [`push_withdraw_all`] added
+fn withdrawn_amounts(
+    effects: &sui_sandbox_core::ptb::TransactionEffects,
+    withdrawals: &[(usize, usize)],
+) -> Result<[u64; 3]> {
+    let mut amounts = [0u64; 3];
+    for (slot, command_idx) in withdrawals {
+        amounts[*slot] = parse_u64_command_return(effects, *command_idx, 0, "withdrawn_amount")?;
+    }
+    Ok(amounts)
+}
+
+/// Settle `session_id`'s balances in `pool_id` into its balance manager with
+/// `pool::withdraw_settled_amounts`, then withdraw everything the manager
+/// holds back into the VM reserves so the session can be credited.
+pub(super) fn withdraw_settled(
+    state: &mut RouterEnvState,
+    session_id: &str,
+    pool_id: PoolId,
+) -> Result<WithdrawSettledResult> {
+    let session_bm = state
+        .session_balance_managers
+        .get(session_id)
+        .copied()
+        .ok_or_else(|| {
+            anyhow!(
+                "Session {} has no balance manager; place a limit order first",
+                session_id
+            )
+        })?;
+
+    let original_sender = state.env.sender();
+    state.env.set_sender(session_bm.owner);
+    let result = execute_withdraw_settled_ptb(state, pool_id, session_bm.id);
+    state.env.set_sender(original_sender);
+    let (amounts, gas_used, events) = result?;
+
+    tracing::info!(
+        "Router: withdrew settled balances of {} on {} (base={}, quote={}, deep={})",
+        session_bm.id.to_hex_literal(),
+        pool_id.display_name(),
+        amounts[0],
+        amounts[1],
+        amounts[2]
+    );
+    Ok(WithdrawSettledResult {
+        balance_manager_id: session_bm.id.to_hex_literal(),
+        base_amount: amounts[0],
+        quote_amount: amounts[1],
+        deep_amount: amounts[2],
+        gas_used,
+        events,
+    })
+}
+
+fn execute_withdraw_settled_ptb(
+    state: &mut RouterEnvState,
+    pool_id: PoolId,
+    balance_manager_id: AccountAddress,
+) -> Result<([u64; 3], u64, Vec<SwapEvent>)> {
+    let deepbook_addr = AccountAddress::from_hex_literal(DEEPBOOK_PACKAGE)?;
+    let (base_type, quote_type) = pool_types(state, pool_id);
+    let bm_obj = state.env.get_object(&balance_manager_id).ok_or_else(|| {
+        anyhow!(
+            "Session balance manager missing in env: {}",
+            balance_manager_id
+        )
+    })?;
+    let bm_input = ObjectInput::Owned {
+        id: balance_manager_id,
+        bytes: bm_obj.bcs_bytes.clone(),
+        type_tag: Some(TypeTag::from_str(&format!(
+            "{}::balance_manager::BalanceManager",
+            DEEPBOOK_PACKAGE
+        ))?),
+        version: Some(bm_obj.version),
+    };
+
+    let mut inputs = vec![
+        // 0) pool (shared mutable)
+        InputValue::Object(pool_shared_input(state, pool_id, true)?),
+        // 1) session balance manager (owned)
+        InputValue::Object(bm_input),
+    ];
+    let mut commands = vec![
+        // 0) generate owner trade proof
+        Command::MoveCall {
+            package: deepbook_addr,
+            module: Identifier::new("balance_manager")?,
+            function: Identifier::new("generate_proof_as_owner")?,
+            type_args: vec![],
+            args: vec![Argument::Input(1)],
+        },
+        // 1) move the account's settled balances into the balance manager
+        Command::MoveCall {
+            package: deepbook_addr,
+            module: Identifier::new("pool")?,
+            function: Identifier::new("withdraw_settled_amounts")?,
+            type_args: vec![
+                TypeTag::from_str(&base_type)?,
+                TypeTag::from_str(&quote_type)?,
+            ],
+            args: vec![
+                Argument::Input(0),
+                Argument::Input(1),
+                Argument::NestedResult(0, 0),
+            ],
+        },
+    ];
+    let withdrawals = push_withdraw_all(state, pool_id, 1, &mut inputs, &mut commands)?;
+
+    let result = state.env.execute_ptb(inputs, commands);
+    if !result.success {
+        return Err(anyhow!(
+            "withdraw_settled_amounts failed on {}: {}",
+            pool_id.display_name(),
+            result
+                .raw_error
+                .unwrap_or_else(|| "Unknown error".to_string())
+        ));
+    }
+    let effects = result
+        .effects
+        .as_ref()
+        .ok_or_else(|| anyhow!("Missing PTB effects for withdraw_settled_amounts"))?;
+
+    let amounts = withdrawn_amounts(effects, &withdrawals)?;
+    sync_dynamic_field_entries(state, effects);
+
+    Ok((
+        amounts,
+        effects.gas_used,
+        collect_swap_events(&mut state.bcs_converter, effects),
+    ))
+}
+
+/// BCS layout of `dynamic_field::Field<ID, account::Account>`
+#[derive(Debug, Deserialize)]
+struct AccountFieldBcs {
+    _id: AccountAddress,
+    _name: AccountAddress,
+    value: AccountBcs,
+}
+
+#[derive(Debug, Deserialize)]
+struct AccountBcs {
+    epoch: u64,
+    open_orders: Vec<u128>,
+    taker_volume: u128,
+    maker_volume: u128,
+    active_stake: u64,
+    inactive_stake: u64,
+    _created_proposal: bool,
+    _voted_proposal: Option<AccountAddress>,
+    unclaimed_rebates: AccountBalances,
+    settled_balances: AccountBalances,
+    owed_balances: AccountBalances,
+}
+
+pub(super) fn decode_account_field(bytes: &[u8]) -> Result<AccountBcs> {
+    bcs::from_bytes::<AccountFieldBcs>(bytes)
+        .map(|field| field.value)
+        .map_err(|e| anyhow!("Failed to decode account field: {}", e))
+}
+
+/// Decode a balance manager's entry in a pool's `state.accounts` table.
+///
+/// DeepBook's account view functions need a `&BalanceManager` object, so the
+/// table entry is read and decoded directly instead.
+pub(super) fn read_account_snapshot(
+    state: &RouterEnvState,
+    pool_id: PoolId,
+    balance_manager_id: &str,
+) -> Result<RouterAccountSnapshot> {
+    let manager_addr = AccountAddress::from_hex_literal(balance_manager_id.trim())
+        .map_err(|e| anyhow!("Invalid balance_manager_id {}: {}", balance_manager_id, e))?;
+    let account = read_account_entry(state, pool_id, manager_addr)?;
+
+    let mut snapshot = RouterAccountSnapshot {
+        pool: pool_id.as_str().to_string(),
+        balance_manager_id: manager_addr.to_hex_literal(),
+        exists: false,
+        epoch: 0,
+        open_orders: 0,
+        taker_volume: "0".to_string(),
+        maker_volume: "0".to_string(),
+        active_stake: 0,
+        inactive_stake: 0,
+        settled_balances: AccountBalances::default(),
+        owed_balances: AccountBalances::default(),
+        unclaimed_rebates: AccountBalances::default(),
+    };
+    let Some(account) = account else {
+        return Ok(snapshot);
+    };
+    snapshot.exists = true;
+    snapshot.epoch = account.epoch;
+    snapshot.open_orders = account.open_orders.len();
+    snapshot.taker_volume = account.taker_volume.to_string();
+    snapshot.maker_volume = account.maker_volume.to_string();
+    snapshot.active_stake = account.active_stake;
+    snapshot.inactive_stake = account.inactive_stake;
+    snapshot.settled_balances = account.settled_balances;
+    snapshot.owed_balances = account.owed_balances;
+    snapshot.unclaimed_rebates = account.unclaimed_rebates;
+    Ok(snapshot)
+}
+
+/// A balance manager's decoded `state.accounts` entry in a pool, if it has one
+fn read_account_entry(
+    state: &RouterEnvState,
+    pool_id: PoolId,
+    manager_addr: AccountAddress,
+) -> Result<Option<AccountBcs>> {
+    let entry = state
+        .pool_cache
+        .get(&pool_id)
+        .ok_or_else(|| anyhow!("{} is not loaded in the router", pool_id.display_name()))?;
+    let accounts_table = entry.accounts_table.ok_or_else(|| {
+        anyhow!(
+            "{} has no known state.accounts table",
+            pool_id.display_name()
+        )
+    })?;
+
+    let object_id_tag = TypeTag::from_str(OBJECT_ID_TYPE)?;
+    let key_bytes = bcs::to_bytes(&manager_addr)?;
+    let child_id = derive_dynamic_field_id(accounts_table, &object_id_tag, &key_bytes)?;
+    state
+        .env
+        .get_dynamic_field(accounts_table, child_id)
+        .map(|(_, field_bytes)| decode_account_field(field_bytes))
+        .transpose()
+}
+
+/// Open orders of `session_id`'s balance manager, pool by pool.
+///
+/// The ids come from the manager's `Account.open_orders` in each pool and
+/// each is looked up with `pool::get_order` for its current state. Pools
+/// without a known accounts table are skipped.
+pub(super) fn session_open_orders(
+    state: &mut RouterEnvState,
+    session_id: &str,
+) -> Result<SessionOpenOrders> {
+    let mut open_orders = SessionOpenOrders {
+        balance_manager_id: None,
+        orders: Vec::new(),
+        missing: Vec::new(),
+    };
+    let Some(session_bm) = state.session_balance_managers.get(session_id).copied() else {
+        return Ok(open_orders);
+    };
+    open_orders.balance_manager_id = Some(session_bm.id.to_hex_literal());
+
+    let mut pools: Vec<PoolId> = state
+        .pool_cache
+        .iter()
+        .filter(|(_, entry)| entry.accounts_table.is_some())
+        .map(|(pool_id, _)| *pool_id)
+        .collect();
+    pools.sort_by_key(|pool_id| pool_id.as_str());
+    for pool_id in pools {
+        let Some(account) = read_account_entry(state, pool_id, session_bm.id)? else {
+            continue;
+        };
+        for order_id in account.open_orders {
+            match fetch_order(state, pool_id, order_id) {
+                Ok(order) => open_orders.orders.push((pool_id, order)),
+                Err(e) => {
+                    tracing::warn!(
+                        "Router: open order {} of {} not found on {}: {}",
+                        order_id,
+                        session_bm.id.to_hex_literal(),
+                        pool_id.display_name(),
+                        e
+                    );
+                    open_orders.missing.push((pool_id, order_id));
+                }
+            }
+        }
+    }
+    Ok(open_orders)
+}
+
+/// Cancel every open order of `session_id`'s balance manager.
+///
+/// Orders are cancelled one PTB at a time rather than batched into one: a
+/// PTB aborts as a whole, so a single order that fills or expires between
+/// listing and cancelling would roll back every other cancel with it. An
+/// order that fails to cancel is reported in `failed` and leaves the others
+/// cancelled. Ids listed as open that `pool::get_order` cannot find, or that
+/// do not parse, are reported as failures too.
+pub(super) fn cancel_all_orders(
+    state: &mut RouterEnvState,
+    session_id: &str,
+) -> Result<CancelAllOrdersResult> {
+    let open_orders = session_open_orders(state, session_id)?;
+    let mut result = CancelAllOrdersResult {
+        balance_manager_id: open_orders.balance_manager_id,
+        cancelled: Vec::new(),
+        failed: open_orders
+            .missing
+            .into_iter()
+            .map(|(pool_id, order_id)| CancelOrderFailure {
+                pool_id,
+                order_id: order_id.to_string(),
+                error: format!("{}: order {}", ORDER_NOT_FOUND, order_id),
+            })
+            .collect(),
+    };
+
+    for (pool_id, order) in open_orders.orders {
+        let order_id: u128 = match order.order_id.parse() {
+            Ok(order_id) => order_id,
+            Err(e) => {
+                result.failed.push(CancelOrderFailure {
+                    pool_id,
+                    error: format!("Invalid order id {}: {}", order.order_id, e),
+                    order_id: order.order_id,
+                });
+                continue;
+            }
+        };
+        match cancel_order(state, session_id, pool_id, None, order_id) {
+            Ok(cancelled) => result.cancelled.push((pool_id, cancelled)),
+            Err(e) => {
+                tracing::warn!(
+                    "Router: failed to cancel order {} on {}: {}",
+                    order_id,
+                    pool_id.display_name(),
+                    e
+                );
+                result.failed.push(CancelOrderFailure {
+                    pool_id,
+                    order_id: order.order_id,
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+    Ok(result)
+}
@@ -0,0 +1,493 @@
+//! Quotes
+//!
+//! Single-hop, two-sided, multi-hop, batch, and exact-output quotes, all run
+//! as rolled-back PTBs against the current VM state.
+
+use super::*;
+
+pub(super) fn execute_single_hop_quote(
+    state: &mut RouterEnvState,
+    pool_id: PoolId,
+    input_amount: u64,
+    is_sell_base: bool,
+) -> Result<SingleHopQuote> {
+    let clock = state.next_clock_input()?;
+    single_hop_quote_with_clock(state, pool_id, input_amount, is_sell_base, clock)
+}
+
+/// Single-hop quote against an explicit clock input, so repeated quotes can
+/// share one timestamp without advancing the synthetic clock.
+pub(super) fn single_hop_quote_with_clock(
+    state: &mut RouterEnvState,
+    pool_id: PoolId,
+    input_amount: u64,
+    is_sell_base: bool,
+    clock: ObjectInput,
+) -> Result<SingleHopQuote> {
+    let (base_type, quote_type) = pool_types(state, pool_id);
+    let base_tag = TypeTag::from_str(&base_type)?;
+    let quote_tag = TypeTag::from_str(&quote_type)?;
+    let deepbook_addr = AccountAddress::from_hex_literal(DEEPBOOK_PACKAGE)?;
+    let function_name = if is_sell_base {
+        "get_quote_quantity_out"
+    } else {
+        "get_base_quantity_out"
+    };
+
+    let inputs = vec![
+        InputValue::Object(pool_shared_input(state, pool_id, false)?),
+        InputValue::Pure(bcs::to_bytes(&input_amount)?),
+        InputValue::Object(clock),
+    ];
+
+    let commands = vec![Command::MoveCall {
+        package: deepbook_addr,
+        module: Identifier::new("pool")?,
+        function: Identifier::new(function_name)?,
+        type_args: vec![base_tag, quote_tag],
+        args: vec![Argument::Input(0), Argument::Input(1), Argument::Input(2)],
+    }];
+
+    let result = state.env.execute_ptb(inputs, commands);
+
+    if !result.success {
+        return Err(anyhow!(
+            "single-hop quote via pool::{} failed for {}: {}",
+            function_name,
+            pool_id.display_name(),
+            result
+                .raw_error
+                .unwrap_or_else(|| "Unknown error".to_string())
+        ));
+    }
+
+    let return_values = result
+        .effects
+        .as_ref()
+        .and_then(|effects| effects.return_values.first())
+        .ok_or_else(|| anyhow!("No return values from pool::{}", function_name))?;
+
+    let rv0 = parse_u64_return(return_values, 0, "rv0")?;
+    let rv1 = parse_u64_return(return_values, 1, "rv1")?;
+    let rv2 = parse_u64_return(return_values, 2, "rv2")?;
+    if pool_id.is_debug() && state.debug_log_sampler.sample_routine() {
+        tracing::info!(
+            "Router: debug quote {} returns rv0={}, rv1={}, rv2={}, input={}",
+            function_name,
+            rv0,
+            rv1,
+            rv2,
+            input_amount
+        );
+    }
+
+    let output_amount = if is_sell_base {
+        // get_quote_quantity_out returns (base_left, quote_out, deep_fee)
+        rv1
+    } else {
+        // get_base_quantity_out returns (base_out, quote_left, deep_fee)
+        rv0
+    };
+    if pool_id.is_debug() && output_amount == 0 && state.debug_log_sampler.sample_anomaly() {
+        if let Err(e) = log_debug_pool_snapshot(state, pool_id, "quote-zero-output") {
+            tracing::warn!(
+                "Router: debug snapshot failed after zero quote output: {}",
+                e
+            );
+        }
+    }
+
+    // Both get_*_quantity_out variants return the DEEP fee last
+    Ok(SingleHopQuote {
+        output_amount,
+        deep_fee: rv2,
+    })
+}
+
+pub(super) fn execute_two_sided_quote(
+    state: &mut RouterEnvState,
+    pool_id: PoolId,
+    base_amount: u64,
+) -> Result<TwoSidedQuote> {
+    let sell = execute_single_hop_quote(state, pool_id, base_amount, true)?;
+    let buy = if sell.output_amount > 0 {
+        execute_single_hop_quote(state, pool_id, sell.output_amount, false)?
+    } else {
+        SingleHopQuote {
+            output_amount: 0,
+            deep_fee: 0,
+        }
+    };
+    Ok(TwoSidedQuote {
+        base_amount,
+        sell,
+        buy,
+    })
+}
+
+/// Smallest input a direct swap on a pool with `params` can fill.
+///
+/// Selling base needs `min_size` base. Buying base needs the quote that takes
+/// `min_size` base from `asks` (ascending levels), rounded up per level;
+/// `None` when the asks hold less than `min_size`.
+pub fn min_single_hop_input(
+    params: &PoolParams,
+    is_sell_base: bool,
+    asks: &[PriceLevel],
+) -> Option<u64> {
+    if is_sell_base {
+        return Some(params.min_size);
+    }
+    let mut remaining = params.min_size;
+    let mut quote = 0u64;
+    for level in asks {
+        if remaining == 0 {
+            break;
+        }
+        let take = remaining.min(level.total_quantity);
+        quote = quote.checked_add(scaled_mul_ceil(level.price, take))?;
+        remaining -= take;
+    }
+    (remaining == 0).then_some(quote)
+}
+
+/// Smallest input of a two-hop swap that sells base into the first pool for
+/// USDC and buys base from the second.
+///
+/// The first leg needs its pool's `min_size`; the second needs the quote that
+/// buys the second pool's `min_size` from `second_asks`. The larger of
+/// `first.min_size` and the base (rounded up to `first.lot_size`) whose sale
+/// into `first_bids` raises that quote wins. `None` when either book is too thin.
+pub fn min_two_hop_input(
+    first: &PoolParams,
+    first_bids: &[PriceLevel],
+    second: &PoolParams,
+    second_asks: &[PriceLevel],
+) -> Option<u64> {
+    let mut remaining = min_single_hop_input(second, false, second_asks)? as u128;
+    let mut base = 0u128;
+    for level in first_bids {
+        if remaining == 0 {
+            break;
+        }
+        if level.price == 0 {
+            continue;
+        }
+        let level_quote = scaled_mul_floor(level.price, level.total_quantity) as u128;
+        if level_quote >= remaining {
+            base += (remaining * 1_000_000_000u128).div_ceil(level.price as u128);
+            remaining = 0;
+        } else {
+            base += level.total_quantity as u128;
+            remaining -= level_quote;
+        }
+    }
+    if remaining > 0 {
+        return None;
+    }
+    let lot_size = first.lot_size.max(1) as u128;
+    let base = u64::try_from(base.div_ceil(lot_size) * lot_size).ok()?;
+    Some(base.max(first.min_size))
+}
+
+/// Execute a two-hop quote via the MoveVM router contract
+pub(super) fn execute_two_hop_quote(
+    state: &mut RouterEnvState,
+    from_pool: PoolId,
+    to_pool: PoolId,
+    input_amount: u64,
+) -> Result<TwoHopQuote> {
+    let clock_input = state.next_clock_input()?;
+    two_hop_quote_with_clock(state, from_pool, to_pool, input_amount, clock_input)
+}
+
+/// Two-hop quote against an explicit clock input (see `single_hop_quote_with_clock`)
+fn two_hop_quote_with_clock(
+    state: &mut RouterEnvState,
+    from_pool: PoolId,
+    to_pool: PoolId,
+    input_amount: u64,
+    clock_input: ObjectInput,
+) -> Result<TwoHopQuote> {
+    if !state.router_deployed {
+        return Err(anyhow!(
+            "Router contract not deployed (two-hop quotes unavailable)"
+        ));
+    }
+
+    // Determine type args: A (base of from_pool), Q (shared quote), B (base of to_pool)
+    let (a_type, q_type, b_type) = resolve_two_hop_types(state, from_pool, to_pool)?;
+
+    let a_tag = TypeTag::from_str(&a_type)?;
+    let q_tag = TypeTag::from_str(&q_type)?;
+    let b_tag = TypeTag::from_str(&b_type)?;
+
+    let router_addr = AccountAddress::from_hex_literal(ROUTER_PACKAGE_ADDR)?;
+
+    let inputs = vec![
+        // Input 0: Pool<A, Q> (shared, immutable ref)
+        InputValue::Object(pool_shared_input(state, from_pool, false)?),
+        // Input 1: Pool<B, Q> (shared, immutable ref)
+        InputValue::Object(pool_shared_input(state, to_pool, false)?),
+        // Input 2: input_amount (pure u64)
+        InputValue::Pure(bcs::to_bytes(&input_amount)?),
+        // Input 3: Clock at 0x6 (shared, immutable ref)
+        InputValue::Object(clock_input),
+    ];
+
+    let commands = vec![Command::MoveCall {
+        package: router_addr,
+        module: Identifier::new("router")?,
+        function: Identifier::new("quote_two_hop")?,
+        type_args: vec![a_tag, q_tag, b_tag],
+        args: vec![
+            Argument::Input(0), // pool_aq
+            Argument::Input(1), // pool_bq
+            Argument::Input(2), // input_amount
+            Argument::Input(3), // clock
+        ],
+    }];
+
+    let result = state.env.execute_ptb(inputs, commands);
+
+    if !result.success {
+        return Err(anyhow!(
+            "quote_two_hop failed: {}",
+            result
+                .raw_error
+                .unwrap_or_else(|| "Unknown error".to_string())
+        ));
+    }
+
+    // Parse return values: (u64, u64) = (final_output, intermediate_amount)
+    let return_values = result
+        .effects
+        .as_ref()
+        .and_then(|effects| effects.return_values.first())
+        .ok_or_else(|| anyhow!("No return values from quote_two_hop"))?;
+
+    // First return value: base_out (u64)
+    let final_output = parse_u64_return(return_values, 0, "final_output")?;
+
+    // Second return value: quote_out (u64)
+    let intermediate_amount = parse_u64_return(return_values, 1, "intermediate_amount")?;
+
+    Ok(TwoHopQuote {
+        final_output,
+        intermediate_amount,
+    })
+}
+
+/// Reject routes that reuse a pool or whose legs do not connect.
+///
+/// A repeated pool would be quoted twice against the same untouched book,
+/// so its second leg would overstate the output.
+pub(super) fn validate_route_hops(state: &RouterEnvState, hops: &[RouteHop]) -> Result<()> {
+    for (i, hop) in hops.iter().enumerate() {
+        if hops[..i].iter().any(|prev| prev.pool_id == hop.pool_id) {
+            return Err(anyhow!(
+                "Route visits {} more than once",
+                hop.pool_id.display_name()
+            ));
+        }
+    }
+    for pair in hops.windows(2) {
+        let (_, produced) = pair[0].io_types(state);
+        let (consumed, _) = pair[1].io_types(state);
+        if produced != consumed {
+            return Err(anyhow!(
+                "Route legs do not connect: {} produces {} but {} consumes {}",
+                pair[0].pool_id.display_name(),
+                produced,
+                pair[1].pool_id.display_name(),
+                consumed
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Chain three `pool::get_*_quantity_out` views, feeding each hop's output
+/// into the next. All hops are quoted at the current clock time.
+pub(super) fn execute_three_hop_quote(
+    state: &mut RouterEnvState,
+    hops: [RouteHop; 3],
+    input_amount: u64,
+) -> Result<ThreeHopQuote> {
+    let clock_ms = state.clock_now_ms();
+    three_hop_quote_at(state, hops, input_amount, clock_ms)
+}
+
+fn three_hop_quote_at(
+    state: &mut RouterEnvState,
+    hops: [RouteHop; 3],
+    input_amount: u64,
+    clock_ms: u64,
+) -> Result<ThreeHopQuote> {
+    validate_route_hops(state, &hops)?;
+
+    let mut amount = input_amount;
+    let mut hop_outputs = Vec::with_capacity(hops.len());
+    let mut deep_fee = 0u64;
+    for (i, hop) in hops.iter().enumerate() {
+        let clock = state.clock_input_at(clock_ms)?;
+        let quote =
+            single_hop_quote_with_clock(state, hop.pool_id, amount, hop.is_sell_base, clock)
+                .map_err(|e| {
+                    anyhow!(
+                        "three-hop quote leg {} ({}) failed: {}",
+                        i + 1,
+                        hop.pool_id.display_name(),
+                        e
+                    )
+                })?;
+        amount = quote.output_amount;
+        deep_fee = deep_fee.saturating_add(quote.deep_fee);
+        hop_outputs.push(amount);
+    }
+
+    Ok(ThreeHopQuote {
+        final_output: amount,
+        hop_outputs,
+        deep_fee,
+    })
+}
+
+/// Quote each item at one shared clock timestamp.
+///
+/// The clock is read once rather than stepped per quote, so the batch sees
+/// a single consistent point in time, like the legs of a three-hop quote.
+/// Items with their own `clock_timestamp_ms` are quoted at that instead.
+pub(super) fn execute_batch_quote(
+    state: &mut RouterEnvState,
+    quotes: &[BatchQuoteInput],
+) -> Vec<Result<BatchQuote>> {
+    let shared_clock_ms = state.clock_now_ms();
+    quotes
+        .iter()
+        .map(|item| {
+            let clock_ms = item.clock_timestamp_ms.unwrap_or(shared_clock_ms);
+            match item.route {
+                BatchQuoteRoute::SingleHop {
+                    pool_id,
+                    is_sell_base,
+                } => single_hop_quote_with_clock(
+                    state,
+                    pool_id,
+                    item.input_amount,
+                    is_sell_base,
+                    state.clock_input_at(clock_ms)?,
+                )
+                .map(BatchQuote::SingleHop),
+                BatchQuoteRoute::TwoHop { from_pool, to_pool } => two_hop_quote_with_clock(
+                    state,
+                    from_pool,
+                    to_pool,
+                    item.input_amount,
+                    state.clock_input_at(clock_ms)?,
+                )
+                .map(BatchQuote::TwoHop),
+                BatchQuoteRoute::ThreeHop { hops } => {
+                    three_hop_quote_at(state, hops, item.input_amount, clock_ms)
+                        .map(BatchQuote::ThreeHop)
+                }
+            }
+        })
+        .collect()
+}
+
+/// Narrow `(lo, hi]` to the smallest input whose quote reaches `target`.
+///
+/// Expects `hi_quote`, the quote at `hi`, to reach `target` and the quote at
+/// `lo` not to. Pool quotes grow monotonically with the input, and a quote
+/// that aborts counts as too small an input (a lot/min-size rejection).
+/// Returns the input and its quote.
+pub(super) fn bisect_exact_out_input(
+    state: &mut RouterEnvState,
+    quote: &mut impl FnMut(&mut RouterEnvState, u64) -> Result<SingleHopQuote>,
+    target: u64,
+    mut lo: u64,
+    mut hi: u64,
+    mut hi_quote: SingleHopQuote,
+) -> (u64, SingleHopQuote) {
+    while hi - lo > 1 {
+        let mid = lo + (hi - lo) / 2;
+        match quote(state, mid) {
+            Ok(mid_quote) if mid_quote.output_amount >= target => {
+                hi = mid;
+                hi_quote = mid_quote;
+            }
+            _ => lo = mid,
+        }
+    }
+    (hi, hi_quote)
+}
+
+/// Smallest single-hop input whose quote reaches `desired_output`.
+///
+/// Doubles the input from 1 until the quote reaches the target, then
+/// bisects the last doubling, so it takes at most ~128 quote PTBs, all at
+/// the current clock. The pool cannot fill the target when doubling stops
+/// adding output (the book is exhausted) or starts to abort after a quote
+/// already filled.
+pub(super) fn execute_reverse_quote(
+    state: &mut RouterEnvState,
+    pool_id: PoolId,
+    desired_output: u64,
+    is_sell_base: bool,
+) -> Result<ReverseQuote> {
+    if desired_output == 0 {
+        return Err(anyhow!("desired output must be greater than zero"));
+    }
+    let clock_ms = state.clock_now_ms();
+    let mut quote_iterations = 0u32;
+    let mut quote = |state: &mut RouterEnvState, input: u64| -> Result<SingleHopQuote> {
+        quote_iterations += 1;
+        let clock = build_clock_input(clock_ms)?;
+        single_hop_quote_with_clock(state, pool_id, input, is_sell_base, clock)
+    };
+    let unfillable = |max_output: u64| {
+        anyhow!(
+            "{}: {} can deliver at most {} output (requested {})",
+            EXACT_OUT_UNFILLABLE,
+            pool_id.display_name(),
+            max_output,
+            desired_output
+        )
+    };
+
+    // Invariant: the quote at `lo` stays below `desired_output`.
+    let (mut lo, mut hi) = (0u64, 1u64);
+    let mut best_output = 0u64;
+    let hi_quote = loop {
+        match quote(state, hi) {
+            Ok(hi_quote) if hi_quote.output_amount >= desired_output => break hi_quote,
+            Ok(hi_quote) if best_output > 0 && hi_quote.output_amount <= best_output => {
+                return Err(unfillable(best_output));
+            }
+            Ok(hi_quote) => best_output = hi_quote.output_amount,
+            Err(e) if best_output > 0 => {
+                tracing::debug!(
+                    "Router: reverse quote on {} aborted at input {}: {}",
+                    pool_id.display_name(),
+                    hi,
+                    e
+                );
+                return Err(unfillable(best_output));
+            }
+            // Below the pool's min size
+            Err(_) => {}
+        }
+        lo = hi;
+        hi = hi.checked_mul(2).ok_or_else(|| unfillable(best_output))?;
+    };
+
+    let (input_amount, quote) =
+        bisect_exact_out_input(state, &mut quote, desired_output, lo, hi, hi_quote);
+    Ok(ReverseQuote {
+        input_amount,
+        output_amount: quote.output_amount,
+        deep_fee: quote.deep_fee,
+        quote_iterations,
+    })
+}
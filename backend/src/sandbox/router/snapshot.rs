@@ -0,0 +1,304 @@
+//! Router snapshots
+//!
+//! Captures every loaded object and dynamic field plus the router's own
+//! bookkeeping into a `RouterSnapshot`, writes it to disk, and rebuilds a
+//! router environment from one.
+
+use super::*;
+
+/// Every object loaded in the environment
+pub(super) fn env_objects(state: &RouterEnvState) -> Vec<SnapshotObject> {
+    state
+        .env
+        .list_objects()
+        .into_iter()
+        .map(|obj| SnapshotObject {
+            id: obj.id,
+            type_tag: obj.type_tag.to_string(),
+            bcs_bytes: obj.bcs_bytes.clone(),
+            is_shared: obj.is_shared,
+            version: obj.version,
+        })
+        .collect()
+}
+
+/// Every dynamic field under a parent in `dynamic_field_parents`, in parent order
+pub(super) fn env_dynamic_fields(state: &RouterEnvState) -> Vec<SnapshotDynamicField> {
+    let mut parents: Vec<AccountAddress> = state.dynamic_field_parents.iter().copied().collect();
+    parents.sort();
+    let mut dynamic_fields = Vec::new();
+    for parent in parents {
+        for (child_id, type_tag, bytes) in state.env.get_dynamic_fields_for_parent(parent) {
+            dynamic_fields.push(SnapshotDynamicField {
+                parent,
+                child: child_id.to_owned(),
+                type_tag: type_tag.to_string(),
+                bcs_bytes: bytes.to_vec(),
+            });
+        }
+    }
+    dynamic_fields
+}
+
+/// Snapshot of the environment's packages, objects, dynamic fields, reserve
+/// coins and clock.
+///
+/// Debug token objects and fields are left out, so a restored environment
+/// has no debug pool; session balance managers are dropped the same way.
+fn capture_router_snapshot(
+    state: &RouterEnvState,
+    pool_files: &[(PoolId, String)],
+) -> RouterSnapshot {
+    let is_debug_type = |type_tag: &str| type_tag.contains("::debug_token::");
+
+    let objects = env_objects(state)
+        .into_iter()
+        .filter(|obj| !is_debug_type(&obj.type_tag))
+        .collect();
+    let dynamic_fields = env_dynamic_fields(state)
+        .into_iter()
+        .filter(|field| !is_debug_type(&field.type_tag))
+        .collect();
+
+    let mut pools: Vec<SnapshotPool> = state
+        .pool_cache
+        .iter()
+        .filter(|(pool_id, _)| !pool_id.is_debug())
+        .map(|(pool_id, entry)| SnapshotPool {
+            pool_id: *pool_id,
+            pool_addr: entry.pool_addr,
+            pool_type: entry.pool_type.to_string(),
+            accounts_table: entry.accounts_table,
+            history_table: entry.history_table,
+            history_epoch: entry.history_epoch,
+            whitelisted: entry.whitelisted,
+        })
+        .collect();
+    pools.sort_by_key(|pool| pool.pool_id.as_str());
+
+    let mut reserve_coins: Vec<SnapshotReserveCoin> = state
+        .coin_reserve_cache
+        .iter()
+        .map(|(coin_type, object_id)| {
+            let provenance = state.reserve_provenance.get(coin_type);
+            SnapshotReserveCoin {
+                coin_type: coin_type.clone(),
+                object_id: *object_id,
+                source_checkpoint: provenance.map(|p| p.checkpoint),
+                source_value: provenance.map(|p| p.value),
+                source_version: provenance.map(|p| p.version),
+            }
+        })
+        .collect();
+    reserve_coins.sort_by(|a, b| a.coin_type.cmp(&b.coin_type));
+
+    RouterSnapshot {
+        created_at_unix_ms: now_unix_ms(),
+        pool_files: pool_files.to_vec(),
+        packages: state.snapshot_packages.clone(),
+        objects,
+        dynamic_fields,
+        pools,
+        reserve_coins,
+        router_contract: SnapshotRouterContract {
+            address: state.router_contract.address.clone(),
+            modules: state.router_contract.modules.clone(),
+            build_method: state.router_contract.build_method.clone(),
+            mainnet_build_error: state.router_contract.mainnet_build_error.clone(),
+            deployed_at_unix_ms: state.router_contract.deployed_at_unix_ms,
+        },
+        loaded_packages: state
+            .loaded_packages
+            .iter()
+            .map(|package| SnapshotPackageCheck {
+                name: package.name.clone(),
+                package_id: package.package_id.clone(),
+                module_count: package.module_count,
+            })
+            .collect(),
+        sender: state.env.sender(),
+        epoch: state.epoch,
+        next_clock_timestamp_ms: state.next_clock_timestamp_ms,
+        last_deep_price_point_ms: state.last_deep_price_point_ms,
+    }
+}
+
+fn snapshot_info(path: &Path, size_bytes: u64, snapshot: &RouterSnapshot) -> RouterSnapshotInfo {
+    RouterSnapshotInfo {
+        path: path.display().to_string(),
+        format_version: router_snapshot::SNAPSHOT_FORMAT_VERSION,
+        size_bytes,
+        created_at_unix_ms: snapshot.created_at_unix_ms,
+        packages: snapshot.packages.len(),
+        objects: snapshot.objects.len(),
+        dynamic_fields: snapshot.dynamic_fields.len(),
+        clock_now_ms: snapshot.next_clock_timestamp_ms,
+    }
+}
+
+pub(super) fn write_router_snapshot(
+    state: &RouterEnvState,
+    pool_files: &[(PoolId, String)],
+    path: &Path,
+) -> Result<RouterSnapshotInfo> {
+    let snapshot = capture_router_snapshot(state, pool_files);
+    let size_bytes = router_snapshot::write_snapshot(path, &snapshot)?;
+    Ok(snapshot_info(path, size_bytes, &snapshot))
+}
+
+/// Rebuild the router environment from a snapshot file without gRPC or a
+/// contract build, then rerun the startup self-check.
+pub(super) fn restore_router_snapshot(
+    path: &Path,
+    pool_files: &[(PoolId, String)],
+) -> Result<(RouterEnvState, RouterSnapshotInfo)> {
+    let snapshot = router_snapshot::read_snapshot(path)?;
+    if snapshot.pool_files != pool_files {
+        return Err(anyhow!(
+            "snapshot {} was taken from different pool state files",
+            path.display()
+        ));
+    }
+    let info = snapshot_info(path, std::fs::metadata(path)?.len(), &snapshot);
+    tracing::info!(
+        "Router thread: restoring SimulationEnvironment from snapshot {} ({} objects, {} dynamic fields)",
+        info.path,
+        info.objects,
+        info.dynamic_fields
+    );
+    let state = router_env_from_snapshot(snapshot, true)?;
+    Ok((state, info))
+}
+
+/// Rebuild a router environment from an in-memory snapshot.
+///
+/// With `fetch_missing` unset no gRPC fetcher is installed, so anything the
+/// snapshot lacks fails instead of being pulled from mainnet.
+pub(super) fn router_env_from_snapshot(
+    snapshot: RouterSnapshot,
+    fetch_missing: bool,
+) -> Result<RouterEnvState> {
+    let mut env = SimulationEnvironment::new()?;
+    let mut bcs_converter = JsonToBcsConverter::new();
+    if fetch_missing {
+        env.set_fetcher(Box::new(GrpcFetcher::mainnet()));
+        env.set_fetcher_config(FetcherConfig::mainnet());
+    }
+    for package in &snapshot.packages {
+        let bytecode_list: Vec<Vec<u8>> = package
+            .modules
+            .iter()
+            .map(|(_, bytes)| bytes.clone())
+            .collect();
+        if let Err(e) = bcs_converter.add_modules_from_bytes(&bytecode_list) {
+            tracing::warn!(
+                "Router: failed to add {} to BCS converter: {}",
+                package.address,
+                e
+            );
+        }
+        env.deploy_package_at_address(&package.address, package.modules.clone())?;
+    }
+    if let Some(epoch) = snapshot.epoch {
+        env.config_mut().epoch = epoch;
+    }
+    for object in &snapshot.objects {
+        env.load_object_from_data(
+            &object.id.to_hex_literal(),
+            object.bcs_bytes.clone(),
+            Some(&object.type_tag),
+            object.is_shared,
+            false,
+            object.version,
+        )?;
+    }
+    let mut dynamic_field_parents = HashSet::new();
+    for field in &snapshot.dynamic_fields {
+        let type_tag = SimulationEnvironment::parse_type_string(&field.type_tag)
+            .ok_or_else(|| anyhow!("Failed to parse field type: {}", field.type_tag))?;
+        env.set_dynamic_field(field.parent, field.child, type_tag, field.bcs_bytes.clone());
+        dynamic_field_parents.insert(field.parent);
+    }
+    env.set_sender(snapshot.sender);
+
+    let mut pool_cache = HashMap::new();
+    for pool in &snapshot.pools {
+        let pool_type = TypeTag::from_str(&pool.pool_type)?;
+        let (base_type, quote_type) = pool_type_params(&pool_type)
+            .map(|(base, quote)| {
+                (
+                    canonical_coin_type(&base.to_string()),
+                    canonical_coin_type(&quote.to_string()),
+                )
+            })
+            .ok_or_else(|| anyhow!("Unexpected pool type in snapshot: {}", pool.pool_type))?;
+        pool_cache.insert(
+            pool.pool_id,
+            PoolCacheEntry {
+                pool_addr: pool.pool_addr,
+                pool_type,
+                base_type,
+                quote_type,
+                accounts_table: pool.accounts_table,
+                history_table: pool.history_table,
+                history_epoch: pool.history_epoch,
+                whitelisted: pool.whitelisted,
+                params: None,
+            },
+        );
+    }
+    let contract = snapshot.router_contract;
+    let router_contract = RouterContractInfo {
+        address: contract.address,
+        deployed: true,
+        modules: contract.modules,
+        build_method: contract.build_method,
+        mainnet_build_error: contract.mainnet_build_error,
+        deployed_at_unix_ms: contract.deployed_at_unix_ms,
+    };
+    let loaded_packages = snapshot
+        .loaded_packages
+        .into_iter()
+        .map(|package| RouterPackageCheck {
+            name: package.name,
+            package_id: package.package_id,
+            loaded: package.module_count > 0,
+            module_count: package.module_count,
+        })
+        .collect();
+
+    let mut state = new_router_env_state(
+        env,
+        pool_cache,
+        router_contract,
+        loaded_packages,
+        bcs_converter,
+    );
+    for coin in snapshot.reserve_coins {
+        if let (Some(checkpoint), Some(value), Some(version)) = (
+            coin.source_checkpoint,
+            coin.source_value,
+            coin.source_version,
+        ) {
+            state.reserve_provenance.insert(
+                coin.coin_type.clone(),
+                ReserveCoinProvenance {
+                    checkpoint,
+                    value,
+                    version,
+                },
+            );
+        }
+        state
+            .coin_reserve_cache
+            .insert(coin.coin_type, coin.object_id);
+    }
+    state.snapshot_packages = snapshot.packages;
+    state.dynamic_field_parents = dynamic_field_parents;
+    state.epoch = snapshot.epoch;
+    state.next_clock_timestamp_ms = snapshot.next_clock_timestamp_ms;
+    state.last_deep_price_point_ms = snapshot.last_deep_price_point_ms;
+
+    state.startup_check = run_startup_self_check(&mut state)?;
+    Ok(state)
+}
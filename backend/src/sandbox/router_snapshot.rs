@@ -0,0 +1,251 @@
+//! On-disk snapshots of the router's MoveVM environment
+//!
+//! A snapshot holds what `setup_router_env` fetches and builds at boot:
+//! package bytecode (including the compiled router contract), loaded objects,
+//! dynamic fields, the reserve coin cache and the synthetic clock. A later
+//! boot restores it instead of rerunning the gRPC bootstrap, the JSONL loads
+//! and `sui move build` (`SNAPSHOT_PATH`).
+//!
+//! The file is `SNAPSHOT_MAGIC`, a little-endian `u32` format version, then
+//! the BCS-encoded [`RouterSnapshot`]. Files with another version are rejected
+//! rather than decoded, so bump [`SNAPSHOT_FORMAT_VERSION`] whenever a
+//! snapshot type changes.
+
+use anyhow::{anyhow, Result};
+use move_core_types::account_address::AccountAddress;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use super::state_loader::PoolId;
+
+/// Leading bytes of every snapshot file
+pub const SNAPSHOT_MAGIC: &[u8; 8] = b"DBSBSNAP";
+/// Layout version of [`RouterSnapshot`]
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+const HEADER_LEN: usize = SNAPSHOT_MAGIC.len() + 4;
+
+/// Serialized router environment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RouterSnapshot {
+    pub created_at_unix_ms: u64,
+    /// `(pool, state file)` pairs the environment was loaded from; a boot
+    /// with different pool files does not restore this snapshot
+    pub pool_files: Vec<(PoolId, String)>,
+    pub packages: Vec<SnapshotPackage>,
+    pub objects: Vec<SnapshotObject>,
+    pub dynamic_fields: Vec<SnapshotDynamicField>,
+    pub pools: Vec<SnapshotPool>,
+    pub reserve_coins: Vec<SnapshotReserveCoin>,
+    pub router_contract: SnapshotRouterContract,
+    pub loaded_packages: Vec<SnapshotPackageCheck>,
+    pub sender: AccountAddress,
+    pub epoch: Option<u64>,
+    pub next_clock_timestamp_ms: u64,
+    pub last_deep_price_point_ms: Option<u64>,
+}
+
+/// Package modules deployed at `address`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotPackage {
+    pub address: String,
+    /// `(module name, bytecode)`
+    pub modules: Vec<(String, Vec<u8>)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotObject {
+    pub id: AccountAddress,
+    pub type_tag: String,
+    pub bcs_bytes: Vec<u8>,
+    pub is_shared: bool,
+    pub version: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotDynamicField {
+    pub parent: AccountAddress,
+    pub child: AccountAddress,
+    pub type_tag: String,
+    pub bcs_bytes: Vec<u8>,
+}
+
+/// Router pool cache entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotPool {
+    pub pool_id: PoolId,
+    pub pool_addr: AccountAddress,
+    pub pool_type: String,
+    pub accounts_table: Option<AccountAddress>,
+    pub history_table: Option<AccountAddress>,
+    pub history_epoch: Option<u64>,
+    pub whitelisted: Option<bool>,
+}
+
+/// Reserve coin and the mainnet checkpoint it was bootstrapped from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotReserveCoin {
+    pub coin_type: String,
+    pub object_id: AccountAddress,
+    pub source_checkpoint: Option<u64>,
+    pub source_value: Option<u64>,
+    pub source_version: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotRouterContract {
+    pub address: String,
+    pub modules: Vec<String>,
+    pub build_method: String,
+    pub mainnet_build_error: Option<String>,
+    pub deployed_at_unix_ms: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotPackageCheck {
+    pub name: String,
+    pub package_id: String,
+    pub module_count: usize,
+}
+
+/// Snapshot file from `SNAPSHOT_PATH`, if set
+pub fn snapshot_path_from_env() -> Option<PathBuf> {
+    match std::env::var("SNAPSHOT_PATH") {
+        Ok(path) if !path.trim().is_empty() => Some(PathBuf::from(path.trim())),
+        _ => None,
+    }
+}
+
+/// Write `snapshot` to `path` and return the file size in bytes.
+///
+/// The bytes go to a sibling temp file first and are renamed into place, so
+/// a crash mid-write never leaves a truncated snapshot at `path`.
+pub fn write_snapshot(path: &Path, snapshot: &RouterSnapshot) -> Result<u64> {
+    let body = bcs::to_bytes(snapshot)?;
+    let mut bytes = Vec::with_capacity(HEADER_LEN + body.len());
+    bytes.extend_from_slice(SNAPSHOT_MAGIC);
+    bytes.extend_from_slice(&SNAPSHOT_FORMAT_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&body);
+
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp_path = path.with_extension("tmp");
+    std::fs::write(&tmp_path, &bytes)
+        .map_err(|e| anyhow!("Failed to write snapshot {}: {}", tmp_path.display(), e))?;
+    std::fs::rename(&tmp_path, path)
+        .map_err(|e| anyhow!("Failed to move snapshot into {}: {}", path.display(), e))?;
+    Ok(bytes.len() as u64)
+}
+
+/// Read a snapshot, rejecting files with a foreign header or another format version.
+pub fn read_snapshot(path: &Path) -> Result<RouterSnapshot> {
+    let bytes = std::fs::read(path)
+        .map_err(|e| anyhow!("Failed to read snapshot {}: {}", path.display(), e))?;
+    decode_snapshot(&bytes).map_err(|e| anyhow!("Invalid snapshot {}: {}", path.display(), e))
+}
+
+fn decode_snapshot(bytes: &[u8]) -> Result<RouterSnapshot> {
+    if bytes.len() < HEADER_LEN || &bytes[..SNAPSHOT_MAGIC.len()] != SNAPSHOT_MAGIC {
+        return Err(anyhow!("not a router snapshot"));
+    }
+    let mut version_bytes = [0u8; 4];
+    version_bytes.copy_from_slice(&bytes[SNAPSHOT_MAGIC.len()..HEADER_LEN]);
+    let version = u32::from_le_bytes(version_bytes);
+    if version != SNAPSHOT_FORMAT_VERSION {
+        return Err(anyhow!(
+            "format version {} does not match {}",
+            version,
+            SNAPSHOT_FORMAT_VERSION
+        ));
+    }
+    Ok(bcs::from_bytes(&bytes[HEADER_LEN..])?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_snapshot() -> RouterSnapshot {
+        RouterSnapshot {
+            created_at_unix_ms: 1_770_000_000_000,
+            pool_files: vec![(PoolId::SuiUsdc, "data/sui_usdc.jsonl".to_string())],
+            packages: vec![SnapshotPackage {
+                address: "0x2".to_string(),
+                modules: vec![("coin".to_string(), vec![0xa1, 0x1c, 0xeb, 0x0b])],
+            }],
+            objects: vec![SnapshotObject {
+                id: AccountAddress::from_hex_literal("0x6").unwrap(),
+                type_tag: "0x2::clock::Clock".to_string(),
+                bcs_bytes: vec![0; 40],
+                is_shared: true,
+                version: 1,
+            }],
+            dynamic_fields: vec![SnapshotDynamicField {
+                parent: AccountAddress::from_hex_literal("0x10").unwrap(),
+                child: AccountAddress::from_hex_literal("0x11").unwrap(),
+                type_tag: "0x2::dynamic_field::Field<u64, u64>".to_string(),
+                bcs_bytes: vec![1, 2, 3],
+            }],
+            pools: vec![SnapshotPool {
+                pool_id: PoolId::SuiUsdc,
+                pool_addr: AccountAddress::from_hex_literal("0x20").unwrap(),
+                pool_type: "0x2::pool::Pool".to_string(),
+                accounts_table: None,
+                history_table: Some(AccountAddress::from_hex_literal("0x21").unwrap()),
+                history_epoch: Some(900),
+                whitelisted: None,
+            }],
+            reserve_coins: vec![SnapshotReserveCoin {
+                coin_type: "0x2::sui::SUI".to_string(),
+                object_id: AccountAddress::from_hex_literal("0x30").unwrap(),
+                source_checkpoint: Some(240_000_000),
+                source_value: Some(5),
+                source_version: Some(7),
+            }],
+            router_contract: SnapshotRouterContract {
+                address: "0x100".to_string(),
+                modules: vec!["router".to_string()],
+                build_method: "mainnet".to_string(),
+                mainnet_build_error: None,
+                deployed_at_unix_ms: 1,
+            },
+            loaded_packages: vec![SnapshotPackageCheck {
+                name: "Sui Framework".to_string(),
+                package_id: "0x2".to_string(),
+                module_count: 1,
+            }],
+            sender: AccountAddress::ZERO,
+            epoch: Some(900),
+            next_clock_timestamp_ms: 1_770_000_061_000,
+            last_deep_price_point_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_snapshot_round_trips_through_file() {
+        let path =
+            std::env::temp_dir().join(format!("router-snapshot-{}.bin", uuid::Uuid::new_v4()));
+        let snapshot = sample_snapshot();
+
+        let size = write_snapshot(&path, &snapshot).expect("write snapshot");
+        assert_eq!(size, std::fs::metadata(&path).unwrap().len());
+        let restored = read_snapshot(&path).expect("read snapshot");
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(restored, snapshot);
+    }
+
+    #[test]
+    fn test_snapshot_rejects_other_versions_and_foreign_files() {
+        let body = bcs::to_bytes(&sample_snapshot()).unwrap();
+        let mut stale = SNAPSHOT_MAGIC.to_vec();
+        stale.extend_from_slice(&(SNAPSHOT_FORMAT_VERSION + 1).to_le_bytes());
+        stale.extend_from_slice(&body);
+        let err = decode_snapshot(&stale).expect_err("stale version is rejected");
+        assert!(err.to_string().contains("format version"));
+
+        assert!(decode_snapshot(b"{\"objects\": []}").is_err());
+        assert!(decode_snapshot(SNAPSHOT_MAGIC).is_err());
+    }
+}
@@ -1,13 +1,15 @@
 //! JSON to BCS Reconstruction Utility
 //!
 //! This module converts Snowflake's OBJECT_JSON (decoded Move object data) back to
-//! BCS bytes using struct layouts extracted from Move bytecode.
+//! BCS bytes using struct layouts extracted from Move bytecode, and decodes BCS
+//! (e.g. event payloads) into JSON with the same layouts.
 //!
 //! Copied from sui-sandbox examples/common/snowflake_bcs.rs
 
 use anyhow::{anyhow, Context, Result};
 use base64::Engine;
 use move_core_types::account_address::AccountAddress;
+use move_core_types::u256::U256;
 use serde_json::Value as JsonValue;
 
 // Import from sui-sandbox-core utilities - these are public but not re-exported
@@ -753,6 +755,191 @@ impl JsonToBcsConverter {
             }
         }
     }
+
+    // =========================================================================
+    // BCS to JSON
+    // =========================================================================
+
+    /// Decode BCS bytes back to JSON using the bytecode layout of `type_str`.
+    ///
+    /// The inverse of [`convert`](Self::convert): bools and u8-u32 become JSON
+    /// booleans and numbers, wider integers decimal strings, addresses and IDs
+    /// hex literals, `vector<u8>` a `0x` hex string, `Option` null or its value,
+    /// and `String`/`TypeName` plain strings.
+    pub fn decode(&mut self, type_str: &str, bcs_bytes: &[u8]) -> Result<JsonValue> {
+        let (layout, type_args) = self
+            .layout_registry
+            .get_layout_with_type_args(type_str)
+            .ok_or_else(|| anyhow!("Could not find layout for type: {}", type_str))?;
+
+        let mut reader = BcsReader::new(bcs_bytes);
+        let value = self.decode_struct_fields(&mut reader, &layout, &type_args)?;
+        if reader.remaining() > 0 {
+            return Err(anyhow!(
+                "{} trailing bytes after decoding {}",
+                reader.remaining(),
+                type_str
+            ));
+        }
+        Ok(value)
+    }
+
+    /// Decode struct fields in layout order into a JSON object.
+    fn decode_struct_fields(
+        &mut self,
+        reader: &mut BcsReader<'_>,
+        layout: &StructLayout,
+        type_args: &[MoveType],
+    ) -> Result<JsonValue> {
+        let mut fields = serde_json::Map::new();
+        for field_layout in &layout.fields {
+            let field_type = self.substitute_type_params(&field_layout.field_type, type_args);
+            let value = self.decode_field(reader, &field_type, &field_layout.name)?;
+            fields.insert(field_layout.name.clone(), value);
+        }
+        Ok(JsonValue::Object(fields))
+    }
+
+    /// Decode a single value of `move_type`.
+    fn decode_field(
+        &mut self,
+        reader: &mut BcsReader<'_>,
+        move_type: &MoveType,
+        field_name: &str,
+    ) -> Result<JsonValue> {
+        match move_type {
+            MoveType::Bool => match reader.read_array::<1>(field_name)?[0] {
+                0 => Ok(JsonValue::Bool(false)),
+                1 => Ok(JsonValue::Bool(true)),
+                b => Err(anyhow!("Invalid bool byte {} for field {}", b, field_name)),
+            },
+
+            MoveType::U8 => Ok(JsonValue::from(reader.read_array::<1>(field_name)?[0])),
+
+            MoveType::U16 => Ok(JsonValue::from(u16::from_le_bytes(
+                reader.read_array(field_name)?,
+            ))),
+
+            MoveType::U32 => Ok(JsonValue::from(u32::from_le_bytes(
+                reader.read_array(field_name)?,
+            ))),
+
+            MoveType::U64 => Ok(JsonValue::String(
+                u64::from_le_bytes(reader.read_array(field_name)?).to_string(),
+            )),
+
+            MoveType::U128 => Ok(JsonValue::String(
+                u128::from_le_bytes(reader.read_array(field_name)?).to_string(),
+            )),
+
+            MoveType::U256 => Ok(JsonValue::String(
+                U256::from_le_bytes(&reader.read_array(field_name)?).to_string(),
+            )),
+
+            MoveType::Address | MoveType::Signer => Ok(JsonValue::String(
+                AccountAddress::new(reader.read_array(field_name)?).to_hex_literal(),
+            )),
+
+            MoveType::Vector(inner_type) => {
+                let len = reader.read_uleb128(field_name)?;
+                // vector<u8> as a hex string, which convert_vector accepts back
+                if matches!(**inner_type, MoveType::U8) {
+                    let bytes = reader.read_bytes(len, field_name)?;
+                    return Ok(JsonValue::String(format!("0x{}", hex::encode(bytes))));
+                }
+                let mut elements = Vec::new();
+                for i in 0..len {
+                    let elem_name = format!("{}[{}]", field_name, i);
+                    elements.push(self.decode_field(reader, inner_type, &elem_name)?);
+                }
+                Ok(JsonValue::Array(elements))
+            }
+
+            MoveType::Struct {
+                address,
+                module,
+                name,
+                type_args,
+            } => self.decode_struct(reader, address, module, name, type_args, field_name),
+
+            MoveType::TypeParameter(_) => {
+                Err(anyhow!("Unresolved type parameter in field {}", field_name))
+            }
+        }
+    }
+
+    /// Decode a struct value, mirroring the special cases of `convert_struct`.
+    fn decode_struct(
+        &mut self,
+        reader: &mut BcsReader<'_>,
+        address: &AccountAddress,
+        module: &str,
+        name: &str,
+        type_args: &[MoveType],
+        field_name: &str,
+    ) -> Result<JsonValue> {
+        // UID and ID both wrap a single address
+        if module == "object" && (name == "UID" || name == "ID") {
+            let id = AccountAddress::new(reader.read_array(field_name)?).to_hex_literal();
+            return Ok(if name == "UID" {
+                serde_json::json!({ "id": id })
+            } else {
+                JsonValue::String(id)
+            });
+        }
+
+        // Balance<T>
+        if module == "balance" && name == "Balance" {
+            return self.decode_field(reader, &MoveType::U64, field_name);
+        }
+
+        // Option<T> is a vector with 0 or 1 elements
+        if module == "option" && name == "Option" {
+            let inner_type = type_args
+                .first()
+                .ok_or_else(|| anyhow!("Option without type argument in {}", field_name))?;
+            return match reader.read_uleb128(field_name)? {
+                0 => Ok(JsonValue::Null),
+                1 => self.decode_field(reader, inner_type, field_name),
+                len => Err(anyhow!("Option of length {} in {}", len, field_name)),
+            };
+        }
+
+        // String (0x1::string::String / 0x1::ascii::String) and TypeName { name: String }
+        if (name == "String" && (module == "string" || module == "ascii"))
+            || (name == "TypeName" && module == "type_name")
+        {
+            let len = reader.read_uleb128(field_name)?;
+            let bytes = reader.read_bytes(len, field_name)?;
+            let s = std::str::from_utf8(bytes)
+                .with_context(|| format!("Invalid UTF-8 string in {}", field_name))?;
+            return Ok(JsonValue::String(s.to_string()));
+        }
+
+        // Generic struct - get layout and recurse
+        let base_type = format!("{}::{}::{}", address.to_hex_literal(), module, name);
+        let full_type = if type_args.is_empty() {
+            base_type
+        } else {
+            let type_args_str = type_args
+                .iter()
+                .map(format_move_type)
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("{}<{}>", base_type, type_args_str)
+        };
+        let (layout, nested_type_args) = self
+            .layout_registry
+            .get_layout_with_type_args(&full_type)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Could not find layout for type {} in field {}",
+                    full_type,
+                    field_name
+                )
+            })?;
+        self.decode_struct_fields(reader, &layout, &nested_type_args)
+    }
 }
 
 impl Default for JsonToBcsConverter {
@@ -849,6 +1036,61 @@ fn parse_hex_address(s: &str) -> Result<[u8; 32]> {
     Ok(arr)
 }
 
+/// Forward-only cursor over BCS bytes for [`JsonToBcsConverter::decode`]
+struct BcsReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BcsReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.pos
+    }
+
+    fn read_bytes(&mut self, len: usize, field_name: &str) -> Result<&'a [u8]> {
+        if len > self.remaining() {
+            return Err(anyhow!(
+                "Unexpected end of BCS data in {} (need {} bytes, have {})",
+                field_name,
+                len,
+                self.remaining()
+            ));
+        }
+        let bytes = &self.bytes[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn read_array<const N: usize>(&mut self, field_name: &str) -> Result<[u8; N]> {
+        let mut arr = [0u8; N];
+        arr.copy_from_slice(self.read_bytes(N, field_name)?);
+        Ok(arr)
+    }
+
+    /// ULEB128 length prefix of vectors, strings and options
+    fn read_uleb128(&mut self, field_name: &str) -> Result<usize> {
+        let mut value = 0u64;
+        let mut shift = 0u32;
+        loop {
+            let byte = self.read_array::<1>(field_name)?[0];
+            if shift >= 64 {
+                return Err(anyhow!("ULEB128 length overflow in {}", field_name));
+            }
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                break;
+            }
+            shift += 7;
+        }
+        usize::try_from(value)
+            .map_err(|_| anyhow!("ULEB128 length {} too large in {}", value, field_name))
+    }
+}
+
 fn format_move_type(move_type: &MoveType) -> String {
     match move_type {
         MoveType::Bool => "bool".to_string(),
@@ -882,3 +1124,75 @@ fn format_move_type(move_type: &MoveType) -> String {
         MoveType::TypeParameter(idx) => format!("T{}", idx),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn std_struct(module: &str, name: &str, type_args: Vec<MoveType>) -> MoveType {
+        MoveType::Struct {
+            address: AccountAddress::ONE,
+            module: module.to_string(),
+            name: name.to_string(),
+            type_args,
+        }
+    }
+
+    fn decode_value(move_type: &MoveType, bytes: &[u8]) -> Result<JsonValue> {
+        let mut reader = BcsReader::new(bytes);
+        let value = JsonToBcsConverter::new().decode_field(&mut reader, move_type, "value")?;
+        assert_eq!(reader.remaining(), 0, "all bytes consumed");
+        Ok(value)
+    }
+
+    #[test]
+    fn test_decode_field_primitives_and_std_structs() {
+        let u64_value = bcs::to_bytes(&1_500_000_000u64).unwrap();
+        assert_eq!(
+            decode_value(&MoveType::U64, &u64_value).unwrap(),
+            JsonValue::String("1500000000".to_string())
+        );
+        assert_eq!(
+            decode_value(&MoveType::U8, &[7]).unwrap(),
+            JsonValue::from(7)
+        );
+        assert_eq!(
+            decode_value(&MoveType::Bool, &[1]).unwrap(),
+            JsonValue::Bool(true)
+        );
+
+        let address = AccountAddress::from_hex_literal("0xabc").unwrap();
+        assert_eq!(
+            decode_value(&MoveType::Address, &bcs::to_bytes(&address).unwrap()).unwrap(),
+            JsonValue::String(address.to_hex_literal())
+        );
+
+        let bytes = bcs::to_bytes(&vec![0xdeu8, 0xad]).unwrap();
+        assert_eq!(
+            decode_value(&MoveType::Vector(Box::new(MoveType::U8)), &bytes).unwrap(),
+            JsonValue::String("0xdead".to_string())
+        );
+
+        let option_u64 = std_struct("option", "Option", vec![MoveType::U64]);
+        let some = bcs::to_bytes(&Some(42u64)).unwrap();
+        assert_eq!(
+            decode_value(&option_u64, &some).unwrap(),
+            JsonValue::String("42".to_string())
+        );
+        assert_eq!(decode_value(&option_u64, &[0]).unwrap(), JsonValue::Null);
+
+        let string = bcs::to_bytes("DEEP").unwrap();
+        assert_eq!(
+            decode_value(&std_struct("string", "String", vec![]), &string).unwrap(),
+            JsonValue::String("DEEP".to_string())
+        );
+    }
+
+    #[test]
+    fn test_decode_field_rejects_truncated_data() {
+        assert!(decode_value(&MoveType::U64, &[1, 2, 3]).is_err());
+        // vector claims 5 elements, carries 2
+        assert!(decode_value(&MoveType::Vector(Box::new(MoveType::U8)), &[5, 1, 2]).is_err());
+        assert!(decode_value(&MoveType::Bool, &[2]).is_err());
+    }
+}
@@ -5,8 +5,11 @@
 //! the SimulationEnvironment.
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+
+use crate::units;
 
 /// Represents a single object exported from Snowflake
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,49 +29,417 @@ pub struct ExportedObject {
     pub checkpoint: u64,
 }
 
-/// Pool identifier for the three supported pools
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
-pub enum PoolId {
-    SuiUsdc,
-    WalUsdc,
-    DeepUsdc,
-    DebugUsdc,
+/// Pool identifier.
+///
+/// The checkpoint 240M pools and the first debug pool have fixed ids; any
+/// other `POOLS_MANIFEST` entry registers one at startup with
+/// [`PoolId::register`], and further debug pools with
+/// [`PoolId::register_debug`].
+/// Keys and display names live in a process-wide table, so ids stay `Copy`
+/// and a pool's label follows the pair its state file actually holds.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PoolId(u16);
+
+/// Key and display name of a registered pool
+struct PoolName {
+    key: &'static str,
+    display_name: &'static str,
+    /// Created in the VM rather than loaded from a state file
+    debug: bool,
+}
+
+/// Every registered pool, indexed by `PoolId`. Names are leaked so they can
+/// be handed out as `&'static str`; only a handful of pools ever register.
+fn pool_names() -> &'static RwLock<Vec<PoolName>> {
+    static NAMES: OnceLock<RwLock<Vec<PoolName>>> = OnceLock::new();
+    NAMES.get_or_init(|| {
+        let builtin = [
+            ("sui_usdc", "SUI/USDC"),
+            ("wal_usdc", "WAL/USDC"),
+            ("deep_usdc", "DEEP/USDC"),
+            ("debug_usdc", "DBG/USDC"),
+        ];
+        RwLock::new(
+            builtin
+                .into_iter()
+                .map(|(key, display_name)| PoolName {
+                    key,
+                    display_name,
+                    debug: key == "debug_usdc",
+                })
+                .collect(),
+        )
+    })
+}
+
+/// `sui-usdc`, `SUI_USDC` and `suiusdc` all name `sui_usdc`
+fn compact_pool_key(key: &str) -> String {
+    key.trim()
+        .to_lowercase()
+        .chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .collect()
 }
 
+#[allow(non_upper_case_globals)]
 impl PoolId {
+    pub const SuiUsdc: PoolId = PoolId(0);
+    pub const WalUsdc: PoolId = PoolId(1);
+    pub const DeepUsdc: PoolId = PoolId(2);
+    pub const DebugUsdc: PoolId = PoolId(3);
+
     pub fn as_str(&self) -> &'static str {
-        match self {
-            PoolId::SuiUsdc => "sui_usdc",
-            PoolId::WalUsdc => "wal_usdc",
-            PoolId::DeepUsdc => "deep_usdc",
-            PoolId::DebugUsdc => "debug_usdc",
-        }
+        let names = pool_names().read().unwrap_or_else(|e| e.into_inner());
+        names[self.0 as usize].key
     }
 
+    /// `BASE/QUOTE` of the pool as loaded; see [`PoolId::set_display_name`]
     pub fn display_name(&self) -> &'static str {
-        match self {
-            PoolId::SuiUsdc => "SUI/USDC",
-            PoolId::WalUsdc => "WAL/USDC",
-            PoolId::DeepUsdc => "DEEP/USDC",
-            PoolId::DebugUsdc => "DBG/USDC",
+        let names = pool_names().read().unwrap_or_else(|e| e.into_inner());
+        names[self.0 as usize].display_name
+    }
+
+    /// Label the pool with the pair it was loaded with, e.g. `SUI/DEEP`
+    pub fn set_display_name(&self, display_name: &str) {
+        let mut names = pool_names().write().unwrap_or_else(|e| e.into_inner());
+        let name = &mut names[self.0 as usize];
+        if name.display_name != display_name {
+            name.display_name = Box::leak(display_name.to_string().into_boxed_str());
         }
     }
 
     pub fn from_str(s: &str) -> Option<Self> {
-        match s.to_lowercase().as_str() {
-            "sui_usdc" | "sui-usdc" | "suiusdc" => Some(PoolId::SuiUsdc),
-            "wal_usdc" | "wal-usdc" | "walusdc" => Some(PoolId::WalUsdc),
-            "deep_usdc" | "deep-usdc" | "deepusdc" => Some(PoolId::DeepUsdc),
-            "debug_usdc" | "debug-usdc" | "debugusdc" | "dbg_usdc" | "dbg-usdc" | "dbgusdc" => {
-                Some(PoolId::DebugUsdc)
+        let key = compact_pool_key(s);
+        if key == "dbgusdc" {
+            return Some(PoolId::DebugUsdc);
+        }
+        let names = pool_names().read().unwrap_or_else(|e| e.into_inner());
+        names
+            .iter()
+            .position(|name| compact_pool_key(name.key) == key)
+            .map(|idx| PoolId(idx as u16))
+    }
+
+    /// Id of the pool keyed `key` (lowercase letters, digits and `_`),
+    /// registering it on first use. Its display name is the key until a
+    /// state file is loaded for it.
+    pub fn register(key: &str) -> Result<Self, String> {
+        let key = key.trim().to_lowercase();
+        if key.is_empty()
+            || !key
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        {
+            return Err(format!(
+                "invalid pool key {:?}: use lowercase letters, digits and _",
+                key
+            ));
+        }
+        if let Some(pool_id) = Self::from_str(&key) {
+            return Ok(pool_id);
+        }
+        let mut names = pool_names().write().unwrap_or_else(|e| e.into_inner());
+        let idx = u16::try_from(names.len()).map_err(|_| "too many pools".to_string())?;
+        let key: &'static str = Box::leak(key.into_boxed_str());
+        names.push(PoolName {
+            key,
+            display_name: key,
+            debug: false,
+        });
+        Ok(PoolId(idx))
+    }
+
+    /// Id of the debug pool for token `symbol` (`debug_<symbol>_usdc`,
+    /// shown as `SYMBOL/USDC`), registering it on first use. The first debug
+    /// pool of an environment uses [`PoolId::DebugUsdc`] instead.
+    pub fn register_debug(symbol: &str) -> Result<Self, String> {
+        let symbol = symbol.trim().to_uppercase();
+        if symbol.is_empty() || !symbol.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(format!(
+                "invalid debug token symbol {:?}: use letters and digits",
+                symbol
+            ));
+        }
+        let key = format!("debug_{}_usdc", symbol.to_lowercase());
+        if let Some(pool_id) = Self::from_str(&key) {
+            if !pool_id.is_debug() {
+                return Err(format!("pool {} is not a debug pool", key));
             }
+            return Ok(pool_id);
+        }
+        let mut names = pool_names().write().unwrap_or_else(|e| e.into_inner());
+        let idx = u16::try_from(names.len()).map_err(|_| "too many pools".to_string())?;
+        names.push(PoolName {
+            key: Box::leak(key.into_boxed_str()),
+            display_name: Box::leak(format!("{}/USDC", symbol).into_boxed_str()),
+            debug: true,
+        });
+        Ok(PoolId(idx))
+    }
+
+    /// Whether this is a debug pool, created in the VM with no state file
+    pub fn is_debug(&self) -> bool {
+        let names = pool_names().read().unwrap_or_else(|e| e.into_inner());
+        names[self.0 as usize].debug
+    }
+
+    /// Every pool traded from a state file: the built-in ones and any
+    /// registered from `POOLS_MANIFEST`, in registration order
+    pub fn all() -> Vec<PoolId> {
+        let count = pool_names().read().unwrap_or_else(|e| e.into_inner()).len();
+        (0..count as u16)
+            .map(PoolId)
+            .filter(|pool_id| !pool_id.is_debug())
+            .collect()
+    }
+
+    /// Checkpoint 240M state file (relative to the working directory) of a
+    /// built-in pool.
+    ///
+    /// The debug pool is created in the VM and has no state file; registered
+    /// pools name theirs in `POOLS_MANIFEST`.
+    pub fn state_file(&self) -> Option<&'static str> {
+        match *self {
+            PoolId::SuiUsdc => Some("./data/sui_usdc_state_cp240M.jsonl"),
+            PoolId::WalUsdc => Some("./data/wal_usdc_state_cp240M.jsonl"),
+            PoolId::DeepUsdc => Some("./data/deep_usdc_state_cp240M.jsonl"),
             _ => None,
         }
     }
+}
+
+impl std::fmt::Debug for PoolId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PoolId({})", self.as_str())
+    }
+}
+
+impl Serialize for PoolId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for PoolId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let key = String::deserialize(deserializer)?;
+        PoolId::from_str(&key)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown pool {:?}", key)))
+    }
+}
+
+/// Base and quote coin types of a `...::pool::Pool<Base, Quote>` type string.
+///
+/// Nested generics in either parameter are kept intact; returns `None` for
+/// any other type, including `PoolInner`.
+pub fn pool_type_params(type_str: &str) -> Option<(String, String)> {
+    let start = type_str.find("::pool::Pool<")? + "::pool::Pool<".len();
+    let params = type_str[start..].trim_end().strip_suffix('>')?;
+
+    let mut depth = 0usize;
+    let mut split = None;
+    for (idx, ch) in params.char_indices() {
+        match ch {
+            '<' => depth += 1,
+            '>' => depth = depth.checked_sub(1)?,
+            ',' if depth == 0 => {
+                if split.is_some() {
+                    return None;
+                }
+                split = Some(idx);
+            }
+            _ => {}
+        }
+    }
+    let idx = split?;
+    let (base, quote) = (params[..idx].trim(), params[idx + 1..].trim());
+    if depth != 0 || base.is_empty() || quote.is_empty() {
+        return None;
+    }
+    Some((base.to_string(), quote.to_string()))
+}
+
+/// One pool of a `POOLS_MANIFEST` file
+#[derive(Debug, Clone, Deserialize)]
+pub struct PoolManifestEntry {
+    /// Pool key (e.g. `sui_usdc`, or a new one such as `sui_deep`)
+    pub pool: String,
+    /// JSON/JSONL state export, relative to the working directory
+    pub state_file: String,
+    /// Expected `Pool<Base, Quote>` coin types of the state file
+    pub base_type: Option<String>,
+    pub quote_type: Option<String>,
+    /// Decimals of the loaded pool's coins: checked against the state file's
+    /// `CoinMetadata` when it has one, and used for coins it does not describe
+    pub base_decimals: Option<u8>,
+    pub quote_decimals: Option<u8>,
+}
+
+impl PoolManifestEntry {
+    fn builtin(pool_id: PoolId, state_file: &str) -> Self {
+        Self {
+            pool: pool_id.as_str().to_string(),
+            state_file: state_file.to_string(),
+            base_type: None,
+            quote_type: None,
+            base_decimals: None,
+            quote_decimals: None,
+        }
+    }
+
+    /// Check the entry's expected coin types and decimals against the state
+    /// that was loaded from its file, and supply the decimals of any coin
+    /// the file does not describe.
+    pub fn resolve_loaded(&self, loader: &mut StateLoader) -> Result<(), String> {
+        if self.base_type.is_some() || self.quote_type.is_some() {
+            let (base, quote) = loader
+                .pool_coin_types()
+                .ok_or("state file has no Pool<Base, Quote> object")?;
+            for (side, expected, loaded) in [
+                ("base_type", &self.base_type, &base),
+                ("quote_type", &self.quote_type, &quote),
+            ] {
+                if let Some(expected) = expected {
+                    if !same_coin_type(expected, loaded) {
+                        return Err(format!(
+                            "{} {} does not match the state file's {}",
+                            side, expected, loaded
+                        ));
+                    }
+                }
+            }
+        }
+        let config = loader.config().clone();
+        for (side, expected, coin_type, loaded) in [
+            (
+                "base_decimals",
+                self.base_decimals,
+                config.base_type,
+                config.base_decimals,
+            ),
+            (
+                "quote_decimals",
+                self.quote_decimals,
+                config.quote_type,
+                config.quote_decimals,
+            ),
+        ] {
+            let unresolved = loader
+                .unresolved_coin_types()
+                .iter()
+                .any(|t| same_coin_type(t, &coin_type));
+            match expected {
+                Some(expected) if unresolved => {
+                    units::register_coin_decimals(&coin_type, expected);
+                    loader.set_coin_decimals(&coin_type, expected);
+                }
+                Some(expected) if expected != loaded => {
+                    return Err(format!(
+                        "{} {} does not match the loaded pool's {}",
+                        side, expected, loaded
+                    ));
+                }
+                Some(_) => {}
+                None if unresolved => {
+                    return Err(format!(
+                        "the state file has no CoinMetadata for {}; set {} in the manifest",
+                        coin_type, side
+                    ));
+                }
+                None => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Coin types are equal up to the case and leading zeros of their address.
+fn same_coin_type(a: &str, b: &str) -> bool {
+    let normalize = |t: &str| {
+        let t = t.trim().to_lowercase();
+        match t.split_once("::") {
+            Some((addr, rest)) => {
+                let addr = addr.trim_start_matches("0x").trim_start_matches('0');
+                format!("0x{}::{}", addr, rest)
+            }
+            None => t,
+        }
+    };
+    normalize(a) == normalize(b)
+}
+
+#[derive(Debug, Deserialize)]
+struct PoolManifestFile {
+    pools: Vec<PoolManifestEntry>,
+}
+
+/// Pools loaded at startup, from `POOLS_MANIFEST` or the built-in list
+#[derive(Debug, Clone)]
+pub struct PoolManifest {
+    pub pools: Vec<(PoolId, PoolManifestEntry)>,
+}
+
+impl PoolManifest {
+    /// SUI/USDC, WAL/USDC and DEEP/USDC with their checkpoint 240M state files
+    pub fn builtin() -> Self {
+        let pools = PoolId::all()
+            .iter()
+            .filter_map(|pool_id| {
+                pool_id
+                    .state_file()
+                    .map(|path| (*pool_id, PoolManifestEntry::builtin(*pool_id, path)))
+            })
+            .collect();
+        Self { pools }
+    }
+
+    /// Parse a manifest of the form `{"pools": [{"pool": "sui_usdc",
+    /// "state_file": "./data/sui_usdc.jsonl", ...}]}`.
+    pub fn parse(json: &str) -> Result<Self, String> {
+        let file: PoolManifestFile = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        if file.pools.is_empty() {
+            return Err("manifest lists no pools".to_string());
+        }
+        let mut pools: Vec<(PoolId, PoolManifestEntry)> = Vec::with_capacity(file.pools.len());
+        for entry in file.pools {
+            let pool_id = PoolId::register(&entry.pool)?;
+            if pool_id.is_debug() {
+                return Err(format!(
+                    "pool {:?} is a debug pool, which is created in the VM",
+                    entry.pool
+                ));
+            }
+            if pools.iter().any(|(id, _)| *id == pool_id) {
+                return Err(format!("pool {} is listed twice", pool_id.as_str()));
+            }
+            if entry.state_file.trim().is_empty() {
+                return Err(format!("pool {} has an empty state_file", pool_id.as_str()));
+            }
+            pools.push((pool_id, entry));
+        }
+        Ok(Self { pools })
+    }
 
-    pub fn all() -> &'static [PoolId] {
-        &[PoolId::SuiUsdc, PoolId::WalUsdc, PoolId::DeepUsdc]
+    /// Manifest named by `POOLS_MANIFEST`, or [`PoolManifest::builtin`] when
+    /// it is unset. A manifest that cannot be read or parsed is an error.
+    pub fn from_env() -> Result<Self, String> {
+        let path = match std::env::var("POOLS_MANIFEST") {
+            Ok(path) if !path.trim().is_empty() => path,
+            _ => return Ok(Self::builtin()),
+        };
+        let json = std::fs::read_to_string(path.trim())
+            .map_err(|e| format!("cannot read {}: {}", path, e))?;
+        let manifest = Self::parse(&json).map_err(|e| format!("{}: {}", path, e))?;
+        tracing::info!("Pool manifest {}: {} pool(s)", path, manifest.pools.len());
+        Ok(manifest)
+    }
+
+    /// `(pool, state file)` for every listed pool, in manifest order
+    pub fn state_files(&self) -> Vec<(PoolId, String)> {
+        self.pools
+            .iter()
+            .map(|(pool_id, entry)| (*pool_id, entry.state_file.clone()))
+            .collect()
     }
 }
 
@@ -85,9 +456,13 @@ pub struct DeepBookConfig {
     pub asks_bigvector: String,
     /// Bids BigVector ID
     pub bids_bigvector: String,
-    /// Base token decimals
+    /// Base coin type; replaced by the loaded pool's `Pool<Base, Quote>` type when known
+    pub base_type: String,
+    /// Quote coin type; replaced by the loaded pool's `Pool<Base, Quote>` type when known
+    pub quote_type: String,
+    /// Base token decimals; replaced by the loaded pool's base type when known
     pub base_decimals: u8,
-    /// Quote token decimals (USDC = 6)
+    /// Quote token decimals; replaced by the loaded pool's quote type when known
     pub quote_decimals: u8,
     /// Registry ID (shared across all pools)
     pub registry: String,
@@ -108,8 +483,12 @@ impl DeepBookConfig {
                 .to_string(),
             bids_bigvector: "0x090a8eae3204c76e36eebf3440cbde577e062953391760c37c363530fc1de246"
                 .to_string(),
-            base_decimals: 9,  // SUI has 9 decimals
-            quote_decimals: 6, // USDC has 6 decimals
+            base_type: "0x2::sui::SUI".to_string(),
+            quote_type:
+                "0xdba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7::usdc::USDC"
+                    .to_string(),
+            base_decimals: units::SUI_DECIMALS,
+            quote_decimals: units::USDC_DECIMALS,
             registry: "0xaf16199a2dff736e9f07a845f23c5da6df6f756eddb631aed9d24a93efc4549d"
                 .to_string(),
             package: "0x2c8d603bc51326b8c13cef9dd07031a408a48dddb541963357661df5d3204809"
@@ -129,8 +508,14 @@ impl DeepBookConfig {
                 .to_string(),
             bids_bigvector: "0x82ee32196ab12750268815e005fae4c4db23a4272e52610c0c25a8288f05515a"
                 .to_string(),
-            base_decimals: 9,  // WAL has 9 decimals
-            quote_decimals: 6, // USDC has 6 decimals
+            base_type:
+                "0x356a26eb9e012a68958082340d4c4116e7f55615cf27affcff209cf0ae544f59::wal::WAL"
+                    .to_string(),
+            quote_type:
+                "0xdba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7::usdc::USDC"
+                    .to_string(),
+            base_decimals: units::WAL_DECIMALS,
+            quote_decimals: units::USDC_DECIMALS,
             registry: "0xaf16199a2dff736e9f07a845f23c5da6df6f756eddb631aed9d24a93efc4549d"
                 .to_string(),
             package: "0x2c8d603bc51326b8c13cef9dd07031a408a48dddb541963357661df5d3204809"
@@ -150,8 +535,14 @@ impl DeepBookConfig {
                 .to_string(),
             bids_bigvector: "0xd1fcd1d0a554150fa097508eabcd76f6dbb0d2ce4fdfeffb2f6a4469ac81fd42"
                 .to_string(),
-            base_decimals: 6,  // DEEP has 6 decimals
-            quote_decimals: 6, // USDC has 6 decimals
+            base_type:
+                "0xdeeb7a4662eec9f2f3def03fb937a663dddaa2e215b8078a284d026b7946c270::deep::DEEP"
+                    .to_string(),
+            quote_type:
+                "0xdba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7::usdc::USDC"
+                    .to_string(),
+            base_decimals: units::DEEP_DECIMALS,
+            quote_decimals: units::USDC_DECIMALS,
             registry: "0xaf16199a2dff736e9f07a845f23c5da6df6f756eddb631aed9d24a93efc4549d"
                 .to_string(),
             package: "0x2c8d603bc51326b8c13cef9dd07031a408a48dddb541963357661df5d3204809"
@@ -165,9 +556,37 @@ impl DeepBookConfig {
             PoolId::SuiUsdc => Self::sui_usdc(),
             PoolId::WalUsdc => Self::wal_usdc(),
             PoolId::DeepUsdc => Self::deep_usdc(),
-            PoolId::DebugUsdc => Self::sui_usdc(),
+            _ if pool_id.is_debug() => Self::sui_usdc(),
+            _ => Self::from_state_file(pool_id),
+        }
+    }
+
+    /// Config of a registered pool: object IDs, coin types and decimals are
+    /// all taken from its state file when it is loaded
+    pub fn from_state_file(pool_id: PoolId) -> Self {
+        Self {
+            pool_id,
+            pool_wrapper: String::new(),
+            pool_inner_uid: String::new(),
+            asks_bigvector: String::new(),
+            bids_bigvector: String::new(),
+            base_type: String::new(),
+            quote_type: String::new(),
+            base_decimals: units::DEFAULT_DECIMALS,
+            quote_decimals: units::DEFAULT_DECIMALS,
+            ..Self::sui_usdc()
         }
     }
+
+    /// Symbol of the base coin, e.g. `SUI`
+    pub fn base_symbol(&self) -> String {
+        units::coin_type_symbol(&self.base_type)
+    }
+
+    /// Symbol of the quote coin, e.g. `USDC`
+    pub fn quote_symbol(&self) -> String {
+        units::coin_type_symbol(&self.quote_type)
+    }
 }
 
 impl Default for DeepBookConfig {
@@ -181,8 +600,13 @@ pub struct StateLoader {
     config: DeepBookConfig,
     /// Cached objects indexed by object_id
     objects: HashMap<String, ExportedObject>,
+    /// Object IDs that appeared more than once in the loaded export
+    duplicate_ids: HashSet<String>,
     /// Whether state has been loaded
     loaded: bool,
+    /// Coin types of the loaded pool whose decimals neither the export nor
+    /// the known-coin table gives
+    unresolved_decimals: Vec<String>,
 }
 
 impl StateLoader {
@@ -191,7 +615,9 @@ impl StateLoader {
         Self {
             config: DeepBookConfig::default(),
             objects: HashMap::new(),
+            duplicate_ids: HashSet::new(),
             loaded: false,
+            unresolved_decimals: Vec::new(),
         }
     }
 
@@ -200,7 +626,9 @@ impl StateLoader {
         Self {
             config,
             objects: HashMap::new(),
+            duplicate_ids: HashSet::new(),
             loaded: false,
+            unresolved_decimals: Vec::new(),
         }
     }
 
@@ -226,10 +654,14 @@ impl StateLoader {
         let objects: Vec<ExportedObject> = serde_json::from_str(json)?;
 
         let count = objects.len();
+        self.duplicate_ids.clear();
+        let mut seen = HashSet::new();
         for obj in objects {
-            self.objects.insert(obj.object_id.clone(), obj);
+            self.insert_object(obj, &mut seen);
         }
 
+        self.log_duplicates();
+        self.apply_loaded_pool();
         self.loaded = true;
         Ok(count)
     }
@@ -240,27 +672,176 @@ impl StateLoader {
     /// with the highest version number (most recent state).
     pub fn load_from_jsonl(&mut self, jsonl: &str) -> Result<usize, Box<dyn std::error::Error>> {
         let mut count = 0;
+        self.duplicate_ids.clear();
+        let mut seen = HashSet::new();
         for line in jsonl.lines() {
             let line = line.trim();
             if line.is_empty() {
                 continue;
             }
             let obj: ExportedObject = serde_json::from_str(line)?;
+            self.insert_object(obj, &mut seen);
+            count += 1;
+        }
 
-            // Only insert if this is a newer version than what we have
-            let should_insert = match self.objects.get(&obj.object_id) {
-                Some(existing) => obj.version > existing.version,
-                None => true,
-            };
+        self.log_duplicates();
+        self.apply_loaded_pool();
+        self.loaded = true;
+        Ok(count)
+    }
 
-            if should_insert {
+    /// Insert an object, resolving duplicate IDs deterministically.
+    ///
+    /// The highest version wins; ties are broken by checkpoint, and a full tie
+    /// keeps the first occurrence so the result does not depend on row order.
+    /// Only ids repeated within the current load (`seen`) count as duplicates;
+    /// an object kept from an earlier load is just superseded.
+    fn insert_object(&mut self, obj: ExportedObject, seen: &mut HashSet<String>) {
+        if !seen.insert(obj.object_id.clone()) {
+            self.duplicate_ids.insert(obj.object_id.clone());
+        }
+        match self.objects.get(&obj.object_id) {
+            Some(existing) => {
+                if (obj.version, obj.checkpoint) > (existing.version, existing.checkpoint) {
+                    self.objects.insert(obj.object_id.clone(), obj);
+                }
+            }
+            None => {
                 self.objects.insert(obj.object_id.clone(), obj);
             }
-            count += 1;
         }
+    }
 
-        self.loaded = true;
-        Ok(count)
+    /// Take the config's coin types and decimals from the loaded pool, so a
+    /// state file for another pair (e.g. SUI/DEEP) is traded and scaled as
+    /// that pair.
+    ///
+    /// Decimals come from the coin's `CoinMetadata` in the export, else the
+    /// known-coin table; a coin neither describes is left in
+    /// [`StateLoader::unresolved_coin_types`] rather than guessed.
+    fn apply_loaded_pool(&mut self) {
+        if !self.objects.contains_key(&self.config.pool_wrapper) {
+            self.adopt_loaded_pool_ids();
+        }
+        self.unresolved_decimals.clear();
+        let Some((base_type, quote_type)) = self.pool_coin_types() else {
+            return;
+        };
+        for (coin_type, is_base) in [(&base_type, true), (&quote_type, false)] {
+            let decimals = match self.metadata_decimals(coin_type) {
+                Some(decimals) => {
+                    units::register_coin_decimals(coin_type, decimals);
+                    Some(decimals)
+                }
+                None => units::decimals_for_coin_type(coin_type),
+            };
+            match (decimals, is_base) {
+                (Some(decimals), true) => self.config.base_decimals = decimals,
+                (Some(decimals), false) => self.config.quote_decimals = decimals,
+                (None, _) => self.unresolved_decimals.push(coin_type.clone()),
+            }
+        }
+        self.config.base_type = base_type;
+        self.config.quote_type = quote_type;
+    }
+
+    /// Decimals of `coin_type` from a `CoinMetadata<T>` or `Currency<T>`
+    /// object in the export, if it holds one
+    fn metadata_decimals(&self, coin_type: &str) -> Option<u8> {
+        self.objects.values().find_map(|obj| {
+            let inner = ["::coin::CoinMetadata<", "::coin_registry::Currency<"]
+                .iter()
+                .find_map(|prefix| {
+                    let start = obj.object_type.find(prefix)? + prefix.len();
+                    obj.object_type[start..].trim_end().strip_suffix('>')
+                })?;
+            if !same_coin_type(inner, coin_type) {
+                return None;
+            }
+            let decimals = obj.object_json.get("decimals")?;
+            decimals
+                .as_u64()
+                .or_else(|| decimals.as_str()?.parse().ok())
+                .and_then(|decimals| u8::try_from(decimals).ok())
+        })
+    }
+
+    /// Coin types of the loaded pool with no known decimals; the config
+    /// still holds its defaults for them
+    pub fn unresolved_coin_types(&self) -> &[String] {
+        &self.unresolved_decimals
+    }
+
+    /// Decimals for a coin type the export did not describe
+    pub fn set_coin_decimals(&mut self, coin_type: &str, decimals: u8) {
+        if same_coin_type(&self.config.base_type, coin_type) {
+            self.config.base_decimals = decimals;
+        }
+        if same_coin_type(&self.config.quote_type, coin_type) {
+            self.config.quote_decimals = decimals;
+        }
+        self.unresolved_decimals
+            .retain(|unresolved| !same_coin_type(unresolved, coin_type));
+    }
+
+    /// Point the config at the state file's pool when it holds a different
+    /// one than configured: the only `Pool<Base, Quote>` object, its inner
+    /// UID, and the book's BigVectors from its `PoolInner`.
+    fn adopt_loaded_pool_ids(&mut self) {
+        let json_id = |value: &serde_json::Value, path: &[&str]| {
+            path.iter()
+                .try_fold(value, |value, key| value.get(key))
+                .and_then(serde_json::Value::as_str)
+                .map(str::to_string)
+        };
+
+        let mut pools = self
+            .objects
+            .values()
+            .filter(|obj| pool_type_params(&obj.object_type).is_some());
+        let (Some(pool), None) = (pools.next(), pools.next()) else {
+            return;
+        };
+        let Some(inner_uid) = json_id(&pool.object_json, &["inner", "id", "id"]) else {
+            return;
+        };
+        tracing::info!(
+            "{} state holds pool {} ({}); using it instead of {}",
+            self.config.pool_id.display_name(),
+            pool.object_id,
+            pool.object_type,
+            self.config.pool_wrapper
+        );
+        self.config.pool_wrapper = pool.object_id.clone();
+        self.config.pool_inner_uid = inner_uid;
+
+        let book = self
+            .get_pool_inner()
+            .map(|inner| {
+                (
+                    json_id(&inner.object_json, &["value", "book", "asks", "id", "id"]),
+                    json_id(&inner.object_json, &["value", "book", "bids", "id", "id"]),
+                )
+            })
+            .unwrap_or_default();
+        if let (Some(asks), Some(bids)) = book {
+            self.config.asks_bigvector = asks;
+            self.config.bids_bigvector = bids;
+        }
+    }
+
+    fn log_duplicates(&self) {
+        if self.duplicate_ids.is_empty() {
+            return;
+        }
+        let mut ids: Vec<&str> = self.duplicate_ids.iter().map(String::as_str).collect();
+        ids.sort_unstable();
+        tracing::warn!(
+            "{} state contains {} duplicate object ids (kept highest version): [{}]",
+            self.config.pool_id.display_name(),
+            ids.len(),
+            ids.join(", ")
+        );
     }
 
     /// Check if state has been loaded
@@ -273,6 +854,11 @@ impl StateLoader {
         self.objects.len()
     }
 
+    /// Get the number of object IDs that appeared more than once
+    pub fn duplicate_count(&self) -> usize {
+        self.duplicate_ids.len()
+    }
+
     /// Get an object by ID
     pub fn get_object(&self, object_id: &str) -> Option<&ExportedObject> {
         self.objects.get(object_id)
@@ -283,6 +869,12 @@ impl StateLoader {
         self.objects.get(&self.config.pool_wrapper)
     }
 
+    /// `(base, quote)` coin types parsed from the pool wrapper's type string
+    pub fn pool_coin_types(&self) -> Option<(String, String)> {
+        self.get_pool()
+            .and_then(|pool| pool_type_params(&pool.object_type))
+    }
+
     /// Get the PoolInner object
     pub fn get_pool_inner(&self) -> Option<&ExportedObject> {
         self.objects.values().find(|obj| {
@@ -338,6 +930,7 @@ impl StateLoader {
             bids_slices: bids_count,
             max_checkpoint,
             max_version,
+            duplicate_objects: self.duplicate_ids.len(),
         }
     }
 
@@ -374,11 +967,16 @@ pub struct StateStats {
     pub bids_slices: usize,
     pub max_checkpoint: u64,
     pub max_version: u64,
+    pub duplicate_objects: usize,
 }
 
 /// Registry managing multiple pool state loaders
 pub struct PoolRegistry {
     pools: HashMap<PoolId, StateLoader>,
+    /// File each pool was loaded from by `load_pool_from_file`
+    state_files: HashMap<PoolId, PathBuf>,
+    /// Pools the startup manifest lists, in manifest order
+    expected: Vec<PoolId>,
 }
 
 impl PoolRegistry {
@@ -386,6 +984,23 @@ impl PoolRegistry {
     pub fn new() -> Self {
         Self {
             pools: HashMap::new(),
+            state_files: HashMap::new(),
+            expected: Vec::new(),
+        }
+    }
+
+    /// Record the pools the startup manifest lists
+    pub fn set_expected_pools(&mut self, pools: Vec<PoolId>) {
+        self.expected = pools;
+    }
+
+    /// Pools the startup manifest lists, or every state-file pool when no
+    /// manifest was recorded
+    pub fn expected_pools(&self) -> Vec<PoolId> {
+        if self.expected.is_empty() {
+            PoolId::all()
+        } else {
+            self.expected.clone()
         }
     }
 
@@ -398,15 +1013,48 @@ impl PoolRegistry {
         let config = DeepBookConfig::for_pool(pool_id);
         let mut loader = StateLoader::with_config(config);
         let count = loader.load_from_file(path)?;
+        if loader.pool_coin_types().is_some() {
+            let config = loader.config();
+            pool_id.set_display_name(&format!(
+                "{}/{}",
+                config.base_symbol(),
+                config.quote_symbol()
+            ));
+        }
         self.pools.insert(pool_id, loader);
+        self.state_files.insert(pool_id, path.to_path_buf());
         Ok(count)
     }
 
+    /// State file a pool was loaded from at startup
+    pub fn state_file(&self, pool_id: PoolId) -> Option<&Path> {
+        self.state_files.get(&pool_id).map(PathBuf::as_path)
+    }
+
+    /// Replace a pool's state with an already loaded one
+    pub fn insert_pool(&mut self, pool_id: PoolId, loader: StateLoader) {
+        self.pools.insert(pool_id, loader);
+    }
+
     /// Get a loader for a specific pool
     pub fn get(&self, pool_id: PoolId) -> Option<&StateLoader> {
         self.pools.get(&pool_id)
     }
 
+    /// Get a mutable loader for a specific pool
+    pub fn get_mut(&mut self, pool_id: PoolId) -> Option<&mut StateLoader> {
+        self.pools.get_mut(&pool_id)
+    }
+
+    /// Config of a pool as loaded (coin types and decimals from its state
+    /// file), or [`DeepBookConfig::for_pool`] when it is not loaded
+    pub fn config(&self, pool_id: PoolId) -> DeepBookConfig {
+        self.pools
+            .get(&pool_id)
+            .map(|loader| loader.config().clone())
+            .unwrap_or_else(|| DeepBookConfig::for_pool(pool_id))
+    }
+
     /// Get all loaded pool IDs
     pub fn loaded_pools(&self) -> Vec<PoolId> {
         self.pools.keys().copied().collect()
@@ -506,6 +1154,224 @@ mod tests {
         assert!(loader.get_object("0x123").is_some());
     }
 
+    #[test]
+    fn test_duplicate_ids_keep_highest_version() {
+        let mut loader = StateLoader::new();
+        let jsonl = r#"{"object_id": "0x1", "type": "T", "version": 7, "object_json": {}, "checkpoint": 2}
+{"object_id": "0x1", "type": "T", "version": 5, "object_json": {}, "checkpoint": 1}
+{"object_id": "0x2", "type": "T", "version": 1, "object_json": {}, "checkpoint": 1}"#;
+
+        let result = loader.load_from_jsonl(jsonl);
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(loader.object_count(), 2);
+        assert_eq!(loader.get_object("0x1").unwrap().version, 7);
+        assert_eq!(loader.stats().duplicate_objects, 1);
+
+        // Reloading a clean export does not report the earlier duplicates
+        let reload = r#"{"object_id": "0x1", "type": "T", "version": 8, "object_json": {}, "checkpoint": 3}"#;
+        loader.load_from_jsonl(reload).unwrap();
+        assert_eq!(loader.duplicate_count(), 0);
+        assert_eq!(loader.get_object("0x1").unwrap().version, 8);
+    }
+
+    #[test]
+    fn test_pool_type_params() {
+        let (base, quote) =
+            pool_type_params("0x2c8d::pool::Pool<0x2::sui::SUI, 0xdeeb::deep::DEEP>").unwrap();
+        assert_eq!(base, "0x2::sui::SUI");
+        assert_eq!(quote, "0xdeeb::deep::DEEP");
+
+        let (base, quote) =
+            pool_type_params("0x1::pool::Pool<0x2::wrap::W<0x2::sui::SUI, u8>,0x3::usdc::USDC>")
+                .unwrap();
+        assert_eq!(base, "0x2::wrap::W<0x2::sui::SUI, u8>");
+        assert_eq!(quote, "0x3::usdc::USDC");
+
+        assert!(pool_type_params("0x1::pool::PoolInner<0x2::sui::SUI, 0x3::usdc::USDC>").is_none());
+        assert!(pool_type_params("0x1::pool::Pool<0x2::sui::SUI>").is_none());
+        assert!(pool_type_params("0x1::pool::Pool<A, B, C>").is_none());
+    }
+
+    #[test]
+    fn test_quote_decimals_follow_loaded_pool_type() {
+        let config = DeepBookConfig::sui_usdc();
+        let json = format!(
+            r#"[{{
+            "object_id": "{}",
+            "type": "0x2c8d::pool::Pool<0xdeeb::deep::DEEP, 0x2::sui::SUI>",
+            "version": 1,
+            "object_json": {{}},
+            "owner_type": "Shared",
+            "checkpoint": 1
+        }}]"#,
+            config.pool_wrapper
+        );
+        let mut loader = StateLoader::with_config(config);
+        loader.load_from_json(&json).unwrap();
+
+        let (_, quote) = loader.pool_coin_types().unwrap();
+        assert_eq!(quote, "0x2::sui::SUI");
+        assert_eq!(loader.config().base_decimals, units::DEEP_DECIMALS);
+        assert_eq!(loader.config().quote_decimals, units::SUI_DECIMALS);
+        assert_eq!(loader.config().base_symbol(), "DEEP");
+        assert_eq!(loader.config().quote_symbol(), "SUI");
+    }
+
+    #[test]
+    fn test_loader_adopts_the_state_files_pool() {
+        let json = r#"[{
+            "object_id": "0x51",
+            "type": "0x2c8d::pool::Pool<0x2::sui::SUI, 0xdeeb::deep::DEEP>",
+            "version": 1,
+            "object_json": {"id": {"id": "0x51"}, "inner": {"id": {"id": "0x52"}, "version": "1"}},
+            "owner_type": "Shared",
+            "checkpoint": 1
+        }, {
+            "object_id": "0x53",
+            "type": "0x2::dynamic_field::Field<u64, 0x2c8d::pool::PoolInner<0x2::sui::SUI, 0xdeeb::deep::DEEP>>",
+            "version": 1,
+            "object_json": {"value": {"book": {"asks": {"id": {"id": "0x54"}}, "bids": {"id": {"id": "0x55"}}}}},
+            "owner_address": "0x52",
+            "checkpoint": 1
+        }]"#;
+        let mut loader = StateLoader::with_config(DeepBookConfig::sui_usdc());
+        loader.load_from_json(json).unwrap();
+
+        let config = loader.config();
+        assert_eq!(config.pool_wrapper, "0x51");
+        assert_eq!(config.pool_inner_uid, "0x52");
+        assert_eq!(config.asks_bigvector, "0x54");
+        assert_eq!(config.bids_bigvector, "0x55");
+        assert_eq!(config.quote_type, "0xdeeb::deep::DEEP");
+        assert_eq!(config.quote_decimals, units::DEEP_DECIMALS);
+        assert!(loader.get_pool_inner().is_some());
+    }
+
+    #[test]
+    fn test_pool_manifest_parse() {
+        let manifest = PoolManifest::parse(
+            r#"{"pools": [
+                {"pool": "wal_usdc", "state_file": "./data/wal.jsonl"},
+                {"pool": "sui-usdc", "state_file": "./data/sui.jsonl", "base_decimals": 9}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            manifest.state_files(),
+            vec![
+                (PoolId::WalUsdc, "./data/wal.jsonl".to_string()),
+                (PoolId::SuiUsdc, "./data/sui.jsonl".to_string()),
+            ]
+        );
+        assert_eq!(manifest.pools[1].1.base_decimals, Some(9));
+
+        assert!(PoolManifest::parse(r#"{"pools": []}"#).is_err());
+        let manifest =
+            PoolManifest::parse(r#"{"pools": [{"pool": "eth_usdc", "state_file": "x"}]}"#).unwrap();
+        let eth_usdc = manifest.pools[0].0;
+        assert_eq!(eth_usdc.as_str(), "eth_usdc");
+        assert_eq!(PoolId::from_str("ETH-USDC"), Some(eth_usdc));
+        assert!(PoolId::all().contains(&eth_usdc));
+        assert!(
+            PoolManifest::parse(r#"{"pools": [{"pool": "eth/usdc", "state_file": "x"}]}"#).is_err()
+        );
+        assert!(
+            PoolManifest::parse(r#"{"pools": [{"pool": "debug_usdc", "state_file": "x"}]}"#)
+                .is_err()
+        );
+        assert!(PoolManifest::parse(
+            r#"{"pools": [{"pool": "sui_usdc", "state_file": "a"}, {"pool": "sui_usdc", "state_file": "b"}]}"#
+        )
+        .is_err());
+        assert_eq!(PoolManifest::builtin().pools.len(), 3);
+    }
+
+    #[test]
+    fn test_register_debug_pool() {
+        let foo = PoolId::register_debug("foo").unwrap();
+        assert_eq!(foo.as_str(), "debug_foo_usdc");
+        assert_eq!(foo.display_name(), "FOO/USDC");
+        assert_eq!(PoolId::register_debug("FOO"), Ok(foo));
+        assert!(foo.is_debug());
+        assert!(PoolId::DebugUsdc.is_debug());
+        assert!(!PoolId::all().contains(&foo));
+        assert!(PoolManifest::parse(
+            r#"{"pools": [{"pool": "debug_foo_usdc", "state_file": "x"}]}"#
+        )
+        .is_err());
+        assert!(PoolId::register_debug("").is_err());
+        assert!(PoolId::register_debug("FOO/USDC").is_err());
+    }
+
+    #[test]
+    fn test_pool_manifest_entry_checks_loaded_types() {
+        let config = DeepBookConfig::sui_usdc();
+        let json = format!(
+            r#"[{{
+            "object_id": "{}",
+            "type": "0x2c8d::pool::Pool<0x2::sui::SUI, 0xdba3::usdc::USDC>",
+            "version": 1,
+            "object_json": {{}},
+            "owner_type": "Shared",
+            "checkpoint": 1
+        }}]"#,
+            config.pool_wrapper
+        );
+        let mut loader = StateLoader::with_config(config);
+        loader.load_from_json(&json).unwrap();
+
+        let mut entry = PoolManifestEntry::builtin(PoolId::SuiUsdc, "./data/sui.jsonl");
+        entry.base_type = Some(
+            "0x0000000000000000000000000000000000000000000000000000000000000002::sui::SUI".into(),
+        );
+        entry.quote_decimals = Some(units::USDC_DECIMALS);
+        assert!(entry.resolve_loaded(&mut loader).is_ok());
+
+        entry.quote_type = Some("0x2::sui::SUI".into());
+        assert!(entry.resolve_loaded(&mut loader).is_err());
+        entry.quote_type = None;
+        entry.base_decimals = Some(6);
+        assert!(entry.resolve_loaded(&mut loader).is_err());
+    }
+
+    #[test]
+    fn test_manifest_pool_decimals_from_metadata_or_manifest() {
+        let json = r#"[{
+            "object_id": "0x61",
+            "type": "0x2c8d::pool::Pool<0xa11::alpha::ALPHA, 0xbe7::beta::BETA>",
+            "version": 1,
+            "object_json": {"id": {"id": "0x61"}, "inner": {"id": {"id": "0x62"}, "version": "1"}},
+            "owner_type": "Shared",
+            "checkpoint": 1
+        }, {
+            "object_id": "0x63",
+            "type": "0x2::coin::CoinMetadata<0x0a11::alpha::ALPHA>",
+            "version": 1,
+            "object_json": {"decimals": 7, "symbol": "ALPHA"},
+            "owner_type": "Immutable",
+            "checkpoint": 1
+        }]"#;
+        let pool_id = PoolId::register("alpha_beta").unwrap();
+        let mut loader = StateLoader::with_config(DeepBookConfig::for_pool(pool_id));
+        loader.load_from_json(json).unwrap();
+
+        assert_eq!(loader.config().pool_wrapper, "0x61");
+        assert_eq!(loader.config().base_decimals, 7);
+        assert_eq!(
+            units::decimals_for_coin_type("0xa11::alpha::ALPHA"),
+            Some(7)
+        );
+        assert_eq!(loader.unresolved_coin_types(), ["0xbe7::beta::BETA"]);
+
+        let mut entry = PoolManifestEntry::builtin(pool_id, "./data/alpha_beta.jsonl");
+        assert!(entry.resolve_loaded(&mut loader).is_err());
+        entry.quote_decimals = Some(3);
+        entry.resolve_loaded(&mut loader).unwrap();
+        assert_eq!(loader.config().quote_decimals, 3);
+        assert!(loader.unresolved_coin_types().is_empty());
+        assert_eq!(units::token_decimals("beta"), Some(3));
+    }
+
     #[test]
     fn test_default_config() {
         let config = DeepBookConfig::default();
@@ -7,10 +7,12 @@ use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, RwLock};
 
 use super::orderbook_builder::SandboxOrderbook;
 use super::state_loader::PoolId;
+use crate::units;
 
 // Sessions start unfunded; balances are added via VM faucet PTBs.
 const INITIAL_SUI: u64 = 0;
@@ -18,11 +20,38 @@ const INITIAL_USDC: u64 = 0;
 const INITIAL_DEEP: u64 = 0;
 const INITIAL_WAL: u64 = 0;
 
+/// Pool updates buffered per orderbook subscriber before it starts lagging
+const POOL_UPDATE_CAPACITY: usize = 256;
+
+/// Largest single faucet call when `FAUCET_MAX_PER_REQUEST` is unset, in whole tokens
+const DEFAULT_FAUCET_MAX_PER_REQUEST: f64 = 1_000_000.0;
+/// Per-session, per-token faucet total per window when `FAUCET_DAILY_CAP` is unset
+const DEFAULT_FAUCET_DAILY_CAP: f64 = 10_000_000.0;
+/// Window over which a session's faucet mints count towards the daily cap
+const FAUCET_CAP_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+/// How long a swap response is replayed for its idempotency key when
+/// `IDEMPOTENCY_TTL_SECS` is unset
+const DEFAULT_IDEMPOTENCY_TTL_SECS: u64 = 600;
+
+/// Pools whose orderbooks changed, published to orderbook stream subscribers
+#[derive(Debug, Clone)]
+pub struct PoolUpdate {
+    /// Session whose orderbooks changed; `None` for the global orderbooks
+    pub session_id: Option<String>,
+    pub pools: Vec<PoolId>,
+}
+
 /// Result of a swap execution
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SwapResult {
     pub success: bool,
     pub error: Option<String>,
+    /// When the swap was applied (unix ms); 0 in bundles exported before it was recorded
+    #[serde(default)]
+    pub timestamp_ms: u64,
+    /// "direct" or "two_hop"; empty in bundles exported before it was recorded
+    #[serde(default)]
+    pub route_type: String,
     pub input_token: String,
     pub output_token: String,
     pub input_amount: u64,
@@ -44,6 +73,9 @@ pub struct PtbExecution {
     pub created_objects: Vec<String>,
     pub mutated_objects: Vec<String>,
     pub deleted_objects: Vec<String>,
+    /// Per-command results from the executed PTB, when the router reported them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub command_effects: Vec<CommandEffect>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +88,19 @@ pub struct CommandInfo {
     pub type_args: Vec<String>,
 }
 
+/// Outcome of a single PTB command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandEffect {
+    pub index: usize,
+    pub success: bool,
+    /// Raw BCS return values, hex-encoded
+    pub return_values: Vec<String>,
+    /// Return values decoded where the layout is recognizable (u64, u128, Coin)
+    pub decoded_return_values: Vec<Option<serde_json::Value>>,
+    /// Objects this command took as mutable shared or owned inputs
+    pub mutated_objects: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EventInfo {
     pub event_type: String,
@@ -130,6 +175,29 @@ impl UserBalances {
     }
 }
 
+/// Amount of one token a session has drawn from the shared VM reserve coin
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct ReserveDraw {
+    /// Split off the reserve by faucet PTBs (including DEEP auto top-ups)
+    pub faucet: u64,
+    /// Swap inputs and DEEP fees that were not joined back into the reserve
+    pub swaps: u64,
+}
+
+impl ReserveDraw {
+    pub fn total(&self) -> u64 {
+        self.faucet.saturating_add(self.swaps)
+    }
+}
+
+/// Session state a swap changes, captured so a simulated swap can be undone
+#[derive(Debug, Clone)]
+pub struct SwapUndo {
+    balances: UserBalances,
+    history_len: usize,
+    reserve_draws: HashMap<String, ReserveDraw>,
+}
+
 /// A trading session with user state
 pub struct TradingSession {
     pub created_at: std::time::Instant,
@@ -138,20 +206,62 @@ pub struct TradingSession {
     pub checkpoint: u64,
     /// Per-session orderbook clones (modified by swaps)
     pub orderbooks: HashMap<PoolId, SandboxOrderbook>,
+    /// Cumulative reserve drawdown keyed by token symbol.
+    ///
+    /// Reserves are shared VM state, so this survives `reset`.
+    pub reserve_draws: HashMap<String, ReserveDraw>,
+    /// Balances the session started with, restored by `reset`
+    pub initial_balances: UserBalances,
 }
 
 impl TradingSession {
     /// Create a new trading session with cloned orderbooks
-    pub fn new(_session_id: String, orderbooks: HashMap<PoolId, SandboxOrderbook>) -> Result<Self> {
+    pub fn new(session_id: String, orderbooks: HashMap<PoolId, SandboxOrderbook>) -> Result<Self> {
+        Self::with_initial_balances(session_id, orderbooks, UserBalances::initial())
+    }
+
+    /// Create a session that starts (and resets) with `initial_balances`
+    pub fn with_initial_balances(
+        _session_id: String,
+        orderbooks: HashMap<PoolId, SandboxOrderbook>,
+        initial_balances: UserBalances,
+    ) -> Result<Self> {
         Ok(Self {
             created_at: std::time::Instant::now(),
-            balances: UserBalances::initial(),
+            balances: initial_balances.clone(),
             swap_history: Vec::new(),
             checkpoint: 240_000_000, // Default to checkpoint 240M
             orderbooks,
+            reserve_draws: HashMap::new(),
+            initial_balances,
         })
     }
 
+    /// Record `amount` of `token` split off the VM reserve by a faucet PTB.
+    pub fn record_faucet_draw(&mut self, token: &str, amount: u64) {
+        let draw = self.reserve_draws.entry(token.to_uppercase()).or_default();
+        draw.faucet = draw.faucet.saturating_add(amount);
+    }
+
+    /// Record `amount` of `token` a swap took from the VM reserve without returning it.
+    pub fn record_swap_draw(&mut self, token: &str, amount: u64) {
+        if amount == 0 {
+            return;
+        }
+        let draw = self.reserve_draws.entry(token.to_uppercase()).or_default();
+        draw.swaps = draw.swaps.saturating_add(amount);
+    }
+
+    /// Record `amount` of `token` joined back into the VM reserve, e.g. by an order cancel.
+    pub fn record_swap_return(&mut self, token: &str, amount: u64) {
+        if amount == 0 {
+            return;
+        }
+        if let Some(draw) = self.reserve_draws.get_mut(&token.to_uppercase()) {
+            draw.swaps = draw.swaps.saturating_sub(amount);
+        }
+    }
+
     /// Apply a VM-executed swap to session balances and record it in history.
     ///
     /// `input_amount` is the requested input size, while `input_refund` is the
@@ -160,6 +270,7 @@ impl TradingSession {
         &mut self,
         from_token: &str,
         to_token: &str,
+        route_type: &str,
         input_amount: u64,
         input_refund: u64,
         deep_input_amount: u64,
@@ -206,10 +317,17 @@ impl TradingSession {
         self.balances.subtract(from_token, consumed_input)?;
         self.balances.subtract("DEEP", consumed_deep)?;
         self.balances.add(to_token, output_amount);
+        self.record_swap_draw(from_token, consumed_input);
+        self.record_swap_draw("DEEP", consumed_deep);
 
         let result = SwapResult {
             success: true,
             error: None,
+            timestamp_ms: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            route_type: route_type.to_string(),
             input_token: from_token.to_string(),
             output_token: to_token.to_string(),
             input_amount,
@@ -225,34 +343,173 @@ impl TradingSession {
         Ok(result)
     }
 
-    /// Reset session to initial state with fresh orderbook clones
+    /// Capture what [`Self::apply_vm_swap`] and the reserve draw records change
+    pub fn swap_undo(&self) -> SwapUndo {
+        SwapUndo {
+            balances: self.balances.clone(),
+            history_len: self.swap_history.len(),
+            reserve_draws: self.reserve_draws.clone(),
+        }
+    }
+
+    /// Put balances and reserve draws back to `undo`, dropping swaps recorded since
+    pub fn undo_swap(&mut self, undo: SwapUndo) {
+        self.balances = undo.balances;
+        self.swap_history.truncate(undo.history_len);
+        self.reserve_draws = undo.reserve_draws;
+    }
+
+    /// Reset session to its initial balances with fresh orderbook clones
     pub fn reset(&mut self, fresh_orderbooks: HashMap<PoolId, SandboxOrderbook>) {
-        self.balances = UserBalances::initial();
+        self.balances = self.initial_balances.clone();
         self.swap_history.clear();
         self.orderbooks = fresh_orderbooks;
     }
 }
 
+/// Bounds on `POST /api/faucet`, in whole tokens of the requested coin
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FaucetLimits {
+    /// Largest single faucet call (`FAUCET_MAX_PER_REQUEST`)
+    pub max_per_request: f64,
+    /// Most one session may mint of one token per 24h (`FAUCET_DAILY_CAP`)
+    pub daily_cap: f64,
+}
+
+impl Default for FaucetLimits {
+    fn default() -> Self {
+        Self {
+            max_per_request: DEFAULT_FAUCET_MAX_PER_REQUEST,
+            daily_cap: DEFAULT_FAUCET_DAILY_CAP,
+        }
+    }
+}
+
+impl FaucetLimits {
+    /// Read `FAUCET_MAX_PER_REQUEST` and `FAUCET_DAILY_CAP` (whole tokens),
+    /// keeping the default for any value that is missing or not positive.
+    pub fn from_env() -> Self {
+        fn env_limit(key: &str, default: f64) -> f64 {
+            match std::env::var(key) {
+                Ok(raw) => match raw.trim().parse::<f64>() {
+                    Ok(limit) if limit > 0.0 && limit.is_finite() => limit,
+                    _ => {
+                        tracing::warn!("Ignoring invalid {} '{}'; using {}", key, raw, default);
+                        default
+                    }
+                },
+                Err(_) => default,
+            }
+        }
+        Self {
+            max_per_request: env_limit("FAUCET_MAX_PER_REQUEST", DEFAULT_FAUCET_MAX_PER_REQUEST),
+            daily_cap: env_limit("FAUCET_DAILY_CAP", DEFAULT_FAUCET_DAILY_CAP),
+        }
+    }
+}
+
+/// Faucet amount a session has minted of one token in the current window
+#[derive(Debug, Clone, Copy)]
+struct FaucetWindow {
+    started: Instant,
+    minted: u64,
+}
+
+/// Read `IDEMPOTENCY_TTL_SECS`, keeping the default when it is missing or not positive.
+fn idempotency_ttl_from_env() -> Duration {
+    let secs = match std::env::var("IDEMPOTENCY_TTL_SECS") {
+        Ok(raw) => match raw.trim().parse::<u64>() {
+            Ok(secs) if secs > 0 => secs,
+            _ => {
+                tracing::warn!(
+                    "Ignoring invalid IDEMPOTENCY_TTL_SECS '{}'; using {}",
+                    raw,
+                    DEFAULT_IDEMPOTENCY_TTL_SECS
+                );
+                DEFAULT_IDEMPOTENCY_TTL_SECS
+            }
+        },
+        Err(_) => DEFAULT_IDEMPOTENCY_TTL_SECS,
+    };
+    Duration::from_secs(secs)
+}
+
+/// A request seen under an idempotency key
+#[derive(Debug, Clone)]
+struct IdempotentEntry {
+    /// Identifies the request body, so a reused key for another swap is caught
+    fingerprint: String,
+    /// Serialized response; `None` while the first request is still executing
+    response: Option<serde_json::Value>,
+    expires_at: Instant,
+}
+
+/// Outcome of [`SessionManager::begin_idempotent`]
+#[derive(Debug)]
+pub enum IdempotencyCheck {
+    /// First request under this key: execute it, then call
+    /// [`SessionManager::finish_idempotent`]
+    Proceed,
+    /// Same request seen before; return this response without executing
+    Replay(serde_json::Value),
+}
+
+/// Why a request cannot use its idempotency key
+#[derive(Debug, thiserror::Error)]
+pub enum IdempotencyConflict {
+    #[error("Idempotency key '{0}' was already used for a different request in this session")]
+    KeyReused(String),
+    #[error("A request with idempotency key '{0}' is still executing")]
+    InProgress(String),
+}
+
 /// Session store for managing multiple trading sessions
 pub struct SessionManager {
     sessions: RwLock<HashMap<String, Arc<RwLock<TradingSession>>>>,
     /// Global orderbooks cloned into each new session
     global_orderbooks: RwLock<HashMap<PoolId, SandboxOrderbook>>,
+    pool_updates: broadcast::Sender<PoolUpdate>,
+    faucet_limits: FaucetLimits,
+    /// `(session id, token symbol)` -> faucet mints in the current window
+    faucet_usage: RwLock<HashMap<(String, String), FaucetWindow>>,
+    /// `(session id, idempotency key)` -> request fingerprint and cached response
+    idempotent_responses: RwLock<HashMap<(String, String), IdempotentEntry>>,
+    /// How long an idempotency key is remembered (`IDEMPOTENCY_TTL_SECS`)
+    idempotency_ttl: Duration,
 }
 
 impl SessionManager {
     pub fn new(global_orderbooks: HashMap<PoolId, SandboxOrderbook>) -> Self {
+        let (pool_updates, _) = broadcast::channel(POOL_UPDATE_CAPACITY);
         Self {
             sessions: RwLock::new(HashMap::new()),
             global_orderbooks: RwLock::new(global_orderbooks),
+            pool_updates,
+            faucet_limits: FaucetLimits::from_env(),
+            faucet_usage: RwLock::new(HashMap::new()),
+            idempotent_responses: RwLock::new(HashMap::new()),
+            idempotency_ttl: idempotency_ttl_from_env(),
         }
     }
 
     /// Create a new session with cloned orderbooks
     pub async fn create_session(&self) -> Result<String> {
+        self.create_session_with_balances(UserBalances::initial())
+            .await
+    }
+
+    /// Create a new session with cloned orderbooks that starts with `initial_balances`
+    pub async fn create_session_with_balances(
+        &self,
+        initial_balances: UserBalances,
+    ) -> Result<String> {
         let session_id = uuid::Uuid::new_v4().to_string();
         let orderbooks = self.global_orderbooks.read().await.clone();
-        let session = TradingSession::new(session_id.clone(), orderbooks)?;
+        let session = TradingSession::with_initial_balances(
+            session_id.clone(),
+            orderbooks,
+            initial_balances,
+        )?;
 
         let mut sessions = self.sessions.write().await;
         sessions.insert(session_id.clone(), Arc::new(RwLock::new(session)));
@@ -260,9 +517,261 @@ impl SessionManager {
         Ok(session_id)
     }
 
+    /// Replace the orderbooks cloned into sessions created from now on
+    pub async fn set_global_orderbooks(&self, orderbooks: HashMap<PoolId, SandboxOrderbook>) {
+        *self.global_orderbooks.write().await = orderbooks;
+    }
+
     /// Get a session by ID
     pub async fn get_session(&self, session_id: &str) -> Option<Arc<RwLock<TradingSession>>> {
         let sessions = self.sessions.read().await;
         sessions.get(session_id).cloned()
     }
+
+    pub fn faucet_limits(&self) -> FaucetLimits {
+        self.faucet_limits
+    }
+
+    /// Count `amount` of `token` (smallest units, `decimals` places) against
+    /// the session's faucet limits before it is minted.
+    ///
+    /// Fails without recording anything when the call exceeds the per-request
+    /// maximum or would take the session past the daily cap. Call
+    /// [`Self::release_faucet_allowance`] if the mint then fails.
+    pub async fn reserve_faucet_allowance(
+        &self,
+        session_id: &str,
+        token: &str,
+        amount: u64,
+        decimals: u8,
+    ) -> Result<()> {
+        let to_raw = |limit: f64| units::from_human(limit, decimals).unwrap_or(u64::MAX);
+        let max_per_request = to_raw(self.faucet_limits.max_per_request);
+        if amount > max_per_request {
+            return Err(anyhow!(
+                "{} faucet request of {} exceeds the per-request maximum of {} ({} {})",
+                token,
+                amount,
+                max_per_request,
+                self.faucet_limits.max_per_request,
+                token
+            ));
+        }
+
+        let daily_cap = to_raw(self.faucet_limits.daily_cap);
+        let now = Instant::now();
+        let mut usage = self.faucet_usage.write().await;
+        let window = usage
+            .entry((session_id.to_string(), token.to_uppercase()))
+            .or_insert(FaucetWindow {
+                started: now,
+                minted: 0,
+            });
+        if now.duration_since(window.started) >= FAUCET_CAP_WINDOW {
+            *window = FaucetWindow {
+                started: now,
+                minted: 0,
+            };
+        }
+        let total = window.minted.saturating_add(amount);
+        if total > daily_cap {
+            let resets_in = FAUCET_CAP_WINDOW.saturating_sub(now.duration_since(window.started));
+            return Err(anyhow!(
+                "{} faucet daily cap of {} ({} {}) reached: {} already minted, {} requested; resets in {}s",
+                token,
+                daily_cap,
+                self.faucet_limits.daily_cap,
+                token,
+                window.minted,
+                amount,
+                resets_in.as_secs().max(1)
+            ));
+        }
+        window.minted = total;
+        Ok(())
+    }
+
+    /// Return an allowance taken by [`Self::reserve_faucet_allowance`] whose mint failed.
+    pub async fn release_faucet_allowance(&self, session_id: &str, token: &str, amount: u64) {
+        let mut usage = self.faucet_usage.write().await;
+        if let Some(window) = usage.get_mut(&(session_id.to_string(), token.to_uppercase())) {
+            window.minted = window.minted.saturating_sub(amount);
+        }
+    }
+
+    /// Claim `key` for a request identified by `fingerprint`, or find its
+    /// cached response.
+    ///
+    /// Expired keys are dropped first. A key reused with another fingerprint,
+    /// or claimed by a request that has not finished, is a conflict.
+    pub async fn begin_idempotent(
+        &self,
+        session_id: &str,
+        key: &str,
+        fingerprint: &str,
+    ) -> std::result::Result<IdempotencyCheck, IdempotencyConflict> {
+        let now = Instant::now();
+        let mut entries = self.idempotent_responses.write().await;
+        entries.retain(|_, entry| entry.expires_at > now);
+
+        let slot = (session_id.to_string(), key.to_string());
+        match entries.get(&slot) {
+            Some(entry) if entry.fingerprint != fingerprint => {
+                Err(IdempotencyConflict::KeyReused(key.to_string()))
+            }
+            Some(IdempotentEntry {
+                response: Some(response),
+                ..
+            }) => Ok(IdempotencyCheck::Replay(response.clone())),
+            Some(_) => Err(IdempotencyConflict::InProgress(key.to_string())),
+            None => {
+                entries.insert(
+                    slot,
+                    IdempotentEntry {
+                        fingerprint: fingerprint.to_string(),
+                        response: None,
+                        expires_at: now + self.idempotency_ttl,
+                    },
+                );
+                Ok(IdempotencyCheck::Proceed)
+            }
+        }
+    }
+
+    /// Store the response for a key claimed by [`Self::begin_idempotent`];
+    /// `None` releases the key so the request can be retried.
+    pub async fn finish_idempotent(
+        &self,
+        session_id: &str,
+        key: &str,
+        response: Option<serde_json::Value>,
+    ) {
+        let slot = (session_id.to_string(), key.to_string());
+        let mut entries = self.idempotent_responses.write().await;
+        match response {
+            Some(response) => {
+                if let Some(entry) = entries.get_mut(&slot) {
+                    entry.response = Some(response);
+                    entry.expires_at = Instant::now() + self.idempotency_ttl;
+                }
+            }
+            None => {
+                entries.remove(&slot);
+            }
+        }
+    }
+
+    /// Receive every [`PoolUpdate`] published from now on
+    pub fn subscribe_pool_updates(&self) -> broadcast::Receiver<PoolUpdate> {
+        self.pool_updates.subscribe()
+    }
+
+    /// Tell orderbook subscribers that `pools` changed; a no-op without subscribers.
+    pub fn notify_pool_update(&self, session_id: Option<&str>, pools: &[PoolId]) {
+        let _ = self.pool_updates.send(PoolUpdate {
+            session_id: session_id.map(str::to_string),
+            pools: pools.to_vec(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SESSION: &str = "session-a";
+
+    fn manager(idempotency_ttl: Duration) -> SessionManager {
+        SessionManager {
+            idempotency_ttl,
+            ..SessionManager::new(HashMap::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_replays_the_cached_response() {
+        let manager = manager(Duration::from_secs(60));
+        assert!(matches!(
+            manager.begin_idempotent(SESSION, "k1", "swap:a").await,
+            Ok(IdempotencyCheck::Proceed)
+        ));
+        manager
+            .finish_idempotent(SESSION, "k1", Some(json!({ "output_amount": "42" })))
+            .await;
+
+        match manager.begin_idempotent(SESSION, "k1", "swap:a").await {
+            Ok(IdempotencyCheck::Replay(response)) => {
+                assert_eq!(response, json!({ "output_amount": "42" }))
+            }
+            other => panic!("expected a replay, got {:?}", other),
+        }
+        // Keys are scoped to their session
+        assert!(matches!(
+            manager.begin_idempotent("session-b", "k1", "swap:a").await,
+            Ok(IdempotencyCheck::Proceed)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_conflicts_on_reuse_and_in_flight_requests() {
+        let manager = manager(Duration::from_secs(60));
+        manager
+            .begin_idempotent(SESSION, "k1", "swap:a")
+            .await
+            .unwrap();
+        assert!(matches!(
+            manager.begin_idempotent(SESSION, "k1", "swap:a").await,
+            Err(IdempotencyConflict::InProgress(_))
+        ));
+        assert!(matches!(
+            manager.begin_idempotent(SESSION, "k1", "swap:b").await,
+            Err(IdempotencyConflict::KeyReused(_))
+        ));
+
+        manager
+            .finish_idempotent(SESSION, "k1", Some(json!({})))
+            .await;
+        assert!(matches!(
+            manager.begin_idempotent(SESSION, "k1", "swap:b").await,
+            Err(IdempotencyConflict::KeyReused(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_failed_request_releases_its_key() {
+        let manager = manager(Duration::from_secs(60));
+        manager
+            .begin_idempotent(SESSION, "k1", "swap:a")
+            .await
+            .unwrap();
+        manager.finish_idempotent(SESSION, "k1", None).await;
+        assert!(matches!(
+            manager.begin_idempotent(SESSION, "k1", "swap:b").await,
+            Ok(IdempotencyCheck::Proceed)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_keys_expire_and_are_cleaned_up() {
+        let manager = manager(Duration::from_millis(20));
+        for key in ["k1", "k2"] {
+            manager
+                .begin_idempotent(SESSION, key, "swap:a")
+                .await
+                .unwrap();
+            manager
+                .finish_idempotent(SESSION, key, Some(json!({})))
+                .await;
+        }
+        tokio::time::sleep(Duration::from_millis(40)).await;
+
+        assert!(matches!(
+            manager.begin_idempotent(SESSION, "k1", "swap:b").await,
+            Ok(IdempotencyCheck::Proceed)
+        ));
+        let entries = manager.idempotent_responses.read().await;
+        assert_eq!(entries.len(), 1, "expired k2 is dropped on the next lookup");
+        assert!(entries.contains_key(&(SESSION.to_string(), "k1".to_string())));
+    }
 }
@@ -19,27 +19,68 @@ pub enum ApiError {
     #[error("Not found: {0}")]
     NotFound(String),
 
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
     #[error("Internal error: {0}")]
     Internal(String),
+
+    #[error("Service unavailable: {0}")]
+    ServiceUnavailable(String),
+
+    #[error("Router timed out: {0}")]
+    RouterTimeout(String),
+
+    /// A known DeepBook abort; answered with 400 plus a stable `deepbook_code`
+    #[error("Bad request: {message}")]
+    DeepBook {
+        deepbook_code: &'static str,
+        message: String,
+    },
+}
+
+impl ApiError {
+    /// The `DeepBookAbort` code behind this error, if any
+    pub fn deepbook_code(&self) -> Option<&'static str> {
+        match self {
+            ApiError::DeepBook { deepbook_code, .. } => Some(deepbook_code),
+            _ => None,
+        }
+    }
+
+    /// Response status and the `code` field of the error body
+    pub fn status_and_code(&self) -> (StatusCode, &'static str) {
+        match self {
+            ApiError::BadRequest(_) | ApiError::DeepBook { .. } => {
+                (StatusCode::BAD_REQUEST, "BAD_REQUEST")
+            }
+            ApiError::NotFound(_) => (StatusCode::NOT_FOUND, "NOT_FOUND"),
+            ApiError::Conflict(_) => (StatusCode::CONFLICT, "CONFLICT"),
+            ApiError::Internal(_) => (StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR"),
+            ApiError::ServiceUnavailable(_) => {
+                (StatusCode::SERVICE_UNAVAILABLE, "SERVICE_UNAVAILABLE")
+            }
+            ApiError::RouterTimeout(_) => (StatusCode::GATEWAY_TIMEOUT, "ROUTER_TIMEOUT"),
+        }
+    }
 }
 
 #[derive(Serialize)]
 struct ErrorResponse {
     error: String,
     code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deepbook_code: Option<&'static str>,
 }
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
-        let (status, code) = match &self {
-            ApiError::BadRequest(_) => (StatusCode::BAD_REQUEST, "BAD_REQUEST"),
-            ApiError::NotFound(_) => (StatusCode::NOT_FOUND, "NOT_FOUND"),
-            ApiError::Internal(_) => (StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR"),
-        };
+        let (status, code) = self.status_and_code();
 
         let body = Json(ErrorResponse {
             error: self.to_string(),
             code: code.to_string(),
+            deepbook_code: self.deepbook_code(),
         });
 
         (status, body).into_response()
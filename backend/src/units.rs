@@ -0,0 +1,362 @@
+//! Token amount and price unit conversions
+//!
+//! Single source of truth for token decimals and the raw <-> human scaling
+//! used across the API and sandbox layers.
+
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+pub const SUI_DECIMALS: u8 = 9;
+pub const WAL_DECIMALS: u8 = 9;
+pub const DEEP_DECIMALS: u8 = 6;
+pub const USDC_DECIMALS: u8 = 6;
+/// Decimals of the sandbox debug token
+pub const DEBUG_TOKEN_DECIMALS: u8 = 9;
+/// Fallback for request tokens that no loaded pool trades
+pub const DEFAULT_DECIMALS: u8 = 9;
+
+/// DeepBook V3 normalizes prices as if every base token had this many decimals
+const DEEPBOOK_PRICE_BASE_DECIMALS: i32 = 9;
+
+/// Decimals of coins outside the built-in table, learned from loaded pool
+/// state (`CoinMetadata`) or `POOLS_MANIFEST`, keyed by normalized coin type
+fn registered_decimals() -> &'static RwLock<HashMap<String, u8>> {
+    static REGISTERED: OnceLock<RwLock<HashMap<String, u8>>> = OnceLock::new();
+    REGISTERED.get_or_init(Default::default)
+}
+
+/// Coin type with its address lowercased and stripped of leading zeros
+fn normalize_coin_type(coin_type: &str) -> String {
+    let coin_type = coin_type.trim();
+    match coin_type.split_once("::") {
+        Some((addr, rest)) => {
+            let addr = addr.to_lowercase();
+            let addr = addr.trim_start_matches("0x").trim_start_matches('0');
+            format!("0x{}::{}", addr, rest)
+        }
+        None => coin_type.to_string(),
+    }
+}
+
+/// Record the decimals of `coin_type`, so its symbol and type resolve in
+/// [`token_decimals`] and [`decimals_for_coin_type`]
+pub fn register_coin_decimals(coin_type: &str, decimals: u8) {
+    registered_decimals()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(normalize_coin_type(coin_type), decimals);
+}
+
+/// Decimals for a known token symbol (case-insensitive): a built-in coin or
+/// one registered with [`register_coin_decimals`]
+pub fn token_decimals(symbol: &str) -> Option<u8> {
+    let symbol = symbol.to_uppercase();
+    match symbol.as_str() {
+        "SUI" => Some(SUI_DECIMALS),
+        "WAL" => Some(WAL_DECIMALS),
+        "DEEP" => Some(DEEP_DECIMALS),
+        "USDC" => Some(USDC_DECIMALS),
+        _ => registered_decimals()
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .find(|(coin_type, _)| coin_type_symbol(coin_type) == symbol)
+            .map(|(_, decimals)| *decimals),
+    }
+}
+
+/// Decimals for a fully-qualified coin type such as `0x2::sui::SUI`.
+///
+/// Registered coin types win; otherwise matches on the struct name, so any
+/// package's `...::usdc::USDC` resolves to USDC decimals. `None` for a coin
+/// nothing has told us about, rather than a guess.
+pub fn decimals_for_coin_type(coin_type: &str) -> Option<u8> {
+    let registered = registered_decimals()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&normalize_coin_type(coin_type))
+        .copied();
+    if registered.is_some() {
+        return registered;
+    }
+    let name = coin_type.trim().rsplit("::").next()?;
+    match name {
+        "DEBUG_TOKEN" => Some(DEBUG_TOKEN_DECIMALS),
+        "SUI" | "WAL" | "DEEP" | "USDC" => token_decimals(name),
+        _ => None,
+    }
+}
+
+/// Token symbol of a fully-qualified coin type: its struct name, such as
+/// `SUI` for `0x2::sui::SUI`
+pub fn coin_type_symbol(coin_type: &str) -> String {
+    let path = coin_type.trim().split('<').next().unwrap_or_default();
+    path.rsplit("::").next().unwrap_or(path).to_uppercase()
+}
+
+/// Convert a raw on-chain amount to a human-readable value
+pub fn to_human(amount: u64, decimals: u8) -> f64 {
+    amount as f64 / 10f64.powi(decimals as i32)
+}
+
+/// Convert a human-readable value to a raw on-chain amount.
+///
+/// Truncates sub-unit dust. Returns None for negative, non-finite or
+/// out-of-range values.
+pub fn from_human(amount: f64, decimals: u8) -> Option<u64> {
+    if !amount.is_finite() || amount < 0.0 {
+        return None;
+    }
+    let raw = (amount * 10f64.powi(decimals as i32)).trunc();
+    if raw > u64::MAX as f64 {
+        return None;
+    }
+    Some(raw as u64)
+}
+
+/// Parse a request amount into raw units.
+///
+/// An integer (`"1500"`) is already raw; a decimal (`"1.5"`) is in whole
+/// tokens and scaled by `decimals` exactly, without going through f64.
+/// Underscores are ignored as digit separators (`"1_000"`). Rejects signs,
+/// more fractional digits than the token has, and values above `u64::MAX`.
+pub fn parse_amount(input: &str, decimals: u8) -> Result<u64, String> {
+    let trimmed = input.trim();
+    if trimmed.starts_with('-') {
+        return Err(format!("amount must not be negative (got {:?})", input));
+    }
+    let digits: String = trimmed.chars().filter(|c| *c != '_').collect();
+    let (whole, fraction) = match digits.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (digits.as_str(), None),
+    };
+    let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+    if (whole.is_empty() && fraction.map_or(true, str::is_empty))
+        || !is_digits(whole)
+        || !fraction.map_or(true, is_digits)
+    {
+        return Err(format!(
+            "Invalid amount {:?}: expected raw units (\"1500000000\") or a decimal token amount (\"1.5\")",
+            input
+        ));
+    }
+    let overflow = || {
+        format!(
+            "amount {:?} exceeds the maximum of {} raw units",
+            input,
+            u64::MAX
+        )
+    };
+
+    let Some(fraction) = fraction else {
+        return whole.parse::<u64>().map_err(|_| overflow());
+    };
+    if fraction.len() > decimals as usize {
+        return Err(format!(
+            "amount {:?} has more than {} decimal places",
+            input, decimals
+        ));
+    }
+    let scale = 10u64.checked_pow(decimals as u32).ok_or_else(overflow)?;
+    let whole_raw = match whole {
+        "" => 0,
+        whole => whole.parse::<u64>().map_err(|_| overflow())?,
+    };
+    let fraction_raw = match fraction {
+        "" => 0,
+        fraction => {
+            let padding = 10u64.pow((decimals as usize - fraction.len()) as u32);
+            fraction.parse::<u64>().map_err(|_| overflow())? * padding
+        }
+    };
+    whole_raw
+        .checked_mul(scale)
+        .and_then(|raw| raw.checked_add(fraction_raw))
+        .ok_or_else(overflow)
+}
+
+/// Divisor converting a raw DeepBook price to quote-per-base
+pub fn price_divisor(base_decimals: u8, quote_decimals: u8) -> f64 {
+    let normalization = 10f64.powi(DEEPBOOK_PRICE_BASE_DECIMALS - base_decimals as i32);
+    10f64.powi(quote_decimals as i32) * normalization
+}
+
+/// Convert a raw DeepBook price to a human quote-per-base price
+pub fn scale_price(raw_price: u64, base_decimals: u8, quote_decimals: u8) -> f64 {
+    raw_price as f64 / price_divisor(base_decimals, quote_decimals)
+}
+
+/// Raw DeepBook price of a fill: `quote_amount * 1e9 / base_amount` in raw
+/// units, the value tick sizes divide. `None` for an empty fill.
+pub fn raw_fill_price(base_amount: u64, quote_amount: u64) -> Option<u64> {
+    if base_amount == 0 {
+        return None;
+    }
+    let scaled = quote_amount as u128 * 10u128.pow(DEEPBOOK_PRICE_BASE_DECIMALS as u32);
+    u64::try_from(scaled / base_amount as u128).ok()
+}
+
+/// Snap a raw price onto the `tick_size` grid: down for sells and up for
+/// buys, so a limit order at the result crosses at least the same levels.
+pub fn round_to_tick(raw_price: u64, tick_size: u64, is_sell: bool) -> u64 {
+    if tick_size == 0 {
+        return raw_price;
+    }
+    let floor = raw_price - raw_price % tick_size;
+    if is_sell || floor == raw_price {
+        floor
+    } else {
+        floor.saturating_add(tick_size)
+    }
+}
+
+/// Basis points in 100%
+pub const BPS_DENOMINATOR: u32 = 10_000;
+
+/// Minimum acceptable output for `quoted` after allowing `slippage_bps` of slippage.
+///
+/// Rounds down so the bound never exceeds what the tolerance permits.
+/// Tolerances above 100% clamp to a zero minimum.
+pub fn min_out_with_slippage(quoted: u64, slippage_bps: u32) -> u64 {
+    let keep_bps = BPS_DENOMINATOR.saturating_sub(slippage_bps) as u128;
+    (quoted as u128 * keep_bps / BPS_DENOMINATOR as u128) as u64
+}
+
+/// Price impact of a fill at `effective` against `mid`, in basis points.
+///
+/// Both prices are quote per base (output per input for multi-hop routes,
+/// which pass `is_sell = true`). The formula is the same for exact-in and
+/// exact-out fills, since an exact-out fill's price is still consumed input
+/// over received output. A sell is hurt by a lower price and a buy by a
+/// higher one; a fill better than mid is price improvement and reports 0, as
+/// does a missing (non-positive) mid.
+pub fn price_impact_bps(mid: f64, effective: f64, is_sell: bool) -> u32 {
+    if mid <= 0.0 || !mid.is_finite() || !effective.is_finite() {
+        return 0;
+    }
+    let adverse = if is_sell {
+        mid - effective
+    } else {
+        effective - mid
+    };
+    if adverse <= 0.0 {
+        return 0;
+    }
+    (adverse / mid * BPS_DENOMINATOR as f64) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_human_round_trip() {
+        assert_eq!(to_human(1_500_000, USDC_DECIMALS), 1.5);
+        assert_eq!(from_human(1.5, USDC_DECIMALS), Some(1_500_000));
+        assert_eq!(from_human(-1.0, SUI_DECIMALS), None);
+        assert_eq!(from_human(f64::NAN, SUI_DECIMALS), None);
+    }
+
+    #[test]
+    fn test_parse_amount_scales_decimals_and_ignores_underscores() {
+        assert_eq!(parse_amount("1.5", SUI_DECIMALS), Ok(1_500_000_000));
+        assert_eq!(parse_amount("0.000001", USDC_DECIMALS), Ok(1));
+        assert_eq!(parse_amount(".5", USDC_DECIMALS), Ok(500_000));
+        assert_eq!(parse_amount("2.", USDC_DECIMALS), Ok(2_000_000));
+        // Integers stay raw units
+        assert_eq!(parse_amount("1_000", SUI_DECIMALS), Ok(1_000));
+        assert_eq!(parse_amount("1_000.25", USDC_DECIMALS), Ok(1_000_250_000));
+    }
+
+    #[test]
+    fn test_parse_amount_rejects_invalid_values() {
+        let overflow = parse_amount("18446744073709551616", SUI_DECIMALS).unwrap_err();
+        assert!(overflow.contains("exceeds"), "{}", overflow);
+        let overflow = parse_amount("18446744074.0", SUI_DECIMALS).unwrap_err();
+        assert!(overflow.contains("exceeds"), "{}", overflow);
+        assert_eq!(
+            parse_amount("18446744073.709551615", SUI_DECIMALS),
+            Ok(u64::MAX)
+        );
+        let negative = parse_amount("-1.5", SUI_DECIMALS).unwrap_err();
+        assert!(negative.contains("negative"), "{}", negative);
+        let precision = parse_amount("0.0000001", USDC_DECIMALS).unwrap_err();
+        assert!(precision.contains("6 decimal places"), "{}", precision);
+        for bad in ["", ".", "1.2.3", "1e9", "+1", "abc"] {
+            assert!(parse_amount(bad, SUI_DECIMALS).is_err(), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn test_scale_price_normalizes_base_decimals() {
+        // 9-decimal base: raw price is quote units per whole base token
+        assert_eq!(scale_price(3_500_000, SUI_DECIMALS, USDC_DECIMALS), 3.5);
+        // 6-decimal base is normalized as if it had 9 decimals
+        assert_eq!(scale_price(50_000_000, DEEP_DECIMALS, USDC_DECIMALS), 0.05);
+        assert_eq!(token_decimals("usdc"), Some(USDC_DECIMALS));
+    }
+
+    #[test]
+    fn test_decimals_for_coin_type_uses_struct_name() {
+        assert_eq!(decimals_for_coin_type("0x2::sui::SUI"), Some(SUI_DECIMALS));
+        assert_eq!(
+            decimals_for_coin_type(
+                "0xdeeb7a4662eec9f2f3def03fb937a663dddaa2e215b8078a284d026b7946c270::deep::DEEP"
+            ),
+            Some(DEEP_DECIMALS)
+        );
+        assert_eq!(
+            decimals_for_coin_type("0xaaaa::debug_token::DEBUG_TOKEN"),
+            Some(DEBUG_TOKEN_DECIMALS)
+        );
+        assert_eq!(decimals_for_coin_type("0x1::foo::FOO"), None);
+    }
+
+    #[test]
+    fn test_registered_coin_decimals_resolve_by_type_and_symbol() {
+        assert_eq!(decimals_for_coin_type("0x0b0b::bar::BARCOIN"), None);
+        register_coin_decimals("0xB0B::bar::BARCOIN", 4);
+        assert_eq!(decimals_for_coin_type("0x0b0b::bar::BARCOIN"), Some(4));
+        assert_eq!(token_decimals("barcoin"), Some(4));
+    }
+
+    #[test]
+    fn test_coin_type_symbol_is_struct_name() {
+        assert_eq!(coin_type_symbol("0x2::sui::SUI"), "SUI");
+        assert_eq!(coin_type_symbol(" 0xdba3::usdc::USDC "), "USDC");
+        assert_eq!(coin_type_symbol("0x2::coin::Coin<0x2::sui::SUI>"), "COIN");
+    }
+
+    #[test]
+    fn test_price_impact_bps_counts_only_adverse_moves() {
+        // Selling 1 SUI for 1.5 USDC against a 2.0 mid: 25% worse
+        assert_eq!(price_impact_bps(2.0, 1.5, true), 2_500);
+        // Buying at 2.5 USDC per SUI against a 2.0 mid: 25% worse
+        assert_eq!(price_impact_bps(2.0, 2.5, false), 2_500);
+        // An exact-out buy that consumed 1.03 USDC for 0.5 SUI vs a 2.0 mid
+        assert_eq!(price_impact_bps(2.0, 1.03 / 0.5, false), 300);
+        // Fills better than mid are price improvement, not impact
+        assert_eq!(price_impact_bps(2.0, 1.5, false), 0);
+        assert_eq!(price_impact_bps(2.0, 2.5, true), 0);
+        assert_eq!(price_impact_bps(0.0, 1.5, true), 0);
+    }
+
+    #[test]
+    fn test_raw_fill_price_rounds_onto_ticks() {
+        // 2 SUI for 7.0012 USDC: 3.5006 USDC per SUI
+        let raw = raw_fill_price(2_000_000_000, 7_001_200).unwrap();
+        assert_eq!(raw, 3_500_600);
+        assert_eq!(round_to_tick(raw, 1_000, true), 3_500_000);
+        assert_eq!(round_to_tick(raw, 1_000, false), 3_501_000);
+        assert_eq!(round_to_tick(3_500_000, 1_000, false), 3_500_000);
+        assert_eq!(raw_fill_price(0, 1), None);
+    }
+
+    #[test]
+    fn test_min_out_with_slippage_rounds_down() {
+        assert_eq!(min_out_with_slippage(1_000_000, 50), 995_000);
+        assert_eq!(min_out_with_slippage(999, 50), 994);
+        assert_eq!(min_out_with_slippage(u64::MAX, 0), u64::MAX);
+        assert_eq!(min_out_with_slippage(1_000, BPS_DENOMINATOR + 1), 0);
+    }
+}